@@ -758,9 +758,44 @@ function cloneTranscriptSummary(summary) {
     message_count: summary.message_count ?? 0,
     source: summary.source ?? 'unknown',
     size_bytes: summary.size_bytes ?? 0,
+    parent: summary.parent ?? null,
   };
 }
 
+// Order transcripts as a tree: each branch follows its parent, roots and
+// siblings keep the cache order (most recent first).
+function transcriptTreeOrder(entries) {
+  const ids = new Set(entries.map((entry) => entry.id));
+  const children = new Map();
+  const roots = [];
+  entries.forEach((entry) => {
+    const parentId = entry.parent?.conversation_id;
+    if (parentId && parentId !== entry.id && ids.has(parentId)) {
+      if (!children.has(parentId)) {
+        children.set(parentId, []);
+      }
+      children.get(parentId).push(entry);
+    } else {
+      roots.push(entry);
+    }
+  });
+
+  const ordered = [];
+  const visited = new Set();
+  const visit = (entry, depth) => {
+    if (visited.has(entry.id)) {
+      return;
+    }
+    visited.add(entry.id);
+    ordered.push({ summary: entry, depth });
+    (children.get(entry.id) ?? []).forEach((child) => visit(child, depth + 1));
+  };
+  roots.forEach((entry) => visit(entry, 0));
+  // Entries caught in a parent cycle are listed flat at the end.
+  entries.forEach((entry) => visit(entry, 0));
+  return ordered;
+}
+
 function upsertTranscriptSummary(summary) {
   const cloned = cloneTranscriptSummary(summary);
   if (!cloned) {
@@ -828,9 +863,13 @@ function renderTranscriptList() {
     return;
   }
 
-  transcriptCache.forEach((summary) => {
+  transcriptTreeOrder(transcriptCache).forEach(({ summary, depth }) => {
     const item = document.createElement('li');
     item.className = 'transcript-item';
+    if (depth > 0) {
+      item.classList.add('branch');
+      item.style.setProperty('--branch-depth', String(Math.min(depth, 4)));
+    }
     if (summary.id === selectedTranscriptId) {
       item.classList.add('selected');
     }
@@ -907,6 +946,15 @@ function renderTranscriptDetail(detail) {
   meta.append(`Source: ${formatTranscriptSource(transcript.source)}`);
   meta.append(`Created: ${formatDateTime(transcript.created_at)}`);
   meta.append(`Updated: ${formatDateTime(transcript.updated_at)}`);
+  if (transcript.parent?.conversation_id) {
+    const parentSummary = transcriptCache.find(
+      (entry) => entry.id === transcript.parent.conversation_id,
+    );
+    const parentTitle = parentSummary
+      ? formatTranscriptTitle(parentSummary)
+      : transcript.parent.conversation_id;
+    meta.append(`Branched from: ${parentTitle} after message ${transcript.parent.message_index + 1}`);
+  }
   header.append(meta);
 
   const actions = document.createElement('div');
@@ -938,7 +986,7 @@ function renderTranscriptDetail(detail) {
     empty.textContent = 'No messages recorded for this transcript.';
     messagesContainer.append(empty);
   } else {
-    transcript.messages.forEach((message) => {
+    transcript.messages.forEach((message, index) => {
       const entry = document.createElement('article');
       entry.className = `transcript-message ${message.role}`;
 
@@ -990,6 +1038,14 @@ function renderTranscriptDetail(detail) {
         entry.append(attachmentList);
       }
 
+      const branchButton = document.createElement('button');
+      branchButton.type = 'button';
+      branchButton.className = 'transcript-branch';
+      branchButton.textContent = 'Branch from here';
+      branchButton.title = `Start a new conversation from messages 1–${index + 1}`;
+      branchButton.addEventListener('click', () => requestTranscriptBranch(detail.id, index));
+      entry.append(branchButton);
+
       messagesContainer.append(entry);
     });
   }
@@ -1044,6 +1100,11 @@ function connectNative() {
       return;
     }
 
+    if (message.kind === 'transcript_branch') {
+      handleTranscriptBranchResponse(message);
+      return;
+    }
+
     if (message.kind === 'connectors' && message.connectors) {
       handleConnectorsResponse(message);
       return;
@@ -1462,6 +1523,27 @@ function handleTranscriptJsonResponse(message) {
   setTranscriptStatus('Transcript ready', 'ok');
 }
 
+function handleTranscriptBranchResponse(message) {
+  if (!message.success) {
+    setTranscriptStatus(message.error ?? 'Failed to branch transcript', 'error');
+    return;
+  }
+
+  const summary = message.transcripts?.transcript;
+  if (!summary || !summary.id) {
+    setTranscriptStatus('Branch created but not returned', 'warn');
+    requestTranscripts();
+    return;
+  }
+
+  upsertTranscriptSummary(summary);
+  setActiveConversation(summary.id, formatTranscriptTitle(summary));
+  selectedTranscriptId = summary.id;
+  renderTranscriptList();
+  requestTranscriptDetail(summary.id);
+  appendMessage('info', `Branched into ${formatTranscriptTitle(summary)}`);
+}
+
 function findProviderEntry(name) {
   if (!name) {
     return providerCache.find((entry) => entry.name === defaultProviderName) ?? null;
@@ -1605,6 +1687,14 @@ function requestTranscriptDetail(id) {
   port.postMessage({ type: 'transcript_json', id });
 }
 
+function requestTranscriptBranch(id, messageIndex) {
+  if (!port || !id) {
+    return;
+  }
+  setTranscriptStatus('Branching transcript…', 'pending');
+  port.postMessage({ type: 'transcript_branch', id, message_index: messageIndex });
+}
+
 function requestConnectors() {
   if (!port) {
     return;
@@ -7,25 +7,47 @@
 //!
 //! Safety: without `execute`, the loop is a dry-run — it plans and observes but
 //! never mutates the page. With `execute`, High/Critical-risk actions still gate on
-//! confirmation unless `auto_confirm` is set.
+//! confirmation unless `auto_confirm` is set; unattended runs decline them
+//! instead of prompting ([`ConfirmPolicy`]).
+//!
+//! Long runs can be checkpointed: with a [`CheckpointStore`] configured, the
+//! loop writes an [`AgentCheckpoint`] after every step (goal, steps, planner
+//! history, current URL and a [`StorageSnapshot`]) so [`BrowserAgent::resume`]
+//! can continue after a crash or an explicit pause. Checkpoints hold session
+//! cookies, so they are owner-only files, encrypted when transcripts are.
+//!
+//! Completion can be verified: with [`BrowserAgent::with_verification`], a
+//! `finish` is checked against explicit [`SuccessCriterion`]s (or, without any,
+//! judged by a second model call) before the run is reported as completed. A
+//! rejected finish is fed back to the planner and retried within a bound.
 
-use std::path::PathBuf;
+use std::io::Write;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
 
-use anyhow::Result;
-use chrono::Utc;
-use serde::Serialize;
+use anyhow::{Context, Result, bail};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
 use crate::ai::AiHttp;
+use crate::audit::ConfirmationDecision;
 use crate::automation::{
-    ActionResult, AutomationOrchestrator, NextAction, ValidationResult, WebAction,
+    ActionResult, AutomationOrchestrator, NextAction, ValidationResult, WebAction, glob_match,
 };
-use crate::browser::BrowserDriver;
+use crate::browser::{BrowserDriver, StorageSnapshot};
+use crate::transcript::{TranscriptStore, write_sealed};
+use crate::transcript_crypto::{self, TranscriptCipher};
+
+/// How often a paused run re-checks its pause and cancel flags.
+const PAUSE_POLL_INTERVAL: Duration = Duration::from_millis(200);
+/// Rejected finishes tolerated before the verifier's verdict becomes final.
+pub const DEFAULT_VERIFY_RETRIES: usize = 2;
 
 /// A single recorded step of an agent run.
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AgentStep {
     /// 1-indexed step number.
     pub index: usize,
@@ -35,10 +57,14 @@ pub struct AgentStep {
     pub action: WebAction,
     /// Result of executing (or previewing) the action.
     pub result: ActionResult,
+    /// Position within a recipe's nested blocks, e.g. `3.then.1` or
+    /// `4.iter2.1`; `None` for plain agent runs.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
 }
 
 /// The outcome of an agent run.
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AgentOutcome {
     /// Unique run ID.
     pub id: Uuid,
@@ -48,10 +74,233 @@ pub struct AgentOutcome {
     pub executed: bool,
     /// Steps taken.
     pub steps: Vec<AgentStep>,
-    /// Whether the planner explicitly signalled completion.
+    /// Whether the planner explicitly signalled completion (and, when
+    /// verification is enabled, the verifier accepted it).
     pub completed: bool,
     /// Final answer / summary.
     pub summary: String,
+    /// Verdict of the last verification pass, if verification was enabled.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub verification: Option<VerificationVerdict>,
+    /// HAR file of the run's network traffic, when the driver captured it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub har: Option<String>,
+    /// Results of recipe `assert` steps, in evaluation order.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub assertions: Vec<AssertionResult>,
+}
+
+/// An explicit, checkable condition for a goal being achieved.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum SuccessCriterion {
+    /// The page text contains `text`.
+    TextPresent { text: String },
+    /// The current URL matches the glob `pattern` (see [`glob_match`]).
+    UrlMatches { pattern: String },
+    /// An element matching `selector` exists.
+    ElementPresent { selector: String },
+    /// The element matching `selector` contains `contains` in its text.
+    ElementText { selector: String, contains: String },
+}
+
+impl SuccessCriterion {
+    /// Parse the compact CLI form: `text:<TEXT>`, `url:<GLOB>`,
+    /// `element:<SELECTOR>` or `element:<SELECTOR>=<TEXT>`.
+    pub fn parse(spec: &str) -> Result<Self> {
+        let (kind, rest) = spec
+            .split_once(':')
+            .with_context(|| format!("success criterion '{spec}' must look like KIND:VALUE"))?;
+        let rest = rest.trim();
+        if rest.is_empty() {
+            bail!("success criterion '{spec}' has an empty value");
+        }
+        Ok(match kind.trim() {
+            "text" => Self::TextPresent { text: rest.into() },
+            "url" => Self::UrlMatches {
+                pattern: rest.into(),
+            },
+            "element" => match rest.split_once('=') {
+                Some((selector, contains)) => Self::ElementText {
+                    selector: selector.trim().into(),
+                    contains: contains.trim().into(),
+                },
+                None => Self::ElementPresent {
+                    selector: rest.into(),
+                },
+            },
+            other => bail!("unknown success criterion kind '{other}' (use text, url or element)"),
+        })
+    }
+
+    /// Check the criterion against the live page, fetching the page text only
+    /// when the criterion needs it.
+    pub fn evaluate(&self, driver: &dyn BrowserDriver) -> (bool, String) {
+        let page_text = match self {
+            Self::TextPresent { .. } => driver.observe().map(|obs| obs.text).unwrap_or_default(),
+            _ => String::new(),
+        };
+        self.check(driver, &page_text)
+    }
+
+    /// Check the criterion against the live page, returning a one-line report.
+    fn check(&self, driver: &dyn BrowserDriver, page_text: &str) -> (bool, String) {
+        match self {
+            Self::TextPresent { text } => {
+                let ok = page_text.contains(text.as_str());
+                (ok, format!("text \"{text}\" present"))
+            }
+            Self::UrlMatches { pattern } => {
+                let url = driver.current_url().unwrap_or_default();
+                (
+                    glob_match(pattern, &url),
+                    format!("URL {url} matches {pattern}"),
+                )
+            }
+            Self::ElementPresent { selector } => {
+                let ok = driver.extract(selector).is_ok();
+                (ok, format!("element {selector} present"))
+            }
+            Self::ElementText { selector, contains } => {
+                let ok = driver
+                    .extract(selector)
+                    .map(|text| text.contains(contains.as_str()))
+                    .unwrap_or(false);
+                (ok, format!("element {selector} contains \"{contains}\""))
+            }
+        }
+    }
+}
+
+/// How a [`VerificationVerdict`] was reached.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VerificationMethod {
+    /// Explicit [`SuccessCriterion`]s were checked against the page.
+    Criteria,
+    /// A second model call judged the final observation.
+    Model,
+}
+
+/// Result of verifying a `finish` before reporting success.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VerificationVerdict {
+    /// Whether the goal was verified as achieved.
+    pub passed: bool,
+    /// How the verdict was reached.
+    pub method: VerificationMethod,
+    /// Per-criterion results, or the model's reason.
+    pub details: Vec<String>,
+    /// Verification passes run so far (1 = no retries).
+    pub attempts: usize,
+}
+
+/// Result of one recipe `assert` step.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AssertionResult {
+    /// Dotted recipe step path of the assertion.
+    pub path: String,
+    /// What was asserted, e.g. `element count of tr.row >= 3`.
+    pub description: String,
+    pub status: AssertionStatus,
+    /// The observed value, when one was read.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub actual: Option<String>,
+    /// The recipe author's failure message.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
+
+/// Whether an [`AssertionResult`] held.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AssertionStatus {
+    Passed,
+    Failed,
+    /// Not evaluated because the run was a preview.
+    Skipped,
+}
+
+/// Lifecycle state recorded in an [`AgentCheckpoint`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CheckpointStatus {
+    /// The run was in progress when the checkpoint was written.
+    Running,
+    /// The run was paused through its pause flag.
+    Paused,
+    /// The run was cancelled (or its client went away); it can be resumed.
+    Cancelled,
+    /// The run ended (finished, failed or hit the step limit).
+    Finished,
+}
+
+/// Resumable state of an agent run, persisted after every step.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AgentCheckpoint {
+    /// Run ID (shared with the final [`AgentOutcome`]).
+    pub id: Uuid,
+    /// Goal the agent is pursuing.
+    pub goal: String,
+    /// Start URL the run was launched with.
+    #[serde(default)]
+    pub start_url: Option<String>,
+    /// Provider override the run was launched with.
+    #[serde(default)]
+    pub provider: Option<String>,
+    /// Whether the run executes actions (vs preview/dry-run).
+    pub executed: bool,
+    /// Step budget for the whole run, including steps already taken.
+    pub max_steps: usize,
+    /// Steps recorded so far.
+    #[serde(default)]
+    pub steps: Vec<AgentStep>,
+    /// Planner conversation state (the "actions so far" history).
+    #[serde(default)]
+    pub history: Vec<String>,
+    /// Document URL after the last recorded step.
+    #[serde(default)]
+    pub current_url: Option<String>,
+    /// Cookies and web storage after the last recorded step.
+    #[serde(default)]
+    pub storage: Option<StorageSnapshot>,
+    /// Lifecycle state when the checkpoint was written.
+    pub status: CheckpointStatus,
+    /// When the checkpoint was written.
+    pub updated_at: DateTime<Utc>,
+}
+
+/// How High/Critical-risk actions that need confirmation are handled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ConfirmPolicy {
+    /// Ask on the terminal.
+    #[default]
+    Prompt,
+    /// Approve without asking (`auto_confirm`).
+    AutoApprove,
+    /// Decline without asking, for runs nobody is watching.
+    Decline,
+}
+
+impl ConfirmPolicy {
+    /// [`Self::AutoApprove`] when `auto_confirm`, else [`Self::Prompt`].
+    pub fn from_auto_confirm(auto_confirm: bool) -> Self {
+        if auto_confirm {
+            Self::AutoApprove
+        } else {
+            Self::Prompt
+        }
+    }
+
+    /// Policy for runs without a terminal: approve only when unattended
+    /// high-risk actions are allowed, never prompt.
+    pub fn unattended(allow_high_risk: bool) -> Self {
+        if allow_high_risk {
+            Self::AutoApprove
+        } else {
+            Self::Decline
+        }
+    }
 }
 
 /// Callback invoked with each [`AgentStep`] as it is recorded, used to stream
@@ -63,12 +312,28 @@ pub struct BrowserAgent {
     orchestrator: Arc<AutomationOrchestrator>,
     max_steps: usize,
     execute: bool,
-    auto_confirm: bool,
+    confirm: ConfirmPolicy,
     transcript_dir: Option<PathBuf>,
     /// Optional callback invoked with each [`AgentStep`] as it is recorded, so a
     /// caller (e.g. the SSE `/agent/run` surface) can stream live progress. The
     /// end-of-run [`AgentOutcome`] persistence is unaffected.
     step_observer: Option<StepObserver>,
+    /// Store receiving an [`AgentCheckpoint`] after every step.
+    checkpoints: Option<CheckpointStore>,
+    /// While set, the loop parks between steps (after checkpointing).
+    pause: Option<Arc<AtomicBool>>,
+    /// Fixed run ID, so callers can address the run before it finishes.
+    run_id: Option<Uuid>,
+    /// Success criteria checked before accepting a `finish`.
+    verification: Option<AgentVerification>,
+    /// Encrypts the persisted outcome when transcript encryption is on.
+    cipher: Option<TranscriptCipher>,
+}
+
+/// Verification settings for [`BrowserAgent::with_verification`].
+struct AgentVerification {
+    criteria: Vec<SuccessCriterion>,
+    max_retries: usize,
 }
 
 impl BrowserAgent {
@@ -88,18 +353,70 @@ impl BrowserAgent {
             orchestrator,
             max_steps: max_steps.max(1),
             execute,
-            auto_confirm,
+            confirm: ConfirmPolicy::from_auto_confirm(auto_confirm),
             transcript_dir,
             step_observer: None,
+            checkpoints: None,
+            pause: None,
+            run_id: None,
+            verification: None,
+            cipher: None,
         }
     }
 
+    /// Handle confirmations with `policy` instead of the `auto_confirm` flag.
+    pub fn with_confirm_policy(mut self, policy: ConfirmPolicy) -> Self {
+        self.confirm = policy;
+        self
+    }
+
     /// Register a callback invoked with each [`AgentStep`] as it is recorded.
     pub fn with_step_observer(mut self, observer: StepObserver) -> Self {
         self.step_observer = Some(observer);
         self
     }
 
+    /// Persist an [`AgentCheckpoint`] to `store` after every step.
+    pub fn with_checkpoint_store(mut self, store: CheckpointStore) -> Self {
+        self.checkpoints = Some(store);
+        self
+    }
+
+    /// Pause the loop between steps while `flag` is set.
+    pub fn with_pause_flag(mut self, flag: Arc<AtomicBool>) -> Self {
+        self.pause = Some(flag);
+        self
+    }
+
+    /// Use `id` as the run ID instead of generating one.
+    pub fn with_run_id(mut self, id: Uuid) -> Self {
+        self.run_id = Some(id);
+        self
+    }
+
+    /// Verify every `finish` before accepting it: check `criteria` against the
+    /// page, or ask the model to judge the final observation when `criteria`
+    /// is empty. Up to `max_retries` rejected finishes are fed back to the
+    /// planner; after that the failed verdict is final.
+    pub fn with_verification(
+        mut self,
+        criteria: Vec<SuccessCriterion>,
+        max_retries: usize,
+    ) -> Self {
+        self.verification = Some(AgentVerification {
+            criteria,
+            max_retries,
+        });
+        self
+    }
+
+    /// Encrypt the persisted outcome with `cipher` (the transcript cipher, so
+    /// agent transcripts follow `transcripts_encryption`).
+    pub fn with_encryption(mut self, cipher: Option<TranscriptCipher>) -> Self {
+        self.cipher = cipher;
+        self
+    }
+
     /// Record a step and notify the live observer (if any) in order.
     fn record_step(&self, steps: &mut Vec<AgentStep>, step: AgentStep) {
         if let Some(observer) = &self.step_observer {
@@ -118,11 +435,6 @@ impl BrowserAgent {
         http: &H,
         cancel: &AtomicBool,
     ) -> Result<AgentOutcome> {
-        let mut steps: Vec<AgentStep> = Vec::new();
-        let mut history: Vec<String> = Vec::new();
-        let mut completed = false;
-        let mut summary = String::new();
-
         // The start URL is user-provided, not agent-chosen; navigate to it directly
         // so the agent has a page to observe. In preview mode we still need the page
         // loaded to plan, so this navigation happens regardless of `execute`.
@@ -130,67 +442,200 @@ impl BrowserAgent {
             driver.navigate(url)?;
         }
 
-        for index in 1..=self.max_steps {
-            if cancel.load(Ordering::Relaxed) {
+        let checkpoint = AgentCheckpoint {
+            id: self.run_id.unwrap_or_else(Uuid::new_v4),
+            goal: goal.to_string(),
+            start_url: start_url.map(str::to_string),
+            provider: provider.map(str::to_string),
+            executed: self.execute,
+            max_steps: self.max_steps,
+            steps: Vec::new(),
+            history: Vec::new(),
+            current_url: None,
+            storage: None,
+            status: CheckpointStatus::Running,
+            updated_at: Utc::now(),
+        };
+        self.drive(checkpoint, driver, http, cancel)
+    }
+
+    /// Continue a run from `checkpoint`.
+    ///
+    /// Restores the saved cookies/web storage, returns to the checkpoint URL and
+    /// re-enters the loop with the recorded steps and planner history, up to the
+    /// checkpoint's original step budget. `provider` overrides the provider the
+    /// run was started with. Finished runs cannot be resumed; cancelled ones
+    /// can.
+    pub fn resume<H: AiHttp>(
+        &self,
+        mut checkpoint: AgentCheckpoint,
+        driver: &dyn BrowserDriver,
+        provider: Option<&str>,
+        http: &H,
+        cancel: &AtomicBool,
+    ) -> Result<AgentOutcome> {
+        if checkpoint.status == CheckpointStatus::Finished {
+            bail!("agent run {} already finished", checkpoint.id);
+        }
+
+        if let Some(storage) = &checkpoint.storage {
+            driver
+                .restore_storage(storage)
+                .context("failed to restore checkpoint browser storage")?;
+        }
+        let target = checkpoint
+            .current_url
+            .clone()
+            .or_else(|| checkpoint.start_url.clone());
+        if let Some(url) = target.filter(|url| !url.is_empty())
+            && driver.current_url().ok().as_deref() != Some(url.as_str())
+        {
+            driver.navigate(&url)?;
+        }
+
+        if let Some(provider) = provider {
+            checkpoint.provider = Some(provider.to_string());
+        }
+        checkpoint.executed = self.execute;
+        checkpoint.status = CheckpointStatus::Running;
+        self.drive(checkpoint, driver, http, cancel)
+    }
+
+    /// The observe → plan → act loop shared by [`run`](Self::run) and
+    /// [`resume`](Self::resume).
+    fn drive<H: AiHttp>(
+        &self,
+        mut state: AgentCheckpoint,
+        driver: &dyn BrowserDriver,
+        http: &H,
+        cancel: &AtomicBool,
+    ) -> Result<AgentOutcome> {
+        let mut completed = false;
+        let mut cancelled = false;
+        let mut summary = String::new();
+        let mut verification: Option<VerificationVerdict> = None;
+        let goal = state.goal.clone();
+        let provider = state.provider.clone();
+
+        self.checkpoint(&mut state, driver);
+
+        // Every planner turn counts against the budget, including finishes the
+        // verifier rejected, so step indices stay contiguous while retries
+        // remain bounded.
+        let mut turns = state.steps.len();
+        while turns < state.max_steps {
+            turns += 1;
+            let index = state.steps.len() + 1;
+            if cancel.load(Ordering::Relaxed)
+                || !self.wait_while_paused(&mut state, driver, cancel)
+            {
+                cancelled = true;
                 summary = "Run cancelled".to_string();
                 break;
             }
 
             let observation = driver.observe()?.render_for_prompt();
 
-            match self
-                .orchestrator
-                .plan_next_action(goal, &observation, &history, provider, http)?
-            {
+            match self.orchestrator.plan_next_action(
+                &goal,
+                &observation,
+                &state.history,
+                provider.as_deref(),
+                http,
+            )? {
                 NextAction::Finish(answer) => {
-                    completed = true;
-                    summary = answer;
+                    let Some(config) = &self.verification else {
+                        completed = true;
+                        summary = answer;
+                        break;
+                    };
+                    let attempts = verification.as_ref().map_or(0, |v| v.attempts) + 1;
+                    let verdict = self.verify(
+                        config,
+                        &goal,
+                        &answer,
+                        driver,
+                        provider.as_deref(),
+                        http,
+                        attempts,
+                    )?;
+                    let passed = verdict.passed;
+                    if !passed && attempts <= config.max_retries {
+                        state.history.push(format!(
+                            "finish rejected by verifier: {}",
+                            verdict.details.join("; ")
+                        ));
+                        verification = Some(verdict);
+                        continue;
+                    }
+                    verification = Some(verdict);
+                    completed = passed;
+                    summary = if passed {
+                        answer
+                    } else {
+                        format!("Goal not verified: {answer}")
+                    };
                     break;
                 }
                 NextAction::Act(action) => {
+                    let action = action.with_domain_from(driver);
                     let validation = self.orchestrator.validate_action(&action);
 
                     if !self.execute {
                         // Dry-run: log the intended action, never mutate the page.
                         let result = preview_result(&action, "preview: not executed");
-                        history.push(format!(
-                            "[preview] {}",
-                            describe_action(&action)
-                        ));
-                        self.record_step(&mut steps, AgentStep {
+                        state
+                            .history
+                            .push(format!("[preview] {}", describe_action(&action)));
+                        self.record_step(&mut state.steps, AgentStep {
                             index,
                             observation,
                             action,
                             result,
+                            path: None,
                         });
+                        self.checkpoint(&mut state, driver);
                         continue;
                     }
 
-                    if validation.requires_confirmation && !self.confirm(&action, &validation) {
+                    let confirmation = self.confirm(&action, &validation);
+                    if confirmation == ConfirmationDecision::Declined {
+                        self.orchestrator.record_declined(&action, &validation);
                         let result = preview_result(&action, "declined by user");
-                        history.push(format!("declined {}", describe_action(&action)));
-                        self.record_step(&mut steps, AgentStep {
+                        state
+                            .history
+                            .push(format!("declined {}", describe_action(&action)));
+                        self.record_step(&mut state.steps, AgentStep {
                             index,
                             observation,
                             action,
                             result,
+                            path: None,
                         });
+                        self.checkpoint(&mut state, driver);
                         continue;
                     }
 
-                    let result = self.orchestrator.execute_action_with(&action, driver)?;
+                    let result = self.orchestrator.execute_action_confirmed(
+                        &action,
+                        driver,
+                        &validation,
+                        confirmation,
+                    )?;
                     let ok = result.success;
-                    history.push(format!(
+                    state.history.push(format!(
                         "{} -> {}",
                         describe_action(&action),
                         if ok { "ok" } else { "failed" }
                     ));
-                    self.record_step(&mut steps, AgentStep {
+                    self.record_step(&mut state.steps, AgentStep {
                         index,
                         observation,
                         action,
                         result,
+                        path: None,
                     });
+                    self.checkpoint(&mut state, driver);
 
                     if !ok {
                         summary = "Stopped after a failed action".to_string();
@@ -203,43 +648,306 @@ impl BrowserAgent {
         if summary.is_empty() {
             summary = format!(
                 "Reached the step limit ({}) without an explicit finish",
-                self.max_steps
+                state.max_steps
             );
         }
 
+        // A cancelled run keeps a resumable checkpoint: cancelling is also
+        // what happens when the client driving a paused run disconnects.
+        state.status = if cancelled {
+            CheckpointStatus::Cancelled
+        } else {
+            CheckpointStatus::Finished
+        };
+        self.write_checkpoint(&mut state);
+
+        // Only top-level (persisted) runs save a HAR; nested runs such as
+        // recipe goal steps leave it to their caller.
+        let har = match self.transcript_dir {
+            Some(_) => save_har(driver, &format!("agent-{}", state.id)),
+            None => None,
+        };
+
         let outcome = AgentOutcome {
-            id: Uuid::new_v4(),
-            goal: goal.to_string(),
+            id: state.id,
+            goal,
             executed: self.execute,
-            steps,
+            steps: state.steps,
             completed,
             summary,
+            verification,
+            har,
+            assertions: Vec::new(),
         };
 
         self.persist(&outcome);
         Ok(outcome)
     }
 
-    fn confirm(&self, action: &WebAction, validation: &ValidationResult) -> bool {
-        if self.auto_confirm {
+    /// Run one verification pass over the page the agent finished on.
+    #[allow(clippy::too_many_arguments)]
+    fn verify<H: AiHttp>(
+        &self,
+        config: &AgentVerification,
+        goal: &str,
+        answer: &str,
+        driver: &dyn BrowserDriver,
+        provider: Option<&str>,
+        http: &H,
+        attempts: usize,
+    ) -> Result<VerificationVerdict> {
+        let observation = driver.observe()?;
+
+        if config.criteria.is_empty() {
+            let judgement = self.orchestrator.judge_goal(
+                goal,
+                answer,
+                &observation.render_for_prompt(),
+                provider,
+                http,
+            )?;
+            return Ok(VerificationVerdict {
+                passed: judgement.success,
+                method: VerificationMethod::Model,
+                details: vec![judgement.reason],
+                attempts,
+            });
+        }
+
+        let mut passed = true;
+        let mut details = Vec::with_capacity(config.criteria.len());
+        for criterion in &config.criteria {
+            let (ok, report) = criterion.check(driver, &observation.text);
+            passed &= ok;
+            details.push(format!("{} {report}", if ok { "pass:" } else { "FAIL:" }));
+        }
+        Ok(VerificationVerdict {
+            passed,
+            method: VerificationMethod::Criteria,
+            details,
+            attempts,
+        })
+    }
+
+    /// Park between steps while the pause flag is set, checkpointing once as
+    /// [`CheckpointStatus::Paused`]. Returns `false` if cancelled while paused.
+    fn wait_while_paused(
+        &self,
+        state: &mut AgentCheckpoint,
+        driver: &dyn BrowserDriver,
+        cancel: &AtomicBool,
+    ) -> bool {
+        let Some(pause) = &self.pause else {
+            return true;
+        };
+        if !pause.load(Ordering::Relaxed) {
             return true;
         }
+
+        state.status = CheckpointStatus::Paused;
+        self.checkpoint(state, driver);
+        tracing::info!(run = %state.id, "agent run paused");
+        while pause.load(Ordering::Relaxed) {
+            if cancel.load(Ordering::Relaxed) {
+                return false;
+            }
+            std::thread::sleep(PAUSE_POLL_INTERVAL);
+        }
+        tracing::info!(run = %state.id, "agent run resumed");
+        state.status = CheckpointStatus::Running;
+        true
+    }
+
+    /// Refresh the browser-derived checkpoint fields and persist the checkpoint.
+    fn checkpoint(&self, state: &mut AgentCheckpoint, driver: &dyn BrowserDriver) {
+        if self.checkpoints.is_none() {
+            return;
+        }
+        state.current_url = driver.current_url().ok();
+        match driver.storage_snapshot() {
+            Ok(snapshot) => state.storage = Some(snapshot),
+            Err(err) => {
+                tracing::warn!(error = %err, run = %state.id, "failed to snapshot browser storage")
+            }
+        }
+        self.write_checkpoint(state);
+    }
+
+    fn write_checkpoint(&self, state: &mut AgentCheckpoint) {
+        if let Some(store) = &self.checkpoints {
+            state.updated_at = Utc::now();
+            store.save(state);
+        }
+    }
+
+    fn confirm(&self, action: &WebAction, validation: &ValidationResult) -> ConfirmationDecision {
+        if !validation.requires_confirmation {
+            return ConfirmationDecision::NotRequired;
+        }
+        match self.confirm {
+            ConfirmPolicy::AutoApprove => return ConfirmationDecision::AutoApproved,
+            ConfirmPolicy::Decline => return ConfirmationDecision::Declined,
+            ConfirmPolicy::Prompt => {}
+        }
         let prompt = format!(
             "Execute {} [risk: {:?}]?",
             describe_action(action),
             validation.risk_level
         );
-        dialoguer::Confirm::new()
+        let approved = dialoguer::Confirm::new()
             .with_prompt(prompt)
             .default(false)
             .interact()
-            .unwrap_or(false)
+            .unwrap_or(false);
+        if approved {
+            ConfirmationDecision::Confirmed
+        } else {
+            ConfirmationDecision::Declined
+        }
     }
 
     fn persist(&self, outcome: &AgentOutcome) {
         if let Some(dir) = &self.transcript_dir {
-            persist_outcome(dir, outcome);
+            persist_outcome(dir, outcome, self.cipher.as_ref());
+        }
+    }
+}
+
+/// Path of the checkpoint file for run `id` under `dir`.
+pub fn checkpoint_path(dir: &Path, id: Uuid) -> PathBuf {
+    dir.join(format!("agent-{id}.checkpoint.json"))
+}
+
+/// Agent checkpoints on disk, one file per run. Files are created owner-only
+/// and encrypted with the transcript cipher when transcript encryption is on.
+#[derive(Clone)]
+pub struct CheckpointStore {
+    dir: PathBuf,
+    cipher: Option<TranscriptCipher>,
+}
+
+impl CheckpointStore {
+    /// Plaintext store in `dir` (created on first write).
+    pub fn new(dir: PathBuf) -> Self {
+        Self { dir, cipher: None }
+    }
+
+    /// Store under `<transcripts>/agents/checkpoints`, sharing its encryption.
+    pub fn for_transcripts(transcripts: &TranscriptStore) -> Self {
+        let store = Self::new(transcripts.root().join("agents").join("checkpoints"));
+        match transcripts.cipher() {
+            Some(cipher) => store.with_encryption(cipher.clone()),
+            None => store,
+        }
+    }
+
+    /// Encrypt checkpoints written from now on with `cipher`.
+    pub fn with_encryption(mut self, cipher: TranscriptCipher) -> Self {
+        self.cipher = Some(cipher);
+        self
+    }
+
+    /// Directory backing this store.
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Persist `checkpoint` (written to a temp file, then renamed so a crash
+    /// mid-write never leaves a truncated checkpoint). Failures are logged,
+    /// not fatal.
+    pub fn save(&self, checkpoint: &AgentCheckpoint) {
+        let path = checkpoint_path(&self.dir, checkpoint.id);
+        if let Err(err) = self.try_save(&path, checkpoint) {
+            tracing::warn!(error = %err, path = %path.display(), "failed to write agent checkpoint");
+        }
+    }
+
+    fn try_save(&self, path: &Path, checkpoint: &AgentCheckpoint) -> Result<()> {
+        std::fs::create_dir_all(&self.dir).with_context(|| {
+            format!(
+                "failed to create agent checkpoint dir {}",
+                self.dir.display()
+            )
+        })?;
+        let json = serde_json::to_vec_pretty(checkpoint)
+            .context("failed to serialize agent checkpoint")?;
+        let data = match &self.cipher {
+            Some(cipher) => cipher.encrypt(&json)?,
+            None => json,
+        };
+        let tmp = path.with_extension("json.tmp");
+        // A temp file left by a crashed write would make `create_new` fail.
+        match std::fs::remove_file(&tmp) {
+            Err(err) if err.kind() != std::io::ErrorKind::NotFound => {
+                return Err(err).with_context(|| format!("failed to remove {}", tmp.display()));
+            }
+            _ => {}
+        }
+        let mut options = std::fs::OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+        let mut file = options
+            .open(&tmp)
+            .with_context(|| format!("failed to create {}", tmp.display()))?;
+        file.write_all(&data)
+            .with_context(|| format!("failed to write {}", tmp.display()))?;
+        drop(file);
+        std::fs::rename(&tmp, path)
+            .with_context(|| format!("failed to move {} into place", tmp.display()))
+    }
+
+    /// Load the checkpoint for run `id`.
+    pub fn load(&self, id: Uuid) -> Result<AgentCheckpoint> {
+        let path = checkpoint_path(&self.dir, id);
+        let mut data = std::fs::read(&path)
+            .with_context(|| format!("no checkpoint for agent run {id} at {}", path.display()))?;
+        if transcript_crypto::is_encrypted(&data) {
+            let Some(cipher) = &self.cipher else {
+                bail!(
+                    "agent checkpoint {} is encrypted; enable transcripts_encryption to read it",
+                    path.display()
+                );
+            };
+            data = cipher.decrypt(&data)?;
+        }
+        serde_json::from_slice(&data)
+            .with_context(|| format!("failed to parse agent checkpoint {}", path.display()))
+    }
+
+    /// Encrypt every plaintext checkpoint in place, returning how many were
+    /// rewritten. Fails unless the store has a cipher.
+    pub fn encrypt_existing(&self) -> Result<usize> {
+        if self.cipher.is_none() {
+            bail!("transcript encryption is not enabled");
         }
+        let entries = match std::fs::read_dir(&self.dir) {
+            Ok(entries) => entries,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(0),
+            Err(err) => {
+                return Err(err).with_context(|| format!("failed to list {}", self.dir.display()));
+            }
+        };
+        let mut encrypted = 0;
+        for entry in entries {
+            let path = entry?.path();
+            if !path.to_string_lossy().ends_with(".checkpoint.json") {
+                continue;
+            }
+            let data = std::fs::read(&path)
+                .with_context(|| format!("failed to read {}", path.display()))?;
+            if transcript_crypto::is_encrypted(&data) {
+                continue;
+            }
+            let checkpoint: AgentCheckpoint = serde_json::from_slice(&data)
+                .with_context(|| format!("failed to parse agent checkpoint {}", path.display()))?;
+            self.try_save(&path, &checkpoint)?;
+            encrypted += 1;
+        }
+        Ok(encrypted)
     }
 }
 
@@ -257,6 +965,18 @@ pub fn render_markdown(outcome: &AgentOutcome) -> String {
         }
     ));
     out.push_str(&format!("- Completed: {}\n", outcome.completed));
+    if let Some(verdict) = &outcome.verification {
+        out.push_str(&format!(
+            "- Verified: {} ({:?}, {} attempt{})\n",
+            verdict.passed,
+            verdict.method,
+            verdict.attempts,
+            if verdict.attempts == 1 { "" } else { "s" }
+        ));
+    }
+    if let Some(har) = &outcome.har {
+        out.push_str(&format!("- HAR: {har}\n"));
+    }
     out.push_str(&format!("- Steps: {}\n\n", outcome.steps.len()));
 
     if outcome.steps.is_empty() {
@@ -274,6 +994,9 @@ pub fn render_markdown(outcome: &AgentOutcome) -> String {
             "## {}. {:?} {} [{status}]\n\n",
             step.index, step.action.action_type, target
         ));
+        if let Some(path) = &step.path {
+            out.push_str(&format!("- Step path: {path}\n"));
+        }
         if !step.observation.is_empty() {
             out.push_str(&format!("- Observation: {}\n", step.observation));
         }
@@ -286,6 +1009,41 @@ pub fn render_markdown(outcome: &AgentOutcome) -> String {
         out.push('\n');
     }
 
+    if let Some(verdict) = &outcome.verification {
+        out.push_str("## Verification\n\n");
+        out.push_str(&format!(
+            "{}\n\n",
+            if verdict.passed { "Passed" } else { "Failed" }
+        ));
+        for detail in &verdict.details {
+            out.push_str(&format!("- {detail}\n"));
+        }
+        out.push('\n');
+    }
+
+    if !outcome.assertions.is_empty() {
+        out.push_str("## Assertions\n\n");
+        for assertion in &outcome.assertions {
+            let status = match assertion.status {
+                AssertionStatus::Passed => "passed",
+                AssertionStatus::Failed => "FAILED",
+                AssertionStatus::Skipped => "skipped",
+            };
+            out.push_str(&format!(
+                "- [{status}] step {}: {}",
+                assertion.path, assertion.description
+            ));
+            if let Some(actual) = &assertion.actual {
+                out.push_str(&format!(" (actual: {actual})"));
+            }
+            if let Some(message) = &assertion.message {
+                out.push_str(&format!(" — {message}"));
+            }
+            out.push('\n');
+        }
+        out.push('\n');
+    }
+
     out.push_str("## Summary\n\n");
     out.push_str(&outcome.summary);
     out.push('\n');
@@ -293,8 +1051,13 @@ pub fn render_markdown(outcome: &AgentOutcome) -> String {
 }
 
 /// Persist an [`AgentOutcome`] to `dir` as both `agent-{id}.json` (pretty) and
-/// `agent-{id}.md` ([`render_markdown`]). Failures are logged, not fatal.
-pub fn persist_outcome(dir: &std::path::Path, outcome: &AgentOutcome) {
+/// `agent-{id}.md` ([`render_markdown`]), encrypted with `cipher` when given.
+/// Failures are logged, not fatal.
+pub fn persist_outcome(
+    dir: &std::path::Path,
+    outcome: &AgentOutcome,
+    cipher: Option<&TranscriptCipher>,
+) {
     if let Err(err) = std::fs::create_dir_all(dir) {
         tracing::warn!(error = %err, dir = %dir.display(), "failed to create agent transcript dir");
         return;
@@ -302,18 +1065,30 @@ pub fn persist_outcome(dir: &std::path::Path, outcome: &AgentOutcome) {
     let json_path = dir.join(format!("agent-{}.json", outcome.id));
     match serde_json::to_string_pretty(outcome) {
         Ok(json) => {
-            if let Err(err) = std::fs::write(&json_path, json) {
+            if let Err(err) = write_sealed(&json_path, json.as_bytes(), cipher) {
                 tracing::warn!(error = %err, path = %json_path.display(), "failed to write agent transcript");
             }
         }
         Err(err) => tracing::warn!(error = %err, "failed to serialize agent outcome"),
     }
     let md_path = dir.join(format!("agent-{}.md", outcome.id));
-    if let Err(err) = std::fs::write(&md_path, render_markdown(outcome)) {
+    if let Err(err) = write_sealed(&md_path, render_markdown(outcome).as_bytes(), cipher) {
         tracing::warn!(error = %err, path = %md_path.display(), "failed to write agent transcript markdown");
     }
 }
 
+/// Save the driver's captured traffic as `<name>.har`, returning its path.
+/// Failures are logged, not fatal: the run itself already completed.
+pub fn save_har(driver: &dyn BrowserDriver, name: &str) -> Option<String> {
+    match driver.save_har(name) {
+        Ok(path) => path.map(|path| path.display().to_string()),
+        Err(err) => {
+            tracing::warn!(error = %err, "failed to save agent HAR");
+            None
+        }
+    }
+}
+
 /// Build a non-mutating [`ActionResult`] for preview / declined steps.
 fn preview_result(action: &WebAction, note: &str) -> ActionResult {
     ActionResult {
@@ -577,6 +1352,45 @@ mod tests {
         assert!(driver.calls().iter().any(|c| c == "navigate:https://ok.test"));
     }
 
+    #[test]
+    fn executed_actions_are_audited() {
+        use crate::audit::{AuditActor, AuditEntry, AuditLog, verify_audit_log};
+
+        let dir = tempfile::tempdir().unwrap();
+        let log = Arc::new(AuditLog::open(dir.path().join("actions.log")).unwrap());
+        let root = dir.path().join("transcripts");
+        let store = TranscriptStore::new(root).expect("transcript store");
+        let bridge = AiBridge::from_settings(&AiSettings::default(), Arc::new(store));
+        let orch = Arc::new(
+            AutomationOrchestrator::from_settings(enabled_settings(), Arc::new(bridge))
+                .with_audit(Arc::clone(&log), AuditActor::Cli),
+        );
+        let agent = BrowserAgent::new(orch, 5, true, true, None);
+        let driver = StubDriver::default();
+        let http = ScriptedAiHttp::new(vec![
+            r##"{"action_type":"click","selector":"#go","description":"go"}"##,
+            r##"{"action_type":"extract","selector":"#lnk","description":"read"}"##,
+            r#"{"action_type":"finish","description":"done"}"#,
+        ]);
+        let cancel = AtomicBool::new(false);
+
+        agent
+            .run("audit", None, &driver, None, &http, &cancel)
+            .expect("agent run");
+
+        let raw = std::fs::read_to_string(log.path()).unwrap();
+        let entries: Vec<AuditEntry> = raw
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].record.selector.as_deref(), Some("#go"));
+        assert_eq!(entries[0].record.domain.as_deref(), Some("example.test"));
+        assert!(entries.iter().all(|e| e.record.actor == AuditActor::Cli));
+        assert!(entries.iter().all(|e| e.record.success));
+        assert!(verify_audit_log(log.path()).unwrap().is_intact());
+    }
+
     #[test]
     fn cancellation_stops_the_loop() {
         let orch = orchestrator(enabled_settings());
@@ -630,9 +1444,13 @@ mod tests {
                     latency_ms: 3,
                     timestamp: Utc::now(),
                 },
+                path: None,
             }],
             completed: true,
             summary: "Found the docs link".into(),
+            verification: None,
+            har: None,
+            assertions: Vec::new(),
         };
 
         let md = render_markdown(&outcome);
@@ -653,13 +1471,46 @@ mod tests {
             steps: Vec::new(),
             completed: false,
             summary: "done".into(),
+            verification: None,
+            har: None,
+            assertions: Vec::new(),
         };
-        persist_outcome(&dir, &outcome);
+        persist_outcome(&dir, &outcome, None);
         assert!(dir.join(format!("agent-{}.json", outcome.id)).exists());
         assert!(dir.join(format!("agent-{}.md", outcome.id)).exists());
         let _ = std::fs::remove_dir_all(&dir);
     }
 
+    #[test]
+    fn persist_outcome_encrypts_with_transcript_cipher() {
+        use crate::config::TranscriptKeySource;
+
+        let dir = tempfile::tempdir().unwrap();
+        let cipher = TranscriptCipher::from_key([5; 32], TranscriptKeySource::File);
+        let outcome = AgentOutcome {
+            id: Uuid::new_v4(),
+            goal: "pay the rent".into(),
+            executed: false,
+            steps: Vec::new(),
+            completed: true,
+            summary: "done".into(),
+            verification: None,
+            har: None,
+            assertions: Vec::new(),
+        };
+        persist_outcome(dir.path(), &outcome, Some(&cipher));
+        for name in [
+            format!("agent-{}.json", outcome.id),
+            format!("agent-{}.md", outcome.id),
+        ] {
+            let path = dir.path().join(name);
+            let raw = std::fs::read(&path).unwrap();
+            assert!(transcript_crypto::is_encrypted(&raw));
+            let plain = crate::transcript::read_sealed(&path, Some(&cipher)).unwrap();
+            assert!(String::from_utf8(plain).unwrap().contains("pay the rent"));
+        }
+    }
+
     #[test]
     fn step_observer_fires_once_per_step_in_order() {
         use std::sync::Mutex;
@@ -689,4 +1540,435 @@ mod tests {
         assert_eq!(outcome.steps.len(), 2);
         assert_eq!(*observed.lock().unwrap(), vec![1, 2]);
     }
+
+    #[test]
+    fn checkpoint_is_written_per_step_and_marked_finished() {
+        let dir = tempfile::tempdir().expect("temp dir");
+        let orch = orchestrator(enabled_settings());
+        let run_id = Uuid::new_v4();
+        let agent = BrowserAgent::new(orch, 5, true, true, None)
+            .with_checkpoint_store(CheckpointStore::new(dir.path().to_path_buf()))
+            .with_run_id(run_id);
+        let driver = StubDriver::default();
+        let http = ScriptedAiHttp::new(vec![
+            r##"{"action_type":"extract","selector":"#a","description":"one"}"##,
+            r#"{"action_type":"finish","description":"done"}"#,
+        ]);
+        let cancel = AtomicBool::new(false);
+
+        let outcome = agent
+            .run("checkpoint", None, &driver, None, &http, &cancel)
+            .expect("agent run");
+        assert_eq!(outcome.id, run_id);
+
+        let checkpoint = CheckpointStore::new(dir.path().to_path_buf())
+            .load(run_id)
+            .expect("checkpoint written");
+        assert_eq!(checkpoint.status, CheckpointStatus::Finished);
+        assert_eq!(checkpoint.goal, "checkpoint");
+        assert_eq!(checkpoint.steps.len(), 1);
+        assert_eq!(checkpoint.history, vec!["Extract #a -> ok".to_string()]);
+        assert_eq!(
+            checkpoint.current_url.as_deref(),
+            Some("https://example.test/")
+        );
+        assert!(checkpoint.storage.is_some());
+    }
+
+    #[test]
+    fn resume_continues_from_checkpoint() {
+        let dir = tempfile::tempdir().expect("temp dir");
+        let first = AgentStep {
+            index: 1,
+            observation: "URL: https://example.test/".into(),
+            action: WebAction::extract("#a"),
+            result: preview_result(&WebAction::extract("#a"), "earlier"),
+            path: None,
+        };
+        let checkpoint = AgentCheckpoint {
+            id: Uuid::new_v4(),
+            goal: "resume me".into(),
+            start_url: None,
+            provider: None,
+            executed: true,
+            max_steps: 3,
+            steps: vec![first],
+            history: vec!["Extract #a -> ok".into()],
+            current_url: Some("https://example.test/next".into()),
+            storage: None,
+            status: CheckpointStatus::Running,
+            updated_at: Utc::now(),
+        };
+        let store = CheckpointStore::new(dir.path().to_path_buf());
+        store.save(&checkpoint);
+        let loaded = store.load(checkpoint.id).expect("load checkpoint");
+
+        let orch = orchestrator(enabled_settings());
+        let agent = BrowserAgent::new(orch, 10, true, true, None).with_checkpoint_store(store);
+        let driver = StubDriver::default();
+        let http = ScriptedAiHttp::new(vec![
+            r##"{"action_type":"extract","selector":"#b","description":"two"}"##,
+            r#"{"action_type":"finish","description":"resumed"}"#,
+        ]);
+        let cancel = AtomicBool::new(false);
+
+        let outcome = agent
+            .resume(loaded, &driver, None, &http, &cancel)
+            .expect("resume");
+
+        assert_eq!(outcome.id, checkpoint.id);
+        assert!(outcome.completed);
+        assert_eq!(outcome.summary, "resumed");
+        assert_eq!(outcome.steps.len(), 2);
+        assert_eq!(outcome.steps[1].index, 2);
+        // The stub never leaves example.test/, so resume navigates back first.
+        assert_eq!(driver.calls()[0], "navigate:https://example.test/next");
+        assert!(driver.calls().iter().any(|c| c == "extract:#b"));
+    }
+
+    #[test]
+    fn checkpoints_are_private_and_encrypted_with_transcripts() {
+        use crate::config::TranscriptKeySource;
+
+        let dir = tempfile::tempdir().expect("temp dir");
+        let checkpoint = AgentCheckpoint {
+            id: Uuid::new_v4(),
+            goal: "keep my cookies".into(),
+            start_url: None,
+            provider: None,
+            executed: true,
+            max_steps: 3,
+            steps: Vec::new(),
+            history: Vec::new(),
+            current_url: None,
+            storage: None,
+            status: CheckpointStatus::Running,
+            updated_at: Utc::now(),
+        };
+        let path = checkpoint_path(dir.path(), checkpoint.id);
+        let plain = CheckpointStore::new(dir.path().to_path_buf());
+        plain.save(&checkpoint);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+
+        let cipher = TranscriptCipher::from_key([9; 32], TranscriptKeySource::File);
+        let sealed = CheckpointStore::new(dir.path().to_path_buf()).with_encryption(cipher);
+        assert_eq!(sealed.encrypt_existing().unwrap(), 1);
+        assert_eq!(sealed.encrypt_existing().unwrap(), 0);
+        let raw = std::fs::read(&path).unwrap();
+        assert!(transcript_crypto::is_encrypted(&raw));
+        assert!(!String::from_utf8_lossy(&raw).contains("keep my cookies"));
+
+        assert_eq!(sealed.load(checkpoint.id).unwrap().goal, "keep my cookies");
+        let err = plain.load(checkpoint.id).unwrap_err().to_string();
+        assert!(err.contains("encrypted"), "{err}");
+    }
+
+    #[test]
+    fn resume_rejects_finished_runs() {
+        let orch = orchestrator(enabled_settings());
+        let agent = BrowserAgent::new(orch, 3, true, true, None);
+        let checkpoint = AgentCheckpoint {
+            id: Uuid::new_v4(),
+            goal: "done".into(),
+            start_url: None,
+            provider: None,
+            executed: true,
+            max_steps: 3,
+            steps: Vec::new(),
+            history: Vec::new(),
+            current_url: None,
+            storage: None,
+            status: CheckpointStatus::Finished,
+            updated_at: Utc::now(),
+        };
+        let driver = StubDriver::default();
+        let http = ScriptedAiHttp::new(vec![]);
+        let cancel = AtomicBool::new(false);
+
+        let err = agent
+            .resume(checkpoint, &driver, None, &http, &cancel)
+            .unwrap_err();
+        assert!(err.to_string().contains("already finished"));
+    }
+
+    #[test]
+    fn paused_run_waits_until_unpaused() {
+        let orch = orchestrator(enabled_settings());
+        let pause = Arc::new(AtomicBool::new(true));
+        let agent =
+            BrowserAgent::new(orch, 3, true, true, None).with_pause_flag(Arc::clone(&pause));
+        let driver = StubDriver::default();
+        let http = ScriptedAiHttp::new(vec![r#"{"action_type":"finish","description":"ok"}"#]);
+        let cancel = AtomicBool::new(false);
+
+        let unpause = Arc::clone(&pause);
+        let waker = std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(300));
+            unpause.store(false, Ordering::Relaxed);
+        });
+        let started = std::time::Instant::now();
+        let outcome = agent
+            .run("pause", None, &driver, None, &http, &cancel)
+            .expect("agent run");
+        waker.join().unwrap();
+
+        assert!(outcome.completed);
+        assert!(started.elapsed() >= Duration::from_millis(250));
+    }
+
+    #[test]
+    fn cancelled_runs_stay_resumable() {
+        let dir = tempfile::tempdir().expect("temp dir");
+        let store = || CheckpointStore::new(dir.path().to_path_buf());
+        let run_id = Uuid::new_v4();
+        // Pause after the first step, then cancel while parked, as the host
+        // does when the client of a paused run disconnects.
+        let pause = Arc::new(AtomicBool::new(false));
+        let pause_after_step = Arc::clone(&pause);
+        let agent = BrowserAgent::new(orchestrator(enabled_settings()), 5, true, true, None)
+            .with_checkpoint_store(store())
+            .with_run_id(run_id)
+            .with_pause_flag(Arc::clone(&pause))
+            .with_step_observer(Box::new(move |_| {
+                pause_after_step.store(true, Ordering::Relaxed);
+            }));
+        let driver = StubDriver::default();
+        let http = ScriptedAiHttp::new(vec![
+            r##"{"action_type":"extract","selector":"#a","description":"one"}"##,
+        ]);
+        let cancel = Arc::new(AtomicBool::new(false));
+        let canceller = {
+            let cancel = Arc::clone(&cancel);
+            std::thread::spawn(move || {
+                std::thread::sleep(Duration::from_millis(300));
+                cancel.store(true, Ordering::Relaxed);
+            })
+        };
+        let outcome = agent
+            .run("survive", None, &driver, None, &http, &cancel)
+            .expect("agent run");
+        canceller.join().unwrap();
+        assert!(!outcome.completed);
+        assert_eq!(outcome.summary, "Run cancelled");
+
+        let checkpoint = store().load(run_id).expect("checkpoint written");
+        assert_eq!(checkpoint.status, CheckpointStatus::Cancelled);
+        assert_eq!(checkpoint.steps.len(), 1);
+
+        let resumed = BrowserAgent::new(orchestrator(enabled_settings()), 5, true, true, None)
+            .with_checkpoint_store(store())
+            .resume(
+                checkpoint,
+                &driver,
+                None,
+                &ScriptedAiHttp::new(vec![r#"{"action_type":"finish","description":"done"}"#]),
+                &AtomicBool::new(false),
+            )
+            .expect("resume cancelled run");
+        assert!(resumed.completed);
+        assert_eq!(resumed.steps.len(), 1);
+        assert_eq!(
+            store().load(run_id).unwrap().status,
+            CheckpointStatus::Finished
+        );
+    }
+
+    #[test]
+    fn domain_rules_apply_to_agent_actions() {
+        let settings = AutomationSettings {
+            rules: vec![crate::config::AutomationRule {
+                domain: "example.test".into(),
+                forbid_typing: true,
+                ..Default::default()
+            }],
+            ..enabled_settings()
+        };
+        let agent = BrowserAgent::new(orchestrator(settings), 3, true, true, None);
+        let driver = StubDriver::default();
+        let http = ScriptedAiHttp::new(vec![
+            r##"{"action_type":"type","selector":"#q","value":"hi","description":"search"}"##,
+        ]);
+        let cancel = AtomicBool::new(false);
+
+        let outcome = agent
+            .run("search", None, &driver, None, &http, &cancel)
+            .expect("agent run");
+
+        let step = &outcome.steps[0];
+        assert_eq!(step.action.domain.as_deref(), Some("example.test"));
+        assert!(!step.result.success);
+        assert!(
+            step.result
+                .error
+                .as_deref()
+                .is_some_and(|e| e.contains("Typing is forbidden"))
+        );
+        assert_eq!(driver.mutations(), 0);
+    }
+
+    #[test]
+    fn domain_rate_limits_count_each_executed_action_once() {
+        let settings = AutomationSettings {
+            rules: vec![crate::config::AutomationRule {
+                domain: "example.test".into(),
+                max_actions_per_minute: Some(2),
+                ..Default::default()
+            }],
+            ..enabled_settings()
+        };
+        let agent = BrowserAgent::new(orchestrator(settings), 3, true, true, None);
+        let driver = StubDriver::default();
+        let http = ScriptedAiHttp::new(vec![
+            r##"{"action_type":"click","selector":"#a","description":"a"}"##,
+            r##"{"action_type":"click","selector":"#b","description":"b"}"##,
+            r##"{"action_type":"click","selector":"#c","description":"c"}"##,
+        ]);
+        let cancel = AtomicBool::new(false);
+
+        let outcome = agent
+            .run("click", None, &driver, None, &http, &cancel)
+            .expect("agent run");
+
+        // Validating an action before executing it must not use up the budget.
+        assert!(outcome.steps[0].result.success);
+        assert!(outcome.steps[1].result.success);
+        assert!(!outcome.steps[2].result.success);
+        assert!(
+            outcome.steps[2]
+                .result
+                .error
+                .as_deref()
+                .is_some_and(|e| e.contains("Rate limit for 'example.test'"))
+        );
+        assert_eq!(driver.mutations(), 2);
+    }
+
+    #[test]
+    fn success_criterion_parses_cli_forms() {
+        assert_eq!(
+            SuccessCriterion::parse("text:Order placed").unwrap(),
+            SuccessCriterion::TextPresent {
+                text: "Order placed".into()
+            }
+        );
+        assert_eq!(
+            SuccessCriterion::parse("url:*/checkout/done*").unwrap(),
+            SuccessCriterion::UrlMatches {
+                pattern: "*/checkout/done*".into()
+            }
+        );
+        assert_eq!(
+            SuccessCriterion::parse("element:#status=Shipped").unwrap(),
+            SuccessCriterion::ElementText {
+                selector: "#status".into(),
+                contains: "Shipped".into()
+            }
+        );
+        assert_eq!(
+            SuccessCriterion::parse("element:.cart").unwrap(),
+            SuccessCriterion::ElementPresent {
+                selector: ".cart".into()
+            }
+        );
+        assert!(SuccessCriterion::parse("text:").is_err());
+        assert!(SuccessCriterion::parse("cookie:x").is_err());
+        assert!(SuccessCriterion::parse("no-kind").is_err());
+    }
+
+    #[test]
+    fn criteria_verification_accepts_matching_finish() {
+        let orch = orchestrator(enabled_settings());
+        let agent = BrowserAgent::new(orch, 5, true, true, None).with_verification(
+            vec![
+                SuccessCriterion::TextPresent {
+                    text: "body text".into(),
+                },
+                SuccessCriterion::UrlMatches {
+                    pattern: "https://example.test/*".into(),
+                },
+                SuccessCriterion::ElementText {
+                    selector: "#lnk".into(),
+                    contains: "extract".into(),
+                },
+            ],
+            DEFAULT_VERIFY_RETRIES,
+        );
+        let driver = StubDriver::default();
+        let http = ScriptedAiHttp::new(vec![r#"{"action_type":"finish","description":"done"}"#]);
+        let cancel = AtomicBool::new(false);
+
+        let outcome = agent
+            .run("check", None, &driver, None, &http, &cancel)
+            .expect("agent run");
+
+        assert!(outcome.completed);
+        assert_eq!(outcome.summary, "done");
+        let verdict = outcome.verification.expect("verdict recorded");
+        assert!(verdict.passed);
+        assert_eq!(verdict.method, VerificationMethod::Criteria);
+        assert_eq!(verdict.attempts, 1);
+        assert_eq!(verdict.details.len(), 3);
+    }
+
+    #[test]
+    fn failed_verification_retries_then_reports_failure() {
+        let orch = orchestrator(enabled_settings());
+        let agent = BrowserAgent::new(orch, 10, true, true, None).with_verification(
+            vec![SuccessCriterion::TextPresent {
+                text: "Order placed".into(),
+            }],
+            1,
+        );
+        let driver = StubDriver::default();
+        let http = ScriptedAiHttp::new(vec![
+            r#"{"action_type":"finish","description":"done"}"#,
+            r##"{"action_type":"extract","selector":"#lnk","description":"look again"}"##,
+            r#"{"action_type":"finish","description":"really done"}"#,
+        ]);
+        let cancel = AtomicBool::new(false);
+
+        let outcome = agent
+            .run("place order", None, &driver, None, &http, &cancel)
+            .expect("agent run");
+
+        assert!(!outcome.completed);
+        assert!(outcome.summary.starts_with("Goal not verified"));
+        assert_eq!(outcome.steps.len(), 1);
+        assert_eq!(outcome.steps[0].index, 1);
+        let verdict = outcome.verification.expect("verdict recorded");
+        assert!(!verdict.passed);
+        assert_eq!(verdict.attempts, 2);
+        assert!(verdict.details[0].starts_with("FAIL:"));
+    }
+
+    #[test]
+    fn model_verification_judges_without_criteria() {
+        let orch = orchestrator(enabled_settings());
+        let agent = BrowserAgent::new(orch, 5, true, true, None)
+            .with_verification(Vec::new(), DEFAULT_VERIFY_RETRIES);
+        let driver = StubDriver::default();
+        let http = ScriptedAiHttp::new(vec![
+            r#"{"action_type":"finish","description":"found it"}"#,
+            r#"{"success": true, "reason": "the link is on the page"}"#,
+        ]);
+        let cancel = AtomicBool::new(false);
+
+        let outcome = agent
+            .run("find the link", None, &driver, None, &http, &cancel)
+            .expect("agent run");
+
+        assert!(outcome.completed);
+        let verdict = outcome.verification.clone().expect("verdict recorded");
+        assert_eq!(verdict.method, VerificationMethod::Model);
+        assert_eq!(verdict.details, vec!["the link is on the page".to_string()]);
+
+        let md = render_markdown(&outcome);
+        assert!(md.contains("- Verified: true (Model, 1 attempt)"));
+        assert!(md.contains("## Verification"));
+    }
 }
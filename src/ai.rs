@@ -14,10 +14,11 @@ use serde_json::{Value, json};
 use url::Url;
 
 use crate::config::{AiProviderCapabilities, AiProviderConfig, AiProviderKind, AiSettings};
+use crate::recall::TranscriptRecall;
 use crate::sync_util::LockResultExt;
 use crate::transcript::{
-    AttachmentInput, TranscriptInput, TranscriptRole, TranscriptSource, TranscriptStore,
-    TranscriptSummary,
+    AttachmentInput, RecalledMessage, TranscriptInput, TranscriptRole, TranscriptSource,
+    TranscriptStore, TranscriptSummary,
 };
 use uuid::Uuid;
 
@@ -30,6 +31,7 @@ pub struct AiBridge {
     providers: Vec<AiProviderConfig>,
     default_provider: String,
     transcripts: Arc<TranscriptStore>,
+    recall: TranscriptRecall,
     metrics: Arc<AiProviderMetrics>,
     telemetry: Option<ServiceTelemetry>,
 }
@@ -47,6 +49,7 @@ impl AiBridge {
         Self {
             providers: settings.providers.clone(),
             default_provider: settings.default_provider.clone(),
+            recall: TranscriptRecall::new(settings.recall.clone(), Arc::clone(&transcripts)),
             transcripts,
             metrics: Arc::new(AiProviderMetrics::default()),
             telemetry,
@@ -65,6 +68,11 @@ impl AiBridge {
         Arc::clone(&self.transcripts)
     }
 
+    /// Semantic recall over this bridge's transcripts.
+    pub fn recall(&self) -> &TranscriptRecall {
+        &self.recall
+    }
+
     pub fn provider_metrics(&self) -> Vec<ProviderMetricsEntry> {
         self.metrics.snapshot()
     }
@@ -151,8 +159,51 @@ impl AiBridge {
 
         self.ensure_capabilities(config, &prompt)?;
 
+        let prompt = self.attach_recall(prompt, http);
         let response_result = self.dispatch_chat(config, &prompt, http);
-        self.finalize_response(config, &prompt, response_result)
+        let response = self.finalize_response(config, &prompt, response_result)?;
+        self.remember(&prompt, &response, http);
+        Ok(response)
+    }
+
+    /// Chat with `invoker`'s tools on offer. While the model replies with a
+    /// `<tool_call>`, the tool is run and its output sent back as a
+    /// `<tool_result>` turn of the same conversation, for at most `max_rounds`
+    /// calls. Returns the first reply that is not a tool call (or the last one
+    /// once the budget is spent).
+    pub fn chat_with_tools<T: AiHttp>(
+        &self,
+        provider: Option<&str>,
+        prompt: AiChatPrompt,
+        http: &T,
+        invoker: &dyn AiToolInvoker,
+        max_rounds: usize,
+    ) -> Result<AiChatResponse> {
+        // Recall once for the user's request rather than for each tool result.
+        let mut prompt = self.attach_recall(prompt.with_tools(invoker.tools()), http);
+        let mut response = self.chat_with_prompt(provider, prompt.clone(), http)?;
+        for _ in 0..max_rounds {
+            let Some((name, arguments)) = parse_tool_call(&response.reply) else {
+                break;
+            };
+            let output = match invoker.invoke(&name, arguments) {
+                Ok(output) => output,
+                Err(err) => format!("error: {err:#}"),
+            };
+            prompt.history.push(AiChatHistoryEntry {
+                role: AiChatRole::User,
+                content: std::mem::take(&mut prompt.text),
+            });
+            prompt.history.push(AiChatHistoryEntry {
+                role: AiChatRole::Assistant,
+                content: response.reply.clone(),
+            });
+            prompt.text = format!("<tool_result name=\"{name}\">\n{output}\n</tool_result>");
+            prompt.attachments.clear();
+            prompt.conversation_id = response.conversation_id;
+            response = self.chat_with_prompt(provider, prompt.clone(), http)?;
+        }
+        Ok(response)
     }
 
     /// Provider-agnostic streaming chat. Emits incremental reply text to `on_delta` as it
@@ -178,6 +229,7 @@ impl AiBridge {
 
         self.ensure_capabilities(config, &prompt)?;
 
+        let prompt = self.attach_recall(prompt, http);
         let response_result = match config.kind {
             AiProviderKind::LocalOllama => {
                 self.chat_with_ollama_streaming(config, &prompt, http, on_delta)
@@ -193,7 +245,61 @@ impl AiBridge {
             }
         };
 
-        self.finalize_response(config, &prompt, response_result)
+        let response = self.finalize_response(config, &prompt, response_result)?;
+        self.remember(&prompt, &response, http);
+        Ok(response)
+    }
+
+    /// Whether recall applies to `prompt`: its explicit toggle, otherwise the
+    /// stored setting of its conversation (off for new conversations).
+    fn recall_requested(&self, prompt: &AiChatPrompt) -> bool {
+        match (prompt.recall, prompt.conversation_id) {
+            (Some(enabled), _) => enabled,
+            (None, Some(id)) => self.transcripts.recall_enabled(id).unwrap_or(false),
+            (None, None) => false,
+        }
+    }
+
+    /// Add past messages similar to the prompt when recall applies. Failures
+    /// (e.g. Ollama not running) only cost the recalled context.
+    fn attach_recall<T: AiHttp>(&self, mut prompt: AiChatPrompt, http: &T) -> AiChatPrompt {
+        if !self.recall.is_enabled()
+            || !prompt.recalled.is_empty()
+            || prompt.text.trim().is_empty()
+            || !self.recall_requested(&prompt)
+        {
+            return prompt;
+        }
+        match self
+            .recall
+            .recall(http, &prompt.text, prompt.conversation_id)
+        {
+            Ok(recalled) => prompt.recalled = recalled,
+            Err(err) => {
+                tracing::warn!(error = %err, "semantic recall failed; continuing without it")
+            }
+        }
+        prompt
+    }
+
+    /// Persist the prompt's recall toggle and embed the new messages of a
+    /// conversation that has recall switched on.
+    fn remember<T: AiHttp>(&self, prompt: &AiChatPrompt, response: &AiChatResponse, http: &T) {
+        let Some(id) = response.conversation_id else {
+            return;
+        };
+        if let Some(enabled) = prompt.recall
+            && let Err(err) = self.transcripts.set_recall(id, enabled)
+        {
+            tracing::warn!(error = %err, transcript = %id, "failed to store recall setting");
+            return;
+        }
+        if !self.recall.is_enabled() || !self.transcripts.recall_enabled(id).unwrap_or(false) {
+            return;
+        }
+        if let Err(err) = self.recall.index_conversation(http, id) {
+            tracing::warn!(error = %err, transcript = %id, "failed to embed transcript messages");
+        }
     }
 
     /// Dispatch a (non-streaming) chat request to the configured provider.
@@ -1312,6 +1418,13 @@ pub struct AiChatPrompt {
     pub conversation_id: Option<Uuid>,
     pub source: TranscriptSource,
     pub page_context: Option<PageContext>,
+    /// Tools the model may call, described in the system prompt.
+    pub tools: Vec<AiTool>,
+    /// Switch semantic recall on or off for this conversation; `None` keeps
+    /// the conversation's stored setting.
+    pub recall: Option<bool>,
+    /// Past messages added by recall, listed in the system prompt.
+    pub recalled: Vec<RecalledMessage>,
 }
 
 impl AiChatPrompt {
@@ -1323,6 +1436,9 @@ impl AiChatPrompt {
             conversation_id: None,
             source: TranscriptSource::Unknown,
             page_context: None,
+            tools: Vec::new(),
+            recall: None,
+            recalled: Vec::new(),
         }
     }
 
@@ -1334,6 +1450,9 @@ impl AiChatPrompt {
             conversation_id: None,
             source: TranscriptSource::Unknown,
             page_context: None,
+            tools: Vec::new(),
+            recall: None,
+            recalled: Vec::new(),
         }
     }
 
@@ -1363,18 +1482,87 @@ impl AiChatPrompt {
         self
     }
 
+    pub fn with_tools(mut self, tools: Vec<AiTool>) -> Self {
+        self.tools = tools;
+        self
+    }
+
+    pub fn with_recall(mut self, recall: Option<bool>) -> Self {
+        self.recall = recall;
+        self
+    }
+
     /// Build the system prompt for this turn, appending the page-context block
-    /// when a non-empty page snapshot is attached.
+    /// when a non-empty page snapshot is attached, recalled messages and the
+    /// tool catalogue when tools are offered.
     fn system_prompt(&self) -> String {
-        match &self.page_context {
+        let mut prompt = match &self.page_context {
             Some(ctx) if !ctx.is_empty() => {
                 format!("{SYSTEM_PROMPT}{}", ctx.render_block())
             }
             _ => SYSTEM_PROMPT.to_string(),
+        };
+        if !self.recalled.is_empty() {
+            prompt.push_str(
+                "\n\nExcerpts from the user's earlier conversations that may be relevant. \
+                 Use them only if they help with the current request:\n",
+            );
+            for message in &self.recalled {
+                prompt.push_str(&format!(
+                    "- [{} · \"{}\" · {}] {}\n",
+                    message.timestamp.format("%Y-%m-%d"),
+                    message.title,
+                    message.role,
+                    message.content.trim().replace('\n', " ")
+                ));
+            }
+        }
+        if !self.tools.is_empty() {
+            prompt.push_str(
+                "\n\nYou can call tools. To call one, reply with only \
+                 <tool_call>{\"name\": \"<tool>\", \"arguments\": {...}}</tool_call> \
+                 and wait for the <tool_result>. Answer normally once you have what you need. \
+                 Available tools:\n",
+            );
+            for tool in &self.tools {
+                prompt.push_str(&format!(
+                    "- {}: {} Arguments schema: {}\n",
+                    tool.name,
+                    tool.description.trim(),
+                    tool.input_schema
+                ));
+            }
         }
+        prompt
     }
 }
 
+/// A tool offered to the model through [`AiChatPrompt::with_tools`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AiTool {
+    pub name: String,
+    pub description: String,
+    /// JSON Schema of the tool's arguments.
+    pub input_schema: Value,
+}
+
+/// Source of tools for [`AiBridge::chat_with_tools`] (e.g. MCP connectors).
+pub trait AiToolInvoker {
+    fn tools(&self) -> Vec<AiTool>;
+    /// Run `name` and return its result as text for the model.
+    fn invoke(&self, name: &str, arguments: Value) -> Result<String>;
+}
+
+/// Extract a `<tool_call>{"name", "arguments"}</tool_call>` request from a reply.
+fn parse_tool_call(reply: &str) -> Option<(String, Value)> {
+    let start = reply.find("<tool_call>")? + "<tool_call>".len();
+    let end = start + reply[start..].find("</tool_call>")?;
+    let call: Value = serde_json::from_str(reply[start..end].trim()).ok()?;
+    let name = call.get("name")?.as_str()?.to_string();
+    let arguments = call.get("arguments").cloned().unwrap_or_else(|| json!({}));
+    Some((name, arguments))
+}
+
 /// Return the trimmed string slice when present and non-empty.
 fn non_blank(value: &Option<String>) -> Option<&str> {
     value.as_deref().map(str::trim).filter(|s| !s.is_empty())
@@ -2031,6 +2219,200 @@ mod tests {
         assert!(calls.iter().any(|call| call.url == chat));
     }
 
+    /// Ollama stub answering every chat request with the next scripted reply.
+    struct ScriptedOllamaHttp {
+        replies: RefCell<Vec<&'static str>>,
+        bodies: RefCell<Vec<Value>>,
+    }
+
+    impl AiHttp for ScriptedOllamaHttp {
+        fn get_json(&self, _url: &str, _headers: &[(String, String)]) -> Result<Value> {
+            Ok(json!({ "version": "0.1" }))
+        }
+
+        fn post_json(
+            &self,
+            _url: &str,
+            _headers: &[(String, String)],
+            body: &Value,
+        ) -> Result<Value> {
+            self.bodies.borrow_mut().push(body.clone());
+            let reply = self.replies.borrow_mut().remove(0);
+            Ok(json!({ "message": { "role": "assistant", "content": reply } }))
+        }
+    }
+
+    struct EchoTools {
+        calls: RefCell<Vec<(String, Value)>>,
+    }
+
+    impl AiToolInvoker for EchoTools {
+        fn tools(&self) -> Vec<AiTool> {
+            vec![AiTool {
+                name: "files__read".into(),
+                description: "Read a file.".into(),
+                input_schema: json!({ "type": "object" }),
+            }]
+        }
+
+        fn invoke(&self, name: &str, arguments: Value) -> Result<String> {
+            self.calls.borrow_mut().push((name.to_string(), arguments));
+            Ok("file contents".into())
+        }
+    }
+
+    #[test]
+    fn chat_with_tools_runs_requested_tool_and_returns_final_reply() {
+        let bridge = bridge_with_settings(&AiSettings::default());
+        let http = ScriptedOllamaHttp {
+            replies: RefCell::new(vec![
+                "<tool_call>{\"name\": \"files__read\", \"arguments\": {\"path\": \"a.txt\"}}</tool_call>",
+                "It says: file contents",
+            ]),
+            bodies: RefCell::new(Vec::new()),
+        };
+        let tools = EchoTools {
+            calls: RefCell::new(Vec::new()),
+        };
+
+        let response = bridge
+            .chat_with_tools(None, AiChatPrompt::text("read a.txt"), &http, &tools, 3)
+            .expect("chat should succeed");
+        assert_eq!(response.reply, "It says: file contents");
+        assert_eq!(
+            tools.calls.borrow().as_slice(),
+            [("files__read".to_string(), json!({ "path": "a.txt" }))]
+        );
+
+        let bodies = http.bodies.borrow();
+        assert_eq!(bodies.len(), 2);
+        let system = bodies[0]["messages"][0]["content"].as_str().unwrap();
+        assert!(system.contains("- files__read: Read a file."));
+        let messages = bodies[1]["messages"].as_array().unwrap();
+        assert_eq!(messages[1]["content"], "read a.txt");
+        let last = messages.last().unwrap()["content"].as_str().unwrap();
+        assert!(last.starts_with("<tool_result name=\"files__read\">"));
+        assert!(last.contains("file contents"));
+    }
+
+    /// Ollama stub embedding text as `[mentions DoQ, mentions cooking, ε]` and
+    /// replying "ok" to every chat.
+    struct RecallOllamaHttp {
+        chats: RefCell<Vec<Value>>,
+        embedded: RefCell<Vec<String>>,
+    }
+
+    impl AiHttp for RecallOllamaHttp {
+        fn get_json(&self, _url: &str, _headers: &[(String, String)]) -> Result<Value> {
+            Ok(json!({ "version": "0.1" }))
+        }
+
+        fn post_json(
+            &self,
+            url: &str,
+            _headers: &[(String, String)],
+            body: &Value,
+        ) -> Result<Value> {
+            if url.ends_with("/api/embed") {
+                let inputs = body["input"].as_array().unwrap();
+                let embeddings: Vec<Value> = inputs
+                    .iter()
+                    .map(|input| {
+                        let text = input.as_str().unwrap().to_lowercase();
+                        self.embedded.borrow_mut().push(text.clone());
+                        let doq = if text.contains("doq") { 1.0 } else { 0.0 };
+                        let cook = if text.contains("cook") { 1.0 } else { 0.0 };
+                        json!([doq, cook, 0.01])
+                    })
+                    .collect();
+                return Ok(json!({ "embeddings": embeddings }));
+            }
+            self.chats.borrow_mut().push(body.clone());
+            Ok(json!({ "message": { "role": "assistant", "content": "ok" } }))
+        }
+    }
+
+    #[test]
+    fn recall_offers_similar_messages_from_opted_in_conversations() {
+        let mut settings = AiSettings::default();
+        settings.recall.enabled = true;
+        let bridge = bridge_with_settings(&settings);
+        let http = RecallOllamaHttp {
+            chats: RefCell::new(Vec::new()),
+            embedded: RefCell::new(Vec::new()),
+        };
+        let system = |index: usize| {
+            http.chats.borrow()[index]["messages"][0]["content"]
+                .as_str()
+                .unwrap()
+                .to_string()
+        };
+
+        let doq = bridge
+            .chat_with_prompt(
+                None,
+                AiChatPrompt::text("How do I rotate DoQ certificates?").with_recall(Some(true)),
+                &http,
+            )
+            .unwrap()
+            .conversation_id
+            .unwrap();
+        assert!(bridge.transcript_store().recall_enabled(doq).unwrap());
+        assert!(!system(0).contains("Excerpts"));
+
+        // Conversations without recall are neither embedded nor given context.
+        let embedded_before = http.embedded.borrow().len();
+        bridge
+            .chat_with_prompt(None, AiChatPrompt::text("DoQ cooking tips"), &http)
+            .unwrap();
+        assert_eq!(http.embedded.borrow().len(), embedded_before);
+        assert!(!system(1).contains("Excerpts"));
+
+        let renew = bridge
+            .chat_with_prompt(
+                None,
+                AiChatPrompt::text("Renew the DoQ cert").with_recall(Some(true)),
+                &http,
+            )
+            .unwrap()
+            .conversation_id
+            .unwrap();
+        let prompt = system(2);
+        assert!(prompt.contains("Excerpts from the user's earlier conversations"));
+        assert!(
+            prompt.contains("How do I rotate DoQ certificates?"),
+            "{prompt}"
+        );
+        assert!(
+            !prompt.contains("] ok"),
+            "dissimilar replies are not recalled"
+        );
+
+        let related = bridge.recall().related(renew, 5).unwrap();
+        assert_eq!(related.first().map(|entry| entry.id), Some(doq));
+
+        // Opting out drops the conversation's embeddings.
+        bridge.transcript_store().set_recall(doq, false).unwrap();
+        bridge
+            .chat_with_prompt(
+                None,
+                AiChatPrompt::text("DoQ again").with_recall(Some(true)),
+                &http,
+            )
+            .unwrap();
+        assert!(!system(3).contains("How do I rotate DoQ certificates?"));
+    }
+
+    #[test]
+    fn parse_tool_call_requires_a_named_call() {
+        assert_eq!(
+            parse_tool_call("<tool_call>{\"name\":\"x\"}</tool_call>"),
+            Some(("x".to_string(), json!({})))
+        );
+        assert!(parse_tool_call("no call here").is_none());
+        assert!(parse_tool_call("<tool_call>{\"arguments\":{}}</tool_call>").is_none());
+    }
+
     /// Stub that serves `get_json`/`post_json` from a map, but overrides `post_stream`
     /// to emit a scripted list of NDJSON lines (one `on_line` call per entry).
     struct StreamingStubAiHttp {
@@ -0,0 +1,486 @@
+//! Tamper-evident audit log of executed browser actions.
+//!
+//! Every action that reaches [`crate::automation::AutomationOrchestrator`]'s
+//! live executor (or is declined at its confirmation prompt) is appended to a
+//! JSON-lines file. Each entry carries the SHA-256 digest of the previous
+//! entry, so editing, reordering or removing a line breaks the chain. A small
+//! `<log>.head` sidecar records the latest sequence number and digest so that
+//! truncating the tail is detected too. `archon --audit-verify` walks the chain
+//! via [`verify_audit_log`].
+
+use std::fs::{self, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+use crate::automation::{ActionResult, ActionType, RiskLevel, WebAction};
+use crate::config::LaunchSettings;
+use crate::sync_util::LockResultExt;
+
+/// `prev` digest of the first entry in a chain.
+pub const GENESIS_DIGEST: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+/// Placeholder stored instead of values typed into sensitive fields.
+const REDACTED: &str = "[redacted]";
+
+/// Who drove the browser.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditActor {
+    /// Interactive CLI agent (`archon --agent`).
+    Cli,
+    /// External MCP client (`archon --mcp`).
+    Mcp,
+    /// The AI host HTTP API (`archon-host`).
+    Host,
+    /// An automation recipe (`archon --automate`).
+    Recipe,
+    /// A scheduled or webhook-triggered recipe run (`archon-host`).
+    Scheduler,
+}
+
+/// Outcome of the confirmation gate for an action.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConfirmationDecision {
+    /// Policy did not ask for confirmation.
+    NotRequired,
+    /// A human approved the action at the prompt.
+    Confirmed,
+    /// Confirmation was required but waived (`--agent-yes`, unattended policy).
+    AutoApproved,
+    /// A human declined the action; it was not executed.
+    Declined,
+}
+
+/// The hashed body of an audit entry.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AuditRecord {
+    /// Position in the chain, starting at 0.
+    pub seq: u64,
+    pub timestamp: DateTime<Utc>,
+    pub actor: AuditActor,
+    pub action_id: Uuid,
+    pub action: ActionType,
+    #[serde(default)]
+    pub selector: Option<String>,
+    /// Action value; redacted for sensitive and secret actions.
+    #[serde(default)]
+    pub value: Option<String>,
+    #[serde(default)]
+    pub domain: Option<String>,
+    pub risk: RiskLevel,
+    pub confirmation: ConfirmationDecision,
+    /// Whether the action ran and succeeded (always false when declined).
+    pub success: bool,
+    #[serde(default)]
+    pub error: Option<String>,
+    /// Digest of the previous entry ([`GENESIS_DIGEST`] for the first).
+    pub prev: String,
+}
+
+impl AuditRecord {
+    /// Hex SHA-256 over the record's canonical JSON.
+    pub fn digest(&self) -> String {
+        let bytes = serde_json::to_vec(self).expect("audit record serialises");
+        hex::encode(Sha256::digest(bytes))
+    }
+}
+
+/// One line of the audit log: a record plus its digest.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AuditEntry {
+    #[serde(flatten)]
+    pub record: AuditRecord,
+    pub hash: String,
+}
+
+/// Latest chain position, mirrored to the `.head` sidecar.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct ChainHead {
+    seq: u64,
+    hash: String,
+}
+
+/// Append-only writer for a hash-chained audit log.
+#[derive(Debug)]
+pub struct AuditLog {
+    path: PathBuf,
+    lock: Mutex<()>,
+}
+
+impl AuditLog {
+    /// Open (creating parent directories) the audit log at `path`.
+    pub fn open(path: impl Into<PathBuf>) -> Result<Self> {
+        let path = path.into();
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).with_context(|| {
+                format!("Failed to create audit log directory {}", parent.display())
+            })?;
+        }
+        Ok(Self {
+            path,
+            lock: Mutex::new(()),
+        })
+    }
+
+    /// The configured audit log, or `None` when `automation.audit_log` is off.
+    /// A log that cannot be opened is reported and skipped rather than
+    /// blocking the run.
+    pub fn from_settings(settings: &LaunchSettings) -> Option<Self> {
+        if !settings.automation.audit_log {
+            return None;
+        }
+        match settings.resolve_audit_log().and_then(Self::open) {
+            Ok(log) => Some(log),
+            Err(err) => {
+                tracing::warn!(error = %err, "failed to open automation audit log");
+                None
+            }
+        }
+    }
+
+    /// Path of the log file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Append an entry for `action` and return it.
+    ///
+    /// The chain position is re-read from the head sidecar on every append,
+    /// under an advisory lock on `<log>.lock`, so that several processes (CLI,
+    /// MCP server, host) can share one log without forking the chain.
+    pub fn append(
+        &self,
+        actor: AuditActor,
+        action: &WebAction,
+        risk: RiskLevel,
+        confirmation: ConfirmationDecision,
+        result: Option<&ActionResult>,
+    ) -> Result<AuditEntry> {
+        let _guard = self.lock.lock().recover();
+        let lock_file = lock_path(&self.path);
+        let process_lock = OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(&lock_file)
+            .with_context(|| format!("Failed to open audit lock {}", lock_file.display()))?;
+        // Released when `process_lock` is dropped at the end of the append.
+        process_lock
+            .lock()
+            .with_context(|| format!("Failed to lock audit log {}", self.path.display()))?;
+        let head = self.current_head()?;
+        let (seq, prev) = match head {
+            Some(head) => (head.seq + 1, head.hash),
+            None => (0, GENESIS_DIGEST.to_string()),
+        };
+        let value = if action.sensitive || action.secret {
+            action.value.as_ref().map(|_| REDACTED.to_string())
+        } else {
+            action.value.clone()
+        };
+        let record = AuditRecord {
+            seq,
+            timestamp: Utc::now(),
+            actor,
+            action_id: action.id,
+            action: action.action_type.clone(),
+            selector: action.selector.clone(),
+            value,
+            domain: action.domain.clone(),
+            risk,
+            confirmation,
+            success: result.is_some_and(|r| r.success),
+            error: result.and_then(|r| r.error.clone()),
+            prev,
+        };
+        let entry = AuditEntry {
+            hash: record.digest(),
+            record,
+        };
+
+        let mut line = serde_json::to_string(&entry)?;
+        line.push('\n');
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .with_context(|| format!("Failed to open audit log {}", self.path.display()))?;
+        file.write_all(line.as_bytes())
+            .and_then(|_| file.sync_data())
+            .with_context(|| format!("Failed to append to audit log {}", self.path.display()))?;
+
+        write_head(
+            &head_path(&self.path),
+            &ChainHead {
+                seq: entry.record.seq,
+                hash: entry.hash.clone(),
+            },
+        )?;
+        Ok(entry)
+    }
+
+    fn current_head(&self) -> Result<Option<ChainHead>> {
+        let head_file = head_path(&self.path);
+        if head_file.exists() {
+            return read_head(&head_file).map(Some);
+        }
+        // No sidecar yet: resume from the last line (e.g. a log copied in
+        // without its head file).
+        if !self.path.exists() {
+            return Ok(None);
+        }
+        let file = fs::File::open(&self.path)
+            .with_context(|| format!("Failed to read audit log {}", self.path.display()))?;
+        let mut last = None;
+        for line in BufReader::new(file).lines() {
+            let line = line?;
+            if !line.trim().is_empty() {
+                last = Some(line);
+            }
+        }
+        match last {
+            Some(line) => {
+                let entry: AuditEntry =
+                    serde_json::from_str(&line).context("Failed to parse last audit log entry")?;
+                Ok(Some(ChainHead {
+                    seq: entry.record.seq,
+                    hash: entry.hash,
+                }))
+            }
+            None => Ok(None),
+        }
+    }
+}
+
+/// Result of [`verify_audit_log`].
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct AuditReport {
+    /// Entries read from the log.
+    pub entries: u64,
+    /// Digest of the last entry, if any.
+    pub head: Option<String>,
+    /// Problems found; empty when the chain is intact.
+    pub issues: Vec<String>,
+}
+
+impl AuditReport {
+    /// Whether the chain verified with no issues.
+    pub fn is_intact(&self) -> bool {
+        self.issues.is_empty()
+    }
+}
+
+/// Walk the chain in `path`, recomputing every digest and checking the
+/// `prev` links, the sequence numbering and the head sidecar.
+pub fn verify_audit_log(path: &Path) -> Result<AuditReport> {
+    let file = fs::File::open(path)
+        .with_context(|| format!("Failed to open audit log {}", path.display()))?;
+    let mut report = AuditReport::default();
+    let mut prev = GENESIS_DIGEST.to_string();
+    let mut last_seq = None;
+
+    for (index, line) in BufReader::new(file).lines().enumerate() {
+        let line = line.with_context(|| format!("Failed to read {}", path.display()))?;
+        let lineno = index + 1;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let entry: AuditEntry = match serde_json::from_str(&line) {
+            Ok(entry) => entry,
+            Err(err) => {
+                report
+                    .issues
+                    .push(format!("line {lineno}: unparseable entry ({err})"));
+                // The chain cannot be followed past a corrupt line.
+                break;
+            }
+        };
+        let expected_seq = report.entries;
+        if entry.record.seq != expected_seq {
+            report.issues.push(format!(
+                "line {lineno}: sequence {} (expected {expected_seq})",
+                entry.record.seq
+            ));
+        }
+        if entry.record.prev != prev {
+            report.issues.push(format!(
+                "line {lineno}: previous-digest mismatch (entry removed or reordered)"
+            ));
+        }
+        if entry.record.digest() != entry.hash {
+            report
+                .issues
+                .push(format!("line {lineno}: digest mismatch (entry edited)"));
+        }
+        prev = entry.hash;
+        last_seq = Some(entry.record.seq);
+        report.entries += 1;
+    }
+    if report.entries > 0 {
+        report.head = Some(prev.clone());
+    }
+
+    let head_file = head_path(path);
+    if head_file.exists() {
+        match read_head(&head_file) {
+            Ok(head) => {
+                if Some(head.seq) != last_seq || head.hash != prev {
+                    report.issues.push(format!(
+                        "log ends at sequence {} but head records sequence {} (log truncated or head rewritten)",
+                        last_seq.map_or_else(|| "-".to_string(), |seq| seq.to_string()),
+                        head.seq
+                    ));
+                }
+            }
+            Err(err) => report.issues.push(format!("unreadable head file: {err:#}")),
+        }
+    } else if report.entries > 0 {
+        report
+            .issues
+            .push(format!("head file {} is missing", head_file.display()));
+    }
+
+    Ok(report)
+}
+
+/// Sidecar path holding the chain head for `log`.
+pub fn head_path(log: &Path) -> PathBuf {
+    let mut name = log.file_name().unwrap_or_default().to_os_string();
+    name.push(".head");
+    log.with_file_name(name)
+}
+
+/// Lock file serialising appends across processes.
+fn lock_path(log: &Path) -> PathBuf {
+    let mut name = log.file_name().unwrap_or_default().to_os_string();
+    name.push(".lock");
+    log.with_file_name(name)
+}
+
+fn read_head(path: &Path) -> Result<ChainHead> {
+    let raw = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read audit head {}", path.display()))?;
+    serde_json::from_str(&raw)
+        .with_context(|| format!("Failed to parse audit head {}", path.display()))
+}
+
+/// Write the head via a temp file + rename so a crash never leaves it torn.
+fn write_head(path: &Path, head: &ChainHead) -> Result<()> {
+    let tmp = path.with_extension("head.tmp");
+    fs::write(&tmp, serde_json::to_vec(head)?)
+        .with_context(|| format!("Failed to write audit head {}", tmp.display()))?;
+    fs::rename(&tmp, path)
+        .with_context(|| format!("Failed to update audit head {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn populated_log(dir: &Path, count: usize) -> AuditLog {
+        let log = AuditLog::open(dir.join("audit").join("actions.log")).unwrap();
+        for i in 0..count {
+            let action = WebAction::click(format!("#button-{i}"));
+            log.append(
+                AuditActor::Mcp,
+                &action,
+                RiskLevel::Medium,
+                ConfirmationDecision::NotRequired,
+                None,
+            )
+            .unwrap();
+        }
+        log
+    }
+
+    #[test]
+    fn appended_chain_verifies() {
+        let dir = tempfile::tempdir().unwrap();
+        let log = populated_log(dir.path(), 3);
+        let report = verify_audit_log(log.path()).unwrap();
+        assert!(report.is_intact(), "{:?}", report.issues);
+        assert_eq!(report.entries, 3);
+
+        // A second writer resumes the chain rather than restarting it.
+        let reopened = AuditLog::open(log.path()).unwrap();
+        let action = WebAction::type_text("#password", "hunter2").as_sensitive();
+        let entry = reopened
+            .append(
+                AuditActor::Cli,
+                &action,
+                RiskLevel::Critical,
+                ConfirmationDecision::Declined,
+                None,
+            )
+            .unwrap();
+        assert_eq!(entry.record.seq, 3);
+        assert_eq!(entry.record.value.as_deref(), Some(REDACTED));
+        assert!(verify_audit_log(log.path()).unwrap().is_intact());
+    }
+
+    #[test]
+    fn concurrent_writers_keep_one_chain() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("actions.log");
+        // Separate handles stand in for separate processes: they share no
+        // in-process lock.
+        std::thread::scope(|scope| {
+            for _ in 0..4 {
+                let log = AuditLog::open(&path).unwrap();
+                scope.spawn(move || {
+                    for i in 0..10 {
+                        log.append(
+                            AuditActor::Host,
+                            &WebAction::click(format!("#item-{i}")),
+                            RiskLevel::Low,
+                            ConfirmationDecision::NotRequired,
+                            None,
+                        )
+                        .unwrap();
+                    }
+                });
+            }
+        });
+        let report = verify_audit_log(&path).unwrap();
+        assert!(report.is_intact(), "{:?}", report.issues);
+        assert_eq!(report.entries, 40);
+    }
+
+    #[test]
+    fn edited_entry_is_detected() {
+        let dir = tempfile::tempdir().unwrap();
+        let log = populated_log(dir.path(), 3);
+        let raw = fs::read_to_string(log.path()).unwrap();
+        fs::write(log.path(), raw.replacen("#button-1", "#button-9", 1)).unwrap();
+
+        let report = verify_audit_log(log.path()).unwrap();
+        assert!(!report.is_intact());
+        assert!(report.issues.iter().any(|i| i.contains("line 2: digest")));
+    }
+
+    #[test]
+    fn truncation_and_removal_are_detected() {
+        let dir = tempfile::tempdir().unwrap();
+        let log = populated_log(dir.path(), 3);
+        let raw = fs::read_to_string(log.path()).unwrap();
+        let lines: Vec<&str> = raw.lines().collect();
+
+        // Tail truncated: the chain is still valid but the head disagrees.
+        fs::write(log.path(), format!("{}\n{}\n", lines[0], lines[1])).unwrap();
+        let report = verify_audit_log(log.path()).unwrap();
+        assert!(report.issues.iter().any(|i| i.contains("truncated")));
+
+        // Middle entry removed: the next entry's link breaks.
+        fs::write(log.path(), format!("{}\n{}\n", lines[0], lines[2])).unwrap();
+        let report = verify_audit_log(log.path()).unwrap();
+        assert!(report.issues.iter().any(|i| i.contains("previous-digest")));
+    }
+}
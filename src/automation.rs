@@ -3,6 +3,7 @@
 //! Provides safe, user-confirmed web automation actions
 //! with rate limiting, domain restrictions, and audit logging.
 
+use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 
@@ -12,8 +13,9 @@ use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
 use crate::ai::{AiBridge, AiChatPrompt, AiHttp, BlockingAiHttp};
+use crate::audit::{AuditActor, AuditLog, ConfirmationDecision};
 use crate::browser::BrowserDriver;
-use crate::config::AutomationSettings;
+use crate::config::{AutomationRule, AutomationSettings};
 use crate::sync_util::LockResultExt;
 
 /// Upper bound on a `Wait` action's sleep, in milliseconds.
@@ -43,6 +45,16 @@ pub enum ActionType {
     Select,
     /// Hover over an element.
     Hover,
+    /// Read the JSON body of a captured XHR/fetch response (value = URL pattern).
+    ExtractResponse,
+    /// Go back one entry in the tab's history.
+    GoBack,
+    /// Go forward one entry in the tab's history.
+    GoForward,
+    /// Act on another tab (value = tab index).
+    SwitchTab,
+    /// Evaluate JavaScript in the page (value = expression).
+    Evaluate,
 }
 
 /// Risk level for actions.
@@ -64,6 +76,17 @@ impl RiskLevel {
     pub fn requires_confirmation(&self) -> bool {
         matches!(self, RiskLevel::High | RiskLevel::Critical)
     }
+
+    /// Parse a risk name (`low`, `medium`, `high`, `critical`).
+    pub fn from_keyword(keyword: &str) -> Option<Self> {
+        Some(match keyword.trim().to_lowercase().as_str() {
+            "low" => Self::Low,
+            "medium" => Self::Medium,
+            "high" => Self::High,
+            "critical" => Self::Critical,
+            _ => return None,
+        })
+    }
 }
 
 impl ActionType {
@@ -80,6 +103,11 @@ impl ActionType {
             "submit" => Self::Submit,
             "select" => Self::Select,
             "hover" => Self::Hover,
+            "extract_response" => Self::ExtractResponse,
+            "go_back" => Self::GoBack,
+            "go_forward" => Self::GoForward,
+            "switch_tab" => Self::SwitchTab,
+            "evaluate" => Self::Evaluate,
             _ => return None,
         })
     }
@@ -87,12 +115,19 @@ impl ActionType {
     /// Get the risk level for this action type.
     pub fn risk_level(&self) -> RiskLevel {
         match self {
-            ActionType::Screenshot | ActionType::Extract => RiskLevel::Low,
-            ActionType::Scroll | ActionType::Wait | ActionType::Hover => RiskLevel::Medium,
+            ActionType::Screenshot | ActionType::Extract | ActionType::ExtractResponse => {
+                RiskLevel::Low
+            }
+            ActionType::Scroll
+            | ActionType::Wait
+            | ActionType::Hover
+            | ActionType::GoBack
+            | ActionType::GoForward
+            | ActionType::SwitchTab => RiskLevel::Medium,
             ActionType::Click | ActionType::Type | ActionType::Navigate | ActionType::Select => {
                 RiskLevel::High
             }
-            ActionType::Submit => RiskLevel::Critical,
+            ActionType::Submit | ActionType::Evaluate => RiskLevel::Critical,
         }
     }
 }
@@ -111,6 +146,10 @@ pub struct WebAction {
     /// Whether this action is sensitive (e.g., password field).
     #[serde(default)]
     pub sensitive: bool,
+    /// The value came from a secret reference (e.g. a recipe `secret` param):
+    /// still executed, but redacted from transcripts and the audit log.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub secret: bool,
     /// Whether to require explicit confirmation.
     #[serde(default)]
     pub require_confirmation: bool,
@@ -129,6 +168,7 @@ impl WebAction {
             selector: Some(selector.into()),
             value: None,
             sensitive: false,
+            secret: false,
             require_confirmation: false,
             description: None,
             domain: None,
@@ -143,6 +183,7 @@ impl WebAction {
             selector: Some(selector.into()),
             value: Some(text.into()),
             sensitive: false,
+            secret: false,
             require_confirmation: false,
             description: None,
             domain: None,
@@ -157,6 +198,37 @@ impl WebAction {
             selector: None,
             value: Some(url.into()),
             sensitive: false,
+            secret: false,
+            require_confirmation: false,
+            description: None,
+            domain: None,
+        }
+    }
+
+    /// Create a scroll action (into view of `selector`, else one viewport down).
+    pub fn scroll(selector: Option<String>) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            action_type: ActionType::Scroll,
+            selector,
+            value: None,
+            sensitive: false,
+            secret: false,
+            require_confirmation: false,
+            description: None,
+            domain: None,
+        }
+    }
+
+    /// Create a select action choosing `value` in a `<select>`.
+    pub fn select(selector: impl Into<String>, value: impl Into<String>) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            action_type: ActionType::Select,
+            selector: Some(selector.into()),
+            value: Some(value.into()),
+            sensitive: false,
+            secret: false,
             require_confirmation: false,
             description: None,
             domain: None,
@@ -171,6 +243,7 @@ impl WebAction {
             selector: None,
             value: None,
             sensitive: false,
+            secret: false,
             require_confirmation: false,
             description: None,
             domain: None,
@@ -185,6 +258,72 @@ impl WebAction {
             selector: Some(selector.into()),
             value: None,
             sensitive: false,
+            secret: false,
+            require_confirmation: false,
+            description: None,
+            domain: None,
+        }
+    }
+
+    /// Create an action that reads the JSON body of a captured response whose
+    /// URL matches `url_pattern`.
+    pub fn extract_response(url_pattern: impl Into<String>) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            action_type: ActionType::ExtractResponse,
+            selector: None,
+            value: Some(url_pattern.into()),
+            sensitive: false,
+            secret: false,
+            require_confirmation: false,
+            description: None,
+            domain: None,
+        }
+    }
+
+    /// Create a history action: back, or forward when `forward` is set.
+    pub fn history(forward: bool) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            action_type: if forward {
+                ActionType::GoForward
+            } else {
+                ActionType::GoBack
+            },
+            selector: None,
+            value: None,
+            sensitive: false,
+            secret: false,
+            require_confirmation: false,
+            description: None,
+            domain: None,
+        }
+    }
+
+    /// Create an action that makes the tab at `index` the driven one.
+    pub fn switch_tab(index: usize) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            action_type: ActionType::SwitchTab,
+            selector: None,
+            value: Some(index.to_string()),
+            sensitive: false,
+            secret: false,
+            require_confirmation: false,
+            description: None,
+            domain: None,
+        }
+    }
+
+    /// Create an action evaluating the JavaScript `expression` in the page.
+    pub fn evaluate(expression: impl Into<String>) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            action_type: ActionType::Evaluate,
+            selector: None,
+            value: Some(expression.into()),
+            sensitive: false,
+            secret: false,
             require_confirmation: false,
             description: None,
             domain: None,
@@ -209,6 +348,19 @@ impl WebAction {
         self
     }
 
+    /// Fill in the domain (unless already set) from the navigation target, or
+    /// from the page the driver is on for every other action, so domain
+    /// restrictions and per-domain rules apply.
+    pub fn with_domain_from(mut self, driver: &dyn BrowserDriver) -> Self {
+        if self.domain.is_none() {
+            self.domain = match self.action_type {
+                ActionType::Navigate => self.value.as_deref().and_then(host_of),
+                _ => driver.current_url().ok().as_deref().and_then(host_of),
+            };
+        }
+        self
+    }
+
     /// Get the risk level for this action.
     pub fn risk_level(&self) -> RiskLevel {
         if self.sensitive {
@@ -326,6 +478,42 @@ pub struct AutomationHealthReport {
     pub issues: Vec<String>,
 }
 
+/// The combined effect of the per-domain [`AutomationRule`]s matching one
+/// action (see [`AutomationOrchestrator::rule_effect`]).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RuleEffect {
+    /// Indices into `AutomationSettings::rules` of the matching rules.
+    pub matched: Vec<usize>,
+    /// Risk override from the last matching rule that sets one.
+    pub risk: Option<RiskLevel>,
+    /// Confirmation override from the last matching rule that sets one.
+    pub require_confirmation: Option<bool>,
+    /// Whether any matching rule forbids typing.
+    pub forbid_typing: bool,
+}
+
+impl RuleEffect {
+    /// The risk of `action` once the override is applied. Sensitive actions
+    /// stay Critical whatever the rules say.
+    pub fn risk_for(&self, action: &WebAction) -> RiskLevel {
+        if action.sensitive {
+            RiskLevel::Critical
+        } else {
+            self.risk.unwrap_or_else(|| action.action_type.risk_level())
+        }
+    }
+}
+
+/// Whether `rule` covers `action_type` on `domain`.
+fn rule_matches(rule: &AutomationRule, domain: &str, action_type: &ActionType) -> bool {
+    glob_match(&rule.domain, domain)
+        && (rule.actions.is_empty()
+            || rule
+                .actions
+                .iter()
+                .any(|keyword| ActionType::from_keyword(keyword).as_ref() == Some(action_type)))
+}
+
 /// Rate limiter for automation actions.
 #[derive(Debug)]
 struct RateLimiter {
@@ -341,6 +529,11 @@ impl RateLimiter {
         }
     }
 
+    /// Whether the current window has no free slot (does not take one).
+    fn is_full(&self) -> bool {
+        self.count() as u32 >= self.max_per_minute
+    }
+
     fn check(&self) -> bool {
         let mut timestamps = self.timestamps.lock().recover();
         let now = Instant::now();
@@ -371,7 +564,11 @@ pub struct AutomationOrchestrator {
     ai: Arc<AiBridge>,
     settings: AutomationSettings,
     rate_limiter: RateLimiter,
+    /// Per-(rule, domain) limiters for rules with `max_actions_per_minute`.
+    rule_limiters: Mutex<HashMap<(usize, String), Arc<RateLimiter>>>,
     history: Mutex<Vec<ActionHistoryEntry>>,
+    /// Durable audit trail for executed actions, tagged with who drove them.
+    audit: Option<(Arc<AuditLog>, AuditActor)>,
 }
 
 impl std::fmt::Debug for AutomationOrchestrator {
@@ -390,10 +587,19 @@ impl AutomationOrchestrator {
             ai,
             settings,
             rate_limiter,
+            rule_limiters: Mutex::new(HashMap::new()),
             history: Mutex::new(Vec::new()),
+            audit: None,
         }
     }
 
+    /// Append every live action (and every declined confirmation) to `log`,
+    /// attributed to `actor`.
+    pub fn with_audit(mut self, log: Arc<AuditLog>, actor: AuditActor) -> Self {
+        self.audit = Some((log, actor));
+        self
+    }
+
     /// Get current settings.
     pub fn settings(&self) -> &AutomationSettings {
         &self.settings
@@ -417,6 +623,27 @@ impl AutomationOrchestrator {
             issues.push("No allowed domains configured - all domains blocked by default".into());
         }
 
+        for (index, rule) in self.settings.rules.iter().enumerate() {
+            if let Some(risk) = &rule.risk
+                && RiskLevel::from_keyword(risk).is_none()
+            {
+                issues.push(format!(
+                    "Automation rule {} ({}) has unknown risk '{risk}'",
+                    index + 1,
+                    rule.domain
+                ));
+            }
+            for keyword in &rule.actions {
+                if ActionType::from_keyword(keyword).is_none() {
+                    issues.push(format!(
+                        "Automation rule {} ({}) has unknown action '{keyword}'",
+                        index + 1,
+                        rule.domain
+                    ));
+                }
+            }
+        }
+
         let history = self.history.lock().recover();
 
         AutomationHealthReport {
@@ -432,6 +659,9 @@ impl AutomationOrchestrator {
     }
 
     /// Validate an action before execution.
+    ///
+    /// Validation has no side effects: rate limits are checked, not spent. A
+    /// slot is taken only when an action is executed.
     pub fn validate_action(&self, action: &WebAction) -> ValidationResult {
         let mut issues = Vec::new();
         let mut suggestions = Vec::new();
@@ -449,6 +679,17 @@ impl AutomationOrchestrator {
             suggestions.push("Add domain to allowed_domains in settings".into());
         }
 
+        // Script evaluation has its own switch and needs a checkable page
+        if action.action_type == ActionType::Evaluate {
+            if !self.settings.allow_script_evaluation {
+                issues.push("Script evaluation is disabled".into());
+                suggestions.push("Set automation.allow_script_evaluation = true".into());
+            }
+            if action.domain.is_none() {
+                issues.push("Script evaluation requires a page on an allowed domain".into());
+            }
+        }
+
         // Check for sensitive fields
         if action.sensitive {
             issues.push("Action targets a sensitive field".into());
@@ -464,16 +705,37 @@ impl AutomationOrchestrator {
             }
         }
 
+        // Apply per-domain rules
+        let effect = self.rule_effect(action);
+        if let Some(ref domain) = action.domain {
+            if effect.forbid_typing && action.action_type == ActionType::Type {
+                issues.push(format!(
+                    "Typing is forbidden on '{domain}' by an automation rule"
+                ));
+                suggestions.push("Enter text on this site manually".into());
+            }
+            for &index in &effect.matched {
+                if let Some(cap) = self.settings.rules[index].max_actions_per_minute
+                    && self.rule_limiter(index, domain, cap).is_full()
+                {
+                    issues.push(format!("Rate limit for '{domain}' exceeded ({cap}/min)"));
+                    suggestions.push("Wait before executing more actions on this site".into());
+                }
+            }
+        }
+
         // Check rate limiting
-        if !self.rate_limiter.check() {
+        if self.rate_limiter.is_full() {
             issues.push("Rate limit exceeded".into());
             suggestions.push("Wait before executing more actions".into());
         }
 
-        let risk_level = action.risk_level();
-        let requires_confirmation = self.settings.require_confirmation
-            || action.require_confirmation
-            || risk_level.requires_confirmation();
+        let risk_level = effect.risk_for(action);
+        // A risk override replaces the action's own (type-derived) flag.
+        let action_flag = action.require_confirmation && effect.risk.is_none();
+        let requires_confirmation = effect.require_confirmation.unwrap_or(
+            self.settings.require_confirmation || action_flag || risk_level.requires_confirmation(),
+        );
 
         ValidationResult {
             valid: issues.is_empty(),
@@ -495,6 +757,10 @@ impl AutomationOrchestrator {
         if !validation.valid {
             bail!("Action validation failed: {}", validation.issues.join("; "));
         }
+        let exhausted = self.take_rate_slots(action);
+        if !exhausted.is_empty() {
+            bail!("Action validation failed: {}", exhausted.join("; "));
+        }
 
         let started = Instant::now();
 
@@ -550,6 +816,26 @@ impl AutomationOrchestrator {
         &self,
         action: &WebAction,
         driver: &dyn BrowserDriver,
+    ) -> Result<ActionResult> {
+        let validation = self.validate_action(action);
+        let confirmation = if validation.requires_confirmation {
+            ConfirmationDecision::AutoApproved
+        } else {
+            ConfirmationDecision::NotRequired
+        };
+        self.execute_action_confirmed(action, driver, &validation, confirmation)
+    }
+
+    /// [`execute_action_with`](Self::execute_action_with) for callers that ran
+    /// their own confirmation gate on `validation` (from
+    /// [`validate_action`](Self::validate_action)); `confirmation` is recorded
+    /// in the audit log. Rate-limit slots are taken here, once per action.
+    pub fn execute_action_confirmed(
+        &self,
+        action: &WebAction,
+        driver: &dyn BrowserDriver,
+        validation: &ValidationResult,
+        confirmation: ConfirmationDecision,
     ) -> Result<ActionResult> {
         if !self.settings.enabled {
             bail!("Automation is disabled");
@@ -557,15 +843,15 @@ impl AutomationOrchestrator {
 
         let started = Instant::now();
 
-        let validation = self.validate_action(action);
-        let (success, data, error) = if !validation.valid {
+        let mut issues = validation.issues.clone();
+        if validation.valid {
+            issues = self.take_rate_slots(action);
+        }
+        let (success, data, error) = if !issues.is_empty() {
             (
                 false,
                 None,
-                Some(format!(
-                    "Action validation failed: {}",
-                    validation.issues.join("; ")
-                )),
+                Some(format!("Action validation failed: {}", issues.join("; "))),
             )
         } else {
             match self.dispatch_action(action, driver) {
@@ -594,10 +880,65 @@ impl AutomationOrchestrator {
             };
             self.history.lock().recover().push(entry);
         }
+        self.audit(action, validation.risk_level, confirmation, Some(&result));
 
         Ok(result)
     }
 
+    /// Record an action the user declined at the confirmation prompt. No
+    /// rate-limit slot is spent.
+    pub fn record_declined(&self, action: &WebAction, validation: &ValidationResult) {
+        self.audit(
+            action,
+            validation.risk_level,
+            ConfirmationDecision::Declined,
+            None,
+        );
+    }
+
+    /// Take one slot from the global limiter and from every matching rule's
+    /// per-domain limiter. Nothing is taken unless all have room; the issues
+    /// for full limiters are returned otherwise.
+    fn take_rate_slots(&self, action: &WebAction) -> Vec<String> {
+        let mut limiters = Vec::new();
+        let mut issues = Vec::new();
+        if let Some(domain) = action.domain.as_deref() {
+            for index in self.rule_effect(action).matched {
+                if let Some(cap) = self.settings.rules[index].max_actions_per_minute {
+                    let limiter = self.rule_limiter(index, domain, cap);
+                    if limiter.is_full() {
+                        issues.push(format!("Rate limit for '{domain}' exceeded ({cap}/min)"));
+                    }
+                    limiters.push(limiter);
+                }
+            }
+        }
+        if self.rate_limiter.is_full() {
+            issues.push("Rate limit exceeded".into());
+        }
+        if issues.is_empty() {
+            for limiter in &limiters {
+                limiter.check();
+            }
+            self.rate_limiter.check();
+        }
+        issues
+    }
+
+    fn audit(
+        &self,
+        action: &WebAction,
+        risk: RiskLevel,
+        confirmation: ConfirmationDecision,
+        result: Option<&ActionResult>,
+    ) {
+        if let Some((log, actor)) = &self.audit
+            && let Err(err) = log.append(*actor, action, risk, confirmation, result)
+        {
+            tracing::warn!(error = %err, path = %log.path().display(), "failed to append audit log entry");
+        }
+    }
+
     /// Dispatch a single validated action to the driver, returning optional result data.
     fn dispatch_action(
         &self,
@@ -638,6 +979,7 @@ impl AutomationOrchestrator {
             }
             ActionType::Extract => Ok(Some(driver.extract(selector()?)?)),
             ActionType::Screenshot => Ok(Some(driver.screenshot()?)),
+            ActionType::ExtractResponse => Ok(Some(driver.extract_response(value()?)?)),
             ActionType::Wait => {
                 let ms = action
                     .value
@@ -648,7 +990,63 @@ impl AutomationOrchestrator {
                 std::thread::sleep(Duration::from_millis(ms));
                 Ok(None)
             }
-            ActionType::Select | ActionType::Hover => {
+            ActionType::Select => {
+                driver.select_option(selector()?, value()?)?;
+                Ok(None)
+            }
+            ActionType::GoBack | ActionType::GoForward => {
+                if action.action_type == ActionType::GoForward {
+                    driver.go_forward()?;
+                } else {
+                    driver.go_back()?;
+                }
+                // History can lead anywhere; leave pages automation may not touch.
+                let url = driver.current_url()?;
+                if let Some(host) = page_host(&url)
+                    && !self.is_domain_allowed(&host)
+                {
+                    driver.navigate("about:blank")?;
+                    bail!(
+                        "history led to '{host}', where automation is not allowed; left the page"
+                    );
+                }
+                Ok(Some(format!("Now at {url}")))
+            }
+            ActionType::SwitchTab => {
+                let index: usize = value()?
+                    .trim()
+                    .parse()
+                    .context("switch_tab requires a tab index")?;
+                let tabs = driver.tabs()?;
+                let tab = tabs
+                    .iter()
+                    .find(|tab| tab.index == index)
+                    .with_context(|| format!("no tab at index {index}"))?;
+                if let Some(host) = page_host(&tab.url)
+                    && !self.is_domain_allowed(&host)
+                {
+                    bail!("tab {index} is on '{host}', where automation is not allowed");
+                }
+                driver.switch_tab(index)?;
+                Ok(Some(format!(
+                    "Switched to tab {index}: {}",
+                    driver.current_url()?
+                )))
+            }
+            ActionType::Evaluate => {
+                if !self.settings.allow_script_evaluation {
+                    bail!("script evaluation requires automation.allow_script_evaluation = true");
+                }
+                // Re-check the page itself, not just the domain captured earlier.
+                match page_host(&driver.current_url()?) {
+                    Some(host) if self.is_domain_allowed(&host) => {}
+                    Some(host) => bail!("automation is not allowed on '{host}'"),
+                    None => bail!("script evaluation requires a page on an allowed domain"),
+                }
+                let result = driver.evaluate(value()?)?;
+                Ok(Some(serde_json::to_string_pretty(&result)?))
+            }
+            ActionType::Hover => {
                 bail!("action type {:?} is not yet supported by the driver", action.action_type)
             }
         }
@@ -765,6 +1163,7 @@ impl AutomationOrchestrator {
                 selector: p.selector,
                 value: p.value,
                 sensitive: false,
+                secret: false,
                 require_confirmation: action_type.risk_level().requires_confirmation(),
                 description: p.description.clone(),
                 domain: None,
@@ -817,10 +1216,12 @@ impl AutomationOrchestrator {
              Actions so far:\n{history_block}\n\n\
              Current page observation:\n{observation}\n\n\
              Respond with EXACTLY ONE JSON object and nothing else:\n\
-             {{\"action_type\": \"navigate|click|type|scroll|extract|screenshot|wait|finish\", \
+             {{\"action_type\": \"navigate|click|type|scroll|extract|extract_response|screenshot|wait|finish\", \
              \"selector\": \"css selector or null\", \
              \"value\": \"text/url/ms or null\", \
              \"description\": \"short reason\"}}\n\
+             Use extract_response with a URL pattern in \"value\" to read the JSON \
+             body of an API call the page made.\n\
              Use action_type \"finish\" when the goal is achieved; put the final \
              answer in \"description\"."
         );
@@ -872,6 +1273,7 @@ impl AutomationOrchestrator {
             selector: clean(parsed.selector),
             value: clean(parsed.value),
             sensitive: false,
+            secret: false,
             require_confirmation: action_type.risk_level().requires_confirmation(),
             description: clean(parsed.description),
             domain: None,
@@ -880,8 +1282,74 @@ impl AutomationOrchestrator {
         Ok(NextAction::Act(action))
     }
 
+    /// Ask the model whether `goal` was actually achieved, given the agent's
+    /// final `answer` and the final page observation.
+    ///
+    /// Used as the self-critique pass when a goal carries no explicit success
+    /// criteria. An unparseable verdict counts as a failure rather than an error
+    /// so the agent can retry; transport errors still propagate.
+    pub fn judge_goal<H: AiHttp>(
+        &self,
+        goal: &str,
+        answer: &str,
+        observation: &str,
+        provider: Option<&str>,
+        http: &H,
+    ) -> Result<GoalJudgement> {
+        let prompt = format!(
+            "You are verifying the work of Archon's browser automation agent. Judge \
+             strictly whether the goal has actually been achieved, based only on the \
+             final page observation and the agent's final answer.\n\n\
+             Goal: {goal}\n\n\
+             Agent's final answer: {answer}\n\n\
+             Final page observation:\n{observation}\n\n\
+             Respond with EXACTLY ONE JSON object and nothing else:\n\
+             {{\"success\": true|false, \"reason\": \"one short sentence\"}}"
+        );
+
+        let ai_prompt = AiChatPrompt::text(&prompt);
+        let response = self
+            .ai
+            .chat_with_prompt(provider, ai_prompt, http)
+            .with_context(|| "Failed to verify goal")?;
+
+        Ok(parse_goal_judgement(&response.reply))
+    }
+
+    /// Combine the per-domain rules matching `action`'s domain and type.
+    /// Actions without a domain match no rules.
+    pub fn rule_effect(&self, action: &WebAction) -> RuleEffect {
+        let mut effect = RuleEffect::default();
+        let Some(domain) = action.domain.as_deref() else {
+            return effect;
+        };
+        for (index, rule) in self.settings.rules.iter().enumerate() {
+            if !rule_matches(rule, domain, &action.action_type) {
+                continue;
+            }
+            effect.matched.push(index);
+            if let Some(risk) = rule.risk.as_deref().and_then(RiskLevel::from_keyword) {
+                effect.risk = Some(risk);
+            }
+            if let Some(required) = rule.require_confirmation {
+                effect.require_confirmation = Some(required);
+            }
+            effect.forbid_typing |= rule.forbid_typing;
+        }
+        effect
+    }
+
+    fn rule_limiter(&self, index: usize, domain: &str, cap: u32) -> Arc<RateLimiter> {
+        let mut limiters = self.rule_limiters.lock().recover();
+        Arc::clone(
+            limiters
+                .entry((index, domain.to_lowercase()))
+                .or_insert_with(|| Arc::new(RateLimiter::new(cap))),
+        )
+    }
+
     /// Check if a domain is allowed.
-    fn is_domain_allowed(&self, domain: &str) -> bool {
+    pub fn is_domain_allowed(&self, domain: &str) -> bool {
         let domain_lower = domain.to_lowercase();
 
         // Check blocklist first
@@ -926,10 +1394,79 @@ impl AutomationOrchestrator {
             blocked_domains: self.settings.blocked_domains.clone(),
             max_actions_per_minute: self.settings.max_actions_per_minute,
             action_timeout_seconds: self.settings.action_timeout_seconds,
+            rules: self.settings.rules.clone(),
         }
     }
 }
 
+/// A model verdict on whether a goal was achieved (see
+/// [`AutomationOrchestrator::judge_goal`]).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GoalJudgement {
+    /// Whether the model judged the goal achieved.
+    pub success: bool,
+    /// The model's one-line justification.
+    pub reason: String,
+}
+
+/// Parse a `{"success": bool, "reason": "..."}` verifier reply.
+fn parse_goal_judgement(response: &str) -> GoalJudgement {
+    #[derive(Deserialize)]
+    struct ParsedJudgement {
+        success: bool,
+        #[serde(default)]
+        reason: Option<String>,
+    }
+
+    let parsed = match (response.find('{'), response.rfind('}')) {
+        (Some(s), Some(e)) if e > s => {
+            serde_json::from_str::<ParsedJudgement>(&response[s..=e]).ok()
+        }
+        _ => None,
+    };
+    match parsed {
+        Some(parsed) => GoalJudgement {
+            success: parsed.success,
+            reason: parsed
+                .reason
+                .map(|r| r.trim().to_string())
+                .filter(|r| !r.is_empty())
+                .unwrap_or_else(|| "no reason given".into()),
+        },
+        None => GoalJudgement {
+            success: false,
+            reason: "verifier reply was not a JSON verdict".into(),
+        },
+    }
+}
+
+/// Case-insensitive glob match where `*` matches any run of characters
+/// (including none) and `?` matches exactly one character.
+pub fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.to_lowercase().chars().collect();
+    let text: Vec<char> = text.to_lowercase().chars().collect();
+    let (mut p, mut t) = (0usize, 0usize);
+    let mut star: Option<(usize, usize)> = None;
+
+    while t < text.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == text[t]) {
+            p += 1;
+            t += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            star = Some((p, t));
+            p += 1;
+        } else if let Some((star_p, star_t)) = star {
+            // Backtrack: let the last `*` swallow one more character.
+            p = star_p + 1;
+            t = star_t + 1;
+            star = Some((star_p, star_t + 1));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
 /// Current automation policy (serializable).
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AutomationPolicy {
@@ -940,6 +1477,55 @@ pub struct AutomationPolicy {
     pub blocked_domains: Vec<String>,
     pub max_actions_per_minute: u32,
     pub action_timeout_seconds: u32,
+    pub rules: Vec<AutomationRule>,
+}
+
+/// Extract the lower-cased host of a URL (ignoring scheme, credentials and
+/// port), also accepting bare `host[:port][/path]` targets. Returns `None`
+/// for host-less URLs such as `about:blank`, `data:` or `file:///`. Used to
+/// populate [`WebAction::domain`] for the allow/block guard and per-domain
+/// rules.
+pub fn host_of(url: &str) -> Option<String> {
+    let url = url.trim();
+    if url.contains("://") {
+        return url::Url::parse(url)
+            .ok()?
+            .host_str()
+            .filter(|host| !host.is_empty())
+            .map(str::to_lowercase);
+    }
+    // `scheme:opaque` (about:blank, data:…, javascript:…) has no host, unlike
+    // `host:port`, whose suffix starts with a digit.
+    if let Some((_, rest)) = url.split_once(':')
+        && !rest.starts_with(|c: char| c.is_ascii_digit())
+    {
+        return None;
+    }
+    let host = url
+        .split('/')
+        .next()
+        .unwrap_or("")
+        .split('@')
+        .next_back()
+        .unwrap_or("")
+        .split(':')
+        .next()
+        .unwrap_or("");
+    if host.is_empty() {
+        None
+    } else {
+        Some(host.to_lowercase())
+    }
+}
+
+/// Host of an http(s) page URL; `None` for about:, data:, file: and other
+/// host-less pages (unlike [`host_of`], which accepts bare host names).
+pub fn page_host(url: &str) -> Option<String> {
+    let url = url::Url::parse(url).ok()?;
+    if !matches!(url.scheme(), "http" | "https") {
+        return None;
+    }
+    url.host_str().map(str::to_lowercase)
 }
 
 #[cfg(test)]
@@ -962,6 +1548,15 @@ mod tests {
         let nav = WebAction::navigate("https://example.com");
         assert_eq!(nav.action_type, ActionType::Navigate);
         assert_eq!(nav.value.as_deref(), Some("https://example.com"));
+
+        let response = WebAction::extract_response("*/api/items*");
+        assert_eq!(response.action_type, ActionType::ExtractResponse);
+        assert_eq!(response.value.as_deref(), Some("*/api/items*"));
+        assert_eq!(response.risk_level(), RiskLevel::Low);
+        assert_eq!(
+            ActionType::from_keyword("extract_response"),
+            Some(ActionType::ExtractResponse)
+        );
     }
 
     #[test]
@@ -1029,6 +1624,128 @@ mod tests {
         assert!(result.valid, "issues: {:?}", result.issues);
     }
 
+    fn rule(domain: &str) -> AutomationRule {
+        AutomationRule {
+            domain: domain.into(),
+            ..AutomationRule::default()
+        }
+    }
+
+    #[test]
+    fn domain_rules_raise_risk_force_confirmation_and_forbid_typing() {
+        let settings = AutomationSettings {
+            rules: vec![AutomationRule {
+                risk: Some("critical".into()),
+                require_confirmation: Some(true),
+                forbid_typing: true,
+                ..rule("*.bank.test")
+            }],
+            ..enabled_settings()
+        };
+        let orch = orchestrator(settings);
+
+        let extract = WebAction::extract("#balance").with_domain("www.bank.test");
+        let result = orch.validate_action(&extract);
+        assert!(result.valid, "issues: {:?}", result.issues);
+        assert_eq!(result.risk_level, RiskLevel::Critical);
+        assert!(result.requires_confirmation);
+
+        let typing = WebAction::type_text("#amount", "100").with_domain("www.bank.test");
+        let result = orch.validate_action(&typing);
+        assert!(!result.valid);
+        assert!(
+            result
+                .issues
+                .iter()
+                .any(|i| i.contains("Typing is forbidden"))
+        );
+
+        // Other domains and domain-less actions are unaffected.
+        let elsewhere = WebAction::type_text("#q", "hi").with_domain("search.test");
+        assert!(orch.validate_action(&elsewhere).valid);
+        assert!(
+            orch.rule_effect(&WebAction::type_text("#q", "hi"))
+                .matched
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn domain_rules_can_waive_confirmation_and_lower_risk() {
+        let settings = AutomationSettings {
+            require_confirmation: true,
+            rules: vec![
+                AutomationRule {
+                    risk: Some("low".into()),
+                    ..rule("dash.internal")
+                },
+                AutomationRule {
+                    actions: vec!["click".into(), "navigate".into()],
+                    require_confirmation: Some(false),
+                    ..rule("dash.internal")
+                },
+            ],
+            ..enabled_settings()
+        };
+        let orch = orchestrator(settings);
+
+        let click = WebAction::click("#refresh").with_domain("dash.internal");
+        let result = orch.validate_action(&click);
+        assert_eq!(result.risk_level, RiskLevel::Low);
+        assert!(!result.requires_confirmation);
+        assert_eq!(orch.rule_effect(&click).matched, vec![0, 1]);
+
+        // `type` only matches the first rule: low risk, but the global switch
+        // still asks for confirmation.
+        let typing = WebAction::type_text("#note", "x").with_domain("dash.internal");
+        let result = orch.validate_action(&typing);
+        assert_eq!(result.risk_level, RiskLevel::Low);
+        assert!(result.requires_confirmation);
+    }
+
+    #[test]
+    fn domain_rules_cap_rate_per_domain() {
+        let settings = AutomationSettings {
+            rules: vec![AutomationRule {
+                max_actions_per_minute: Some(2),
+                ..rule("*.slow.test")
+            }],
+            ..enabled_settings()
+        };
+        let orch = orchestrator(settings);
+        let action = || WebAction::screenshot().with_domain("a.slow.test");
+
+        // Validation alone never spends a slot.
+        for _ in 0..3 {
+            assert!(orch.validate_action(&action()).valid);
+        }
+        assert!(orch.take_rate_slots(&action()).is_empty());
+        assert!(orch.take_rate_slots(&action()).is_empty());
+        let third = orch.take_rate_slots(&action());
+        assert!(third.iter().any(|i| i.contains("a.slow.test")));
+        assert!(!orch.validate_action(&action()).valid);
+        // Each domain has its own budget.
+        assert!(
+            orch.validate_action(&WebAction::screenshot().with_domain("b.slow.test"))
+                .valid
+        );
+    }
+
+    #[test]
+    fn health_report_flags_unknown_rule_values() {
+        let settings = AutomationSettings {
+            rules: vec![AutomationRule {
+                actions: vec!["teleport".into()],
+                risk: Some("extreme".into()),
+                ..rule("*")
+            }],
+            ..enabled_settings()
+        };
+        let report = orchestrator(settings).health_report();
+        assert!(report.issues.iter().any(|i| i.contains("'teleport'")));
+        assert!(report.issues.iter().any(|i| i.contains("'extreme'")));
+    }
+
     #[test]
     fn domain_blocklist_takes_precedence_over_allowlist() {
         let settings = AutomationSettings {
@@ -1084,6 +1801,36 @@ mod tests {
         assert_eq!(steps[1].action.action_type, ActionType::Click);
     }
 
+    #[test]
+    fn parse_goal_judgement_reads_verdict_and_fails_closed() {
+        let verdict = parse_goal_judgement(r#"ok {"success": true, "reason": " found it "}"#);
+        assert!(verdict.success);
+        assert_eq!(verdict.reason, "found it");
+
+        let garbage = parse_goal_judgement("looks good to me");
+        assert!(!garbage.success);
+        assert!(garbage.reason.contains("not a JSON verdict"));
+    }
+
+    #[test]
+    fn glob_match_handles_wildcards() {
+        assert!(glob_match("https://example.com/*", "https://example.com/a/b"));
+        assert!(glob_match("*.bank.test", "WWW.Bank.test"));
+        assert!(glob_match("https://?.test/", "https://a.test/"));
+        assert!(!glob_match("*.bank.test", "bank.test.evil"));
+        assert!(!glob_match("https://example.com/", "https://example.com/x"));
+        assert!(glob_match("*", ""));
+    }
+
+    #[test]
+    fn host_of_ignores_host_less_urls() {
+        assert_eq!(host_of("about:blank"), None);
+        assert_eq!(host_of("data:text/html,hi"), None);
+        assert_eq!(host_of("file:///tmp/x.html"), None);
+        assert_eq!(host_of("localhost:8080/x").as_deref(), Some("localhost"));
+        assert_eq!(host_of("Example.com/a").as_deref(), Some("example.com"));
+    }
+
     #[test]
     fn parse_plan_response_errors_without_array() {
         let orch = orchestrator(enabled_settings());
@@ -1,38 +1,64 @@
 use std::{
-    collections::HashMap,
+    collections::{BTreeMap, HashMap, hash_map::Entry},
     convert::Infallible,
-    net::SocketAddr,
+    net::{IpAddr, SocketAddr},
     path::PathBuf,
-    sync::{Arc, Mutex, atomic::AtomicBool},
+    sync::{
+        Arc, Mutex, PoisonError,
+        atomic::{AtomicBool, Ordering},
+    },
     thread,
     time::{Duration, Instant, SystemTime},
 };
 
 use anyhow::{Context, Result, bail};
-use archon::agent::{AgentOutcome, BrowserAgent};
+use archon::agent::{
+    AgentOutcome, BrowserAgent, CheckpointStore, ConfirmPolicy, DEFAULT_VERIFY_RETRIES,
+    SuccessCriterion,
+};
 use archon::ai::{
     AiAttachment, AiAttachmentKind, AiBridge, AiChatHistoryEntry, AiChatPrompt, AiChatResponse,
     AiChatRole, AiHttp, BlockingAiHttp, PageContext, PageSegment,
 };
+use archon::audit::{AuditActor, AuditLog};
 use archon::automation::AutomationOrchestrator;
 use archon::browser::CdpBrowser;
+use archon::conduit::ConduitState;
 use archon::config::{
     AiHostSettings, AiProviderConfig, AiProviderKind, AutomationSettings, LaunchSettings,
     default_config_path,
 };
 use archon::crypto::CryptoStack;
 use archon::host::AiHost;
-use archon::mcp::{McpOrchestrator, McpToolCallResponse};
+use archon::mcp::{McpConnectorStatus, McpOrchestrator, McpToolCallResponse};
+use archon::mcp_http::{
+    HttpRefusal, McpHttpHub, PROTOCOL_VERSION_HEADER, SESSION_HEADER, constant_time_eq,
+    origin_allowed,
+};
+use archon::mcp_server::BrowserToolbox;
 use archon::n8n::{N8nOrchestrator, N8nTriggerResult, N8nWebhookResult};
+use archon::network::NetworkOptions;
+use archon::recipe::{Recipe, RecipeVars};
+use archon::research::{
+    ResearchDepth, ResearchEvent, ResearchOrchestrator, ResearchQuery, ResearchSession,
+    ResearchSessionStore,
+};
+use archon::scheduler::{
+    RecipeRunner, RunHistory, RunTrigger, Scheduler, SysfsConditions, TriggerRefusal, WebhookSink,
+};
 use archon::search::ArcOrchestrator;
+use archon::summarize::SummarizeOrchestrator;
 use archon::telemetry::ServiceTelemetry;
-use archon::transcript::{TranscriptSource, TranscriptStore};
+use archon::transcript::{
+    TranscriptSearchQuery, TranscriptSource, TranscriptStore, TranscriptSummary, parse_search_date,
+};
+use archon::transcript_crypto::TranscriptCipher;
 use axum::{
     Json, Router,
-    extract::{Path, Query, State},
+    extract::{ConnectInfo, Path, Query, State},
     http::{HeaderMap, HeaderValue, StatusCode, header},
-    response::IntoResponse,
     response::sse::{Event, KeepAlive, Sse},
+    response::{IntoResponse, Response},
     routing::{get, post},
 };
 use base64::Engine as _;
@@ -46,7 +72,7 @@ use tokio::net::TcpListener;
 use tokio::sync::mpsc;
 use tokio::{io, task};
 use tokio_stream::StreamExt;
-use tokio_stream::wrappers::ReceiverStream;
+use tokio_stream::wrappers::{ReceiverStream, UnboundedReceiverStream};
 use tracing::{error, info, warn};
 use uuid::Uuid;
 
@@ -84,6 +110,7 @@ struct AppState {
     mcp: Arc<McpOrchestrator>,
     n8n: Arc<N8nOrchestrator>,
     arc: Arc<ArcOrchestrator>,
+    research: Arc<ResearchOrchestrator>,
     transcripts: Arc<TranscriptStore>,
     crypto: Arc<CryptoStack>,
     provider_health: Arc<Mutex<HashMap<String, ProviderHealthSnapshot>>>,
@@ -91,6 +118,57 @@ struct AppState {
     /// Resolved profile directory used as the DevToolsActivePort fallback when
     /// attaching the agent to the user's running browser.
     profile_dir: Option<PathBuf>,
+    /// Pause/cancel flags for in-flight `/agent/run` streams, keyed by run ID.
+    agent_runs: Arc<Mutex<HashMap<Uuid, AgentRunControl>>>,
+    /// Hash-chained record of actions executed by `/agent/run`.
+    audit: Option<Arc<AuditLog>>,
+    /// Recipe scheduler, when `scheduler.enabled` is set.
+    scheduler: Option<Arc<Scheduler>>,
+    /// MCP Streamable HTTP sessions, when `mcp.server.http_enabled` is set.
+    mcp_http: Option<Arc<McpHttpHub>>,
+    /// Conduit directory managed through `/conduit/*`, when `conduit.enabled`
+    /// is set.
+    conduit: Option<Arc<ConduitDir>>,
+}
+
+/// The Conduit directory plus a lock serialising state-file updates.
+struct ConduitDir {
+    path: PathBuf,
+    state_lock: Mutex<()>,
+    /// Credentials required to change scripts over HTTP.
+    token: ConduitToken,
+    /// `conduit.allowed_origins`.
+    allowed_origins: Vec<String>,
+}
+
+/// How requests that change Conduit scripts authenticate.
+enum ConduitToken {
+    /// No `conduit.token_env`: loopback clients only.
+    Loopback,
+    /// The bearer token read from `conduit.token_env`.
+    Bearer(String),
+    /// `conduit.token_env` names an unset variable; every change is refused.
+    Unset(String),
+}
+
+impl ConduitToken {
+    fn from_env(token_env: Option<&str>) -> Self {
+        match token_env {
+            None => Self::Loopback,
+            Some(var) => match std::env::var(var) {
+                Ok(token) if !token.trim().is_empty() => Self::Bearer(token.trim().to_string()),
+                _ => Self::Unset(var.to_string()),
+            },
+        }
+    }
+}
+
+/// Control flags shared between an `/agent/run` worker and the
+/// `/agent/run/:id/{pause,resume,cancel}` endpoints.
+#[derive(Clone, Default)]
+struct AgentRunControl {
+    pause: Arc<AtomicBool>,
+    cancel: Arc<AtomicBool>,
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -120,6 +198,16 @@ struct ChatRequest {
     conversation_id: Option<String>,
     #[serde(default)]
     page_context: Option<PageContextPayload>,
+    /// Offer the tools listed in each connector's `chat_tools` to the model.
+    #[serde(default)]
+    tools: bool,
+    /// `<connector>__<tool>` names of state-changing tools the user approved
+    /// for this request.
+    #[serde(default)]
+    approved_tools: Vec<String>,
+    /// Switch semantic recall on or off for the conversation (`ai.recall`).
+    #[serde(default)]
+    recall: Option<bool>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -260,6 +348,20 @@ impl ApiError {
             message: message.into(),
         }
     }
+
+    fn not_found(message: impl Into<String>) -> Self {
+        Self {
+            status: StatusCode::NOT_FOUND,
+            message: message.into(),
+        }
+    }
+
+    fn forbidden(message: impl Into<String>) -> Self {
+        Self {
+            status: StatusCode::FORBIDDEN,
+            message: message.into(),
+        }
+    }
 }
 
 impl IntoResponse for ApiError {
@@ -318,6 +420,7 @@ impl ChatRequest {
             .with_conversation(conversation_id)
             .with_history(history)
             .with_page_context(page_context)
+            .with_recall(self.recall)
             .with_source(TranscriptSource::Sidebar))
     }
 }
@@ -340,6 +443,8 @@ fn apply_overrides(settings: &mut AiHostSettings, args: &Args) {
 }
 
 const HEALTH_PROBE_MAX_ATTEMPTS: u32 = 3;
+/// Tool calls a `/chat` request with `tools: true` may make before replying.
+const MAX_CHAT_TOOL_ROUNDS: usize = 5;
 const HEALTH_PROBE_RETRY_DELAY: Duration = Duration::from_secs(2);
 const HEALTH_PROBE_INTERVAL: Duration = Duration::from_secs(60);
 
@@ -626,7 +731,17 @@ async fn main() -> Result<()> {
             return Err(err);
         }
     };
-    let transcripts = match TranscriptStore::new(transcript_root) {
+    let transcripts = match TranscriptStore::new(transcript_root.clone()).and_then(|store| {
+        Ok(
+            match TranscriptCipher::from_settings(
+                &settings.transcripts_encryption,
+                &transcript_root,
+            )? {
+                Some(cipher) => store.with_encryption(cipher),
+                None => store,
+            },
+        )
+    }) {
         Ok(store) => Arc::new(store),
         Err(err) => {
             telemetry.record_error(&err);
@@ -794,17 +909,78 @@ async fn main() -> Result<()> {
         .resolve_profile_root()
         .ok()
         .map(|root| root.join("default"));
+    let audit = AuditLog::from_settings(&settings).map(Arc::new);
+    let runner = RecipeRunEnv {
+        automation: settings.automation.clone(),
+        bridge: Arc::clone(&bridge),
+        transcript_root: transcripts.root().to_path_buf(),
+        cipher: transcripts.cipher().cloned(),
+        profile_dir: profile_dir.clone(),
+        audit: audit.clone(),
+    };
+    let mcp_http = match start_mcp_http(&settings, runner.clone(), Arc::clone(&transcripts)) {
+        Ok(hub) => hub,
+        Err(err) => {
+            telemetry.record_error(&err);
+            return Err(err);
+        }
+    };
+    let scheduler = match start_scheduler(&settings, runner, Arc::clone(&n8n)) {
+        Ok(scheduler) => scheduler,
+        Err(err) => {
+            telemetry.record_error(&err);
+            return Err(err);
+        }
+    };
+    let conduit = if settings.conduit.enabled {
+        let path = settings.conduit.resolve_dir()?;
+        if let Err(err) = archon::conduit::seed_example(&path) {
+            warn!(error = %err, "failed to prepare Conduit directory");
+        }
+        let token = ConduitToken::from_env(settings.conduit.token_env.as_deref());
+        if let ConduitToken::Unset(var) = &token {
+            warn!(
+                token_env = %var,
+                "conduit.token_env is unset; Conduit script changes over HTTP are refused"
+            );
+        }
+        Some(Arc::new(ConduitDir {
+            path,
+            state_lock: Mutex::new(()),
+            token,
+            allowed_origins: settings.conduit.allowed_origins.clone(),
+        }))
+    } else {
+        None
+    };
+    let mut research = ResearchOrchestrator::from_settings(
+        settings.research.clone(),
+        Arc::clone(&bridge),
+        Arc::clone(&arc),
+    );
+    match ResearchSessionStore::for_transcripts(&transcripts) {
+        Ok(store) => research = research.with_sessions(store),
+        Err(err) => warn!(error = %err, "research sessions are unavailable"),
+    }
+    let research = Arc::new(research);
     let state = AppState {
         bridge,
         mcp,
         n8n,
         arc,
+        research,
         transcripts,
         crypto,
         provider_health,
         automation: settings.automation.clone(),
         profile_dir,
+        agent_runs: Arc::new(Mutex::new(HashMap::new())),
+        audit,
+        scheduler,
+        mcp_http,
+        conduit,
     };
+    let mcp_path = settings.mcp.server.path.clone();
     let router = Router::new()
         .route("/health", get(health_handler))
         .route("/metrics", get(metrics_handler))
@@ -814,12 +990,33 @@ async fn main() -> Result<()> {
         .route("/vision", post(vision_handler))
         .route("/chat/stream", post(chat_stream_handler))
         .route("/agent/run", post(agent_run_handler))
+        .route("/agent/run/:id/pause", post(agent_pause_handler))
+        .route("/agent/run/:id/resume", post(agent_resume_handler))
+        .route("/agent/run/:id/cancel", post(agent_cancel_handler))
+        .route("/recipe/run", post(recipe_run_handler))
+        .route("/scheduler/jobs", get(scheduler_jobs_handler))
+        .route("/scheduler/history", get(scheduler_history_handler))
+        .route(
+            "/scheduler/jobs/:name/trigger",
+            post(scheduler_trigger_handler),
+        )
+        .route("/conduit/scripts", get(conduit_scripts_handler))
+        .route(
+            "/conduit/scripts/*key",
+            get(conduit_script_handler).put(conduit_script_update_handler),
+        )
+        .route("/conduit/toggle", post(conduit_toggle_handler))
         .route("/connectors", get(connectors_handler))
         .route("/tool-call", post(tool_call_handler))
         .route("/resolve", get(resolve_handler))
         .route("/transcripts", get(transcripts_handler))
+        .route("/transcripts/search", get(transcript_search_handler))
         .route("/transcripts/:id/json", get(transcript_json_handler))
         .route("/transcripts/:id/history", get(transcript_history_handler))
+        .route("/transcripts/:id/related", get(transcript_related_handler))
+        .route("/transcripts/:id/recall", post(transcript_recall_handler))
+        .route("/transcripts/:id/tree", get(transcript_tree_handler))
+        .route("/transcripts/:id/branch", post(transcript_branch_handler))
         .route(
             "/transcripts/:id/markdown",
             get(transcript_markdown_handler),
@@ -835,6 +1032,24 @@ async fn main() -> Result<()> {
         .route("/arc/search", post(arc_search_handler))
         .route("/arc/ask", post(arc_ask_handler))
         .route("/arc/ask/stream", post(arc_ask_stream_handler))
+        .route("/research", post(research_handler))
+        .route("/research/stream", post(research_stream_handler))
+        .route("/research/sessions", get(research_sessions_handler))
+        .route(
+            "/research/sessions/:id",
+            get(research_session_handler).delete(research_session_delete_handler),
+        )
+        .route(
+            "/research/sessions/:id/markdown",
+            get(research_session_markdown_handler),
+        )
+        // MCP Streamable HTTP transport
+        .route(
+            &mcp_path,
+            post(mcp_post_handler)
+                .get(mcp_get_handler)
+                .delete(mcp_delete_handler),
+        )
         .with_state(state);
 
     let listener = match TcpListener::bind(listen_addr)
@@ -849,8 +1064,12 @@ async fn main() -> Result<()> {
     };
     info!(addr = %listen_addr, "starting archon-host service");
 
-    let result = axum::serve(listener, router)
-        .await
+    // Peer addresses let the Conduit endpoints tell loopback clients apart.
+    let result = axum::serve(
+        listener,
+        router.into_make_service_with_connect_info::<SocketAddr>(),
+    )
+    .await
         .context("AI host server terminated unexpectedly");
 
     match &result {
@@ -917,21 +1136,15 @@ async fn run_stdio(
 
         match message_type.as_deref() {
             Some("connectors") => {
-                let report = mcp.health_report();
+                // MCP connectors may spawn a server or open a session to list tools.
+                let orchestrator = mcp.clone();
+                let report = task::spawn_blocking(move || orchestrator.health_report())
+                    .await
+                    .map_err(|err| anyhow::anyhow!(err).context("worker task panicked"))?;
                 let connectors = report
                     .connectors
                     .into_iter()
-                    .map(|connector| {
-                        json!({
-                            "name": connector.name,
-                            "kind": connector.kind,
-                            "endpoint": connector.endpoint,
-                            "enabled": connector.enabled,
-                            "healthy": connector.healthy,
-                            "has_api_key": connector.has_api_key,
-                            "issues": connector.issues,
-                        })
-                    })
+                    .map(connector_status_json)
                     .collect::<Vec<_>>();
                 let docker = report.docker.map(|docker| {
                     json!({
@@ -1225,6 +1438,69 @@ async fn run_stdio(
 
                 write_native_message(&mut stdout, &response).await?;
             }
+            Some("transcript_branch") => {
+                let id = message
+                    .get("id")
+                    .and_then(|value| value.as_str())
+                    .and_then(|value| Uuid::parse_str(value).ok());
+                let message_index = message
+                    .get("message_index")
+                    .and_then(|value| value.as_u64())
+                    .map(|value| value as usize);
+                let (Some(uuid), Some(message_index)) = (id, message_index) else {
+                    let response = NativeResponse {
+                        success: false,
+                        kind: Some("transcript_branch".into()),
+                        data: None,
+                        tool: None,
+                        connectors: None,
+                        providers: None,
+                        transcripts: None,
+                        metrics: None,
+                        arc_result: None,
+                        error: Some("transcript id and message_index are required".into()),
+                    };
+                    write_native_message(&mut stdout, &response).await?;
+                    continue;
+                };
+
+                let store = transcripts.clone();
+                let branch_result = task::spawn_blocking(move || store.branch(uuid, message_index))
+                    .await
+                    .map_err(|err| anyhow::anyhow!(err).context("worker task panicked"))?;
+
+                let response = match branch_result {
+                    Ok(record) => NativeResponse {
+                        success: true,
+                        kind: Some("transcript_branch".into()),
+                        data: None,
+                        tool: None,
+                        connectors: None,
+                        providers: None,
+                        transcripts: Some(json!({ "transcript": record.summary })),
+                        metrics: None,
+                        arc_result: None,
+                        error: None,
+                    },
+                    Err(err) => {
+                        error!(error = %err, transcript = %uuid, "failed to branch transcript");
+                        NativeResponse {
+                            success: false,
+                            kind: Some("transcript_branch".into()),
+                            data: None,
+                            tool: None,
+                            connectors: None,
+                            providers: None,
+                            transcripts: None,
+                            metrics: None,
+                            arc_result: None,
+                            error: Some(format!("failed to branch transcript: {err}")),
+                        }
+                    }
+                };
+
+                write_native_message(&mut stdout, &response).await?;
+            }
             Some("transcript_markdown") => {
                 let id_value = message.get("id").and_then(|value| value.as_str());
                 let Some(id_str) = id_value else {
@@ -1585,9 +1861,15 @@ where
     Ok(())
 }
 
-async fn health_handler(State(state): State<AppState>) -> Json<Value> {
+async fn health_handler(State(state): State<AppState>) -> Result<Json<Value>, ApiError> {
     let providers_report = state.bridge.health_report();
-    let mcp_report = state.mcp.health_report();
+    let mcp = Arc::clone(&state.mcp);
+    let mcp_report = task::spawn_blocking(move || mcp.health_report())
+        .await
+        .map_err(|err| {
+            error!(?err, "blocking task panicked");
+            ApiError::internal("worker task failed")
+        })?;
     let metrics = state.bridge.provider_metrics();
     let provider_health: Vec<ProviderHealthSnapshot> = match state.provider_health.lock() {
         Ok(guard) => guard.values().cloned().collect(),
@@ -1622,21 +1904,13 @@ async fn health_handler(State(state): State<AppState>) -> Json<Value> {
                 "compose_present": docker.compose_present,
                 "issues": docker.issues,
             })),
-            "connectors": mcp_report.connectors.into_iter().map(|connector| json!({
-                "name": connector.name,
-                "kind": connector.kind,
-                "endpoint": connector.endpoint,
-                "enabled": connector.enabled,
-                "healthy": connector.healthy,
-                "has_api_key": connector.has_api_key,
-                "issues": connector.issues,
-            })).collect::<Vec<_>>(),
+            "connectors": mcp_report.connectors.into_iter().map(connector_status_json).collect::<Vec<_>>(),
         }
         ,
         "metrics": metrics,
         "provider_health": provider_health,
     });
-    Json(payload)
+    Ok(Json(payload))
 }
 
 async fn metrics_handler(State(state): State<AppState>) -> Json<Value> {
@@ -1705,15 +1979,28 @@ fn prepare_chat_prompt(
 
 async fn chat_handler(
     State(state): State<AppState>,
-    Json(payload): Json<ChatRequest>,
+    Json(mut payload): Json<ChatRequest>,
 ) -> Result<Json<AiChatResponse>, ApiError> {
     let bridge = Arc::clone(&state.bridge);
+    let use_tools = payload.tools;
+    let approved_tools = std::mem::take(&mut payload.approved_tools);
     let (provider, prompt) = prepare_chat_prompt(&bridge, payload)?;
     let chat_bridge = Arc::clone(&bridge);
+    let mcp = Arc::clone(&state.mcp);
 
     let response = task::spawn_blocking(move || {
         let client = archon::ai::BlockingAiHttp::default();
-        chat_bridge.chat_with_prompt(provider.as_deref(), prompt, &client)
+        if use_tools {
+            chat_bridge.chat_with_tools(
+                provider.as_deref(),
+                prompt,
+                &client,
+                &mcp.chat_tools(approved_tools),
+                MAX_CHAT_TOOL_ROUNDS,
+            )
+        } else {
+            chat_bridge.chat_with_prompt(provider.as_deref(), prompt, &client)
+        }
     })
     .await
     .map_err(|err| {
@@ -2008,6 +2295,7 @@ async fn chat_stream_handler(
 
 #[derive(Debug, Deserialize)]
 struct AgentRunRequest {
+    #[serde(default)]
     goal: String,
     #[serde(default)]
     start_url: Option<String>,
@@ -2019,6 +2307,15 @@ struct AgentRunRequest {
     attach: bool,
     #[serde(default)]
     provider: Option<String>,
+    /// Run ID to continue from its last checkpoint instead of starting `goal`.
+    #[serde(default)]
+    resume: Option<Uuid>,
+    /// Verify the goal before reporting success (implied by `success_criteria`).
+    #[serde(default)]
+    verify: bool,
+    /// Explicit success criteria checked when the agent finishes.
+    #[serde(default)]
+    success_criteria: Vec<SuccessCriterion>,
 }
 
 async fn agent_run_handler(
@@ -2026,7 +2323,7 @@ async fn agent_run_handler(
     Json(payload): Json<AgentRunRequest>,
 ) -> Result<Sse<impl Stream<Item = Result<Event, Infallible>>>, ApiError> {
     let goal = payload.goal.trim().to_string();
-    if goal.is_empty() {
+    if goal.is_empty() && payload.resume.is_none() {
         return Err(ApiError::bad_request("agent goal must not be empty"));
     }
 
@@ -2039,33 +2336,83 @@ async fn agent_run_handler(
         ));
     }
 
+    let checkpoints = CheckpointStore::for_transcripts(&state.transcripts);
+    let checkpoint = match payload.resume {
+        Some(id) => Some(
+            checkpoints
+                .load(id)
+                .map_err(|err| ApiError::bad_request(format!("{err:#}")))?,
+        ),
+        None => None,
+    };
+
     let automation = state.automation.clone();
     let bridge = Arc::clone(&state.bridge);
     let transcript_root = state.transcripts.root().to_path_buf();
+    let cipher = state.transcripts.cipher().cloned();
     let profile_dir = state.profile_dir.clone();
+    let audit = state.audit.clone();
 
     let max_steps = payload.max_steps.unwrap_or(8).clamp(1, 50);
     let execute = payload.execute;
     let attach = payload.attach;
     let provider = payload.provider.clone();
     let start_url = payload.start_url.clone();
+    let verification = (payload.verify || !payload.success_criteria.is_empty())
+        .then(|| payload.success_criteria.clone());
+
+    let run_id = checkpoint.as_ref().map_or_else(Uuid::new_v4, |cp| cp.id);
+    let control = AgentRunControl::default();
+    // Check and register under one lock so two resumes of the same checkpoint
+    // cannot both drive the run.
+    match state
+        .agent_runs
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .entry(run_id)
+    {
+        Entry::Occupied(_) => {
+            return Err(ApiError::bad_request(format!(
+                "agent run {run_id} is still active; use /agent/run/{run_id}/resume to unpause it"
+            )));
+        }
+        Entry::Vacant(slot) => {
+            slot.insert(control.clone());
+        }
+    }
+    let registry = Arc::clone(&state.agent_runs);
+    let cancel_on_disconnect = Arc::clone(&control.cancel);
 
     let (tx, rx) = mpsc::channel::<Result<Event, String>>(64);
 
     tokio::spawn(async move {
         let started_event = Event::default().event("status").data(
-            json!({ "stage": "started", "execute": execute, "attach": attach }).to_string(),
+            json!({
+                "stage": if checkpoint.is_some() { "resumed" } else { "started" },
+                "run_id": run_id,
+                "execute": execute,
+                "attach": attach,
+            })
+            .to_string(),
         );
         if tx.send(Ok(started_event)).await.is_err() {
+            registry
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .remove(&run_id);
             return;
         }
 
         // Steps are streamed live via the per-step observer using blocking_send,
         // mirroring the delta bridge in chat_stream_handler.
         let step_tx = tx.clone();
-        let agent_result = task::spawn_blocking(move || -> Result<AgentOutcome> {
-            let orchestrator =
-                Arc::new(AutomationOrchestrator::from_settings(automation.clone(), bridge));
+        let mut worker = task::spawn_blocking(move || -> Result<AgentOutcome> {
+            let mut orchestrator =
+                AutomationOrchestrator::from_settings(automation.clone(), bridge);
+            if let Some(log) = audit {
+                orchestrator = orchestrator.with_audit(log, AuditActor::Host);
+            }
+            let orchestrator = Arc::new(orchestrator);
 
             let agent_transcript_dir = transcript_root.join("agents");
             let artifacts_dir = transcript_root.join("agent-artifacts");
@@ -2085,33 +2432,62 @@ async fn agent_run_handler(
             } else {
                 CdpBrowser::launch(false, artifacts_dir)
                     .context("failed to launch the agent browser (is Chromium installed?)")?
-            };
+            }
+            .with_encryption(cipher.clone())
+            .with_network(&NetworkOptions::from_settings(&automation))?;
 
-            let agent = BrowserAgent::new(
+            let mut agent = BrowserAgent::new(
                 orchestrator,
                 max_steps,
                 execute,
                 false,
                 Some(agent_transcript_dir),
             )
+            .with_checkpoint_store(checkpoints)
+            .with_pause_flag(Arc::clone(&control.pause))
+            .with_run_id(run_id)
+            .with_encryption(cipher)
             .with_step_observer(Box::new(move |step| {
                 let data = serde_json::to_string(step).unwrap_or_else(|_| "{}".to_string());
                 let event = Event::default().event("step").data(data);
                 let _ = step_tx.blocking_send(Ok(event));
             }));
+            if let Some(criteria) = verification {
+                agent = agent.with_verification(criteria, DEFAULT_VERIFY_RETRIES);
+            }
 
             let http = BlockingAiHttp::default();
-            let cancel = AtomicBool::new(false);
-            agent.run(
-                &goal,
-                start_url.as_deref(),
-                &driver,
-                provider.as_deref(),
-                &http,
-                &cancel,
-            )
-        })
-        .await;
+            match checkpoint {
+                Some(checkpoint) => agent.resume(
+                    checkpoint,
+                    &driver,
+                    provider.as_deref(),
+                    &http,
+                    &control.cancel,
+                ),
+                None => agent.run(
+                    &goal,
+                    start_url.as_deref(),
+                    &driver,
+                    provider.as_deref(),
+                    &http,
+                    &control.cancel,
+                ),
+            }
+        });
+        // A client that disconnects cannot unpause or watch the run, so cancel
+        // it rather than leave it parked until restart.
+        let agent_result = tokio::select! {
+            result = &mut worker => result,
+            _ = tx.closed() => {
+                cancel_on_disconnect.store(true, Ordering::Relaxed);
+                worker.await
+            }
+        };
+        registry
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .remove(&run_id);
 
         match agent_result {
             Ok(Ok(outcome)) => {
@@ -2129,7 +2505,7 @@ async fn agent_run_handler(
 
                 let finished_event = Event::default()
                     .event("status")
-                    .data(json!({ "stage": "finished" }).to_string());
+                    .data(json!({ "stage": "finished", "run_id": run_id }).to_string());
                 let _ = tx.send(Ok(finished_event)).await;
             }
             Ok(Err(err)) => {
@@ -2157,22 +2533,60 @@ async fn agent_run_handler(
     ))
 }
 
-async fn connectors_handler(State(state): State<AppState>) -> Json<Value> {
-    let report = state.mcp.health_report();
+/// Look up the control flags of an in-flight agent run.
+fn agent_run_control(state: &AppState, id: &str) -> Result<(Uuid, AgentRunControl), ApiError> {
+    let uuid = Uuid::parse_str(id)
+        .map_err(|_| ApiError::bad_request(format!("invalid agent run id '{id}'")))?;
+    let control = state
+        .agent_runs
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .get(&uuid)
+        .cloned()
+        .ok_or_else(|| ApiError::bad_request(format!("no active agent run '{id}'")))?;
+    Ok((uuid, control))
+}
+
+async fn agent_pause_handler(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<Json<Value>, ApiError> {
+    let (uuid, control) = agent_run_control(&state, &id)?;
+    control.pause.store(true, Ordering::Relaxed);
+    Ok(Json(json!({ "run_id": uuid, "paused": true })))
+}
+
+async fn agent_resume_handler(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<Json<Value>, ApiError> {
+    let (uuid, control) = agent_run_control(&state, &id)?;
+    control.pause.store(false, Ordering::Relaxed);
+    Ok(Json(json!({ "run_id": uuid, "paused": false })))
+}
+
+async fn agent_cancel_handler(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<Json<Value>, ApiError> {
+    let (uuid, control) = agent_run_control(&state, &id)?;
+    control.cancel.store(true, Ordering::Relaxed);
+    Ok(Json(json!({ "run_id": uuid, "cancelled": true })))
+}
+
+async fn connectors_handler(State(state): State<AppState>) -> Result<Json<Value>, ApiError> {
+    // MCP connectors may spawn a server or open a session to list tools.
+    let mcp = Arc::clone(&state.mcp);
+    let report = task::spawn_blocking(move || mcp.health_report())
+        .await
+        .map_err(|err| {
+            error!(?err, "blocking task panicked");
+            ApiError::internal("worker task failed")
+        })?;
     let connectors = report
         .connectors
         .into_iter()
-        .map(|connector| {
-            json!({
-                "name": connector.name,
-                "kind": connector.kind,
-                "endpoint": connector.endpoint,
-                "enabled": connector.enabled,
-                "healthy": connector.healthy,
-                "has_api_key": connector.has_api_key,
-                "issues": connector.issues,
-            })
-        })
+        .map(connector_status_json)
         .collect::<Vec<_>>();
 
     let docker = report.docker.map(|docker| {
@@ -2185,64 +2599,934 @@ async fn connectors_handler(State(state): State<AppState>) -> Json<Value> {
         })
     });
 
-    Json(json!({
+    Ok(Json(json!({
         "connectors": connectors,
         "docker": docker,
-    }))
+    })))
 }
 
-async fn transcripts_handler(State(state): State<AppState>) -> Result<Json<Value>, ApiError> {
-    let transcripts = state.transcripts.list().map_err(|err| {
-        error!(error = %err, "failed to list transcripts");
-        ApiError::internal("failed to list transcripts")
-    })?;
+/// JSON shape of a connector in `/connectors`, `/health` and the native
+/// `connectors` message.
+fn connector_status_json(connector: McpConnectorStatus) -> Value {
+    json!({
+        "name": connector.name,
+        "kind": connector.kind,
+        "transport": connector.transport.to_string(),
+        "endpoint": connector.endpoint,
+        "enabled": connector.enabled,
+        "healthy": connector.healthy,
+        "has_api_key": connector.has_api_key,
+        "issues": connector.issues,
+        "tools": connector.tools,
+    })
+}
 
-    Ok(Json(json!({ "transcripts": transcripts })))
+#[derive(Debug, Deserialize)]
+struct RecipeRunRequest {
+    /// Bare recipe name under `automation/recipes/`; paths are refused.
+    recipe: String,
+    /// Parameter values, as with `archon --automate ... --param key=value`.
+    #[serde(default)]
+    params: BTreeMap<String, String>,
+    #[serde(default)]
+    max_steps: Option<usize>,
+    #[serde(default)]
+    execute: bool,
+    #[serde(default)]
+    attach: bool,
+    #[serde(default)]
+    provider: Option<String>,
 }
 
-async fn transcript_history_handler(
+/// Run a recipe to completion and return its [`AgentOutcome`].
+async fn recipe_run_handler(
     State(state): State<AppState>,
-    Path(id): Path<String>,
-) -> Result<Json<Value>, ApiError> {
-    let uuid = Uuid::parse_str(&id)
-        .map_err(|_| ApiError::bad_request(format!("invalid transcript id '{id}'")))?;
+    Json(payload): Json<RecipeRunRequest>,
+) -> Result<Json<AgentOutcome>, ApiError> {
+    if payload.execute && !state.automation.enabled {
+        return Err(ApiError::bad_request(
+            "execute requires automation.enabled = true in config; \
+             omit execute for a preview (dry-run)",
+        ));
+    }
+    let recipe = archon::recipe::load_named_recipe(&archon::recipe::recipes_dir(), &payload.recipe)
+        .map_err(|err| ApiError::bad_request(format!("{err:#}")))?;
+    let vars = recipe
+        .resolve_params(&payload.params)
+        .map_err(|err| ApiError::bad_request(format!("{err:#}")))?;
+
+    let env = RecipeRunEnv::from_state(&state);
+    let options = RecipeRunOptions {
+        max_steps: payload.max_steps.unwrap_or(8).clamp(1, 50),
+        execute: payload.execute,
+        attach: payload.attach,
+        provider: payload.provider,
+    };
+    let result = task::spawn_blocking(move || env.run(&recipe, &vars, &options, AuditActor::Host))
+        .await
+        .map_err(|err| ApiError::internal(format!("worker task failed: {err}")))?
+        .map_err(|err| ApiError::internal(format!("{err:#}")))?;
 
-    let history = state.bridge.conversation_history(uuid).map_err(|err| {
-        error!(error = %err, transcript = %id, "failed to load transcript history");
-        ApiError::internal("failed to load transcript history")
-    })?;
+    Ok(Json(result))
+}
 
-    Ok(Json(json!({ "history": history })))
+/// How to run a recipe: shared by `/recipe/run` and scheduled jobs.
+struct RecipeRunOptions {
+    max_steps: usize,
+    execute: bool,
+    attach: bool,
+    provider: Option<String>,
 }
 
-async fn transcript_json_handler(
-    State(state): State<AppState>,
-    Path(id): Path<String>,
-) -> Result<Json<Value>, ApiError> {
-    let uuid = Uuid::parse_str(&id)
-        .map_err(|_| ApiError::bad_request(format!("invalid transcript id '{id}'")))?;
+/// The parts of [`AppState`] a blocking recipe run needs.
+#[derive(Clone)]
+struct RecipeRunEnv {
+    automation: AutomationSettings,
+    bridge: Arc<AiBridge>,
+    transcript_root: PathBuf,
+    /// Transcript cipher, so agent outcomes and artifacts follow
+    /// `transcripts_encryption`.
+    cipher: Option<TranscriptCipher>,
+    profile_dir: Option<PathBuf>,
+    audit: Option<Arc<AuditLog>>,
+}
 
-    let raw = state.transcripts.load_json(uuid).map_err(|err| {
-        error!(error = %err, transcript = %id, "failed to load transcript JSON");
-        ApiError::internal("failed to load transcript JSON")
-    })?;
+impl RecipeRunEnv {
+    fn from_state(state: &AppState) -> Self {
+        Self {
+            automation: state.automation.clone(),
+            bridge: Arc::clone(&state.bridge),
+            transcript_root: state.transcripts.root().to_path_buf(),
+            cipher: state.transcripts.cipher().cloned(),
+            profile_dir: state.profile_dir.clone(),
+            audit: state.audit.clone(),
+        }
+    }
 
-    let value: Value = serde_json::from_str(&raw).map_err(|err| {
-        error!(error = %err, transcript = %id, "failed to parse transcript JSON");
-        ApiError::internal("failed to parse transcript JSON")
-    })?;
+    fn agents_dir(&self) -> PathBuf {
+        self.transcript_root.join("agents")
+    }
 
-    Ok(Json(value))
+    /// Run `recipe` to completion on a launched or attached browser and
+    /// persist its transcript. Blocking; call from a worker thread. Nobody can
+    /// answer a prompt here, so actions needing confirmation are declined
+    /// unless `automation.allow_unattended_high_risk` approves them.
+    fn run(
+        &self,
+        recipe: &Recipe,
+        vars: &RecipeVars,
+        options: &RecipeRunOptions,
+        actor: AuditActor,
+    ) -> Result<AgentOutcome> {
+        let automation = &self.automation;
+        let mut orchestrator =
+            AutomationOrchestrator::from_settings(automation.clone(), Arc::clone(&self.bridge));
+        if let Some(log) = &self.audit {
+            orchestrator = orchestrator.with_audit(Arc::clone(log), actor);
+        }
+        let artifacts_dir = self.transcript_root.join("agent-artifacts");
+        let driver = if options.attach {
+            let port = automation.remote_debug_port;
+            let ws_url = CdpBrowser::devtools_ws_url(port, self.profile_dir.as_deref())
+                .with_context(|| {
+                    format!("could not find a debuggable Archon browser on port {port}")
+                })?;
+            CdpBrowser::connect(&ws_url, artifacts_dir)
+                .context("failed to attach to the running Archon browser")?
+        } else {
+            CdpBrowser::launch(false, artifacts_dir)
+                .context("failed to launch the agent browser (is Chromium installed?)")?
+        }
+        .with_encryption(self.cipher.clone())
+        .with_network(&NetworkOptions::from_settings(automation))?;
+
+        let cancel = AtomicBool::new(false);
+        let outcome = archon::recipe::run_recipe(
+            recipe,
+            vars,
+            Arc::new(orchestrator),
+            options.max_steps,
+            options.execute,
+            ConfirmPolicy::unattended(automation.allow_unattended_high_risk),
+            &driver,
+            options.provider.as_deref(),
+            &BlockingAiHttp::default(),
+            &cancel,
+        )?;
+        archon::agent::persist_outcome(&self.agents_dir(), &outcome, self.cipher.as_ref());
+        Ok(outcome)
+    }
 }
 
-async fn transcript_markdown_handler(
-    State(state): State<AppState>,
-    Path(id): Path<String>,
-) -> Result<impl IntoResponse, ApiError> {
-    let uuid = Uuid::parse_str(&id)
-        .map_err(|_| ApiError::bad_request(format!("invalid transcript id '{id}'")))?;
+/// Runs scheduled jobs through [`RecipeRunEnv::run`].
+struct HostRecipeRunner {
+    env: RecipeRunEnv,
+}
 
-    let body = state.transcripts.load_markdown(uuid).map_err(|err| {
+impl RecipeRunner for HostRecipeRunner {
+    fn run(
+        &self,
+        job: &archon::config::ScheduledRecipe,
+    ) -> Result<(AgentOutcome, Option<PathBuf>)> {
+        if job.execute && !self.env.automation.enabled {
+            bail!(
+                "job '{}' sets execute but automation.enabled is false",
+                job.name
+            );
+        }
+        let recipe = archon::recipe::load_recipe(&job.recipe)?;
+        let vars = recipe.resolve_params(&job.params)?;
+        let options = RecipeRunOptions {
+            max_steps: job.max_steps.unwrap_or(8).clamp(1, 50),
+            execute: job.execute,
+            attach: job.attach,
+            provider: None,
+        };
+        let outcome = self
+            .env
+            .run(&recipe, &vars, &options, AuditActor::Scheduler)?;
+        let transcript = self
+            .env
+            .agents_dir()
+            .join(format!("agent-{}.md", outcome.id));
+        Ok((outcome, Some(transcript)))
+    }
+}
+
+/// Build the recipe scheduler and start its tick loop, if enabled.
+fn start_scheduler(
+    settings: &LaunchSettings,
+    env: RecipeRunEnv,
+    n8n: Arc<N8nOrchestrator>,
+) -> Result<Option<Arc<Scheduler>>> {
+    let config = &settings.scheduler;
+    if !config.enabled {
+        return Ok(None);
+    }
+    let history = RunHistory::new(settings.resolve_scheduler_history()?, config.history_limit);
+    let scheduler = Arc::new(
+        Scheduler::new(
+            config.clone(),
+            Arc::new(HostRecipeRunner { env }),
+            Arc::new(WebhookSink::new(n8n)),
+            Arc::new(SysfsConditions::default()),
+            Arc::new(history),
+        )
+        .context("invalid scheduler configuration")?,
+    );
+    info!(
+        jobs = config.jobs.len(),
+        history = %scheduler.history().path().display(),
+        "started recipe scheduler"
+    );
+
+    let ticker = Arc::clone(&scheduler);
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(15));
+        loop {
+            interval.tick().await;
+            // Gates read sysfs and starting a run appends to the history file.
+            let scheduler = Arc::clone(&ticker);
+            let now = chrono::Local::now().naive_local();
+            if let Err(err) = task::spawn_blocking(move || scheduler.tick(now)).await {
+                error!(?err, "scheduler tick panicked");
+            }
+        }
+    });
+    Ok(Some(scheduler))
+}
+
+/// Build the shared MCP toolbox and session hub, if enabled.
+fn start_mcp_http(
+    settings: &LaunchSettings,
+    env: RecipeRunEnv,
+    transcripts: Arc<TranscriptStore>,
+) -> Result<Option<Arc<McpHttpHub>>> {
+    let config = settings.mcp.server.clone();
+    if !config.http_enabled {
+        return Ok(None);
+    }
+    if !config.path.starts_with('/') {
+        bail!(
+            "mcp.server.path must start with '/' (got '{}')",
+            config.path
+        );
+    }
+    let Some(token) = std::env::var(&config.token_env)
+        .ok()
+        .filter(|token| !token.trim().is_empty())
+    else {
+        warn!(
+            token_env = %config.token_env,
+            "MCP HTTP transport enabled but its token variable is unset; not serving {}",
+            config.path
+        );
+        return Ok(None);
+    };
+    let attach = config.attach;
+    let summarizer =
+        SummarizeOrchestrator::from_settings(settings.summarize.clone(), Arc::clone(&env.bridge));
+    let recipe_dir = archon::recipe::recipes_dir();
+    let hub = McpHttpHub::start(config, token, move || {
+        let mut orchestrator =
+            AutomationOrchestrator::from_settings(env.automation.clone(), Arc::clone(&env.bridge));
+        if let Some(log) = &env.audit {
+            orchestrator = orchestrator.with_audit(Arc::clone(log), AuditActor::Mcp);
+        }
+        let artifacts_dir = env.transcript_root.join("agent-artifacts");
+        let network = NetworkOptions::from_settings(&env.automation);
+        let port = env.automation.remote_debug_port;
+        let profile_dir = env.profile_dir.clone();
+        let cipher = env.cipher.clone();
+        let driver_factory: archon::mcp_server::DriverFactory = Box::new(move || {
+            let driver: Box<dyn archon::browser::BrowserDriver> = if attach {
+                let ws_url = CdpBrowser::devtools_ws_url(port, profile_dir.as_deref())
+                    .with_context(|| {
+                        format!("could not find a debuggable Archon browser on port {port}")
+                    })?;
+                Box::new(
+                    CdpBrowser::connect(&ws_url, artifacts_dir.clone())?
+                        .with_encryption(cipher.clone())
+                        .with_network(&network)?,
+                )
+            } else {
+                Box::new(
+                    CdpBrowser::launch(false, artifacts_dir.clone())?
+                        .with_encryption(cipher.clone())
+                        .with_network(&network)?,
+                )
+            };
+            Ok(driver)
+        });
+        BrowserToolbox::new(
+            Arc::new(orchestrator),
+            Some(env.agents_dir()),
+            None,
+            driver_factory,
+        )
+        .with_transcripts(transcripts)
+        .with_recipe_dir(recipe_dir)
+        .with_summarizer(summarizer)
+    })?;
+    Ok(Some(hub))
+}
+
+fn mcp_refusal(refusal: HttpRefusal) -> Response {
+    let status =
+        StatusCode::from_u16(refusal.status_code()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+    let mut response = (status, Json(json!({ "error": refusal.message() }))).into_response();
+    if refusal == HttpRefusal::Unauthorized {
+        response
+            .headers_mut()
+            .insert(header::WWW_AUTHENTICATE, HeaderValue::from_static("Bearer"));
+    }
+    response
+}
+
+/// Authorize an MCP request and return the hub and its session ID.
+fn mcp_request(
+    state: &AppState,
+    headers: &HeaderMap,
+) -> Result<(Arc<McpHttpHub>, Option<String>), HttpRefusal> {
+    let hub = state.mcp_http.clone().ok_or_else(|| {
+        HttpRefusal::Unavailable(
+            "MCP HTTP transport is disabled; set mcp.server.http_enabled = true".into(),
+        )
+    })?;
+    let header_str = |name: &str| headers.get(name).and_then(|value| value.to_str().ok());
+    hub.authorize(
+        header_str(header::ORIGIN.as_str()),
+        header_str(header::AUTHORIZATION.as_str()),
+    )
+    .and_then(|()| hub.check_protocol_version(header_str(PROTOCOL_VERSION_HEADER)))?;
+    let session = header_str(SESSION_HEADER).map(str::to_string);
+    Ok((hub, session))
+}
+
+fn accepts_event_stream(headers: &HeaderMap) -> bool {
+    headers
+        .get(header::ACCEPT)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|accept| accept.contains("text/event-stream"))
+}
+
+fn mcp_sse(
+    frames: tokio::sync::mpsc::UnboundedReceiver<String>,
+) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    let stream = UnboundedReceiverStream::new(frames)
+        .map(|frame| Ok(Event::default().event("message").data(frame)));
+    Sse::new(stream).keep_alive(
+        KeepAlive::new()
+            .interval(Duration::from_secs(15))
+            .text("keep-alive"),
+    )
+}
+
+/// POST: one JSON-RPC message. `tools/call` requests from clients accepting
+/// `text/event-stream` get an SSE response carrying progress notifications
+/// before the result; everything else is answered with JSON (or 202 for
+/// notifications).
+async fn mcp_post_handler(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    body: String,
+) -> Response {
+    let (hub, session) = match mcp_request(&state, &headers) {
+        Ok(request) => request,
+        Err(refusal) => return mcp_refusal(refusal),
+    };
+
+    let streamed_id = accepts_event_stream(&headers)
+        .then(|| serde_json::from_str::<Value>(&body).ok())
+        .flatten()
+        .filter(|message| message.get("method").and_then(Value::as_str) == Some("tools/call"))
+        .and_then(|message| message.get("id").cloned());
+    if let Some(request_id) = streamed_id {
+        let Some(session) = session else {
+            return mcp_refusal(HttpRefusal::BadRequest(format!(
+                "missing {SESSION_HEADER} header"
+            )));
+        };
+        if let Err(refusal) = hub.touch(&session) {
+            return mcp_refusal(refusal);
+        }
+        let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+        tokio::spawn(async move {
+            match hub.handle(Some(&session), &body, Some(tx.clone())).await {
+                Ok(reply) => {
+                    if let Some(response) = reply.response {
+                        let _ = tx.send(response);
+                    }
+                }
+                Err(refusal) => {
+                    warn!(error = %refusal.message(), "MCP tool call failed");
+                    // The 200 and SSE headers are already sent; answer in-stream.
+                    let _ = tx.send(refusal.to_jsonrpc(&request_id));
+                }
+            }
+        });
+        return mcp_sse(rx).into_response();
+    }
+
+    match hub.handle(session.as_deref(), &body, None).await {
+        Ok(reply) => {
+            let mut response = match reply.response {
+                Some(frame) => {
+                    ([(header::CONTENT_TYPE, "application/json")], frame).into_response()
+                }
+                None => StatusCode::ACCEPTED.into_response(),
+            };
+            if let Some(id) = reply.session_id
+                && let Ok(value) = HeaderValue::from_str(&id)
+            {
+                response.headers_mut().insert(SESSION_HEADER, value);
+            }
+            response
+        }
+        Err(refusal) => mcp_refusal(refusal),
+    }
+}
+
+/// GET: the session's stream of server-initiated notifications.
+async fn mcp_get_handler(State(state): State<AppState>, headers: HeaderMap) -> Response {
+    let (hub, session) = match mcp_request(&state, &headers) {
+        Ok(request) => request,
+        Err(refusal) => return mcp_refusal(refusal),
+    };
+    if !accepts_event_stream(&headers) {
+        return StatusCode::METHOD_NOT_ALLOWED.into_response();
+    }
+    let Some(session) = session else {
+        return mcp_refusal(HttpRefusal::BadRequest(format!(
+            "missing {SESSION_HEADER} header"
+        )));
+    };
+    let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+    if let Err(refusal) = hub.listen(&session, tx) {
+        return mcp_refusal(refusal);
+    }
+    mcp_sse(rx).into_response()
+}
+
+/// DELETE: terminate the session.
+async fn mcp_delete_handler(State(state): State<AppState>, headers: HeaderMap) -> Response {
+    let (hub, session) = match mcp_request(&state, &headers) {
+        Ok(request) => request,
+        Err(refusal) => return mcp_refusal(refusal),
+    };
+    let Some(session) = session else {
+        return mcp_refusal(HttpRefusal::BadRequest(format!(
+            "missing {SESSION_HEADER} header"
+        )));
+    };
+    match hub.close(&session).await {
+        Ok(()) => StatusCode::NO_CONTENT.into_response(),
+        Err(refusal) => mcp_refusal(refusal),
+    }
+}
+
+fn scheduler_state(state: &AppState) -> Result<&Arc<Scheduler>, ApiError> {
+    state.scheduler.as_ref().ok_or_else(|| {
+        ApiError::not_found("scheduler is disabled; set scheduler.enabled = true in config")
+    })
+}
+
+fn conduit_state(state: &AppState) -> Result<&Arc<ConduitDir>, ApiError> {
+    state.conduit.as_ref().ok_or_else(|| {
+        ApiError::not_found("Conduit is disabled; set conduit.enabled = true in config")
+    })
+}
+
+/// Gate the endpoints that change Conduit scripts. A browser `Origin` must be
+/// local or in `conduit.allowed_origins`; then the request must carry the
+/// `conduit.token_env` bearer token, or, without one configured, come from a
+/// loopback address.
+fn conduit_authorize(
+    conduit: &ConduitDir,
+    peer: IpAddr,
+    headers: &HeaderMap,
+) -> Result<(), ApiError> {
+    let header_str = |name: header::HeaderName| headers.get(name).and_then(|v| v.to_str().ok());
+    if let Some(origin) = header_str(header::ORIGIN)
+        && !origin_allowed(origin, &conduit.allowed_origins)
+    {
+        return Err(ApiError::forbidden(format!(
+            "origin {origin} is not allowed to change Conduit scripts"
+        )));
+    }
+    match &conduit.token {
+        ConduitToken::Loopback if peer.to_canonical().is_loopback() => Ok(()),
+        ConduitToken::Loopback => Err(ApiError::forbidden(
+            "Conduit scripts can only be changed from this machine unless conduit.token_env is set",
+        )),
+        ConduitToken::Unset(var) => Err(ApiError::forbidden(format!(
+            "conduit.token_env names {var}, which is unset; set it to change Conduit scripts"
+        ))),
+        ConduitToken::Bearer(token) => {
+            let presented = header_str(header::AUTHORIZATION)
+                .and_then(|value| value.strip_prefix("Bearer "));
+            if presented.is_some_and(|presented| constant_time_eq(presented.trim(), token)) {
+                Ok(())
+            } else {
+                Err(ApiError {
+                    status: StatusCode::UNAUTHORIZED,
+                    message: "missing or invalid Conduit bearer token".to_string(),
+                })
+            }
+        }
+    }
+}
+
+async fn conduit_scripts_handler(State(state): State<AppState>) -> Result<Json<Value>, ApiError> {
+    let conduit = conduit_state(&state)?;
+    let scripts = archon::conduit::list_scripts(&conduit.path).map_err(|err| {
+        error!(error = %err, "failed to list Conduit scripts");
+        ApiError::internal(format!("failed to list Conduit scripts: {err}"))
+    })?;
+    let toggles = ConduitState::load(&conduit.path)
+        .map_err(|err| ApiError::internal(format!("failed to read Conduit state: {err}")))?;
+    Ok(Json(json!({
+        "dir": conduit.path,
+        "scripts": scripts,
+        "disabled_sites": toggles.disabled_sites,
+    })))
+}
+
+async fn conduit_script_handler(
+    State(state): State<AppState>,
+    Path(key): Path<String>,
+) -> Result<Json<Value>, ApiError> {
+    let conduit = conduit_state(&state)?;
+    let source = archon::conduit::read_script(&conduit.path, &key)
+        .map_err(|err| ApiError::not_found(err.to_string()))?;
+    Ok(Json(json!({ "key": key, "source": source })))
+}
+
+#[derive(Debug, Deserialize)]
+struct ConduitScriptUpdate {
+    source: String,
+}
+
+async fn conduit_script_update_handler(
+    State(state): State<AppState>,
+    ConnectInfo(peer): ConnectInfo<SocketAddr>,
+    Path(key): Path<String>,
+    headers: HeaderMap,
+    Json(payload): Json<ConduitScriptUpdate>,
+) -> Result<Json<Value>, ApiError> {
+    let conduit = Arc::clone(conduit_state(&state)?);
+    conduit_authorize(&conduit, peer.ip(), &headers)?;
+    let script = key.clone();
+    task::spawn_blocking(move || {
+        archon::conduit::write_script(&conduit.path, &script, &payload.source)
+    })
+    .await
+    .map_err(|err| ApiError::internal(format!("worker task failed: {err}")))?
+    .map_err(|err| ApiError::bad_request(err.to_string()))?;
+    info!(script = %key, "updated Conduit script");
+    Ok(Json(json!({ "key": key, "saved": true })))
+}
+
+/// Enable or disable one script (`key`) or every script on a site (`site`).
+#[derive(Debug, Deserialize)]
+struct ConduitToggleRequest {
+    #[serde(default)]
+    key: Option<String>,
+    #[serde(default)]
+    site: Option<String>,
+    enabled: bool,
+}
+
+async fn conduit_toggle_handler(
+    State(state): State<AppState>,
+    ConnectInfo(peer): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
+    Json(payload): Json<ConduitToggleRequest>,
+) -> Result<Json<Value>, ApiError> {
+    let conduit = Arc::clone(conduit_state(&state)?);
+    conduit_authorize(&conduit, peer.ip(), &headers)?;
+    task::spawn_blocking(move || toggle_conduit(&conduit, &payload))
+        .await
+        .map_err(|err| ApiError::internal(format!("worker task failed: {err}")))?
+        .map(Json)
+}
+
+fn toggle_conduit(conduit: &ConduitDir, payload: &ConduitToggleRequest) -> Result<Value, ApiError> {
+    let _guard = conduit
+        .state_lock
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner);
+    let mut toggles = ConduitState::load(&conduit.path)
+        .map_err(|err| ApiError::internal(format!("failed to read Conduit state: {err}")))?;
+    match (&payload.key, &payload.site) {
+        (Some(key), None) => {
+            archon::conduit::read_script(&conduit.path, key)
+                .map_err(|err| ApiError::not_found(err.to_string()))?;
+            toggles.set_enabled(key, payload.enabled);
+        }
+        (None, Some(site)) => toggles
+            .set_site_enabled(site, payload.enabled)
+            .map_err(|err| ApiError::bad_request(err.to_string()))?,
+        _ => return Err(ApiError::bad_request("set exactly one of `key` or `site`")),
+    }
+    toggles.save(&conduit.path).map_err(|err| {
+        error!(error = %err, "failed to save Conduit state");
+        ApiError::internal("failed to save Conduit state")
+    })?;
+    Ok(json!({
+        "disabled": toggles.disabled,
+        "disabled_sites": toggles.disabled_sites,
+    }))
+}
+
+async fn scheduler_jobs_handler(State(state): State<AppState>) -> Result<Json<Value>, ApiError> {
+    let scheduler = scheduler_state(&state)?;
+    let jobs = scheduler.jobs(chrono::Local::now().naive_local());
+    Ok(Json(json!({ "jobs": jobs })))
+}
+
+#[derive(Debug, Deserialize)]
+struct SchedulerHistoryQuery {
+    #[serde(default)]
+    job: Option<String>,
+    #[serde(default)]
+    limit: Option<usize>,
+}
+
+async fn scheduler_history_handler(
+    State(state): State<AppState>,
+    Query(query): Query<SchedulerHistoryQuery>,
+) -> Result<Json<Value>, ApiError> {
+    let scheduler = Arc::clone(scheduler_state(&state)?);
+    let limit = query.limit.unwrap_or(50).clamp(1, 500);
+    let runs =
+        task::spawn_blocking(move || scheduler.history().recent(query.job.as_deref(), limit))
+            .await
+            .map_err(|err| ApiError::internal(format!("worker task failed: {err}")))?
+            .map_err(|err| {
+                error!(error = %err, "failed to read scheduler history");
+                ApiError::internal("failed to read scheduler history")
+            })?;
+    Ok(Json(json!({ "runs": runs })))
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct SchedulerTriggerRequest {
+    /// Parameter values overriding the job's configured `params`.
+    #[serde(default)]
+    params: BTreeMap<String, String>,
+}
+
+/// Start a webhook-enabled job. The body is optional.
+async fn scheduler_trigger_handler(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+    headers: HeaderMap,
+    body: Option<Json<SchedulerTriggerRequest>>,
+) -> Result<Json<Value>, ApiError> {
+    let scheduler = Arc::clone(scheduler_state(&state)?);
+    let token = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "));
+    let params = body.map(|Json(body)| body.params).unwrap_or_default();
+    scheduler
+        .check_webhook(&name, token, &params)
+        .map_err(|refusal| match refusal {
+            TriggerRefusal::UnknownJob => {
+                ApiError::not_found(format!("unknown scheduler job '{name}'"))
+            }
+            TriggerRefusal::Forbidden(message) => ApiError::forbidden(message),
+        })?;
+    let record =
+        task::spawn_blocking(move || scheduler.trigger(&name, RunTrigger::Webhook, params))
+            .await
+            .map_err(|err| ApiError::internal(format!("worker task failed: {err}")))?
+            .map_err(|err| ApiError::internal(format!("{err:#}")))?;
+    Ok(Json(json!({ "run": record })))
+}
+
+async fn transcripts_handler(State(state): State<AppState>) -> Result<Json<Value>, ApiError> {
+    let transcripts = state.transcripts.list().map_err(|err| {
+        error!(error = %err, "failed to list transcripts");
+        ApiError::internal("failed to list transcripts")
+    })?;
+
+    Ok(Json(json!({ "transcripts": transcripts })))
+}
+
+#[derive(Debug, Deserialize)]
+struct TranscriptSearchParams {
+    q: String,
+    #[serde(default)]
+    provider: Option<String>,
+    #[serde(default)]
+    source: Option<String>,
+    #[serde(default)]
+    since: Option<String>,
+    #[serde(default)]
+    until: Option<String>,
+    #[serde(default)]
+    attachments: Option<bool>,
+    #[serde(default)]
+    limit: Option<usize>,
+}
+
+async fn transcript_search_handler(
+    State(state): State<AppState>,
+    Query(params): Query<TranscriptSearchParams>,
+) -> Result<Json<Value>, ApiError> {
+    if !params.q.chars().any(char::is_alphanumeric) {
+        return Err(ApiError::bad_request(
+            "q must contain at least one search term",
+        ));
+    }
+    let mut query = TranscriptSearchQuery::new(params.q.as_str())
+        .with_limit(params.limit.unwrap_or(20).clamp(1, 200));
+    if let Some(provider) = params.provider.filter(|value| !value.trim().is_empty()) {
+        query = query.with_provider(provider);
+    }
+    if let Some(source) = params.source.as_deref() {
+        let source = source
+            .parse::<TranscriptSource>()
+            .map_err(|_| ApiError::bad_request(format!("unknown transcript source '{source}'")))?;
+        query = query.with_source(source);
+    }
+    let since = params
+        .since
+        .as_deref()
+        .map(|value| parse_search_date(value, false))
+        .transpose()
+        .map_err(|err| ApiError::bad_request(err.to_string()))?;
+    let until = params
+        .until
+        .as_deref()
+        .map(|value| parse_search_date(value, true))
+        .transpose()
+        .map_err(|err| ApiError::bad_request(err.to_string()))?;
+    query = query.with_range(since, until);
+    if let Some(attachments) = params.attachments {
+        query = query.with_attachments(attachments);
+    }
+
+    let store = Arc::clone(&state.transcripts);
+    let hits = task::spawn_blocking(move || store.search(&query))
+        .await
+        .map_err(|err| ApiError::internal(format!("worker task failed: {err}")))?
+        .map_err(|err| {
+            error!(error = %err, "failed to search transcripts");
+            ApiError::internal("failed to search transcripts")
+        })?;
+    Ok(Json(json!({ "results": hits })))
+}
+
+async fn transcript_history_handler(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<Json<Value>, ApiError> {
+    let uuid = Uuid::parse_str(&id)
+        .map_err(|_| ApiError::bad_request(format!("invalid transcript id '{id}'")))?;
+
+    let history = state.bridge.conversation_history(uuid).map_err(|err| {
+        error!(error = %err, transcript = %id, "failed to load transcript history");
+        ApiError::internal("failed to load transcript history")
+    })?;
+
+    Ok(Json(json!({ "history": history })))
+}
+
+#[derive(Debug, Deserialize)]
+struct TranscriptRelatedQuery {
+    #[serde(default)]
+    limit: Option<usize>,
+}
+
+async fn transcript_related_handler(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Query(query): Query<TranscriptRelatedQuery>,
+) -> Result<Json<Value>, ApiError> {
+    let uuid = Uuid::parse_str(&id)
+        .map_err(|_| ApiError::bad_request(format!("invalid transcript id '{id}'")))?;
+    let limit = query.limit.unwrap_or(5).clamp(1, 50);
+
+    let bridge = state.bridge.clone();
+    let related = task::spawn_blocking(move || bridge.recall().related(uuid, limit))
+        .await
+        .map_err(|err| ApiError::internal(format!("worker task failed: {err}")))?
+        .map_err(|err| {
+            error!(error = %err, transcript = %id, "failed to find related transcripts");
+            ApiError::internal("failed to find related transcripts")
+        })?;
+    Ok(Json(json!({ "related": related })))
+}
+
+#[derive(Debug, Deserialize)]
+struct TranscriptRecallRequest {
+    enabled: bool,
+}
+
+async fn transcript_recall_handler(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Json(payload): Json<TranscriptRecallRequest>,
+) -> Result<Json<Value>, ApiError> {
+    let uuid = Uuid::parse_str(&id)
+        .map_err(|_| ApiError::bad_request(format!("invalid transcript id '{id}'")))?;
+    if !state.transcripts.json_path(uuid).exists() {
+        return Err(ApiError::not_found(format!("unknown transcript '{id}'")));
+    }
+
+    let bridge = state.bridge.clone();
+    let enabled = payload.enabled;
+    let indexed = task::spawn_blocking(move || -> Result<usize> {
+        bridge.transcript_store().set_recall(uuid, enabled)?;
+        if !enabled || !bridge.recall().is_enabled() {
+            return Ok(0);
+        }
+        // Embed the existing messages now so they can be recalled right away.
+        let http = BlockingAiHttp::default();
+        bridge.recall().index_conversation(&http, uuid)
+    })
+    .await
+    .map_err(|err| ApiError::internal(format!("worker task failed: {err}")))?
+    .map_err(|err| {
+        error!(error = %err, transcript = %id, "failed to update transcript recall");
+        ApiError::internal(format!("failed to update transcript recall: {err}"))
+    })?;
+    Ok(Json(
+        json!({ "id": uuid, "recall": enabled, "indexed": indexed }),
+    ))
+}
+
+async fn transcript_tree_handler(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<Json<Value>, ApiError> {
+    let uuid = Uuid::parse_str(&id)
+        .map_err(|_| ApiError::bad_request(format!("invalid transcript id '{id}'")))?;
+    if !state.transcripts.contains(uuid) {
+        return Err(ApiError::not_found(format!("unknown transcript '{id}'")));
+    }
+
+    let store = state.transcripts.clone();
+    let tree = task::spawn_blocking(move || store.tree(uuid))
+        .await
+        .map_err(|err| ApiError::internal(format!("worker task failed: {err}")))?
+        .map_err(|err| {
+            error!(error = %err, transcript = %id, "failed to build transcript tree");
+            ApiError::internal("failed to build transcript tree")
+        })?;
+    Ok(Json(json!({ "tree": tree })))
+}
+
+#[derive(Debug, Deserialize)]
+struct TranscriptBranchRequest {
+    /// Zero-based index of the last message copied into the branch.
+    message_index: usize,
+}
+
+async fn transcript_branch_handler(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Json(payload): Json<TranscriptBranchRequest>,
+) -> Result<Json<Value>, ApiError> {
+    let uuid = Uuid::parse_str(&id)
+        .map_err(|_| ApiError::bad_request(format!("invalid transcript id '{id}'")))?;
+    if !state.transcripts.contains(uuid) {
+        return Err(ApiError::not_found(format!("unknown transcript '{id}'")));
+    }
+
+    let store = state.transcripts.clone();
+    let message_index = payload.message_index;
+    let record = task::spawn_blocking(move || -> Result<Option<TranscriptSummary>> {
+        if message_index >= store.load_messages(uuid)?.len() {
+            return Ok(None);
+        }
+        Ok(Some(store.branch(uuid, message_index)?.summary))
+    })
+    .await
+    .map_err(|err| ApiError::internal(format!("worker task failed: {err}")))?
+    .map_err(|err| {
+        error!(error = %err, transcript = %id, "failed to branch transcript");
+        ApiError::internal("failed to branch transcript")
+    })?
+    .ok_or_else(|| {
+        ApiError::bad_request(format!(
+            "message_index {message_index} is out of range for transcript '{id}'"
+        ))
+    })?;
+    Ok(Json(json!({ "transcript": record })))
+}
+
+async fn transcript_json_handler(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<Json<Value>, ApiError> {
+    let uuid = Uuid::parse_str(&id)
+        .map_err(|_| ApiError::bad_request(format!("invalid transcript id '{id}'")))?;
+
+    let raw = state.transcripts.load_json(uuid).map_err(|err| {
+        error!(error = %err, transcript = %id, "failed to load transcript JSON");
+        ApiError::internal("failed to load transcript JSON")
+    })?;
+
+    let value: Value = serde_json::from_str(&raw).map_err(|err| {
+        error!(error = %err, transcript = %id, "failed to parse transcript JSON");
+        ApiError::internal("failed to parse transcript JSON")
+    })?;
+
+    Ok(Json(value))
+}
+
+async fn transcript_markdown_handler(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<impl IntoResponse, ApiError> {
+    let uuid = Uuid::parse_str(&id)
+        .map_err(|_| ApiError::bad_request(format!("invalid transcript id '{id}'")))?;
+
+    let body = state.transcripts.load_markdown(uuid).map_err(|err| {
         error!(error = %err, transcript = %id, "failed to load transcript markdown");
         ApiError::internal("failed to load transcript markdown")
     })?;
@@ -2567,6 +3851,8 @@ async fn arc_health_handler(State(state): State<AppState>) -> Json<Value> {
     Json(json!({
         "enabled": state.arc.is_enabled(),
         "providers": state.arc.providers(),
+        "fusion": state.arc.fusion_enabled(),
+        "fetch": state.arc.fetch_enabled(),
     }))
 }
 
@@ -2891,6 +4177,257 @@ async fn arc_ask_stream_handler(
     ))
 }
 
+#[derive(Debug, Deserialize)]
+struct ResearchRequest {
+    question: String,
+    #[serde(default)]
+    context: Option<String>,
+    /// Research depth; defaults to `research.default_depth`.
+    #[serde(default)]
+    depth: Option<ResearchDepth>,
+    #[serde(default)]
+    max_sources: Option<usize>,
+    /// AI provider override.
+    #[serde(default)]
+    provider: Option<String>,
+    /// Stored research session to ask a follow-up in; a new session is
+    /// started otherwise.
+    #[serde(default)]
+    session: Option<String>,
+}
+
+impl ResearchRequest {
+    /// The follow-up session, which must exist.
+    fn session_id(&self, research: &ResearchOrchestrator) -> Result<Option<Uuid>, ApiError> {
+        let Some(raw) = self.session.as_deref() else {
+            return Ok(None);
+        };
+        let id = parse_research_session_id(raw)?;
+        match research.sessions() {
+            Some(store) if store.contains(id) => Ok(Some(id)),
+            _ => Err(ApiError::not_found(format!(
+                "research session {id} not found"
+            ))),
+        }
+    }
+
+    fn into_query(self, default_depth: &str) -> Result<ResearchQuery, ApiError> {
+        if self.question.trim().is_empty() {
+            return Err(ApiError::bad_request("question must not be empty"));
+        }
+        let depth = self
+            .depth
+            .unwrap_or_else(|| default_depth.parse().unwrap_or_default());
+        let mut query = ResearchQuery::new(self.question).with_depth(depth);
+        if let Some(context) = self.context {
+            query = query.with_context(context);
+        }
+        if let Some(max) = self.max_sources {
+            query = query.with_max_sources(max);
+        }
+        if let Some(provider) = self.provider {
+            query = query.with_provider(provider);
+        }
+        Ok(query)
+    }
+}
+
+/// Run a research loop and return the report.
+async fn research_handler(
+    State(state): State<AppState>,
+    Json(payload): Json<ResearchRequest>,
+) -> Result<Json<Value>, ApiError> {
+    if !state.arc.is_enabled() {
+        return Err(ApiError::bad_request("Arc search is not enabled"));
+    }
+    let research = state.research.clone();
+    let session = payload.session_id(&research)?;
+    let query = payload.into_query(&research.settings().default_depth)?;
+
+    let report =
+        task::spawn_blocking(move || run_research_session(&research, session, &query, &mut |_| {}))
+            .await
+            .map_err(|err| ApiError::internal(format!("worker task failed: {err}")))?
+            .map_err(|err| {
+                warn!(error = %err, "research failed");
+                ApiError::bad_request(format!("{err:#}"))
+            })?;
+
+    Ok(Json(report))
+}
+
+/// Run a research loop, streaming `progress` events and a final `complete`
+/// event carrying the report.
+async fn research_stream_handler(
+    State(state): State<AppState>,
+    Json(payload): Json<ResearchRequest>,
+) -> Result<Sse<impl Stream<Item = Result<Event, Infallible>>>, ApiError> {
+    if !state.arc.is_enabled() {
+        return Err(ApiError::bad_request("Arc search is not enabled"));
+    }
+    let research = state.research.clone();
+    let session = payload.session_id(&research)?;
+    let query = payload.into_query(&research.settings().default_depth)?;
+
+    let (tx, rx) = mpsc::channel::<Result<Event, String>>(64);
+    tokio::spawn(async move {
+        // Progress is bridged onto the SSE channel with blocking_send, as in
+        // chat_stream_handler.
+        let progress_tx = tx.clone();
+        let outcome = task::spawn_blocking(move || {
+            run_research_session(&research, session, &query, &mut |progress| {
+                let event = Event::default()
+                    .event("progress")
+                    .data(json!(progress).to_string());
+                let _ = progress_tx.blocking_send(Ok(event));
+            })
+        })
+        .await;
+
+        let message = match outcome {
+            Ok(Ok(report)) => Ok(Event::default().event("complete").data(report.to_string())),
+            Ok(Err(err)) => Err(format!("Research failed: {err:#}")),
+            Err(err) => Err(format!("Research task panicked: {err}")),
+        };
+        let _ = tx.send(message).await;
+    });
+
+    let stream = ReceiverStream::new(rx).map(|result| match result {
+        Ok(event) => Ok(event),
+        Err(message) => Ok(Event::default()
+            .event("error")
+            .data(json!({ "message": message }).to_string())),
+    });
+
+    Ok(Sse::new(stream).keep_alive(
+        KeepAlive::new()
+            .interval(Duration::from_secs(15))
+            .text("keep-alive"),
+    ))
+}
+
+/// Research `query` in session `session` (or a new one) and return the report
+/// JSON, with `session_id` when sessions are stored.
+fn run_research_session(
+    research: &ResearchOrchestrator,
+    session: Option<Uuid>,
+    query: &ResearchQuery,
+    on_event: &mut dyn FnMut(&ResearchEvent),
+) -> Result<Value> {
+    let mut session = match session {
+        Some(id) => research.resume_session(id)?,
+        None => research.create_session(&query.question),
+    };
+    let report = research.research_in_session(&mut session, query, on_event)?;
+    let mut value = json!(report);
+    if research.persists_sessions() {
+        value["session_id"] = json!(session.id);
+    }
+    Ok(value)
+}
+
+fn parse_research_session_id(raw: &str) -> Result<Uuid, ApiError> {
+    Uuid::parse_str(raw.trim())
+        .map_err(|_| ApiError::bad_request(format!("invalid research session id '{raw}'")))
+}
+
+fn research_sessions(research: &ResearchOrchestrator) -> Result<&ResearchSessionStore, ApiError> {
+    research
+        .sessions()
+        .ok_or_else(|| ApiError::not_found("research sessions are not stored"))
+}
+
+/// Run `op` against the research session store on the blocking pool; session
+/// files are read, decrypted and parsed synchronously.
+async fn with_research_sessions<T, F>(state: &AppState, op: F) -> Result<T, ApiError>
+where
+    F: FnOnce(&ResearchSessionStore) -> Result<T, ApiError> + Send + 'static,
+    T: Send + 'static,
+{
+    let research = Arc::clone(&state.research);
+    task::spawn_blocking(move || op(research_sessions(&research)?))
+        .await
+        .map_err(|err| ApiError::internal(format!("worker task failed: {err}")))?
+}
+
+/// Load stored research session `uuid`, mapping a missing one to 404.
+fn load_research_session(
+    store: &ResearchSessionStore,
+    uuid: Uuid,
+) -> Result<ResearchSession, ApiError> {
+    if !store.contains(uuid) {
+        return Err(ApiError::not_found(format!(
+            "research session {uuid} not found"
+        )));
+    }
+    store.load(uuid).map_err(|err| {
+        error!(error = %err, session = %uuid, "failed to load research session");
+        ApiError::internal("failed to load research session")
+    })
+}
+
+async fn research_sessions_handler(State(state): State<AppState>) -> Result<Json<Value>, ApiError> {
+    let sessions = with_research_sessions(&state, |store| {
+        store.list().map_err(|err| {
+            error!(error = %err, "failed to list research sessions");
+            ApiError::internal("failed to list research sessions")
+        })
+    })
+    .await?;
+
+    Ok(Json(json!({ "sessions": sessions })))
+}
+
+async fn research_session_handler(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<Json<Value>, ApiError> {
+    let uuid = parse_research_session_id(&id)?;
+    let session =
+        with_research_sessions(&state, move |store| load_research_session(store, uuid)).await?;
+
+    Ok(Json(json!(session)))
+}
+
+async fn research_session_markdown_handler(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<impl IntoResponse, ApiError> {
+    let uuid = parse_research_session_id(&id)?;
+    let markdown = with_research_sessions(&state, move |store| {
+        load_research_session(store, uuid).map(|session| session.to_markdown())
+    })
+    .await?;
+
+    let mut headers = HeaderMap::new();
+    headers.insert(
+        header::CONTENT_TYPE,
+        HeaderValue::from_static("text/markdown; charset=utf-8"),
+    );
+    Ok((headers, markdown))
+}
+
+async fn research_session_delete_handler(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<Json<Value>, ApiError> {
+    let uuid = parse_research_session_id(&id)?;
+    let deleted = with_research_sessions(&state, move |store| {
+        store.delete(uuid).map_err(|err| {
+            error!(error = %err, session = %uuid, "failed to delete research session");
+            ApiError::internal("failed to delete research session")
+        })
+    })
+    .await?;
+    if !deleted {
+        return Err(ApiError::not_found(format!(
+            "research session {uuid} not found"
+        )));
+    }
+
+    Ok(Json(json!({ "deleted": uuid })))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -3005,6 +4542,17 @@ mod tests {
         assert!(request.provider.is_none());
     }
 
+    #[test]
+    fn agent_run_request_accepts_resume_without_goal() {
+        let id = Uuid::new_v4();
+        let request: AgentRunRequest =
+            serde_json::from_value(json!({ "resume": id.to_string(), "execute": true }))
+                .expect("valid resume request");
+        assert!(request.goal.is_empty());
+        assert_eq!(request.resume, Some(id));
+        assert!(request.execute);
+    }
+
     #[test]
     fn agent_step_serialises_for_sse() {
         use archon::agent::AgentStep;
@@ -3025,6 +4573,7 @@ mod tests {
             observation: "a button".to_string(),
             action,
             result,
+            path: None,
         };
         let value: Value = serde_json::from_str(
             &serde_json::to_string(&step).expect("step serialises"),
@@ -3047,6 +4596,9 @@ mod tests {
             steps: Vec::new(),
             completed: true,
             summary: "done".to_string(),
+            verification: None,
+            har: None,
+            assertions: Vec::new(),
         };
         let value: Value = serde_json::from_str(
             &serde_json::to_string(&outcome).expect("outcome serialises"),
@@ -3057,4 +4609,49 @@ mod tests {
         assert_eq!(value["completed"], json!(true));
         assert_eq!(value["summary"], json!("done"));
     }
+
+    #[test]
+    fn conduit_changes_need_a_local_client_or_the_token() {
+        let conduit = |token| ConduitDir {
+            path: PathBuf::new(),
+            state_lock: Mutex::new(()),
+            token,
+            allowed_origins: vec!["https://sidebar.example".into()],
+        };
+        let headers = |pairs: &[(header::HeaderName, &str)]| {
+            let mut headers = HeaderMap::new();
+            for (name, value) in pairs {
+                headers.insert(name.clone(), HeaderValue::from_str(value).unwrap());
+            }
+            headers
+        };
+        let local: IpAddr = "127.0.0.1".parse().unwrap();
+        let remote: IpAddr = "192.0.2.7".parse().unwrap();
+
+        let open = conduit(ConduitToken::Loopback);
+        assert!(conduit_authorize(&open, local, &HeaderMap::new()).is_ok());
+        assert!(
+            conduit_authorize(&open, "::ffff:127.0.0.1".parse().unwrap(), &HeaderMap::new())
+                .is_ok()
+        );
+        let origin = headers(&[(header::ORIGIN, "https://sidebar.example")]);
+        assert!(conduit_authorize(&open, local, &origin).is_ok());
+        let err = conduit_authorize(&open, remote, &HeaderMap::new()).unwrap_err();
+        assert_eq!(err.status, StatusCode::FORBIDDEN);
+        let evil = headers(&[(header::ORIGIN, "https://evil.example")]);
+        let err = conduit_authorize(&open, local, &evil).unwrap_err();
+        assert_eq!(err.status, StatusCode::FORBIDDEN);
+
+        let guarded = conduit(ConduitToken::Bearer("s3cret".into()));
+        let bearer = headers(&[(header::AUTHORIZATION, "Bearer s3cret")]);
+        assert!(conduit_authorize(&guarded, remote, &bearer).is_ok());
+        let err = conduit_authorize(&guarded, local, &HeaderMap::new()).unwrap_err();
+        assert_eq!(err.status, StatusCode::UNAUTHORIZED);
+        let wrong = headers(&[(header::AUTHORIZATION, "Bearer nope")]);
+        assert!(conduit_authorize(&guarded, local, &wrong).is_err());
+
+        let unset = conduit(ConduitToken::Unset("ARCHON_CONDUIT_TOKEN".into()));
+        let err = conduit_authorize(&unset, local, &bearer).unwrap_err();
+        assert!(err.message.contains("ARCHON_CONDUIT_TOKEN"));
+    }
 }
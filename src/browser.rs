@@ -5,23 +5,45 @@
 //! the `headless_chrome` CDP client. The driver exposes the primitive actions the
 //! agent needs — navigate, click, type, scroll, extract, screenshot, observe — and
 //! a structured [`PageObservation`] used to feed the planner.
+//!
+//! Optionally ([`CdpBrowser::with_network`]) the driver also records traffic
+//! into a [`NetworkLog`] for HAR export and `extract_response`, and fails
+//! requests matching URL block patterns.
 
+use std::collections::{BTreeMap, HashSet};
 use std::path::{Path, PathBuf};
-use std::sync::Arc;
-use std::time::Duration;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
-use anyhow::{Context, Result, bail};
-use headless_chrome::protocol::cdp::Page::CaptureScreenshotFormatOption;
+use anyhow::{Context, Result, anyhow, bail};
+use chrono::{TimeZone, Utc};
+use headless_chrome::browser::tab::RequestPausedDecision;
+use headless_chrome::browser::transport::{SessionId, Transport};
+use headless_chrome::protocol::cdp::Fetch::{self, events::RequestPausedEvent};
+use headless_chrome::protocol::cdp::Network;
+use headless_chrome::protocol::cdp::Page::{self, CaptureScreenshotFormatOption};
+use headless_chrome::protocol::cdp::types::Event;
 use headless_chrome::{Browser, LaunchOptionsBuilder, Tab};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+use crate::network::{
+    CapturedResponse, NetworkLog, NetworkOptions, headers_from_json, should_capture_body,
+};
+use crate::sync_util::LockResultExt;
+use crate::transcript::{read_sealed, write_sealed};
+use crate::transcript_crypto::TranscriptCipher;
+
 /// Maximum characters of page text captured in an observation.
 const MAX_OBSERVATION_TEXT: usize = 6_000;
 /// Maximum characters returned by an extract action.
 const MAX_EXTRACT_CHARS: usize = 4_000;
 /// Maximum interactive elements summarised per observation.
 const MAX_INTERACTIVE_ELEMENTS: usize = 40;
+/// How long `extract_response` waits for a matching response to arrive.
+const RESPONSE_WAIT: Duration = Duration::from_secs(5);
+/// Poll interval of the default [`BrowserDriver::wait_for`].
+const WAIT_POLL: Duration = Duration::from_millis(200);
 
 /// A summary of a single interactive element on the page.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -68,6 +90,76 @@ impl PageObservation {
         out.push_str(&self.text);
         out
     }
+
+    /// Render the page as a Markdown document: title heading, source URL,
+    /// visible text, then the interactive elements as a list.
+    pub fn render_markdown(&self) -> String {
+        let title = if self.title.trim().is_empty() {
+            self.url.as_str()
+        } else {
+            self.title.trim()
+        };
+        let mut out = format!("# {title}\n\nSource: <{}>\n\n", self.url);
+        out.push_str(self.text.trim());
+        out.push('\n');
+        if !self.interactive.is_empty() {
+            out.push_str("\n## Interactive elements\n\n");
+            for el in &self.interactive {
+                out.push_str(&format!(
+                    "- {} `{}` ({})\n",
+                    if el.text.is_empty() {
+                        &el.tag
+                    } else {
+                        &el.text
+                    },
+                    el.selector_hint,
+                    el.role
+                ));
+            }
+        }
+        out
+    }
+}
+
+/// A cookie captured in a [`StorageSnapshot`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StoredCookie {
+    pub name: String,
+    pub value: String,
+    pub domain: String,
+    pub path: String,
+    /// Expiry as seconds since the epoch; `None` for session cookies.
+    #[serde(default)]
+    pub expires: Option<f64>,
+    #[serde(default)]
+    pub http_only: bool,
+    #[serde(default)]
+    pub secure: bool,
+}
+
+/// Browser state captured so an interrupted agent run can be resumed in a fresh
+/// browser: cookies plus the current origin's `localStorage`/`sessionStorage`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct StorageSnapshot {
+    /// Document URL the web-storage entries belong to.
+    pub url: String,
+    #[serde(default)]
+    pub cookies: Vec<StoredCookie>,
+    #[serde(default)]
+    pub local_storage: BTreeMap<String, String>,
+    #[serde(default)]
+    pub session_storage: BTreeMap<String, String>,
+}
+
+/// An open browser tab, as listed by [`BrowserDriver::tabs`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TabSummary {
+    /// Position in the tab list; pass it to [`BrowserDriver::switch_tab`].
+    pub index: usize,
+    pub url: String,
+    pub title: String,
+    /// Whether this is the tab the driver currently acts on.
+    pub active: bool,
 }
 
 /// Object-safe abstraction over a controllable browser.
@@ -82,12 +174,103 @@ pub trait BrowserDriver {
     fn scroll(&self, selector: Option<&str>) -> Result<()>;
     /// Extract the inner text of the first element matching the selector (bounded).
     fn extract(&self, selector: &str) -> Result<String>;
+    /// Extract the inner text of every element matching the selector. The
+    /// default returns the first match only.
+    fn extract_all(&self, selector: &str) -> Result<Vec<String>> {
+        Ok(vec![self.extract(selector)?])
+    }
     /// Capture a PNG screenshot, returning the path it was written to.
     fn screenshot(&self) -> Result<String>;
     /// Capture a structured observation of the current page.
     fn observe(&self) -> Result<PageObservation>;
     /// Return the current document URL.
     fn current_url(&self) -> Result<String>;
+    /// Capture cookies and web storage for checkpointing. Drivers without
+    /// storage access return only the current URL.
+    fn storage_snapshot(&self) -> Result<StorageSnapshot> {
+        Ok(StorageSnapshot {
+            url: self.current_url()?,
+            ..StorageSnapshot::default()
+        })
+    }
+    /// Restore a [`StorageSnapshot`], leaving the browser on `snapshot.url`
+    /// when web storage had to be written. The default is a no-op.
+    fn restore_storage(&self, _snapshot: &StorageSnapshot) -> Result<()> {
+        Ok(())
+    }
+    /// Return the JSON body of the latest XHR/fetch response whose URL matches
+    /// `url_pattern` (glob, or substring without wildcards). Requires network
+    /// capture; the default errors.
+    fn extract_response(&self, url_pattern: &str) -> Result<String> {
+        bail!("cannot extract a response for {url_pattern}: network capture is not enabled")
+    }
+    /// Write captured traffic as `<artifacts>/<name>.har`, returning the path,
+    /// or `None` when the driver does not capture traffic (the default).
+    fn save_har(&self, _name: &str) -> Result<Option<PathBuf>> {
+        Ok(None)
+    }
+    /// Read back an artifact this driver wrote (a screenshot or PDF),
+    /// decrypting it if needed. The default reads the file as-is.
+    fn read_artifact(&self, path: &Path) -> Result<Vec<u8>> {
+        std::fs::read(path).with_context(|| format!("failed to read {}", path.display()))
+    }
+    /// Choose the option whose value (or visible label) is `value` in the
+    /// `<select>` matching the selector. The default errors.
+    fn select_option(&self, selector: &str, value: &str) -> Result<()> {
+        bail!("cannot select '{value}' in {selector}: the driver does not support selects")
+    }
+    /// Wait until an element matches the selector, for at most `timeout`. The
+    /// default polls [`extract`](Self::extract).
+    fn wait_for(&self, selector: &str, timeout: Duration) -> Result<()> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            if self.extract(selector).is_ok() {
+                return Ok(());
+            }
+            if Instant::now() >= deadline {
+                bail!("no element matching {selector} appeared within {timeout:?}");
+            }
+            std::thread::sleep(WAIT_POLL);
+        }
+    }
+    /// Go back one entry in the tab's session history. The default errors.
+    fn go_back(&self) -> Result<()> {
+        bail!("the driver does not support history navigation")
+    }
+    /// Go forward one entry in the tab's session history. The default errors.
+    fn go_forward(&self) -> Result<()> {
+        bail!("the driver does not support history navigation")
+    }
+    /// List the browser's open tabs. The default reports the driven page as
+    /// the only tab.
+    fn tabs(&self) -> Result<Vec<TabSummary>> {
+        let observation = self.observe()?;
+        Ok(vec![TabSummary {
+            index: 0,
+            url: observation.url,
+            title: observation.title,
+            active: true,
+        }])
+    }
+    /// Act on the tab at `index` (as listed by [`tabs`](Self::tabs)) from now
+    /// on. The default only accepts the single tab it reports.
+    fn switch_tab(&self, index: usize) -> Result<()> {
+        if index == 0 {
+            Ok(())
+        } else {
+            bail!("no tab at index {index}")
+        }
+    }
+    /// Evaluate a JavaScript expression in the page, awaiting promises, and
+    /// return its JSON value. The default errors.
+    fn evaluate(&self, _expression: &str) -> Result<serde_json::Value> {
+        bail!("the driver does not support script evaluation")
+    }
+    /// Print the page to a PDF file, returning the path it was written to.
+    /// The default errors.
+    fn print_pdf(&self) -> Result<String> {
+        bail!("the driver does not support PDF export")
+    }
 }
 
 /// JavaScript that collects a structured [`PageObservation`] from the live DOM.
@@ -127,12 +310,40 @@ const OBSERVE_SCRIPT: &str = r##"
 })()
 "##;
 
+/// JavaScript that dumps the current origin's web storage as a JSON string.
+const STORAGE_DUMP_SCRIPT: &str = r##"
+(function () {
+  function dump(store) {
+    const out = {};
+    try {
+      for (let i = 0; i < store.length; i++) {
+        const key = store.key(i);
+        out[key] = store.getItem(key);
+      }
+    } catch (e) {}
+    return out;
+  }
+  return JSON.stringify({
+    local_storage: dump(window.localStorage),
+    session_storage: dump(window.sessionStorage),
+  });
+})()
+"##;
+
 /// A `headless_chrome`-backed [`BrowserDriver`].
 pub struct CdpBrowser {
     // Kept alive for the lifetime of the driver; dropping it closes the browser.
-    _browser: Browser,
-    tab: Arc<Tab>,
+    browser: Browser,
+    /// The tab actions apply to; replaced by [`BrowserDriver::switch_tab`].
+    tab: Mutex<Arc<Tab>>,
     artifacts_dir: PathBuf,
+    network: Option<Arc<NetworkLog>>,
+    /// Set by [`CdpBrowser::with_network`]; applied to each tab the driver uses.
+    network_options: Option<NetworkOptions>,
+    /// Target IDs of tabs already carrying the network listeners.
+    instrumented: Mutex<HashSet<String>>,
+    /// Encrypts screenshots, PDFs and HARs when transcript encryption is on.
+    cipher: Option<TranscriptCipher>,
 }
 
 impl CdpBrowser {
@@ -169,9 +380,13 @@ impl CdpBrowser {
             .context("failed to open agent browser tab")?;
 
         Ok(Self {
-            _browser: browser,
-            tab,
+            browser,
+            tab: Mutex::new(tab),
             artifacts_dir,
+            network: None,
+            network_options: None,
+            instrumented: Mutex::new(HashSet::new()),
+            cipher: None,
         })
     }
 
@@ -209,9 +424,13 @@ impl CdpBrowser {
         };
 
         Ok(Self {
-            _browser: browser,
-            tab,
+            browser,
+            tab: Mutex::new(tab),
             artifacts_dir,
+            network: None,
+            network_options: None,
+            instrumented: Mutex::new(HashSet::new()),
+            cipher: None,
         })
     }
 
@@ -276,8 +495,177 @@ impl CdpBrowser {
         Ok(format!("ws://127.0.0.1:{port}{ws_path}"))
     }
 
+    /// Encrypt screenshots, PDFs and HARs written from now on with `cipher`
+    /// (the transcript cipher, as they live under the transcript directory).
+    pub fn with_encryption(mut self, cipher: Option<TranscriptCipher>) -> Self {
+        self.cipher = cipher;
+        self
+    }
+
+    /// Enable CDP network features for this tab and every tab the driver
+    /// switches to later.
+    ///
+    /// With `capture`, requests and responses are recorded into a
+    /// [`NetworkLog`] (JSON XHR/fetch bodies included) for [`BrowserDriver::save_har`]
+    /// and [`BrowserDriver::extract_response`]. Requests matching
+    /// `block_patterns` are paused via the `Fetch` domain and failed as
+    /// blocked-by-client. A no-op when `options` enables nothing.
+    pub fn with_network(mut self, options: &NetworkOptions) -> Result<Self> {
+        if !options.capture && options.block_patterns.is_empty() {
+            return Ok(self);
+        }
+        if options.capture {
+            self.network = Some(Arc::new(
+                NetworkLog::new().with_sensitive_data(options.include_sensitive),
+            ));
+        }
+        self.network_options = Some(options.clone());
+        self.instrument_tab(&self.tab())?;
+        Ok(self)
+    }
+
+    /// Install the network listeners and request blocker configured by
+    /// [`with_network`](Self::with_network) on `tab`, once per tab.
+    fn instrument_tab(&self, tab: &Arc<Tab>) -> Result<()> {
+        let Some(options) = &self.network_options else {
+            return Ok(());
+        };
+        if self
+            .instrumented
+            .lock()
+            .recover()
+            .contains(tab.get_target_id())
+        {
+            return Ok(());
+        }
+
+        if let Some(log) = &self.network {
+            let requests = Arc::clone(log);
+            tab.add_event_listener(Arc::new(move |event: &Event| {
+                if let Event::NetworkRequestWillBeSent(sent) = event {
+                    let params = &sent.params;
+                    let started = Utc
+                        .timestamp_millis_opt((params.wall_time * 1_000.0) as i64)
+                        .single()
+                        .unwrap_or_else(Utc::now);
+                    requests.record_request(
+                        &params.request_id,
+                        &params.request.method,
+                        headers_from_json(params.request.headers.0.as_ref()),
+                        params.request.post_data.clone(),
+                        started,
+                    );
+                }
+            }))
+            .context("failed to listen for network requests")?;
+
+            let responses = Arc::clone(log);
+            tab.register_response_handling(
+                "archon-network-log",
+                Box::new(move |params, fetch_body| {
+                    let response = params.response;
+                    let resource_type = enum_label(&params.Type);
+                    let body = if should_capture_body(&resource_type, &response.mime_type) {
+                        fetch_body()
+                            .ok()
+                            .filter(|body| !body.base_64_encoded)
+                            .map(|body| body.body)
+                    } else {
+                        None
+                    };
+                    responses.record_response(
+                        &params.request_id,
+                        CapturedResponse {
+                            url: response.url,
+                            resource_type,
+                            status: response.status,
+                            status_text: response.status_text,
+                            http_version: response.protocol.unwrap_or_default(),
+                            mime_type: response.mime_type,
+                            headers: headers_from_json(response.headers.0.as_ref()),
+                            body,
+                            body_size: response.encoded_data_length as i64,
+                        },
+                    );
+                }),
+            )
+            .context("failed to enable network capture")?;
+        }
+
+        if !options.block_patterns.is_empty() {
+            let patterns: Vec<Fetch::RequestPattern> = options
+                .block_patterns
+                .iter()
+                .map(|pattern| Fetch::RequestPattern {
+                    url_pattern: Some(pattern.clone()),
+                    resource_Type: None,
+                    request_stage: Some(Fetch::RequestStage::Request),
+                })
+                .collect();
+            let matcher = options.clone();
+            let log = self.network.clone();
+            tab.enable_request_interception(Arc::new(
+                move |_transport: Arc<Transport>,
+                      _session: SessionId,
+                      event: RequestPausedEvent| {
+                    let params = event.params;
+                    // CDP's own pattern matching already selected these;
+                    // re-check so a pattern quirk never blocks more than asked.
+                    if !matcher.is_blocked(&params.request.url) {
+                        return RequestPausedDecision::Continue(None);
+                    }
+                    if let Some(log) = &log {
+                        log.record_blocked(
+                            &params.request.method,
+                            &params.request.url,
+                            &enum_label(&params.resource_Type),
+                        );
+                    }
+                    RequestPausedDecision::Fail(Fetch::FailRequest {
+                        request_id: params.request_id,
+                        error_reason: Network::ErrorReason::BlockedByClient,
+                    })
+                },
+            ))
+            .context("failed to install request blocker")?;
+            tab.enable_fetch(Some(&patterns), None)
+                .context("failed to enable request blocking")?;
+        }
+
+        self.instrumented
+            .lock()
+            .recover()
+            .insert(tab.get_target_id().clone());
+        Ok(())
+    }
+
+    /// The tab actions currently apply to.
+    fn tab(&self) -> Arc<Tab> {
+        Arc::clone(&self.tab.lock().recover())
+    }
+
+    /// Move `delta` entries through the session history.
+    fn step_history(&self, delta: i64) -> Result<()> {
+        let tab = self.tab();
+        let history = tab
+            .call_method(Page::GetNavigationHistory(None))
+            .context("failed to read navigation history")?;
+        let target = usize::try_from(history.current_index as i64 + delta).ok();
+        let Some(entry) = target.and_then(|index| history.entries.get(index)) else {
+            bail!(
+                "there is no {} page in this tab's history",
+                if delta < 0 { "previous" } else { "next" }
+            );
+        };
+        tab.call_method(Page::NavigateToHistoryEntry { entry_id: entry.id })
+            .with_context(|| format!("failed to navigate to {}", entry.url))?;
+        tab.wait_until_navigated()
+            .with_context(|| format!("navigation did not complete for {}", entry.url))?;
+        Ok(())
+    }
+
     fn eval_json(&self, script: &str) -> Result<serde_json::Value> {
-        self.tab
+        self.tab()
             .evaluate(script, false)
             .context("failed to evaluate script")?
             .value
@@ -287,17 +675,17 @@ impl CdpBrowser {
 
 impl BrowserDriver for CdpBrowser {
     fn navigate(&self, url: &str) -> Result<()> {
-        self.tab
+        self.tab()
             .navigate_to(url)
             .with_context(|| format!("failed to navigate to {url}"))?;
-        self.tab
+        self.tab()
             .wait_until_navigated()
             .with_context(|| format!("navigation did not complete for {url}"))?;
         Ok(())
     }
 
     fn click(&self, selector: &str) -> Result<()> {
-        self.tab
+        self.tab()
             .find_element(selector)
             .with_context(|| format!("no element matching selector {selector}"))?
             .click()
@@ -306,8 +694,8 @@ impl BrowserDriver for CdpBrowser {
     }
 
     fn type_text(&self, selector: &str, text: &str) -> Result<()> {
-        let element = self
-            .tab
+        let tab = self.tab();
+        let element = tab
             .find_element(selector)
             .with_context(|| format!("no element matching selector {selector}"))?;
         element
@@ -334,7 +722,7 @@ impl BrowserDriver for CdpBrowser {
 
     fn extract(&self, selector: &str) -> Result<String> {
         let text = self
-            .tab
+            .tab()
             .find_element(selector)
             .with_context(|| format!("no element matching selector {selector}"))?
             .get_inner_text()
@@ -342,13 +730,29 @@ impl BrowserDriver for CdpBrowser {
         Ok(truncate(&text, MAX_EXTRACT_CHARS))
     }
 
+    fn extract_all(&self, selector: &str) -> Result<Vec<String>> {
+        let tab = self.tab();
+        let elements = tab
+            .find_elements(selector)
+            .with_context(|| format!("no elements matching selector {selector}"))?;
+        elements
+            .iter()
+            .map(|element| {
+                element
+                    .get_inner_text()
+                    .map(|text| truncate(&text, MAX_EXTRACT_CHARS))
+                    .with_context(|| format!("failed to read text of {selector}"))
+            })
+            .collect()
+    }
+
     fn screenshot(&self) -> Result<String> {
         let png = self
-            .tab
+            .tab()
             .capture_screenshot(CaptureScreenshotFormatOption::Png, None, None, true)
             .context("failed to capture screenshot")?;
         let path = self.artifacts_dir.join(format!("shot-{}.png", Uuid::new_v4()));
-        std::fs::write(&path, png)
+        write_sealed(&path, &png, self.cipher.as_ref())
             .with_context(|| format!("failed to write screenshot to {}", path.display()))?;
         Ok(path.display().to_string())
     }
@@ -365,12 +769,251 @@ impl BrowserDriver for CdpBrowser {
     }
 
     fn current_url(&self) -> Result<String> {
-        Ok(self.tab.get_url())
+        Ok(self.tab().get_url())
+    }
+
+    fn storage_snapshot(&self) -> Result<StorageSnapshot> {
+        let cookies = self
+            .tab()
+            .call_method(Network::GetCookies { urls: None })
+            .context("failed to read cookies")?
+            .cookies
+            .into_iter()
+            .map(|cookie| StoredCookie {
+                name: cookie.name,
+                value: cookie.value,
+                domain: cookie.domain,
+                path: cookie.path,
+                expires: (!cookie.session).then_some(cookie.expires),
+                http_only: cookie.http_only,
+                secure: cookie.secure,
+            })
+            .collect();
+
+        #[derive(Deserialize)]
+        struct WebStorage {
+            local_storage: BTreeMap<String, String>,
+            session_storage: BTreeMap<String, String>,
+        }
+        let value = self
+            .eval_json(STORAGE_DUMP_SCRIPT)
+            .context("failed to read web storage")?;
+        let storage: WebStorage = serde_json::from_str(
+            value
+                .as_str()
+                .context("storage script did not return a JSON string")?,
+        )
+        .context("failed to parse web storage")?;
+
+        Ok(StorageSnapshot {
+            url: self.tab().get_url(),
+            cookies,
+            local_storage: storage.local_storage,
+            session_storage: storage.session_storage,
+        })
+    }
+
+    fn restore_storage(&self, snapshot: &StorageSnapshot) -> Result<()> {
+        if !snapshot.cookies.is_empty() {
+            let cookies = snapshot
+                .cookies
+                .iter()
+                .map(|cookie| Network::CookieParam {
+                    name: cookie.name.clone(),
+                    value: cookie.value.clone(),
+                    url: None,
+                    domain: Some(cookie.domain.clone()),
+                    path: Some(cookie.path.clone()),
+                    secure: Some(cookie.secure),
+                    http_only: Some(cookie.http_only),
+                    same_site: None,
+                    expires: cookie.expires,
+                    priority: None,
+                    same_party: None,
+                    source_scheme: None,
+                    source_port: None,
+                    partition_key: None,
+                })
+                .collect();
+            self.tab()
+                .call_method(Network::SetCookies { cookies })
+                .context("failed to restore cookies")?;
+        }
+
+        if snapshot.local_storage.is_empty() && snapshot.session_storage.is_empty() {
+            return Ok(());
+        }
+        // Web storage is per-origin, so it can only be written once the page is
+        // on the snapshot's document.
+        self.navigate(&snapshot.url)?;
+        let script = format!(
+            "(function(){{const l={local};const s={session};\
+             for(const k in l){{localStorage.setItem(k,l[k]);}}\
+             for(const k in s){{sessionStorage.setItem(k,s[k]);}}return true;}})()",
+            local = serde_json::to_string(&snapshot.local_storage)?,
+            session = serde_json::to_string(&snapshot.session_storage)?,
+        );
+        self.eval_json(&script)
+            .context("failed to restore web storage")?;
+        Ok(())
+    }
+
+    fn extract_response(&self, url_pattern: &str) -> Result<String> {
+        let log = self.network.as_ref().with_context(|| {
+            format!(
+                "cannot extract a response for {url_pattern}: \
+                 enable automation.capture_network to record traffic"
+            )
+        })?;
+        // The call may still be in flight right after the click that caused it.
+        let deadline = Instant::now() + RESPONSE_WAIT;
+        loop {
+            if let Some(body) = log.find_json_response(url_pattern) {
+                return Ok(truncate(&body, MAX_EXTRACT_CHARS));
+            }
+            if Instant::now() >= deadline {
+                bail!("no JSON response matching {url_pattern} was captured");
+            }
+            std::thread::sleep(Duration::from_millis(100));
+        }
+    }
+
+    fn save_har(&self, name: &str) -> Result<Option<PathBuf>> {
+        let Some(log) = &self.network else {
+            return Ok(None);
+        };
+        let path = self.artifacts_dir.join(format!("{name}.har"));
+        log.write_har(&path, self.cipher.as_ref())?;
+        Ok(Some(path))
+    }
+
+    fn read_artifact(&self, path: &Path) -> Result<Vec<u8>> {
+        read_sealed(path, self.cipher.as_ref())
+            .with_context(|| format!("failed to read {}", path.display()))
+    }
+
+    fn select_option(&self, selector: &str, value: &str) -> Result<()> {
+        let script = format!(
+            "(function(){{const e=document.querySelector({sel});\
+             if(!e||e.tagName!=='SELECT'){{return 'missing';}}\
+             const v={value};const opts=Array.from(e.options);\
+             const o=opts.find(o=>o.value===v)||opts.find(o=>o.text.trim()===v);\
+             if(!o){{return 'no-option';}}e.value=o.value;\
+             e.dispatchEvent(new Event('input',{{bubbles:true}}));\
+             e.dispatchEvent(new Event('change',{{bubbles:true}}));return 'ok';}})()",
+            sel = serde_json::to_string(selector)?,
+            value = serde_json::to_string(value)?,
+        );
+        let outcome = self
+            .eval_json(&script)
+            .with_context(|| format!("failed to select '{value}' in {selector}"))?;
+        match outcome.as_str() {
+            Some("ok") => Ok(()),
+            Some("no-option") => bail!("{selector} has no option '{value}'"),
+            _ => bail!("no <select> element matching selector {selector}"),
+        }
+    }
+
+    fn wait_for(&self, selector: &str, timeout: Duration) -> Result<()> {
+        self.tab()
+            .wait_for_element_with_custom_timeout(selector, timeout)
+            .with_context(|| {
+                format!("no element matching {selector} appeared within {timeout:?}")
+            })?;
+        Ok(())
+    }
+
+    fn go_back(&self) -> Result<()> {
+        self.step_history(-1)
+    }
+
+    fn go_forward(&self) -> Result<()> {
+        self.step_history(1)
+    }
+
+    fn tabs(&self) -> Result<Vec<TabSummary>> {
+        let active = self.tab();
+        let tabs = self
+            .browser
+            .get_tabs()
+            .lock()
+            .map_err(|_| anyhow!("browser tab list mutex poisoned"))?
+            .clone();
+        Ok(tabs
+            .iter()
+            .enumerate()
+            .map(|(index, tab)| TabSummary {
+                index,
+                url: tab.get_url(),
+                title: tab.get_title().unwrap_or_default(),
+                active: Arc::ptr_eq(tab, &active),
+            })
+            .collect())
     }
+
+    /// Network capture and request blocking follow the driver to the new
+    /// tab; the switch fails rather than leave that tab unfiltered.
+    fn switch_tab(&self, index: usize) -> Result<()> {
+        let tab = self
+            .browser
+            .get_tabs()
+            .lock()
+            .map_err(|_| anyhow!("browser tab list mutex poisoned"))?
+            .get(index)
+            .cloned()
+            .with_context(|| format!("no tab at index {index}"))?;
+        self.instrument_tab(&tab)
+            .with_context(|| format!("failed to set up network options on tab {index}"))?;
+        tab.activate()
+            .with_context(|| format!("failed to activate tab {index}"))?;
+        *self.tab.lock().recover() = tab;
+        Ok(())
+    }
+
+    fn evaluate(&self, expression: &str) -> Result<serde_json::Value> {
+        // Indirect eval runs the source in global scope; the result travels
+        // back as a JSON string so objects survive the CDP round trip.
+        let script = format!(
+            "(async function(){{const v=await (0,eval)({source});\
+             const s=JSON.stringify(v);return s===undefined?'null':s;}})()",
+            source = serde_json::to_string(expression)?,
+        );
+        let value = self
+            .tab()
+            .evaluate(&script, true)
+            .context("failed to evaluate script")?
+            .value
+            .context("script returned no value")?;
+        let json = value
+            .as_str()
+            .context("evaluation did not return a JSON string")?;
+        serde_json::from_str(json).context("failed to parse evaluation result")
+    }
+
+    fn print_pdf(&self) -> Result<String> {
+        let pdf = self
+            .tab()
+            .print_to_pdf(None)
+            .context("failed to print page to PDF (PDF export needs a headless browser)")?;
+        let path = self
+            .artifacts_dir
+            .join(format!("page-{}.pdf", Uuid::new_v4()));
+        write_sealed(&path, &pdf, self.cipher.as_ref())
+            .with_context(|| format!("failed to write PDF to {}", path.display()))?;
+        Ok(path.display().to_string())
+    }
+}
+
+/// The wire name of a CDP enum value (e.g. `ResourceType::Xhr` -> `XHR`).
+fn enum_label<T: Serialize>(value: &T) -> String {
+    serde_json::to_value(value)
+        .ok()
+        .and_then(|value| value.as_str().map(str::to_string))
+        .unwrap_or_default()
 }
 
 /// Truncate `value` to at most `max` characters on a char boundary.
-fn truncate(value: &str, max: usize) -> String {
+pub(crate) fn truncate(value: &str, max: usize) -> String {
     if value.chars().count() <= max {
         return value.to_string();
     }
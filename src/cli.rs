@@ -1,23 +1,39 @@
-use std::{env, fs, path::PathBuf};
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+};
 
 use crate::{
     Launcher,
-    agent::BrowserAgent,
+    agent::{
+        AgentCheckpoint, BrowserAgent, CheckpointStore, ConfirmPolicy, DEFAULT_VERIFY_RETRIES,
+        SuccessCriterion,
+    },
     ai::{AiAttachment, AiAttachmentKind, AiChatPrompt, AiBridge, BlockingAiHttp},
+    audit::{AuditActor, AuditLog},
     automation::AutomationOrchestrator,
     browser::CdpBrowser,
-    config::{EngineKind, LaunchMode, LaunchRequest},
+    config::{
+        ArcSearchProviderConfig, ArcSearchProviderKind, AutomationSettings, EngineKind, LaunchMode,
+        LaunchRequest, LaunchSettings,
+    },
     crypto::DomainResolution,
+    grounding::PageFetcher,
+    local_index::{LocalIndex, profile_history_files},
+    network::NetworkOptions,
     profile::ProfileBadge,
+    research::{ResearchDepth, ResearchEvent, ResearchQuery},
     sync::SyncPhase,
-    transcript::TranscriptSource,
+    transcript::{TranscriptSearchQuery, TranscriptSource, parse_search_date},
+    transcript_bundle,
 };
-use anyhow::{Context, Result, bail};
-use clap::{ArgAction, Parser};
+use anyhow::{Context, Result, anyhow, bail};
+use clap::{ArgAction, ArgGroup, Parser};
 use tracing::info;
 
 #[derive(Parser, Debug)]
 #[command(name = "archon", author = "GhostKellz", version, about = "Hybrid Archon browser launcher", long_about = None)]
+#[command(group(ArgGroup::new("recipe_run").args(["automate", "automate_test"])))]
 pub struct Cli {
     /// Engine to launch (archon-edge, the Chromium Max build).
     #[arg(long, value_enum)]
@@ -67,6 +83,62 @@ pub struct Cli {
     #[arg(long, value_name = "COUNT", num_args = 0..=1, default_missing_value = "10")]
     pub transcripts: Option<usize>,
 
+    /// Full-text search recorded AI transcripts and exit. Every term must
+    /// match; a trailing `*` matches a prefix (e.g. `cert*`).
+    #[arg(long, value_name = "QUERY")]
+    pub transcripts_search: Option<String>,
+
+    /// Only conversations answered by this provider (--transcripts-search).
+    #[arg(long, value_name = "NAME", requires = "transcripts_search")]
+    pub transcripts_provider: Option<String>,
+
+    /// Only conversations from this source: cli, sidebar, host_api, arc_search
+    /// or unknown (--transcripts-search).
+    #[arg(long, value_name = "SOURCE", requires = "transcripts_search")]
+    pub transcripts_source: Option<String>,
+
+    /// Only messages sent on or after DATE (YYYY-MM-DD or RFC 3339).
+    #[arg(long, value_name = "DATE", requires = "transcripts_search")]
+    pub transcripts_since: Option<String>,
+
+    /// Only messages sent on or before DATE (YYYY-MM-DD or RFC 3339).
+    #[arg(long, value_name = "DATE", requires = "transcripts_search")]
+    pub transcripts_until: Option<String>,
+
+    /// Only conversations with attachments (or without, with `=false`).
+    #[arg(
+        long,
+        value_name = "BOOL",
+        num_args = 0..=1,
+        default_missing_value = "true",
+        requires = "transcripts_search"
+    )]
+    pub transcripts_attachments: Option<bool>,
+
+    /// Encrypt existing plaintext transcripts and attachments in place using
+    /// the configured transcripts_encryption key, then exit.
+    #[arg(long, action = ArgAction::SetTrue)]
+    pub transcripts_encrypt: bool,
+
+    /// Write conversation ID (or `all`) with its attachments to a transcript
+    /// bundle FILE and exit.
+    #[arg(long, num_args = 2, value_names = ["ID|all", "FILE"])]
+    pub transcripts_export: Option<Vec<String>>,
+
+    /// Import a transcript bundle and exit; conversations whose ID is already
+    /// taken get a new one.
+    #[arg(long, value_name = "FILE")]
+    pub transcripts_import: Option<PathBuf>,
+
+    /// Fork conversation ID after its Nth message (1-based) into a new
+    /// conversation and exit.
+    #[arg(long, num_args = 2, value_names = ["ID", "N"])]
+    pub transcripts_branch: Option<Vec<String>>,
+
+    /// Maximum number of conversations listed by --transcripts-search.
+    #[arg(long, value_name = "N", default_value_t = 20)]
+    pub transcripts_limit: usize,
+
     /// Resolve an ENS (.eth) or Unstoppable domain and exit.
     #[arg(long, value_name = "NAME")]
     pub resolve: Option<String>,
@@ -83,10 +155,51 @@ pub struct Cli {
     #[arg(long, value_name = "NAME")]
     pub chat_provider: Option<String>,
 
+    /// Recall similar messages from earlier recall-enabled conversations for
+    /// --chat and enable recall for this one (requires ai.recall.enabled).
+    #[arg(long, action = ArgAction::SetTrue, requires = "chat")]
+    pub chat_recall: bool,
+
     /// Perform an Arc web search with AI-grounded response and exit.
     #[arg(long, value_name = "QUERY")]
     pub search: Option<String>,
 
+    /// Arc provider for --search; `all` fuses every enabled provider.
+    #[arg(long, value_name = "NAME", requires = "search")]
+    pub search_provider: Option<String>,
+
+    /// Research QUESTION with iterative Arc searches, print the report and exit.
+    #[arg(long, value_name = "QUESTION")]
+    pub research: Option<String>,
+
+    /// Depth for --research: quick, standard, deep or exhaustive
+    /// (defaults to research.default_depth).
+    #[arg(long, value_name = "DEPTH", requires = "research")]
+    pub research_depth: Option<String>,
+
+    /// Ask --research as a follow-up in stored research session ID, with its
+    /// earlier findings as context.
+    #[arg(long, value_name = "ID", requires = "research")]
+    pub research_session: Option<String>,
+
+    /// List stored research sessions (optionally specify COUNT entries).
+    #[arg(long, value_name = "COUNT", num_args = 0..=1, default_missing_value = "10")]
+    pub research_sessions: Option<usize>,
+
+    /// Write research session ID as a Markdown report with a bibliography to
+    /// FILE and exit.
+    #[arg(long, num_args = 2, value_names = ["ID", "FILE"])]
+    pub research_export: Option<Vec<String>>,
+
+    /// Import the browsing history of every profile into the Arc local index
+    /// and exit.
+    #[arg(long, action = ArgAction::SetTrue)]
+    pub local_index_sync: bool,
+
+    /// Fetch URL and save its readable content to the Arc local index, then exit.
+    #[arg(long, value_name = "URL")]
+    pub local_index_save: Option<String>,
+
     /// Run the browser agent toward a natural-language GOAL and exit.
     #[arg(long, value_name = "GOAL")]
     pub agent: Option<String>,
@@ -120,12 +233,49 @@ pub struct Cli {
     #[arg(long, value_name = "NAME")]
     pub agent_provider: Option<String>,
 
+    /// Record the run's network traffic to a HAR file next to its artifacts
+    /// (also enables the `extract_response` action); see automation.capture_network.
+    #[arg(long, action = ArgAction::SetTrue)]
+    pub agent_har: bool,
+
+    /// Verify the goal before reporting success. Without --agent-success a
+    /// second model call judges the final page; a rejected finish is retried.
+    #[arg(long, action = ArgAction::SetTrue)]
+    pub agent_verify: bool,
+
+    /// Explicit success criterion (repeatable; implies --agent-verify):
+    /// `text:<TEXT>`, `url:<GLOB>`, `element:<SELECTOR>` or `element:<SELECTOR>=<TEXT>`.
+    #[arg(long, value_name = "CRITERION")]
+    pub agent_success: Vec<String>,
+
+    /// Resume an interrupted or paused agent run from its last checkpoint.
+    /// Honors --agent-execute/-yes/-headful/-attach/-provider.
+    #[arg(long, value_name = "RUN_ID", conflicts_with = "agent")]
+    pub agent_resume: Option<String>,
+
     /// Run a hybrid automation RECIPE (explicit actions + agent goals) and exit.
     /// Accepts a path or a bare name resolved under automation/recipes/<NAME>.json.
     /// Honors --agent-execute/-yes/-headful/-attach/-provider/-max-steps.
     #[arg(long, value_name = "RECIPE")]
     pub automate: Option<String>,
 
+    /// Run every recipe (*.json) in DIR as a smoke test, write JUnit XML and a
+    /// Markdown summary, and exit nonzero if any recipe fails or an assertion
+    /// does not hold. Honors --agent-execute/-yes/-headful/-attach/-provider/-max-steps.
+    #[arg(long, value_name = "DIR", conflicts_with = "automate")]
+    pub automate_test: Option<PathBuf>,
+
+    /// Directory for --automate-test reports and run transcripts
+    /// (default: <transcripts>/recipe-tests/<timestamp>).
+    #[arg(long, value_name = "DIR", requires = "automate_test")]
+    pub test_report: Option<PathBuf>,
+
+    /// Recipe parameter for --automate or --automate-test (repeatable):
+    /// `KEY=VALUE`. Under --automate-test each recipe receives the
+    /// parameters it declares.
+    #[arg(long = "param", value_name = "KEY=VALUE", requires = "recipe_run")]
+    pub params: Vec<String>,
+
     /// Also export the agent/recipe run transcript (JSON + Markdown) to DIR.
     #[arg(long, value_name = "DIR")]
     pub agent_export: Option<PathBuf>,
@@ -135,12 +285,24 @@ pub struct Cli {
     #[arg(long, action = ArgAction::SetTrue)]
     pub mcp: bool,
 
+    /// Verify the automation audit log's hash chain (default location, or
+    /// PATH) and exit; fails if entries were edited, removed or truncated.
+    #[arg(long, value_name = "PATH", num_args = 0..=1)]
+    pub audit_verify: Option<Option<PathBuf>>,
+
     /// Run the Conduit per-site JS/CSS injector against the running Archon
     /// browser (requires automation.remote_debug_port != 0). Attaches over CDP
     /// and injects local userscripts/userstyles until interrupted.
     #[arg(long, action = ArgAction::SetTrue)]
     pub conduit: bool,
 
+    /// Record a recipe from the running Archon browser (requires
+    /// automation.remote_debug_port != 0): captures clicks, typed values and
+    /// navigations until Ctrl-C, then writes automation/recipes/<NAME>.json
+    /// (or NAME itself when it is a .json path).
+    #[arg(long, value_name = "NAME")]
+    pub record: Option<String>,
+
     /// List N8N workflows from the configured instance and exit.
     #[arg(long, action = ArgAction::SetTrue)]
     pub n8n_list: bool,
@@ -437,6 +599,151 @@ fn print_transcripts(launcher: &Launcher, limit: usize) -> Result<()> {
     Ok(())
 }
 
+fn print_research_event(event: &ResearchEvent) {
+    match event {
+        ResearchEvent::Planning => println!("Planning sub-questions..."),
+        ResearchEvent::Searching {
+            iteration,
+            questions,
+        } => println!(
+            "Round {iteration}: searching {} question(s)",
+            questions.len()
+        ),
+        ResearchEvent::Searched {
+            question,
+            results,
+            error,
+            ..
+        } => match error {
+            Some(error) => println!("  {question}: failed ({error})"),
+            None => println!("  {question}: {results} result(s)"),
+        },
+        ResearchEvent::Reading { sources, .. } => {
+            println!("Reviewing {sources} source(s) for gaps...")
+        }
+        ResearchEvent::Gaps { gaps, complete, .. } => {
+            if *complete || gaps.is_empty() {
+                println!("Coverage looks complete");
+            } else {
+                for gap in gaps {
+                    println!("  gap: {gap}");
+                }
+            }
+        }
+        ResearchEvent::Synthesizing { sources } => {
+            println!("Writing report from {sources} source(s)...")
+        }
+    }
+}
+
+/// Open the index of the first `local-index` Arc provider (or the default one).
+fn open_local_index(settings: &LaunchSettings) -> Result<LocalIndex> {
+    let provider = settings
+        .arc
+        .providers
+        .iter()
+        .find(|provider| provider.kind == ArcSearchProviderKind::LocalIndex)
+        .cloned()
+        .unwrap_or_else(ArcSearchProviderConfig::local_index_default);
+    LocalIndex::open(provider.resolve_local_index()?)
+}
+
+fn export_transcripts(launcher: &Launcher, selection: &str, path: &Path) -> Result<()> {
+    let transcripts = launcher.transcripts();
+    let ids = if selection.eq_ignore_ascii_case("all") {
+        transcripts
+            .list()?
+            .into_iter()
+            .map(|summary| summary.id)
+            .collect()
+    } else {
+        let id = uuid::Uuid::parse_str(selection)
+            .with_context(|| format!("invalid transcript id '{selection}'"))?;
+        if !transcripts.contains(id) {
+            bail!("transcript {id} not found");
+        }
+        vec![id]
+    };
+    if ids.is_empty() {
+        bail!("no transcripts to export");
+    }
+    let manifest = transcript_bundle::export_bundle(&transcripts, &ids, path)?;
+    let files: usize = manifest
+        .conversations
+        .iter()
+        .map(|conversation| conversation.files.len())
+        .sum();
+    println!(
+        "Exported {} conversation(s), {files} file(s) to {}",
+        manifest.conversations.len(),
+        path.display()
+    );
+    if transcripts.is_encrypted() {
+        println!(
+            "Note: bundles are not encrypted; protect {} accordingly.",
+            path.display()
+        );
+    }
+    Ok(())
+}
+
+fn search_transcripts(launcher: &Launcher, cli: &Cli, text: &str) -> Result<()> {
+    let mut query = TranscriptSearchQuery::new(text).with_limit(cli.transcripts_limit);
+    if let Some(provider) = &cli.transcripts_provider {
+        query = query.with_provider(provider);
+    }
+    if let Some(source) = &cli.transcripts_source {
+        let source = source.parse::<TranscriptSource>().map_err(|_| {
+            anyhow!(
+                "unknown transcript source '{source}' (expected cli, sidebar, host_api, arc_search or unknown)"
+            )
+        })?;
+        query = query.with_source(source);
+    }
+    let since = cli
+        .transcripts_since
+        .as_deref()
+        .map(|value| parse_search_date(value, false))
+        .transpose()?;
+    let until = cli
+        .transcripts_until
+        .as_deref()
+        .map(|value| parse_search_date(value, true))
+        .transpose()?;
+    query = query.with_range(since, until);
+    if let Some(attachments) = cli.transcripts_attachments {
+        query = query.with_attachments(attachments);
+    }
+
+    let transcripts = launcher.transcripts();
+    let hits = transcripts.search(&query)?;
+    if hits.is_empty() {
+        println!("No transcripts match '{text}'.");
+        return Ok(());
+    }
+
+    println!("{} conversation(s) match '{text}':", hits.len());
+    println!();
+    for hit in hits {
+        println!("- {}", hit.title);
+        println!("    id        : {}", hit.id);
+        println!("    source    : {}", hit.source);
+        match &hit.provider {
+            Some(provider) => println!("    match     : {} ({provider})", hit.role),
+            None => println!("    match     : {}", hit.role),
+        }
+        println!("    sent      : {}", hit.timestamp.to_rfc3339());
+        println!("    snippet   : {}", hit.snippet.replace('\n', " "));
+        println!(
+            "    markdown  : {}",
+            transcripts.markdown_path(hit.id).display()
+        );
+        println!();
+    }
+
+    Ok(())
+}
+
 fn print_diagnostics(launcher: &Launcher) -> Result<()> {
     let report = launcher.diagnostics()?;
     let crate::DiagnosticsReport {
@@ -933,7 +1240,13 @@ fn print_diagnostics(launcher: &Launcher) -> Result<()> {
     Ok(())
 }
 
-fn run_agent(launcher: &Launcher, cli: &Cli, goal: &str) -> Result<()> {
+/// How `run_agent` starts the loop: a fresh goal or a saved checkpoint.
+enum AgentStart<'a> {
+    Goal(&'a str),
+    Resume(Box<AgentCheckpoint>),
+}
+
+fn run_agent(launcher: &Launcher, cli: &Cli, start: AgentStart<'_>) -> Result<()> {
     let settings = launcher.settings();
 
     if cli.agent_execute && !settings.automation.enabled {
@@ -946,12 +1259,15 @@ fn run_agent(launcher: &Launcher, cli: &Cli, goal: &str) -> Result<()> {
     let transcripts = launcher.ai().transcript_store();
     let transcript_root = transcripts.root().to_path_buf();
     let agent_transcript_dir = transcript_root.join("agents");
+    let checkpoints = CheckpointStore::for_transcripts(&transcripts);
     let artifacts_dir = transcript_root.join("agent-artifacts");
+    let cipher = transcripts.cipher().cloned();
 
     let ai = std::sync::Arc::new(AiBridge::from_settings(&settings.ai, transcripts));
-    let orchestrator = std::sync::Arc::new(AutomationOrchestrator::from_settings(
-        settings.automation.clone(),
-        ai,
+    let orchestrator = std::sync::Arc::new(audited(
+        AutomationOrchestrator::from_settings(settings.automation.clone(), ai),
+        launcher,
+        AuditActor::Cli,
     ));
 
     let mode = if cli.agent_execute {
@@ -959,11 +1275,29 @@ fn run_agent(launcher: &Launcher, cli: &Cli, goal: &str) -> Result<()> {
     } else {
         "preview (dry-run)"
     };
-    println!("Agent goal: {goal}");
-    println!("Mode: {mode} | max steps: {}", cli.agent_max_steps);
-    if let Some(url) = &cli.agent_url {
-        println!("Start URL: {url}");
-    }
+    let run_id = match &start {
+        AgentStart::Goal(goal) => {
+            println!("Agent goal: {goal}");
+            println!("Mode: {mode} | max steps: {}", cli.agent_max_steps);
+            if let Some(url) = &cli.agent_url {
+                println!("Start URL: {url}");
+            }
+            uuid::Uuid::new_v4()
+        }
+        AgentStart::Resume(checkpoint) => {
+            println!("Agent goal: {}", checkpoint.goal);
+            println!(
+                "Mode: {mode} | resuming after {} of {} step(s)",
+                checkpoint.steps.len(),
+                checkpoint.max_steps
+            );
+            if let Some(url) = &checkpoint.current_url {
+                println!("Resume URL: {url}");
+            }
+            checkpoint.id
+        }
+    };
+    println!("Run ID: {run_id} (resume with --agent-resume {run_id})");
 
     let driver = if cli.agent_attach {
         let port = settings.automation.remote_debug_port;
@@ -984,29 +1318,51 @@ fn run_agent(launcher: &Launcher, cli: &Cli, goal: &str) -> Result<()> {
     } else {
         CdpBrowser::launch(cli.agent_headful, artifacts_dir)
             .context("failed to launch the agent browser (is Chromium installed?)")?
-    };
+    }
+    .with_encryption(cipher.clone())
+    .with_network(&agent_network_options(cli, &settings.automation))?;
 
-    let agent = BrowserAgent::new(
+    let mut agent = BrowserAgent::new(
         orchestrator,
         cli.agent_max_steps,
         cli.agent_execute,
         cli.agent_yes,
         Some(agent_transcript_dir),
-    );
+    )
+    .with_checkpoint_store(checkpoints)
+    .with_run_id(run_id)
+    .with_encryption(cipher);
+    if cli.agent_verify || !cli.agent_success.is_empty() {
+        let criteria = cli
+            .agent_success
+            .iter()
+            .map(|spec| SuccessCriterion::parse(spec))
+            .collect::<Result<Vec<_>>>()?;
+        agent = agent.with_verification(criteria, DEFAULT_VERIFY_RETRIES);
+    }
 
     let http = BlockingAiHttp::default();
     let cancel = std::sync::atomic::AtomicBool::new(false);
-    let outcome = agent.run(
-        goal,
-        cli.agent_url.as_deref(),
-        &driver,
-        cli.agent_provider.as_deref(),
-        &http,
-        &cancel,
-    )?;
+    let outcome = match start {
+        AgentStart::Goal(goal) => agent.run(
+            goal,
+            cli.agent_url.as_deref(),
+            &driver,
+            cli.agent_provider.as_deref(),
+            &http,
+            &cancel,
+        )?,
+        AgentStart::Resume(checkpoint) => agent.resume(
+            *checkpoint,
+            &driver,
+            cli.agent_provider.as_deref(),
+            &http,
+            &cancel,
+        )?,
+    };
 
     if let Some(dir) = &cli.agent_export {
-        crate::agent::persist_outcome(dir, &outcome);
+        crate::agent::persist_outcome(dir, &outcome, None);
         println!("Exported transcript (JSON + Markdown) to {}", dir.display());
     }
 
@@ -1043,10 +1399,63 @@ fn run_agent(launcher: &Launcher, cli: &Cli, goal: &str) -> Result<()> {
         },
         outcome.summary
     );
+    if let Some(verdict) = &outcome.verification {
+        println!(
+            "Verification {} after {} attempt(s):",
+            if verdict.passed { "passed" } else { "failed" },
+            verdict.attempts
+        );
+        for detail in &verdict.details {
+            println!("  - {detail}");
+        }
+    }
+    if let Some(har) = &outcome.har {
+        println!("Network HAR: {har}");
+    }
 
     Ok(())
 }
 
+/// Network capture for agent and recipe runs: the automation settings, with
+/// `--agent-har` forcing capture on.
+fn agent_network_options(cli: &Cli, settings: &AutomationSettings) -> NetworkOptions {
+    let mut options = NetworkOptions::from_settings(settings);
+    options.capture |= cli.agent_har;
+    options
+}
+
+fn run_audit_verify(path: &std::path::Path) -> Result<()> {
+    let report = crate::audit::verify_audit_log(path)?;
+    println!("Audit log: {}", path.display());
+    println!("Entries: {}", report.entries);
+    if let Some(head) = &report.head {
+        println!("Head: {head}");
+    }
+    if report.is_intact() {
+        println!("Chain intact.");
+        return Ok(());
+    }
+    for issue in &report.issues {
+        println!("  - {issue}");
+    }
+    bail!(
+        "audit log failed verification ({} issue(s))",
+        report.issues.len()
+    );
+}
+
+/// Attach the configured audit log (if enabled) to `orchestrator`.
+fn audited(
+    orchestrator: AutomationOrchestrator,
+    launcher: &Launcher,
+    actor: AuditActor,
+) -> AutomationOrchestrator {
+    match AuditLog::from_settings(launcher.settings()) {
+        Some(log) => orchestrator.with_audit(std::sync::Arc::new(log), actor),
+        None => orchestrator,
+    }
+}
+
 fn run_automate(launcher: &Launcher, cli: &Cli, recipe_path: &str) -> Result<()> {
     let settings = launcher.settings();
 
@@ -1058,16 +1467,19 @@ fn run_automate(launcher: &Launcher, cli: &Cli, recipe_path: &str) -> Result<()>
     }
 
     let recipe = crate::recipe::load_recipe(recipe_path)?;
+    let vars = recipe.resolve_params(&crate::recipe::parse_param_args(&cli.params)?)?;
 
     let transcripts = launcher.ai().transcript_store();
     let transcript_root = transcripts.root().to_path_buf();
     let agent_transcript_dir = transcript_root.join("agents");
     let artifacts_dir = transcript_root.join("agent-artifacts");
+    let cipher = transcripts.cipher().cloned();
 
     let ai = std::sync::Arc::new(AiBridge::from_settings(&settings.ai, transcripts));
-    let orchestrator = std::sync::Arc::new(AutomationOrchestrator::from_settings(
-        settings.automation.clone(),
-        ai,
+    let orchestrator = std::sync::Arc::new(audited(
+        AutomationOrchestrator::from_settings(settings.automation.clone(), ai),
+        launcher,
+        AuditActor::Recipe,
     ));
 
     let mode = if cli.agent_execute {
@@ -1080,45 +1492,30 @@ fn run_automate(launcher: &Launcher, cli: &Cli, recipe_path: &str) -> Result<()>
     if let Some(url) = &recipe.start_url {
         println!("Start URL: {url}");
     }
+    for (name, value) in vars.redacted() {
+        println!("Param {name} = {value}");
+    }
 
-    let driver = if cli.agent_attach {
-        let port = settings.automation.remote_debug_port;
-        let user_data_dir = settings
-            .resolve_profile_root()
-            .ok()
-            .map(|root| root.join(&cli.profile));
-        let ws_url = CdpBrowser::devtools_ws_url(port, user_data_dir.as_deref()).with_context(|| {
-            format!(
-                "could not find a debuggable Archon browser on port {port}. \
-                 Launch Archon first (e.g. `archon --engine edge --execute`) so it \
-                 exposes the CDP port, then retry with --agent-attach."
-            )
-        })?;
-        println!("Attaching to running browser at {ws_url}");
-        CdpBrowser::connect(&ws_url, artifacts_dir)
-            .context("failed to attach to the running Archon browser")?
-    } else {
-        CdpBrowser::launch(cli.agent_headful, artifacts_dir)
-            .context("failed to launch the agent browser (is Chromium installed?)")?
-    };
+    let driver = recipe_driver(launcher, cli, artifacts_dir)?;
 
     let http = BlockingAiHttp::default();
     let cancel = std::sync::atomic::AtomicBool::new(false);
     let outcome = crate::recipe::run_recipe(
         &recipe,
+        &vars,
         orchestrator,
         cli.agent_max_steps,
         cli.agent_execute,
-        cli.agent_yes,
+        ConfirmPolicy::from_auto_confirm(cli.agent_yes),
         &driver,
         cli.agent_provider.as_deref(),
         &http,
         &cancel,
     )?;
 
-    crate::agent::persist_outcome(&agent_transcript_dir, &outcome);
+    crate::agent::persist_outcome(&agent_transcript_dir, &outcome, cipher.as_ref());
     if let Some(dir) = &cli.agent_export {
-        crate::agent::persist_outcome(dir, &outcome);
+        crate::agent::persist_outcome(dir, &outcome, None);
         println!("Exported transcript (JSON + Markdown) to {}", dir.display());
     }
 
@@ -1134,8 +1531,9 @@ fn run_automate(launcher: &Launcher, cli: &Cli, recipe_path: &str) -> Result<()>
             .as_deref()
             .or(step.action.value.as_deref())
             .unwrap_or("");
+        let label = step.path.as_deref().unwrap_or_default();
         println!(
-            "  {}. {:?} {} [{status}]",
+            "  {}. ({label}) {:?} {} [{status}]",
             step.index, step.action.action_type, detail
         );
         if let Some(data) = &step.result.data {
@@ -1155,10 +1553,145 @@ fn run_automate(launcher: &Launcher, cli: &Cli, recipe_path: &str) -> Result<()>
         },
         outcome.summary
     );
+    if let Some(har) = &outcome.har {
+        println!("Network HAR: {har}");
+    }
 
     Ok(())
 }
 
+/// Launch (or, with --agent-attach, attach to) the browser recipes run in.
+fn recipe_driver(launcher: &Launcher, cli: &Cli, artifacts_dir: PathBuf) -> Result<CdpBrowser> {
+    let settings = launcher.settings();
+    let driver = if cli.agent_attach {
+        let port = settings.automation.remote_debug_port;
+        let user_data_dir = settings
+            .resolve_profile_root()
+            .ok()
+            .map(|root| root.join(&cli.profile));
+        let ws_url = CdpBrowser::devtools_ws_url(port, user_data_dir.as_deref()).with_context(|| {
+            format!(
+                "could not find a debuggable Archon browser on port {port}. \
+                 Launch Archon first (e.g. `archon --engine edge --execute`) so it \
+                 exposes the CDP port, then retry with --agent-attach."
+            )
+        })?;
+        println!("Attaching to running browser at {ws_url}");
+        CdpBrowser::connect(&ws_url, artifacts_dir)
+            .context("failed to attach to the running Archon browser")?
+    } else {
+        CdpBrowser::launch(cli.agent_headful, artifacts_dir)
+            .context("failed to launch the agent browser (is Chromium installed?)")?
+    };
+    driver
+        .with_encryption(launcher.ai().transcript_store().cipher().cloned())
+        .with_network(&agent_network_options(cli, &settings.automation))
+}
+
+fn run_automate_test(launcher: &Launcher, cli: &Cli, dir: &std::path::Path) -> Result<()> {
+    use crate::recipe_suite::{CaseStatus, JUNIT_FILE, SUMMARY_FILE};
+
+    let settings = launcher.settings();
+    if cli.agent_execute && !settings.automation.enabled {
+        bail!(
+            "--agent-execute requires automation to be enabled in config \
+             (set automation.enabled = true). Omit --agent-execute for a dry-run preview."
+        );
+    }
+
+    let transcripts = launcher.ai().transcript_store();
+    let transcript_root = transcripts.root().to_path_buf();
+    let agent_transcript_dir = transcript_root.join("agents");
+    let report_dir = cli.test_report.clone().unwrap_or_else(|| {
+        transcript_root
+            .join("recipe-tests")
+            .join(chrono::Utc::now().format("%Y%m%d-%H%M%S").to_string())
+    });
+    let cipher = transcripts.cipher().cloned();
+    // Reports under the transcript directory follow its encryption; an
+    // explicit --test-report directory is the user's to read as-is.
+    let report_cipher = cipher.as_ref().filter(|_| cli.test_report.is_none());
+
+    let ai = std::sync::Arc::new(AiBridge::from_settings(&settings.ai, transcripts));
+    let orchestrator = std::sync::Arc::new(audited(
+        AutomationOrchestrator::from_settings(settings.automation.clone(), ai),
+        launcher,
+        AuditActor::Recipe,
+    ));
+
+    println!("Recipe tests: {}", dir.display());
+    if !cli.agent_execute {
+        println!(
+            "Mode: preview (dry-run) — assertions are skipped; pass --agent-execute to run them"
+        );
+    }
+    let params = crate::recipe::parse_param_args(&cli.params)?;
+    let http = BlockingAiHttp::default();
+    let cancel = std::sync::atomic::AtomicBool::new(false);
+
+    let report = crate::recipe_suite::run_suite(dir, |_, recipe| {
+        let overrides = params
+            .iter()
+            .filter(|(name, _)| recipe.params.contains_key(*name))
+            .map(|(name, value)| (name.clone(), value.clone()))
+            .collect();
+        let vars = recipe.resolve_params(&overrides)?;
+        // A driver per case, so cookies, tabs and network logs never leak
+        // from one recipe into the next.
+        let driver = recipe_driver(launcher, cli, transcript_root.join("agent-artifacts"))?;
+        let outcome = crate::recipe::run_recipe(
+            recipe,
+            &vars,
+            orchestrator.clone(),
+            cli.agent_max_steps,
+            cli.agent_execute,
+            ConfirmPolicy::from_auto_confirm(cli.agent_yes),
+            &driver,
+            cli.agent_provider.as_deref(),
+            &http,
+            &cancel,
+        )?;
+        crate::agent::persist_outcome(&agent_transcript_dir, &outcome, cipher.as_ref());
+        crate::agent::persist_outcome(&report_dir, &outcome, report_cipher);
+        Ok(outcome)
+    })?;
+    report.write(&report_dir)?;
+
+    for case in &report.cases {
+        let status = match case.status() {
+            CaseStatus::Passed => "PASS",
+            CaseStatus::Failed => "FAIL",
+            CaseStatus::Skipped => "SKIP",
+            CaseStatus::Errored => "ERROR",
+        };
+        println!("  [{status}] {} ({})", case.name, case.path.display());
+        if let Some(reason) = case.failure_reason() {
+            println!("      {reason}");
+        }
+    }
+    println!(
+        "\n{} passed, {} failed, {} errored, {} skipped",
+        report.count(CaseStatus::Passed),
+        report.count(CaseStatus::Failed),
+        report.count(CaseStatus::Errored),
+        report.count(CaseStatus::Skipped),
+    );
+    println!(
+        "Reports: {} and {}",
+        report_dir.join(JUNIT_FILE).display(),
+        report_dir.join(SUMMARY_FILE).display()
+    );
+
+    if !report.succeeded() {
+        bail!(
+            "{} of {} recipe test(s) failed",
+            report.count(CaseStatus::Failed) + report.count(CaseStatus::Errored),
+            report.cases.len()
+        );
+    }
+    Ok(())
+}
+
 fn run_mcp(launcher: &Launcher, cli: &Cli) -> Result<()> {
     use crate::mcp_server::{BrowserToolbox, serve_stdin};
 
@@ -1168,10 +1701,11 @@ fn run_mcp(launcher: &Launcher, cli: &Cli) -> Result<()> {
     let agent_transcript_dir = transcript_root.join("agents");
     let artifacts_dir = transcript_root.join("agent-artifacts");
 
-    let ai = std::sync::Arc::new(AiBridge::from_settings(&settings.ai, transcripts));
-    let orchestrator = std::sync::Arc::new(AutomationOrchestrator::from_settings(
-        settings.automation.clone(),
-        ai,
+    let ai = std::sync::Arc::new(AiBridge::from_settings(&settings.ai, transcripts.clone()));
+    let orchestrator = std::sync::Arc::new(audited(
+        AutomationOrchestrator::from_settings(settings.automation.clone(), ai),
+        launcher,
+        AuditActor::Mcp,
     ));
 
     // Browser lifecycle captured for the lazy driver factory: either attach to
@@ -1184,6 +1718,8 @@ fn run_mcp(launcher: &Launcher, cli: &Cli) -> Result<()> {
         .ok()
         .map(|root| root.join(&cli.profile));
     let factory_artifacts = artifacts_dir.clone();
+    let network = agent_network_options(cli, &settings.automation);
+    let cipher = transcripts.cipher().cloned();
 
     let driver_factory: crate::mcp_server::DriverFactory = Box::new(move || {
         let driver: Box<dyn crate::browser::BrowserDriver> = if attach {
@@ -1195,19 +1731,31 @@ fn run_mcp(launcher: &Launcher, cli: &Cli) -> Result<()> {
                          exposes the CDP port, then run the MCP server with --agent-attach."
                     )
                 })?;
-            Box::new(CdpBrowser::connect(&ws_url, factory_artifacts.clone())?)
+            Box::new(
+                CdpBrowser::connect(&ws_url, factory_artifacts.clone())?
+                    .with_encryption(cipher.clone())
+                    .with_network(&network)?,
+            )
         } else {
-            Box::new(CdpBrowser::launch(headful, factory_artifacts.clone())?)
+            Box::new(
+                CdpBrowser::launch(headful, factory_artifacts.clone())?
+                    .with_encryption(cipher.clone())
+                    .with_network(&network)?,
+            )
         };
         Ok(driver)
     });
 
+    let recipe_dir = crate::recipe::recipes_dir();
     let toolbox = BrowserToolbox::new(
         orchestrator,
         Some(agent_transcript_dir),
         cli.agent_provider.clone(),
         driver_factory,
-    );
+    )
+    .with_transcripts(transcripts)
+    .with_recipe_dir(recipe_dir)
+    .with_summarizer(launcher.summarize().clone());
 
     info!(
         attach,
@@ -1273,6 +1821,92 @@ fn run_conduit(launcher: &Launcher, cli: &Cli) -> Result<()> {
     service.run(&cancel)
 }
 
+fn run_record(launcher: &Launcher, cli: &Cli, target: &str) -> Result<()> {
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    use crate::recorder::{RecipeRecorder, RecorderSession};
+
+    let settings = launcher.settings();
+    let port = settings.automation.remote_debug_port;
+    if port == 0 {
+        bail!(
+            "--record requires automation.remote_debug_port != 0 so it can attach over CDP; \
+             set a port (default 9222) and launch Archon with it."
+        );
+    }
+
+    let base = env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
+    let path = crate::recorder::output_path(&base, target);
+    if path.exists() {
+        bail!(
+            "{} already exists; record under another name",
+            path.display()
+        );
+    }
+    let name = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_else(|| target.to_string());
+
+    let user_data_dir = settings
+        .resolve_profile_root()
+        .ok()
+        .map(|root| root.join(&cli.profile));
+    let ws_url =
+        CdpBrowser::devtools_ws_url(port, user_data_dir.as_deref()).with_context(|| {
+            format!(
+                "could not find a debuggable Archon browser on port {port}. Launch Archon first \
+             (e.g. `archon --engine edge --execute`) so it exposes the CDP port, then run --record."
+            )
+        })?;
+    let session = RecorderSession::attach(&ws_url)?;
+    let mut recorder = RecipeRecorder::new(name, Some(session.current_url()));
+
+    let cancel = Arc::new(AtomicBool::new(false));
+    let signal_cancel = cancel.clone();
+    std::thread::spawn(move || {
+        if let Ok(rt) = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+        {
+            let _ = rt.block_on(tokio::signal::ctrl_c());
+        }
+        signal_cancel.store(true, Ordering::Relaxed);
+    });
+
+    println!(
+        "Recording on port {port} (starting at {}). Use the browser, then press Ctrl-C to save.",
+        session.current_url()
+    );
+    while !cancel.load(Ordering::Relaxed) {
+        if let Some((event, at)) = session.next_event(std::time::Duration::from_millis(200))
+            && let Some(line) = recorder.record(event, at)
+        {
+            println!("  + {line}");
+        }
+    }
+    // Drain anything reported while Ctrl-C was being handled.
+    while let Some((event, at)) = session.next_event(std::time::Duration::from_millis(50)) {
+        recorder.record(event, at);
+    }
+
+    recorder.write(&path)?;
+    println!("\nSaved recipe to {}", path.display());
+    let secrets: Vec<&str> = recorder.secret_params().collect();
+    if !secrets.is_empty() {
+        println!(
+            "Sensitive fields were stored as secret parameters: {}",
+            secrets.join(", ")
+        );
+        println!(
+            "Pass them at run time with --param NAME=VALUE or the ARCHON_RECIPE_<NAME> environment variables."
+        );
+    }
+    println!("Replay with: archon --automate {target} --agent-attach --agent-execute");
+    Ok(())
+}
+
 pub fn run() -> Result<()> {
     let cli = Cli::parse();
     let config_path = match cli.config.clone() {
@@ -1297,6 +1931,21 @@ pub fn run() -> Result<()> {
         return Ok(());
     }
 
+    if let Some(target) = cli.record.clone() {
+        if target.trim().is_empty() {
+            bail!("--record requires a recipe name or path");
+        }
+        return run_record(&launcher, &cli, &target);
+    }
+
+    if let Some(path) = &cli.audit_verify {
+        let path = match path {
+            Some(path) => path.clone(),
+            None => launcher.settings().resolve_audit_log()?,
+        };
+        return run_audit_verify(&path);
+    }
+
     if cli.sync_ghostdns_policy {
         let report = launcher.sync_ghostdns_policy(cli.force)?;
         report_config_action("GhostDNS", &report.ghostdns, cli.force);
@@ -1441,7 +2090,13 @@ pub fn run() -> Result<()> {
             bail!("--chat requires a prompt or at least one --attach");
         }
 
+        if cli.chat_recall && !launcher.settings().ai.recall.enabled {
+            eprintln!(
+                "note: ai.recall.enabled is false; the conversation is marked for recall but nothing is recalled or embedded"
+            );
+        }
         let prompt = AiChatPrompt::with_attachments(prompt_text, attachments)
+            .with_recall(cli.chat_recall.then_some(true))
             .with_source(TranscriptSource::Cli);
         let provider = cli.chat_provider.as_deref();
         let response = launcher.chat_with_prompt(provider, prompt)?;
@@ -1470,7 +2125,21 @@ pub fn run() -> Result<()> {
             bail!("Arc search is not enabled; configure arc settings in config.json");
         }
         println!("Searching: {query}");
-        let result = launcher.arc_search(&query)?;
+        let result = match cli.search_provider.as_deref() {
+            Some(provider) => launcher
+                .arc()
+                .grounded_search_with_provider(&query, Some(provider))?,
+            None => launcher.arc_search(&query)?,
+        };
+        for outcome in &result.providers {
+            match &outcome.error {
+                Some(error) => println!("  {}: failed ({error})", outcome.provider),
+                None => println!(
+                    "  {}: {} result(s) in {} ms",
+                    outcome.provider, outcome.results, outcome.latency_ms
+                ),
+            }
+        }
         println!("\n{}\n", result.context.trim());
         if !result.citations.is_empty() {
             println!("Sources:");
@@ -1481,6 +2150,115 @@ pub fn run() -> Result<()> {
         return Ok(());
     }
 
+    if let Some(question) = cli.research.clone() {
+        if question.trim().is_empty() {
+            bail!("--research requires a question");
+        }
+        if !launcher.arc().is_enabled() {
+            bail!("Arc search is not enabled; configure arc settings in config.json");
+        }
+        let depth_name = cli
+            .research_depth
+            .clone()
+            .unwrap_or_else(|| launcher.research().settings().default_depth.clone());
+        let depth: ResearchDepth = depth_name
+            .parse()
+            .map_err(|_| anyhow!("unknown research depth '{depth_name}'"))?;
+        let research = launcher.research();
+        let mut session = match cli.research_session.as_deref() {
+            Some(id) => {
+                let id = uuid::Uuid::parse_str(id.trim())
+                    .with_context(|| format!("invalid research session id '{id}'"))?;
+                research.resume_session(id)?
+            }
+            None => research.create_session(&question),
+        };
+        let query = ResearchQuery::new(question).with_depth(depth);
+        let report =
+            research.research_in_session(&mut session, &query, &mut print_research_event)?;
+
+        println!("\n{}\n", report.summary.trim());
+        for finding in &report.findings {
+            println!("  - {}", finding.statement);
+        }
+        if !report.sources.is_empty() {
+            println!("\nSources:");
+            for (i, source) in report.sources.iter().enumerate() {
+                println!("  [{}] {} - {}", i + 1, source.title, source.url);
+            }
+        }
+        if !report.related_questions.is_empty() {
+            println!("\nRelated questions:");
+            for related in &report.related_questions {
+                println!("  - {related}");
+            }
+        }
+        println!("\n{}", report.methodology);
+        if research.persists_sessions() {
+            println!("Session: {}", session.id);
+        }
+        return Ok(());
+    }
+
+    if let Some(limit) = cli.research_sessions {
+        let Some(store) = launcher.research().sessions() else {
+            bail!("research sessions are not stored");
+        };
+        let sessions = store.list()?;
+        if sessions.is_empty() {
+            println!("No research sessions under {}", store.root().display());
+        }
+        for summary in sessions.iter().take(limit) {
+            println!("  {} {}", summary.id, summary.topic);
+            println!(
+                "    {} question(s), {} source(s), updated {}",
+                summary.queries,
+                summary.sources,
+                summary.updated_at.to_rfc3339()
+            );
+        }
+        return Ok(());
+    }
+
+    if let Some(args) = cli.research_export.as_deref() {
+        let id = uuid::Uuid::parse_str(args[0].trim())
+            .with_context(|| format!("invalid research session id '{}'", args[0]))?;
+        let session = launcher.research().resume_session(id)?;
+        let path = Path::new(&args[1]);
+        fs::write(path, session.to_markdown())
+            .with_context(|| format!("failed to write {}", path.display()))?;
+        println!("Exported research session {id} to {}", path.display());
+        return Ok(());
+    }
+
+    if cli.local_index_sync {
+        let index = open_local_index(launcher.settings())?;
+        let profile_root = launcher.settings().resolve_profile_root()?;
+        let histories = profile_history_files(&profile_root);
+        if histories.is_empty() {
+            println!("No browser history found under {}", profile_root.display());
+        }
+        for history in histories {
+            let imported = index.import_history(&history)?;
+            println!(
+                "Imported {imported} history entries from {}",
+                history.display()
+            );
+        }
+        println!("Local index: {}", index.path().display());
+        return Ok(());
+    }
+
+    if let Some(url) = cli.local_index_save.clone() {
+        let index = open_local_index(launcher.settings())?;
+        let fetcher = PageFetcher::from_settings(launcher.settings().arc.fetch.clone())?;
+        let page = fetcher.fetch(url.trim())?;
+        let title = page.title.clone().unwrap_or_else(|| page.url.clone());
+        index.save_page(&page.url, &title, &page.markdown)?;
+        println!("Saved {title} ({}) to {}", page.url, index.path().display());
+        return Ok(());
+    }
+
     if let Some(recipe) = cli.automate.clone() {
         if recipe.trim().is_empty() {
             bail!("--automate requires a recipe path or name");
@@ -1489,11 +2267,25 @@ pub fn run() -> Result<()> {
         return Ok(());
     }
 
+    if let Some(dir) = cli.automate_test.clone() {
+        run_automate_test(&launcher, &cli, &dir)?;
+        return Ok(());
+    }
+
+    if let Some(run_id) = cli.agent_resume.clone() {
+        let id = uuid::Uuid::parse_str(run_id.trim())
+            .with_context(|| format!("--agent-resume expects a run ID, got '{run_id}'"))?;
+        let checkpoint =
+            CheckpointStore::for_transcripts(&launcher.ai().transcript_store()).load(id)?;
+        run_agent(&launcher, &cli, AgentStart::Resume(Box::new(checkpoint)))?;
+        return Ok(());
+    }
+
     if let Some(goal) = cli.agent.clone() {
         if goal.trim().is_empty() {
             bail!("--agent requires a non-empty GOAL");
         }
-        run_agent(&launcher, &cli, &goal)?;
+        run_agent(&launcher, &cli, AgentStart::Goal(&goal))?;
         return Ok(());
     }
 
@@ -1567,6 +2359,86 @@ pub fn run() -> Result<()> {
         return Ok(());
     }
 
+    if cli.transcripts_encrypt {
+        let transcripts = launcher.transcripts();
+        if !transcripts.is_encrypted() {
+            bail!(
+                "transcripts_encryption.enabled is false; enable it and choose a key_source before migrating"
+            );
+        }
+        let report = transcripts.encrypt_existing()?;
+        println!(
+            "Encrypted {} file(s) in {} conversation(s) under {} ({} already encrypted).",
+            report.files,
+            report.conversations,
+            transcripts.root().display(),
+            report.already_encrypted
+        );
+        if report.agent_files > 0 {
+            println!(
+                "Encrypted {} agent transcript(s) and artifact(s).",
+                report.agent_files
+            );
+        }
+        if report.removed_index {
+            println!("Removed the plaintext search index; it is now rebuilt in memory.");
+        }
+        if let Some(sessions) = launcher.research().sessions() {
+            let encrypted = sessions.encrypt_existing()?;
+            if encrypted > 0 {
+                println!("Encrypted {encrypted} research session(s).");
+            }
+        }
+        let encrypted = CheckpointStore::for_transcripts(&transcripts).encrypt_existing()?;
+        if encrypted > 0 {
+            println!("Encrypted {encrypted} agent checkpoint(s).");
+        }
+        return Ok(());
+    }
+
+    if let Some(args) = cli.transcripts_export.as_deref() {
+        export_transcripts(&launcher, &args[0], Path::new(&args[1]))?;
+        return Ok(());
+    }
+
+    if let Some(path) = cli.transcripts_import.as_deref() {
+        let report = transcript_bundle::import_bundle(&launcher.transcripts(), path)?;
+        for entry in &report.conversations {
+            if entry.reassigned() {
+                println!(
+                    "Imported {} as {} (ID {} was taken)",
+                    entry.title, entry.id, entry.original_id
+                );
+            } else {
+                println!("Imported {} ({})", entry.title, entry.id);
+            }
+        }
+        println!("{} conversation(s) imported.", report.conversations.len());
+        return Ok(());
+    }
+
+    if let Some(args) = cli.transcripts_branch.as_deref() {
+        let id = uuid::Uuid::parse_str(&args[0])
+            .with_context(|| format!("invalid transcript id '{}'", args[0]))?;
+        let count: usize = args[1]
+            .parse()
+            .ok()
+            .filter(|count| *count > 0)
+            .with_context(|| format!("invalid message number '{}'", args[1]))?;
+        let record = launcher.transcripts().branch(id, count - 1)?;
+        println!(
+            "Branched {} after message {count} into {} ({})",
+            id, record.summary.title, record.summary.id
+        );
+        println!("JSON: {}", record.json_path.display());
+        return Ok(());
+    }
+
+    if let Some(query) = cli.transcripts_search.as_deref() {
+        search_transcripts(&launcher, &cli, query)?;
+        return Ok(());
+    }
+
     if cli.diagnostics {
         print_diagnostics(&launcher)?;
         return Ok(());
@@ -8,22 +8,36 @@
 //! specific files (`com`, `github.com`, `gist.github.com`, plus path-prefix
 //! combinations) layer on top, with the most specific file applied last.
 //!
+//! JavaScript files may instead carry a Greasemonkey-style `// ==UserScript==`
+//! metadata block; such userscripts are matched by their `@match`/`@include`/
+//! `@exclude` rules rather than by file name, honour `@run-at`, and have local
+//! `@require` files prepended.
+//!
 //! The injector attaches to a browser already exposing a CDP debug port (the
 //! same `automation.remote_debug_port` the agent and MCP server use); it never
-//! launches or controls a remote browser. JS is registered to run at
-//! document-start via `Page.addScriptToEvaluateOnNewDocument`; CSS is applied
-//! through a small document-start shim that appends a `<style data-conduit>`
-//! element (guarded by a `MutationObserver` for the pre-`<head>` window).
+//! launches or controls a remote browser. Every script is wrapped in a URL guard
+//! and registered on each tab via `Page.addScriptToEvaluateOnNewDocument`, so it
+//! runs at document-start on every navigation it matches; `Page.frameNavigated`
+//! events re-register the catalogue when files change. CSS is applied through a
+//! small document-start shim that appends a `<style data-conduit>` element
+//! (guarded by a `MutationObserver` for the pre-`<head>` window).
 
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap, HashSet};
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{RecvTimeoutError, channel};
 use std::time::Duration;
 
 use anyhow::{Context, Result};
-use headless_chrome::Browser;
-use headless_chrome::protocol::cdp::Page::AddScriptToEvaluateOnNewDocument;
+use headless_chrome::protocol::cdp::Page::{
+    AddScriptToEvaluateOnNewDocument, RemoveScriptToEvaluateOnNewDocument,
+};
+use headless_chrome::protocol::cdp::types::Event;
+use headless_chrome::{Browser, Tab};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use tracing::{debug, info, warn};
 
 use crate::config::ConduitSettings;
@@ -34,6 +48,15 @@ pub const GLOBAL_SCRIPT_NAME: &str = "_global";
 /// Maximum bytes read from a single Conduit `.js`/`.css` file.
 const MAX_FILE_BYTES: u64 = 2 * 1024 * 1024;
 
+/// Maximum number of files scanned from the Conduit directory.
+const MAX_SCRIPTS: usize = 500;
+
+/// Maximum directory depth scanned below the Conduit directory.
+const MAX_DIR_DEPTH: usize = 8;
+
+/// Name of the enable/disable state file kept in the Conduit directory.
+pub const STATE_FILE_NAME: &str = ".conduit-state.json";
+
 /// The resolved set of scripts/styles applicable to a given URL.
 #[derive(Debug, Default, Clone, PartialEq, Eq)]
 pub struct ConduitBundle {
@@ -113,37 +136,764 @@ fn iterate_path_segments(path: &str) -> Vec<String> {
     out
 }
 
-/// Load the applicable JS/CSS bundle for `url` from the Conduit directory.
+/// When a userscript runs relative to page load (`@run-at`).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum RunAt {
+    /// Before any page script, as soon as the document exists.
+    DocumentStart,
+    /// Once the DOM is parsed (`DOMContentLoaded`).
+    DocumentEnd,
+    /// Once the page and its subresources have loaded (`load`); the
+    /// userscript default.
+    #[default]
+    DocumentIdle,
+}
+
+impl RunAt {
+    fn parse(value: &str) -> Option<Self> {
+        match value {
+            "document-start" => Some(RunAt::DocumentStart),
+            "document-end" => Some(RunAt::DocumentEnd),
+            "document-idle" => Some(RunAt::DocumentIdle),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for RunAt {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RunAt::DocumentStart => write!(f, "document-start"),
+            RunAt::DocumentEnd => write!(f, "document-end"),
+            RunAt::DocumentIdle => write!(f, "document-idle"),
+        }
+    }
+}
+
+/// The `// ==UserScript==` metadata block of a userscript.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct UserscriptMeta {
+    pub name: Option<String>,
+    /// `@match` patterns (`*://*.example.com/*`, `<all_urls>`).
+    pub matches: Vec<String>,
+    /// `@include` globs over the whole URL.
+    pub includes: Vec<String>,
+    /// `@exclude` globs over the whole URL.
+    pub excludes: Vec<String>,
+    pub run_at: RunAt,
+    /// `@require` entries: paths relative to the Conduit directory or
+    /// `file://` URLs inside it.
+    pub requires: Vec<String>,
+}
+
+/// Parse the `// ==UserScript==` … `// ==/UserScript==` block of `source`, or
+/// `None` when it has no metadata block. Unknown keys are ignored.
+pub fn parse_userscript_meta(source: &str) -> Option<UserscriptMeta> {
+    let mut lines = source.lines().map(str::trim);
+    lines.find(|line| comment_body(line) == Some("==UserScript=="))?;
+    let mut meta = UserscriptMeta::default();
+    for line in lines {
+        let Some(body) = comment_body(line) else {
+            continue;
+        };
+        if body == "==/UserScript==" {
+            return Some(meta);
+        }
+        let Some(entry) = body.strip_prefix('@') else {
+            continue;
+        };
+        let (key, value) = entry
+            .split_once(char::is_whitespace)
+            .map(|(key, value)| (key, value.trim()))
+            .unwrap_or((entry, ""));
+        if value.is_empty() {
+            continue;
+        }
+        match key {
+            "name" => meta.name = Some(value.to_string()),
+            "match" => meta.matches.push(value.to_string()),
+            "include" => meta.includes.push(value.to_string()),
+            "exclude" => meta.excludes.push(value.to_string()),
+            "require" => meta.requires.push(value.to_string()),
+            "run-at" => match RunAt::parse(value) {
+                Some(run_at) => meta.run_at = run_at,
+                None => warn!(value, "unsupported userscript @run-at; using document-idle"),
+            },
+            _ => {}
+        }
+    }
+    // An unterminated block is not a metadata block.
+    None
+}
+
+fn comment_body(line: &str) -> Option<&str> {
+    line.strip_prefix("//").map(str::trim)
+}
+
+/// Translate a `@match` pattern into an anchored regex over the URL (without
+/// its fragment), or `None` when the pattern is invalid. Ports are ignored
+/// unless the URL has one, matching browser extension semantics.
+pub fn match_pattern_regex(pattern: &str) -> Option<String> {
+    if pattern == "<all_urls>" {
+        return Some("^(?:https?|wss?|ftp|file)://".to_string());
+    }
+    let (scheme, rest) = pattern.split_once("://")?;
+    let scheme_re = match scheme {
+        "*" => "https?",
+        "http" | "https" | "ws" | "wss" | "ftp" | "file" => scheme,
+        _ => return None,
+    };
+    let (host, path) = match rest.find('/') {
+        Some(idx) => rest.split_at(idx),
+        None => return None,
+    };
+    let host_re = if scheme == "file" {
+        if !host.is_empty() {
+            return None;
+        }
+        String::new()
+    } else if host == "*" {
+        "[^/]*".to_string()
+    } else if let Some(domain) = host.strip_prefix("*.") {
+        if domain.is_empty() || domain.contains('*') {
+            return None;
+        }
+        format!("(?:[^/]*\\.)?{}(?::[0-9]+)?", regex::escape(domain))
+    } else {
+        if host.is_empty() || host.contains('*') {
+            return None;
+        }
+        format!("{}(?::[0-9]+)?", regex::escape(host))
+    };
+    Some(format!("^{scheme_re}://{host_re}{}$", glob_body(path)))
+}
+
+/// Translate an `@include`/`@exclude` glob (`*` wildcards) into an anchored
+/// regex over the URL.
+pub fn glob_regex(glob: &str) -> String {
+    format!("^{}$", glob_body(glob))
+}
+
+/// Compile a userscript's rule regexes, dropping (with a warning) any the
+/// regex engine rejects.
+fn compile_rules(key: &str, patterns: Vec<String>) -> Vec<Regex> {
+    patterns
+        .into_iter()
+        .filter_map(|pattern| match Regex::new(&pattern) {
+            Ok(regex) => Some(regex),
+            Err(err) => {
+                warn!(script = %key, pattern = %pattern, error = %err, "unusable userscript rule; ignoring");
+                None
+            }
+        })
+        .collect()
+}
+
+fn glob_body(glob: &str) -> String {
+    glob.split('*')
+        .map(regex::escape)
+        .collect::<Vec<_>>()
+        .join(".*")
+}
+
+/// The URL without its fragment, the string userscript rules are matched
+/// against.
+fn rule_target(url: &str) -> Option<String> {
+    let mut parsed = url::Url::parse(url).ok()?;
+    parsed.set_fragment(None);
+    Some(parsed.into())
+}
+
+/// Whether a script is a `.js` or `.css` file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ScriptKind {
+    Js,
+    Css,
+}
+
+/// Which URLs a [`ConduitScript`] applies to.
+#[derive(Debug, Clone)]
+pub enum ScriptScope {
+    /// Matched by file name: the path without extension is one of
+    /// [`candidate_basenames`].
+    Basename(String),
+    /// Matched by userscript rules: any include regex and no exclude regex,
+    /// compiled once when the script loads.
+    Rules {
+        include: Vec<Regex>,
+        exclude: Vec<Regex>,
+    },
+}
+
+/// One injectable file from the Conduit directory.
+#[derive(Debug, Clone)]
+pub struct ConduitScript {
+    /// Path relative to the Conduit directory, e.g. `github.com.css`.
+    pub key: String,
+    pub kind: ScriptKind,
+    /// File contents; userscripts have their `@require` files prepended.
+    pub source: String,
+    pub scope: ScriptScope,
+    pub run_at: RunAt,
+    /// The metadata block, for userscripts.
+    pub meta: Option<UserscriptMeta>,
+}
+
+impl ConduitScript {
+    /// Whether the script applies to `url`.
+    pub fn matches(&self, url: &str) -> bool {
+        match &self.scope {
+            ScriptScope::Basename(base) => candidate_basenames(url).contains(base),
+            ScriptScope::Rules { include, exclude } => {
+                let Some(target) = rule_target(url) else {
+                    return false;
+                };
+                let hit = |patterns: &[Regex]| patterns.iter().any(|regex| regex.is_match(&target));
+                hit(include) && !hit(exclude)
+            }
+        }
+    }
+
+    /// Order key: file-name scripts general → specific (domain levels, then
+    /// path depth), userscripts after them.
+    fn specificity(&self) -> (usize, usize, usize) {
+        match &self.scope {
+            ScriptScope::Basename(base) => {
+                let (domain, path) = split_basename(base);
+                let levels = if domain == GLOBAL_SCRIPT_NAME {
+                    0
+                } else {
+                    domain.split('.').count()
+                };
+                (0, levels, path.split('/').filter(|s| !s.is_empty()).count())
+            }
+            ScriptScope::Rules { .. } => (1, 0, 0),
+        }
+    }
+
+    /// JavaScript expression that is true when the current document matches,
+    /// mirroring [`ConduitScript::matches`] in the page.
+    fn js_guard(&self) -> String {
+        match &self.scope {
+            ScriptScope::Basename(base) => {
+                let (domain, path) = split_basename(base);
+                format!(
+                    "(function(d,p){{\
+var h=location.hostname;\
+if(d!=={global}&&(h.charAt(0)==='['||/^[0-9.]+$/.test(h)||(h!==d&&h.slice(-d.length-1)!=='.'+d)))return false;\
+if(!p)return true;\
+return('/'+location.pathname.split('/').filter(Boolean).join('/')+'/').indexOf(p+'/')===0;\
+}})({domain},{path})",
+                    global = json_string(GLOBAL_SCRIPT_NAME),
+                    domain = json_string(domain),
+                    path = json_string(path),
+                )
+            }
+            ScriptScope::Rules { include, exclude } => {
+                let sources = |patterns: &[Regex]| {
+                    serde_json::to_string(&patterns.iter().map(Regex::as_str).collect::<Vec<_>>())
+                        .unwrap_or_else(|_| "[]".into())
+                };
+                format!(
+                    "(function(u,i,x){{\
+function hit(l){{return l.some(function(r){{return new RegExp(r).test(u);}});}}\
+return hit(i)&&!hit(x);\
+}})(location.href.split('#')[0],{},{})",
+                    sources(include),
+                    sources(exclude),
+                )
+            }
+        }
+    }
+
+    /// The source registered with `Page.addScriptToEvaluateOnNewDocument`:
+    /// the script behind its URL guard (and off on `disabled_sites`), run
+    /// once per document at its `@run-at` point (CSS through the style shim
+    /// at document-start, replacing an older version in place).
+    ///
+    /// The per-document marker is keyed on the script, not its source, so a
+    /// changed version replaces the one that ran: the old instance's
+    /// `conduit.onTeardown` callbacks run first, and its pending `@run-at`
+    /// hook is cancelled.
+    pub fn document_start_source(&self, disabled_sites: &BTreeSet<String>) -> String {
+        let run = match self.kind {
+            ScriptKind::Css => style_shim(&self.key, &self.source),
+            ScriptKind::Js => format!(
+                "try{{\n{}\n}}catch(e){{console.error('[conduit] '+{}+':',e);}}",
+                self.source,
+                json_string(&self.key)
+            ),
+        };
+        let schedule = match self.run_at {
+            RunAt::DocumentStart => "run();",
+            RunAt::DocumentEnd => {
+                "if(document.readyState==='loading')\
+document.addEventListener('DOMContentLoaded',run,{once:true});else run();"
+            }
+            RunAt::DocumentIdle => {
+                "if(document.readyState==='complete')run();\
+else window.addEventListener('load',run,{once:true});"
+            }
+        };
+        format!(
+            "(function(){{\
+var id={id},v={version};\
+var ran=window.__conduitRan||(window.__conduitRan={{}});\
+var prev=ran[id];\
+if(prev&&prev.v===v)return;\
+if(prev){{\
+delete ran[id];\
+prev.teardown.forEach(function(f){{try{{f();}}catch(e){{console.error('[conduit] '+id+' teardown:',e);}}}});\
+}}\
+if(!({guard}))return;\
+var inst=ran[id]={{v:v,teardown:[]}};\
+var conduit={{onTeardown:function(f){{inst.teardown.push(f);}}}};\
+function run(){{\
+if(ran[id]!==inst)return;\n{run}\n}}\
+{schedule}\
+}})();",
+            id = json_string(&self.key),
+            version = json_string(&short_hash(&self.source)),
+            guard = if disabled_sites.is_empty() {
+                self.js_guard()
+            } else {
+                format!("!{}&&{}", sites_guard(disabled_sites), self.js_guard())
+            },
+        )
+    }
+}
+
+/// JavaScript expression that is true when the document's host is one of
+/// `sites` or a subdomain of one, mirroring [`ConduitState::site_enabled`].
+fn sites_guard(sites: &BTreeSet<String>) -> String {
+    format!(
+        "(function(s){{\
+var h=location.hostname;\
+return s.some(function(d){{return h===d||h.slice(-d.length-1)==='.'+d;}});\
+}})({})",
+        serde_json::to_string(sites).unwrap_or_else(|_| "[]".into())
+    )
+}
+
+/// Split a file-name basename into its domain part and `/path` part.
+fn split_basename(base: &str) -> (&str, &str) {
+    match base.find('/') {
+        Some(idx) => base.split_at(idx),
+        None => (base, ""),
+    }
+}
+
+fn json_string(value: &str) -> String {
+    serde_json::to_string(value).unwrap_or_else(|_| "\"\"".to_string())
+}
+
+fn short_hash(value: &str) -> String {
+    hex::encode(&Sha256::digest(value.as_bytes())[..8])
+}
+
+/// Load every injectable file under the Conduit directory, ordered the way
+/// they should run (file-name scripts general → specific, then userscripts).
 ///
-/// Reads each `{basename}.js` / `{basename}.css` (in general → specific order so
-/// site-specific files win by being applied last), skipping any that are
-/// missing. Every resolved path is canonicalized and asserted to stay within
-/// `dir` (path-traversal guard), and files larger than [`MAX_FILE_BYTES`] are
-/// skipped with a warning.
-pub fn load_bundle(dir: &Path, url: &str) -> Result<ConduitBundle> {
-    let mut bundle = ConduitBundle::default();
+/// `.js` files with a metadata block become userscripts; files they
+/// `@require` are not injected on their own. Userscripts whose `@require`
+/// cannot be read locally (remote URLs included) are skipped with a warning.
+/// All reads go through the same traversal guard and size bound as
+/// [`load_bundle`].
+pub fn load_scripts(dir: &Path) -> Result<Vec<ConduitScript>> {
     if !dir.is_dir() {
-        return Ok(bundle);
+        return Ok(Vec::new());
     }
     let dir_canon = dir
         .canonicalize()
         .with_context(|| format!("failed to canonicalize Conduit dir {}", dir.display()))?;
 
-    for base in candidate_basenames(url) {
-        for (ext, sink) in [("js", &mut bundle.js), ("css", &mut bundle.css)] {
-            let candidate = dir.join(format!("{base}.{ext}"));
-            match read_guarded(&dir_canon, &candidate)? {
-                Some(contents) => {
-                    bundle.sources.push(format!("{base}.{ext}"));
-                    sink.push(contents);
+    let mut files = Vec::new();
+    collect_files(dir, 0, &mut files);
+
+    let mut scripts = Vec::new();
+    let mut required = HashSet::new();
+    for path in files {
+        let kind = match path.extension().and_then(|ext| ext.to_str()) {
+            Some("js") => ScriptKind::Js,
+            Some("css") => ScriptKind::Css,
+            _ => continue,
+        };
+        let Ok(relative) = path.strip_prefix(dir) else {
+            continue;
+        };
+        let key = relative.to_string_lossy().replace('\\', "/");
+        let Some(source) = read_guarded(&dir_canon, &path)? else {
+            continue;
+        };
+
+        let meta = match kind {
+            ScriptKind::Js => parse_userscript_meta(&source),
+            ScriptKind::Css => None,
+        };
+        let Some(meta) = meta else {
+            let base = key.rsplit_once('.').map(|(base, _)| base).unwrap_or(&key);
+            scripts.push(ConduitScript {
+                scope: ScriptScope::Basename(base.to_string()),
+                key,
+                kind,
+                source,
+                run_at: RunAt::DocumentStart,
+                meta: None,
+            });
+            continue;
+        };
+
+        let mut prelude = String::new();
+        let mut missing = None;
+        for spec in &meta.requires {
+            match resolve_require(dir, &dir_canon, spec)? {
+                Some((canonical, contents)) => {
+                    prelude.push_str(&contents);
+                    prelude.push('\n');
+                    required.insert(canonical);
+                }
+                None => {
+                    missing = Some(spec.clone());
+                    break;
+                }
+            }
+        }
+        if let Some(spec) = missing {
+            warn!(script = %key, require = %spec, "userscript @require is not a readable local file; skipping script");
+            continue;
+        }
+
+        let mut include = Vec::new();
+        for pattern in &meta.matches {
+            match match_pattern_regex(pattern) {
+                Some(regex) => include.push(regex),
+                None => {
+                    warn!(script = %key, pattern = %pattern, "invalid userscript @match; ignoring")
                 }
-                None => continue,
             }
         }
+        include.extend(meta.includes.iter().map(|glob| glob_regex(glob)));
+        let include = compile_rules(&key, include);
+        let exclude = compile_rules(
+            &key,
+            meta.excludes.iter().map(|glob| glob_regex(glob)).collect(),
+        );
+        if include.is_empty() {
+            warn!(script = %key, "userscript has no usable @match or @include; it will not run");
+        }
+        scripts.push(ConduitScript {
+            key,
+            kind,
+            source: format!("{prelude}{source}"),
+            scope: ScriptScope::Rules { include, exclude },
+            run_at: meta.run_at,
+            meta: Some(meta),
+        });
+    }
+
+    scripts.retain(|script| {
+        dir.join(&script.key)
+            .canonicalize()
+            .map(|path| !required.contains(&path))
+            .unwrap_or(true)
+    });
+    scripts.sort_by(|a, b| {
+        a.specificity()
+            .cmp(&b.specificity())
+            .then_with(|| a.key.cmp(&b.key))
+    });
+    Ok(scripts)
+}
+
+/// Collect regular files and symlinks under `dir` (symlinked directories are
+/// not followed), bounded by [`MAX_DIR_DEPTH`] and [`MAX_SCRIPTS`].
+fn collect_files(dir: &Path, depth: usize, out: &mut Vec<PathBuf>) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    let mut entries: Vec<_> = entries.filter_map(|entry| entry.ok()).collect();
+    entries.sort_by_key(|entry| entry.file_name());
+    for entry in entries {
+        if out.len() >= MAX_SCRIPTS {
+            warn!(
+                limit = MAX_SCRIPTS,
+                "Conduit directory has too many files; ignoring the rest"
+            );
+            return;
+        }
+        let Ok(file_type) = entry.file_type() else {
+            continue;
+        };
+        if file_type.is_dir() {
+            if depth < MAX_DIR_DEPTH {
+                collect_files(&entry.path(), depth + 1, out);
+            }
+        } else {
+            out.push(entry.path());
+        }
+    }
+}
+
+/// Resolve and read one `@require` entry, returning its canonical path and
+/// contents, or `None` when it is remote, missing or outside the directory.
+fn resolve_require(dir: &Path, dir_canon: &Path, spec: &str) -> Result<Option<(PathBuf, String)>> {
+    let path = if spec.starts_with("file://") {
+        match url::Url::parse(spec)
+            .ok()
+            .and_then(|url| url.to_file_path().ok())
+        {
+            Some(path) => path,
+            None => return Ok(None),
+        }
+    } else if spec.contains("://") {
+        return Ok(None);
+    } else {
+        dir.join(spec)
+    };
+    let Some(contents) = read_guarded(dir_canon, &path)? else {
+        return Ok(None);
+    };
+    Ok(Some((path.canonicalize()?, contents)))
+}
+
+/// Load the applicable JS/CSS bundle for `url` from the Conduit directory.
+///
+/// Collects the enabled [`load_scripts`] entries that match `url`, in order,
+/// so site-specific files win by being applied last; nothing applies on a
+/// disabled site. Every resolved path is canonicalized and asserted to stay
+/// within `dir` (path-traversal guard), and files larger than
+/// [`MAX_FILE_BYTES`] are skipped with a warning.
+pub fn load_bundle(dir: &Path, url: &str) -> Result<ConduitBundle> {
+    let mut bundle = ConduitBundle::default();
+    let state = ConduitState::load(dir)?;
+    if !state.site_enabled(url) {
+        return Ok(bundle);
+    }
+    for script in load_scripts(dir)? {
+        if !state.is_enabled(&script.key) || !script.matches(url) {
+            continue;
+        }
+        match script.kind {
+            ScriptKind::Js => bundle.js.push(script.source),
+            ScriptKind::Css => bundle.css.push(script.source),
+        }
+        bundle.sources.push(script.key);
     }
     Ok(bundle)
 }
 
+/// Per-script and per-site enable state, kept in [`STATE_FILE_NAME`] inside
+/// the Conduit directory. Everything is enabled unless listed here.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConduitState {
+    /// Keys (paths relative to the Conduit directory) of disabled scripts.
+    #[serde(default)]
+    pub disabled: BTreeSet<String>,
+    /// Hosts on which no script runs; subdomains are included.
+    #[serde(default)]
+    pub disabled_sites: BTreeSet<String>,
+}
+
+impl ConduitState {
+    /// Load the state file of `dir`; a missing file is the default state.
+    pub fn load(dir: &Path) -> Result<Self> {
+        let path = dir.join(STATE_FILE_NAME);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let raw = std::fs::read_to_string(&path)
+            .with_context(|| format!("failed to read {}", path.display()))?;
+        serde_json::from_str(&raw).with_context(|| format!("failed to parse {}", path.display()))
+    }
+
+    /// Write the state file of `dir` (atomically, via a temporary file).
+    pub fn save(&self, dir: &Path) -> Result<()> {
+        std::fs::create_dir_all(dir)
+            .with_context(|| format!("failed to create Conduit dir {}", dir.display()))?;
+        let path = dir.join(STATE_FILE_NAME);
+        let tmp = dir.join(format!("{STATE_FILE_NAME}.tmp"));
+        std::fs::write(&tmp, serde_json::to_string_pretty(self)?)
+            .with_context(|| format!("failed to write {}", tmp.display()))?;
+        std::fs::rename(&tmp, &path)
+            .with_context(|| format!("failed to replace {}", path.display()))
+    }
+
+    pub fn is_enabled(&self, key: &str) -> bool {
+        !self.disabled.contains(key)
+    }
+
+    pub fn set_enabled(&mut self, key: &str, enabled: bool) {
+        if enabled {
+            self.disabled.remove(key);
+        } else {
+            self.disabled.insert(key.to_string());
+        }
+    }
+
+    /// Whether scripts may run on `url`'s host.
+    pub fn site_enabled(&self, url: &str) -> bool {
+        let Some(host) = url::Url::parse(url)
+            .ok()
+            .and_then(|parsed| parsed.host_str().map(str::to_ascii_lowercase))
+        else {
+            return true;
+        };
+        !self.disabled_sites.iter().any(|site| {
+            host == *site
+                || host
+                    .strip_suffix(site.as_str())
+                    .is_some_and(|rest| rest.ends_with('.'))
+        })
+    }
+
+    /// Enable or disable every script on `site` (a host name such as
+    /// `example.com`; a URL is reduced to its host).
+    pub fn set_site_enabled(&mut self, site: &str, enabled: bool) -> Result<()> {
+        let site = normalize_site(site)?;
+        if enabled {
+            self.disabled_sites.remove(&site);
+        } else {
+            self.disabled_sites.insert(site);
+        }
+        Ok(())
+    }
+}
+
+fn normalize_site(site: &str) -> Result<String> {
+    let site = site.trim();
+    let host = match url::Url::parse(site) {
+        Ok(parsed) if parsed.host_str().is_some() => {
+            parsed.host_str().unwrap_or_default().to_string()
+        }
+        _ => site.trim_end_matches('/').to_string(),
+    };
+    let host = host.to_ascii_lowercase();
+    if host.is_empty() || host.contains(['/', ' ', '*']) {
+        anyhow::bail!("'{site}' is not a host name");
+    }
+    Ok(host)
+}
+
+/// A Conduit file as listed by the script manager.
+#[derive(Debug, Clone, Serialize)]
+pub struct ScriptInfo {
+    pub key: String,
+    pub kind: ScriptKind,
+    pub enabled: bool,
+    pub run_at: RunAt,
+    /// `file-name` or `userscript`.
+    pub matching: &'static str,
+    /// The metadata block, for userscripts.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub meta: Option<UserscriptMeta>,
+    /// Size of the injected source in bytes (including `@require`s).
+    pub bytes: usize,
+}
+
+/// List the Conduit directory's scripts with their enable state.
+pub fn list_scripts(dir: &Path) -> Result<Vec<ScriptInfo>> {
+    let state = ConduitState::load(dir)?;
+    Ok(load_scripts(dir)?
+        .into_iter()
+        .map(|script| ScriptInfo {
+            enabled: state.is_enabled(&script.key),
+            matching: match script.scope {
+                ScriptScope::Basename(_) => "file-name",
+                ScriptScope::Rules { .. } => "userscript",
+            },
+            bytes: script.source.len(),
+            key: script.key,
+            kind: script.kind,
+            run_at: script.run_at,
+            meta: script.meta,
+        })
+        .collect())
+}
+
+/// Resolve a script key to its path in `dir`, refusing anything that is not
+/// a relative `.js`/`.css` path inside the directory.
+fn script_path(dir: &Path, key: &str) -> Result<PathBuf> {
+    let relative = Path::new(key);
+    let plain = relative
+        .components()
+        .all(|component| matches!(component, std::path::Component::Normal(_)));
+    let extension = relative.extension().and_then(|ext| ext.to_str());
+    if key.is_empty() || !plain || !matches!(extension, Some("js" | "css")) {
+        anyhow::bail!("'{key}' is not a .js or .css path inside the Conduit directory");
+    }
+    Ok(dir.join(relative))
+}
+
+/// Read the raw source of the script `key`.
+pub fn read_script(dir: &Path, key: &str) -> Result<String> {
+    let path = script_path(dir, key)?;
+    let dir_canon = dir
+        .canonicalize()
+        .with_context(|| format!("failed to canonicalize Conduit dir {}", dir.display()))?;
+    read_guarded(&dir_canon, &path)?.with_context(|| format!("no Conduit script '{key}'"))
+}
+
+/// Create or replace the script `key` with `source`, creating parent
+/// directories as needed. Running services pick the change up on their next
+/// directory check.
+pub fn write_script(dir: &Path, key: &str, source: &str) -> Result<()> {
+    let path = script_path(dir, key)?;
+    if source.len() as u64 > MAX_FILE_BYTES {
+        anyhow::bail!("script exceeds the {MAX_FILE_BYTES}-byte limit");
+    }
+    let dir_canon = dir
+        .canonicalize()
+        .with_context(|| format!("failed to canonicalize Conduit dir {}", dir.display()))?;
+    // Create missing parents one level at a time, checking each existing
+    // level first, so a symlinked subdirectory never gets directories
+    // created outside the Conduit directory.
+    let mut parent = dir.to_path_buf();
+    for component in Path::new(key).parent().into_iter().flat_map(Path::components) {
+        parent.push(component);
+        match parent.canonicalize() {
+            Ok(resolved) if resolved.starts_with(&dir_canon) => {}
+            Ok(_) => anyhow::bail!("'{key}' resolves outside the Conduit directory"),
+            Err(_) => std::fs::create_dir(&parent)
+                .with_context(|| format!("failed to create {}", parent.display()))?,
+        }
+    }
+    if !parent.canonicalize()?.starts_with(&dir_canon)
+        || path
+            .symlink_metadata()
+            .is_ok_and(|meta| meta.file_type().is_symlink())
+    {
+        anyhow::bail!("'{key}' resolves outside the Conduit directory");
+    }
+    let tmp = path.with_extension("conduit-tmp");
+    std::fs::write(&tmp, source).with_context(|| format!("failed to write {}", tmp.display()))?;
+    std::fs::rename(&tmp, &path).with_context(|| format!("failed to replace {}", path.display()))
+}
+
+/// Fingerprint of the directory's file names, sizes and modification times
+/// (state file included), used to notice edits without reading every file.
+pub fn dir_stamp(dir: &Path) -> String {
+    let mut files = Vec::new();
+    collect_files(dir, 0, &mut files);
+    let mut hasher = Sha256::new();
+    for path in files {
+        let Ok(meta) = std::fs::metadata(&path) else {
+            continue;
+        };
+        let modified = meta
+            .modified()
+            .ok()
+            .and_then(|time| time.duration_since(std::time::UNIX_EPOCH).ok())
+            .map(|age| age.as_nanos())
+            .unwrap_or_default();
+        hasher.update(path.to_string_lossy().as_bytes());
+        hasher.update(meta.len().to_le_bytes());
+        hasher.update(modified.to_le_bytes());
+    }
+    hex::encode(hasher.finalize())
+}
+
 /// Read `candidate` if it exists, enforcing the traversal guard + size bound.
 ///
 /// Returns `Ok(None)` when the file is absent, too large, or (defensively)
@@ -185,16 +935,26 @@ fn read_guarded(dir_canon: &Path, candidate: &Path) -> Result<Option<String>> {
 /// `<style data-conduit>` element, guarded by a `MutationObserver` for the
 /// window before `<head>`/`documentElement` exists.
 pub fn css_shim(css: &str) -> String {
-    let encoded = serde_json::to_string(css).unwrap_or_else(|_| "\"\"".to_string());
+    format!("(function(){{{}}})();", style_shim("", css))
+}
+
+/// Body of [`css_shim`]: puts `css` in the `<style data-conduit="{id}">`
+/// element, replacing the text of an existing one in place.
+fn style_shim(id: &str, css: &str) -> String {
     format!(
-        "(function(){{\
-var css={encoded};\
+        "var id={id},css={css};\
 function inject(){{\
-if(document.querySelector('style[data-conduit]'))return true;\
+var styles=document.querySelectorAll('style[data-conduit]');\
+for(var i=0;i<styles.length;i++){{\
+if(styles[i].getAttribute('data-conduit')===id){{\
+if(styles[i].textContent!==css)styles[i].textContent=css;\
+return true;\
+}}\
+}}\
 var target=document.head||document.documentElement;\
 if(!target)return false;\
 var style=document.createElement('style');\
-style.setAttribute('data-conduit','');\
+style.setAttribute('data-conduit',id);\
 style.textContent=css;\
 target.appendChild(style);\
 return true;\
@@ -202,8 +962,9 @@ return true;\
 if(!inject()){{\
 var obs=new MutationObserver(function(){{if(inject())obs.disconnect();}});\
 obs.observe(document.documentElement||document,{{childList:true,subtree:true}});\
-}}\
-}})();"
+}}",
+        id = json_string(id),
+        css = json_string(css),
     )
 }
 
@@ -260,8 +1021,8 @@ pub fn inject_bundle<I: ScriptInjector>(
     Ok(())
 }
 
-/// A long-running service that attaches to a CDP endpoint and injects the
-/// applicable Conduit bundle into each tab, re-applying on navigation.
+/// A long-running service that attaches to a CDP endpoint and registers the
+/// Conduit catalogue on each tab for document-start injection.
 pub struct ConduitService {
     browser: Browser,
     dir: PathBuf,
@@ -270,6 +1031,26 @@ pub struct ConduitService {
     poll: Duration,
 }
 
+/// The enabled scripts of the Conduit directory, ready to register.
+#[derive(Default)]
+struct Catalogue {
+    scripts: Vec<ConduitScript>,
+    /// [`ConduitScript::document_start_source`] of each script.
+    sources: Vec<String>,
+    state: ConduitState,
+    /// Hash of `sources`; tabs registered with it are up to date.
+    fingerprint: String,
+    /// [`dir_stamp`] the catalogue was loaded at.
+    stamp: String,
+}
+
+/// A tab the service has wired, with the scripts currently registered on it.
+struct WiredTab {
+    tab: Arc<Tab>,
+    identifiers: Vec<String>,
+    fingerprint: String,
+}
+
 impl ConduitService {
     /// Attach to the browser exposing CDP at `ws_url`.
     ///
@@ -287,17 +1068,27 @@ impl ConduitService {
         })
     }
 
-    /// Poll attached tabs and inject the matching bundle until `cancel` is set.
+    /// Wire attached tabs and keep their scripts current until `cancel` is set.
     ///
-    /// New tabs are wired once; when a tab's URL changes the bundle is
-    /// recomputed and re-applied (v1 uses poll + eval; a `Page.frameNavigated`
-    /// listener for true document-start on same-tab navigations is a follow-up).
+    /// Each new tab gets the catalogue registered for document-start and a
+    /// `Page.frameNavigated` listener. Every `poll_interval_ms` (and on every
+    /// main-frame navigation) the directory is checked for edits; when files
+    /// or the state file changed, every open tab is re-registered and its
+    /// current document updated in place.
     pub fn run(&self, cancel: &AtomicBool) -> Result<()> {
-        // Last-seen URL per target id; a change (or first sight) triggers (re)injection.
-        let mut wired: HashMap<String, String> = HashMap::new();
+        let (tx, navigations) = channel::<(String, String)>();
+        let mut wired: HashMap<String, WiredTab> = HashMap::new();
+        let mut catalogue = Catalogue::default();
 
         info!(dir = %self.dir.display(), "Conduit injection service running");
         while !cancel.load(Ordering::Relaxed) {
+            if self.reload_if_changed(&mut catalogue) {
+                for entry in wired.values_mut() {
+                    let url = entry.tab.get_url();
+                    self.refresh(entry, &catalogue, &url);
+                }
+            }
+
             let tabs = {
                 let guard = self
                     .browser
@@ -307,38 +1098,183 @@ impl ConduitService {
                 guard.clone()
             };
 
+            let open: HashSet<String> = tabs
+                .iter()
+                .map(|tab| tab.get_target_id().to_string())
+                .collect();
+            wired.retain(|target_id, _| open.contains(target_id));
+
             for tab in tabs {
                 let target_id = tab.get_target_id().to_string();
-                let url = tab.get_url();
-                if url.is_empty() || url == "about:blank" {
+                if wired.contains_key(&target_id) {
                     continue;
                 }
-                let changed = wired.get(&target_id) != Some(&url);
-                if !changed {
+                let listener_tx = tx.clone();
+                let listener_id = target_id.clone();
+                if let Err(err) = tab.add_event_listener(Arc::new(move |event: &Event| {
+                    if let Event::PageFrameNavigated(nav) = event
+                        && nav.params.frame.parent_id.is_none()
+                    {
+                        let _ =
+                            listener_tx.send((listener_id.clone(), nav.params.frame.url.clone()));
+                    }
+                })) {
+                    warn!(error = %err, "Conduit could not listen for navigations");
                     continue;
                 }
+                let url = tab.get_url();
+                let entry = wired.entry(target_id).or_insert(WiredTab {
+                    tab,
+                    identifiers: Vec::new(),
+                    fingerprint: String::new(),
+                });
+                self.refresh(entry, &catalogue, &url);
+            }
 
-                match load_bundle(&self.dir, &url) {
-                    Ok(bundle) if !bundle.is_empty() => {
-                        if let Err(err) =
-                            inject_bundle(&tab, &bundle, self.inject_js, self.inject_css)
-                        {
-                            warn!(%url, error = %err, "Conduit injection failed");
-                        } else {
-                            debug!(%url, sources = ?bundle.sources, "Conduit injected");
+            match navigations.recv_timeout(self.poll) {
+                Ok((target_id, url)) => {
+                    if self.reload_if_changed(&mut catalogue) {
+                        for entry in wired.values_mut() {
+                            let url = entry.tab.get_url();
+                            self.refresh(entry, &catalogue, &url);
                         }
+                    } else if let Some(entry) = wired.get_mut(&target_id) {
+                        self.refresh(entry, &catalogue, &url);
                     }
-                    Ok(_) => {}
-                    Err(err) => warn!(%url, error = %err, "Conduit bundle load failed"),
                 }
-                wired.insert(target_id, url);
+                Err(RecvTimeoutError::Timeout) => {}
+                Err(RecvTimeoutError::Disconnected) => break,
             }
-
-            std::thread::sleep(self.poll);
         }
         info!("Conduit injection service stopped");
         Ok(())
     }
+
+    /// Reload `catalogue` when the directory changed since it was loaded.
+    fn reload_if_changed(&self, catalogue: &mut Catalogue) -> bool {
+        let stamp = dir_stamp(&self.dir);
+        if stamp == catalogue.stamp {
+            return false;
+        }
+        let loaded =
+            ConduitState::load(&self.dir).and_then(|state| Ok((state, load_scripts(&self.dir)?)));
+        let (state, scripts) = match loaded {
+            Ok(loaded) => loaded,
+            Err(err) => {
+                // Keep the stamp so a broken file is reported once per edit.
+                warn!(error = %err, "Conduit script load failed");
+                catalogue.stamp = stamp;
+                return false;
+            }
+        };
+        let scripts: Vec<ConduitScript> = scripts
+            .into_iter()
+            .filter(|script| {
+                state.is_enabled(&script.key)
+                    && match script.kind {
+                        ScriptKind::Js => self.inject_js,
+                        ScriptKind::Css => self.inject_css,
+                    }
+            })
+            .collect();
+        let sources: Vec<String> = scripts
+            .iter()
+            .map(|script| script.document_start_source(&state.disabled_sites))
+            .collect();
+        let fingerprint = short_hash(&sources.join("\n"));
+        let changed = fingerprint != catalogue.fingerprint;
+        if changed && !catalogue.stamp.is_empty() {
+            info!(
+                scripts = scripts.len(),
+                "Conduit directory changed; reloading"
+            );
+        }
+        *catalogue = Catalogue {
+            scripts,
+            sources,
+            state,
+            fingerprint,
+            stamp,
+        };
+        changed
+    }
+
+    /// Bring a tab up to date with `catalogue`: replace its registered
+    /// scripts, run the ones matching `url` in the current document (their
+    /// guards stop double runs; changed CSS replaces the old `<style>`), and
+    /// drop Conduit styles that no longer apply.
+    fn refresh(&self, wired: &mut WiredTab, catalogue: &Catalogue, url: &str) {
+        if catalogue.fingerprint == wired.fingerprint {
+            return;
+        }
+
+        for identifier in wired.identifiers.drain(..) {
+            if let Err(err) = wired
+                .tab
+                .call_method(RemoveScriptToEvaluateOnNewDocument { identifier })
+            {
+                debug!(error = %err, "failed to remove Conduit script");
+            }
+        }
+        for source in &catalogue.sources {
+            match wired.tab.call_method(AddScriptToEvaluateOnNewDocument {
+                source: source.clone(),
+                world_name: None,
+                include_command_line_api: None,
+                run_immediately: None,
+            }) {
+                Ok(registered) => wired.identifiers.push(registered.identifier),
+                Err(err) => warn!(%url, error = %err, "failed to register Conduit script"),
+            }
+        }
+        wired.fingerprint = catalogue.fingerprint.clone();
+
+        if url.is_empty() || url == "about:blank" {
+            return;
+        }
+        let site_enabled = catalogue.state.site_enabled(url);
+        let mut applied = Vec::new();
+        for (script, source) in catalogue.scripts.iter().zip(&catalogue.sources) {
+            if !site_enabled || !script.matches(url) {
+                continue;
+            }
+            match wired.tab.eval_now(source) {
+                Ok(()) => applied.push(script.key.as_str()),
+                Err(err) => {
+                    warn!(%url, script = %script.key, error = %err, "Conduit injection failed")
+                }
+            }
+        }
+        let kept: Vec<&str> = catalogue
+            .scripts
+            .iter()
+            .filter(|script| {
+                script.kind == ScriptKind::Css && applied.contains(&script.key.as_str())
+            })
+            .map(|script| script.key.as_str())
+            .collect();
+        if let Err(err) = wired.tab.eval_now(&prune_styles_script(&kept)) {
+            debug!(%url, error = %err, "failed to prune Conduit styles");
+        }
+        if !applied.is_empty() {
+            debug!(%url, sources = ?applied, "Conduit injected");
+        }
+    }
+}
+
+/// Script removing per-file Conduit `<style>` elements whose key is not in
+/// `keep` (disabled, deleted or no longer matching files).
+fn prune_styles_script(keep: &[&str]) -> String {
+    format!(
+        "(function(keep){{\
+var styles=document.querySelectorAll('style[data-conduit]');\
+for(var i=0;i<styles.length;i++){{\
+var id=styles[i].getAttribute('data-conduit');\
+if(id&&keep.indexOf(id)<0)styles[i].remove();\
+}}\
+}})({})",
+        serde_json::to_string(keep).unwrap_or_else(|_| "[]".into())
+    )
 }
 
 /// Seed an example `_global.css` (and a short README header) in `dir` on first
@@ -353,7 +1289,8 @@ pub fn seed_example(dir: &Path) -> Result<()> {
  *   _global.css            applies to every site\n\
  *   github.com.css         applies to github.com and subdomains\n\
  *   github.com/user.css    applies under that path\n\
- * The matching .js variants are injected at document-start. */\n";
+ * The matching .js variants are injected at document-start. .js files with a\n\
+ * // ==UserScript== block are matched by their @match/@exclude rules. */\n";
         std::fs::write(&example, body)
             .with_context(|| format!("failed to seed {}", example.display()))?;
     }
@@ -466,6 +1403,248 @@ mod tests {
         }
     }
 
+    #[test]
+    fn parse_userscript_meta_reads_rules_and_run_at() {
+        let source = "// ==UserScript==\n\
+// @name         Tidy\n\
+// @match        *://*.example.com/*\n\
+// @match        https://example.org/app/*\n\
+// @exclude      *://*.example.com/admin*\n\
+// @run-at       document-end\n\
+// @require      lib/util.js\n\
+// @grant        none\n\
+// ==/UserScript==\n\
+document.body.dataset.tidy = 1;\n";
+        let meta = parse_userscript_meta(source).expect("metadata block");
+        assert_eq!(meta.name.as_deref(), Some("Tidy"));
+        assert_eq!(meta.matches.len(), 2);
+        assert_eq!(meta.excludes, vec!["*://*.example.com/admin*".to_string()]);
+        assert_eq!(meta.run_at, RunAt::DocumentEnd);
+        assert_eq!(meta.requires, vec!["lib/util.js".to_string()]);
+
+        assert!(parse_userscript_meta("console.log(1)").is_none());
+        assert!(parse_userscript_meta("// ==UserScript==\n// @match *://*/*\n").is_none());
+    }
+
+    #[test]
+    fn match_patterns_follow_extension_semantics() {
+        let matches = |pattern: &str, url: &str| {
+            let regex = Regex::new(&match_pattern_regex(pattern).expect("valid pattern")).unwrap();
+            regex.is_match(&rule_target(url).unwrap())
+        };
+        assert!(matches(
+            "*://*.example.com/*",
+            "https://a.example.com/x?q=1"
+        ));
+        assert!(matches("*://*.example.com/*", "http://example.com:8080/"));
+        assert!(!matches(
+            "*://*.example.com/*",
+            "https://example.com.evil.net/"
+        ));
+        assert!(!matches("*://*.example.com/*", "ftp://example.com/"));
+        assert!(matches(
+            "https://example.org/app/*",
+            "https://example.org/app/page#top"
+        ));
+        assert!(!matches(
+            "https://example.org/app/*",
+            "https://example.org/other"
+        ));
+        assert!(matches("file:///home/*", "file:///home/user/page.html"));
+        assert!(matches("<all_urls>", "https://anything.test/"));
+
+        for invalid in [
+            "example.com",
+            "chrome://*/*",
+            "https://*foo.com/*",
+            "https://a.com",
+        ] {
+            assert!(match_pattern_regex(invalid).is_none(), "{invalid}");
+        }
+    }
+
+    #[test]
+    fn load_scripts_matches_userscripts_by_rules_with_requires() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir(dir.path().join("lib")).unwrap();
+        fs::write(dir.path().join("lib/util.js"), "var util = 1;").unwrap();
+        fs::write(dir.path().join("_global.css"), "body{}").unwrap();
+        fs::write(
+            dir.path().join("tidy.user.js"),
+            "// ==UserScript==\n// @match *://*.example.com/*\n// @exclude *admin*\n\
+// @require lib/util.js\n// ==/UserScript==\nutil;\n",
+        )
+        .unwrap();
+        fs::write(
+            dir.path().join("remote.user.js"),
+            "// ==UserScript==\n// @match *://*/*\n\
+// @require https://cdn.example.net/lib.js\n// ==/UserScript==\n",
+        )
+        .unwrap();
+
+        let scripts = load_scripts(dir.path()).unwrap();
+        let keys: Vec<&str> = scripts.iter().map(|s| s.key.as_str()).collect();
+        // The required library is not injected on its own and the script
+        // with a remote @require is skipped.
+        assert_eq!(keys, ["_global.css", "tidy.user.js"]);
+        assert!(scripts[1].source.starts_with("var util = 1;\n"));
+        assert_eq!(scripts[1].run_at, RunAt::DocumentIdle);
+
+        let bundle = load_bundle(dir.path(), "https://www.example.com/page").unwrap();
+        assert_eq!(bundle.sources, vec!["_global.css", "tidy.user.js"]);
+        let excluded = load_bundle(dir.path(), "https://www.example.com/admin").unwrap();
+        assert_eq!(excluded.sources, vec!["_global.css"]);
+        // A userscript's file name plays no part in matching.
+        let unrelated = load_bundle(dir.path(), "https://tidy.user/").unwrap();
+        assert_eq!(unrelated.sources, vec!["_global.css"]);
+    }
+
+    #[test]
+    fn load_scripts_orders_file_names_general_to_specific() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir(dir.path().join("example.com")).unwrap();
+        fs::write(dir.path().join("example.com/docs.js"), "3").unwrap();
+        fs::write(dir.path().join("www.example.com.js"), "2").unwrap();
+        fs::write(dir.path().join("com.js"), "1").unwrap();
+        fs::write(dir.path().join("_global.js"), "0").unwrap();
+
+        let bundle = load_bundle(dir.path(), "https://www.example.com/docs/intro").unwrap();
+        assert_eq!(bundle.js, vec!["0", "1", "3", "2"]);
+    }
+
+    #[test]
+    fn document_start_source_guards_and_schedules() {
+        let script = ConduitScript {
+            key: "tidy.user.js".into(),
+            kind: ScriptKind::Js,
+            source: "run_me() // trailing comment".into(),
+            scope: ScriptScope::Rules {
+                include: vec![
+                    Regex::new(&match_pattern_regex("*://*.example.com/*").unwrap()).unwrap(),
+                ],
+                exclude: vec![],
+            },
+            run_at: RunAt::DocumentEnd,
+            meta: None,
+        };
+        let wrapped = script.document_start_source(&BTreeSet::new());
+        assert!(wrapped.contains("new RegExp(r).test(u)"));
+        assert!(wrapped.contains("DOMContentLoaded"));
+        assert!(wrapped.contains("__conduitRan"));
+        // The marker is keyed on the script, with the version stored beside it,
+        // so an edited script replaces the instance that ran.
+        assert!(wrapped.contains("var id=\"tidy.user.js\",v="));
+        assert!(wrapped.contains("prev.teardown.forEach"));
+        // User code sits on its own lines so a trailing comment cannot swallow the wrapper.
+        assert!(wrapped.contains("run_me() // trailing comment\n"));
+
+        let style = ConduitScript {
+            key: "github.com/user.css".into(),
+            kind: ScriptKind::Css,
+            source: "a{}".into(),
+            scope: ScriptScope::Basename("github.com/user".into()),
+            run_at: RunAt::DocumentStart,
+            meta: None,
+        };
+        let wrapped = style.document_start_source(&BTreeSet::new());
+        assert!(wrapped.contains("\"github.com\",\"/user\""));
+        assert!(wrapped.contains("data-conduit"));
+        assert!(wrapped.contains("run();"));
+    }
+
+    #[test]
+    fn state_disables_scripts_and_sites() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("_global.css"), "body{}").unwrap();
+        fs::write(dir.path().join("example.com.js"), "1").unwrap();
+
+        let mut state = ConduitState::default();
+        state.set_enabled("example.com.js", false);
+        state
+            .set_site_enabled("https://blocked.test/page", false)
+            .unwrap();
+        state.save(dir.path()).unwrap();
+        assert_eq!(ConduitState::load(dir.path()).unwrap(), state);
+        assert!(state.disabled_sites.contains("blocked.test"));
+
+        let bundle = load_bundle(dir.path(), "https://example.com/").unwrap();
+        assert_eq!(bundle.sources, vec!["_global.css"]);
+        let blocked = load_bundle(dir.path(), "https://www.blocked.test/").unwrap();
+        assert!(blocked.is_empty());
+        assert!(state.site_enabled("https://notblocked.test/"));
+
+        let listed = list_scripts(dir.path()).unwrap();
+        let enabled: Vec<(&str, bool)> = listed
+            .iter()
+            .map(|info| (info.key.as_str(), info.enabled))
+            .collect();
+        assert_eq!(enabled, [("_global.css", true), ("example.com.js", false)]);
+        // The state file itself is never treated as a script.
+        assert!(listed.iter().all(|info| info.key != STATE_FILE_NAME));
+
+        let wrapped =
+            load_scripts(dir.path()).unwrap()[0].document_start_source(&state.disabled_sites);
+        assert!(wrapped.contains("[\"blocked.test\"]"));
+    }
+
+    #[test]
+    fn write_script_stays_inside_the_directory() {
+        let dir = tempfile::tempdir().unwrap();
+        write_script(dir.path(), "github.com/user.css", "a{}").unwrap();
+        assert_eq!(
+            read_script(dir.path(), "github.com/user.css").unwrap(),
+            "a{}"
+        );
+        write_script(dir.path(), "github.com/user.css", "b{}").unwrap();
+        assert_eq!(
+            read_script(dir.path(), "github.com/user.css").unwrap(),
+            "b{}"
+        );
+
+        for key in [
+            "../escape.js",
+            "/etc/passwd.js",
+            "notes.txt",
+            STATE_FILE_NAME,
+            "",
+        ] {
+            assert!(write_script(dir.path(), key, "x").is_err(), "{key}");
+        }
+        assert!(read_script(dir.path(), "missing.js").is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn write_script_creates_nothing_through_symlinked_dirs() {
+        let dir = tempfile::tempdir().unwrap();
+        let outside = tempfile::tempdir().unwrap();
+        std::os::unix::fs::symlink(outside.path(), dir.path().join("link")).unwrap();
+
+        assert!(write_script(dir.path(), "link/nested/x.js", "x").is_err());
+        assert!(!outside.path().join("nested").exists());
+    }
+
+    #[test]
+    fn dir_stamp_changes_when_a_file_changes() {
+        let dir = tempfile::tempdir().unwrap();
+        let empty = dir_stamp(dir.path());
+        fs::write(dir.path().join("_global.css"), "a{}").unwrap();
+        let one = dir_stamp(dir.path());
+        assert_ne!(empty, one);
+        assert_eq!(one, dir_stamp(dir.path()));
+        fs::write(dir.path().join("_global.css"), "a{color:red}").unwrap();
+        assert_ne!(one, dir_stamp(dir.path()));
+    }
+
+    #[test]
+    fn styles_are_replaced_in_place_and_pruned() {
+        let shim = style_shim("site.css", "a{}");
+        assert!(shim.contains("styles[i].textContent=css"));
+        let prune = prune_styles_script(&["kept.css"]);
+        assert!(prune.contains("[\"kept.css\"]"));
+        assert!(prune.contains(".remove()"));
+    }
+
     /// Captures injected sources so we can assert wiring without a browser.
     #[derive(Default)]
     struct StubInjector {
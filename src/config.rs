@@ -1,4 +1,5 @@
 use std::{
+    collections::BTreeMap,
     fs,
     path::{Path, PathBuf},
 };
@@ -200,6 +201,9 @@ pub struct LaunchSettings {
     /// Retention limits for stored transcripts.
     #[serde(default)]
     pub transcripts_retention: TranscriptRetentionSettings,
+    /// At-rest encryption for stored transcripts and attachments.
+    #[serde(default)]
+    pub transcripts_encryption: TranscriptEncryptionSettings,
     #[serde(default)]
     pub engines: EngineSettings,
     #[serde(default)]
@@ -233,6 +237,8 @@ pub struct LaunchSettings {
     #[serde(default)]
     pub conduit: ConduitSettings,
     #[serde(default)]
+    pub scheduler: SchedulerSettings,
+    #[serde(default)]
     pub ipfs: IpfsSettings,
     #[serde(default)]
     pub ens: EnsSettings,
@@ -255,6 +261,7 @@ impl Default for LaunchSettings {
             sync_log: None,
             transcripts_root: None,
             transcripts_retention: TranscriptRetentionSettings::default(),
+            transcripts_encryption: TranscriptEncryptionSettings::default(),
             engines: EngineSettings::default(),
             ai: AiSettings::default(),
             ai_host: AiHostSettings::default(),
@@ -271,6 +278,7 @@ impl Default for LaunchSettings {
             research: ResearchSettings::default(),
             automation: AutomationSettings::default(),
             conduit: ConduitSettings::default(),
+            scheduler: SchedulerSettings::default(),
             ipfs: IpfsSettings::default(),
             ens: EnsSettings::default(),
             policy_profile: PolicyProfile::default(),
@@ -307,6 +315,74 @@ impl Default for TranscriptRetentionSettings {
     }
 }
 
+/// At-rest encryption of transcript JSON, Markdown and attachments
+/// (XChaCha20-Poly1305). Existing plaintext stores are converted with
+/// `archon --transcripts-encrypt`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TranscriptEncryptionSettings {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default)]
+    pub key_source: TranscriptKeySource,
+    /// Environment variable holding the passphrase (`passphrase` source).
+    #[serde(default = "TranscriptEncryptionSettings::default_passphrase_env")]
+    pub passphrase_env: String,
+    /// Name of the key in the Secret Service or kernel keyring.
+    #[serde(default = "TranscriptEncryptionSettings::default_key_name")]
+    pub key_name: String,
+    /// Key file for the `file` source; created (mode 0600) when missing.
+    #[serde(default)]
+    pub key_file: Option<PathBuf>,
+}
+
+impl TranscriptEncryptionSettings {
+    fn default_passphrase_env() -> String {
+        "ARCHON_TRANSCRIPT_PASSPHRASE".into()
+    }
+
+    fn default_key_name() -> String {
+        "archon-transcripts".into()
+    }
+}
+
+impl Default for TranscriptEncryptionSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            key_source: TranscriptKeySource::default(),
+            passphrase_env: Self::default_passphrase_env(),
+            key_name: Self::default_key_name(),
+            key_file: None,
+        }
+    }
+}
+
+/// Where the transcript encryption key comes from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum TranscriptKeySource {
+    /// Derived with Argon2id from a passphrase in `passphrase_env`.
+    #[default]
+    Passphrase,
+    /// Stored in the desktop Secret Service via `secret-tool`; generated on first use.
+    SecretService,
+    /// Read from the kernel user keyring via `keyctl`; must be added beforehand.
+    KernelKeyring,
+    /// Hex key in `key_file`; meant for headless setups and tests.
+    File,
+}
+
+impl std::fmt::Display for TranscriptKeySource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TranscriptKeySource::Passphrase => write!(f, "passphrase"),
+            TranscriptKeySource::SecretService => write!(f, "secret-service"),
+            TranscriptKeySource::KernelKeyring => write!(f, "kernel-keyring"),
+            TranscriptKeySource::File => write!(f, "file"),
+        }
+    }
+}
+
 impl LaunchSettings {
     /// Load settings from disk, writing defaults if missing.
     pub fn load_or_default(path: &Path) -> Result<Self> {
@@ -360,6 +436,26 @@ impl LaunchSettings {
         Ok(dirs.data_dir().join("transcripts"))
     }
 
+    /// Resolve path to the automation audit log.
+    pub fn resolve_audit_log(&self) -> Result<PathBuf> {
+        if let Some(path) = &self.automation.audit_log_path {
+            return Ok(path.clone());
+        }
+        let dirs = ProjectDirs::from("sh", "ghostkellz", "Archon")
+            .context("Unable to resolve platform data directory")?;
+        Ok(dirs.data_dir().join("audit").join("actions.log"))
+    }
+
+    /// Resolve path to the recipe scheduler's run history.
+    pub fn resolve_scheduler_history(&self) -> Result<PathBuf> {
+        if let Some(path) = &self.scheduler.history_path {
+            return Ok(path.clone());
+        }
+        let dirs = ProjectDirs::from("sh", "ghostkellz", "Archon")
+            .context("Unable to resolve platform data directory")?;
+        Ok(dirs.data_dir().join("scheduler").join("history.jsonl"))
+    }
+
     /// Retrieve engine-specific configuration by kind.
     pub fn engine_config(&self, kind: EngineKind) -> &EngineSpecificConfig {
         match kind {
@@ -405,6 +501,9 @@ pub struct AiSettings {
     pub default_provider: String,
     #[serde(default = "AiSettings::default_providers")]
     pub providers: Vec<AiProviderConfig>,
+    /// Semantic recall of earlier conversations (opt-in per conversation).
+    #[serde(default)]
+    pub recall: AiRecallSettings,
 }
 
 impl AiSettings {
@@ -429,6 +528,65 @@ impl Default for AiSettings {
         Self {
             default_provider: Self::default_provider_name(),
             providers: Self::default_providers(),
+            recall: AiRecallSettings::default(),
+        }
+    }
+}
+
+/// Embedding-based recall: past transcript messages similar to a new prompt are
+/// offered to the model as context. Only conversations with recall switched on
+/// are embedded or receive recalled context.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AiRecallSettings {
+    #[serde(default)]
+    pub enabled: bool,
+    /// Ollama endpoint serving the embedding model.
+    #[serde(default = "AiRecallSettings::default_endpoint")]
+    pub endpoint: String,
+    #[serde(default = "AiRecallSettings::default_model")]
+    pub model: String,
+    /// Maximum number of recalled messages added to a prompt.
+    #[serde(default = "AiRecallSettings::default_max_results")]
+    pub max_results: usize,
+    /// Cosine similarity a message needs to be recalled (0.0–1.0).
+    #[serde(default = "AiRecallSettings::default_min_similarity")]
+    pub min_similarity: f32,
+    /// Characters of each recalled message included in the prompt.
+    #[serde(default = "AiRecallSettings::default_max_chars")]
+    pub max_chars: usize,
+}
+
+impl AiRecallSettings {
+    fn default_endpoint() -> String {
+        "http://127.0.0.1:11434".into()
+    }
+
+    fn default_model() -> String {
+        "nomic-embed-text".into()
+    }
+
+    fn default_max_results() -> usize {
+        4
+    }
+
+    fn default_min_similarity() -> f32 {
+        0.6
+    }
+
+    fn default_max_chars() -> usize {
+        600
+    }
+}
+
+impl Default for AiRecallSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            endpoint: Self::default_endpoint(),
+            model: Self::default_model(),
+            max_results: Self::default_max_results(),
+            min_similarity: Self::default_min_similarity(),
+            max_chars: Self::default_max_chars(),
         }
     }
 }
@@ -764,6 +922,9 @@ pub struct McpSettings {
     pub docker: Option<McpDockerSettings>,
     #[serde(default)]
     pub connectors: Vec<McpConnector>,
+    /// Archon's own MCP server over Streamable HTTP (served by `archon-host`).
+    #[serde(default)]
+    pub server: McpServerSettings,
 }
 
 /// N8N workflow automation integration settings.
@@ -819,6 +980,12 @@ pub struct ArcSearchSettings {
     /// System prompt enhancement for grounded responses.
     #[serde(default = "ArcSearchSettings::default_system_prompt")]
     pub system_prompt: String,
+    /// Query every enabled provider at once and merge the rankings.
+    #[serde(default)]
+    pub fusion: ArcFusionSettings,
+    /// Download top result pages and ground answers on their text.
+    #[serde(default)]
+    pub fetch: ArcFetchSettings,
 }
 
 impl ArcSearchSettings {
@@ -827,6 +994,11 @@ impl ArcSearchSettings {
             ArcSearchProviderConfig::searxng_default(),
             ArcSearchProviderConfig::brave_default(),
             ArcSearchProviderConfig::tavily_default(),
+            ArcSearchProviderConfig::kagi_default(),
+            ArcSearchProviderConfig::exa_default(),
+            ArcSearchProviderConfig::mojeek_default(),
+            ArcSearchProviderConfig::marginalia_default(),
+            ArcSearchProviderConfig::local_index_default(),
         ]
     }
 
@@ -850,6 +1022,113 @@ impl Default for ArcSearchSettings {
             max_results: Self::default_max_results(),
             auto_search: false,
             system_prompt: Self::default_system_prompt(),
+            fusion: ArcFusionSettings::default(),
+            fetch: ArcFetchSettings::default(),
+        }
+    }
+}
+
+/// Fetching result pages for Arc grounding.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArcFetchSettings {
+    /// Fetch the top results and add their main text to the search context.
+    #[serde(default)]
+    pub enabled: bool,
+    /// Number of top results to fetch.
+    #[serde(default = "ArcFetchSettings::default_top_n")]
+    pub top_n: usize,
+    /// Timeout for each page request.
+    #[serde(default = "ArcFetchSettings::default_timeout_ms")]
+    pub timeout_ms: u64,
+    /// Bytes read from a page before the rest is dropped.
+    #[serde(default = "ArcFetchSettings::default_max_bytes")]
+    pub max_bytes: usize,
+    /// Target size of a text chunk in characters.
+    #[serde(default = "ArcFetchSettings::default_chunk_chars")]
+    pub chunk_chars: usize,
+    /// Chunks per page (the ones matching the query best) put in the context.
+    #[serde(default = "ArcFetchSettings::default_max_chunks")]
+    pub max_chunks: usize,
+    /// How long a fetched page is reused before it is fetched again.
+    #[serde(default = "ArcFetchSettings::default_cache_ttl_secs")]
+    pub cache_ttl_secs: u64,
+    /// Skip pages that the site's robots.txt disallows for Archon.
+    #[serde(default = "bool_true")]
+    pub respect_robots: bool,
+}
+
+impl ArcFetchSettings {
+    fn default_top_n() -> usize {
+        3
+    }
+
+    fn default_timeout_ms() -> u64 {
+        8_000
+    }
+
+    fn default_max_bytes() -> usize {
+        2 * 1024 * 1024
+    }
+
+    fn default_chunk_chars() -> usize {
+        1_200
+    }
+
+    fn default_max_chunks() -> usize {
+        3
+    }
+
+    fn default_cache_ttl_secs() -> u64 {
+        3_600
+    }
+}
+
+impl Default for ArcFetchSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            top_n: Self::default_top_n(),
+            timeout_ms: Self::default_timeout_ms(),
+            max_bytes: Self::default_max_bytes(),
+            chunk_chars: Self::default_chunk_chars(),
+            max_chunks: Self::default_max_chunks(),
+            cache_ttl_secs: Self::default_cache_ttl_secs(),
+            respect_robots: true,
+        }
+    }
+}
+
+/// Result fusion across Arc search providers.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArcFusionSettings {
+    /// Fuse all enabled providers for searches that do not name a provider.
+    #[serde(default)]
+    pub enabled: bool,
+    /// How long to wait for a provider without its own `timeout_ms`.
+    #[serde(default = "ArcFusionSettings::default_timeout_ms")]
+    pub timeout_ms: u64,
+    /// Reciprocal rank fusion constant `k`: a result at rank `r` from one
+    /// provider contributes `1 / (k + r)`. Larger values flatten the ranking.
+    #[serde(default = "ArcFusionSettings::default_rrf_k")]
+    pub rrf_k: f32,
+}
+
+impl ArcFusionSettings {
+    fn default_timeout_ms() -> u64 {
+        5_000
+    }
+
+    fn default_rrf_k() -> f32 {
+        60.0
+    }
+}
+
+impl Default for ArcFusionSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            timeout_ms: Self::default_timeout_ms(),
+            rrf_k: Self::default_rrf_k(),
         }
     }
 }
@@ -869,6 +1148,10 @@ pub struct ArcSearchProviderConfig {
     /// Whether this provider is enabled.
     #[serde(default = "bool_true")]
     pub enabled: bool,
+    /// Request timeout for this provider, also used as its deadline when
+    /// results are fused (defaults to `fusion.timeout_ms` there).
+    #[serde(default)]
+    pub timeout_ms: Option<u64>,
 }
 
 impl ArcSearchProviderConfig {
@@ -880,6 +1163,7 @@ impl ArcSearchProviderConfig {
             endpoint: "http://localhost:8888".into(),
             api_key_env: None,
             enabled: true,
+            timeout_ms: None,
         }
     }
 
@@ -891,6 +1175,7 @@ impl ArcSearchProviderConfig {
             endpoint: "https://api.search.brave.com/res/v1/web/search".into(),
             api_key_env: Some("BRAVE_SEARCH_API_KEY".into()),
             enabled: false,
+            timeout_ms: None,
         }
     }
 
@@ -902,8 +1187,83 @@ impl ArcSearchProviderConfig {
             endpoint: "https://api.tavily.com/search".into(),
             api_key_env: Some("TAVILY_API_KEY".into()),
             enabled: false,
+            timeout_ms: None,
+        }
+    }
+
+    /// Default Kagi Search configuration.
+    pub fn kagi_default() -> Self {
+        Self {
+            name: "kagi".into(),
+            kind: ArcSearchProviderKind::Kagi,
+            endpoint: "https://kagi.com/api/v0/search".into(),
+            api_key_env: Some("KAGI_API_KEY".into()),
+            enabled: false,
+            timeout_ms: None,
+        }
+    }
+
+    /// Default Exa configuration.
+    pub fn exa_default() -> Self {
+        Self {
+            name: "exa".into(),
+            kind: ArcSearchProviderKind::Exa,
+            endpoint: "https://api.exa.ai/search".into(),
+            api_key_env: Some("EXA_API_KEY".into()),
+            enabled: false,
+            timeout_ms: None,
+        }
+    }
+
+    /// Default Mojeek configuration.
+    pub fn mojeek_default() -> Self {
+        Self {
+            name: "mojeek".into(),
+            kind: ArcSearchProviderKind::Mojeek,
+            endpoint: "https://api.mojeek.com/search".into(),
+            api_key_env: Some("MOJEEK_API_KEY".into()),
+            enabled: false,
+            timeout_ms: None,
         }
     }
+
+    /// Default Marginalia configuration (uses the shared `public` key when
+    /// no key is set).
+    pub fn marginalia_default() -> Self {
+        Self {
+            name: "marginalia".into(),
+            kind: ArcSearchProviderKind::Marginalia,
+            endpoint: "https://api.marginalia.nu".into(),
+            api_key_env: Some("MARGINALIA_API_KEY".into()),
+            enabled: false,
+            timeout_ms: None,
+        }
+    }
+
+    /// Default local index configuration; an empty endpoint means the index
+    /// under the platform data directory.
+    pub fn local_index_default() -> Self {
+        Self {
+            name: "local".into(),
+            kind: ArcSearchProviderKind::LocalIndex,
+            endpoint: String::new(),
+            api_key_env: None,
+            enabled: false,
+            timeout_ms: None,
+        }
+    }
+
+    /// Resolve the index file of a [`ArcSearchProviderKind::LocalIndex`]
+    /// provider: `endpoint` when set, else `<data>/arc/local-index.sqlite`.
+    pub fn resolve_local_index(&self) -> Result<PathBuf> {
+        let endpoint = self.endpoint.trim();
+        if !endpoint.is_empty() {
+            return Ok(PathBuf::from(endpoint));
+        }
+        let dirs = ProjectDirs::from("sh", "ghostkellz", "Archon")
+            .context("Unable to resolve platform data directory")?;
+        Ok(dirs.data_dir().join("arc").join("local-index.sqlite"))
+    }
 }
 
 /// Supported search providers for Arc.
@@ -920,6 +1280,16 @@ pub enum ArcSearchProviderKind {
     Tavily,
     /// DuckDuckGo (via HTML scraping - rate limited).
     DuckDuckGo,
+    /// Kagi Search API (paid, ad-free).
+    Kagi,
+    /// Exa neural search API.
+    Exa,
+    /// Mojeek independent, no-tracking index.
+    Mojeek,
+    /// Marginalia search for the small, non-commercial web.
+    Marginalia,
+    /// Archon's own index of browsing history and saved pages.
+    LocalIndex,
 }
 
 /// Opt-in telemetry configuration shared across Archon services.
@@ -1017,16 +1387,121 @@ pub struct McpDockerSettings {
     pub auto_start: bool,
 }
 
-/// External MCP connectors Archon can target (n8n, langchain, etc.).
+/// Streamable HTTP transport for Archon's MCP server, mounted on
+/// `archon-host`. All sessions share one browser.
 #[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct McpServerSettings {
+    /// Serve MCP at `path` (disabled by default).
+    #[serde(default)]
+    pub http_enabled: bool,
+    #[serde(default = "McpServerSettings::default_path")]
+    pub path: String,
+    /// Environment variable holding the bearer token clients must send. The
+    /// transport refuses to start when it is unset.
+    #[serde(default = "McpServerSettings::default_token_env")]
+    pub token_env: String,
+    /// Browser origins allowed besides localhost (e.g. `https://app.example`).
+    /// Requests without an `Origin` header (non-browser clients) are allowed.
+    #[serde(default)]
+    pub allowed_origins: Vec<String>,
+    /// Attach to the running Archon browser instead of launching one.
+    #[serde(default = "bool_true")]
+    pub attach: bool,
+    /// Sessions idle longer than this are dropped.
+    #[serde(default = "McpServerSettings::default_session_idle_secs")]
+    pub session_idle_secs: u64,
+    /// Maximum concurrent sessions.
+    #[serde(default = "McpServerSettings::default_max_sessions")]
+    pub max_sessions: usize,
+}
+
+impl McpServerSettings {
+    fn default_path() -> String {
+        "/mcp".into()
+    }
+
+    fn default_token_env() -> String {
+        "ARCHON_MCP_TOKEN".into()
+    }
+
+    fn default_session_idle_secs() -> u64 {
+        30 * 60
+    }
+
+    fn default_max_sessions() -> usize {
+        16
+    }
+}
+
+impl Default for McpServerSettings {
+    fn default() -> Self {
+        Self {
+            http_enabled: false,
+            path: Self::default_path(),
+            token_env: Self::default_token_env(),
+            allowed_origins: Vec::new(),
+            attach: true,
+            session_idle_secs: Self::default_session_idle_secs(),
+            max_sessions: Self::default_max_sessions(),
+        }
+    }
+}
+
+/// External MCP connectors Archon can target (n8n, langchain, etc.).
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct McpConnector {
     pub name: String,
     pub kind: String,
+    /// Base URL for `rest` connectors, MCP endpoint URL for `http` ones.
+    #[serde(default)]
     pub endpoint: String,
     #[serde(default)]
     pub api_key_env: Option<String>,
     #[serde(default)]
     pub enabled: bool,
+    #[serde(default)]
+    pub transport: McpTransport,
+    /// Executable launched for `stdio` connectors.
+    #[serde(default)]
+    pub command: Option<String>,
+    #[serde(default)]
+    pub args: Vec<String>,
+    /// Extra environment variables for the `stdio` server process.
+    #[serde(default)]
+    pub env: BTreeMap<String, String>,
+    /// Tools of this connector that `/chat` may offer to the model (`"*"`
+    /// for all). Empty offers none.
+    #[serde(default)]
+    pub chat_tools: Vec<String>,
+    /// Let tools this server annotates `readOnlyHint` run from `/chat`
+    /// without confirmation. Off by default: the hint is the server's claim.
+    #[serde(default)]
+    pub trust_read_only_hints: bool,
+}
+
+/// How Archon talks to an [`McpConnector`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum McpTransport {
+    /// Archon's original REST shape: `POST <endpoint>/tool-call` with
+    /// `{tool, arguments}`, probed through `GET <endpoint>/health`.
+    #[default]
+    Rest,
+    /// A standard MCP server spawned from `command`/`args`, speaking
+    /// newline-delimited JSON-RPC over stdio.
+    Stdio,
+    /// A standard MCP server reached at `endpoint` over Streamable HTTP.
+    Http,
+}
+
+impl std::fmt::Display for McpTransport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            McpTransport::Rest => write!(f, "rest"),
+            McpTransport::Stdio => write!(f, "stdio"),
+            McpTransport::Http => write!(f, "http"),
+        }
+    }
 }
 
 /// Settings controlling ENS / Unstoppable / Hedera / XRPL resolution.
@@ -1565,6 +2040,66 @@ pub struct AutomationSettings {
     /// are unaffected by this flag.
     #[serde(default)]
     pub allow_unattended_high_risk: bool,
+    /// Permit the MCP `evaluate` tool to run arbitrary JavaScript in the
+    /// page. Off by default even when automation is enabled, since a script
+    /// can do anything the page can.
+    #[serde(default)]
+    pub allow_script_evaluation: bool,
+    /// Let the MCP `get_cookies` tool return cookie values. Off by default:
+    /// names, domains and flags are listed but values (which include HttpOnly
+    /// session tokens) are redacted.
+    #[serde(default)]
+    pub expose_cookie_values: bool,
+    /// Record agent/recipe network traffic via CDP: saves a HAR file next to the
+    /// run's artifacts and enables the `extract_response` action.
+    #[serde(default)]
+    pub capture_network: bool,
+    /// Keep `Cookie`, `Set-Cookie` and `Authorization` headers and request
+    /// bodies in saved HAR files. Off by default: they are replaced with
+    /// `[redacted]`, since they often carry session tokens and credentials.
+    #[serde(default)]
+    pub har_include_sensitive: bool,
+    /// URL globs (`*`/`?`) blocked during agent and recipe runs, e.g.
+    /// `"*://*.doubleclick.net/*"` for third-party trackers.
+    #[serde(default)]
+    pub block_url_patterns: Vec<String>,
+    /// Per-domain rules layered over the global switches above, evaluated in
+    /// order (later rules win where they conflict).
+    #[serde(default)]
+    pub rules: Vec<AutomationRule>,
+    /// Append executed (and declined) browser actions to a hash-chained audit
+    /// log; check it with `archon --audit-verify`.
+    #[serde(default = "bool_true")]
+    pub audit_log: bool,
+    /// Optional override for the audit log location (defaults to
+    /// `<data dir>/audit/actions.log`).
+    #[serde(default)]
+    pub audit_log_path: Option<PathBuf>,
+}
+
+/// A per-domain automation rule, e.g. making a bank read-only or letting an
+/// internal dashboard run unattended.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AutomationRule {
+    /// Domain glob (`*`/`?`, case-insensitive), e.g. `*.bank.example`.
+    pub domain: String,
+    /// Action types the rule applies to (`click`, `type`, `navigate`, ...);
+    /// empty applies to every action.
+    #[serde(default)]
+    pub actions: Vec<String>,
+    /// Override the action's risk level (`low`, `medium`, `high`, `critical`).
+    #[serde(default)]
+    pub risk: Option<String>,
+    /// Force (`true`) or waive (`false`) confirmation, regardless of risk and
+    /// the global `require_confirmation`.
+    #[serde(default)]
+    pub require_confirmation: Option<bool>,
+    /// Reject `type` actions on matching domains.
+    #[serde(default)]
+    pub forbid_typing: bool,
+    /// Cap on matching actions per minute, per domain.
+    #[serde(default)]
+    pub max_actions_per_minute: Option<u32>,
 }
 
 impl AutomationSettings {
@@ -1594,6 +2129,14 @@ impl Default for AutomationSettings {
             sandbox_mode: true,
             remote_debug_port: Self::default_remote_debug_port(),
             allow_unattended_high_risk: false,
+            allow_script_evaluation: false,
+            expose_cookie_values: false,
+            capture_network: false,
+            har_include_sensitive: false,
+            block_url_patterns: Vec::new(),
+            rules: Vec::new(),
+            audit_log: true,
+            audit_log_path: None,
         }
     }
 }
@@ -1624,9 +2167,19 @@ pub struct ConduitSettings {
     /// Inject matching CSS files.
     #[serde(default = "bool_true")]
     pub inject_css: bool,
-    /// Polling interval (milliseconds) for discovering new/navigated tabs.
+    /// Interval (milliseconds) for discovering newly opened tabs; navigations
+    /// are picked up from `Page.frameNavigated` events.
     #[serde(default = "ConduitSettings::default_poll_interval_ms")]
     pub poll_interval_ms: u64,
+    /// Environment variable holding a bearer token that `archon-host`
+    /// requires on requests changing scripts. Unset (the default), those
+    /// requests are only accepted from loopback clients.
+    #[serde(default)]
+    pub token_env: Option<String>,
+    /// Browser origins allowed to change scripts besides localhost. Requests
+    /// without an `Origin` header (non-browser clients) are allowed.
+    #[serde(default)]
+    pub allowed_origins: Vec<String>,
 }
 
 impl ConduitSettings {
@@ -1656,10 +2209,120 @@ impl Default for ConduitSettings {
             inject_js: true,
             inject_css: true,
             poll_interval_ms: Self::default_poll_interval_ms(),
+            token_env: None,
+            allowed_origins: Vec::new(),
         }
     }
 }
 
+// ============================================================================
+// Scheduler Settings
+// ============================================================================
+
+/// Scheduled and webhook-triggered recipe runs, executed by `archon-host`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SchedulerSettings {
+    /// Run the scheduler inside `archon-host` (disabled by default).
+    #[serde(default)]
+    pub enabled: bool,
+    /// Maximum recipe runs in flight at once; further runs are skipped.
+    #[serde(default = "SchedulerSettings::default_max_concurrent")]
+    pub max_concurrent: usize,
+    /// Skip runs while the machine is on battery power.
+    #[serde(default = "bool_true")]
+    pub skip_on_battery: bool,
+    /// Skip runs while no network interface is up.
+    #[serde(default = "bool_true")]
+    pub skip_when_offline: bool,
+    /// Run history (JSON lines). Defaults to `<data dir>/scheduler/history.jsonl`.
+    #[serde(default)]
+    pub history_path: Option<PathBuf>,
+    /// Number of runs kept in the history file.
+    #[serde(default = "SchedulerSettings::default_history_limit")]
+    pub history_limit: usize,
+    /// Scheduled recipes.
+    #[serde(default)]
+    pub jobs: Vec<ScheduledRecipe>,
+}
+
+impl SchedulerSettings {
+    fn default_max_concurrent() -> usize {
+        1
+    }
+
+    fn default_history_limit() -> usize {
+        500
+    }
+}
+
+impl Default for SchedulerSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_concurrent: Self::default_max_concurrent(),
+            skip_on_battery: true,
+            skip_when_offline: true,
+            history_path: None,
+            history_limit: Self::default_history_limit(),
+            jobs: Vec::new(),
+        }
+    }
+}
+
+/// A recipe run on a cron schedule and/or when its trigger webhook is called.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScheduledRecipe {
+    /// Unique job name (used in history and the trigger URL).
+    pub name: String,
+    /// Recipe path or bare name (resolved like `archon --automate`).
+    pub recipe: String,
+    /// Five-field cron expression in local time (`min hour dom month dow`),
+    /// or `@hourly`/`@daily`/`@weekly`/`@monthly`/`@yearly`.
+    #[serde(default)]
+    pub cron: Option<String>,
+    /// Accept `POST /scheduler/jobs/<name>/trigger` (e.g. from an n8n workflow).
+    #[serde(default)]
+    pub webhook: bool,
+    /// Environment variable holding the bearer token trigger requests must
+    /// carry. Webhook jobs without one refuse every trigger.
+    #[serde(default)]
+    pub webhook_token_env: Option<String>,
+    /// Recipe parameters (`--param` equivalents).
+    #[serde(default)]
+    pub params: BTreeMap<String, String>,
+    /// Parameters a webhook trigger may override; others are refused.
+    #[serde(default)]
+    pub webhook_params: Vec<String>,
+    /// Perform real actions (requires `automation.enabled`); previews otherwise.
+    #[serde(default)]
+    pub execute: bool,
+    /// Attach to the running browser instead of launching a headless one.
+    #[serde(default)]
+    pub attach: bool,
+    /// Step budget for goal steps (default 8).
+    #[serde(default)]
+    pub max_steps: Option<usize>,
+    /// Where to send each run's result.
+    #[serde(default)]
+    pub deliver: Option<DeliverySettings>,
+    #[serde(default = "bool_true")]
+    pub enabled: bool,
+}
+
+/// Delivery targets for a scheduled run's result.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DeliverySettings {
+    /// POST the result as JSON to this URL.
+    #[serde(default)]
+    pub webhook_url: Option<String>,
+    /// Call this n8n webhook path through the n8n integration.
+    #[serde(default)]
+    pub n8n_webhook: Option<String>,
+    /// n8n instance for `n8n_webhook` (default instance when unset).
+    #[serde(default)]
+    pub n8n_instance: Option<String>,
+}
+
 // ============================================================================
 // IPFS Settings
 // ============================================================================
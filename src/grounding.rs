@@ -0,0 +1,1417 @@
+//! Page fetching for Arc grounding.
+//!
+//! The top search results are downloaded (bounded in size and time, honouring
+//! robots.txt), reduced to their main content as Markdown and split into
+//! chunks; the chunks that best match the query are added to the search
+//! context. Fetched pages are cached per canonical URL for `cache_ttl_secs`.
+//!
+//! Result URLs come from third parties, so pages are only fetched from public
+//! addresses: loopback, private, link-local and similar targets are refused
+//! for the first request and every redirect hop, and each hop is checked
+//! against its site's robots.txt.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::io::Read;
+use std::net::{IpAddr, SocketAddr, ToSocketAddrs};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use anyhow::{Context, Result, anyhow, bail};
+use chrono::{DateTime, Utc};
+use reqwest::blocking::Client;
+use reqwest::dns::{Addrs, Name, Resolve, Resolving};
+use reqwest::redirect::Policy;
+use scraper::{ElementRef, Html, Node, Selector};
+use serde::{Deserialize, Serialize};
+use tracing::{debug, warn};
+use url::Url;
+
+use crate::config::ArcFetchSettings;
+use crate::search::{SearchResult, canonical_url};
+use crate::sync_util::LockResultExt;
+
+/// Product token matched against robots.txt `User-agent` lines.
+const ROBOTS_AGENT: &str = "archon";
+
+/// Bytes read from a robots.txt file.
+const MAX_ROBOTS_BYTES: usize = 512 * 1024;
+
+/// Redirects followed for a single page.
+const MAX_REDIRECTS: usize = 5;
+
+/// Pages kept in the cache; the oldest is dropped beyond this.
+const MAX_CACHED_PAGES: usize = 256;
+
+/// Paragraph text an `article`/`main` element needs to be taken as the content.
+const MIN_CONTENT_CHARS: usize = 200;
+
+/// Elements that never hold main content.
+const SKIPPED_TAGS: &[&str] = &[
+    "script", "style", "noscript", "template", "svg", "canvas", "iframe", "nav", "header",
+    "footer", "aside", "form", "button", "select", "input", "textarea", "dialog", "menu",
+];
+
+/// `class`/`id` words marking navigation, ads and other boilerplate.
+const BOILERPLATE_HINTS: &[&str] = &[
+    "nav",
+    "menu",
+    "footer",
+    "sidebar",
+    "comment",
+    "share",
+    "social",
+    "advert",
+    "ad",
+    "ads",
+    "cookie",
+    "banner",
+    "promo",
+    "related",
+    "newsletter",
+    "subscribe",
+    "breadcrumb",
+    "popup",
+    "modal",
+];
+
+/// Response of a bounded GET request. Redirects are not followed.
+#[derive(Debug, Clone)]
+pub struct HttpPage {
+    pub status: u16,
+    /// `Location` header of a redirect response.
+    pub location: Option<String>,
+    pub content_type: Option<String>,
+    pub body: Vec<u8>,
+    /// Whether the body was cut at the size limit.
+    pub truncated: bool,
+}
+
+/// HTTP access used by [`PageFetcher`].
+pub trait PageHttp: Send + Sync {
+    /// GET `url`, reading at most `max_bytes` of the body.
+    fn get(&self, url: &str, max_bytes: usize) -> Result<HttpPage>;
+}
+
+/// [`PageHttp`] over a blocking reqwest client. Redirects are left to
+/// [`PageFetcher`] so each hop is checked, and host names only resolve to
+/// public addresses ([`PublicResolver`]). System proxies are bypassed, since
+/// a proxy would resolve targets itself.
+pub struct BlockingPageHttp {
+    client: Client,
+}
+
+impl BlockingPageHttp {
+    pub fn new(timeout: Duration) -> Result<Self> {
+        let client = Client::builder()
+            .timeout(timeout)
+            .redirect(Policy::none())
+            .dns_resolver(Arc::new(PublicResolver))
+            .no_proxy()
+            .user_agent("Archon/0.1 (arc-search)")
+            .build()
+            .context("Failed to build HTTP client for Arc page fetching")?;
+        Ok(Self { client })
+    }
+}
+
+impl PageHttp for BlockingPageHttp {
+    fn get(&self, url: &str, max_bytes: usize) -> Result<HttpPage> {
+        let parsed = Url::parse(url).with_context(|| format!("invalid URL '{url}'"))?;
+        if let Some(url::Host::Ipv4(ip)) = parsed.host() {
+            ensure_public(IpAddr::V4(ip), url)?;
+        }
+        if let Some(url::Host::Ipv6(ip)) = parsed.host() {
+            ensure_public(IpAddr::V6(ip), url)?;
+        }
+        let response = self
+            .client
+            .get(url)
+            .header("Accept", "text/html,application/xhtml+xml,text/plain;q=0.8")
+            .send()
+            .with_context(|| format!("Failed to fetch {url}"))?;
+        let status = response.status().as_u16();
+        let location = response
+            .headers()
+            .get(reqwest::header::LOCATION)
+            .and_then(|value| value.to_str().ok())
+            .map(|value| value.to_string());
+        let content_type = response
+            .headers()
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .map(|value| value.to_string());
+        let mut body = Vec::new();
+        response
+            .take(max_bytes as u64 + 1)
+            .read_to_end(&mut body)
+            .with_context(|| format!("Failed to read {url}"))?;
+        let truncated = body.len() > max_bytes;
+        body.truncate(max_bytes);
+        Ok(HttpPage {
+            status,
+            location,
+            content_type,
+            body,
+            truncated,
+        })
+    }
+}
+
+/// DNS resolver that fails for names resolving to any non-public address, so
+/// the check holds at connect time (no window for DNS rebinding).
+struct PublicResolver;
+
+impl Resolve for PublicResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        Box::pin(async move {
+            let host = name.as_str().to_string();
+            let addrs = tokio::task::spawn_blocking(move || -> Result<Vec<SocketAddr>> {
+                let addrs: Vec<SocketAddr> = (host.as_str(), 0)
+                    .to_socket_addrs()
+                    .with_context(|| format!("failed to resolve {host}"))?
+                    .collect();
+                for addr in &addrs {
+                    ensure_public(addr.ip(), &host)?;
+                }
+                Ok(addrs)
+            })
+            .await
+            .map_err(|err| anyhow!("resolver task failed: {err}"))
+            .and_then(|result| result)
+            .map_err(|err| -> Box<dyn std::error::Error + Send + Sync> { err.into() })?;
+            Ok(Box::new(addrs.into_iter()) as Addrs)
+        })
+    }
+}
+
+/// Refuse `ip` (reached for `target`) unless it is a public unicast address.
+fn ensure_public(ip: IpAddr, target: &str) -> Result<()> {
+    if !is_public_ip(ip) {
+        bail!("refusing to fetch {target}: {ip} is not a public address");
+    }
+    Ok(())
+}
+
+/// Whether `ip` is publicly routable: not loopback, private (RFC 1918 or
+/// unique local), link-local, shared (CGNAT), unspecified, multicast or
+/// otherwise reserved.
+fn is_public_ip(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            let [a, b, ..] = ip.octets();
+            !(ip.is_loopback()
+                || ip.is_private()
+                || ip.is_link_local()
+                || ip.is_unspecified()
+                || ip.is_broadcast()
+                || ip.is_multicast()
+                || ip.is_documentation()
+                || a == 0
+                || a >= 240
+                || (a == 100 && (64..128).contains(&b))
+                || (a == 198 && (b == 18 || b == 19)))
+        }
+        IpAddr::V6(ip) => {
+            if let Some(mapped) = ip.to_ipv4_mapped() {
+                return is_public_ip(IpAddr::V4(mapped));
+            }
+            let first = ip.segments()[0];
+            !(ip.is_loopback()
+                || ip.is_unspecified()
+                || ip.is_multicast()
+                || (first & 0xfe00) == 0xfc00
+                || (first & 0xffc0) == 0xfe80
+                || first == 0x2001 && ip.segments()[1] == 0x0db8)
+        }
+    }
+}
+
+/// Readable content of a fetched page.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FetchedPage {
+    /// URL after redirects.
+    pub url: String,
+    pub title: Option<String>,
+    /// Main content as Markdown.
+    pub markdown: String,
+    /// `markdown` split into chunks of about `chunk_chars`.
+    pub chunks: Vec<String>,
+    /// Whether the page was cut at `max_bytes`.
+    pub truncated: bool,
+    pub fetched_at: DateTime<Utc>,
+}
+
+/// Excerpts of a fetched search result used for grounding.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GroundedSource {
+    /// Index of the result in the search results.
+    pub result_index: usize,
+    pub url: String,
+    #[serde(default)]
+    pub title: Option<String>,
+    /// Chunks matching the query best, in page order.
+    pub excerpts: Vec<String>,
+}
+
+/// Fetches, extracts and caches result pages.
+#[derive(Clone)]
+pub struct PageFetcher {
+    settings: ArcFetchSettings,
+    http: Arc<dyn PageHttp>,
+    pages: Arc<Mutex<HashMap<String, (Instant, FetchedPage)>>>,
+    robots: Arc<Mutex<HashMap<String, (Instant, RobotsRules)>>>,
+}
+
+impl fmt::Debug for PageFetcher {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PageFetcher")
+            .field("settings", &self.settings)
+            .field("cached_pages", &self.pages.lock().recover().len())
+            .finish()
+    }
+}
+
+impl PageFetcher {
+    /// Create a fetcher using a real HTTP client.
+    pub fn from_settings(settings: ArcFetchSettings) -> Result<Self> {
+        let http = BlockingPageHttp::new(Duration::from_millis(settings.timeout_ms))?;
+        Ok(Self::with_http(settings, Arc::new(http)))
+    }
+
+    /// Create a fetcher over a custom HTTP implementation.
+    pub fn with_http(settings: ArcFetchSettings, http: Arc<dyn PageHttp>) -> Self {
+        Self {
+            settings,
+            http,
+            pages: Arc::new(Mutex::new(HashMap::new())),
+            robots: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    pub fn settings(&self) -> &ArcFetchSettings {
+        &self.settings
+    }
+
+    /// Fetch `url` (or reuse a cached copy younger than the TTL) and extract
+    /// its main content.
+    pub fn fetch(&self, url: &str) -> Result<FetchedPage> {
+        let key = canonical_url(url);
+        let ttl = Duration::from_secs(self.settings.cache_ttl_secs);
+        if let Some((stored, page)) = self.pages.lock().recover().get(&key)
+            && stored.elapsed() < ttl
+        {
+            debug!(url, "using cached page");
+            return Ok(page.clone());
+        }
+
+        let parsed = Url::parse(url).with_context(|| format!("invalid URL '{url}'"))?;
+        let (final_url, response) = self.get_following(
+            parsed,
+            self.settings.max_bytes,
+            self.settings.respect_robots,
+        )?;
+        if !(200..300).contains(&response.status) {
+            bail!("{url} returned status {}", response.status);
+        }
+        let content_type = response
+            .content_type
+            .as_deref()
+            .unwrap_or("text/html")
+            .to_ascii_lowercase();
+        let text = String::from_utf8_lossy(&response.body);
+        let (title, markdown) = if content_type.contains("html") {
+            extract_readable(&text, Some(&final_url))
+        } else if content_type.starts_with("text/") {
+            (None, text.trim().to_string())
+        } else {
+            bail!("{url} is not a text page ({content_type})");
+        };
+        if markdown.is_empty() {
+            bail!("no readable content found on {url}");
+        }
+
+        let page = FetchedPage {
+            url: final_url.to_string(),
+            title,
+            chunks: chunk_markdown(&markdown, self.settings.chunk_chars),
+            markdown,
+            truncated: response.truncated,
+            fetched_at: Utc::now(),
+        };
+        let mut pages = self.pages.lock().recover();
+        if pages.len() >= MAX_CACHED_PAGES
+            && let Some(oldest) = pages
+                .iter()
+                .min_by_key(|(_, (stored, _))| *stored)
+                .map(|(key, _)| key.clone())
+        {
+            pages.remove(&oldest);
+        }
+        pages.insert(key, (Instant::now(), page.clone()));
+        Ok(page)
+    }
+
+    /// Fetch the top `top_n` results in parallel and pick the excerpts that
+    /// best match `query`. Pages that cannot be fetched are skipped.
+    pub fn ground(&self, query: &str, results: &[SearchResult]) -> Vec<GroundedSource> {
+        let targets: Vec<(usize, &SearchResult)> = results
+            .iter()
+            .enumerate()
+            .filter(|(_, result)| !result.url.trim().is_empty())
+            .take(self.settings.top_n)
+            .collect();
+        let pages: Vec<(usize, &SearchResult, Result<FetchedPage>)> = thread::scope(|scope| {
+            let handles: Vec<_> = targets
+                .into_iter()
+                .map(|(index, result)| {
+                    (index, result, scope.spawn(move || self.fetch(&result.url)))
+                })
+                .collect();
+            handles
+                .into_iter()
+                .map(|(index, result, handle)| {
+                    let page = handle
+                        .join()
+                        .unwrap_or_else(|_| Err(anyhow::anyhow!("page fetch panicked")));
+                    (index, result, page)
+                })
+                .collect()
+        });
+
+        pages
+            .into_iter()
+            .filter_map(|(index, result, page)| match page {
+                Ok(page) => Some(GroundedSource {
+                    result_index: index,
+                    url: page.url.clone(),
+                    title: page.title.clone(),
+                    excerpts: select_chunks(&page.chunks, query, self.settings.max_chunks)
+                        .into_iter()
+                        .map(str::to_string)
+                        .collect(),
+                }),
+                Err(err) => {
+                    warn!(url = %result.url, error = %err, "skipping page for grounding");
+                    None
+                }
+            })
+            .filter(|source| !source.excerpts.is_empty())
+            .collect()
+    }
+
+    /// GET `url`, following up to [`MAX_REDIRECTS`] redirects. Every hop must
+    /// be http(s) and, with `check_robots`, allowed by its site's robots.txt.
+    /// Returns the URL that answered along with its response.
+    fn get_following(
+        &self,
+        url: Url,
+        max_bytes: usize,
+        check_robots: bool,
+    ) -> Result<(Url, HttpPage)> {
+        let mut current = url;
+        for _ in 0..=MAX_REDIRECTS {
+            if !matches!(current.scheme(), "http" | "https") {
+                bail!("only http(s) pages can be fetched, not '{current}'");
+            }
+            if check_robots && !self.robots_allow(&current) {
+                bail!("robots.txt disallows fetching {current}");
+            }
+            let response = self.http.get(current.as_str(), max_bytes)?;
+            let redirect = response
+                .location
+                .as_deref()
+                .filter(|_| (300..400).contains(&response.status));
+            let Some(location) = redirect else {
+                return Ok((current, response));
+            };
+            let next = current
+                .join(location)
+                .with_context(|| format!("{current} redirected to an invalid URL '{location}'"))?;
+            debug!(from = %current, to = %next, "following redirect");
+            current = next;
+        }
+        bail!("{current} redirected more than {MAX_REDIRECTS} times")
+    }
+
+    fn robots_allow(&self, url: &Url) -> bool {
+        let origin = url.origin().ascii_serialization();
+        let ttl = Duration::from_secs(self.settings.cache_ttl_secs);
+        let mut path = url.path().to_string();
+        if let Some(query) = url.query() {
+            path.push('?');
+            path.push_str(query);
+        }
+        if let Some((stored, rules)) = self.robots.lock().recover().get(&origin)
+            && stored.elapsed() < ttl
+        {
+            return rules.allows(&path);
+        }
+
+        let robots_url = format!("{origin}/robots.txt");
+        let fetched = Url::parse(&robots_url)
+            .map_err(anyhow::Error::from)
+            .and_then(|robots| self.get_following(robots, MAX_ROBOTS_BYTES, false))
+            .map(|(_, response)| response);
+        let rules = match fetched {
+            Ok(response) if (200..300).contains(&response.status) => {
+                RobotsRules::parse(&String::from_utf8_lossy(&response.body), ROBOTS_AGENT)
+            }
+            // No robots.txt (4xx) means no restrictions.
+            Ok(response) if (400..500).contains(&response.status) => RobotsRules::default(),
+            Ok(response) => {
+                warn!(url = %robots_url, status = response.status, "robots.txt unavailable; not fetching from this site");
+                RobotsRules::disallow_all()
+            }
+            Err(err) => {
+                warn!(url = %robots_url, error = %err, "robots.txt unreachable; not fetching from this site");
+                RobotsRules::disallow_all()
+            }
+        };
+        let allowed = rules.allows(&path);
+        self.robots
+            .lock()
+            .recover()
+            .insert(origin, (Instant::now(), rules));
+        allowed
+    }
+}
+
+/// `Allow`/`Disallow` rules of the robots.txt group that applies to an agent.
+#[derive(Debug, Clone, Default)]
+pub struct RobotsRules {
+    rules: Vec<(bool, String)>,
+}
+
+impl RobotsRules {
+    /// Rules for `agent` from a robots.txt body: the groups naming the agent,
+    /// or the `*` groups when none does.
+    pub fn parse(text: &str, agent: &str) -> Self {
+        let agent = agent.to_ascii_lowercase();
+        let mut specific = Vec::new();
+        let mut wildcard = Vec::new();
+        let mut agents: Vec<String> = Vec::new();
+        let mut in_rules = false;
+
+        for line in text.lines() {
+            let line = line.split('#').next().unwrap_or_default().trim();
+            let Some((field, value)) = line.split_once(':') else {
+                continue;
+            };
+            let field = field.trim().to_ascii_lowercase();
+            let value = value.trim();
+            match field.as_str() {
+                "user-agent" => {
+                    if in_rules {
+                        agents.clear();
+                        in_rules = false;
+                    }
+                    agents.push(value.to_ascii_lowercase());
+                }
+                "allow" | "disallow" => {
+                    in_rules = true;
+                    if value.is_empty() {
+                        continue;
+                    }
+                    let rule = (field == "allow", value.to_string());
+                    if agents
+                        .iter()
+                        .any(|name| name != "*" && agent.starts_with(name))
+                    {
+                        specific.push(rule.clone());
+                    }
+                    if agents.iter().any(|name| name == "*") {
+                        wildcard.push(rule);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        Self {
+            rules: if specific.is_empty() {
+                wildcard
+            } else {
+                specific
+            },
+        }
+    }
+
+    fn disallow_all() -> Self {
+        Self {
+            rules: vec![(false, "/".to_string())],
+        }
+    }
+
+    /// Whether `path` (with query) may be fetched: the longest matching rule
+    /// wins and `Allow` wins ties.
+    pub fn allows(&self, path: &str) -> bool {
+        self.rules
+            .iter()
+            .filter(|(_, pattern)| robots_match(pattern, path))
+            .max_by_key(|(allow, pattern)| (pattern.len(), *allow))
+            .is_none_or(|(allow, _)| *allow)
+    }
+}
+
+/// Match a robots.txt path pattern (`*` wildcards, trailing `$` anchor).
+fn robots_match(pattern: &str, path: &str) -> bool {
+    let (pattern, anchored) = match pattern.strip_suffix('$') {
+        Some(pattern) => (pattern, true),
+        None => (pattern, false),
+    };
+    let parts: Vec<&str> = pattern.split('*').collect();
+    if !path.starts_with(parts[0]) {
+        return false;
+    }
+    let mut position = parts[0].len();
+    for (index, part) in parts.iter().enumerate().skip(1) {
+        if anchored && index == parts.len() - 1 {
+            return path[position..].ends_with(part);
+        }
+        match path[position..].find(part) {
+            Some(offset) => position += offset + part.len(),
+            None => return false,
+        }
+    }
+    !anchored || position == path.len()
+}
+
+/// Extract the title and main content of an HTML page as Markdown, dropping
+/// navigation, scripts and other boilerplate. Relative links resolve against
+/// `base`.
+pub fn extract_readable(html: &str, base: Option<&Url>) -> (Option<String>, String) {
+    let document = Html::parse_document(html);
+    let title = select_first(&document, "title")
+        .or_else(|| select_first(&document, "h1"))
+        .map(|element| collapse_whitespace(&element.text().collect::<String>()))
+        .filter(|title| !title.is_empty());
+
+    let Some(root) = main_content(&document) else {
+        return (title, String::new());
+    };
+    let mut markdown = String::new();
+    render_blocks(root, base, 0, &mut markdown);
+    (title, tidy_markdown(&markdown))
+}
+
+fn select_first<'a>(document: &'a Html, selector: &str) -> Option<ElementRef<'a>> {
+    let selector = Selector::parse(selector).expect("valid selector");
+    document.select(&selector).next()
+}
+
+/// The element holding the page's main text: the largest `article`/`main`
+/// when it has enough text, otherwise the block whose own paragraphs carry the
+/// most non-link text, falling back to `body`.
+fn main_content(document: &Html) -> Option<ElementRef<'_>> {
+    for selector in ["article", "main", "[role=main]"] {
+        let selector = Selector::parse(selector).expect("valid selector");
+        if let Some(best) = document
+            .select(&selector)
+            .max_by_key(|element| paragraph_chars(*element))
+            && paragraph_chars(best) >= MIN_CONTENT_CHARS
+        {
+            return Some(best);
+        }
+    }
+
+    let blocks = Selector::parse("div, section, td").expect("valid selector");
+    document
+        .select(&blocks)
+        .filter(|element| !is_boilerplate(element))
+        .map(|element| (content_score(&element), element))
+        .filter(|(score, _)| *score > 0.0)
+        .max_by(|(a, _), (b, _)| a.total_cmp(b))
+        .map(|(_, element)| element)
+        .or_else(|| select_first(document, "body"))
+}
+
+fn paragraph_chars(element: ElementRef<'_>) -> usize {
+    let paragraphs = Selector::parse("p, pre").expect("valid selector");
+    element
+        .select(&paragraphs)
+        .map(|paragraph| text_chars(paragraph))
+        .sum()
+}
+
+fn content_score(element: &ElementRef<'_>) -> f32 {
+    let own_paragraphs: usize = element
+        .children()
+        .filter_map(ElementRef::wrap)
+        .filter(|child| matches!(child.value().name(), "p" | "pre" | "blockquote"))
+        .map(text_chars)
+        .sum();
+    own_paragraphs as f32 * (1.0 - link_density(element))
+}
+
+fn link_density(element: &ElementRef<'_>) -> f32 {
+    let total = text_chars(*element);
+    if total == 0 {
+        return 1.0;
+    }
+    let links = Selector::parse("a").expect("valid selector");
+    let linked: usize = element.select(&links).map(text_chars).sum();
+    linked as f32 / total as f32
+}
+
+fn text_chars(element: ElementRef<'_>) -> usize {
+    element
+        .text()
+        .map(|text| {
+            text.split_whitespace()
+                .map(|word| word.len() + 1)
+                .sum::<usize>()
+        })
+        .sum()
+}
+
+fn is_boilerplate(element: &ElementRef<'_>) -> bool {
+    let value = element.value();
+    if SKIPPED_TAGS.contains(&value.name()) {
+        return true;
+    }
+    if matches!(value.attr("aria-hidden"), Some("true")) || value.attr("hidden").is_some() {
+        return true;
+    }
+    let role = value.attr("role").unwrap_or_default();
+    if matches!(
+        role,
+        "navigation" | "banner" | "contentinfo" | "complementary"
+    ) {
+        return true;
+    }
+    value
+        .classes()
+        .chain(value.id())
+        .flat_map(|name| name.split(['-', '_']))
+        .any(|word| {
+            let word = word.to_ascii_lowercase();
+            BOILERPLATE_HINTS
+                .iter()
+                .any(|hint| word == *hint || (hint.len() > 3 && word.starts_with(hint)))
+        })
+}
+
+fn is_block(name: &str) -> bool {
+    matches!(
+        name,
+        "p" | "div"
+            | "section"
+            | "article"
+            | "main"
+            | "h1"
+            | "h2"
+            | "h3"
+            | "h4"
+            | "h5"
+            | "h6"
+            | "ul"
+            | "ol"
+            | "li"
+            | "pre"
+            | "blockquote"
+            | "table"
+            | "tr"
+            | "hr"
+            | "figure"
+            | "figcaption"
+            | "dl"
+            | "dt"
+            | "dd"
+    )
+}
+
+/// Render the children of `element` as Markdown blocks.
+fn render_blocks(element: ElementRef<'_>, base: Option<&Url>, depth: usize, out: &mut String) {
+    let mut inline = String::new();
+    for child in element.children() {
+        match child.value() {
+            Node::Text(text) => inline.push_str(text),
+            Node::Element(_) => {
+                let child = ElementRef::wrap(child).expect("element node");
+                if is_boilerplate(&child) {
+                    continue;
+                }
+                if is_block(child.value().name()) {
+                    flush_paragraph(&mut inline, out);
+                    render_block(child, base, depth, out);
+                } else {
+                    inline.push_str(&render_inline(child, base));
+                }
+            }
+            _ => {}
+        }
+    }
+    flush_paragraph(&mut inline, out);
+}
+
+fn flush_paragraph(inline: &mut String, out: &mut String) {
+    let text = collapse_whitespace(inline);
+    if !text.is_empty() {
+        out.push_str(&text);
+        out.push_str("\n\n");
+    }
+    inline.clear();
+}
+
+fn render_block(element: ElementRef<'_>, base: Option<&Url>, depth: usize, out: &mut String) {
+    let name = element.value().name();
+    match name {
+        "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+            let level = name[1..].parse::<usize>().unwrap_or(2);
+            let text = collapse_whitespace(&render_inline_children(element, base));
+            if !text.is_empty() {
+                out.push_str(&format!("{} {text}\n\n", "#".repeat(level)));
+            }
+        }
+        "p" | "dt" | "dd" | "figcaption" => {
+            let text = collapse_whitespace(&render_inline_children(element, base));
+            if !text.is_empty() {
+                out.push_str(&text);
+                out.push_str("\n\n");
+            }
+        }
+        "ul" | "ol" => {
+            let ordered = name == "ol";
+            let mut number = 0;
+            for item in element.children().filter_map(ElementRef::wrap) {
+                if item.value().name() != "li" || is_boilerplate(&item) {
+                    continue;
+                }
+                number += 1;
+                let marker = if ordered {
+                    format!("{number}.")
+                } else {
+                    "-".to_string()
+                };
+                let mut text = String::new();
+                let mut nested = String::new();
+                for child in item.children() {
+                    match child.value() {
+                        Node::Text(value) => text.push_str(value),
+                        Node::Element(_) => {
+                            let child = ElementRef::wrap(child).expect("element node");
+                            if is_boilerplate(&child) {
+                                continue;
+                            }
+                            if matches!(child.value().name(), "ul" | "ol") {
+                                render_block(child, base, depth + 1, &mut nested);
+                            } else if is_block(child.value().name()) {
+                                text.push(' ');
+                                text.push_str(&render_inline_children(child, base));
+                                text.push(' ');
+                            } else {
+                                text.push_str(&render_inline(child, base));
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+                let text = collapse_whitespace(&text);
+                if !text.is_empty() {
+                    out.push_str(&format!("{}{marker} {text}\n", "  ".repeat(depth)));
+                }
+                out.push_str(nested.trim_end_matches('\n'));
+                if !nested.is_empty() {
+                    out.push('\n');
+                }
+            }
+            if depth == 0 {
+                out.push('\n');
+            }
+        }
+        "pre" => {
+            let code: String = element.text().collect();
+            let code = code.trim_matches('\n');
+            if !code.trim().is_empty() {
+                out.push_str(&format!("```\n{code}\n```\n\n"));
+            }
+        }
+        "blockquote" => {
+            let mut inner = String::new();
+            render_blocks(element, base, depth, &mut inner);
+            for line in tidy_markdown(&inner).lines() {
+                out.push_str(if line.is_empty() { ">" } else { "> " });
+                out.push_str(line);
+                out.push('\n');
+            }
+            out.push('\n');
+        }
+        "table" => render_table(element, base, out),
+        "hr" => {}
+        _ => render_blocks(element, base, depth, out),
+    }
+}
+
+fn render_table(table: ElementRef<'_>, base: Option<&Url>, out: &mut String) {
+    let rows = Selector::parse("tr").expect("valid selector");
+    let cells = Selector::parse("th, td").expect("valid selector");
+    let mut first = true;
+    for row in table.select(&rows) {
+        let values: Vec<String> = row
+            .select(&cells)
+            .map(|cell| {
+                collapse_whitespace(&render_inline_children(cell, base)).replace('|', "\\|")
+            })
+            .collect();
+        if values.iter().all(|value| value.is_empty()) {
+            continue;
+        }
+        out.push_str(&format!("| {} |\n", values.join(" | ")));
+        if first {
+            out.push_str(&format!("|{}\n", " --- |".repeat(values.len())));
+            first = false;
+        }
+    }
+    out.push('\n');
+}
+
+fn render_inline_children(element: ElementRef<'_>, base: Option<&Url>) -> String {
+    let mut text = String::new();
+    for child in element.children() {
+        match child.value() {
+            Node::Text(value) => text.push_str(value),
+            Node::Element(_) => {
+                let child = ElementRef::wrap(child).expect("element node");
+                if is_boilerplate(&child) {
+                    continue;
+                }
+                if is_block(child.value().name()) {
+                    text.push(' ');
+                    text.push_str(&render_inline_children(child, base));
+                    text.push(' ');
+                } else {
+                    text.push_str(&render_inline(child, base));
+                }
+            }
+            _ => {}
+        }
+    }
+    text
+}
+
+fn render_inline(element: ElementRef<'_>, base: Option<&Url>) -> String {
+    let inner = || collapse_whitespace(&render_inline_children(element, base));
+    match element.value().name() {
+        "br" => "\n".to_string(),
+        "img" | "picture" | "video" | "audio" | "source" => String::new(),
+        "strong" | "b" => wrap_inline(&inner(), "**"),
+        "em" | "i" => wrap_inline(&inner(), "*"),
+        "code" | "kbd" | "samp" => {
+            let code = collapse_whitespace(&element.text().collect::<String>());
+            wrap_inline(&code, "`")
+        }
+        "a" => {
+            let text = inner();
+            let href = element
+                .value()
+                .attr("href")
+                .map(str::trim)
+                .filter(|href| !href.is_empty() && !href.starts_with('#'))
+                .and_then(|href| match base {
+                    Some(base) => base.join(href).ok(),
+                    None => Url::parse(href).ok(),
+                })
+                .filter(|url| matches!(url.scheme(), "http" | "https"));
+            match href {
+                Some(url) if !text.is_empty() => format!(" [{text}]({url}) "),
+                _ => format!(" {text} "),
+            }
+        }
+        _ => format!(" {} ", inner()),
+    }
+}
+
+fn wrap_inline(text: &str, marker: &str) -> String {
+    if text.is_empty() {
+        String::new()
+    } else {
+        format!(" {marker}{text}{marker} ")
+    }
+}
+
+fn collapse_whitespace(text: &str) -> String {
+    let mut collapsed = String::with_capacity(text.len());
+    for line in text.split('\n') {
+        let line = line.split_whitespace().collect::<Vec<_>>().join(" ");
+        if !collapsed.is_empty() && !line.is_empty() {
+            collapsed.push(' ');
+        }
+        collapsed.push_str(&line);
+    }
+    collapsed
+        .replace(" ,", ",")
+        .replace(" .", ".")
+        .trim()
+        .to_string()
+}
+
+/// Trim trailing spaces and collapse runs of blank lines.
+fn tidy_markdown(markdown: &str) -> String {
+    let mut tidy = String::with_capacity(markdown.len());
+    let mut blank = 0;
+    for line in markdown.lines() {
+        let line = line.trim_end();
+        if line.is_empty() {
+            blank += 1;
+            if blank > 1 {
+                continue;
+            }
+        } else {
+            blank = 0;
+        }
+        tidy.push_str(line);
+        tidy.push('\n');
+    }
+    tidy.trim().to_string()
+}
+
+/// Split Markdown into chunks of about `chunk_chars` characters on paragraph
+/// (then sentence) boundaries. A chunk that starts mid-section repeats the
+/// section heading so it reads on its own.
+pub fn chunk_markdown(markdown: &str, chunk_chars: usize) -> Vec<String> {
+    let chunk_chars = chunk_chars.max(200);
+    let mut chunks = Vec::new();
+    let mut current = String::new();
+    let mut heading: Option<&str> = None;
+
+    for block in markdown
+        .split("\n\n")
+        .map(str::trim)
+        .filter(|block| !block.is_empty())
+    {
+        let is_heading = block.starts_with('#');
+        for piece in split_long(block, chunk_chars) {
+            let size = current.chars().count() + piece.chars().count() + 2;
+            if !current.is_empty() && size > chunk_chars {
+                if !is_heading_only(&current) {
+                    chunks.push(std::mem::take(&mut current));
+                } else {
+                    current.clear();
+                }
+                if let Some(heading) = heading
+                    && !is_heading
+                {
+                    current.push_str(heading);
+                }
+            }
+            if !current.is_empty() {
+                current.push_str("\n\n");
+            }
+            current.push_str(&piece);
+        }
+        if is_heading {
+            heading = Some(block);
+        }
+    }
+    if !current.is_empty() && !is_heading_only(&current) {
+        chunks.push(current);
+    }
+    chunks
+}
+
+fn is_heading_only(chunk: &str) -> bool {
+    chunk
+        .split("\n\n")
+        .all(|block| block.trim_start().starts_with('#'))
+}
+
+fn split_long(block: &str, chunk_chars: usize) -> Vec<String> {
+    if block.chars().count() <= chunk_chars {
+        return vec![block.to_string()];
+    }
+    let mut pieces = Vec::new();
+    let mut current = String::new();
+    for sentence in block.split_inclusive(['.', '!', '?', '\n']) {
+        if !current.is_empty() && current.chars().count() + sentence.chars().count() > chunk_chars {
+            pieces.push(current.trim().to_string());
+            current.clear();
+        }
+        if sentence.chars().count() > chunk_chars {
+            let chars: Vec<char> = sentence.chars().collect();
+            for part in chars.chunks(chunk_chars) {
+                pieces.push(part.iter().collect::<String>().trim().to_string());
+            }
+            continue;
+        }
+        current.push_str(sentence);
+    }
+    if !current.trim().is_empty() {
+        pieces.push(current.trim().to_string());
+    }
+    pieces.retain(|piece| !piece.is_empty());
+    pieces
+}
+
+/// Up to `max` chunks containing the most distinct query terms, returned in
+/// page order. Without usable terms the first chunks are returned.
+pub fn select_chunks<'a>(chunks: &'a [String], query: &str, max: usize) -> Vec<&'a str> {
+    let mut terms: Vec<String> = query
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|term| term.chars().count() > 2)
+        .map(str::to_lowercase)
+        .collect();
+    terms.sort();
+    terms.dedup();
+
+    let mut scored: Vec<(usize, usize)> = chunks
+        .iter()
+        .enumerate()
+        .map(|(index, chunk)| {
+            let text = chunk.to_lowercase();
+            let hits = terms
+                .iter()
+                .filter(|term| text.contains(term.as_str()))
+                .count();
+            (index, hits)
+        })
+        .collect();
+    if !terms.is_empty() && scored.iter().any(|(_, hits)| *hits > 0) {
+        scored.retain(|(_, hits)| *hits > 0);
+    }
+    scored.sort_by(|(a_index, a_hits), (b_index, b_hits)| {
+        b_hits.cmp(a_hits).then(a_index.cmp(b_index))
+    });
+    scored.truncate(max);
+    scored.sort_by_key(|(index, _)| *index);
+    scored
+        .into_iter()
+        .map(|(index, _)| chunks[index].as_str())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ARTICLE: &str = r#"<!doctype html>
+<html><head><title>Rotating DoQ certificates</title><script>track()</script></head>
+<body>
+  <nav class="site-nav"><a href="/">Home</a> <a href="/blog">Blog</a></nav>
+  <div class="cookie-banner">We use cookies.</div>
+  <article>
+    <header><h1>Rotating DoQ certificates</h1></header>
+    <p>GhostDNS serves <strong>DNS over QUIC</strong> with a certificate chain that
+       expires every ninety days, so rotation has to be routine.</p>
+    <h2>Steps</h2>
+    <ol><li>Renew the chain with <code>certbot renew</code>.</li>
+        <li>Point <a href="/docs/ghostdns">the daemon</a> at the new files.</li></ol>
+    <pre>systemctl restart ghostdns</pre>
+    <p>After the restart, clients pick up the new certificate on their next handshake,
+       which usually happens within a minute.</p>
+    <aside class="related-posts"><p>Related: Tuning QUIC</p></aside>
+    <div class="share-buttons"><a href="https://x.example/share">Share</a></div>
+  </article>
+  <footer>© Archon</footer>
+</body></html>"#;
+
+    struct FixtureHttp {
+        pages: HashMap<String, (u16, &'static str, String)>,
+        calls: Mutex<Vec<String>>,
+    }
+
+    impl FixtureHttp {
+        fn new(pages: &[(&str, u16, &'static str, &str)]) -> Self {
+            Self {
+                pages: pages
+                    .iter()
+                    .map(|(url, status, content_type, body)| {
+                        (url.to_string(), (*status, *content_type, body.to_string()))
+                    })
+                    .collect(),
+                calls: Mutex::new(Vec::new()),
+            }
+        }
+
+        fn calls(&self, url: &str) -> usize {
+            self.calls
+                .lock()
+                .unwrap()
+                .iter()
+                .filter(|call| *call == url)
+                .count()
+        }
+    }
+
+    impl PageHttp for FixtureHttp {
+        fn get(&self, url: &str, max_bytes: usize) -> Result<HttpPage> {
+            self.calls.lock().unwrap().push(url.to_string());
+            let (status, content_type, body) =
+                self.pages
+                    .get(url)
+                    .cloned()
+                    .unwrap_or((404, "text/plain", String::new()));
+            // Redirect fixtures carry their target as the body.
+            let location = (300..400).contains(&status).then(|| body.clone());
+            let mut body = body.into_bytes();
+            let truncated = body.len() > max_bytes;
+            body.truncate(max_bytes);
+            Ok(HttpPage {
+                status,
+                location,
+                content_type: Some(content_type.to_string()),
+                body,
+                truncated,
+            })
+        }
+    }
+
+    #[test]
+    fn extracts_main_content_as_markdown() {
+        let base = Url::parse("https://blog.example/posts/doq").unwrap();
+        let (title, markdown) = extract_readable(ARTICLE, Some(&base));
+        assert_eq!(title.as_deref(), Some("Rotating DoQ certificates"));
+        assert!(
+            markdown.starts_with("GhostDNS serves **DNS over QUIC** with"),
+            "{markdown}"
+        );
+        assert!(markdown.contains("## Steps"), "{markdown}");
+        assert!(
+            markdown.contains("1. Renew the chain with `certbot renew`."),
+            "{markdown}"
+        );
+        assert!(
+            markdown.contains("2. Point [the daemon](https://blog.example/docs/ghostdns) at"),
+            "{markdown}"
+        );
+        assert!(
+            markdown.contains("```\nsystemctl restart ghostdns\n```"),
+            "{markdown}"
+        );
+        for boilerplate in ["cookies", "Home", "Related", "Share", "©", "track()"] {
+            assert!(
+                !markdown.contains(boilerplate),
+                "{boilerplate} in {markdown}"
+            );
+        }
+    }
+
+    #[test]
+    fn finds_content_without_article_markup() {
+        let html = r#"<html><body>
+            <div id="menu"><a href="/a">A</a><a href="/b">B</a><a href="/c">C</a></div>
+            <div class="content"><p>First paragraph with enough words to count as content.</p>
+            <p>Second paragraph that continues the explanation in detail.</p></div>
+            <div class="links"><p><a href="/x">Link one</a> <a href="/y">Link two</a></p></div>
+        </body></html>"#;
+        let (_, markdown) = extract_readable(html, None);
+        assert_eq!(
+            markdown,
+            "First paragraph with enough words to count as content.\n\n\
+             Second paragraph that continues the explanation in detail."
+        );
+    }
+
+    #[test]
+    fn robots_rules_use_longest_match() {
+        let robots = "User-agent: *\nDisallow: /private\nAllow: /private/open$\n\n\
+                      User-agent: Archon\nUser-agent: other\nDisallow: /*.pdf$\nAllow: /\n";
+        let archon = RobotsRules::parse(robots, "archon");
+        assert!(archon.allows("/private/notes"));
+        assert!(!archon.allows("/files/report.pdf"));
+        assert!(archon.allows("/files/report.pdf?download=1"));
+
+        let generic = RobotsRules::parse(robots, "somebot");
+        assert!(!generic.allows("/private/notes"));
+        assert!(generic.allows("/private/open"));
+        assert!(!generic.allows("/private/open/more"));
+        assert!(generic.allows("/public"));
+        assert!(RobotsRules::default().allows("/anything"));
+    }
+
+    #[test]
+    fn chunks_on_paragraphs_and_repeats_headings() {
+        let paragraph = "Sentence about QUIC handshakes. ".repeat(8);
+        let markdown =
+            format!("# Guide\n\n{paragraph}\n\n{paragraph}\n\n## Rotation\n\n{paragraph}");
+        let chunks = chunk_markdown(&markdown, 300);
+        assert_eq!(chunks.len(), 3, "{chunks:#?}");
+        assert!(chunks[0].starts_with("# Guide\n\nSentence"));
+        assert!(chunks[1].starts_with("# Guide\n\nSentence"));
+        assert!(chunks[2].starts_with("## Rotation\n\nSentence"));
+        assert!(chunks.iter().all(|chunk| chunk.chars().count() <= 300));
+
+        let chunks = vec![
+            "intro".to_string(),
+            "rotation of certificates".to_string(),
+            "unrelated".to_string(),
+            "certificates expire".to_string(),
+        ];
+        assert_eq!(
+            select_chunks(&chunks, "How do I rotate certificates?", 2),
+            vec!["rotation of certificates", "certificates expire"]
+        );
+        assert_eq!(select_chunks(&chunks, "?", 1), vec!["intro"]);
+    }
+
+    #[test]
+    fn fetcher_respects_robots_limits_size_and_caches() {
+        let long_page = format!(
+            "<html><body><article><p>{}</p></article></body></html>",
+            "certificates rotate often. ".repeat(200)
+        );
+        let http = Arc::new(FixtureHttp::new(&[
+            (
+                "https://blog.example/robots.txt",
+                200,
+                "text/plain",
+                "User-agent: *\nDisallow: /drafts\n",
+            ),
+            (
+                "https://blog.example/posts/doq",
+                200,
+                "text/html; charset=utf-8",
+                ARTICLE,
+            ),
+            (
+                "https://blog.example/drafts/next",
+                200,
+                "text/html",
+                ARTICLE,
+            ),
+            (
+                "https://blog.example/old/doq",
+                301,
+                "text/html",
+                "/posts/doq",
+            ),
+            (
+                "https://blog.example/old/next",
+                302,
+                "text/html",
+                "/drafts/next",
+            ),
+            ("https://long.example/page", 200, "text/html", &long_page),
+            (
+                "https://files.example/app.bin",
+                200,
+                "application/octet-stream",
+                "binary",
+            ),
+        ]));
+        let settings = ArcFetchSettings {
+            enabled: true,
+            max_bytes: 1_000,
+            ..ArcFetchSettings::default()
+        };
+        let fetcher = PageFetcher::with_http(settings.clone(), http.clone());
+
+        let page = fetcher.fetch("https://blog.example/posts/doq").unwrap();
+        assert_eq!(page.title.as_deref(), Some("Rotating DoQ certificates"));
+        assert!(!page.chunks.is_empty());
+        fetcher
+            .fetch("https://blog.example/posts/doq#steps")
+            .unwrap();
+        assert_eq!(http.calls("https://blog.example/posts/doq"), 1);
+        assert_eq!(http.calls("https://blog.example/robots.txt"), 1);
+
+        let err = fetcher
+            .fetch("https://blog.example/drafts/next")
+            .unwrap_err();
+        assert!(err.to_string().contains("robots.txt"), "{err}");
+        assert_eq!(http.calls("https://blog.example/drafts/next"), 0);
+
+        let page = fetcher.fetch("https://blog.example/old/doq").unwrap();
+        assert_eq!(page.url, "https://blog.example/posts/doq");
+        let err = fetcher.fetch("https://blog.example/old/next").unwrap_err();
+        assert!(err.to_string().contains("robots.txt"), "{err}");
+        assert_eq!(http.calls("https://blog.example/drafts/next"), 0);
+
+        let page = fetcher.fetch("https://long.example/page").unwrap();
+        assert!(page.truncated);
+        assert!(page.markdown.len() < 1_000);
+        assert!(fetcher.fetch("https://files.example/app.bin").is_err());
+        assert!(fetcher.fetch("ftp://files.example/x").is_err());
+
+        let uncached = PageFetcher::with_http(
+            ArcFetchSettings {
+                cache_ttl_secs: 0,
+                ..settings
+            },
+            http.clone(),
+        );
+        uncached.fetch("https://blog.example/posts/doq").unwrap();
+        uncached.fetch("https://blog.example/posts/doq").unwrap();
+        assert_eq!(http.calls("https://blog.example/posts/doq"), 4);
+    }
+
+    #[test]
+    fn refuses_non_public_targets() {
+        for ip in [
+            "127.0.0.1",
+            "10.1.2.3",
+            "172.16.0.1",
+            "192.168.1.1",
+            "169.254.169.254",
+            "100.64.0.1",
+            "0.0.0.0",
+            "::1",
+            "fe80::1",
+            "fd00::1",
+            "::ffff:192.168.1.1",
+        ] {
+            assert!(!is_public_ip(ip.parse().unwrap()), "{ip}");
+        }
+        for ip in ["93.184.216.34", "1.1.1.1", "2606:4700:4700::1111"] {
+            assert!(is_public_ip(ip.parse().unwrap()), "{ip}");
+        }
+
+        let http = BlockingPageHttp::new(Duration::from_secs(5)).unwrap();
+        for url in [
+            "http://127.0.0.1:9/",
+            "http://[::1]:9/",
+            "http://localhost:9/",
+        ] {
+            let err = http.get(url, 1_000).unwrap_err();
+            assert!(
+                format!("{err:#}").contains("not a public address"),
+                "{err:#}"
+            );
+        }
+    }
+
+    #[test]
+    fn grounds_top_results_with_matching_excerpts() {
+        let http = Arc::new(FixtureHttp::new(&[(
+            "https://blog.example/posts/doq",
+            200,
+            "text/html",
+            ARTICLE,
+        )]));
+        let fetcher = PageFetcher::with_http(
+            ArcFetchSettings {
+                enabled: true,
+                top_n: 2,
+                max_chunks: 1,
+                chunk_chars: 200,
+                ..ArcFetchSettings::default()
+            },
+            http,
+        );
+        let result = |url: &str| SearchResult {
+            title: url.to_string(),
+            url: url.to_string(),
+            snippet: String::new(),
+            domain: None,
+            published_date: None,
+            score: None,
+            sources: Vec::new(),
+        };
+        let results = vec![
+            result("https://missing.example/"),
+            result("https://blog.example/posts/doq"),
+            result("https://never.example/"),
+        ];
+
+        let grounded = fetcher.ground("client handshake after restart", &results);
+        assert_eq!(grounded.len(), 1);
+        assert_eq!(grounded[0].result_index, 1);
+        assert_eq!(grounded[0].excerpts.len(), 1);
+        assert!(
+            grounded[0].excerpts[0].contains("handshake"),
+            "{:?}",
+            grounded[0].excerpts
+        );
+    }
+}
@@ -1,5 +1,6 @@
 pub mod agent;
 pub mod ai;
+pub mod audit;
 pub mod automation;
 pub mod browser;
 pub mod cli;
@@ -9,16 +10,25 @@ pub mod crypto;
 pub mod engine;
 pub mod ens;
 pub mod ghostdns;
+pub mod grounding;
 pub mod host;
 pub mod ipfs;
+pub mod local_index;
 pub mod mcp;
+pub mod mcp_client;
+pub mod mcp_http;
 pub mod mcp_server;
 pub mod n8n;
+pub mod network;
 pub mod policy;
 pub(crate) mod process_util;
 pub mod profile;
+pub mod recall;
 pub mod recipe;
+pub mod recipe_suite;
+pub mod recorder;
 pub mod research;
+pub mod scheduler;
 pub mod search;
 pub mod summarize;
 pub mod sync;
@@ -28,6 +38,8 @@ pub mod telemetry;
 mod test_util;
 pub mod theme;
 pub mod transcript;
+pub mod transcript_bundle;
+pub mod transcript_crypto;
 pub mod ui;
 pub mod vision;
 pub mod voice;
@@ -55,13 +67,14 @@ use crate::mcp::{McpHealthReport, McpOrchestrator};
 use crate::n8n::{N8nOrchestrator, N8nTriggerResult, N8nWorkflow};
 use crate::policy::{PolicyWriteAction, PolicyWriteOutcome};
 use crate::profile::{ProfileBadge, ProfileRecord, ProfileStore};
-use crate::research::ResearchOrchestrator;
+use crate::research::{ResearchOrchestrator, ResearchSessionStore};
 use crate::search::{ArcOrchestrator, ArcSearchResult};
 use crate::summarize::SummarizeOrchestrator;
 use crate::sync::{SyncEvent, SyncLayer};
 use crate::telemetry::ProcessMonitor;
 use crate::theme::ThemeRegistry;
 use crate::transcript::{TranscriptRetention, TranscriptStore};
+use crate::transcript_crypto::TranscriptCipher;
 use crate::ui::{UiHealthReport, UiShell};
 use crate::vision::{VisionOrchestrator, VisionRequest, VisionResponse};
 use crate::voice::{AudioOutput, TtsRequest, VoiceOrchestrator};
@@ -196,7 +209,13 @@ impl Launcher {
         let profiles = ProfileStore::open(profile_root, sync_layer)?;
         let transcript_root = settings.resolve_transcript_root()?;
         let retention = transcript_retention_from_settings(&settings.transcripts_retention);
-        let transcripts = Arc::new(TranscriptStore::with_retention(transcript_root, retention)?);
+        let mut transcripts = TranscriptStore::with_retention(transcript_root.clone(), retention)?;
+        if let Some(cipher) =
+            TranscriptCipher::from_settings(&settings.transcripts_encryption, &transcript_root)?
+        {
+            transcripts = transcripts.with_encryption(cipher);
+        }
+        let transcripts = Arc::new(transcripts);
         let ai = AiBridge::from_settings(&settings.ai, Arc::clone(&transcripts));
         let ai_arc = Arc::new(ai.clone());
         let ai_host = AiHost::from_settings(&settings.ai_host)?;
@@ -211,11 +230,15 @@ impl Launcher {
         let voice = VoiceOrchestrator::from_settings(settings.voice.clone(), Arc::clone(&ai_arc));
         let summarize =
             SummarizeOrchestrator::from_settings(settings.summarize.clone(), Arc::clone(&ai_arc));
-        let research = ResearchOrchestrator::from_settings(
+        let mut research = ResearchOrchestrator::from_settings(
             settings.research.clone(),
             Arc::clone(&ai_arc),
             Arc::clone(&arc_arc),
         );
+        match ResearchSessionStore::for_transcripts(&transcripts) {
+            Ok(store) => research = research.with_sessions(store),
+            Err(err) => warn!(error = %err, "Research sessions are unavailable"),
+        }
         let automation =
             AutomationOrchestrator::from_settings(settings.automation.clone(), Arc::clone(&ai_arc));
         let ipfs = IpfsClient::from_settings(settings.ipfs.clone());
@@ -0,0 +1,446 @@
+//! Local search index for Arc.
+//!
+//! A SQLite FTS5 index of pages the user has seen: titles and URLs imported
+//! from the Chromium `History` database of each Archon profile, and the full
+//! text of pages saved explicitly. Backs the `local-index` Arc provider so
+//! searches can stay entirely on this machine.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, TimeZone, Utc};
+use rusqlite::{Connection, params};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Schema version stored in `user_version`; a mismatch rebuilds the index.
+const INDEX_VERSION: i64 = 1;
+
+/// Most recent history entries imported per profile.
+const MAX_HISTORY_ROWS: i64 = 50_000;
+
+/// Tokens in a result snippet.
+const SNIPPET_TOKENS: i64 = 32;
+
+/// Seconds between 1601-01-01 (Chromium's epoch) and the Unix epoch.
+const CHROMIUM_EPOCH_OFFSET_SECS: i64 = 11_644_473_600;
+
+/// Where an indexed page came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LocalSource {
+    /// Browsing history (title and URL only).
+    History,
+    /// A page saved with its content.
+    Saved,
+}
+
+impl LocalSource {
+    fn as_str(self) -> &'static str {
+        match self {
+            LocalSource::History => "history",
+            LocalSource::Saved => "saved",
+        }
+    }
+
+    fn from_key(key: &str) -> Self {
+        match key {
+            "saved" => LocalSource::Saved,
+            _ => LocalSource::History,
+        }
+    }
+}
+
+/// A local index search hit.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LocalHit {
+    pub url: String,
+    pub title: String,
+    /// Matching text, or the URL for history entries without content.
+    pub snippet: String,
+    pub source: LocalSource,
+    /// Last visit (history) or save time.
+    pub last_visited: Option<DateTime<Utc>>,
+    pub visit_count: i64,
+    /// BM25 rank; lower is better.
+    pub score: f64,
+}
+
+/// SQLite-backed page index.
+#[derive(Debug, Clone)]
+pub struct LocalIndex {
+    path: PathBuf,
+}
+
+impl LocalIndex {
+    /// Open (creating if needed) the index at `path`.
+    pub fn open(path: impl Into<PathBuf>) -> Result<Self> {
+        let index = Self { path: path.into() };
+        if let Some(parent) = index.path.parent() {
+            fs::create_dir_all(parent).with_context(|| {
+                format!(
+                    "failed to create local index directory {}",
+                    parent.display()
+                )
+            })?;
+        }
+        index.connect()?;
+        Ok(index)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    fn connect(&self) -> Result<Connection> {
+        let conn = Connection::open(&self.path)
+            .with_context(|| format!("failed to open local index {}", self.path.display()))?;
+        conn.busy_timeout(Duration::from_secs(5))
+            .context("failed to configure local index timeout")?;
+        let version: i64 = conn
+            .pragma_query_value(None, "user_version", |row| row.get(0))
+            .context("failed to read local index version")?;
+        if version != INDEX_VERSION {
+            conn.execute_batch(
+                "DROP TABLE IF EXISTS pages_fts;
+                 DROP TABLE IF EXISTS pages;
+                 CREATE TABLE pages (
+                     id INTEGER PRIMARY KEY,
+                     url TEXT NOT NULL UNIQUE,
+                     title TEXT NOT NULL,
+                     body TEXT NOT NULL,
+                     source TEXT NOT NULL,
+                     visit_count INTEGER NOT NULL,
+                     last_visited INTEGER
+                 );
+                 CREATE VIRTUAL TABLE pages_fts USING fts5(
+                     title,
+                     url,
+                     body,
+                     content = 'pages',
+                     content_rowid = 'id',
+                     tokenize = 'porter unicode61'
+                 );
+                 CREATE TRIGGER pages_insert AFTER INSERT ON pages BEGIN
+                     INSERT INTO pages_fts (rowid, title, url, body)
+                     VALUES (new.id, new.title, new.url, new.body);
+                 END;
+                 CREATE TRIGGER pages_delete AFTER DELETE ON pages BEGIN
+                     INSERT INTO pages_fts (pages_fts, rowid, title, url, body)
+                     VALUES ('delete', old.id, old.title, old.url, old.body);
+                 END;
+                 CREATE TRIGGER pages_update AFTER UPDATE ON pages BEGIN
+                     INSERT INTO pages_fts (pages_fts, rowid, title, url, body)
+                     VALUES ('delete', old.id, old.title, old.url, old.body);
+                     INSERT INTO pages_fts (rowid, title, url, body)
+                     VALUES (new.id, new.title, new.url, new.body);
+                 END;",
+            )
+            .context("failed to create local index schema")?;
+            conn.pragma_update(None, "user_version", INDEX_VERSION)
+                .context("failed to record local index version")?;
+        }
+        Ok(conn)
+    }
+
+    /// Import the history of a Chromium profile (`<profile>/Default/History`
+    /// or the file itself). Saved pages keep their content. Returns the
+    /// number of entries imported.
+    pub fn import_history(&self, history: &Path) -> Result<usize> {
+        // Read a copy: the browser keeps the original locked and may be
+        // writing to it.
+        let snapshot = HistorySnapshot::copy(history, &self.path)?;
+        let source = Connection::open(&snapshot.path)
+            .with_context(|| format!("failed to open browser history {}", history.display()))?;
+        let mut stmt = source
+            .prepare(
+                "SELECT url, title, visit_count, last_visit_time FROM urls
+                 WHERE hidden = 0 ORDER BY last_visit_time DESC LIMIT ?1",
+            )
+            .with_context(|| format!("{} is not a browser history file", history.display()))?;
+        let entries: Vec<(String, String, i64, i64)> = stmt
+            .query_map(params![MAX_HISTORY_ROWS], |row| {
+                Ok((
+                    row.get(0)?,
+                    row.get::<_, Option<String>>(1)?.unwrap_or_default(),
+                    row.get(2)?,
+                    row.get(3)?,
+                ))
+            })
+            .and_then(|rows| rows.collect::<rusqlite::Result<_>>())
+            .with_context(|| format!("failed to read browser history {}", history.display()))?;
+
+        let mut conn = self.connect()?;
+        let tx = conn
+            .transaction()
+            .context("failed to start local index update")?;
+        let mut imported = 0;
+        for (url, title, visit_count, last_visit_time) in entries {
+            if !(url.starts_with("http://") || url.starts_with("https://")) {
+                continue;
+            }
+            tx.execute(
+                "INSERT INTO pages (url, title, body, source, visit_count, last_visited)
+                 VALUES (?1, ?2, '', ?3, ?4, ?5)
+                 ON CONFLICT (url) DO UPDATE SET
+                     visit_count = excluded.visit_count,
+                     title = CASE WHEN source = 'saved' THEN title ELSE excluded.title END,
+                     last_visited = CASE WHEN source = 'saved' THEN last_visited
+                                         ELSE excluded.last_visited END",
+                params![
+                    url,
+                    title,
+                    LocalSource::History.as_str(),
+                    visit_count,
+                    chromium_time(last_visit_time).map(|time| time.timestamp()),
+                ],
+            )
+            .context("failed to update local index entry")?;
+            imported += 1;
+        }
+        tx.commit().context("failed to commit local index update")?;
+        Ok(imported)
+    }
+
+    /// Store a page with its (Markdown or plain text) content, replacing any
+    /// earlier copy of the URL.
+    pub fn save_page(&self, url: &str, title: &str, body: &str) -> Result<()> {
+        self.connect()?
+            .execute(
+                "INSERT INTO pages (url, title, body, source, visit_count, last_visited)
+                 VALUES (?1, ?2, ?3, ?4, 0, ?5)
+                 ON CONFLICT (url) DO UPDATE SET
+                     title = excluded.title,
+                     body = excluded.body,
+                     source = excluded.source,
+                     last_visited = excluded.last_visited",
+                params![
+                    url,
+                    title,
+                    body,
+                    LocalSource::Saved.as_str(),
+                    Utc::now().timestamp(),
+                ],
+            )
+            .context("failed to save page to local index")?;
+        Ok(())
+    }
+
+    /// Full-text search over titles, URLs and saved content, best first.
+    pub fn search(&self, query: &str, limit: usize) -> Result<Vec<LocalHit>> {
+        let Some(expression) = fts_expression(query) else {
+            return Ok(Vec::new());
+        };
+        let conn = self.connect()?;
+        let mut stmt = conn
+            .prepare(
+                "SELECT p.url, p.title, snippet(pages_fts, 2, '', '', '…', ?2), p.source,
+                        p.visit_count, p.last_visited, bm25(pages_fts, 4.0, 2.0, 1.0)
+                 FROM pages_fts JOIN pages p ON p.id = pages_fts.rowid
+                 WHERE pages_fts MATCH ?1
+                 ORDER BY bm25(pages_fts, 4.0, 2.0, 1.0) LIMIT ?3",
+            )
+            .context("failed to prepare local index search")?;
+        let hits = stmt
+            .query_map(params![expression, SNIPPET_TOKENS, limit as i64], |row| {
+                let url: String = row.get(0)?;
+                let snippet: String = row.get(2)?;
+                let source: String = row.get(3)?;
+                let last_visited: Option<i64> = row.get(5)?;
+                Ok(LocalHit {
+                    snippet: if snippet.trim().is_empty() {
+                        url.clone()
+                    } else {
+                        snippet
+                    },
+                    url,
+                    title: row.get(1)?,
+                    source: LocalSource::from_key(&source),
+                    last_visited: last_visited.and_then(|secs| Utc.timestamp_opt(secs, 0).single()),
+                    visit_count: row.get(4)?,
+                    score: row.get(6)?,
+                })
+            })
+            .and_then(|rows| rows.collect::<rusqlite::Result<Vec<_>>>())
+            .context("local index search failed")?;
+        Ok(hits)
+    }
+}
+
+/// Chromium `History` files of the profiles under `profile_root`.
+pub fn profile_history_files(profile_root: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(profile_root) else {
+        return Vec::new();
+    };
+    let mut files: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path().join("Default").join("History"))
+        .filter(|path| path.is_file())
+        .collect();
+    files.sort();
+    files
+}
+
+/// Convert Chromium's microseconds since 1601 to a UTC time.
+fn chromium_time(micros: i64) -> Option<DateTime<Utc>> {
+    if micros <= 0 {
+        return None;
+    }
+    Utc.timestamp_opt(micros / 1_000_000 - CHROMIUM_EPOCH_OFFSET_SECS, 0)
+        .single()
+}
+
+/// Quote each term so user text cannot inject FTS5 syntax.
+fn fts_expression(text: &str) -> Option<String> {
+    let terms: Vec<String> = text
+        .split_whitespace()
+        .filter(|term| term.chars().any(char::is_alphanumeric))
+        .map(|term| format!("\"{}\"", term.replace('"', "\"\"")))
+        .collect();
+    if terms.is_empty() {
+        None
+    } else {
+        Some(terms.join(" OR "))
+    }
+}
+
+/// Journal files SQLite may keep next to a database.
+const SQLITE_SIDECARS: [&str; 3] = ["-journal", "-wal", "-shm"];
+
+/// A private copy of a browser `History` database (and its journal), removed
+/// when dropped.
+struct HistorySnapshot {
+    path: PathBuf,
+}
+
+impl HistorySnapshot {
+    /// Copy `history` next to the index at `index_path`.
+    fn copy(history: &Path, index_path: &Path) -> Result<Self> {
+        let name = format!(".history-{}.sqlite", Uuid::new_v4().simple());
+        let snapshot = Self {
+            path: index_path.with_file_name(name),
+        };
+        fs::copy(history, &snapshot.path)
+            .with_context(|| format!("failed to copy browser history {}", history.display()))?;
+        // A journal left by an in-flight write lets SQLite open the copy in
+        // a consistent state.
+        for suffix in &SQLITE_SIDECARS[..2] {
+            let sidecar = sidecar_path(history, suffix);
+            if sidecar.exists() {
+                fs::copy(&sidecar, sidecar_path(&snapshot.path, suffix)).with_context(|| {
+                    format!("failed to copy browser history {}", sidecar.display())
+                })?;
+            }
+        }
+        Ok(snapshot)
+    }
+}
+
+impl Drop for HistorySnapshot {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+        for suffix in SQLITE_SIDECARS {
+            let _ = fs::remove_file(sidecar_path(&self.path, suffix));
+        }
+    }
+}
+
+fn sidecar_path(database: &Path, suffix: &str) -> PathBuf {
+    let mut name = database.as_os_str().to_owned();
+    name.push(suffix);
+    PathBuf::from(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn chromium_history(path: &Path) {
+        let conn = Connection::open(path).unwrap();
+        conn.execute_batch(
+            "CREATE TABLE urls (id INTEGER PRIMARY KEY, url LONGVARCHAR, title LONGVARCHAR,
+                 visit_count INTEGER DEFAULT 0, typed_count INTEGER DEFAULT 0,
+                 last_visit_time INTEGER NOT NULL, hidden INTEGER DEFAULT 0);
+             INSERT INTO urls (url, title, visit_count, last_visit_time, hidden) VALUES
+                 ('https://ghostdns.example/docs/doq', 'GhostDNS DoQ setup', 7, 13370000000000000, 0),
+                 ('https://news.example/', 'Daily news', 2, 13369000000000000, 0),
+                 ('chrome://settings/', 'Settings', 1, 13368000000000000, 0),
+                 ('https://hidden.example/', 'GhostDNS hidden', 1, 13367000000000000, 1);",
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn imports_history_from_unusual_paths_without_leaving_copies() {
+        let dir = tempdir().unwrap();
+        let profile = dir.path().join("what?#100%");
+        fs::create_dir_all(&profile).unwrap();
+        chromium_history(&profile.join("History"));
+
+        let index_dir = dir.path().join("index");
+        let index = LocalIndex::open(index_dir.join("index.sqlite")).unwrap();
+        assert_eq!(index.import_history(&profile.join("History")).unwrap(), 2);
+        let leftovers: Vec<_> = fs::read_dir(&index_dir)
+            .unwrap()
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.file_name().to_string_lossy().starts_with(".history-"))
+            .collect();
+        assert!(leftovers.is_empty(), "{leftovers:?}");
+    }
+
+    #[test]
+    fn imports_history_and_searches_saved_pages() {
+        let dir = tempdir().unwrap();
+        let profile = dir.path().join("profiles").join("default").join("Default");
+        fs::create_dir_all(&profile).unwrap();
+        chromium_history(&profile.join("History"));
+        let histories = profile_history_files(&dir.path().join("profiles"));
+        assert_eq!(histories, vec![profile.join("History")]);
+
+        let index = LocalIndex::open(dir.path().join("index.sqlite")).unwrap();
+        assert_eq!(index.import_history(&histories[0]).unwrap(), 2);
+        index
+            .save_page(
+                "https://wiki.example/quic",
+                "QUIC notes",
+                "Rotating certificates for ghostdns takes one restart.",
+            )
+            .unwrap();
+
+        let hits = index.search("ghostdns", 10).unwrap();
+        let urls: Vec<&str> = hits.iter().map(|hit| hit.url.as_str()).collect();
+        assert_eq!(
+            urls,
+            [
+                "https://ghostdns.example/docs/doq",
+                "https://wiki.example/quic"
+            ]
+        );
+        assert_eq!(hits[0].source, LocalSource::History);
+        assert_eq!(hits[0].visit_count, 7);
+        assert_eq!(
+            hits[0].last_visited.unwrap().format("%Y-%m-%d").to_string(),
+            "2024-09-05"
+        );
+        assert_eq!(hits[1].source, LocalSource::Saved);
+        assert!(hits[1].snippet.contains("ghostdns"), "{}", hits[1].snippet);
+
+        // Re-importing history keeps saved content and does not duplicate.
+        index
+            .save_page(
+                "https://news.example/",
+                "Daily news",
+                "Weather and QUIC rollout",
+            )
+            .unwrap();
+        index.import_history(&histories[0]).unwrap();
+        let hits = index.search("rollout", 10).unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].source, LocalSource::Saved);
+        assert_eq!(hits[0].visit_count, 2);
+        assert!(index.search("\"*", 10).unwrap().is_empty());
+    }
+}
@@ -1,7 +1,9 @@
 use std::{
+    collections::{BTreeSet, HashMap},
     env,
     path::PathBuf,
     process::Command,
+    sync::{Arc, Mutex},
     time::{Duration, Instant},
 };
 
@@ -9,16 +11,92 @@ use anyhow::{Context, Result, bail};
 use reqwest::blocking::Client;
 use serde::Serialize;
 use serde_json::{Value, json};
+use tracing::warn;
 use url::Url;
 
-use crate::config::{McpConnector, McpDockerSettings, McpSettings};
+use crate::ai::{AiTool, AiToolInvoker};
+use crate::config::{McpConnector, McpDockerSettings, McpSettings, McpTransport};
+use crate::mcp_client::{McpClient, McpToolInfo, render_tool_result};
 use crate::process_util::run_with_timeout;
+use crate::sync_util::LockResultExt;
+
+/// Timeout for each request to a `stdio`/`http` MCP connector.
+const MCP_SESSION_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Timeout for the background `tools/list` discovery behind `/health`.
+const MCP_DISCOVERY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Delay before retrying discovery after the first failure; doubles with each
+/// further failure up to [`MCP_DISCOVERY_MAX_BACKOFF`].
+const MCP_DISCOVERY_BACKOFF: Duration = Duration::from_secs(5);
+const MCP_DISCOVERY_MAX_BACKOFF: Duration = Duration::from_secs(300);
+
+/// How long a successful discovery is reported before it is refreshed.
+const MCP_DISCOVERY_REFRESH: Duration = Duration::from_secs(60);
+
+/// Separator between connector and tool in the names handed to the AI bridge.
+const AI_TOOL_SEPARATOR: &str = "__";
 
 /// High-level orchestrator for Model Context Protocol sidecars and connectors.
 #[derive(Debug, Clone)]
 pub struct McpOrchestrator {
     settings: McpSettings,
     client: Client,
+    /// Live sessions with `stdio`/`http` connectors, keyed by connector name.
+    /// Each slot has its own lock so a slow server does not stall the others.
+    sessions: Arc<Mutex<HashMap<String, SessionSlot>>>,
+    /// Latest discovery outcome per connector, which is what health reports
+    /// show; they never connect themselves.
+    discovery: Arc<Mutex<HashMap<String, Discovery>>>,
+}
+
+type SessionSlot = Arc<Mutex<Option<McpSession>>>;
+
+#[derive(Debug)]
+struct McpSession {
+    client: McpClient,
+    tools: Option<Vec<McpToolInfo>>,
+}
+
+#[derive(Debug, Default)]
+struct Discovery {
+    tools: Option<Vec<McpToolInfo>>,
+    error: Option<String>,
+    failures: u32,
+    /// When the next background discovery may start.
+    next_attempt: Option<Instant>,
+    running: bool,
+}
+
+impl Discovery {
+    fn record(&mut self, result: &Result<Vec<McpToolInfo>>) {
+        let now = Instant::now();
+        match result {
+            Ok(tools) => {
+                self.tools = Some(tools.clone());
+                self.error = None;
+                self.failures = 0;
+                self.next_attempt = Some(now + MCP_DISCOVERY_REFRESH);
+            }
+            Err(err) => {
+                self.tools = None;
+                self.error = Some(format!("{err:#}"));
+                self.failures = self.failures.saturating_add(1);
+                let backoff = MCP_DISCOVERY_BACKOFF
+                    .saturating_mul(1 << (self.failures - 1).min(16))
+                    .min(MCP_DISCOVERY_MAX_BACKOFF);
+                self.next_attempt = Some(now + backoff);
+            }
+        }
+    }
+
+    fn backing_off(&self) -> bool {
+        self.error.is_some() && self.next_attempt.is_some_and(|at| at > Instant::now())
+    }
+
+    fn due(&self) -> bool {
+        !self.running && self.next_attempt.is_none_or(|at| at <= Instant::now())
+    }
 }
 
 impl McpOrchestrator {
@@ -28,7 +106,12 @@ impl McpOrchestrator {
             .timeout(Duration::from_secs(4))
             .build()
             .context("failed to build reqwest client for MCP orchestrator")?;
-        Ok(Self { settings, client })
+        Ok(Self {
+            settings,
+            client,
+            sessions: Arc::default(),
+            discovery: Arc::default(),
+        })
     }
 
     pub fn connectors(&self) -> &[McpConnector] {
@@ -132,17 +215,20 @@ impl McpOrchestrator {
         tool: &str,
         arguments: Value,
     ) -> Result<McpToolCallResponse> {
-        let connector = self
-            .settings
-            .connectors
-            .iter()
-            .find(|candidate| candidate.name == connector)
-            .with_context(|| format!("Connector '{connector}' not configured"))?;
-
+        let connector = self.enabled_connector(connector)?;
         let connector_name = connector.name.clone();
 
-        if !connector.enabled {
-            bail!("Connector '{connector_name}' is disabled in configuration");
+        if connector.transport != McpTransport::Rest {
+            let started = Instant::now();
+            let payload = self.with_session(connector, |session| {
+                session.client.call_tool(tool, arguments)
+            })?;
+            return Ok(McpToolCallResponse {
+                connector: connector_name,
+                tool: tool.to_string(),
+                latency_ms: started.elapsed().as_millis() as u64,
+                payload,
+            });
         }
 
         let url = join_endpoint(&connector.endpoint, "tool-call");
@@ -188,6 +274,125 @@ impl McpOrchestrator {
         })
     }
 
+    /// Tools advertised by an MCP (`stdio`/`http`) connector, discovered with
+    /// `tools/list` once per session.
+    pub fn list_tools(&self, connector: &str) -> Result<Vec<McpToolInfo>> {
+        let connector = self.enabled_connector(connector)?;
+        if connector.transport == McpTransport::Rest {
+            bail!(
+                "Connector '{}' uses the rest transport and cannot list tools",
+                connector.name
+            );
+        }
+        let result = self.with_session(connector, |session| {
+            if let Some(tools) = &session.tools {
+                return Ok(tools.clone());
+            }
+            let tools = session.client.list_tools()?;
+            session.tools = Some(tools.clone());
+            Ok(tools)
+        });
+        self.discovery
+            .lock()
+            .recover()
+            .entry(connector.name.clone())
+            .or_default()
+            .record(&result);
+        result
+    }
+
+    /// Tools of every enabled MCP connector, paired with the connector name.
+    /// Connectors that cannot be reached are skipped with a warning, and are
+    /// not retried until their discovery backoff has passed.
+    pub fn discovered_tools(&self) -> Vec<(String, McpToolInfo)> {
+        let mut discovered = Vec::new();
+        for connector in &self.settings.connectors {
+            if !connector.enabled || connector.transport == McpTransport::Rest {
+                continue;
+            }
+            if self
+                .discovery
+                .lock()
+                .recover()
+                .get(&connector.name)
+                .is_some_and(Discovery::backing_off)
+            {
+                continue;
+            }
+            match self.list_tools(&connector.name) {
+                Ok(tools) => {
+                    discovered.extend(tools.into_iter().map(|tool| (connector.name.clone(), tool)))
+                }
+                Err(err) => warn!(
+                    connector = %connector.name,
+                    "failed to discover MCP tools: {err:#}"
+                ),
+            }
+        }
+        discovered
+    }
+
+    fn enabled_connector(&self, name: &str) -> Result<&McpConnector> {
+        let connector = self
+            .settings
+            .connectors
+            .iter()
+            .find(|candidate| candidate.name == name)
+            .with_context(|| format!("Connector '{name}' not configured"))?;
+        if !connector.enabled {
+            bail!(
+                "Connector '{}' is disabled in configuration",
+                connector.name
+            );
+        }
+        Ok(connector)
+    }
+
+    /// Run `f` against the connector's session, connecting first when there
+    /// is none. A failed call drops the session so the next one reconnects.
+    fn with_session<R>(
+        &self,
+        connector: &McpConnector,
+        f: impl FnOnce(&mut McpSession) -> Result<R>,
+    ) -> Result<R> {
+        let slot = self
+            .sessions
+            .lock()
+            .recover()
+            .entry(connector.name.clone())
+            .or_default()
+            .clone();
+        let mut slot = slot.lock().recover();
+        if slot.is_none() {
+            *slot = Some(McpSession {
+                client: McpClient::connect(connector, MCP_SESSION_TIMEOUT)?,
+                tools: None,
+            });
+        }
+        let result = f(slot.as_mut().expect("session initialised above"));
+        if result.is_err() {
+            *slot = None;
+        }
+        result
+    }
+
+    /// List `connector`'s tools on a throwaway session with the short
+    /// discovery timeout, off the caller's thread.
+    fn spawn_discovery(&self, connector: McpConnector) {
+        let discovery = Arc::clone(&self.discovery);
+        std::thread::spawn(move || {
+            let result = McpClient::connect(&connector, MCP_DISCOVERY_TIMEOUT)
+                .and_then(|mut client| client.list_tools());
+            if let Err(err) = &result {
+                warn!(connector = %connector.name, "MCP tool discovery failed: {err:#}");
+            }
+            let mut discovery = discovery.lock().recover();
+            let state = discovery.entry(connector.name).or_default();
+            state.running = false;
+            state.record(&result);
+        });
+    }
+
     fn inspect_docker(&self, settings: &McpDockerSettings) -> McpDockerStatus {
         let compose_present = settings
             .compose_file
@@ -221,6 +426,10 @@ impl McpOrchestrator {
             .and_then(|key| env::var(key).ok())
             .is_some();
 
+        if connector.transport != McpTransport::Rest {
+            return self.inspect_mcp_connector(connector, endpoint, has_api_key);
+        }
+
         let parsed = Url::parse(&connector.endpoint);
         if parsed.is_err() {
             issues.push("invalid endpoint URL".into());
@@ -278,8 +487,163 @@ impl McpOrchestrator {
             healthy,
             has_api_key,
             issues,
+            transport: connector.transport,
+            tools: Vec::new(),
         }
     }
+
+    /// Health of a `stdio`/`http` connector from its last discovery: healthy
+    /// once `tools/list` has answered. Never blocks on the server; a missing
+    /// or stale result starts a background discovery unless the connector is
+    /// backing off after a failure.
+    fn inspect_mcp_connector(
+        &self,
+        connector: &McpConnector,
+        endpoint: String,
+        has_api_key: bool,
+    ) -> McpConnectorStatus {
+        let mut issues = Vec::new();
+        let mut tools = Vec::new();
+        match connector.transport {
+            McpTransport::Stdio => {
+                let command = connector.command.as_deref().unwrap_or_default().trim();
+                if command.is_empty() {
+                    issues.push("no command configured for stdio connector".into());
+                } else if which::which(command).is_err() {
+                    issues.push(format!("command not found: {command}"));
+                }
+            }
+            _ => {
+                if Url::parse(&connector.endpoint).is_err() {
+                    issues.push("invalid endpoint URL".into());
+                }
+            }
+        }
+        if connector.enabled
+            && !has_api_key
+            && let Some(env_key) = &connector.api_key_env
+        {
+            issues.push(format!("missing API key environment variable {env_key}"));
+        }
+
+        let mut healthy = false;
+        if connector.enabled && issues.is_empty() {
+            let mut discovery = self.discovery.lock().recover();
+            let state = discovery.entry(connector.name.clone()).or_default();
+            if let Some(listed) = &state.tools {
+                healthy = true;
+                tools = listed.clone();
+            } else if let Some(err) = &state.error {
+                let retry_in = state
+                    .next_attempt
+                    .map(|at| at.saturating_duration_since(Instant::now()).as_secs())
+                    .unwrap_or_default();
+                issues.push(format!(
+                    "MCP session failed: {err} (retrying in {retry_in}s)"
+                ));
+            } else {
+                issues.push("tool discovery pending".into());
+            }
+            if state.due() {
+                state.running = true;
+                drop(discovery);
+                self.spawn_discovery(connector.clone());
+            }
+        }
+        if !connector.enabled {
+            issues.push("connector disabled".into());
+        }
+
+        McpConnectorStatus {
+            name: connector.name.clone(),
+            kind: connector.kind.clone(),
+            endpoint,
+            enabled: connector.enabled,
+            healthy,
+            has_api_key,
+            issues,
+            transport: connector.transport,
+            tools,
+        }
+    }
+}
+
+/// Exposes discovered connector tools to the AI bridge as `<connector>__<tool>`.
+impl McpOrchestrator {
+    /// The connector tools `/chat` may use, with `approved` naming the
+    /// state-changing ones the user confirmed for this request.
+    pub fn chat_tools(&self, approved: impl IntoIterator<Item = String>) -> ChatTools<'_> {
+        ChatTools {
+            mcp: self,
+            approved: approved.into_iter().collect(),
+        }
+    }
+}
+
+/// [`AiToolInvoker`] over the tools listed in each connector's `chat_tools`.
+///
+/// Tools are named `<connector>__<tool>`. A tool is refused until the request
+/// lists it in `approved`, and the refusal tells the model to ask. Only on
+/// connectors with `trust_read_only_hints` do tools annotated `readOnlyHint`
+/// run on the model's say-so.
+pub struct ChatTools<'a> {
+    mcp: &'a McpOrchestrator,
+    approved: BTreeSet<String>,
+}
+
+impl ChatTools<'_> {
+    fn offered(connector: &McpConnector, tool: &str) -> bool {
+        connector
+            .chat_tools
+            .iter()
+            .any(|allowed| allowed == "*" || allowed == tool)
+    }
+}
+
+impl AiToolInvoker for ChatTools<'_> {
+    fn tools(&self) -> Vec<AiTool> {
+        self.mcp
+            .discovered_tools()
+            .into_iter()
+            .filter(|(connector, tool)| {
+                self.mcp
+                    .enabled_connector(connector)
+                    .is_ok_and(|connector| Self::offered(connector, &tool.name))
+            })
+            .map(|(connector, tool)| AiTool {
+                name: format!("{connector}{AI_TOOL_SEPARATOR}{}", tool.name),
+                description: tool.description.unwrap_or_default(),
+                input_schema: tool.input_schema,
+            })
+            .collect()
+    }
+
+    fn invoke(&self, name: &str, arguments: Value) -> Result<String> {
+        let (connector, tool) = name
+            .split_once(AI_TOOL_SEPARATOR)
+            .with_context(|| format!("unknown tool '{name}'"))?;
+        let config = self.mcp.enabled_connector(connector)?;
+        if !Self::offered(config, tool) {
+            bail!("tool '{name}' is not offered to chat");
+        }
+        let trust_hints = config.trust_read_only_hints;
+        let read_only = self
+            .mcp
+            .list_tools(connector)?
+            .iter()
+            .find(|info| info.name == tool)
+            .with_context(|| format!("unknown tool '{name}'"))?
+            .read_only();
+        let allowed = (trust_hints && read_only) || self.approved.contains(name);
+        if !allowed {
+            bail!(
+                "tool '{name}' may change state and needs the user's confirmation; \
+                 ask the user to approve it before calling it again"
+            );
+        }
+        let response = self.mcp.call_tool(connector, tool, arguments)?;
+        Ok(render_tool_result(&response.payload))
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -322,6 +686,9 @@ pub struct McpConnectorStatus {
     pub healthy: bool,
     pub has_api_key: bool,
     pub issues: Vec<String>,
+    pub transport: McpTransport,
+    /// Tools discovered on `stdio`/`http` connectors (empty for `rest`).
+    pub tools: Vec<McpToolInfo>,
 }
 
 fn join_endpoint(base: &str, path: &str) -> String {
@@ -358,7 +725,9 @@ mod tests {
                 endpoint: "http://localhost:1234".into(),
                 api_key_env: None,
                 enabled: false,
+                ..McpConnector::default()
             }],
+            ..McpSettings::default()
         };
         let orchestrator = McpOrchestrator::from_settings(settings.clone()).unwrap();
         let report = orchestrator.health_report();
@@ -375,6 +744,7 @@ mod tests {
             endpoint: endpoint.into(),
             api_key_env: None,
             enabled,
+            ..McpConnector::default()
         }
     }
 
@@ -383,6 +753,7 @@ mod tests {
         let settings = McpSettings {
             docker: None,
             connectors: vec![connector("broken", "not a url", false)],
+            ..McpSettings::default()
         };
         let orchestrator = McpOrchestrator::from_settings(settings).unwrap();
         let status = &orchestrator.health_report().connectors[0];
@@ -394,6 +765,7 @@ mod tests {
         let settings = McpSettings {
             docker: None,
             connectors: vec![],
+            ..McpSettings::default()
         };
         let orchestrator = McpOrchestrator::from_settings(settings).unwrap();
         let err = orchestrator
@@ -407,6 +779,7 @@ mod tests {
         let settings = McpSettings {
             docker: None,
             connectors: vec![connector("svc", "http://localhost:9", false)],
+            ..McpSettings::default()
         };
         let orchestrator = McpOrchestrator::from_settings(settings).unwrap();
         let err = orchestrator
@@ -415,11 +788,166 @@ mod tests {
         assert!(err.to_string().contains("disabled"));
     }
 
+    #[test]
+    fn stdio_connector_without_command_is_unhealthy() {
+        let settings = McpSettings {
+            docker: None,
+            connectors: vec![McpConnector {
+                name: "fs".into(),
+                transport: McpTransport::Stdio,
+                enabled: true,
+                ..McpConnector::default()
+            }],
+            ..McpSettings::default()
+        };
+        let orchestrator = McpOrchestrator::from_settings(settings).unwrap();
+        let status = &orchestrator.health_report().connectors[0];
+        assert_eq!(status.transport, McpTransport::Stdio);
+        assert!(!status.healthy);
+        assert!(status.issues.iter().any(|i| i.contains("no command")));
+        assert!(orchestrator.discovered_tools().is_empty());
+    }
+
+    #[cfg(unix)]
+    fn wait_for_report(
+        orchestrator: &McpOrchestrator,
+        done: impl Fn(&McpConnectorStatus) -> bool,
+    ) -> McpConnectorStatus {
+        let deadline = Instant::now() + Duration::from_secs(10);
+        loop {
+            let status = orchestrator.health_report().connectors.remove(0);
+            if done(&status) || Instant::now() > deadline {
+                return status;
+            }
+            std::thread::sleep(Duration::from_millis(20));
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn health_reports_discovery_from_the_background() {
+        let orchestrator = McpOrchestrator::from_settings(McpSettings {
+            connectors: vec![crate::mcp_client::tests::stdio_connector()],
+            ..McpSettings::default()
+        })
+        .unwrap();
+        let status = &orchestrator.health_report().connectors[0];
+        assert!(!status.healthy);
+        assert!(
+            status.issues.iter().any(|i| i.contains("pending")),
+            "{:?}",
+            status.issues
+        );
+
+        let status = wait_for_report(&orchestrator, |status| status.healthy);
+        assert!(status.healthy, "{:?}", status.issues);
+        let names: Vec<_> = status.tools.iter().map(|tool| tool.name.as_str()).collect();
+        assert_eq!(names, ["echo", "add"]);
+        // Discovery used its own session; no long-lived one was opened.
+        assert!(orchestrator.sessions.lock().unwrap().is_empty());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn failed_discovery_backs_off() {
+        let orchestrator = McpOrchestrator::from_settings(McpSettings {
+            connectors: vec![McpConnector {
+                name: "down".into(),
+                transport: McpTransport::Stdio,
+                command: Some("sh".into()),
+                args: vec!["-c".into(), "exit 1".into()],
+                enabled: true,
+                ..McpConnector::default()
+            }],
+            ..McpSettings::default()
+        })
+        .unwrap();
+        let status = wait_for_report(&orchestrator, |status| {
+            status.issues.iter().any(|i| i.contains("retrying in"))
+        });
+        assert!(!status.healthy);
+        assert!(
+            status
+                .issues
+                .iter()
+                .any(|i| i.contains("MCP session failed")),
+            "{:?}",
+            status.issues
+        );
+
+        // Within the backoff, reports do not start another attempt.
+        for _ in 0..3 {
+            orchestrator.health_report();
+        }
+        std::thread::sleep(Duration::from_millis(100));
+        let discovery = orchestrator.discovery.lock().unwrap();
+        let state = &discovery["down"];
+        assert_eq!(state.failures, 1);
+        assert!(!state.running && state.backing_off());
+        drop(discovery);
+        assert!(orchestrator.discovered_tools().is_empty());
+    }
+
+    #[test]
+    fn ai_tool_names_must_name_a_connector() {
+        let orchestrator = McpOrchestrator::from_settings(McpSettings::default()).unwrap();
+        let tools = orchestrator.chat_tools([]);
+        let err = tools.invoke("plain", Value::Null).unwrap_err();
+        assert!(err.to_string().contains("unknown tool"));
+        let err = tools.invoke("ghost__read", Value::Null).unwrap_err();
+        assert!(err.to_string().contains("not configured"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn chat_tools_are_allow_listed_and_confirm_state_changes() {
+        let orchestrator = |chat_tools: &[&str], trust_read_only_hints: bool| {
+            let connector = McpConnector {
+                chat_tools: chat_tools.iter().map(|tool| tool.to_string()).collect(),
+                trust_read_only_hints,
+                ..crate::mcp_client::tests::stdio_connector()
+            };
+            McpOrchestrator::from_settings(McpSettings {
+                connectors: vec![connector],
+                ..McpSettings::default()
+            })
+            .unwrap()
+        };
+
+        let listed = orchestrator(&["echo"], true);
+        let tools = listed.chat_tools([]);
+        let names: Vec<_> = tools.tools().into_iter().map(|tool| tool.name).collect();
+        assert_eq!(names, ["fake__echo"]);
+        // `echo` is annotated read-only and the connector trusts hints, so it
+        // runs without confirmation.
+        assert_eq!(tools.invoke("fake__echo", Value::Null).unwrap(), "echoed");
+        let err = tools.invoke("fake__add", Value::Null).unwrap_err();
+        assert!(err.to_string().contains("not offered"), "{err}");
+
+        // Without the opt-in the hint is ignored.
+        let untrusted = orchestrator(&["echo"], false);
+        let err = untrusted
+            .chat_tools([])
+            .invoke("fake__echo", Value::Null)
+            .unwrap_err();
+        assert!(err.to_string().contains("confirmation"), "{err}");
+
+        let all = orchestrator(&["*"], true);
+        let err = all
+            .chat_tools([])
+            .invoke("fake__add", Value::Null)
+            .unwrap_err();
+        assert!(err.to_string().contains("confirmation"), "{err}");
+        let approved = all.chat_tools(["fake__add".to_string()]);
+        assert_eq!(approved.invoke("fake__add", Value::Null).unwrap(), "echoed");
+    }
+
     #[test]
     fn connectors_accessor_exposes_configuration() {
         let settings = McpSettings {
             docker: None,
             connectors: vec![connector("a", "http://localhost:1", false)],
+            ..McpSettings::default()
         };
         let orchestrator = McpOrchestrator::from_settings(settings).unwrap();
         assert_eq!(orchestrator.connectors().len(), 1);
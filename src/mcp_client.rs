@@ -0,0 +1,665 @@
+//! Model Context Protocol client for connectors.
+//!
+//! Speaks standard MCP to third-party servers so they can be plugged in as
+//! [`McpConnector`]s: servers spawned as a child process (newline-delimited
+//! JSON-RPC over stdio) or reached over Streamable HTTP. A session performs
+//! the `initialize` handshake, discovers tools with `tools/list` and invokes
+//! them with `tools/call`.
+
+use std::env;
+use std::io::{BufRead, BufReader, Write};
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::time::{Duration, Instant};
+
+use anyhow::{Context, Result, bail};
+use reqwest::blocking::Client;
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use tracing::debug;
+
+use crate::config::{McpConnector, McpTransport};
+
+/// Protocol version requested during `initialize`.
+pub const CLIENT_PROTOCOL_VERSION: &str = "2025-06-18";
+
+/// Upper bound on `tools/list` pages followed through `nextCursor`.
+const MAX_TOOL_PAGES: usize = 20;
+
+const SESSION_HEADER: &str = "mcp-session-id";
+const PROTOCOL_VERSION_HEADER: &str = "mcp-protocol-version";
+
+/// A tool advertised by an MCP server.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct McpToolInfo {
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    /// JSON Schema of the tool's arguments.
+    #[serde(default, rename = "inputSchema")]
+    pub input_schema: Value,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub annotations: Option<McpToolAnnotations>,
+}
+
+impl McpToolInfo {
+    /// Whether the server marks the tool as changing nothing (`readOnlyHint`).
+    pub fn read_only(&self) -> bool {
+        self.annotations
+            .as_ref()
+            .is_some_and(|annotations| annotations.read_only_hint)
+    }
+}
+
+/// Behaviour hints a server attaches to a tool. Servers are not trusted to
+/// be truthful; a missing hint is treated as "may change state".
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct McpToolAnnotations {
+    #[serde(default, rename = "readOnlyHint")]
+    pub read_only_hint: bool,
+}
+
+/// An initialized session with one MCP server.
+#[derive(Debug)]
+pub struct McpClient {
+    transport: Transport,
+    next_id: u64,
+    timeout: Duration,
+    server_info: Value,
+}
+
+#[derive(Debug)]
+enum Transport {
+    Stdio(StdioTransport),
+    Http(HttpTransport),
+}
+
+impl McpClient {
+    /// Start a session with `connector`'s server and complete the
+    /// `initialize` handshake. `timeout` bounds every request.
+    pub fn connect(connector: &McpConnector, timeout: Duration) -> Result<Self> {
+        let transport = match connector.transport {
+            McpTransport::Stdio => Transport::Stdio(StdioTransport::spawn(connector)?),
+            McpTransport::Http => Transport::Http(HttpTransport::new(connector, timeout)?),
+            McpTransport::Rest => bail!(
+                "connector '{}' uses the rest transport, not MCP",
+                connector.name
+            ),
+        };
+        let mut client = Self {
+            transport,
+            next_id: 0,
+            timeout,
+            server_info: Value::Null,
+        };
+        let result = client
+            .request(
+                "initialize",
+                json!({
+                    "protocolVersion": CLIENT_PROTOCOL_VERSION,
+                    "capabilities": {},
+                    "clientInfo": { "name": "archon", "version": env!("CARGO_PKG_VERSION") },
+                }),
+            )
+            .with_context(|| format!("MCP handshake with connector '{}' failed", connector.name))?;
+        if let Transport::Http(http) = &mut client.transport {
+            http.protocol_version = result
+                .get("protocolVersion")
+                .and_then(Value::as_str)
+                .map(str::to_string);
+        }
+        client.server_info = result.get("serverInfo").cloned().unwrap_or(Value::Null);
+        client.notify("notifications/initialized")?;
+        Ok(client)
+    }
+
+    /// `serverInfo` reported by the server during `initialize`.
+    pub fn server_info(&self) -> &Value {
+        &self.server_info
+    }
+
+    /// Discover the server's tools, following pagination.
+    pub fn list_tools(&mut self) -> Result<Vec<McpToolInfo>> {
+        let mut tools = Vec::new();
+        let mut cursor: Option<String> = None;
+        for _ in 0..MAX_TOOL_PAGES {
+            let params = match &cursor {
+                Some(cursor) => json!({ "cursor": cursor }),
+                None => json!({}),
+            };
+            let result = self.request("tools/list", params)?;
+            let page: Vec<McpToolInfo> =
+                serde_json::from_value(result.get("tools").cloned().unwrap_or_else(|| json!([])))
+                    .context("tools/list returned malformed tools")?;
+            tools.extend(page);
+            cursor = result
+                .get("nextCursor")
+                .and_then(Value::as_str)
+                .map(str::to_string);
+            if cursor.is_none() {
+                break;
+            }
+        }
+        Ok(tools)
+    }
+
+    /// Invoke `name`, returning the raw `tools/call` result (`content`,
+    /// `isError`, optional `structuredContent`).
+    pub fn call_tool(&mut self, name: &str, arguments: Value) -> Result<Value> {
+        let arguments = if arguments.is_null() {
+            json!({})
+        } else {
+            arguments
+        };
+        self.request(
+            "tools/call",
+            json!({ "name": name, "arguments": arguments }),
+        )
+    }
+
+    fn request(&mut self, method: &str, params: Value) -> Result<Value> {
+        self.next_id += 1;
+        let id = self.next_id;
+        let frame = json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params });
+        let reply = match &mut self.transport {
+            Transport::Stdio(stdio) => stdio.exchange(&frame, id, self.timeout)?,
+            Transport::Http(http) => http
+                .post(&frame, Some(id))?
+                .with_context(|| format!("server sent no response to {method}"))?,
+        };
+        if let Some(error) = reply.get("error") {
+            bail!(
+                "{method} failed: {} (code {})",
+                error
+                    .get("message")
+                    .and_then(Value::as_str)
+                    .unwrap_or("unknown error"),
+                error.get("code").cloned().unwrap_or(Value::Null)
+            );
+        }
+        reply
+            .get("result")
+            .cloned()
+            .with_context(|| format!("{method} response had no result"))
+    }
+
+    fn notify(&mut self, method: &str) -> Result<()> {
+        let frame = json!({ "jsonrpc": "2.0", "method": method });
+        match &mut self.transport {
+            Transport::Stdio(stdio) => stdio.send(&frame),
+            Transport::Http(http) => http.post(&frame, None).map(|_| ()),
+        }
+    }
+}
+
+/// Render a `tools/call` result as plain text for a model or a log: text
+/// content joined, other content types summarised.
+pub fn render_tool_result(result: &Value) -> String {
+    let mut parts = Vec::new();
+    for item in result
+        .get("content")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+    {
+        match item.get("type").and_then(Value::as_str) {
+            Some("text") => {
+                parts.push(item["text"].as_str().unwrap_or_default().to_string());
+            }
+            Some("resource") => {
+                let resource = &item["resource"];
+                match resource.get("text").and_then(Value::as_str) {
+                    Some(text) => parts.push(text.to_string()),
+                    None => parts.push(format!(
+                        "[resource {}]",
+                        resource["uri"].as_str().unwrap_or("?")
+                    )),
+                }
+            }
+            Some(kind) => parts.push(format!(
+                "[{kind} content{}]",
+                item.get("mimeType")
+                    .and_then(Value::as_str)
+                    .map(|mime| format!(" ({mime})"))
+                    .unwrap_or_default()
+            )),
+            None => {}
+        }
+    }
+    if parts.is_empty()
+        && let Some(structured) = result.get("structuredContent")
+    {
+        parts.push(structured.to_string());
+    }
+    let text = parts.join("\n");
+    if result.get("isError").and_then(Value::as_bool) == Some(true) {
+        format!("Tool error: {text}")
+    } else {
+        text
+    }
+}
+
+// ---------------------------------------------------------------------------
+// stdio
+// ---------------------------------------------------------------------------
+
+#[derive(Debug)]
+struct StdioTransport {
+    child: Child,
+    stdin: ChildStdin,
+    /// Lines read from the server's stdout by a reader thread.
+    lines: Receiver<String>,
+}
+
+impl StdioTransport {
+    fn spawn(connector: &McpConnector) -> Result<Self> {
+        let program = connector
+            .command
+            .as_deref()
+            .map(str::trim)
+            .filter(|command| !command.is_empty())
+            .with_context(|| format!("stdio connector '{}' has no command", connector.name))?;
+        let mut child = Command::new(program)
+            .args(&connector.args)
+            .envs(&connector.env)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .with_context(|| {
+                format!(
+                    "failed to start MCP server '{program}' for connector '{}'",
+                    connector.name
+                )
+            })?;
+        let stdin = child.stdin.take().context("MCP server stdin unavailable")?;
+        let stdout = child
+            .stdout
+            .take()
+            .context("MCP server stdout unavailable")?;
+
+        let (tx, lines) = mpsc::channel();
+        std::thread::Builder::new()
+            .name(format!("mcp-{}-stdout", connector.name))
+            .spawn(move || {
+                for line in BufReader::new(stdout).lines() {
+                    let Ok(line) = line else { break };
+                    if tx.send(line).is_err() {
+                        break;
+                    }
+                }
+            })
+            .context("failed to start MCP stdout reader")?;
+        if let Some(stderr) = child.stderr.take() {
+            let name = connector.name.clone();
+            std::thread::Builder::new()
+                .name(format!("mcp-{}-stderr", connector.name))
+                .spawn(move || {
+                    for line in BufReader::new(stderr).lines().map_while(Result::ok) {
+                        debug!(connector = %name, "mcp server: {line}");
+                    }
+                })
+                .context("failed to start MCP stderr reader")?;
+        }
+        Ok(Self {
+            child,
+            stdin,
+            lines,
+        })
+    }
+
+    fn send(&mut self, frame: &Value) -> Result<()> {
+        let mut line = serde_json::to_string(frame)?;
+        line.push('\n');
+        self.stdin
+            .write_all(line.as_bytes())
+            .and_then(|()| self.stdin.flush())
+            .context("failed to write to MCP server (has it exited?)")
+    }
+
+    /// Send a request and wait for the response carrying `id`, answering
+    /// server-initiated requests and skipping notifications meanwhile.
+    fn exchange(&mut self, frame: &Value, id: u64, timeout: Duration) -> Result<Value> {
+        self.send(frame)?;
+        let deadline = Instant::now() + timeout;
+        loop {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            let line = match self.lines.recv_timeout(remaining) {
+                Ok(line) => line,
+                Err(RecvTimeoutError::Timeout) => {
+                    bail!("MCP server did not answer within {timeout:?}")
+                }
+                Err(RecvTimeoutError::Disconnected) => bail!("MCP server closed its output"),
+            };
+            let Ok(message) = serde_json::from_str::<Value>(line.trim()) else {
+                debug!("mcp: ignoring non-JSON server output: {line}");
+                continue;
+            };
+            if let Some(method) = message.get("method").and_then(Value::as_str) {
+                if let Some(request_id) = message.get("id") {
+                    let reply = if method == "ping" {
+                        json!({ "jsonrpc": "2.0", "id": request_id, "result": {} })
+                    } else {
+                        json!({
+                            "jsonrpc": "2.0",
+                            "id": request_id,
+                            "error": { "code": -32601, "message": format!("method not supported: {method}") },
+                        })
+                    };
+                    self.send(&reply)?;
+                }
+                continue;
+            }
+            if message.get("id").and_then(Value::as_u64) == Some(id) {
+                return Ok(message);
+            }
+        }
+    }
+}
+
+impl Drop for StdioTransport {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Streamable HTTP
+// ---------------------------------------------------------------------------
+
+#[derive(Debug)]
+struct HttpTransport {
+    client: Client,
+    endpoint: String,
+    token: Option<String>,
+    session: Option<String>,
+    protocol_version: Option<String>,
+}
+
+impl HttpTransport {
+    fn new(connector: &McpConnector, timeout: Duration) -> Result<Self> {
+        url::Url::parse(&connector.endpoint).with_context(|| {
+            format!(
+                "connector '{}' has an invalid endpoint URL '{}'",
+                connector.name, connector.endpoint
+            )
+        })?;
+        let token = match &connector.api_key_env {
+            Some(key) => Some(
+                env::var(key)
+                    .ok()
+                    .filter(|value| !value.trim().is_empty())
+                    .with_context(|| {
+                        format!(
+                            "Environment variable {key} for connector {} not set",
+                            connector.name
+                        )
+                    })?,
+            ),
+            None => None,
+        };
+        let client = Client::builder()
+            .user_agent("Archon/0.1 (mcp-client)")
+            .timeout(timeout)
+            .build()
+            .context("failed to build reqwest client for MCP connector")?;
+        Ok(Self {
+            client,
+            endpoint: connector.endpoint.clone(),
+            token,
+            session: None,
+            protocol_version: None,
+        })
+    }
+
+    /// POST one frame. Returns the response whose id is `id`, or `None` for
+    /// notifications (answered with 202).
+    fn post(&mut self, frame: &Value, id: Option<u64>) -> Result<Option<Value>> {
+        let mut request = self
+            .client
+            .post(&self.endpoint)
+            .header("Accept", "application/json, text/event-stream")
+            .json(frame);
+        if let Some(token) = &self.token {
+            request = request.bearer_auth(token);
+        }
+        if let Some(session) = &self.session {
+            request = request.header(SESSION_HEADER, session);
+        }
+        if let Some(version) = &self.protocol_version {
+            request = request.header(PROTOCOL_VERSION_HEADER, version);
+        }
+        let response = request
+            .send()
+            .with_context(|| format!("failed to reach MCP server at {}", self.endpoint))?;
+        let status = response.status();
+        if status == reqwest::StatusCode::NOT_FOUND && self.session.is_some() {
+            bail!("MCP session expired at {}", self.endpoint);
+        }
+        if !status.is_success() {
+            bail!("MCP server at {} returned status {status}", self.endpoint);
+        }
+        if let Some(session) = response
+            .headers()
+            .get(SESSION_HEADER)
+            .and_then(|value| value.to_str().ok())
+        {
+            self.session = Some(session.to_string());
+        }
+        let Some(id) = id else {
+            return Ok(None);
+        };
+        let event_stream = response
+            .headers()
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .is_some_and(|value| value.starts_with("text/event-stream"));
+        let body = response
+            .text()
+            .context("failed to read MCP server response")?;
+        let messages = if event_stream {
+            sse_messages(&body)
+        } else {
+            match serde_json::from_str::<Value>(&body)
+                .context("MCP server returned invalid JSON")?
+            {
+                Value::Array(batch) => batch,
+                single => vec![single],
+            }
+        };
+        Ok(messages
+            .into_iter()
+            .find(|message| message.get("id").and_then(Value::as_u64) == Some(id)))
+    }
+}
+
+/// JSON payloads of the `data:` fields in an SSE body, one per event.
+fn sse_messages(body: &str) -> Vec<Value> {
+    let mut messages = Vec::new();
+    let mut data = String::new();
+    for line in body.lines().chain(std::iter::once("")) {
+        if line.is_empty() {
+            if !data.is_empty() {
+                if let Ok(message) = serde_json::from_str(&data) {
+                    messages.push(message);
+                }
+                data.clear();
+            }
+        } else if let Some(value) = line.strip_prefix("data:") {
+            if !data.is_empty() {
+                data.push('\n');
+            }
+            data.push_str(value.strip_prefix(' ').unwrap_or(value));
+        }
+    }
+    messages
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use std::io::Read;
+    use std::net::TcpListener;
+
+    /// A scripted stdio MCP server: answers initialize, pages tools/list
+    /// and echoes tools/call arguments, pinging the client once first.
+    #[cfg(unix)]
+    const FAKE_SERVER: &str = r#"
+while IFS= read -r line; do
+  id=$(printf '%s' "$line" | sed -n 's/.*"id":\([0-9]*\).*/\1/p')
+  case "$line" in
+    *'"method":"initialize"'*)
+      printf '{"jsonrpc":"2.0","id":%s,"result":{"protocolVersion":"2025-06-18","capabilities":{"tools":{}},"serverInfo":{"name":"fake","version":"1"}}}\n' "$id" ;;
+    *'"method":"tools/list"'*'"cursor"'*)
+      printf '{"jsonrpc":"2.0","id":%s,"result":{"tools":[{"name":"add","inputSchema":{"type":"object"}}]}}\n' "$id" ;;
+    *'"method":"tools/list"'*)
+      printf '{"jsonrpc":"2.0","method":"notifications/message","params":{}}\n'
+      printf '{"jsonrpc":"2.0","id":%s,"result":{"tools":[{"name":"echo","description":"Echo text","inputSchema":{"type":"object"},"annotations":{"readOnlyHint":true}}],"nextCursor":"p2"}}\n' "$id" ;;
+    *'"method":"tools/call"'*)
+      printf '{"jsonrpc":"2.0","id":"srv-1","method":"ping"}\n'
+      read -r pong
+      case "$pong" in *'"result"'*) ;; *) exit 1 ;; esac
+      printf '{"jsonrpc":"2.0","id":%s,"result":{"content":[{"type":"text","text":"echoed"}],"isError":false}}\n' "$id" ;;
+  esac
+done
+"#;
+
+    #[cfg(unix)]
+    pub(crate) fn stdio_connector() -> McpConnector {
+        McpConnector {
+            name: "fake".into(),
+            kind: "test".into(),
+            transport: McpTransport::Stdio,
+            command: Some("sh".into()),
+            args: vec!["-c".into(), FAKE_SERVER.into()],
+            enabled: true,
+            ..McpConnector::default()
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn stdio_session_discovers_paged_tools_and_calls_them() {
+        let mut client =
+            McpClient::connect(&stdio_connector(), Duration::from_secs(10)).expect("connect");
+        assert_eq!(client.server_info()["name"], "fake");
+
+        let tools = client.list_tools().expect("tools/list");
+        let names: Vec<&str> = tools.iter().map(|tool| tool.name.as_str()).collect();
+        assert_eq!(names, ["echo", "add"]);
+        assert_eq!(tools[0].description.as_deref(), Some("Echo text"));
+        assert!(tools[0].read_only() && !tools[1].read_only());
+
+        let result = client
+            .call_tool("echo", json!({ "text": "hi" }))
+            .expect("tools/call");
+        assert_eq!(render_tool_result(&result), "echoed");
+    }
+
+    #[test]
+    fn missing_stdio_command_is_reported() {
+        let connector = McpConnector {
+            name: "broken".into(),
+            transport: McpTransport::Stdio,
+            ..McpConnector::default()
+        };
+        let err = McpClient::connect(&connector, Duration::from_secs(1)).unwrap_err();
+        assert!(err.to_string().contains("has no command"));
+    }
+
+    /// Serve `replies` to consecutive HTTP requests, recording each request head.
+    fn http_server(replies: Vec<(&'static str, String)>) -> (String, Receiver<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/mcp", listener.local_addr().unwrap());
+        let (tx, rx) = mpsc::channel();
+        std::thread::spawn(move || {
+            for (content_type, body) in replies {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut raw = Vec::new();
+                let mut buf = [0u8; 4096];
+                loop {
+                    let n = stream.read(&mut buf).unwrap();
+                    raw.extend_from_slice(&buf[..n]);
+                    let text = String::from_utf8_lossy(&raw).to_string();
+                    if let Some(end) = text.find("\r\n\r\n") {
+                        let length = text[..end]
+                            .lines()
+                            .find_map(|line| {
+                                line.to_ascii_lowercase()
+                                    .strip_prefix("content-length:")
+                                    .map(|value| value.trim().parse::<usize>().unwrap())
+                            })
+                            .unwrap_or(0);
+                        if raw.len() >= end + 4 + length {
+                            tx.send(text).unwrap();
+                            break;
+                        }
+                    }
+                }
+                let status = if body.is_empty() {
+                    "202 Accepted"
+                } else {
+                    "200 OK"
+                };
+                let response = format!(
+                    "HTTP/1.1 {status}\r\nContent-Type: {content_type}\r\nContent-Length: {}\r\nMcp-Session-Id: sess-1\r\nConnection: close\r\n\r\n{body}",
+                    body.len()
+                );
+                stream.write_all(response.as_bytes()).unwrap();
+            }
+        });
+        (url, rx)
+    }
+
+    #[test]
+    fn http_session_carries_session_id_and_reads_sse_responses() {
+        let (endpoint, requests) = http_server(vec![
+            (
+                "application/json",
+                json!({ "jsonrpc": "2.0", "id": 1, "result": { "protocolVersion": "2025-03-26", "serverInfo": { "name": "remote" } } }).to_string(),
+            ),
+            ("application/json", String::new()),
+            (
+                "text/event-stream",
+                format!(
+                    "event: message\ndata: {}\n\ndata: {}\n\n",
+                    json!({ "jsonrpc": "2.0", "method": "notifications/progress", "params": {} }),
+                    json!({ "jsonrpc": "2.0", "id": 2, "result": { "content": [{ "type": "text", "text": "done" }] } })
+                ),
+            ),
+        ]);
+        let connector = McpConnector {
+            name: "remote".into(),
+            transport: McpTransport::Http,
+            endpoint,
+            enabled: true,
+            ..McpConnector::default()
+        };
+        let mut client = McpClient::connect(&connector, Duration::from_secs(10)).expect("connect");
+        let result = client.call_tool("work", Value::Null).expect("tools/call");
+        assert_eq!(render_tool_result(&result), "done");
+
+        let initialize = requests.recv().unwrap().to_ascii_lowercase();
+        assert!(!initialize.contains("mcp-session-id"));
+        let initialized = requests.recv().unwrap().to_ascii_lowercase();
+        assert!(initialized.contains("mcp-session-id: sess-1"));
+        assert!(initialized.contains("mcp-protocol-version: 2025-03-26"));
+        assert!(requests.recv().unwrap().contains("\"arguments\":{}"));
+    }
+
+    #[test]
+    fn render_tool_result_flags_errors_and_summarises_binary_content() {
+        let result = json!({
+            "content": [
+                { "type": "text", "text": "partial" },
+                { "type": "image", "data": "AAAA", "mimeType": "image/png" },
+            ],
+            "isError": true,
+        });
+        assert_eq!(
+            render_tool_result(&result),
+            "Tool error: partial\n[image content (image/png)]"
+        );
+    }
+}
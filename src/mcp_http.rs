@@ -0,0 +1,743 @@
+//! MCP Streamable HTTP transport for the [`BrowserToolbox`].
+//!
+//! `archon-host` mounts this at `mcp.server.path` (default `/mcp`) so several
+//! MCP clients can share one browser instead of each spawning `archon --mcp`.
+//! The [`McpHttpHub`] is transport-agnostic: the host maps HTTP requests onto
+//! it and streams the frames it returns.
+//!
+//! - A single worker thread owns the toolbox (and therefore the browser);
+//!   requests from all sessions are handled in arrival order.
+//! - `initialize` opens a session whose ID the client echoes in the
+//!   `Mcp-Session-Id` header; other requests without a known session are
+//!   refused. Sessions expire after `session_idle_secs`.
+//! - Resource subscriptions are tracked per session. The toolbox sees their
+//!   union, and its `notifications/resources/*` are routed to the sessions
+//!   that subscribed; `notifications/progress` go to the request that asked
+//!   for them.
+//! - Every request must carry the bearer token from `mcp.server.token_env`,
+//!   and browser `Origin`s other than localhost must be allow-listed, which
+//!   blocks DNS-rebinding attacks from web pages.
+
+use std::collections::{BTreeSet, HashMap};
+use std::sync::{Arc, Mutex, mpsc};
+use std::time::{Duration, Instant};
+
+use anyhow::{Context, Result};
+use serde_json::{Value, json};
+use tokio::sync::{mpsc::UnboundedSender, oneshot};
+use tracing::{debug, info, warn};
+use uuid::Uuid;
+
+use crate::config::McpServerSettings;
+use crate::mcp_server::BrowserToolbox;
+use crate::sync_util::LockResultExt;
+
+/// Header carrying the session ID.
+pub const SESSION_HEADER: &str = "mcp-session-id";
+
+/// Header carrying the negotiated protocol version.
+pub const PROTOCOL_VERSION_HEADER: &str = "mcp-protocol-version";
+
+/// Protocol versions that define the Streamable HTTP transport.
+pub const SUPPORTED_PROTOCOL_VERSIONS: [&str; 2] = ["2025-06-18", "2025-03-26"];
+
+/// Why a request was refused, mapped to an HTTP status by the host.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HttpRefusal {
+    /// 400: malformed body, missing session header, bad protocol version.
+    BadRequest(String),
+    /// 401: missing or wrong bearer token.
+    Unauthorized,
+    /// 403: origin not allowed.
+    Forbidden(String),
+    /// 404: unknown or expired session (the client must re-initialize).
+    SessionNotFound,
+    /// 503: the session limit is reached or the worker stopped.
+    Unavailable(String),
+}
+
+impl HttpRefusal {
+    pub fn status_code(&self) -> u16 {
+        match self {
+            Self::BadRequest(_) => 400,
+            Self::Unauthorized => 401,
+            Self::Forbidden(_) => 403,
+            Self::SessionNotFound => 404,
+            Self::Unavailable(_) => 503,
+        }
+    }
+
+    pub fn message(&self) -> String {
+        match self {
+            Self::BadRequest(message) | Self::Forbidden(message) | Self::Unavailable(message) => {
+                message.clone()
+            }
+            Self::Unauthorized => "missing or invalid bearer token".to_string(),
+            Self::SessionNotFound => "unknown or expired MCP session".to_string(),
+        }
+    }
+
+    /// A JSON-RPC error response for request `id`, for refusals that happen
+    /// after an SSE response has already started.
+    pub fn to_jsonrpc(&self, id: &Value) -> String {
+        let code = match self {
+            Self::BadRequest(_) => -32600,
+            _ => -32603,
+        };
+        json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "error": {
+                "code": code,
+                "message": self.message(),
+                "data": { "status": self.status_code() },
+            },
+        })
+        .to_string()
+    }
+}
+
+/// The outcome of [`McpHttpHub::handle`].
+#[derive(Debug)]
+pub struct HubReply {
+    /// Set when the request opened a session (`initialize`).
+    pub session_id: Option<String>,
+    /// JSON-RPC response frame; `None` for notifications and responses
+    /// (answered with 202 Accepted).
+    pub response: Option<String>,
+}
+
+/// Frames for one in-flight request (progress) or one session's `GET`
+/// stream (everything else).
+pub type FrameSender = UnboundedSender<String>;
+
+struct Session {
+    last_seen: Instant,
+    subscriptions: BTreeSet<String>,
+    /// The session's `GET` stream, if open.
+    listener: Option<FrameSender>,
+}
+
+type Sessions = Arc<Mutex<HashMap<String, Session>>>;
+
+struct Job {
+    line: String,
+    sink: crate::mcp_server::NotificationSink,
+    reply: oneshot::Sender<Option<String>>,
+}
+
+/// Shares one [`BrowserToolbox`] between MCP sessions.
+pub struct McpHttpHub {
+    settings: McpServerSettings,
+    token: String,
+    sessions: Sessions,
+    worker: mpsc::Sender<Job>,
+}
+
+impl McpHttpHub {
+    /// Start the worker thread. `build` runs on that thread, so the toolbox
+    /// (and its browser driver) never has to be `Send`.
+    pub fn start<F>(settings: McpServerSettings, token: String, build: F) -> Result<Arc<Self>>
+    where
+        F: FnOnce() -> BrowserToolbox + Send + 'static,
+    {
+        let (tx, rx) = mpsc::channel::<Job>();
+        std::thread::Builder::new()
+            .name("archon-mcp-http".into())
+            .spawn(move || {
+                let mut toolbox = build();
+                // Ends when the hub (the only sender) is dropped.
+                while let Ok(job) = rx.recv() {
+                    toolbox.set_notification_sink(Some(job.sink));
+                    let response = toolbox.handle_line(&job.line);
+                    toolbox.set_notification_sink(None);
+                    let _ = job.reply.send(response);
+                }
+                debug!("mcp http worker stopped");
+            })
+            .context("failed to spawn MCP HTTP worker")?;
+        info!(path = %settings.path, "MCP Streamable HTTP transport ready");
+        Ok(Arc::new(Self {
+            settings,
+            token,
+            sessions: Arc::default(),
+            worker: tx,
+        }))
+    }
+
+    pub fn settings(&self) -> &McpServerSettings {
+        &self.settings
+    }
+
+    /// Check the `Origin` and `Authorization` headers of any request.
+    pub fn authorize(
+        &self,
+        origin: Option<&str>,
+        authorization: Option<&str>,
+    ) -> Result<(), HttpRefusal> {
+        if let Some(origin) = origin
+            && !self.origin_allowed(origin)
+        {
+            return Err(HttpRefusal::Forbidden(format!(
+                "origin {origin} is not allowed"
+            )));
+        }
+        let presented = authorization.and_then(|value| value.strip_prefix("Bearer "));
+        if presented.is_none_or(|token| !constant_time_eq(token.trim(), &self.token)) {
+            return Err(HttpRefusal::Unauthorized);
+        }
+        Ok(())
+    }
+
+    fn origin_allowed(&self, origin: &str) -> bool {
+        origin_allowed(origin, &self.settings.allowed_origins)
+    }
+
+    /// Validate the `MCP-Protocol-Version` header (absent is accepted, as
+    /// the spec requires for older clients).
+    pub fn check_protocol_version(&self, version: Option<&str>) -> Result<(), HttpRefusal> {
+        match version {
+            Some(version) if !SUPPORTED_PROTOCOL_VERSIONS.contains(&version) => Err(
+                HttpRefusal::BadRequest(format!("unsupported MCP protocol version {version}")),
+            ),
+            _ => Ok(()),
+        }
+    }
+
+    /// Handle one POSTed JSON-RPC message. `stream` receives the request's
+    /// progress notifications (and resource notifications for this session
+    /// when it has no `GET` stream) while it runs.
+    pub async fn handle(
+        &self,
+        session: Option<&str>,
+        body: &str,
+        stream: Option<FrameSender>,
+    ) -> Result<HubReply, HttpRefusal> {
+        let message: Value = serde_json::from_str(body)
+            .map_err(|err| HttpRefusal::BadRequest(format!("invalid JSON-RPC body: {err}")))?;
+        if message.is_array() {
+            return Err(HttpRefusal::BadRequest(
+                "batched JSON-RPC messages are not supported".into(),
+            ));
+        }
+        let method = message.get("method").and_then(Value::as_str);
+        let is_request = method.is_some() && message.get("id").is_some();
+
+        self.expire_idle().await;
+        let (session_id, opened) = if is_request && method == Some("initialize") {
+            (self.open_session()?, true)
+        } else {
+            let id = session.ok_or_else(|| {
+                HttpRefusal::BadRequest(format!("missing {SESSION_HEADER} header"))
+            })?;
+            self.touch(id)?;
+            (id.to_string(), false)
+        };
+
+        if let Some(response) = self.unsubscribe(&session_id, &message) {
+            return Ok(HubReply {
+                session_id: None,
+                response: Some(response),
+            });
+        }
+
+        let response = self.dispatch(body, &session_id, stream).await?;
+        if method == Some("resources/subscribe")
+            && let Some(uri) = message.pointer("/params/uri").and_then(Value::as_str)
+            && response
+                .as_deref()
+                .and_then(|r| serde_json::from_str::<Value>(r).ok())
+                .is_some_and(|r| r.get("error").is_none())
+            && let Some(entry) = self.sessions.lock().recover().get_mut(&session_id)
+        {
+            entry.subscriptions.insert(uri.to_string());
+        }
+        Ok(HubReply {
+            session_id: opened.then_some(session_id),
+            response: if is_request { response } else { None },
+        })
+    }
+
+    /// Open the session's `GET` stream for server-initiated notifications,
+    /// replacing any previous one.
+    pub fn listen(&self, session: &str, sender: FrameSender) -> Result<(), HttpRefusal> {
+        self.touch(session)?;
+        let mut sessions = self.sessions.lock().recover();
+        let entry = sessions
+            .get_mut(session)
+            .ok_or(HttpRefusal::SessionNotFound)?;
+        entry.listener = Some(sender);
+        Ok(())
+    }
+
+    /// Terminate a session (`DELETE`), dropping subscriptions only it held.
+    pub async fn close(&self, session: &str) -> Result<(), HttpRefusal> {
+        let removed = self
+            .sessions
+            .lock()
+            .recover()
+            .remove(session)
+            .ok_or(HttpRefusal::SessionNotFound)?;
+        self.release_subscriptions(removed.subscriptions).await;
+        Ok(())
+    }
+
+    pub fn session_count(&self) -> usize {
+        self.sessions.lock().recover().len()
+    }
+
+    fn open_session(&self) -> Result<String, HttpRefusal> {
+        let mut sessions = self.sessions.lock().recover();
+        if sessions.len() >= self.settings.max_sessions.max(1) {
+            return Err(HttpRefusal::Unavailable(format!(
+                "MCP session limit ({}) reached",
+                self.settings.max_sessions
+            )));
+        }
+        let id = Uuid::new_v4().simple().to_string();
+        sessions.insert(
+            id.clone(),
+            Session {
+                last_seen: Instant::now(),
+                subscriptions: BTreeSet::new(),
+                listener: None,
+            },
+        );
+        debug!(session = %id, "mcp http session opened");
+        Ok(id)
+    }
+
+    /// Check that `session` names a live session and mark it active.
+    pub fn touch(&self, session: &str) -> Result<(), HttpRefusal> {
+        let mut sessions = self.sessions.lock().recover();
+        let entry = sessions
+            .get_mut(session)
+            .ok_or(HttpRefusal::SessionNotFound)?;
+        entry.last_seen = Instant::now();
+        Ok(())
+    }
+
+    /// Drop idle sessions, releasing toolbox subscriptions only they held.
+    async fn expire_idle(&self) {
+        let idle = Duration::from_secs(self.settings.session_idle_secs.max(1));
+        let expired: Vec<(String, Session)> = self
+            .sessions
+            .lock()
+            .recover()
+            .extract_if(|_, session| session.last_seen.elapsed() >= idle)
+            .collect();
+        for (id, session) in expired {
+            debug!(session = %id, "mcp http session expired");
+            self.release_subscriptions(session.subscriptions).await;
+        }
+    }
+
+    /// Drop a `resources/unsubscribe` from the session. Returns a local
+    /// response when the toolbox must not see the message because another
+    /// session still watches the URI.
+    fn unsubscribe(&self, session: &str, message: &Value) -> Option<String> {
+        if message.get("method").and_then(Value::as_str) != Some("resources/unsubscribe") {
+            return None;
+        }
+        let uri = message.pointer("/params/uri").and_then(Value::as_str)?;
+        let mut sessions = self.sessions.lock().recover();
+        if let Some(entry) = sessions.get_mut(session) {
+            entry.subscriptions.remove(uri);
+        }
+        let shared = sessions.values().any(|s| s.subscriptions.contains(uri));
+        let id = message.get("id")?;
+        shared.then(|| json!({ "jsonrpc": "2.0", "id": id, "result": {} }).to_string())
+    }
+
+    async fn release_subscriptions(&self, uris: BTreeSet<String>) {
+        for uri in uris {
+            let still_used = self
+                .sessions
+                .lock()
+                .recover()
+                .values()
+                .any(|s| s.subscriptions.contains(&uri));
+            if still_used {
+                continue;
+            }
+            let line = json!({
+                "jsonrpc": "2.0",
+                "id": format!("archon-release-{uri}"),
+                "method": "resources/unsubscribe",
+                "params": { "uri": uri },
+            })
+            .to_string();
+            if let Err(err) = self.dispatch(&line, "", None).await {
+                warn!(error = %err.message(), "failed to release MCP subscription");
+            }
+        }
+    }
+
+    async fn dispatch(
+        &self,
+        line: &str,
+        session: &str,
+        stream: Option<FrameSender>,
+    ) -> Result<Option<String>, HttpRefusal> {
+        let (reply, response) = oneshot::channel();
+        let sink = router(Arc::clone(&self.sessions), session.to_string(), stream);
+        self.worker
+            .send(Job {
+                line: line.to_string(),
+                sink,
+                reply,
+            })
+            .map_err(|_| HttpRefusal::Unavailable("MCP worker stopped".into()))?;
+        response
+            .await
+            .map_err(|_| HttpRefusal::Unavailable("MCP worker stopped".into()))
+    }
+}
+
+/// Route a toolbox notification to the sessions it concerns.
+fn router(
+    sessions: Sessions,
+    requester: String,
+    stream: Option<FrameSender>,
+) -> crate::mcp_server::NotificationSink {
+    Arc::new(move |frame: String| {
+        let Ok(value) = serde_json::from_str::<Value>(&frame) else {
+            return;
+        };
+        let method = value.get("method").and_then(Value::as_str).unwrap_or("");
+        let uri = value.pointer("/params/uri").and_then(Value::as_str);
+        if !method.starts_with("notifications/resources/") {
+            if let Some(stream) = &stream {
+                let _ = stream.send(frame);
+            }
+            return;
+        }
+        let sessions = sessions.lock().recover();
+        for (id, session) in sessions.iter() {
+            if uri.is_some_and(|uri| !session.subscriptions.contains(uri)) {
+                continue;
+            }
+            let target = match (&session.listener, &stream) {
+                (Some(listener), _) => Some(listener),
+                (None, Some(stream)) if *id == requester => Some(stream),
+                _ => None,
+            };
+            if let Some(target) = target {
+                let _ = target.send(frame.clone());
+            }
+        }
+    })
+}
+
+/// Whether a browser `Origin` is local (`localhost`, `127.0.0.1`, `[::1]`)
+/// or listed in `allowed_origins`.
+pub fn origin_allowed(origin: &str, allowed_origins: &[String]) -> bool {
+    let origin = origin.trim_end_matches('/');
+    if allowed_origins
+        .iter()
+        .any(|allowed| allowed.trim_end_matches('/').eq_ignore_ascii_case(origin))
+    {
+        return true;
+    }
+    url::Url::parse(origin)
+        .ok()
+        .and_then(|url| url.host_str().map(str::to_ascii_lowercase))
+        .is_some_and(|host| matches!(host.as_str(), "localhost" | "127.0.0.1" | "[::1]"))
+}
+
+/// Compare secrets without exiting at the first differing byte.
+pub fn constant_time_eq(a: &str, b: &str) -> bool {
+    a.len() == b.len()
+        && a.bytes()
+            .zip(b.bytes())
+            .fold(0u8, |acc, (x, y)| acc | (x ^ y))
+            == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai::AiBridge;
+    use crate::automation::AutomationOrchestrator;
+    use crate::browser::{BrowserDriver, PageObservation};
+    use crate::config::{AiSettings, AutomationSettings};
+    use crate::transcript::TranscriptStore;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use tokio::sync::mpsc::unbounded_channel;
+
+    /// Driver whose page text changes after every navigation.
+    struct CountingDriver {
+        navigations: Arc<AtomicUsize>,
+        observes: Arc<AtomicUsize>,
+    }
+
+    impl BrowserDriver for CountingDriver {
+        fn navigate(&self, _url: &str) -> Result<()> {
+            self.navigations.fetch_add(1, Ordering::SeqCst);
+            Ok(())
+        }
+        fn click(&self, _selector: &str) -> Result<()> {
+            Ok(())
+        }
+        fn type_text(&self, _selector: &str, _text: &str) -> Result<()> {
+            Ok(())
+        }
+        fn scroll(&self, _selector: Option<&str>) -> Result<()> {
+            Ok(())
+        }
+        fn extract(&self, _selector: &str) -> Result<String> {
+            Ok(String::new())
+        }
+        fn screenshot(&self) -> Result<String> {
+            Ok("/tmp/none.png".into())
+        }
+        fn observe(&self) -> Result<PageObservation> {
+            self.observes.fetch_add(1, Ordering::SeqCst);
+            Ok(PageObservation {
+                url: "https://example.test/".into(),
+                title: "Example".into(),
+                text: format!("visit {}", self.navigations.load(Ordering::SeqCst)),
+                interactive: Vec::new(),
+            })
+        }
+        fn current_url(&self) -> Result<String> {
+            Ok("https://example.test/".into())
+        }
+    }
+
+    fn hub(
+        settings: McpServerSettings,
+        driver_launches: Arc<AtomicUsize>,
+    ) -> (Arc<McpHttpHub>, tempfile::TempDir) {
+        observed_hub(settings, driver_launches, Arc::default())
+    }
+
+    /// Hub whose driver counts page observations in `observes`, with the
+    /// directory holding its transcripts (removed when dropped).
+    fn observed_hub(
+        settings: McpServerSettings,
+        driver_launches: Arc<AtomicUsize>,
+        observes: Arc<AtomicUsize>,
+    ) -> (Arc<McpHttpHub>, tempfile::TempDir) {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().to_path_buf();
+        let hub = McpHttpHub::start(settings, "s3cret".into(), move || {
+            let store = Arc::new(TranscriptStore::new(root).unwrap());
+            let bridge = Arc::new(AiBridge::from_settings(&AiSettings::default(), store));
+            let automation = AutomationSettings {
+                enabled: true,
+                require_confirmation: false,
+                ..AutomationSettings::default()
+            };
+            let orchestrator = Arc::new(AutomationOrchestrator::from_settings(automation, bridge));
+            let navigations = Arc::new(AtomicUsize::new(0));
+            BrowserToolbox::new(
+                orchestrator,
+                None,
+                None,
+                Box::new(move || {
+                    driver_launches.fetch_add(1, Ordering::SeqCst);
+                    Ok(Box::new(CountingDriver {
+                        navigations: Arc::clone(&navigations),
+                        observes: Arc::clone(&observes),
+                    }) as Box<dyn BrowserDriver>)
+                }),
+            )
+        })
+        .unwrap();
+        (hub, dir)
+    }
+
+    fn request(id: i64, method: &str, params: Value) -> String {
+        json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params }).to_string()
+    }
+
+    async fn initialize(hub: &McpHttpHub) -> String {
+        let reply = hub
+            .handle(None, &request(1, "initialize", json!({})), None)
+            .await
+            .unwrap();
+        assert!(reply.response.unwrap().contains("serverInfo"));
+        reply.session_id.expect("session opened")
+    }
+
+    #[test]
+    fn authorize_checks_token_and_origin() {
+        let settings = McpServerSettings {
+            allowed_origins: vec!["https://app.example".into()],
+            ..McpServerSettings::default()
+        };
+        let (hub, _dir) = hub(settings, Arc::default());
+        assert_eq!(hub.authorize(None, Some("Bearer s3cret")), Ok(()));
+        assert_eq!(
+            hub.authorize(None, Some("Bearer nope")),
+            Err(HttpRefusal::Unauthorized)
+        );
+        assert_eq!(hub.authorize(None, None), Err(HttpRefusal::Unauthorized));
+        assert!(
+            hub.authorize(Some("http://localhost:5173"), Some("Bearer s3cret"))
+                .is_ok()
+        );
+        assert!(
+            hub.authorize(Some("https://app.example/"), Some("Bearer s3cret"))
+                .is_ok()
+        );
+        let refusal = hub
+            .authorize(Some("https://evil.example"), Some("Bearer s3cret"))
+            .unwrap_err();
+        assert_eq!(refusal.status_code(), 403);
+        let frame: Value = serde_json::from_str(&refusal.to_jsonrpc(&json!(7))).unwrap();
+        assert_eq!(frame["id"], 7);
+        assert_eq!(frame["error"]["data"]["status"], 403);
+        assert!(frame.get("result").is_none());
+        assert!(hub.check_protocol_version(Some("2025-06-18")).is_ok());
+        assert!(hub.check_protocol_version(None).is_ok());
+        assert!(hub.check_protocol_version(Some("2024-11-05")).is_err());
+    }
+
+    #[tokio::test]
+    async fn sessions_share_one_browser_and_require_ids() {
+        let launches = Arc::new(AtomicUsize::new(0));
+        let (hub, _dir) = hub(McpServerSettings::default(), Arc::clone(&launches));
+        let a = initialize(&hub).await;
+        let b = initialize(&hub).await;
+        assert_ne!(a, b);
+        assert_eq!(hub.session_count(), 2);
+
+        let read = request(
+            2,
+            "tools/call",
+            json!({ "name": "read_page", "arguments": {} }),
+        );
+        for session in [&a, &b] {
+            let reply = hub.handle(Some(session), &read, None).await.unwrap();
+            assert!(reply.response.unwrap().contains("example.test"));
+            assert!(reply.session_id.is_none());
+        }
+        assert_eq!(launches.load(Ordering::SeqCst), 1, "one shared browser");
+
+        let missing = hub.handle(None, &read, None).await.unwrap_err();
+        assert_eq!(missing.status_code(), 400);
+        let unknown = hub.handle(Some("nope"), &read, None).await.unwrap_err();
+        assert_eq!(unknown, HttpRefusal::SessionNotFound);
+
+        let note = json!({ "jsonrpc": "2.0", "method": "notifications/initialized" }).to_string();
+        let reply = hub.handle(Some(&a), &note, None).await.unwrap();
+        assert!(reply.response.is_none());
+        assert!(hub.handle(Some(&a), "[]", None).await.is_err());
+
+        hub.close(&a).await.unwrap();
+        assert_eq!(
+            hub.handle(Some(&a), &read, None).await.unwrap_err(),
+            HttpRefusal::SessionNotFound
+        );
+
+        let (limited, _limited_dir) = self::hub(
+            McpServerSettings {
+                max_sessions: 1,
+                ..McpServerSettings::default()
+            },
+            Arc::default(),
+        );
+        initialize(&limited).await;
+        let full = limited
+            .handle(None, &request(1, "initialize", json!({})), None)
+            .await
+            .unwrap_err();
+        assert_eq!(full.status_code(), 503);
+    }
+
+    #[tokio::test]
+    async fn resource_updates_reach_only_subscribed_sessions() {
+        let (hub, _dir) = hub(McpServerSettings::default(), Arc::default());
+        let watcher = initialize(&hub).await;
+        let other = initialize(&hub).await;
+        let uri = "archon://page/text";
+
+        let (listener, mut events) = unbounded_channel();
+        hub.listen(&watcher, listener).unwrap();
+        let (other_listener, mut other_events) = unbounded_channel();
+        hub.listen(&other, other_listener).unwrap();
+
+        hub.handle(
+            Some(&watcher),
+            &request(2, "resources/subscribe", json!({ "uri": uri })),
+            None,
+        )
+        .await
+        .unwrap();
+        // The other session navigates; only the watcher is told.
+        let navigate = request(
+            3,
+            "tools/call",
+            json!({ "name": "navigate", "arguments": { "url": "https://example.test/next" } }),
+        );
+        hub.handle(Some(&other), &navigate, None).await.unwrap();
+        let frame: Value = serde_json::from_str(&events.try_recv().unwrap()).unwrap();
+        assert_eq!(frame["method"], "notifications/resources/updated");
+        assert_eq!(frame["params"]["uri"], uri);
+        assert!(other_events.try_recv().is_err());
+
+        // Both subscribe; one unsubscribing keeps the toolbox subscription.
+        hub.handle(
+            Some(&other),
+            &request(4, "resources/subscribe", json!({ "uri": uri })),
+            None,
+        )
+        .await
+        .unwrap();
+        let reply = hub
+            .handle(
+                Some(&other),
+                &request(5, "resources/unsubscribe", json!({ "uri": uri })),
+                None,
+            )
+            .await
+            .unwrap();
+        assert!(reply.response.unwrap().contains("\"result\":{}"));
+        hub.handle(Some(&other), &navigate, None).await.unwrap();
+        assert!(events.try_recv().is_ok());
+        assert!(other_events.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn expired_sessions_release_their_subscriptions() {
+        let observes = Arc::new(AtomicUsize::new(0));
+        let settings = McpServerSettings {
+            session_idle_secs: 1,
+            ..McpServerSettings::default()
+        };
+        let (hub, _dir) = observed_hub(settings, Arc::default(), Arc::clone(&observes));
+        let watcher = initialize(&hub).await;
+        hub.handle(
+            Some(&watcher),
+            &request(
+                2,
+                "resources/subscribe",
+                json!({ "uri": "archon://page/text" }),
+            ),
+            None,
+        )
+        .await
+        .unwrap();
+        hub.sessions
+            .lock()
+            .recover()
+            .get_mut(&watcher)
+            .unwrap()
+            .last_seen -= Duration::from_secs(2);
+
+        // Opening a session expires the watcher and unsubscribes the toolbox,
+        // so navigating no longer observes the page for it.
+        let other = initialize(&hub).await;
+        assert_eq!(hub.session_count(), 1);
+        let before = observes.load(Ordering::SeqCst);
+        let navigate = request(
+            3,
+            "tools/call",
+            json!({ "name": "navigate", "arguments": { "url": "https://example.test/next" } }),
+        );
+        hub.handle(Some(&other), &navigate, None).await.unwrap();
+        assert_eq!(observes.load(Ordering::SeqCst), before);
+    }
+}
@@ -8,30 +8,51 @@
 //! per line). **All diagnostics go to stderr** (via `tracing`); stdout carries
 //! protocol frames only.
 //!
+//! Besides tools, the server is a context source: `resources/*` exposes the
+//! current page (text and Markdown), screenshots taken this session,
+//! transcripts from the [`TranscriptStore`] and recipe files, with
+//! `resources/subscribe` notifications when a page-changing tool changed the
+//! page; `prompts/*` exposes built-in summarize and research prompts.
+//!
 //! Permission model (non-interactive — no human to confirm at the prompt):
-//! - Read-only tools (`read_page`, `screenshot`) are always allowed.
-//! - Mutating tools (`navigate`, `click`, `type`, and `run_task` with
-//!   `execute=true`) require `automation.enabled = true`; every mutating action
-//!   still flows through [`AutomationOrchestrator::execute_action_with`], which
-//!   applies the domain allow/block, rate-limit, sensitive/password guards.
+//! - Read-only tools (`read_page`, `screenshot`, `extract`, `wait_for`,
+//!   `list_tabs`, `get_cookies`, `pdf`, `summarize_page`) are always allowed.
+//! - Mutating tools (`navigate`, `click`, `type`, `scroll`, `select_option`,
+//!   `go_back`, `go_forward`, `switch_tab`, and `run_task` with
+//!   `execute=true`) require `automation.enabled = true`; page actions
+//!   still flow through [`AutomationOrchestrator::execute_action_with`], which
+//!   applies the domain allow/block, rate-limit, per-domain rule and
+//!   sensitive/password guards and writes the audit log. `go_back` and
+//!   `go_forward` leave a page on a disallowed domain, and `switch_tab` is
+//!   checked against the target tab's domain.
+//! - `evaluate` additionally requires `automation.allow_script_evaluation` and
+//!   a current http(s) page on an allowed domain.
 //! - Within `run_task`, High/Critical-risk steps are only auto-executed when
 //!   `automation.allow_unattended_high_risk = true` (mapped to the agent's
 //!   `auto_confirm`); otherwise they are previewed.
 
+use std::collections::{BTreeSet, VecDeque};
 use std::io::{BufRead, Write};
-use std::path::PathBuf;
-use std::sync::Arc;
+use std::path::{Path, PathBuf};
 use std::sync::atomic::AtomicBool;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
-use anyhow::Result;
+use anyhow::{Context, Result};
+use base64::Engine as _;
+use base64::engine::general_purpose::STANDARD as BASE64;
 use serde::{Deserialize, Serialize};
 use serde_json::{Value, json};
 use tracing::{debug, warn};
 
 use crate::ai::BlockingAiHttp;
-use crate::agent::BrowserAgent;
-use crate::automation::{AutomationOrchestrator, WebAction};
-use crate::browser::BrowserDriver;
+use crate::agent::{AgentStep, BrowserAgent};
+use crate::automation::{ActionResult, AutomationOrchestrator, WebAction, host_of, page_host};
+use crate::browser::{BrowserDriver, PageObservation, truncate};
+use crate::research::ResearchDepth;
+use crate::summarize::{SummarizeOrchestrator, SummarizeRequest, SummarizeStyle};
+use crate::sync_util::LockResultExt;
+use crate::transcript::TranscriptStore;
 
 /// MCP protocol version this server implements (echoed back when a client
 /// requests a specific version it shares).
@@ -44,10 +65,52 @@ const MAX_LINE_BYTES: usize = 4 * 1024 * 1024;
 /// Default `run_task` step budget when the caller omits `max_steps`.
 const DEFAULT_TASK_STEPS: usize = 8;
 
+/// Default and maximum `wait_for` timeouts (milliseconds).
+const DEFAULT_WAIT_MS: u64 = 5_000;
+const MAX_WAIT_MS: u64 = 30_000;
+
+/// Characters of an `evaluate` result returned to the client.
+const MAX_EVALUATE_CHARS: usize = 16_000;
+
+/// Placeholder for cookie values unless `automation.expose_cookie_values` is set.
+const REDACTED_COOKIE: &str = "[redacted]";
+
+/// Tools that can change the current page; page subscriptions are only
+/// checked after one of these runs.
+const PAGE_CHANGING_TOOLS: &[&str] = &[
+    "navigate",
+    "click",
+    "type",
+    "scroll",
+    "select_option",
+    "go_back",
+    "go_forward",
+    "switch_tab",
+    "evaluate",
+    "run_task",
+];
+
+/// Screenshots kept as `archon://screenshots/...` resources.
+const MAX_SCREENSHOT_RESOURCES: usize = 10;
+
+/// Transcripts listed by `resources/list` (all remain readable by URI).
+const MAX_LISTED_TRANSCRIPTS: usize = 50;
+
+const PAGE_TEXT_URI: &str = "archon://page/text";
+const PAGE_MARKDOWN_URI: &str = "archon://page/markdown";
+const SCREENSHOT_PREFIX: &str = "archon://screenshots/";
+const TRANSCRIPT_PREFIX: &str = "archon://transcripts/";
+const RECIPE_PREFIX: &str = "archon://recipes/";
+
 /// Factory that lazily produces a [`BrowserDriver`] on first use, so
 /// `initialize`/`tools/list` work with no browser present.
 pub type DriverFactory = Box<dyn Fn() -> Result<Box<dyn BrowserDriver>>>;
 
+/// Receives serialized notification frames as they are produced. Set by
+/// transports that can deliver them while a request is still running (see
+/// [`BrowserToolbox::set_notification_sink`]).
+pub type NotificationSink = Arc<dyn Fn(String) + Send + Sync>;
+
 // ---------------------------------------------------------------------------
 // JSON-RPC 2.0 wire types
 // ---------------------------------------------------------------------------
@@ -130,6 +193,17 @@ impl JsonRpcError {
     fn invalid_params(detail: impl Into<String>) -> Self {
         Self::new(-32602, format!("Invalid params: {}", detail.into()))
     }
+
+    fn internal(detail: impl Into<String>) -> Self {
+        Self::new(-32603, format!("Internal error: {}", detail.into()))
+    }
+
+    /// MCP's code for an unknown resource URI.
+    fn resource_not_found(uri: &str) -> Self {
+        let mut error = Self::new(-32002, "Resource not found");
+        error.data = Some(json!({ "uri": uri }));
+        error
+    }
 }
 
 // ---------------------------------------------------------------------------
@@ -147,6 +221,23 @@ pub struct BrowserToolbox {
     default_provider: Option<String>,
     driver_factory: DriverFactory,
     driver: Option<Box<dyn BrowserDriver>>,
+    transcripts: Option<Arc<TranscriptStore>>,
+    recipe_dir: Option<PathBuf>,
+    summarizer: Option<SummarizeOrchestrator>,
+    /// Screenshot paths from this session, oldest first.
+    screenshots: VecDeque<PathBuf>,
+    /// Resource URIs the client subscribed to.
+    subscriptions: BTreeSet<String>,
+    /// Fingerprint of the page last seen by a subscription check.
+    page_fingerprint: Option<String>,
+    /// Whether a page-changing tool ran since the last subscription check.
+    page_touched: bool,
+    /// Notification frames queued for the transport.
+    notifications: Arc<Mutex<Vec<String>>>,
+    /// Live notification delivery; frames are queued when unset.
+    notification_sink: Option<NotificationSink>,
+    /// `_meta.progressToken` of the `tools/call` in progress.
+    progress_token: Option<Value>,
 }
 
 impl BrowserToolbox {
@@ -163,9 +254,65 @@ impl BrowserToolbox {
             default_provider,
             driver_factory,
             driver: None,
+            transcripts: None,
+            recipe_dir: None,
+            summarizer: None,
+            screenshots: VecDeque::new(),
+            subscriptions: BTreeSet::new(),
+            page_fingerprint: None,
+            page_touched: false,
+            notifications: Arc::default(),
+            notification_sink: None,
+            progress_token: None,
         }
     }
 
+    /// Expose conversations from `store` as `archon://transcripts/<id>`.
+    pub fn with_transcripts(mut self, store: Arc<TranscriptStore>) -> Self {
+        self.transcripts = Some(store);
+        self
+    }
+
+    /// Expose `*.json` recipes in `dir` as `archon://recipes/<name>`.
+    pub fn with_recipe_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.recipe_dir = Some(dir.into());
+        self
+    }
+
+    /// Enable the `summarize_page` tool.
+    pub fn with_summarizer(mut self, summarizer: SummarizeOrchestrator) -> Self {
+        self.summarizer = Some(summarizer);
+        self
+    }
+
+    /// Drain notification frames (e.g. `notifications/resources/updated`)
+    /// queued while handling requests. Transports write them after the
+    /// response that produced them.
+    pub fn take_notifications(&mut self) -> Vec<String> {
+        std::mem::take(&mut *self.notifications.lock().recover())
+    }
+
+    /// Deliver notifications through `sink` as they happen instead of
+    /// queueing them; `None` restores queueing. Progress notifications for
+    /// long `run_task` calls are only useful with a sink.
+    pub fn set_notification_sink(&mut self, sink: Option<NotificationSink>) {
+        self.notification_sink = sink;
+    }
+
+    /// Whether the client subscribed to any resource.
+    pub fn has_subscriptions(&self) -> bool {
+        !self.subscriptions.is_empty()
+    }
+
+    /// The current delivery path for notification frames.
+    fn sink(&self) -> NotificationSink {
+        if let Some(sink) = &self.notification_sink {
+            return Arc::clone(sink);
+        }
+        let queue = Arc::clone(&self.notifications);
+        Arc::new(move |frame| queue.lock().recover().push(frame))
+    }
+
     fn automation_enabled(&self) -> bool {
         self.orchestrator.settings().enabled
     }
@@ -205,7 +352,9 @@ impl BrowserToolbox {
             }
         };
 
-        self.dispatch(request).map(encode)
+        let response = self.dispatch(request).map(encode);
+        self.check_subscriptions();
+        response
     }
 
     fn dispatch(&mut self, request: JsonRpcRequest) -> Option<JsonRpcResponse> {
@@ -227,6 +376,27 @@ impl BrowserToolbox {
                 Ok(result) => JsonRpcResponse::success(id, result),
                 Err(err) => JsonRpcResponse::failure(id, err),
             },
+            "resources/list" => JsonRpcResponse::success(id, self.resource_list()),
+            "resources/templates/list" => {
+                JsonRpcResponse::success(id, json!({ "resourceTemplates": resource_templates() }))
+            }
+            "resources/read" => match self.handle_resources_read(params) {
+                Ok(result) => JsonRpcResponse::success(id, result),
+                Err(err) => JsonRpcResponse::failure(id, err),
+            },
+            "resources/subscribe" | "resources/unsubscribe" => {
+                match self.handle_subscription(&method, params) {
+                    Ok(()) => JsonRpcResponse::success(id, json!({})),
+                    Err(err) => JsonRpcResponse::failure(id, err),
+                }
+            }
+            "prompts/list" => {
+                JsonRpcResponse::success(id, json!({ "prompts": prompt_definitions() }))
+            }
+            "prompts/get" => match self.handle_prompts_get(params) {
+                Ok(result) => JsonRpcResponse::success(id, result),
+                Err(err) => JsonRpcResponse::failure(id, err),
+            },
             other => JsonRpcResponse::failure(id, JsonRpcError::method_not_found(other)),
         };
         Some(response)
@@ -246,6 +416,11 @@ impl BrowserToolbox {
             .get("arguments")
             .cloned()
             .unwrap_or_else(|| json!({}));
+        self.progress_token = params
+            .pointer("/_meta/progressToken")
+            .filter(|token| token.is_string() || token.is_number())
+            .cloned();
+        self.page_touched |= PAGE_CHANGING_TOOLS.contains(&name.as_str());
 
         let result = match name.as_str() {
             "read_page" => self.tool_read_page(),
@@ -253,10 +428,23 @@ impl BrowserToolbox {
             "navigate" => self.tool_navigate(&args),
             "click" => self.tool_click(&args),
             "type" => self.tool_type(&args),
+            "scroll" => self.tool_scroll(&args),
+            "extract" => self.tool_extract(&args),
+            "wait_for" => self.tool_wait_for(&args),
+            "select_option" => self.tool_select_option(&args),
+            "go_back" => self.tool_history(false),
+            "go_forward" => self.tool_history(true),
+            "list_tabs" => self.tool_list_tabs(),
+            "switch_tab" => self.tool_switch_tab(&args),
+            "evaluate" => self.tool_evaluate(&args),
+            "get_cookies" => self.tool_get_cookies(&args),
+            "pdf" => self.tool_pdf(),
+            "summarize_page" => self.tool_summarize_page(&args),
             "run_task" => self.tool_run_task(&args),
             other => Ok(tool_error(format!("unknown tool: {other}"))),
         };
 
+        self.progress_token = None;
         // Tool plumbing errors (e.g. browser launch failure) become tool errors.
         Ok(result.unwrap_or_else(|err| tool_error(format!("{err:#}"))))
     }
@@ -273,8 +461,19 @@ impl BrowserToolbox {
     fn tool_screenshot(&mut self) -> Result<Value> {
         self.ensure_driver()?;
         let driver = self.driver.as_deref().expect("driver initialised");
-        let path = driver.screenshot()?;
-        Ok(tool_text(format!("Screenshot written to {path}")))
+        let path = PathBuf::from(driver.screenshot()?);
+        let png = driver
+            .read_artifact(&path)
+            .with_context(|| format!("failed to read screenshot {}", path.display()))?;
+        let uri = format!("{SCREENSHOT_PREFIX}{}", file_name(&path));
+        self.remember_screenshot(path);
+        Ok(json!({
+            "content": [
+                { "type": "image", "data": BASE64.encode(png), "mimeType": "image/png" },
+                { "type": "text", "text": format!("Screenshot saved as {uri}") },
+            ],
+            "isError": false,
+        }))
     }
 
     fn tool_navigate(&mut self, args: &Value) -> Result<Value> {
@@ -316,6 +515,207 @@ impl BrowserToolbox {
         self.execute_mutation(action)
     }
 
+    fn tool_scroll(&mut self, args: &Value) -> Result<Value> {
+        if let Some(refusal) = self.mutation_guard() {
+            return Ok(refusal);
+        }
+        let mut action = WebAction::scroll(arg_str(args, "selector").map(str::to_string));
+        action.domain = self.current_host();
+        self.execute_mutation(action)
+    }
+
+    fn tool_extract(&mut self, args: &Value) -> Result<Value> {
+        let Some(selector) = arg_str(args, "selector") else {
+            return Ok(tool_error("extract requires a `selector` argument"));
+        };
+        let all = args.get("all").and_then(Value::as_bool).unwrap_or(false);
+        self.ensure_driver()?;
+        let driver = self.driver.as_deref().expect("driver initialised");
+        if all {
+            let texts = driver.extract_all(selector)?;
+            Ok(tool_text(serde_json::to_string_pretty(&texts)?))
+        } else {
+            Ok(tool_text(driver.extract(selector)?))
+        }
+    }
+
+    fn tool_wait_for(&mut self, args: &Value) -> Result<Value> {
+        let Some(selector) = arg_str(args, "selector") else {
+            return Ok(tool_error("wait_for requires a `selector` argument"));
+        };
+        let timeout_ms = args
+            .get("timeout_ms")
+            .and_then(Value::as_u64)
+            .unwrap_or(DEFAULT_WAIT_MS)
+            .min(MAX_WAIT_MS);
+        self.ensure_driver()?;
+        let driver = self.driver.as_deref().expect("driver initialised");
+        match driver.wait_for(selector, Duration::from_millis(timeout_ms)) {
+            Ok(()) => Ok(tool_text(format!("{selector} is present"))),
+            Err(err) => Ok(tool_error(format!("{err:#}"))),
+        }
+    }
+
+    fn tool_select_option(&mut self, args: &Value) -> Result<Value> {
+        let Some(selector) = arg_str(args, "selector") else {
+            return Ok(tool_error("select_option requires a `selector` argument"));
+        };
+        let Some(value) = arg_str(args, "value") else {
+            return Ok(tool_error("select_option requires a `value` argument"));
+        };
+        if let Some(refusal) = self.mutation_guard() {
+            return Ok(refusal);
+        }
+        let mut action = WebAction::select(selector, value);
+        action.domain = self.current_host();
+        self.execute_mutation(action)
+    }
+
+    fn tool_history(&mut self, forward: bool) -> Result<Value> {
+        if let Some(refusal) = self.mutation_guard() {
+            return Ok(refusal);
+        }
+        let mut action = WebAction::history(forward);
+        action.domain = self.current_host();
+        self.execute_mutation(action)
+    }
+
+    fn tool_list_tabs(&mut self) -> Result<Value> {
+        self.ensure_driver()?;
+        let driver = self.driver.as_deref().expect("driver initialised");
+        let tabs = driver.tabs()?;
+        Ok(tool_text(serde_json::to_string_pretty(&tabs)?))
+    }
+
+    fn tool_switch_tab(&mut self, args: &Value) -> Result<Value> {
+        let Some(index) = args.get("index").and_then(Value::as_u64) else {
+            return Ok(tool_error("switch_tab requires an `index` argument"));
+        };
+        if let Some(refusal) = self.mutation_guard() {
+            return Ok(refusal);
+        }
+        self.ensure_driver()?;
+        let driver = self.driver.as_deref().expect("driver initialised");
+        // The domain rules apply to the tab being switched to.
+        let mut action = WebAction::switch_tab(index as usize);
+        action.domain = driver
+            .tabs()?
+            .into_iter()
+            .find(|tab| tab.index == index as usize)
+            .and_then(|tab| page_host(&tab.url));
+        self.execute_mutation(action)
+    }
+
+    fn tool_evaluate(&mut self, args: &Value) -> Result<Value> {
+        let Some(expression) = arg_str(args, "expression") else {
+            return Ok(tool_error("evaluate requires an `expression` argument"));
+        };
+        if let Some(refusal) = self.mutation_guard() {
+            return Ok(refusal);
+        }
+        if !self.orchestrator.settings().allow_script_evaluation {
+            return Ok(tool_error(
+                "evaluate runs arbitrary JavaScript and requires \
+                 automation.allow_script_evaluation = true in config",
+            ));
+        }
+        self.ensure_driver()?;
+        // Pages without a host (about:blank, data:, file:) cannot be checked
+        // against the domain lists, so they are refused too.
+        match self.current_host() {
+            Some(host) if self.orchestrator.is_domain_allowed(&host) => {}
+            Some(host) => {
+                return Ok(tool_error(format!("automation is not allowed on '{host}'")));
+            }
+            None => {
+                return Ok(tool_error(
+                    "evaluate requires a current page on an allowed domain",
+                ));
+            }
+        }
+        let mut action = WebAction::evaluate(expression);
+        action.domain = self.current_host();
+        let result = self.execute_action(&action)?;
+        if result.success {
+            Ok(tool_text(truncate(
+                result.data.as_deref().unwrap_or("null"),
+                MAX_EVALUATE_CHARS,
+            )))
+        } else {
+            Ok(tool_error(
+                result.error.unwrap_or_else(|| "action failed".to_string()),
+            ))
+        }
+    }
+
+    fn tool_get_cookies(&mut self, args: &Value) -> Result<Value> {
+        let name = arg_str(args, "name");
+        let expose_values = self.orchestrator.settings().expose_cookie_values;
+        self.ensure_driver()?;
+        let driver = self.driver.as_deref().expect("driver initialised");
+        let snapshot = driver.storage_snapshot()?;
+        let host = host_of(&snapshot.url).unwrap_or_default();
+        // Only the current page's cookies, never the whole jar.
+        let cookies: Vec<_> = snapshot
+            .cookies
+            .into_iter()
+            .filter(|cookie| cookie_matches_host(&cookie.domain, &host))
+            .filter(|cookie| name.is_none_or(|name| cookie.name == name))
+            .map(|mut cookie| {
+                if !expose_values {
+                    cookie.value = REDACTED_COOKIE.to_string();
+                }
+                cookie
+            })
+            .collect();
+        Ok(tool_text(serde_json::to_string_pretty(&cookies)?))
+    }
+
+    fn tool_pdf(&mut self) -> Result<Value> {
+        self.ensure_driver()?;
+        let driver = self.driver.as_deref().expect("driver initialised");
+        let path = driver.print_pdf()?;
+        let pdf = driver
+            .read_artifact(Path::new(&path))
+            .with_context(|| format!("failed to read PDF {path}"))?;
+        let uri = url::Url::from_file_path(&path)
+            .map(String::from)
+            .unwrap_or_else(|()| format!("file://{path}"));
+        Ok(json!({
+            "content": [
+                { "type": "text", "text": format!("PDF written to {path}") },
+                {
+                    "type": "resource",
+                    "resource": { "uri": uri, "mimeType": "application/pdf", "blob": BASE64.encode(pdf) },
+                },
+            ],
+            "isError": false,
+        }))
+    }
+
+    fn tool_summarize_page(&mut self, args: &Value) -> Result<Value> {
+        let Some(summarizer) = self.summarizer.clone() else {
+            return Ok(tool_error("summarization is not available in this server"));
+        };
+        let style = match arg_str(args, "style") {
+            Some(raw) => match raw.parse::<SummarizeStyle>() {
+                Ok(style) => style,
+                Err(()) => return Ok(tool_error(format!("unknown summarize style '{raw}'"))),
+            },
+            None => SummarizeStyle::default(),
+        };
+        let observation = self.observe_page()?;
+        let mut request = SummarizeRequest::new(observation.text)
+            .with_style(style)
+            .with_url(observation.url)
+            .with_title(observation.title);
+        if let Some(provider) = &self.default_provider {
+            request = request.with_provider(provider.clone());
+        }
+        let response = summarizer.summarize(&request)?;
+        Ok(tool_text(response.summary))
+    }
+
     fn tool_run_task(&mut self, args: &Value) -> Result<Value> {
         let Some(goal) = arg_str(args, "goal") else {
             return Ok(tool_error("run_task requires a `goal` argument"));
@@ -338,13 +738,47 @@ impl BrowserToolbox {
         self.ensure_driver()?;
         let driver = self.driver.as_deref().expect("driver initialised");
 
-        let agent = BrowserAgent::new(
+        let mut agent = BrowserAgent::new(
             Arc::clone(&self.orchestrator),
             max_steps,
             execute,
             self.allow_unattended_high_risk(),
             self.transcript_dir.clone(),
+        )
+        .with_encryption(
+            self.transcripts
+                .as_ref()
+                .and_then(|store| store.cipher().cloned()),
         );
+        if let Some(token) = self.progress_token.clone() {
+            // One progress notification per recorded step.
+            let sink = self.sink();
+            agent = agent.with_step_observer(Box::new(move |step: &AgentStep| {
+                let message = format!(
+                    "step {}: {:?}{} ({})",
+                    step.index,
+                    step.action.action_type,
+                    step.action
+                        .selector
+                        .as_deref()
+                        .or(step.action.value.as_deref())
+                        .map(|target| format!(" {target}"))
+                        .unwrap_or_default(),
+                    if step.result.success { "ok" } else { "failed" },
+                );
+                let frame = json!({
+                    "jsonrpc": "2.0",
+                    "method": "notifications/progress",
+                    "params": {
+                        "progressToken": token,
+                        "progress": step.index,
+                        "total": max_steps,
+                        "message": message,
+                    },
+                });
+                sink(frame.to_string());
+            }));
+        }
         let http = BlockingAiHttp::default();
         let cancel = AtomicBool::new(false);
         let outcome = agent.run(
@@ -387,17 +821,14 @@ impl BrowserToolbox {
         } else {
             Some(tool_error(
                 "this action mutates the page and requires automation.enabled = true in config; \
-                 read_page and screenshot remain available read-only",
+                 read-only tools such as read_page, extract and screenshot remain available",
             ))
         }
     }
 
     /// Run a validated mutating action against the live driver.
     fn execute_mutation(&mut self, action: WebAction) -> Result<Value> {
-        self.ensure_driver()?;
-        let driver = self.driver.as_deref().expect("driver initialised");
-        let orchestrator = &self.orchestrator;
-        let result = orchestrator.execute_action_with(&action, driver)?;
+        let result = self.execute_action(&action)?;
         if result.success {
             Ok(tool_text(
                 result.data.unwrap_or_else(|| "ok".to_string()),
@@ -411,10 +842,296 @@ impl BrowserToolbox {
         }
     }
 
+    /// Run `action` through the orchestrator, which applies the domain lists,
+    /// rate limits and rules and writes the audit log.
+    fn execute_action(&mut self, action: &WebAction) -> Result<ActionResult> {
+        self.ensure_driver()?;
+        let driver = self.driver.as_deref().expect("driver initialised");
+        self.orchestrator.execute_action_with(action, driver)
+    }
+
+    /// Host of the current http(s) page; `None` for about:, data:, file: and
+    /// other host-less pages.
     fn current_host(&mut self) -> Option<String> {
         self.ensure_driver().ok()?;
         let driver = self.driver.as_deref()?;
-        driver.current_url().ok().and_then(|url| host_of(&url))
+        driver.current_url().ok().as_deref().and_then(page_host)
+    }
+
+    fn observe_page(&mut self) -> Result<PageObservation> {
+        self.ensure_driver()?;
+        let driver = self.driver.as_deref().expect("driver initialised");
+        driver.observe()
+    }
+
+    fn remember_screenshot(&mut self, path: PathBuf) {
+        self.screenshots.retain(|known| known != &path);
+        self.screenshots.push_back(path);
+        while self.screenshots.len() > MAX_SCREENSHOT_RESOURCES {
+            self.screenshots.pop_front();
+        }
+        self.notify("notifications/resources/list_changed", json!({}));
+    }
+
+    fn notify(&mut self, method: &str, params: Value) {
+        let frame = json!({ "jsonrpc": "2.0", "method": method, "params": params });
+        (self.sink())(frame.to_string());
+    }
+
+    // ---- resources --------------------------------------------------------
+
+    fn resource_list(&self) -> Value {
+        let mut resources = vec![
+            json!({
+                "uri": PAGE_TEXT_URI,
+                "name": "Current page (text)",
+                "description": "URL, title, interactive elements and visible text of the current tab.",
+                "mimeType": "text/plain",
+            }),
+            json!({
+                "uri": PAGE_MARKDOWN_URI,
+                "name": "Current page (Markdown)",
+                "description": "The current tab rendered as a Markdown document.",
+                "mimeType": "text/markdown",
+            }),
+        ];
+        for path in self.screenshots.iter().rev() {
+            let name = file_name(path);
+            resources.push(json!({
+                "uri": format!("{SCREENSHOT_PREFIX}{name}"),
+                "name": format!("Screenshot {name}"),
+                "mimeType": "image/png",
+            }));
+        }
+        if let Some(store) = &self.transcripts {
+            match store.list() {
+                Ok(summaries) => {
+                    for summary in summaries.into_iter().take(MAX_LISTED_TRANSCRIPTS) {
+                        resources.push(json!({
+                            "uri": format!("{TRANSCRIPT_PREFIX}{}", summary.id),
+                            "name": summary.title,
+                            "description": format!(
+                                "{} message(s), updated {}",
+                                summary.message_count,
+                                summary.updated_at.format("%Y-%m-%d %H:%M UTC")
+                            ),
+                            "mimeType": "text/markdown",
+                        }));
+                    }
+                }
+                Err(err) => warn!(error = %err, "mcp: failed to list transcripts"),
+            }
+        }
+        for (name, _) in self.recipe_files() {
+            resources.push(json!({
+                "uri": format!("{RECIPE_PREFIX}{name}"),
+                "name": format!("Recipe {name}"),
+                "mimeType": "application/json",
+            }));
+        }
+        json!({ "resources": resources })
+    }
+
+    /// `(name, path)` of each `*.json` recipe in the recipe directory.
+    fn recipe_files(&self) -> Vec<(String, PathBuf)> {
+        let Some(dir) = &self.recipe_dir else {
+            return Vec::new();
+        };
+        let Ok(entries) = std::fs::read_dir(dir) else {
+            return Vec::new();
+        };
+        let mut recipes: Vec<(String, PathBuf)> = entries
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+            .filter_map(|path| Some((path.file_stem()?.to_str()?.to_string(), path)))
+            .collect();
+        recipes.sort();
+        recipes
+    }
+
+    fn handle_resources_read(&mut self, params: Option<Value>) -> Result<Value, JsonRpcError> {
+        let uri = params
+            .as_ref()
+            .and_then(|p| p.get("uri"))
+            .and_then(Value::as_str)
+            .ok_or_else(|| JsonRpcError::invalid_params("missing uri"))?
+            .to_string();
+        let content = self.read_resource(&uri)?;
+        Ok(json!({ "contents": [content] }))
+    }
+
+    fn read_resource(&mut self, uri: &str) -> Result<Value, JsonRpcError> {
+        let internal = |err: anyhow::Error| JsonRpcError::internal(format!("{err:#}"));
+        let text = |mime: &str, text: String| json!({ "uri": uri, "mimeType": mime, "text": text });
+
+        if uri == PAGE_TEXT_URI || uri == PAGE_MARKDOWN_URI {
+            let observation = self.observe_page().map_err(internal)?;
+            self.page_fingerprint = Some(page_fingerprint(&observation));
+            return Ok(if uri == PAGE_TEXT_URI {
+                text("text/plain", observation.render_for_prompt())
+            } else {
+                text("text/markdown", observation.render_markdown())
+            });
+        }
+        if let Some(name) = uri.strip_prefix(SCREENSHOT_PREFIX) {
+            let path = self
+                .screenshots
+                .iter()
+                .find(|path| file_name(path) == name)
+                .ok_or_else(|| JsonRpcError::resource_not_found(uri))?;
+            let bytes = match self.driver.as_deref() {
+                Some(driver) => driver.read_artifact(path),
+                None => crate::transcript::read_sealed(
+                    path,
+                    self.transcripts.as_ref().and_then(|store| store.cipher()),
+                ),
+            }
+            .with_context(|| format!("failed to read screenshot {}", path.display()))
+            .map_err(internal)?;
+            return Ok(
+                json!({ "uri": uri, "mimeType": "image/png", "blob": BASE64.encode(bytes) }),
+            );
+        }
+        if let Some(id) = uri.strip_prefix(TRANSCRIPT_PREFIX) {
+            let store = self
+                .transcripts
+                .as_ref()
+                .ok_or_else(|| JsonRpcError::resource_not_found(uri))?;
+            let id =
+                uuid::Uuid::parse_str(id).map_err(|_| JsonRpcError::resource_not_found(uri))?;
+            let markdown = store
+                .load_markdown(id)
+                .map_err(|_| JsonRpcError::resource_not_found(uri))?;
+            return Ok(text("text/markdown", markdown));
+        }
+        if let Some(name) = uri.strip_prefix(RECIPE_PREFIX) {
+            let (_, path) = self
+                .recipe_files()
+                .into_iter()
+                .find(|(recipe, _)| recipe == name)
+                .ok_or_else(|| JsonRpcError::resource_not_found(uri))?;
+            let raw = std::fs::read_to_string(&path)
+                .with_context(|| format!("failed to read recipe {}", path.display()))
+                .map_err(internal)?;
+            return Ok(text("application/json", raw));
+        }
+        Err(JsonRpcError::resource_not_found(uri))
+    }
+
+    fn handle_subscription(
+        &mut self,
+        method: &str,
+        params: Option<Value>,
+    ) -> Result<(), JsonRpcError> {
+        let uri = params
+            .as_ref()
+            .and_then(|p| p.get("uri"))
+            .and_then(Value::as_str)
+            .ok_or_else(|| JsonRpcError::invalid_params("missing uri"))?;
+        if method == "resources/unsubscribe" {
+            self.subscriptions.remove(uri);
+            return Ok(());
+        }
+        if uri != PAGE_TEXT_URI && uri != PAGE_MARKDOWN_URI {
+            return Err(JsonRpcError::invalid_params(format!(
+                "only {PAGE_TEXT_URI} and {PAGE_MARKDOWN_URI} support subscriptions"
+            )));
+        }
+        self.subscriptions.insert(uri.to_string());
+        if self.page_fingerprint.is_none() {
+            let observation = self
+                .observe_page()
+                .map_err(|err| JsonRpcError::internal(format!("{err:#}")))?;
+            self.page_fingerprint = Some(page_fingerprint(&observation));
+        }
+        Ok(())
+    }
+
+    /// Queue `notifications/resources/updated` for subscribed page resources
+    /// when the page's URL, title or text changed since the last check.
+    /// Notifications are request-driven: the page is only observed after a
+    /// page-changing tool call ([`PAGE_CHANGING_TOOLS`]), so changes made in
+    /// an attached tab are not reported until such a call.
+    fn check_subscriptions(&mut self) {
+        if !std::mem::take(&mut self.page_touched)
+            || self.subscriptions.is_empty()
+            || self.driver.is_none()
+        {
+            return;
+        }
+        let fingerprint = match self.observe_page() {
+            Ok(observation) => page_fingerprint(&observation),
+            Err(err) => {
+                debug!(error = %err, "mcp: page check failed");
+                return;
+            }
+        };
+        let previous = self.page_fingerprint.replace(fingerprint.clone());
+        if previous.is_some_and(|previous| previous != fingerprint) {
+            let uris: Vec<String> = self.subscriptions.iter().cloned().collect();
+            for uri in uris {
+                self.notify("notifications/resources/updated", json!({ "uri": uri }));
+            }
+        }
+    }
+
+    // ---- prompts ----------------------------------------------------------
+
+    fn handle_prompts_get(&mut self, params: Option<Value>) -> Result<Value, JsonRpcError> {
+        let params = params.ok_or_else(|| JsonRpcError::invalid_params("missing params"))?;
+        let name = params
+            .get("name")
+            .and_then(Value::as_str)
+            .ok_or_else(|| JsonRpcError::invalid_params("missing prompt name"))?;
+        let args = params
+            .get("arguments")
+            .cloned()
+            .unwrap_or_else(|| json!({}));
+        match name {
+            "summarize_page" => {
+                let style = match arg_str(&args, "style") {
+                    Some(raw) => raw.parse::<SummarizeStyle>().map_err(|()| {
+                        JsonRpcError::invalid_params(format!("unknown summarize style '{raw}'"))
+                    })?,
+                    None => SummarizeStyle::default(),
+                };
+                let page = self.read_resource(PAGE_MARKDOWN_URI)?;
+                Ok(json!({
+                    "description": "Summarize the current page",
+                    "messages": [
+                        {
+                            "role": "user",
+                            "content": { "type": "text", "text": style.system_prompt() },
+                        },
+                        {
+                            "role": "user",
+                            "content": { "type": "resource", "resource": page },
+                        },
+                    ],
+                }))
+            }
+            "research" => {
+                let question = arg_str(&args, "question").ok_or_else(|| {
+                    JsonRpcError::invalid_params("research requires a `question` argument")
+                })?;
+                let depth = match arg_str(&args, "depth") {
+                    Some(raw) => raw.parse::<ResearchDepth>().map_err(|()| {
+                        JsonRpcError::invalid_params(format!("unknown research depth '{raw}'"))
+                    })?,
+                    None => ResearchDepth::default(),
+                };
+                Ok(json!({
+                    "description": "Research a question with the Archon browser",
+                    "messages": [{
+                        "role": "user",
+                        "content": { "type": "text", "text": research_prompt(question, depth) },
+                    }],
+                }))
+            }
+            other => Err(JsonRpcError::invalid_params(format!(
+                "unknown prompt: {other}"
+            ))),
+        }
     }
 }
 
@@ -455,6 +1172,9 @@ pub fn run_stdio<R: BufRead, W: Write>(
         if let Some(response) = toolbox.handle_line(&line) {
             write_frame(&mut writer, &response)?;
         }
+        for notification in toolbox.take_notifications() {
+            write_frame(&mut writer, &notification)?;
+        }
     }
     Ok(())
 }
@@ -486,7 +1206,11 @@ fn handle_initialize(params: Option<&Value>) -> Value {
         .to_string();
     json!({
         "protocolVersion": protocol_version,
-        "capabilities": { "tools": { "listChanged": false } },
+        "capabilities": {
+            "tools": { "listChanged": false },
+            "resources": { "subscribe": true, "listChanged": true },
+            "prompts": { "listChanged": false },
+        },
         "serverInfo": { "name": "archon", "version": env!("CARGO_PKG_VERSION") },
     })
 }
@@ -512,26 +1236,94 @@ fn arg_str<'a>(args: &'a Value, key: &str) -> Option<&'a str> {
         .filter(|s| !s.is_empty())
 }
 
-/// Extract the host portion of a URL without pulling in the `url` crate. Used
-/// only to populate `WebAction::domain` for the allow/block guard, which does a
-/// case-insensitive `contains` match.
-fn host_of(url: &str) -> Option<String> {
-    let without_scheme = url.split("://").nth(1).unwrap_or(url);
-    let host = without_scheme
-        .split('/')
-        .next()
-        .unwrap_or("")
-        .split('@')
-        .next_back()
-        .unwrap_or("")
-        .split(':')
-        .next()
-        .unwrap_or("");
-    if host.is_empty() {
-        None
-    } else {
-        Some(host.to_lowercase())
+/// Whether a cookie set for `domain` is sent to `host`.
+fn cookie_matches_host(domain: &str, host: &str) -> bool {
+    let domain = domain.trim_start_matches('.').to_ascii_lowercase();
+    let host = host.to_ascii_lowercase();
+    !domain.is_empty()
+        && (host == domain
+            || host
+                .strip_suffix(&domain)
+                .is_some_and(|prefix| prefix.ends_with('.')))
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
+
+/// Cheap change detector for subscribed page resources.
+fn page_fingerprint(observation: &PageObservation) -> String {
+    use sha2::{Digest, Sha256};
+    let mut hasher = Sha256::new();
+    for part in [&observation.url, &observation.title, &observation.text] {
+        hasher.update(part.as_bytes());
+        hasher.update([0]);
     }
+    hex::encode(hasher.finalize())
+}
+
+/// Instructions for the `research` prompt: the client drives Archon's tools
+/// and reports with the same structure as [`crate::research`] syntheses.
+fn research_prompt(question: &str, depth: ResearchDepth) -> String {
+    format!(
+        "You are a research assistant with access to the Archon browser tools. \
+         Research the question below by navigating to and reading relevant pages \
+         (use `navigate` and `read_page`, or the archon://page resources). Consult \
+         up to {sources} sources over about {iterations} round(s) of searching, \
+         cross-checking claims between sources.\n\n\
+         Research Question: {question}\n\n\
+         Answer in this format:\n\
+         SUMMARY: <2-3 sentence answer>\n\
+         FINDINGS:\n\
+         1. [confidence:high/medium/low] <finding> [source citations]\n\
+         SOURCES:\n\
+         [1] <title> - <url>\n\
+         RELATED QUESTIONS:\n\
+         - <question>\n",
+        sources = depth.max_sources(),
+        iterations = depth.iterations(),
+    )
+}
+
+/// URI templates advertised by `resources/templates/list`.
+fn resource_templates() -> Value {
+    json!([
+        {
+            "uriTemplate": format!("{TRANSCRIPT_PREFIX}{{id}}"),
+            "name": "Transcript",
+            "description": "A conversation transcript as Markdown, by UUID.",
+            "mimeType": "text/markdown"
+        },
+        {
+            "uriTemplate": format!("{RECIPE_PREFIX}{{name}}"),
+            "name": "Recipe",
+            "description": "An automation recipe file, by name.",
+            "mimeType": "application/json"
+        }
+    ])
+}
+
+/// Static MCP prompt catalogue advertised by `prompts/list`.
+fn prompt_definitions() -> Value {
+    json!([
+        {
+            "name": "summarize_page",
+            "description": "Summarize the current page. Embeds the page as Markdown with Archon's summarization instructions.",
+            "arguments": [
+                { "name": "style", "description": "bullets (default), paragraph, key-points, executive, technical, eli5 or outline.", "required": false }
+            ]
+        },
+        {
+            "name": "research",
+            "description": "Research a question by browsing with Archon's tools and answer with cited findings.",
+            "arguments": [
+                { "name": "question", "description": "The research question.", "required": true },
+                { "name": "depth", "description": "quick, standard (default), deep or exhaustive.", "required": false }
+            ]
+        }
+    ])
 }
 
 /// Static MCP tool catalogue advertised by `tools/list`.
@@ -585,13 +1377,145 @@ fn tool_definitions() -> Value {
         },
         {
             "name": "screenshot",
-            "description": "Capture a PNG screenshot of the current page and return its file path. Read-only.",
+            "description": "Capture a PNG screenshot of the current page, returned as image content. Read-only.",
             "inputSchema": {
                 "type": "object",
                 "properties": {},
                 "additionalProperties": false
             }
         },
+        {
+            "name": "scroll",
+            "description": "Scroll an element into view, or the page down one viewport when no selector is given. Requires automation to be enabled.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "selector": { "type": "string", "description": "Optional CSS selector to scroll into view." }
+                },
+                "additionalProperties": false
+            }
+        },
+        {
+            "name": "extract",
+            "description": "Return the text of the first element matching a CSS selector, or of every match with all=true. Read-only.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "selector": { "type": "string", "description": "CSS selector to read." },
+                    "all": { "type": "boolean", "description": "Return a JSON array with the text of every match." }
+                },
+                "required": ["selector"],
+                "additionalProperties": false
+            }
+        },
+        {
+            "name": "wait_for",
+            "description": "Wait until an element matching a CSS selector appears. Read-only.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "selector": { "type": "string", "description": "CSS selector to wait for." },
+                    "timeout_ms": { "type": "integer", "minimum": 0, "maximum": 30000, "description": "How long to wait (default 5000)." }
+                },
+                "required": ["selector"],
+                "additionalProperties": false
+            }
+        },
+        {
+            "name": "select_option",
+            "description": "Choose an option, by value or visible label, in the <select> matching a CSS selector. Requires automation to be enabled.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "selector": { "type": "string", "description": "CSS selector of the <select> element." },
+                    "value": { "type": "string", "description": "Option value or label to choose." }
+                },
+                "required": ["selector", "value"],
+                "additionalProperties": false
+            }
+        },
+        {
+            "name": "go_back",
+            "description": "Go back one page in the tab's history. Requires automation to be enabled.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {},
+                "additionalProperties": false
+            }
+        },
+        {
+            "name": "go_forward",
+            "description": "Go forward one page in the tab's history. Requires automation to be enabled.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {},
+                "additionalProperties": false
+            }
+        },
+        {
+            "name": "list_tabs",
+            "description": "List open tabs with their index, URL, title and which one tools act on. Read-only.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {},
+                "additionalProperties": false
+            }
+        },
+        {
+            "name": "switch_tab",
+            "description": "Make the tab at an index (from list_tabs) the one tools act on. Requires automation to be enabled.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "index": { "type": "integer", "minimum": 0, "description": "Tab index from list_tabs." }
+                },
+                "required": ["index"],
+                "additionalProperties": false
+            }
+        },
+        {
+            "name": "evaluate",
+            "description": "Evaluate a JavaScript expression in the page and return its JSON result. Requires automation to be enabled and automation.allow_script_evaluation.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "expression": { "type": "string", "description": "JavaScript to evaluate; promises are awaited." }
+                },
+                "required": ["expression"],
+                "additionalProperties": false
+            }
+        },
+        {
+            "name": "get_cookies",
+            "description": "List the cookies sent to the current page. Values are redacted unless automation.expose_cookie_values is set. Read-only.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "name": { "type": "string", "description": "Only return the cookie with this name." }
+                },
+                "additionalProperties": false
+            }
+        },
+        {
+            "name": "pdf",
+            "description": "Print the current page to PDF, returned as an embedded resource. Needs a headless browser. Read-only.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {},
+                "additionalProperties": false
+            }
+        },
+        {
+            "name": "summarize_page",
+            "description": "Summarize the current page with Archon's configured AI provider. Read-only.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "style": { "type": "string", "description": "bullets (default), paragraph, key-points, executive, technical, eli5 or outline." }
+                },
+                "additionalProperties": false
+            }
+        },
         {
             "name": "run_task",
             "description": "Run the autonomous browser agent toward a natural-language goal. Defaults to a preview/dry-run; set execute=true (requires automation.enabled) to perform real actions.",
@@ -614,21 +1538,41 @@ fn tool_definitions() -> Value {
 mod tests {
     use super::*;
     use crate::ai::AiBridge;
-    use crate::browser::{ElementSummary, PageObservation};
+    use crate::browser::{ElementSummary, PageObservation, StorageSnapshot, StoredCookie};
     use crate::config::{AiSettings, AutomationSettings};
-    use crate::transcript::TranscriptStore;
+    use crate::transcript::{TranscriptInput, TranscriptSource};
     use std::cell::RefCell;
+    use std::sync::atomic::{AtomicUsize, Ordering};
     use uuid::Uuid;
 
     /// Minimal in-memory driver for protocol tests (no real browser).
-    #[derive(Default)]
     struct StubDriver {
         calls: RefCell<Vec<String>>,
+        url: RefCell<String>,
+        /// Pages `go_back` returns to, most recent last.
+        back: RefCell<Vec<String>>,
+        /// `observe` calls, shared with the test.
+        observes: Arc<AtomicUsize>,
+        /// Screenshots land here and are removed with the driver.
+        shots: tempfile::TempDir,
+    }
+
+    impl Default for StubDriver {
+        fn default() -> Self {
+            Self {
+                calls: RefCell::default(),
+                url: RefCell::new("https://example.test/".into()),
+                back: RefCell::default(),
+                observes: Arc::default(),
+                shots: tempfile::tempdir().expect("screenshot dir"),
+            }
+        }
     }
 
     impl BrowserDriver for StubDriver {
         fn navigate(&self, url: &str) -> Result<()> {
             self.calls.borrow_mut().push(format!("navigate:{url}"));
+            *self.url.borrow_mut() = url.to_string();
             Ok(())
         }
         fn click(&self, selector: &str) -> Result<()> {
@@ -648,11 +1592,17 @@ mod tests {
             Ok("extracted".into())
         }
         fn screenshot(&self) -> Result<String> {
-            Ok("/tmp/archon-shot.png".into())
+            let path = self
+                .shots
+                .path()
+                .join(format!("archon-shot-{}.png", Uuid::new_v4()));
+            std::fs::write(&path, b"\x89PNG")?;
+            Ok(path.display().to_string())
         }
         fn observe(&self) -> Result<PageObservation> {
+            self.observes.fetch_add(1, Ordering::SeqCst);
             Ok(PageObservation {
-                url: "https://example.test/".into(),
+                url: self.url.borrow().clone(),
                 title: "Example".into(),
                 text: "hello world".into(),
                 interactive: vec![ElementSummary {
@@ -664,7 +1614,42 @@ mod tests {
             })
         }
         fn current_url(&self) -> Result<String> {
-            Ok("https://example.test/".into())
+            Ok(self.url.borrow().clone())
+        }
+        fn storage_snapshot(&self) -> Result<StorageSnapshot> {
+            let cookie = |name: &str, domain: &str| StoredCookie {
+                name: name.into(),
+                value: "v".into(),
+                domain: domain.into(),
+                path: "/".into(),
+                expires: None,
+                http_only: false,
+                secure: true,
+            };
+            Ok(StorageSnapshot {
+                url: self.url.borrow().clone(),
+                cookies: vec![
+                    cookie("sid", ".example.test"),
+                    cookie("pref", "example.test"),
+                    cookie("other", "tracker.test"),
+                ],
+                ..StorageSnapshot::default()
+            })
+        }
+        fn select_option(&self, selector: &str, value: &str) -> Result<()> {
+            self.calls
+                .borrow_mut()
+                .push(format!("select:{selector}={value}"));
+            Ok(())
+        }
+        fn evaluate(&self, expression: &str) -> Result<Value> {
+            self.calls.borrow_mut().push(format!("eval:{expression}"));
+            Ok(json!({ "answer": 42 }))
+        }
+        fn go_back(&self) -> Result<()> {
+            let previous = self.back.borrow_mut().pop().context("no history")?;
+            *self.url.borrow_mut() = previous;
+            Ok(())
         }
     }
 
@@ -711,6 +1696,8 @@ mod tests {
         assert_eq!(result["protocolVersion"], "2025-06-18");
         assert_eq!(result["serverInfo"]["name"], "archon");
         assert!(result["capabilities"]["tools"].is_object());
+        assert_eq!(result["capabilities"]["resources"]["subscribe"], true);
+        assert!(result["capabilities"]["prompts"].is_object());
     }
 
     #[test]
@@ -719,7 +1706,26 @@ mod tests {
         let resp = call(&mut tb, 2, "tools/list", json!({}));
         let tools = resp["result"]["tools"].as_array().expect("tools array");
         let names: Vec<&str> = tools.iter().filter_map(|t| t["name"].as_str()).collect();
-        for expected in ["navigate", "read_page", "click", "type", "screenshot", "run_task"] {
+        for expected in [
+            "navigate",
+            "read_page",
+            "click",
+            "type",
+            "screenshot",
+            "scroll",
+            "extract",
+            "wait_for",
+            "select_option",
+            "go_back",
+            "go_forward",
+            "list_tabs",
+            "switch_tab",
+            "evaluate",
+            "get_cookies",
+            "pdf",
+            "summarize_page",
+            "run_task",
+        ] {
             assert!(names.contains(&expected), "missing tool {expected}");
         }
         for tool in tools {
@@ -731,6 +1737,233 @@ mod tests {
         }
     }
 
+    fn tool(toolbox: &mut BrowserToolbox, name: &str, arguments: Value) -> Value {
+        let resp = call(
+            toolbox,
+            1,
+            "tools/call",
+            json!({ "name": name, "arguments": arguments }),
+        );
+        resp["result"].clone()
+    }
+
+    #[test]
+    fn screenshot_is_returned_as_image_content() {
+        let mut tb = toolbox(AutomationSettings::default());
+        let result = tool(&mut tb, "screenshot", json!({}));
+        assert_eq!(result["isError"], false);
+        assert_eq!(result["content"][0]["type"], "image");
+        assert_eq!(result["content"][0]["mimeType"], "image/png");
+        assert_eq!(result["content"][0]["data"], BASE64.encode(b"\x89PNG"));
+        assert!(
+            result["content"][1]["text"]
+                .as_str()
+                .unwrap()
+                .contains(SCREENSHOT_PREFIX)
+        );
+    }
+
+    #[test]
+    fn read_only_tools_work_and_mutating_tools_are_gated() {
+        let mut tb = toolbox(AutomationSettings::default());
+        for (name, args) in [
+            ("extract", json!({ "selector": "h1" })),
+            ("wait_for", json!({ "selector": "h1", "timeout_ms": 10 })),
+            ("list_tabs", json!({})),
+            ("get_cookies", json!({})),
+        ] {
+            assert_eq!(tool(&mut tb, name, args)["isError"], false, "{name}");
+        }
+        for (name, args) in [
+            ("scroll", json!({})),
+            (
+                "select_option",
+                json!({ "selector": "#size", "value": "L" }),
+            ),
+            ("go_back", json!({})),
+            ("switch_tab", json!({ "index": 0 })),
+            ("evaluate", json!({ "expression": "1 + 1" })),
+        ] {
+            let result = tool(&mut tb, name, args);
+            assert_eq!(result["isError"], true, "{name}");
+            assert!(
+                result["content"][0]["text"]
+                    .as_str()
+                    .unwrap()
+                    .contains("automation.enabled"),
+                "{name}"
+            );
+        }
+
+        let mut tb = toolbox(enabled_settings());
+        let selected = tool(
+            &mut tb,
+            "select_option",
+            json!({ "selector": "#size", "value": "L" }),
+        );
+        assert_eq!(selected["isError"], false);
+        let history = tool(&mut tb, "go_back", json!({}));
+        assert_eq!(
+            history["isError"], true,
+            "stub has no history to go back to"
+        );
+        let tabs = tool(&mut tb, "list_tabs", json!({}));
+        assert!(
+            tabs["content"][0]["text"]
+                .as_str()
+                .unwrap()
+                .contains("\"active\": true")
+        );
+        assert!(
+            tool(&mut tb, "summarize_page", json!({}))["content"][0]["text"]
+                .as_str()
+                .unwrap()
+                .contains("not available")
+        );
+    }
+
+    #[test]
+    fn evaluate_needs_its_own_policy_flag() {
+        let mut tb = toolbox(enabled_settings());
+        let refused = tool(
+            &mut tb,
+            "evaluate",
+            json!({ "expression": "document.title" }),
+        );
+        assert_eq!(refused["isError"], true);
+        assert!(
+            refused["content"][0]["text"]
+                .as_str()
+                .unwrap()
+                .contains("allow_script_evaluation")
+        );
+
+        let mut tb = toolbox(AutomationSettings {
+            allow_script_evaluation: true,
+            ..enabled_settings()
+        });
+        let result = tool(
+            &mut tb,
+            "evaluate",
+            json!({ "expression": "document.title" }),
+        );
+        assert_eq!(result["isError"], false);
+        assert!(
+            result["content"][0]["text"]
+                .as_str()
+                .unwrap()
+                .contains("42")
+        );
+
+        let mut tb = toolbox(AutomationSettings {
+            allow_script_evaluation: true,
+            blocked_domains: vec!["example.test".into()],
+            ..enabled_settings()
+        });
+        let blocked = tool(&mut tb, "evaluate", json!({ "expression": "1" }));
+        assert_eq!(blocked["isError"], true);
+    }
+
+    #[test]
+    fn evaluate_refuses_without_a_browser_or_a_host() {
+        let settings = AutomationSettings {
+            allow_script_evaluation: true,
+            ..enabled_settings()
+        };
+        let mut tb = BrowserToolbox::new(
+            orchestrator(settings.clone()),
+            None,
+            None,
+            Box::new(|| anyhow::bail!("browser failed to launch")),
+        );
+        let failed = tool(&mut tb, "evaluate", json!({ "expression": "1" }));
+        assert_eq!(failed["isError"], true);
+        assert!(
+            failed["content"][0]["text"]
+                .as_str()
+                .unwrap()
+                .contains("failed to launch")
+        );
+
+        let mut tb = BrowserToolbox::new(
+            orchestrator(settings),
+            None,
+            None,
+            Box::new(|| {
+                let driver = StubDriver::default();
+                *driver.url.borrow_mut() = "about:blank".into();
+                Ok(Box::new(driver) as Box<dyn BrowserDriver>)
+            }),
+        );
+        let blank = tool(&mut tb, "evaluate", json!({ "expression": "1" }));
+        assert_eq!(blank["isError"], true);
+        assert!(
+            blank["content"][0]["text"]
+                .as_str()
+                .unwrap()
+                .contains("allowed domain")
+        );
+    }
+
+    #[test]
+    fn history_leaves_pages_on_blocked_domains() {
+        let mut tb = BrowserToolbox::new(
+            orchestrator(AutomationSettings {
+                blocked_domains: vec!["blocked.test".into()],
+                ..enabled_settings()
+            }),
+            None,
+            None,
+            Box::new(|| {
+                let driver = StubDriver::default();
+                driver
+                    .back
+                    .borrow_mut()
+                    .push("https://blocked.test/".into());
+                Ok(Box::new(driver) as Box<dyn BrowserDriver>)
+            }),
+        );
+        let result = tool(&mut tb, "go_back", json!({}));
+        assert_eq!(result["isError"], true);
+        assert!(
+            result["content"][0]["text"]
+                .as_str()
+                .unwrap()
+                .contains("blocked.test")
+        );
+        assert_eq!(
+            tb.driver.as_deref().unwrap().current_url().unwrap(),
+            "about:blank"
+        );
+    }
+
+    #[test]
+    fn get_cookies_returns_only_the_current_sites_cookies() {
+        let mut tb = toolbox(AutomationSettings::default());
+        let result = tool(&mut tb, "get_cookies", json!({}));
+        let cookies: Vec<Value> =
+            serde_json::from_str(result["content"][0]["text"].as_str().unwrap()).unwrap();
+        let names: Vec<&str> = cookies.iter().filter_map(|c| c["name"].as_str()).collect();
+        assert_eq!(names, ["sid", "pref"]);
+        assert!(cookies.iter().all(|c| c["value"] == REDACTED_COOKIE));
+
+        let result = tool(&mut tb, "get_cookies", json!({ "name": "pref" }));
+        let cookies: Vec<Value> =
+            serde_json::from_str(result["content"][0]["text"].as_str().unwrap()).unwrap();
+        assert_eq!(cookies.len(), 1);
+
+        let mut tb = toolbox(AutomationSettings {
+            expose_cookie_values: true,
+            ..AutomationSettings::default()
+        });
+        let result = tool(&mut tb, "get_cookies", json!({ "name": "sid" }));
+        let cookies: Vec<Value> =
+            serde_json::from_str(result["content"][0]["text"].as_str().unwrap()).unwrap();
+        assert_eq!(cookies[0]["value"], "v");
+        assert!(cookie_matches_host(".example.test", "www.example.test"));
+        assert!(!cookie_matches_host("example.test", "notexample.test"));
+    }
+
     #[test]
     fn read_page_works_without_automation_enabled() {
         let mut tb = toolbox(AutomationSettings::default());
@@ -854,6 +2087,227 @@ mod tests {
         assert!(second["result"]["tools"].is_array());
     }
 
+    #[test]
+    fn resources_expose_page_screenshots_transcripts_and_recipes() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = Arc::new(TranscriptStore::new(dir.path().join("transcripts")).unwrap());
+        let record = store
+            .record_interaction(&TranscriptInput {
+                conversation_id: None,
+                source: TranscriptSource::Cli,
+                prompt_text: "What is Archon?",
+                attachments: &[],
+                reply_text: "A browser runtime.",
+                provider: "test",
+                model: "test",
+                latency_ms: 1,
+            })
+            .unwrap();
+        let recipes = dir.path().join("recipes");
+        std::fs::create_dir_all(&recipes).unwrap();
+        std::fs::write(recipes.join("login.json"), r#"{"name":"Login","steps":[]}"#).unwrap();
+        let mut tb = toolbox(AutomationSettings::default())
+            .with_transcripts(store)
+            .with_recipe_dir(&recipes);
+
+        call(
+            &mut tb,
+            1,
+            "tools/call",
+            json!({ "name": "screenshot", "arguments": {} }),
+        );
+
+        let resp = call(&mut tb, 2, "resources/list", json!({}));
+        let uris: Vec<&str> = resp["result"]["resources"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .filter_map(|r| r["uri"].as_str())
+            .collect();
+        let transcript_uri = format!("archon://transcripts/{}", record.summary.id);
+        assert!(uris.contains(&PAGE_MARKDOWN_URI));
+        assert!(uris.contains(&transcript_uri.as_str()));
+        assert!(uris.contains(&"archon://recipes/login"));
+        let shot_uri = uris
+            .iter()
+            .find(|uri| uri.starts_with(SCREENSHOT_PREFIX))
+            .expect("screenshot resource")
+            .to_string();
+
+        let read = |tb: &mut BrowserToolbox, uri: &str| {
+            call(tb, 3, "resources/read", json!({ "uri": uri }))
+        };
+        let page = read(&mut tb, PAGE_MARKDOWN_URI);
+        let content = &page["result"]["contents"][0];
+        assert_eq!(content["mimeType"], "text/markdown");
+        assert!(content["text"].as_str().unwrap().starts_with("# Example"));
+        let shot = read(&mut tb, &shot_uri);
+        assert_eq!(
+            shot["result"]["contents"][0]["blob"],
+            BASE64.encode(b"\x89PNG")
+        );
+        let transcript = read(&mut tb, &transcript_uri);
+        assert!(
+            transcript["result"]["contents"][0]["text"]
+                .as_str()
+                .unwrap()
+                .contains("A browser runtime.")
+        );
+        let recipe = read(&mut tb, "archon://recipes/login");
+        assert!(
+            recipe["result"]["contents"][0]["text"]
+                .as_str()
+                .unwrap()
+                .contains("Login")
+        );
+        assert_eq!(
+            read(&mut tb, "archon://recipes/nope")["error"]["code"],
+            -32002
+        );
+
+        let templates = call(&mut tb, 4, "resources/templates/list", json!({}));
+        assert_eq!(
+            templates["result"]["resourceTemplates"]
+                .as_array()
+                .unwrap()
+                .len(),
+            2
+        );
+    }
+
+    #[test]
+    fn page_subscription_notifies_on_navigation() {
+        let mut tb = toolbox(enabled_settings());
+        let resp = call(
+            &mut tb,
+            1,
+            "resources/subscribe",
+            json!({ "uri": PAGE_TEXT_URI }),
+        );
+        assert!(resp["error"].is_null());
+        assert!(tb.take_notifications().is_empty());
+
+        // Reading the page does not change it.
+        call(
+            &mut tb,
+            2,
+            "tools/call",
+            json!({ "name": "read_page", "arguments": {} }),
+        );
+        assert!(tb.take_notifications().is_empty());
+
+        let input = format!(
+            "{}\n",
+            json!({ "jsonrpc": "2.0", "id": 3, "method": "tools/call",
+                    "params": { "name": "navigate", "arguments": { "url": "https://example.test/next" } } })
+        );
+        let mut output: Vec<u8> = Vec::new();
+        run_stdio(&mut tb, input.as_bytes(), &mut output).unwrap();
+        let frames: Vec<Value> = String::from_utf8(output)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(frames.len(), 2);
+        assert_eq!(frames[0]["id"], 3);
+        assert_eq!(frames[1]["method"], "notifications/resources/updated");
+        assert_eq!(frames[1]["params"]["uri"], PAGE_TEXT_URI);
+
+        call(
+            &mut tb,
+            4,
+            "resources/unsubscribe",
+            json!({ "uri": PAGE_TEXT_URI }),
+        );
+        let bad = call(
+            &mut tb,
+            5,
+            "resources/subscribe",
+            json!({ "uri": "archon://recipes/x" }),
+        );
+        assert_eq!(bad["error"]["code"], -32602);
+    }
+
+    #[test]
+    fn subscriptions_only_observe_after_page_changing_tools() {
+        let observes = Arc::new(AtomicUsize::new(0));
+        let shared = Arc::clone(&observes);
+        let mut tb = BrowserToolbox::new(
+            orchestrator(enabled_settings()),
+            None,
+            None,
+            Box::new(move || {
+                Ok(Box::new(StubDriver {
+                    observes: Arc::clone(&shared),
+                    ..StubDriver::default()
+                }) as Box<dyn BrowserDriver>)
+            }),
+        );
+        call(
+            &mut tb,
+            1,
+            "resources/subscribe",
+            json!({ "uri": PAGE_TEXT_URI }),
+        );
+        let after_subscribe = observes.load(Ordering::SeqCst);
+
+        call(&mut tb, 2, "ping", json!({}));
+        tool(&mut tb, "extract", json!({ "selector": "#main" }));
+        call(&mut tb, 3, "resources/list", json!({}));
+        assert_eq!(observes.load(Ordering::SeqCst), after_subscribe);
+
+        tool(&mut tb, "click", json!({ "selector": "#lnk" }));
+        assert!(observes.load(Ordering::SeqCst) > after_subscribe);
+    }
+
+    #[test]
+    fn prompts_embed_page_and_research_instructions() {
+        let mut tb = toolbox(AutomationSettings::default());
+        let list = call(&mut tb, 1, "prompts/list", json!({}));
+        let names: Vec<&str> = list["result"]["prompts"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .filter_map(|p| p["name"].as_str())
+            .collect();
+        assert_eq!(names, ["summarize_page", "research"]);
+
+        let summary = call(
+            &mut tb,
+            2,
+            "prompts/get",
+            json!({ "name": "summarize_page", "arguments": { "style": "eli5" } }),
+        );
+        let messages = &summary["result"]["messages"];
+        assert_eq!(
+            messages[0]["content"]["text"],
+            SummarizeStyle::Eli5.system_prompt()
+        );
+        assert_eq!(messages[1]["content"]["resource"]["uri"], PAGE_MARKDOWN_URI);
+
+        let research = call(
+            &mut tb,
+            3,
+            "prompts/get",
+            json!({ "name": "research", "arguments": { "question": "Who maintains Archon?", "depth": "deep" } }),
+        );
+        let text = research["result"]["messages"][0]["content"]["text"]
+            .as_str()
+            .unwrap();
+        assert!(text.contains("Who maintains Archon?"));
+        assert!(text.contains("up to 10 sources"));
+
+        let missing = call(&mut tb, 4, "prompts/get", json!({ "name": "research" }));
+        assert_eq!(missing["error"]["code"], -32602);
+        let bad_style = call(
+            &mut tb,
+            5,
+            "prompts/get",
+            json!({ "name": "summarize_page", "arguments": { "style": "haiku" } }),
+        );
+        assert_eq!(bad_style["error"]["code"], -32602);
+    }
+
     #[test]
     fn host_of_extracts_host() {
         assert_eq!(host_of("https://Example.com/path?q=1").as_deref(), Some("example.com"));
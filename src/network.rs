@@ -0,0 +1,560 @@
+//! Network capture for the agent browser.
+//!
+//! [`NetworkLog`] accumulates request/response pairs reported by the CDP
+//! `Network` domain (see [`crate::browser::CdpBrowser::with_network`]) so a run
+//! can be saved as a HAR 1.2 file, and so the `extract_response` action can
+//! return the JSON body of a matching XHR/fetch call. URL block patterns are
+//! applied through the CDP `Fetch` domain; blocked requests are logged too.
+//! Credential headers and request bodies are redacted from the HAR unless
+//! [`NetworkOptions::include_sensitive`] is set.
+
+use std::collections::{HashMap, VecDeque};
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+
+use crate::automation::glob_match;
+use crate::config::AutomationSettings;
+use crate::sync_util::LockResultExt;
+use crate::transcript::write_sealed;
+use crate::transcript_crypto::TranscriptCipher;
+
+/// Maximum entries kept per log; the oldest are dropped beyond this.
+const MAX_NETWORK_ENTRIES: usize = 5_000;
+/// Response bodies larger than this are not retained.
+const MAX_BODY_CHARS: usize = 256 * 1024;
+/// Placeholder for header values and bodies left out of a HAR export.
+const REDACTED: &str = "[redacted]";
+/// Headers whose values are redacted from HAR exports (matched case-insensitively).
+const SENSITIVE_HEADERS: &[&str] = &[
+    "cookie",
+    "set-cookie",
+    "authorization",
+    "proxy-authorization",
+];
+
+/// Which network features to enable on a [`crate::browser::CdpBrowser`].
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NetworkOptions {
+    /// Record traffic (required for HAR export and `extract_response`).
+    #[serde(default)]
+    pub capture: bool,
+    /// URL globs (`*`/`?`) whose requests are failed as blocked-by-client.
+    #[serde(default)]
+    pub block_patterns: Vec<String>,
+    /// Keep credential headers and request bodies in the HAR export.
+    #[serde(default)]
+    pub include_sensitive: bool,
+}
+
+impl NetworkOptions {
+    /// Options derived from the automation settings.
+    pub fn from_settings(settings: &AutomationSettings) -> Self {
+        Self {
+            capture: settings.capture_network,
+            block_patterns: settings.block_url_patterns.clone(),
+            include_sensitive: settings.har_include_sensitive,
+        }
+    }
+
+    /// Whether any network feature is enabled.
+    pub fn is_enabled(&self) -> bool {
+        self.capture || !self.block_patterns.is_empty()
+    }
+
+    /// Whether `url` matches one of the block patterns.
+    pub fn is_blocked(&self, url: &str) -> bool {
+        self.block_patterns
+            .iter()
+            .any(|pattern| glob_match(pattern, url))
+    }
+}
+
+/// A single captured request and (if it completed) its response.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NetworkEntry {
+    /// When the request was sent.
+    pub started: DateTime<Utc>,
+    /// HTTP method.
+    pub method: String,
+    /// Request URL.
+    pub url: String,
+    /// CDP resource type (`Document`, `XHR`, `Fetch`, ...).
+    pub resource_type: String,
+    /// Request headers.
+    pub request_headers: Vec<(String, String)>,
+    /// Request body, when the browser reported one.
+    pub post_data: Option<String>,
+    /// Response status (0 for blocked or failed requests).
+    pub status: u32,
+    /// Response status text.
+    pub status_text: String,
+    /// Negotiated protocol (e.g. `h2`, `http/1.1`).
+    pub http_version: String,
+    /// Response MIME type.
+    pub mime_type: String,
+    /// Response headers.
+    pub response_headers: Vec<(String, String)>,
+    /// Response body, retained for JSON XHR/fetch responses only.
+    pub body: Option<String>,
+    /// Encoded bytes received, or -1 when unknown.
+    pub body_size: i64,
+    /// Wall time from request to completion, in milliseconds.
+    pub time_ms: f64,
+    /// Whether the request was blocked by a URL pattern.
+    #[serde(default)]
+    pub blocked: bool,
+}
+
+/// The response half of a [`NetworkEntry`], as reported by the driver.
+#[derive(Debug, Clone, Default)]
+pub struct CapturedResponse {
+    pub url: String,
+    pub resource_type: String,
+    pub status: u32,
+    pub status_text: String,
+    pub http_version: String,
+    pub mime_type: String,
+    pub headers: Vec<(String, String)>,
+    pub body: Option<String>,
+    pub body_size: i64,
+}
+
+/// A request seen before its response arrived.
+struct PendingRequest {
+    started: DateTime<Utc>,
+    method: String,
+    headers: Vec<(String, String)>,
+    post_data: Option<String>,
+}
+
+#[derive(Default)]
+struct LogState {
+    pending: HashMap<String, PendingRequest>,
+    entries: VecDeque<NetworkEntry>,
+}
+
+/// Thread-safe accumulator of captured traffic, shared with CDP event handlers.
+#[derive(Default)]
+pub struct NetworkLog {
+    state: std::sync::Mutex<LogState>,
+    include_sensitive: bool,
+}
+
+impl NetworkLog {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Keep credential headers and request bodies in [`NetworkLog::to_har`]
+    /// instead of redacting them.
+    pub fn with_sensitive_data(mut self, include: bool) -> Self {
+        self.include_sensitive = include;
+        self
+    }
+
+    /// Record an outgoing request; completed by [`NetworkLog::record_response`].
+    pub fn record_request(
+        &self,
+        request_id: &str,
+        method: &str,
+        headers: Vec<(String, String)>,
+        post_data: Option<String>,
+        started: DateTime<Utc>,
+    ) {
+        self.state.lock().recover().pending.insert(
+            request_id.to_string(),
+            PendingRequest {
+                started,
+                method: method.to_string(),
+                headers,
+                post_data,
+            },
+        );
+    }
+
+    /// Record the finished response for `request_id`.
+    pub fn record_response(&self, request_id: &str, response: CapturedResponse) {
+        let mut state = self.state.lock().recover();
+        let pending = state.pending.remove(request_id);
+        let now = Utc::now();
+        let (started, method, request_headers, post_data) = match pending {
+            Some(req) => (req.started, req.method, req.headers, req.post_data),
+            None => (now, "GET".to_string(), Vec::new(), None),
+        };
+        let body = response.body.filter(|body| body.len() <= MAX_BODY_CHARS);
+        state.push(NetworkEntry {
+            started,
+            method,
+            url: response.url,
+            resource_type: response.resource_type,
+            request_headers,
+            post_data,
+            status: response.status,
+            status_text: response.status_text,
+            http_version: response.http_version,
+            mime_type: response.mime_type,
+            response_headers: response.headers,
+            body,
+            body_size: response.body_size,
+            time_ms: (now - started).num_microseconds().unwrap_or(0) as f64 / 1_000.0,
+            blocked: false,
+        });
+    }
+
+    /// Record a request that was failed because it matched a block pattern.
+    pub fn record_blocked(&self, method: &str, url: &str, resource_type: &str) {
+        self.state.lock().recover().push(NetworkEntry {
+            started: Utc::now(),
+            method: method.to_string(),
+            url: url.to_string(),
+            resource_type: resource_type.to_string(),
+            request_headers: Vec::new(),
+            post_data: None,
+            status: 0,
+            status_text: "blocked".to_string(),
+            http_version: String::new(),
+            mime_type: String::new(),
+            response_headers: Vec::new(),
+            body: None,
+            body_size: -1,
+            time_ms: 0.0,
+            blocked: true,
+        });
+    }
+
+    /// Snapshot of all completed entries, oldest first.
+    pub fn entries(&self) -> Vec<NetworkEntry> {
+        self.state
+            .lock()
+            .recover()
+            .entries
+            .iter()
+            .cloned()
+            .collect()
+    }
+
+    /// The most recent response whose URL matches `pattern` and whose body is
+    /// JSON, returned as compact JSON text.
+    ///
+    /// `pattern` is a glob (`*`/`?`) over the full URL; without wildcards it
+    /// matches as a substring.
+    pub fn find_json_response(&self, pattern: &str) -> Option<String> {
+        let state = self.state.lock().recover();
+        state
+            .entries
+            .iter()
+            .rev()
+            .filter(|entry| url_matches(pattern, &entry.url))
+            .find_map(|entry| {
+                let body = entry.body.as_deref()?;
+                let value: Value = serde_json::from_str(body).ok()?;
+                serde_json::to_string(&value).ok()
+            })
+    }
+
+    /// Render the log as a HAR 1.2 document.
+    pub fn to_har(&self) -> Value {
+        let entries: Vec<Value> = self
+            .entries()
+            .iter()
+            .map(|entry| har_entry(entry, self.include_sensitive))
+            .collect();
+        json!({
+            "log": {
+                "version": "1.2",
+                "creator": { "name": "Archon", "version": env!("CARGO_PKG_VERSION") },
+                "pages": [],
+                "entries": entries,
+            }
+        })
+    }
+
+    /// Write the log as a HAR file at `path`, encrypted with `cipher` when given.
+    pub fn write_har(&self, path: &Path, cipher: Option<&TranscriptCipher>) -> Result<()> {
+        let har =
+            serde_json::to_string_pretty(&self.to_har()).context("failed to serialize HAR")?;
+        write_sealed(path, har.as_bytes(), cipher)
+            .with_context(|| format!("failed to write HAR to {}", path.display()))
+    }
+}
+
+impl LogState {
+    fn push(&mut self, entry: NetworkEntry) {
+        if self.entries.len() >= MAX_NETWORK_ENTRIES {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(entry);
+    }
+}
+
+/// Whether a response body is worth retaining for `extract_response`.
+pub fn should_capture_body(resource_type: &str, mime_type: &str) -> bool {
+    matches!(resource_type, "XHR" | "Fetch") && mime_type.contains("json")
+}
+
+fn url_matches(pattern: &str, url: &str) -> bool {
+    if pattern.contains(['*', '?']) {
+        glob_match(pattern, url)
+    } else {
+        url.contains(pattern)
+    }
+}
+
+fn har_headers(headers: &[(String, String)], include_sensitive: bool) -> Vec<Value> {
+    headers
+        .iter()
+        .map(|(name, value)| {
+            let sensitive = SENSITIVE_HEADERS
+                .iter()
+                .any(|header| name.eq_ignore_ascii_case(header));
+            let value = if sensitive && !include_sensitive {
+                REDACTED
+            } else {
+                value
+            };
+            json!({ "name": name, "value": value })
+        })
+        .collect()
+}
+
+fn har_entry(entry: &NetworkEntry, include_sensitive: bool) -> Value {
+    let query: Vec<Value> = url::Url::parse(&entry.url)
+        .map(|url| {
+            url.query_pairs()
+                .map(|(name, value)| json!({ "name": name, "value": value }))
+                .collect()
+        })
+        .unwrap_or_default();
+    let mut request = json!({
+        "method": entry.method,
+        "url": entry.url,
+        "httpVersion": entry.http_version,
+        "cookies": [],
+        "headers": har_headers(&entry.request_headers, include_sensitive),
+        "queryString": query,
+        "headersSize": -1,
+        "bodySize": entry.post_data.as_ref().map_or(0, |data| data.len() as i64),
+    });
+    if let Some(data) = &entry.post_data {
+        let mime = entry
+            .request_headers
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case("content-type"))
+            .map_or("", |(_, value)| value.as_str());
+        let text = if include_sensitive { data } else { REDACTED };
+        request["postData"] = json!({ "mimeType": mime, "text": text });
+    }
+    let mut content = json!({
+        "size": entry.body.as_ref().map_or(entry.body_size.max(0), |body| body.len() as i64),
+        "mimeType": entry.mime_type,
+    });
+    if let Some(body) = &entry.body {
+        content["text"] = json!(body);
+    }
+    let mut har = json!({
+        "startedDateTime": entry.started.to_rfc3339(),
+        "time": entry.time_ms,
+        "request": request,
+        "response": {
+            "status": entry.status,
+            "statusText": entry.status_text,
+            "httpVersion": entry.http_version,
+            "cookies": [],
+            "headers": har_headers(&entry.response_headers, include_sensitive),
+            "content": content,
+            "redirectURL": "",
+            "headersSize": -1,
+            "bodySize": entry.body_size,
+        },
+        "cache": {},
+        "timings": { "send": 0, "wait": entry.time_ms, "receive": 0 },
+        "_resourceType": entry.resource_type,
+    });
+    if entry.blocked {
+        har["_blocked"] = json!(true);
+    }
+    har
+}
+
+/// Flatten a CDP `Network.Headers` object into name/value pairs.
+pub fn headers_from_json(value: Option<&Value>) -> Vec<(String, String)> {
+    value
+        .and_then(Value::as_object)
+        .map(|map| {
+            map.iter()
+                .map(|(name, value)| {
+                    let value = value
+                        .as_str()
+                        .map_or_else(|| value.to_string(), str::to_string);
+                    (name.clone(), value)
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn json_response(url: &str, body: &str) -> CapturedResponse {
+        CapturedResponse {
+            url: url.into(),
+            resource_type: "XHR".into(),
+            status: 200,
+            status_text: "OK".into(),
+            http_version: "h2".into(),
+            mime_type: "application/json".into(),
+            headers: vec![("content-type".into(), "application/json".into())],
+            body: Some(body.into()),
+            body_size: body.len() as i64,
+        }
+    }
+
+    #[test]
+    fn find_json_response_returns_latest_match() {
+        let log = NetworkLog::new();
+        log.record_request("1", "GET", Vec::new(), None, Utc::now());
+        log.record_response(
+            "1",
+            json_response("https://api.test/items?page=1", r#"{"page": 1}"#),
+        );
+        log.record_request("2", "GET", Vec::new(), None, Utc::now());
+        log.record_response(
+            "2",
+            json_response("https://api.test/items?page=2", r#"{"page": 2}"#),
+        );
+        log.record_response("3", json_response("https://api.test/other", "not json"));
+
+        assert_eq!(
+            log.find_json_response("https://api.test/items*").as_deref(),
+            Some(r#"{"page":2}"#)
+        );
+        assert_eq!(
+            log.find_json_response("page=1").as_deref(),
+            Some(r#"{"page":1}"#)
+        );
+        assert!(log.find_json_response("/other").is_none());
+        assert!(log.find_json_response("/missing").is_none());
+    }
+
+    #[test]
+    fn har_export_has_required_fields() {
+        let log = NetworkLog::new();
+        log.record_request(
+            "1",
+            "POST",
+            vec![("Content-Type".into(), "application/json".into())],
+            Some(r#"{"q":"x"}"#.into()),
+            Utc::now(),
+        );
+        log.record_response(
+            "1",
+            json_response("https://api.test/search?q=x", r#"{"ok":true}"#),
+        );
+        log.record_blocked("GET", "https://tracker.test/pixel.gif", "Image");
+
+        let har = log.to_har();
+        assert_eq!(har["log"]["version"], "1.2");
+        let entries = har["log"]["entries"].as_array().expect("entries array");
+        assert_eq!(entries.len(), 2);
+
+        let first = &entries[0];
+        assert_eq!(first["request"]["method"], "POST");
+        assert_eq!(first["request"]["queryString"][0]["name"], "q");
+        assert_eq!(first["request"]["postData"]["mimeType"], "application/json");
+        assert_eq!(first["response"]["status"], 200);
+        assert_eq!(first["response"]["content"]["text"], r#"{"ok":true}"#);
+        assert!(first["startedDateTime"].is_string());
+
+        assert_eq!(entries[1]["_blocked"], true);
+        assert_eq!(entries[1]["response"]["status"], 0);
+    }
+
+    #[test]
+    fn har_export_redacts_credentials_unless_opted_in() {
+        let record = |log: &NetworkLog| {
+            log.record_request(
+                "1",
+                "POST",
+                vec![
+                    ("Cookie".into(), "sid=abc".into()),
+                    ("authorization".into(), "Bearer t0k".into()),
+                    ("Accept".into(), "*/*".into()),
+                ],
+                Some("password=hunter2".into()),
+                Utc::now(),
+            );
+            let mut response = json_response("https://api.test/login", "{}");
+            response.headers = vec![("Set-Cookie".into(), "sid=def; HttpOnly".into())];
+            log.record_response("1", response);
+        };
+
+        let log = NetworkLog::new();
+        record(&log);
+        let har = log.to_har().to_string();
+        for secret in ["sid=abc", "Bearer t0k", "hunter2", "sid=def"] {
+            assert!(!har.contains(secret), "{secret} leaked into {har}");
+        }
+        let entry = &log.to_har()["log"]["entries"][0];
+        assert_eq!(entry["request"]["headers"][0]["value"], REDACTED);
+        assert_eq!(entry["request"]["headers"][2]["value"], "*/*");
+        assert_eq!(entry["request"]["postData"]["text"], REDACTED);
+        assert_eq!(entry["response"]["headers"][0]["value"], REDACTED);
+        // The log itself keeps everything; only the export is redacted.
+        assert_eq!(
+            log.entries()[0].post_data.as_deref(),
+            Some("password=hunter2")
+        );
+
+        let log = NetworkLog::new().with_sensitive_data(true);
+        record(&log);
+        let har = log.to_har().to_string();
+        for secret in ["sid=abc", "Bearer t0k", "hunter2", "sid=def"] {
+            assert!(har.contains(secret), "{secret} missing from {har}");
+        }
+    }
+
+    #[test]
+    fn oldest_entries_are_dropped_past_the_cap() {
+        let log = NetworkLog::new();
+        for i in 0..=MAX_NETWORK_ENTRIES {
+            log.record_blocked("GET", &format!("https://t.test/{i}"), "Image");
+        }
+        let entries = log.entries();
+        assert_eq!(entries.len(), MAX_NETWORK_ENTRIES);
+        assert_eq!(entries[0].url, "https://t.test/1");
+    }
+
+    #[test]
+    fn block_patterns_and_body_policy() {
+        let options = NetworkOptions {
+            capture: false,
+            block_patterns: vec!["*://*.doubleclick.net/*".into(), "*/analytics.js".into()],
+            include_sensitive: false,
+        };
+        assert!(options.is_enabled());
+        assert!(options.is_blocked("https://ad.doubleclick.net/x"));
+        assert!(options.is_blocked("https://cdn.test/analytics.js"));
+        assert!(!options.is_blocked("https://example.test/app.js"));
+        assert!(!NetworkOptions::default().is_enabled());
+
+        assert!(should_capture_body("XHR", "application/json"));
+        assert!(should_capture_body("Fetch", "application/problem+json"));
+        assert!(!should_capture_body("Document", "application/json"));
+        assert!(!should_capture_body("XHR", "text/html"));
+    }
+
+    #[test]
+    fn oversized_bodies_are_dropped() {
+        let log = NetworkLog::new();
+        let big = format!("[{}]", "1,".repeat(MAX_BODY_CHARS));
+        log.record_response("1", json_response("https://api.test/big", &big));
+        assert!(log.entries()[0].body.is_none());
+        assert!(log.find_json_response("/big").is_none());
+    }
+}
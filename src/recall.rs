@@ -0,0 +1,152 @@
+//! Semantic recall over recorded transcripts.
+//!
+//! Messages of conversations with recall switched on are embedded with a local
+//! Ollama model and stored in the transcript index; new prompts in those
+//! conversations are embedded too and the closest past messages are offered to
+//! the model as context.
+
+use std::sync::Arc;
+
+use anyhow::{Context, Result, bail};
+use serde_json::json;
+use uuid::Uuid;
+
+use crate::ai::AiHttp;
+use crate::config::AiRecallSettings;
+use crate::transcript::{RecalledMessage, RelatedConversation, TranscriptStore};
+
+/// Messages embedded per Ollama request.
+const EMBED_BATCH: usize = 16;
+
+/// Characters of a message sent to the embedding model.
+const MAX_EMBED_CHARS: usize = 4_000;
+
+/// Embedding-backed recall over a [`TranscriptStore`].
+#[derive(Debug, Clone)]
+pub struct TranscriptRecall {
+    settings: AiRecallSettings,
+    transcripts: Arc<TranscriptStore>,
+}
+
+impl TranscriptRecall {
+    pub fn new(settings: AiRecallSettings, transcripts: Arc<TranscriptStore>) -> Self {
+        Self {
+            settings,
+            transcripts,
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.settings.enabled
+    }
+
+    pub fn settings(&self) -> &AiRecallSettings {
+        &self.settings
+    }
+
+    /// Embed `inputs` with the configured Ollama model (`POST /api/embed`).
+    pub fn embed<T: AiHttp>(&self, http: &T, inputs: &[String]) -> Result<Vec<Vec<f32>>> {
+        let url = format!("{}/api/embed", self.settings.endpoint.trim_end_matches('/'));
+        let mut vectors = Vec::with_capacity(inputs.len());
+        for batch in inputs.chunks(EMBED_BATCH) {
+            let input: Vec<&str> = batch
+                .iter()
+                .map(|text| truncate_chars(text, MAX_EMBED_CHARS))
+                .collect();
+            let response = http
+                .post_json(
+                    &url,
+                    &[],
+                    &json!({ "model": self.settings.model, "input": input }),
+                )
+                .with_context(|| format!("embedding request to {url} failed"))?;
+            let embeddings = response
+                .get("embeddings")
+                .and_then(|value| value.as_array())
+                .context("embedding response is missing `embeddings`")?;
+            if embeddings.len() != batch.len() {
+                bail!(
+                    "embedding model returned {} vectors for {} inputs",
+                    embeddings.len(),
+                    batch.len()
+                );
+            }
+            for embedding in embeddings {
+                let vector = embedding
+                    .as_array()
+                    .context("embedding is not an array")?
+                    .iter()
+                    .map(|value| value.as_f64().map(|value| value as f32))
+                    .collect::<Option<Vec<f32>>>()
+                    .context("embedding contains a non-numeric value")?;
+                vectors.push(vector);
+            }
+        }
+        Ok(vectors)
+    }
+
+    /// Past messages most similar to `text`, excluding conversation `exclude`
+    /// (whose history the model already sees), trimmed to `max_chars`.
+    pub fn recall<T: AiHttp>(
+        &self,
+        http: &T,
+        text: &str,
+        exclude: Option<Uuid>,
+    ) -> Result<Vec<RecalledMessage>> {
+        let query = self
+            .embed(http, &[text.to_string()])?
+            .pop()
+            .context("embedding model returned no vector")?;
+        let mut recalled = self.transcripts.recall_similar(
+            &self.settings.model,
+            &query,
+            exclude,
+            self.settings.max_results,
+            self.settings.min_similarity,
+        )?;
+        for message in &mut recalled {
+            let trimmed = truncate_chars(message.content.trim(), self.settings.max_chars);
+            if trimmed.len() < message.content.trim().len() {
+                message.content = format!("{trimmed}…");
+            }
+        }
+        Ok(recalled)
+    }
+
+    /// Embed the messages of conversation `id` that are not indexed yet.
+    /// Returns the number of messages embedded.
+    pub fn index_conversation<T: AiHttp>(&self, http: &T, id: Uuid) -> Result<usize> {
+        let pending = self
+            .transcripts
+            .unembedded_messages(id, &self.settings.model)?;
+        if pending.is_empty() {
+            return Ok(0);
+        }
+        let inputs: Vec<String> = pending
+            .iter()
+            .map(|(_, message)| message.content.clone())
+            .collect();
+        let vectors = self.embed(http, &inputs)?;
+        let embeddings: Vec<_> = pending
+            .into_iter()
+            .zip(vectors)
+            .map(|((index, message), vector)| (index, message, vector))
+            .collect();
+        self.transcripts
+            .store_embeddings(id, &self.settings.model, &embeddings)?;
+        Ok(embeddings.len())
+    }
+
+    /// Conversations related to `id` by embedding similarity.
+    pub fn related(&self, id: Uuid, limit: usize) -> Result<Vec<RelatedConversation>> {
+        self.transcripts
+            .related_conversations(id, &self.settings.model, limit)
+    }
+}
+
+fn truncate_chars(text: &str, max_chars: usize) -> &str {
+    match text.char_indices().nth(max_chars) {
+        Some((index, _)) => &text[..index],
+        None => text,
+    }
+}
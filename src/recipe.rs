@@ -7,19 +7,31 @@
 //! guardrails as the agent (domain allow/block, rate limit, sensitive/password
 //! guards, risk-gated confirmation) and produce an [`AgentOutcome`] so callers
 //! reuse the agent's JSON/Markdown persistence.
+//!
+//! Recipes declare typed `params` (with defaults, or environment-backed
+//! secrets) that steps reference as `{{name}}`; `extract`-style steps can
+//! capture their result into a variable with `save_as` for later steps.
 
+use std::collections::{BTreeMap, BTreeSet};
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::sync::atomic::AtomicBool;
 
-use anyhow::{Context, Result};
+use anyhow::{Context, Result, bail};
 use chrono::Utc;
 use serde::Deserialize;
+use serde_json::Value;
 use uuid::Uuid;
 
-use crate::agent::{AgentOutcome, AgentStep, BrowserAgent};
+use crate::agent::{
+    AgentOutcome, AgentStep, AssertionResult, AssertionStatus, BrowserAgent, ConfirmPolicy,
+    DEFAULT_VERIFY_RETRIES, SuccessCriterion, save_har,
+};
 use crate::ai::AiHttp;
-use crate::automation::{ActionResult, ActionType, AutomationOrchestrator, RiskLevel, WebAction};
+use crate::audit::ConfirmationDecision;
+use crate::automation::{
+    ActionResult, ActionType, AutomationOrchestrator, RiskLevel, WebAction, glob_match,
+};
 use crate::browser::BrowserDriver;
 
 /// A hybrid automation recipe.
@@ -33,11 +45,228 @@ pub struct Recipe {
     /// Optional URL to navigate to before the first step.
     #[serde(default)]
     pub start_url: Option<String>,
+    /// Declared parameters, referenced from steps as `{{name}}`.
+    #[serde(default)]
+    pub params: BTreeMap<String, RecipeParam>,
     /// Ordered steps.
     pub steps: Vec<RecipeStep>,
 }
 
 impl Recipe {
+    /// Resolve the declared parameters into run variables.
+    ///
+    /// Each value comes from `overrides` (`--param key=value`), then the
+    /// parameter's `env` variable, then its `default`; a parameter with none of
+    /// these is required. Values are checked against the declared type.
+    pub fn resolve_params(&self, overrides: &BTreeMap<String, String>) -> Result<RecipeVars> {
+        if let Some(unknown) = overrides.keys().find(|k| !self.params.contains_key(*k)) {
+            let declared: Vec<&str> = self.params.keys().map(String::as_str).collect();
+            bail!(
+                "unknown recipe parameter `{unknown}` (declared: {})",
+                if declared.is_empty() {
+                    "none".to_string()
+                } else {
+                    declared.join(", ")
+                }
+            );
+        }
+
+        let mut vars = RecipeVars::default();
+        for (name, param) in &self.params {
+            let raw = match overrides.get(name) {
+                Some(value) => value.clone(),
+                None => match param.env.as_deref().and_then(|var| std::env::var(var).ok()) {
+                    Some(value) => value,
+                    None => match &param.default {
+                        Some(default) => default_to_string(name, default)?,
+                        None => match &param.env {
+                            Some(var) => bail!(
+                                "missing recipe parameter `{name}` (pass --param {name}=... or set ${var})"
+                            ),
+                            None => {
+                                bail!("missing recipe parameter `{name}` (pass --param {name}=...)")
+                            }
+                        },
+                    },
+                },
+            };
+            let value = param.kind.check(name, &raw)?;
+            if param.kind == ParamType::Secret {
+                vars.set_secret(name, value);
+            } else {
+                vars.set(name, value);
+            }
+        }
+        Ok(vars)
+    }
+
+    /// Check parameter declarations, block structure, and that every `{{var}}`
+    /// reference names a declared parameter, a `save_as` variable of an earlier
+    /// step, or an enclosing `for_each` binding.
+    pub fn validate(&self) -> Result<()> {
+        let mut known: BTreeSet<String> = BTreeSet::new();
+        let mut secrets: BTreeSet<String> = BTreeSet::new();
+        for (name, param) in &self.params {
+            if !is_var_name(name) {
+                bail!("invalid parameter name `{name}` (use letters, digits, `_` or `-`)");
+            }
+            if let Some(default) = &param.default {
+                if param.kind == ParamType::Secret {
+                    bail!("secret parameter `{name}` must not have a default; use `env`");
+                }
+                param.kind.check(name, &default_to_string(name, default)?)?;
+            }
+            known.insert(name.clone());
+            if param.kind == ParamType::Secret {
+                secrets.insert(name.clone());
+            }
+        }
+        check_refs(self.start_url.as_deref(), &known, "start_url")?;
+        self.validate_steps(&self.steps, "", &mut known, &secrets)
+    }
+
+    fn validate_steps(
+        &self,
+        steps: &[RecipeStep],
+        prefix: &str,
+        known: &mut BTreeSet<String>,
+        secrets: &BTreeSet<String>,
+    ) -> Result<()> {
+        for (i, step) in steps.iter().enumerate() {
+            let path = step_path(prefix, i);
+            let what = format!("step {path}");
+            match step {
+                RecipeStep::Action(action) => {
+                    for field in [&action.selector, &action.url, &action.text, &action.value] {
+                        check_refs(field.as_deref(), known, &what)?;
+                    }
+                    if let Some(name) = &action.save_as {
+                        if !matches!(
+                            action.action,
+                            RecipeAction::Extract
+                                | RecipeAction::ExtractResponse
+                                | RecipeAction::Screenshot
+                        ) {
+                            bail!(
+                                "{what}: `save_as` is only supported on extract, \
+                                 extract_response and screenshot steps"
+                            );
+                        }
+                        self.check_binding(name, &what)?;
+                        known.insert(name.clone());
+                    }
+                }
+                RecipeStep::Goal(goal) => {
+                    for field in [Some(goal.goal.as_str()), goal.start_url.as_deref()] {
+                        check_refs(field, known, &what)?;
+                        let refs = field.map(template_refs).unwrap_or_default();
+                        if let Some(secret) = refs.iter().find(|r| secrets.contains(**r)) {
+                            bail!(
+                                "{what}: secret parameter `{secret}` cannot be sent to the \
+                                 model in a goal step"
+                            );
+                        }
+                    }
+                }
+                RecipeStep::If(step) => {
+                    for field in criterion_fields(&step.condition) {
+                        check_refs(Some(field), known, &what)?;
+                    }
+                    if step.then.is_empty() {
+                        bail!("{what}: `then` must not be empty");
+                    }
+                    let mut then_known = known.clone();
+                    self.validate_steps(
+                        &step.then,
+                        &format!("{path}.then"),
+                        &mut then_known,
+                        secrets,
+                    )?;
+                    let mut else_known = known.clone();
+                    self.validate_steps(
+                        &step.otherwise,
+                        &format!("{path}.else"),
+                        &mut else_known,
+                        secrets,
+                    )?;
+                    // Only names bound on both arms are certain to exist afterwards.
+                    known.extend(then_known.intersection(&else_known).cloned());
+                }
+                RecipeStep::ForEach(step) => {
+                    match (&step.for_each.selector, &step.for_each.items) {
+                        (Some(selector), None) => check_refs(Some(selector), known, &what)?,
+                        (None, Some(items)) => check_refs(Some(items), known, &what)?,
+                        _ => bail!("{what}: `for_each` needs exactly one of `selector` or `items`"),
+                    }
+                    if step.steps.is_empty() {
+                        bail!("{what}: `for_each` needs at least one step");
+                    }
+                    self.check_binding(&step.binding, &what)?;
+                    // The binding and anything the body saves stay inside the
+                    // loop, which may run zero times.
+                    let mut body_known = known.clone();
+                    body_known.insert(step.binding.clone());
+                    body_known.insert(format!("{}_index", step.binding));
+                    // Report nested problems at the path the first iteration would run.
+                    self.validate_steps(
+                        &step.steps,
+                        &iteration_path(&path, 1),
+                        &mut body_known,
+                        secrets,
+                    )?;
+                }
+                RecipeStep::Retry(step) => {
+                    if !(1..=MAX_RETRY_ATTEMPTS).contains(&step.attempts) {
+                        bail!("{what}: `attempts` must be between 1 and {MAX_RETRY_ATTEMPTS}");
+                    }
+                    if step.retry.is_empty() {
+                        bail!("{what}: `retry` needs at least one step");
+                    }
+                    self.validate_steps(&step.retry, &attempt_path(&path, 1), known, secrets)?;
+                }
+                RecipeStep::Assert(step) => {
+                    for field in step.assert.fields() {
+                        check_refs(Some(field), known, &what)?;
+                    }
+                    step.assert
+                        .validate()
+                        .with_context(|| format!("{what}: invalid assertion"))?;
+                }
+                RecipeStep::OnError(step) => {
+                    if step.body.is_empty() || step.on_error.is_empty() {
+                        bail!("{what}: `try` and `on_error` both need at least one step");
+                    }
+                    let mut body_known = known.clone();
+                    self.validate_steps(
+                        &step.body,
+                        &format!("{path}.try"),
+                        &mut body_known,
+                        secrets,
+                    )?;
+                    // `try` may fail before binding anything, so the handler
+                    // only sees what existed before it.
+                    let mut handler_known = known.clone();
+                    self.validate_steps(
+                        &step.on_error,
+                        &format!("{path}.on_error"),
+                        &mut handler_known,
+                        secrets,
+                    )?;
+                    known.extend(body_known.intersection(&handler_known).cloned());
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Reject variable names that are malformed or shadow a parameter.
+    fn check_binding(&self, name: &str, what: &str) -> Result<()> {
+        if !is_var_name(name) || self.params.contains_key(name) {
+            bail!("{what}: invalid or conflicting variable name `{name}`");
+        }
+        Ok(())
+    }
+
     /// The goal label for the produced [`AgentOutcome`] (name + description).
     pub fn goal_label(&self) -> String {
         match &self.description {
@@ -47,11 +276,259 @@ impl Recipe {
     }
 }
 
-/// A single recipe step: an explicit action or a natural-language goal.
+/// Declared type of a recipe parameter.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ParamType {
+    #[default]
+    String,
+    Number,
+    Boolean,
+    /// A string kept out of transcripts and the audit log; usually supplied
+    /// through `env` rather than the recipe file.
+    Secret,
+}
+
+impl ParamType {
+    /// Validate `value` for parameter `name`, returning its normalised form.
+    fn check(self, name: &str, value: &str) -> Result<String> {
+        match self {
+            Self::String | Self::Secret => Ok(value.to_string()),
+            Self::Number => value
+                .trim()
+                .parse::<f64>()
+                .map(|_| value.trim().to_string())
+                .with_context(|| format!("parameter `{name}` expects a number, got `{value}`")),
+            Self::Boolean => match value.trim().to_ascii_lowercase().as_str() {
+                "true" | "yes" | "1" => Ok("true".to_string()),
+                "false" | "no" | "0" => Ok("false".to_string()),
+                _ => bail!("parameter `{name}` expects true or false, got `{value}`"),
+            },
+        }
+    }
+}
+
+/// A declared recipe parameter.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct RecipeParam {
+    /// Value type (default `string`).
+    #[serde(rename = "type", default)]
+    pub kind: ParamType,
+    /// Value used when none is passed; parameters without one are required.
+    #[serde(default)]
+    pub default: Option<Value>,
+    /// Optional help text.
+    #[serde(default)]
+    pub description: Option<String>,
+    /// Environment variable consulted when no value is passed.
+    #[serde(default)]
+    pub env: Option<String>,
+}
+
+fn default_to_string(name: &str, value: &Value) -> Result<String> {
+    match value {
+        Value::String(s) => Ok(s.clone()),
+        Value::Number(n) => Ok(n.to_string()),
+        Value::Bool(b) => Ok(b.to_string()),
+        _ => bail!("default for parameter `{name}` must be a string, number or boolean"),
+    }
+}
+
+/// Variables available to a recipe run: resolved parameters plus values
+/// captured by `save_as`.
+#[derive(Debug, Clone, Default)]
+pub struct RecipeVars {
+    values: BTreeMap<String, String>,
+    secrets: BTreeSet<String>,
+}
+
+impl RecipeVars {
+    /// The value of `name`, if bound.
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.values.get(name).map(String::as_str)
+    }
+
+    /// Bind `name` to `value`.
+    pub fn set(&mut self, name: impl Into<String>, value: impl Into<String>) {
+        self.values.insert(name.into(), value.into());
+    }
+
+    /// Bind a secret `name`; its value is redacted wherever the run is recorded.
+    pub fn set_secret(&mut self, name: impl Into<String>, value: impl Into<String>) {
+        let name = name.into();
+        self.secrets.insert(name.clone());
+        self.values.insert(name, value.into());
+    }
+
+    /// Bound variables with secret values masked, e.g. for display.
+    pub fn redacted(&self) -> BTreeMap<String, String> {
+        self.values
+            .iter()
+            .map(|(name, value)| {
+                let shown = if self.secrets.contains(name) {
+                    "[redacted]".to_string()
+                } else {
+                    value.clone()
+                };
+                (name.clone(), shown)
+            })
+            .collect()
+    }
+
+    /// Substitute `{{name}}` references in `template`. Returns the rendered
+    /// text and whether a secret was substituted.
+    pub fn interpolate(&self, template: &str) -> Result<(String, bool)> {
+        let mut out = String::with_capacity(template.len());
+        let mut secret = false;
+        let mut rest = template;
+        while let Some(start) = rest.find("{{") {
+            let Some(len) = rest[start + 2..].find("}}") else {
+                break;
+            };
+            let name = rest[start + 2..start + 2 + len].trim();
+            let value = self
+                .get(name)
+                .with_context(|| format!("unknown recipe variable `{{{{{name}}}}}`"))?;
+            secret |= self.secrets.contains(name);
+            out.push_str(&rest[..start]);
+            out.push_str(value);
+            rest = &rest[start + 2 + len + 2..];
+        }
+        out.push_str(rest);
+        Ok((out, secret))
+    }
+
+    /// Replace secret values in `text` with their `{{name}}` placeholder.
+    fn redact(&self, text: &str) -> String {
+        let mut text = text.to_string();
+        for name in &self.secrets {
+            if let Some(value) = self.get(name).filter(|v| !v.is_empty()) {
+                text = text.replace(value, &format!("{{{{{name}}}}}"));
+            }
+        }
+        text
+    }
+
+    /// `outcome` with secret values replaced by their placeholder in every
+    /// string it carries (step results and errors, summary, assertions).
+    fn redact_outcome(&self, outcome: AgentOutcome) -> AgentOutcome {
+        if self.secrets.is_empty() {
+            return outcome;
+        }
+        let Ok(mut value) = serde_json::to_value(&outcome) else {
+            return outcome;
+        };
+        self.redact_value(&mut value);
+        serde_json::from_value(value).unwrap_or(outcome)
+    }
+
+    fn redact_value(&self, value: &mut Value) {
+        match value {
+            Value::String(text) => *text = self.redact(text),
+            Value::Array(items) => items.iter_mut().for_each(|item| self.redact_value(item)),
+            Value::Object(fields) => fields
+                .values_mut()
+                .for_each(|field| self.redact_value(field)),
+            _ => {}
+        }
+    }
+
+    /// A copy of `action` safe to record: secret values become placeholders.
+    fn redact_action(&self, action: &WebAction) -> WebAction {
+        let mut recorded = action.clone();
+        if action.secret {
+            let redact = |v: &Option<String>| v.as_deref().map(|s| self.redact(s));
+            recorded.selector = redact(&action.selector);
+            recorded.value = redact(&action.value);
+            recorded.description = redact(&action.description);
+        }
+        recorded
+    }
+}
+
+/// Names referenced as `{{name}}` in `template`.
+fn template_refs(template: &str) -> Vec<&str> {
+    let mut refs = Vec::new();
+    let mut rest = template;
+    while let Some(start) = rest.find("{{") {
+        let Some(len) = rest[start + 2..].find("}}") else {
+            break;
+        };
+        refs.push(rest[start + 2..start + 2 + len].trim());
+        rest = &rest[start + 2 + len + 2..];
+    }
+    refs
+}
+
+fn check_refs(text: Option<&str>, known: &BTreeSet<String>, what: &str) -> Result<()> {
+    for reference in text.map(template_refs).unwrap_or_default() {
+        if !known.contains(reference) {
+            bail!("{what} references unknown variable `{{{{{reference}}}}}`");
+        }
+    }
+    Ok(())
+}
+
+/// The string fields of `criterion` that may contain `{{var}}` references.
+fn criterion_fields(criterion: &SuccessCriterion) -> Vec<&str> {
+    match criterion {
+        SuccessCriterion::TextPresent { text } => vec![text],
+        SuccessCriterion::UrlMatches { pattern } => vec![pattern],
+        SuccessCriterion::ElementPresent { selector } => vec![selector],
+        SuccessCriterion::ElementText { selector, contains } => vec![selector, contains],
+    }
+}
+
+/// Dotted path of the `i`th (0-based) step under `prefix`, e.g. `3.then.1`.
+fn step_path(prefix: &str, i: usize) -> String {
+    if prefix.is_empty() {
+        (i + 1).to_string()
+    } else {
+        format!("{prefix}.{}", i + 1)
+    }
+}
+
+/// Path prefix for the steps of the `n`th (1-based) `for_each` iteration.
+fn iteration_path(path: &str, n: usize) -> String {
+    format!("{path}.iter{n}")
+}
+
+/// Path prefix for the steps of the `n`th (1-based) `retry` attempt.
+fn attempt_path(path: &str, n: u32) -> String {
+    format!("{path}.attempt{n}")
+}
+
+fn is_var_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+}
+
+/// Parse `--param key=value` arguments.
+pub fn parse_param_args(args: &[String]) -> Result<BTreeMap<String, String>> {
+    args.iter()
+        .map(|arg| {
+            let (key, value) = arg
+                .split_once('=')
+                .with_context(|| format!("invalid --param `{arg}` (expected key=value)"))?;
+            let key = key.trim();
+            if key.is_empty() {
+                bail!("invalid --param `{arg}` (empty key)");
+            }
+            Ok((key.to_string(), value.to_string()))
+        })
+        .collect()
+}
+
+/// A single recipe step: an explicit action, a natural-language goal, an
+/// assertion, or a control-flow block.
 ///
-/// Untagged: an object with an `action` field parses as [`ActionStep`]; one with
-/// a `goal` field parses as [`GoalStep`]. The disjoint required fields make the
-/// untagged representation unambiguous.
+/// Untagged: each kind is identified by its required field — `action`
+/// ([`ActionStep`]), `goal` ([`GoalStep`]), `assert` ([`AssertStep`]), `if`
+/// ([`IfStep`]), `for_each` ([`ForEachStep`]), `retry` ([`RetryStep`]) or
+/// `try` + `on_error` ([`OnErrorStep`]). The disjoint required fields make the untagged
+/// representation unambiguous.
 #[derive(Debug, Clone, Deserialize)]
 #[serde(untagged)]
 pub enum RecipeStep {
@@ -59,6 +536,287 @@ pub enum RecipeStep {
     Action(ActionStep),
     /// A natural-language goal handed to the agent.
     Goal(GoalStep),
+    /// Run `then` or `else` depending on the page.
+    If(IfStep),
+    /// Run a block once per extracted item.
+    ForEach(ForEachStep),
+    /// Re-run a block until it succeeds.
+    Retry(RetryStep),
+    /// Run a block, falling back to a handler if it fails.
+    OnError(OnErrorStep),
+    /// Check an expectation about the page; a failure stops the run.
+    Assert(AssertStep),
+}
+
+/// Upper bound on `retry` attempts.
+const MAX_RETRY_ATTEMPTS: u32 = 10;
+
+/// Upper bound on the delay between `retry` attempts.
+const MAX_RETRY_BACKOFF: std::time::Duration = std::time::Duration::from_secs(30);
+
+/// How often a `retry` backoff re-checks the cancel flag.
+const RETRY_CANCEL_POLL: std::time::Duration = std::time::Duration::from_millis(100);
+
+/// Delay before attempt `attempt + 1`: `backoff_ms` doubled per attempt,
+/// capped at [`MAX_RETRY_BACKOFF`].
+fn retry_delay(backoff_ms: u64, attempt: u32) -> std::time::Duration {
+    let factor = 1u64 << (attempt.saturating_sub(1)).min(16);
+    std::time::Duration::from_millis(backoff_ms.saturating_mul(factor)).min(MAX_RETRY_BACKOFF)
+}
+
+/// `{"if": <condition>, "then": [...], "else": [...]}`.
+///
+/// The condition uses the [`SuccessCriterion`] forms (`element_present`,
+/// `element_text`, `text_present`, `url_matches`).
+#[derive(Debug, Clone, Deserialize)]
+pub struct IfStep {
+    #[serde(rename = "if")]
+    pub condition: SuccessCriterion,
+    /// Steps run when the condition holds.
+    pub then: Vec<RecipeStep>,
+    /// Steps run otherwise.
+    #[serde(default, rename = "else")]
+    pub otherwise: Vec<RecipeStep>,
+}
+
+/// `{"for_each": {"selector": "..."} | {"items": "{{var}}"}, "as": "row", "steps": [...]}`.
+///
+/// Each iteration binds `{{<as>}}` to the item and `{{<as>_index}}` to its
+/// 1-based position.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ForEachStep {
+    pub for_each: ForEachSource,
+    /// Variable bound to the current item (default `item`).
+    #[serde(rename = "as", default = "ForEachStep::default_binding")]
+    pub binding: String,
+    /// Cap on iterations (default 100).
+    #[serde(default = "ForEachStep::default_max")]
+    pub max: usize,
+    pub steps: Vec<RecipeStep>,
+}
+
+impl ForEachStep {
+    fn default_binding() -> String {
+        "item".to_string()
+    }
+
+    fn default_max() -> usize {
+        100
+    }
+}
+
+/// What a [`ForEachStep`] iterates over.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ForEachSource {
+    /// Inner text of every element matching this selector.
+    #[serde(default)]
+    pub selector: Option<String>,
+    /// A variable holding a JSON array (e.g. from `extract_response`) or
+    /// newline-separated text.
+    #[serde(default)]
+    pub items: Option<String>,
+}
+
+/// `{"retry": [...], "attempts": 3, "backoff_ms": 1000}`: re-run the block
+/// after a failure, doubling the delay between attempts up to
+/// [`MAX_RETRY_BACKOFF`].
+#[derive(Debug, Clone, Deserialize)]
+pub struct RetryStep {
+    pub retry: Vec<RecipeStep>,
+    #[serde(default = "RetryStep::default_attempts")]
+    pub attempts: u32,
+    #[serde(default = "RetryStep::default_backoff_ms")]
+    pub backoff_ms: u64,
+}
+
+impl RetryStep {
+    fn default_attempts() -> u32 {
+        3
+    }
+
+    fn default_backoff_ms() -> u64 {
+        1_000
+    }
+}
+
+/// `{"try": [...], "on_error": [...]}`: when the `try` block fails, run the
+/// handler instead of stopping; the recipe continues if the handler succeeds.
+#[derive(Debug, Clone, Deserialize)]
+pub struct OnErrorStep {
+    #[serde(rename = "try")]
+    pub body: Vec<RecipeStep>,
+    pub on_error: Vec<RecipeStep>,
+}
+
+/// `{"assert": {"kind": "...", ...}, "message": "..."}`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct AssertStep {
+    pub assert: Assertion,
+    /// Reported alongside the failure.
+    #[serde(default)]
+    pub message: Option<String>,
+}
+
+/// An expectation checked by an [`AssertStep`]. String fields support
+/// `{{var}}` interpolation.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Assertion {
+    /// The page text contains `text`.
+    TextPresent { text: String },
+    /// The number of elements matching `selector` is exactly `equals`, or
+    /// within `min..=max`.
+    ElementCount {
+        selector: String,
+        #[serde(default)]
+        equals: Option<usize>,
+        #[serde(default)]
+        min: Option<usize>,
+        #[serde(default)]
+        max: Option<usize>,
+    },
+    /// The current URL matches the glob `pattern`.
+    UrlMatches { pattern: String },
+    /// The trimmed text of the element matching `selector` equals `equals`.
+    ValueEquals { selector: String, equals: String },
+    /// The text of the element matching `selector` matches the regex `pattern`.
+    ValueMatches { selector: String, pattern: String },
+}
+
+impl Assertion {
+    /// Interpolatable string fields, for reference checking.
+    fn fields(&self) -> Vec<&str> {
+        match self {
+            Self::TextPresent { text } => vec![text],
+            Self::ElementCount { selector, .. } => vec![selector],
+            Self::UrlMatches { pattern } => vec![pattern],
+            Self::ValueEquals { selector, equals } => vec![selector, equals],
+            Self::ValueMatches { selector, pattern } => vec![selector, pattern],
+        }
+    }
+
+    /// Reject assertions that can never be evaluated.
+    fn validate(&self) -> Result<()> {
+        match self {
+            Self::ElementCount {
+                equals, min, max, ..
+            } => {
+                if equals.is_none() && min.is_none() && max.is_none() {
+                    bail!("`element_count` needs `equals`, `min` or `max`");
+                }
+                if equals.is_some() && (min.is_some() || max.is_some()) {
+                    bail!("`element_count` takes `equals` or `min`/`max`, not both");
+                }
+            }
+            Self::ValueMatches { pattern, .. } if template_refs(pattern).is_empty() => {
+                regex::Regex::new(pattern)
+                    .with_context(|| format!("invalid `value_matches` pattern `{pattern}`"))?;
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// `self` with `{{var}}` references substituted.
+    fn render(&self, vars: &RecipeVars) -> Result<Self> {
+        let render = |text: &str| vars.interpolate(text).map(|(text, _)| text);
+        Ok(match self {
+            Self::TextPresent { text } => Self::TextPresent {
+                text: render(text)?,
+            },
+            Self::ElementCount {
+                selector,
+                equals,
+                min,
+                max,
+            } => Self::ElementCount {
+                selector: render(selector)?,
+                equals: *equals,
+                min: *min,
+                max: *max,
+            },
+            Self::UrlMatches { pattern } => Self::UrlMatches {
+                pattern: render(pattern)?,
+            },
+            Self::ValueEquals { selector, equals } => Self::ValueEquals {
+                selector: render(selector)?,
+                equals: render(equals)?,
+            },
+            Self::ValueMatches { selector, pattern } => Self::ValueMatches {
+                selector: render(selector)?,
+                pattern: render(pattern)?,
+            },
+        })
+    }
+
+    /// One-line description, e.g. `element count of tr.row >= 3`.
+    pub fn describe(&self) -> String {
+        match self {
+            Self::TextPresent { text } => format!("text \"{text}\" present"),
+            Self::ElementCount {
+                selector,
+                equals,
+                min,
+                max,
+            } => match (equals, min, max) {
+                (Some(n), _, _) => format!("element count of {selector} == {n}"),
+                (None, Some(lo), Some(hi)) => {
+                    format!("element count of {selector} in {lo}..={hi}")
+                }
+                (None, Some(lo), None) => format!("element count of {selector} >= {lo}"),
+                (None, None, Some(hi)) => format!("element count of {selector} <= {hi}"),
+                (None, None, None) => format!("element count of {selector}"),
+            },
+            Self::UrlMatches { pattern } => format!("URL matches {pattern}"),
+            Self::ValueEquals { selector, equals } => {
+                format!("value of {selector} == \"{equals}\"")
+            }
+            Self::ValueMatches { selector, pattern } => {
+                format!("value of {selector} matches /{pattern}/")
+            }
+        }
+    }
+
+    /// Check the assertion against the live page, returning whether it held
+    /// and the observed value.
+    pub fn evaluate(&self, driver: &dyn BrowserDriver) -> Result<(bool, Option<String>)> {
+        Ok(match self {
+            Self::TextPresent { text } => {
+                let criterion = SuccessCriterion::TextPresent { text: text.clone() };
+                (criterion.evaluate(driver).0, None)
+            }
+            Self::ElementCount {
+                selector,
+                equals,
+                min,
+                max,
+            } => {
+                // Drivers report "no match" as an error.
+                let count = driver.extract_all(selector).map(|v| v.len()).unwrap_or(0);
+                let held = match equals {
+                    Some(n) => count == *n,
+                    None => min.is_none_or(|lo| count >= lo) && max.is_none_or(|hi| count <= hi),
+                };
+                (held, Some(count.to_string()))
+            }
+            Self::UrlMatches { pattern } => {
+                let url = driver.current_url().unwrap_or_default();
+                (glob_match(pattern, &url), Some(url))
+            }
+            Self::ValueEquals { selector, equals } => match driver.extract(selector) {
+                Ok(value) => (value.trim() == equals.trim(), Some(value)),
+                Err(err) => (false, Some(format!("error: {err}"))),
+            },
+            Self::ValueMatches { selector, pattern } => {
+                let regex = regex::Regex::new(pattern)
+                    .with_context(|| format!("invalid `value_matches` pattern `{pattern}`"))?;
+                match driver.extract(selector) {
+                    Ok(value) => (regex.is_match(&value), Some(value)),
+                    Err(err) => (false, Some(format!("error: {err}"))),
+                }
+            }
+        })
+    }
 }
 
 /// The deterministic action kinds a recipe can express.
@@ -68,10 +826,14 @@ pub enum RecipeAction {
     Navigate,
     Click,
     Type,
+    /// Choose an option in a `<select>` (`value` = the option's value or label).
+    Select,
     Scroll,
     Extract,
     Screenshot,
     Wait,
+    /// Read the JSON body of a captured XHR/fetch response (`url` = URL pattern).
+    ExtractResponse,
 }
 
 /// An explicit action step.
@@ -79,24 +841,55 @@ pub enum RecipeAction {
 pub struct ActionStep {
     /// The action kind.
     pub action: RecipeAction,
-    /// CSS selector (click / type / extract; optional for scroll).
+    /// CSS selector (click / type / select / extract; optional for scroll).
     #[serde(default)]
     pub selector: Option<String>,
-    /// Target URL (navigate).
+    /// Target URL (navigate) or response URL pattern (extract_response).
     #[serde(default)]
     pub url: Option<String>,
     /// Text to type (type).
     #[serde(default)]
     pub text: Option<String>,
-    /// Generic value (alias for url/text where convenient).
+    /// Option to choose (select); otherwise an alias for url/text where
+    /// convenient.
     #[serde(default)]
     pub value: Option<String>,
     /// Milliseconds to wait (wait).
     #[serde(default)]
     pub ms: Option<u64>,
+    /// Capture the step's result (extracted text, response body, screenshot
+    /// path) into this variable for later `{{name}}` references.
+    #[serde(default)]
+    pub save_as: Option<String>,
 }
 
 impl ActionStep {
+    /// Build the step's [`WebAction`] with `{{var}}` references substituted;
+    /// the action is marked `secret` when a secret parameter was used.
+    pub fn render(&self, vars: &RecipeVars) -> Result<WebAction> {
+        let mut secret = false;
+        let mut render = |field: &Option<String>| -> Result<Option<String>> {
+            field
+                .as_deref()
+                .map(|text| {
+                    let (text, used_secret) = vars.interpolate(text)?;
+                    secret |= used_secret;
+                    Ok(text)
+                })
+                .transpose()
+        };
+        let rendered = Self {
+            selector: render(&self.selector)?,
+            url: render(&self.url)?,
+            text: render(&self.text)?,
+            value: render(&self.value)?,
+            ..self.clone()
+        };
+        let mut action = rendered.to_web_action()?;
+        action.secret = secret;
+        Ok(action)
+    }
+
     /// Build a validated [`WebAction`] for this step, erroring on missing fields.
     pub fn to_web_action(&self) -> Result<WebAction> {
         let action = match self.action {
@@ -123,6 +916,16 @@ impl ActionStep {
                 WebAction::type_text(selector, text)
                     .with_description(format!("type into {selector}"))
             }
+            RecipeAction::Select => {
+                let selector = self.require_selector("select")?;
+                let value = self
+                    .value
+                    .as_deref()
+                    .or(self.text.as_deref())
+                    .context("select step requires a `value`")?;
+                WebAction::select(selector, value)
+                    .with_description(format!("select an option in {selector}"))
+            }
             RecipeAction::Scroll => {
                 let selector = self.selector.clone().filter(|s| !s.is_empty());
                 let desc = match &selector {
@@ -138,6 +941,16 @@ impl ActionStep {
             RecipeAction::Screenshot => {
                 WebAction::screenshot().with_description("screenshot".to_string())
             }
+            RecipeAction::ExtractResponse => {
+                let pattern = self
+                    .url
+                    .as_deref()
+                    .or(self.value.as_deref())
+                    .filter(|s| !s.is_empty())
+                    .context("extract_response step requires a `url` pattern")?;
+                WebAction::extract_response(pattern)
+                    .with_description(format!("extract response {pattern}"))
+            }
             RecipeAction::Wait => {
                 let ms = self.ms.unwrap_or(500);
                 build_action(
@@ -172,6 +985,7 @@ fn build_action(
         selector,
         value,
         sensitive: false,
+        secret: false,
         require_confirmation: false,
         description: Some(description),
         domain: None,
@@ -189,6 +1003,12 @@ pub struct GoalStep {
     /// Optional per-goal step cap (defaults to the run-wide max).
     #[serde(default)]
     pub max_steps: Option<usize>,
+    /// Verify the goal before accepting it (implied by non-empty `success`).
+    #[serde(default)]
+    pub verify: bool,
+    /// Explicit success criteria checked when the agent finishes.
+    #[serde(default)]
+    pub success: Vec<SuccessCriterion>,
 }
 
 /// Load a recipe from `path`, with bare-name resolution.
@@ -196,14 +1016,56 @@ pub struct GoalStep {
 /// If `path` contains no path separator and is not an existing file, falls back
 /// to `automation/recipes/<path>.json` (relative to the current directory).
 pub fn load_recipe(path: &str) -> Result<Recipe> {
-    let resolved = resolve_recipe_path(path);
-    let raw = std::fs::read_to_string(&resolved)
+    load_recipe_file(&resolve_recipe_path(path))
+}
+
+/// Directory bare recipe names resolve in: `automation/recipes/` under the
+/// current directory.
+pub fn recipes_dir() -> PathBuf {
+    std::env::current_dir()
+        .unwrap_or_else(|_| PathBuf::from("."))
+        .join("automation")
+        .join("recipes")
+}
+
+/// Load the recipe called `name` (with or without `.json`) from `dir`. Only
+/// bare names are accepted, so callers that take names from the network
+/// cannot reach files outside `dir`.
+pub fn load_named_recipe(dir: &Path, name: &str) -> Result<Recipe> {
+    let stem = name.strip_suffix(".json").unwrap_or(name);
+    let bare = !stem.is_empty()
+        && !stem.starts_with('.')
+        && stem
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+    if !bare {
+        bail!(
+            "recipe name `{name}` must be a bare name from {}",
+            dir.display()
+        );
+    }
+    let path = dir.join(format!("{stem}.json"));
+    let inside = match (path.canonicalize(), dir.canonicalize()) {
+        (Ok(path), Ok(dir)) => path.starts_with(dir),
+        _ => bail!("no recipe named `{stem}` in {}", dir.display()),
+    };
+    if !inside {
+        bail!("recipe `{stem}` resolves outside {}", dir.display());
+    }
+    load_recipe_file(&path)
+}
+
+fn load_recipe_file(resolved: &Path) -> Result<Recipe> {
+    let raw = std::fs::read_to_string(resolved)
         .with_context(|| format!("failed to read recipe {}", resolved.display()))?;
     let recipe: Recipe = serde_json::from_str(&raw)
         .with_context(|| format!("failed to parse recipe {}", resolved.display()))?;
     if recipe.steps.is_empty() {
         anyhow::bail!("recipe {} has no steps", resolved.display());
     }
+    recipe
+        .validate()
+        .with_context(|| format!("invalid recipe {}", resolved.display()))?;
     Ok(recipe)
 }
 
@@ -230,123 +1092,443 @@ fn resolve_recipe_path_with_base(base: &Path, path: &str) -> PathBuf {
 /// Run a hybrid `recipe`, returning a combined [`AgentOutcome`].
 ///
 /// Explicit actions go through `orchestrator.execute_action_with` (execute mode)
-/// or are recorded as non-mutating previews; High/Critical-risk actions prompt
-/// for confirmation unless `auto_confirm`. Goal steps reuse [`BrowserAgent`] and
+/// or are recorded as non-mutating previews; High/Critical-risk actions are
+/// confirmed according to `confirm`. Goal steps reuse [`BrowserAgent`] and
 /// have their steps flattened (re-indexed) into the combined outcome. Execution
-/// stops on the first failed executed action.
+/// stops on the first failed executed action that no enclosing `retry` or
+/// `on_error` block recovers; every recorded step carries its dotted path.
+/// Assertion results only cover the attempts that stand: those from retried
+/// attempts and from `try` blocks replaced by their handler are dropped.
+///
+/// `vars` holds the resolved parameters (see [`Recipe::resolve_params`]); steps
+/// with `save_as` add to a run-local copy. In preview mode saved variables are
+/// bound to their own `{{name}}` placeholder.
 #[allow(clippy::too_many_arguments)]
 pub fn run_recipe<H: AiHttp>(
     recipe: &Recipe,
+    vars: &RecipeVars,
     orchestrator: Arc<AutomationOrchestrator>,
     max_steps: usize,
     execute: bool,
-    auto_confirm: bool,
+    confirm: ConfirmPolicy,
     driver: &dyn BrowserDriver,
     provider: Option<&str>,
     http: &H,
     cancel: &AtomicBool,
 ) -> Result<AgentOutcome> {
-    let mut steps: Vec<AgentStep> = Vec::new();
-    let mut summary_parts: Vec<String> = Vec::new();
-    let mut completed = true;
-    let mut index = 1usize;
+    let mut run = RecipeRun {
+        orchestrator,
+        max_steps,
+        execute,
+        confirm,
+        driver,
+        provider,
+        http,
+        cancel,
+        vars: vars.clone(),
+        steps: Vec::new(),
+        assertions: Vec::new(),
+        summary_parts: Vec::new(),
+    };
 
     // Optional recipe-level start URL, applied as the first navigate action.
     if let Some(url) = recipe.start_url.as_deref().filter(|s| !s.is_empty()) {
-        let action = WebAction::navigate(url).with_description(format!("navigate to {url}"));
-        let result = apply_action(&orchestrator, &action, execute, auto_confirm, driver)?;
+        let (url, secret) = run.vars.interpolate(url)?;
+        let mut action = WebAction::navigate(&url)
+            .with_description(format!("navigate to {url}"))
+            .with_domain_from(driver);
+        action.secret = secret;
+        let result = run.apply(&action)?;
         let ok = result.success;
-        steps.push(AgentStep {
-            index,
-            observation: "recipe start_url".to_string(),
-            action,
-            result,
-        });
-        index += 1;
+        run.record("start", "recipe start_url".to_string(), &action, result);
         if execute && !ok {
-            completed = false;
-            return Ok(finish(recipe, execute, steps, completed, summary_parts));
+            return Ok(finish(recipe, run, false));
         }
     }
 
-    for step in &recipe.steps {
-        if cancel.load(std::sync::atomic::Ordering::Relaxed) {
-            completed = false;
-            summary_parts.push("Run cancelled".to_string());
-            break;
+    let completed = match run.run_block(&recipe.steps, "")? {
+        Flow::Ok => true,
+        Flow::Incomplete => false,
+        Flow::Failed(path) => {
+            run.summary_parts
+                .push(format!("Stopped at failed step {path}"));
+            false
+        }
+        Flow::Cancelled => {
+            run.summary_parts.push("Run cancelled".to_string());
+            false
         }
+    };
+
+    let mut outcome = finish(recipe, run, completed);
+    outcome.har = save_har(driver, &format!("recipe-{}", outcome.id));
+    Ok(outcome)
+}
+
+/// How a step or block ended.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Flow {
+    Ok,
+    /// A goal step finished without completing; the run continues.
+    Incomplete,
+    /// An executed action failed at this step path.
+    Failed(String),
+    Cancelled,
+}
 
+/// Mutable state of one [`run_recipe`] call.
+struct RecipeRun<'a, H: AiHttp> {
+    orchestrator: Arc<AutomationOrchestrator>,
+    max_steps: usize,
+    execute: bool,
+    confirm: ConfirmPolicy,
+    driver: &'a dyn BrowserDriver,
+    provider: Option<&'a str>,
+    http: &'a H,
+    cancel: &'a AtomicBool,
+    vars: RecipeVars,
+    steps: Vec<AgentStep>,
+    assertions: Vec<AssertionResult>,
+    summary_parts: Vec<String>,
+}
+
+impl<H: AiHttp> RecipeRun<'_, H> {
+    /// Run `steps` in order, stopping at the first failure or cancellation.
+    fn run_block(&mut self, steps: &[RecipeStep], prefix: &str) -> Result<Flow> {
+        let mut flow = Flow::Ok;
+        for (i, step) in steps.iter().enumerate() {
+            if self.cancel.load(std::sync::atomic::Ordering::Relaxed) {
+                return Ok(Flow::Cancelled);
+            }
+            match self.run_step(step, &step_path(prefix, i))? {
+                Flow::Ok => {}
+                Flow::Incomplete => flow = Flow::Incomplete,
+                stop => return Ok(stop),
+            }
+        }
+        Ok(flow)
+    }
+
+    fn run_step(&mut self, step: &RecipeStep, path: &str) -> Result<Flow> {
         match step {
             RecipeStep::Action(action_step) => {
-                let action = action_step.to_web_action()?;
-                let result = apply_action(&orchestrator, &action, execute, auto_confirm, driver)?;
+                let action = action_step
+                    .render(&self.vars)
+                    .with_context(|| format!("step {path}"))?
+                    .with_domain_from(self.driver);
+                let result = self.apply(&action)?;
                 let ok = result.success;
-                steps.push(AgentStep {
-                    index,
-                    observation: format!("recipe action: {}", describe_action(&action)),
-                    action,
-                    result,
-                });
-                index += 1;
-                if execute && !ok {
-                    completed = false;
-                    summary_parts.push("Stopped after a failed action".to_string());
-                    break;
+                if let Some(name) = &action_step.save_as {
+                    let value = match (&result.data, self.execute) {
+                        (Some(data), true) if ok => data.clone(),
+                        _ => format!("{{{{{name}}}}}"),
+                    };
+                    self.vars.set(name, value);
                 }
-            }
-            RecipeStep::Goal(goal_step) => {
-                let agent = BrowserAgent::new(
-                    orchestrator.clone(),
-                    goal_step.max_steps.unwrap_or(max_steps),
-                    execute,
-                    auto_confirm,
-                    None,
+                let observation = format!(
+                    "recipe action: {}",
+                    describe_action(&self.vars.redact_action(&action))
                 );
-                let outcome = agent.run(
-                    &goal_step.goal,
-                    goal_step.start_url.as_deref(),
-                    driver,
-                    provider,
-                    http,
-                    cancel,
-                )?;
-                for mut sub in outcome.steps {
-                    sub.index = index;
-                    index += 1;
-                    steps.push(sub);
+                self.record(path, observation, &action, result);
+                Ok(if self.execute && !ok {
+                    Flow::Failed(path.to_string())
+                } else {
+                    Flow::Ok
+                })
+            }
+            RecipeStep::Goal(goal_step) => self.run_goal(goal_step, path),
+            RecipeStep::If(step) => {
+                let condition = render_criterion(&step.condition, &self.vars)?;
+                let (holds, _) = condition.evaluate(self.driver);
+                if holds {
+                    self.run_block(&step.then, &format!("{path}.then"))
+                } else {
+                    self.run_block(&step.otherwise, &format!("{path}.else"))
                 }
-                summary_parts.push(format!("[goal] {}: {}", goal_step.goal, outcome.summary));
-                if !outcome.completed {
-                    completed = false;
+            }
+            RecipeStep::ForEach(step) => {
+                let items = self.for_each_items(&step.for_each)?;
+                let mut flow = Flow::Ok;
+                for (n, item) in items.into_iter().take(step.max).enumerate() {
+                    self.vars.set(&step.binding, item);
+                    self.vars
+                        .set(format!("{}_index", step.binding), (n + 1).to_string());
+                    match self.run_block(&step.steps, &iteration_path(path, n + 1))? {
+                        Flow::Ok => {}
+                        Flow::Incomplete => flow = Flow::Incomplete,
+                        stop => return Ok(stop),
+                    }
+                }
+                Ok(flow)
+            }
+            RecipeStep::Retry(step) => {
+                let attempts = step.attempts.max(1);
+                let first_assertion = self.assertions.len();
+                let mut flow = Flow::Ok;
+                for attempt in 1..=attempts {
+                    // Assertions from an attempt that is retried do not count.
+                    self.assertions.truncate(first_assertion);
+                    flow = self.run_block(&step.retry, &attempt_path(path, attempt))?;
+                    match flow {
+                        Flow::Ok => {
+                            if attempt > 1 {
+                                self.summary_parts.push(format!(
+                                    "[retry] step {path} succeeded on attempt {attempt}"
+                                ));
+                            }
+                            return Ok(Flow::Ok);
+                        }
+                        Flow::Cancelled => return Ok(flow),
+                        Flow::Incomplete | Flow::Failed(_) if attempt < attempts => {
+                            // Sleep in slices so a cancelled run does not wait out
+                            // the backoff; the next attempt then sees the flag.
+                            let deadline =
+                                std::time::Instant::now() + retry_delay(step.backoff_ms, attempt);
+                            while !self.cancel.load(std::sync::atomic::Ordering::Relaxed) {
+                                let left =
+                                    deadline.saturating_duration_since(std::time::Instant::now());
+                                if left.is_zero() {
+                                    break;
+                                }
+                                std::thread::sleep(left.min(RETRY_CANCEL_POLL));
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+                self.summary_parts.push(format!(
+                    "[retry] step {path} gave up after {attempts} attempt(s)"
+                ));
+                Ok(flow)
+            }
+            RecipeStep::Assert(step) => self.run_assert(step, path),
+            RecipeStep::OnError(step) => {
+                let first_assertion = self.assertions.len();
+                match self.run_block(&step.body, &format!("{path}.try"))? {
+                    Flow::Failed(failed) => {
+                        self.summary_parts
+                            .push(format!("[on_error] step {failed} failed; running handler"));
+                        // The handler replaces the `try` block, assertions included.
+                        self.assertions.truncate(first_assertion);
+                        self.run_block(&step.on_error, &format!("{path}.on_error"))
+                    }
+                    Flow::Incomplete => {
+                        self.summary_parts.push(format!(
+                            "[on_error] step {path} incomplete; running handler"
+                        ));
+                        self.assertions.truncate(first_assertion);
+                        self.run_block(&step.on_error, &format!("{path}.on_error"))
+                    }
+                    flow => Ok(flow),
                 }
             }
         }
     }
 
-    Ok(finish(recipe, execute, steps, completed, summary_parts))
+    fn run_goal(&mut self, goal_step: &GoalStep, path: &str) -> Result<Flow> {
+        let mut agent = BrowserAgent::new(
+            self.orchestrator.clone(),
+            goal_step.max_steps.unwrap_or(self.max_steps),
+            self.execute,
+            false,
+            None,
+        )
+        .with_confirm_policy(self.confirm);
+        if goal_step.verify || !goal_step.success.is_empty() {
+            agent = agent.with_verification(goal_step.success.clone(), DEFAULT_VERIFY_RETRIES);
+        }
+        let (goal, secret) = self.vars.interpolate(&goal_step.goal)?;
+        if secret {
+            bail!("step {path}: goal steps cannot reference secret parameters");
+        }
+        let start_url = goal_step
+            .start_url
+            .as_deref()
+            .map(|url| self.vars.interpolate(url).map(|(url, _)| url))
+            .transpose()?;
+        let outcome = match agent.run(
+            &goal,
+            start_url.as_deref(),
+            self.driver,
+            self.provider,
+            self.http,
+            self.cancel,
+        ) {
+            Ok(outcome) => outcome,
+            // Planner, HTTP and observe errors are usually transient: fail
+            // the step so an enclosing `retry` or `on_error` can handle it.
+            Err(err) => {
+                let action = build_action(ActionType::Wait, None, None, format!("goal {goal}"));
+                let message = format!("{err:#}");
+                self.summary_parts
+                    .push(format!("[goal] step {path} failed: {message}"));
+                let result = ActionResult {
+                    action_id: action.id,
+                    success: false,
+                    data: None,
+                    error: Some(message),
+                    latency_ms: 0,
+                    timestamp: Utc::now(),
+                };
+                self.record(path, format!("recipe goal: {goal}"), &action, result);
+                return Ok(Flow::Failed(path.to_string()));
+            }
+        };
+        for mut sub in outcome.steps {
+            sub.path = Some(format!("{path}.{}", sub.index));
+            sub.index = self.steps.len() + 1;
+            self.steps.push(sub);
+        }
+        self.summary_parts
+            .push(format!("[goal] {goal}: {}", outcome.summary));
+        Ok(if outcome.completed {
+            Flow::Ok
+        } else {
+            Flow::Incomplete
+        })
+    }
+
+    /// Evaluate an `assert` step. Previews record it as skipped, since the
+    /// page never reached the asserted state.
+    fn run_assert(&mut self, step: &AssertStep, path: &str) -> Result<Flow> {
+        let assertion = step
+            .assert
+            .render(&self.vars)
+            .with_context(|| format!("step {path}"))?;
+        let description = assertion.describe();
+        let (status, actual) = if self.execute {
+            let (held, actual) = assertion.evaluate(self.driver)?;
+            let status = if held {
+                AssertionStatus::Passed
+            } else {
+                AssertionStatus::Failed
+            };
+            (status, actual)
+        } else {
+            (AssertionStatus::Skipped, None)
+        };
+        if status == AssertionStatus::Failed {
+            let mut line = format!("[assert] step {path} failed: {description}");
+            if let Some(actual) = &actual {
+                line.push_str(&format!(" (actual: {actual})"));
+            }
+            if let Some(message) = &step.message {
+                line.push_str(&format!(" — {message}"));
+            }
+            self.summary_parts.push(line);
+        }
+        self.assertions.push(AssertionResult {
+            path: path.to_string(),
+            description,
+            status,
+            actual,
+            message: step.message.clone(),
+        });
+        Ok(if status == AssertionStatus::Failed {
+            Flow::Failed(path.to_string())
+        } else {
+            Flow::Ok
+        })
+    }
+
+    /// Items a `for_each` iterates over. A selector matching nothing yields no
+    /// items rather than an error.
+    fn for_each_items(&self, source: &ForEachSource) -> Result<Vec<String>> {
+        if let Some(selector) = &source.selector {
+            let (selector, _) = self.vars.interpolate(selector)?;
+            return Ok(self.driver.extract_all(&selector).unwrap_or_default());
+        }
+        let (raw, _) = self
+            .vars
+            .interpolate(source.items.as_deref().unwrap_or_default())?;
+        Ok(split_items(&raw))
+    }
+
+    fn apply(&self, action: &WebAction) -> Result<ActionResult> {
+        apply_action(
+            &self.orchestrator,
+            action,
+            self.execute,
+            self.confirm,
+            self.driver,
+        )
+    }
+
+    /// Append an [`AgentStep`] for `action` (secret values redacted).
+    fn record(
+        &mut self,
+        path: &str,
+        observation: String,
+        action: &WebAction,
+        result: ActionResult,
+    ) {
+        self.steps.push(AgentStep {
+            index: self.steps.len() + 1,
+            observation,
+            action: self.vars.redact_action(action),
+            result,
+            path: Some(path.to_string()),
+        });
+    }
+}
+
+/// Split a `for_each` items value: a JSON array (strings kept verbatim, other
+/// values as JSON) or else non-empty lines.
+fn split_items(raw: &str) -> Vec<String> {
+    if let Ok(Value::Array(items)) = serde_json::from_str::<Value>(raw) {
+        return items
+            .into_iter()
+            .map(|item| match item {
+                Value::String(s) => s,
+                other => other.to_string(),
+            })
+            .collect();
+    }
+    raw.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(String::from)
+        .collect()
+}
+
+/// `criterion` with `{{var}}` references substituted.
+fn render_criterion(criterion: &SuccessCriterion, vars: &RecipeVars) -> Result<SuccessCriterion> {
+    let render = |text: &str| vars.interpolate(text).map(|(text, _)| text);
+    Ok(match criterion {
+        SuccessCriterion::TextPresent { text } => SuccessCriterion::TextPresent {
+            text: render(text)?,
+        },
+        SuccessCriterion::UrlMatches { pattern } => SuccessCriterion::UrlMatches {
+            pattern: render(pattern)?,
+        },
+        SuccessCriterion::ElementPresent { selector } => SuccessCriterion::ElementPresent {
+            selector: render(selector)?,
+        },
+        SuccessCriterion::ElementText { selector, contains } => SuccessCriterion::ElementText {
+            selector: render(selector)?,
+            contains: render(contains)?,
+        },
+    })
 }
 
 /// Assemble the final [`AgentOutcome`] from accumulated state.
-fn finish(
-    recipe: &Recipe,
-    execute: bool,
-    steps: Vec<AgentStep>,
-    completed: bool,
-    summary_parts: Vec<String>,
-) -> AgentOutcome {
-    let summary = if summary_parts.is_empty() {
-        format!("Ran {} step(s)", steps.len())
+fn finish<H: AiHttp>(recipe: &Recipe, run: RecipeRun<'_, H>, completed: bool) -> AgentOutcome {
+    let summary = if run.summary_parts.is_empty() {
+        format!("Ran {} step(s)", run.steps.len())
     } else {
-        summary_parts.join("\n")
+        run.summary_parts.join("\n")
     };
-    AgentOutcome {
+    run.vars.redact_outcome(AgentOutcome {
         id: Uuid::new_v4(),
         goal: recipe.goal_label(),
-        executed: execute,
-        steps,
+        executed: run.execute,
+        steps: run.steps,
         completed,
         summary,
-    }
+        verification: None,
+        har: None,
+        assertions: run.assertions,
+    })
 }
 
 /// Execute an action (with risk-gated confirmation) or record a preview.
@@ -354,17 +1536,34 @@ fn apply_action(
     orchestrator: &AutomationOrchestrator,
     action: &WebAction,
     execute: bool,
-    auto_confirm: bool,
+    confirm_policy: ConfirmPolicy,
     driver: &dyn BrowserDriver,
 ) -> Result<ActionResult> {
     if !execute {
         return Ok(preview_result(action, "preview: not executed"));
     }
-    let risk = action.risk_level();
-    if risk.requires_confirmation() && !auto_confirm && !confirm(action, risk) {
-        return Ok(preview_result(action, "declined by user"));
-    }
-    orchestrator.execute_action_with(action, driver)
+    let validation = orchestrator.validate_action(action);
+    let risk = validation.risk_level;
+    let confirmation = if !validation.requires_confirmation {
+        ConfirmationDecision::NotRequired
+    } else {
+        match confirm_policy {
+            ConfirmPolicy::AutoApprove => ConfirmationDecision::AutoApproved,
+            ConfirmPolicy::Prompt if confirm(action, risk) => ConfirmationDecision::Confirmed,
+            ConfirmPolicy::Prompt => {
+                orchestrator.record_declined(action, &validation);
+                return Ok(preview_result(action, "declined by user"));
+            }
+            ConfirmPolicy::Decline => {
+                orchestrator.record_declined(action, &validation);
+                return Ok(preview_result(
+                    action,
+                    "declined: needs confirmation and nobody can confirm this run",
+                ));
+            }
+        }
+    };
+    orchestrator.execute_action_confirmed(action, driver, &validation, confirmation)
 }
 
 fn confirm(action: &WebAction, risk: RiskLevel) -> bool {
@@ -407,6 +1606,7 @@ fn describe_action(action: &WebAction) -> String {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::agent::render_markdown;
     use crate::ai::{AiBridge, AiHttp};
     use crate::browser::{ElementSummary, PageObservation};
     use crate::config::{AiSettings, AutomationSettings};
@@ -418,6 +1618,8 @@ mod tests {
     #[derive(Default)]
     struct StubDriver {
         calls: RefCell<Vec<String>>,
+        /// Clicks on `#flaky*` selectors fail while this is non-zero.
+        flaky_clicks: std::cell::Cell<usize>,
     }
 
     impl StubDriver {
@@ -445,6 +1647,10 @@ mod tests {
         }
         fn click(&self, selector: &str) -> Result<()> {
             self.calls.borrow_mut().push(format!("click:{selector}"));
+            if selector.starts_with("#flaky") && self.flaky_clicks.get() > 0 {
+                self.flaky_clicks.set(self.flaky_clicks.get() - 1);
+                anyhow::bail!("element not clickable");
+            }
             Ok(())
         }
         fn type_text(&self, selector: &str, text: &str) -> Result<()> {
@@ -461,8 +1667,17 @@ mod tests {
         }
         fn extract(&self, selector: &str) -> Result<String> {
             self.calls.borrow_mut().push(format!("extract:{selector}"));
+            if selector.starts_with("#missing") {
+                anyhow::bail!("no element matching selector {selector}");
+            }
             Ok("extracted".into())
         }
+        fn extract_all(&self, selector: &str) -> Result<Vec<String>> {
+            self.calls
+                .borrow_mut()
+                .push(format!("extract_all:{selector}"));
+            Ok(vec!["alpha".into(), "beta".into()])
+        }
         fn screenshot(&self) -> Result<String> {
             self.calls.borrow_mut().push("screenshot".into());
             Ok("/tmp/shot.png".into())
@@ -524,10 +1739,16 @@ mod tests {
                 .borrow_mut()
                 .pop_front()
                 .context("ScriptedAiHttp ran out of replies")?;
+            if reply == HTTP_ERROR {
+                anyhow::bail!("HTTP 503 from {url}");
+            }
             Ok(json!({ "model": "test", "message": { "content": reply } }))
         }
     }
 
+    /// A scripted reply that makes [`ScriptedAiHttp`] fail the request.
+    const HTTP_ERROR: &str = "<http error>";
+
     fn orchestrator(settings: AutomationSettings) -> Arc<AutomationOrchestrator> {
         let root = std::env::temp_dir().join(format!("archon-recipe-test-{}", Uuid::new_v4()));
         let store = TranscriptStore::new(root).expect("transcript store");
@@ -603,6 +1824,17 @@ mod tests {
         let action = wait.to_web_action().unwrap();
         assert_eq!(action.action_type, ActionType::Wait);
         assert_eq!(action.value.as_deref(), Some("250"));
+
+        let response: ActionStep = serde_json::from_str(
+            r#"{ "action": "extract_response", "url": "*/api/cart*" }"#,
+        )
+        .unwrap();
+        let action = response.to_web_action().unwrap();
+        assert_eq!(action.action_type, ActionType::ExtractResponse);
+        assert_eq!(action.value.as_deref(), Some("*/api/cart*"));
+        let missing: ActionStep =
+            serde_json::from_str(r#"{ "action": "extract_response" }"#).unwrap();
+        assert!(missing.to_web_action().is_err());
     }
 
     #[test]
@@ -611,6 +1843,7 @@ mod tests {
             name: "explicit".into(),
             description: None,
             start_url: Some("https://example.test/".into()),
+            params: BTreeMap::new(),
             steps: vec![
                 RecipeStep::Action(ActionStep {
                     action: RecipeAction::Extract,
@@ -619,6 +1852,7 @@ mod tests {
                     text: None,
                     value: None,
                     ms: None,
+                    save_as: None,
                 }),
                 RecipeStep::Action(ActionStep {
                     action: RecipeAction::Click,
@@ -627,6 +1861,7 @@ mod tests {
                     text: None,
                     value: None,
                     ms: None,
+                    save_as: None,
                 }),
             ],
         };
@@ -636,7 +1871,16 @@ mod tests {
         let cancel = AtomicBool::new(false);
 
         let outcome = run_recipe(
-            &recipe, orch, 5, true, true, &driver, None, &http, &cancel,
+            &recipe,
+            &RecipeVars::default(),
+            orch,
+            5,
+            true,
+            ConfirmPolicy::AutoApprove,
+            &driver,
+            None,
+            &http,
+            &cancel,
         )
         .unwrap();
 
@@ -654,6 +1898,7 @@ mod tests {
             name: "preview".into(),
             description: None,
             start_url: Some("https://example.test/".into()),
+            params: BTreeMap::new(),
             steps: vec![RecipeStep::Action(ActionStep {
                 action: RecipeAction::Click,
                 selector: Some("#go".into()),
@@ -661,6 +1906,7 @@ mod tests {
                 text: None,
                 value: None,
                 ms: None,
+                save_as: None,
             })],
         };
         let orch = orchestrator(enabled_settings());
@@ -669,7 +1915,16 @@ mod tests {
         let cancel = AtomicBool::new(false);
 
         let outcome = run_recipe(
-            &recipe, orch, 5, false, false, &driver, None, &http, &cancel,
+            &recipe,
+            &RecipeVars::default(),
+            orch,
+            5,
+            false,
+            ConfirmPolicy::Prompt,
+            &driver,
+            None,
+            &http,
+            &cancel,
         )
         .unwrap();
 
@@ -686,6 +1941,7 @@ mod tests {
             name: "hybrid".into(),
             description: None,
             start_url: None,
+            params: BTreeMap::new(),
             steps: vec![
                 RecipeStep::Action(ActionStep {
                     action: RecipeAction::Extract,
@@ -694,11 +1950,14 @@ mod tests {
                     text: None,
                     value: None,
                     ms: None,
+                    save_as: None,
                 }),
                 RecipeStep::Goal(GoalStep {
                     goal: "read the link".into(),
                     start_url: None,
                     max_steps: Some(3),
+                    verify: false,
+                    success: Vec::new(),
                 }),
             ],
         };
@@ -712,7 +1971,16 @@ mod tests {
         let cancel = AtomicBool::new(false);
 
         let outcome = run_recipe(
-            &recipe, orch, 5, true, true, &driver, None, &http, &cancel,
+            &recipe,
+            &RecipeVars::default(),
+            orch,
+            5,
+            true,
+            ConfirmPolicy::AutoApprove,
+            &driver,
+            None,
+            &http,
+            &cancel,
         )
         .unwrap();
 
@@ -724,6 +1992,656 @@ mod tests {
         assert!(outcome.summary.contains("[goal] read the link"));
     }
 
+    #[test]
+    fn params_resolve_with_types_defaults_and_overrides() {
+        let recipe: Recipe = serde_json::from_str(
+            r##"{
+                "name": "export",
+                "params": {
+                    "account": { "description": "account id" },
+                    "limit": { "type": "number", "default": 10 },
+                    "archived": { "type": "boolean", "default": false }
+                },
+                "steps": [{ "action": "extract", "selector": "#x" }]
+            }"##,
+        )
+        .unwrap();
+        recipe.validate().unwrap();
+
+        let missing = recipe.resolve_params(&BTreeMap::new()).unwrap_err();
+        assert!(missing.to_string().contains("`account`"));
+
+        let args = vec!["account=acme".to_string(), "archived=yes".to_string()];
+        let vars = recipe
+            .resolve_params(&parse_param_args(&args).unwrap())
+            .unwrap();
+        assert_eq!(vars.get("account"), Some("acme"));
+        assert_eq!(vars.get("limit"), Some("10"));
+        assert_eq!(vars.get("archived"), Some("true"));
+
+        let bad = parse_param_args(&["account=a".into(), "limit=lots".into()]).unwrap();
+        assert!(recipe.resolve_params(&bad).is_err());
+        let unknown = parse_param_args(&["account=a".into(), "nope=1".into()]).unwrap();
+        assert!(recipe.resolve_params(&unknown).is_err());
+        assert!(parse_param_args(&["no-equals".into()]).is_err());
+    }
+
+    #[test]
+    fn validate_rejects_unknown_references_and_secret_goals() {
+        let unknown: Recipe = serde_json::from_str(
+            r#"{ "name": "r", "steps": [{ "action": "navigate", "url": "https://x.test/{{day}}" }] }"#,
+        )
+        .unwrap();
+        assert!(
+            unknown
+                .validate()
+                .unwrap_err()
+                .to_string()
+                .contains("{{day}}")
+        );
+
+        // save_as makes a variable available to later steps only.
+        let saved: Recipe = serde_json::from_str(
+            r##"{ "name": "r", "steps": [
+                { "action": "extract", "selector": "#id", "save_as": "order" },
+                { "action": "type", "selector": "#q", "text": "{{order}}" }
+            ] }"##,
+        )
+        .unwrap();
+        saved.validate().unwrap();
+
+        let secret_goal: Recipe = serde_json::from_str(
+            r#"{ "name": "r",
+                 "params": { "token": { "type": "secret", "env": "ARCHON_TEST_TOKEN" } },
+                 "steps": [{ "goal": "log in with {{token}}" }] }"#,
+        )
+        .unwrap();
+        assert!(secret_goal.validate().is_err());
+
+        let secret_default: Recipe = serde_json::from_str(
+            r##"{ "name": "r",
+                 "params": { "token": { "type": "secret", "default": "oops" } },
+                 "steps": [{ "action": "click", "selector": "#a" }] }"##,
+        )
+        .unwrap();
+        assert!(secret_default.validate().is_err());
+    }
+
+    #[test]
+    fn run_recipe_interpolates_saves_and_redacts_secrets() {
+        let recipe: Recipe = serde_json::from_str(
+            r##"{
+                "name": "login",
+                "params": {
+                    "user": {},
+                    "token": { "type": "secret" }
+                },
+                "start_url": "https://example.test/u/{{user}}",
+                "steps": [
+                    { "action": "type", "selector": "#api-key", "text": "{{token}}" },
+                    { "action": "extract", "selector": "#order", "save_as": "order" },
+                    { "action": "type", "selector": "#search", "text": "order {{order}}" }
+                ]
+            }"##,
+        )
+        .unwrap();
+        recipe.validate().unwrap();
+        let mut params = BTreeMap::new();
+        params.insert("user".to_string(), "ada".to_string());
+        params.insert("token".to_string(), "s3cr3t".to_string());
+        let vars = recipe.resolve_params(&params).unwrap();
+        assert_eq!(vars.redacted()["token"], "[redacted]");
+
+        let orch = orchestrator(enabled_settings());
+        let driver = StubDriver::default();
+        let http = ScriptedAiHttp::new(vec![]);
+        let cancel = AtomicBool::new(false);
+        let outcome = run_recipe(
+            &recipe,
+            &vars,
+            orch,
+            5,
+            true,
+            ConfirmPolicy::AutoApprove,
+            &driver,
+            None,
+            &http,
+            &cancel,
+        )
+        .unwrap();
+
+        assert!(outcome.completed, "{}", outcome.summary);
+        let calls = driver.calls();
+        assert!(
+            calls
+                .iter()
+                .any(|c| c == "navigate:https://example.test/u/ada")
+        );
+        assert!(calls.iter().any(|c| c == "type:#api-key=s3cr3t"));
+        assert!(calls.iter().any(|c| c == "type:#search=order extracted"));
+
+        // The recorded transcript never contains the secret value.
+        let recorded = serde_json::to_string(&outcome).unwrap();
+        assert!(!recorded.contains("s3cr3t"));
+        assert_eq!(outcome.steps[1].action.value.as_deref(), Some("{{token}}"));
+        assert!(outcome.steps[1].action.secret);
+    }
+
+    #[test]
+    fn secrets_are_redacted_from_results_and_errors() {
+        let recipe: Recipe = serde_json::from_str(
+            r##"{
+                "name": "lookup",
+                "params": { "token": { "type": "secret" } },
+                "steps": [
+                    { "try": [{ "action": "extract", "selector": "#missing-{{token}}" }],
+                      "on_error": [{ "action": "scroll" }] },
+                    { "assert": { "kind": "value_equals", "selector": "#k", "equals": "{{token}}" } }
+                ]
+            }"##,
+        )
+        .unwrap();
+        recipe.validate().unwrap();
+        let mut params = BTreeMap::new();
+        params.insert("token".to_string(), "s3cr3t".to_string());
+        let vars = recipe.resolve_params(&params).unwrap();
+
+        let driver = StubDriver::default();
+        let outcome = run_recipe(
+            &recipe,
+            &vars,
+            orchestrator(enabled_settings()),
+            5,
+            true,
+            ConfirmPolicy::AutoApprove,
+            &driver,
+            None,
+            &ScriptedAiHttp::new(vec![]),
+            &AtomicBool::new(false),
+        )
+        .unwrap();
+
+        assert!(
+            driver
+                .calls()
+                .iter()
+                .any(|c| c == "extract:#missing-s3cr3t")
+        );
+        let recorded = serde_json::to_string(&outcome).unwrap();
+        assert!(!recorded.contains("s3cr3t"), "{recorded}");
+        assert!(recorded.contains("#missing-{{token}}"), "{recorded}");
+    }
+
+    #[test]
+    fn unattended_runs_decline_actions_needing_confirmation() {
+        let recipe: Recipe = serde_json::from_str(
+            r##"{ "name": "pay", "steps": [ { "action": "click", "selector": "#pay" } ] }"##,
+        )
+        .unwrap();
+        let settings = AutomationSettings {
+            require_confirmation: true,
+            ..enabled_settings()
+        };
+        let driver = StubDriver::default();
+        let outcome = run_recipe(
+            &recipe,
+            &RecipeVars::default(),
+            orchestrator(settings),
+            5,
+            true,
+            ConfirmPolicy::unattended(false),
+            &driver,
+            None,
+            &ScriptedAiHttp::new(vec![]),
+            &AtomicBool::new(false),
+        )
+        .unwrap();
+
+        assert!(!driver.calls().iter().any(|c| c == "click:#pay"));
+        let note = outcome.steps[0].result.data.as_deref().unwrap_or_default();
+        assert!(note.starts_with("declined"), "{note}");
+    }
+
+    #[test]
+    fn global_confirmation_setting_gates_low_risk_recipe_actions() {
+        let recipe: Recipe =
+            serde_json::from_str(r#"{ "name": "s", "steps": [ { "action": "scroll" } ] }"#)
+                .unwrap();
+        let settings = AutomationSettings {
+            require_confirmation: true,
+            ..enabled_settings()
+        };
+        let driver = StubDriver::default();
+        let outcome = run_recipe(
+            &recipe,
+            &RecipeVars::default(),
+            orchestrator(settings),
+            5,
+            true,
+            ConfirmPolicy::unattended(false),
+            &driver,
+            None,
+            &ScriptedAiHttp::new(vec![]),
+            &AtomicBool::new(false),
+        )
+        .unwrap();
+
+        assert!(!driver.calls().iter().any(|c| c.starts_with("scroll")));
+        let note = outcome.steps[0].result.data.as_deref().unwrap_or_default();
+        assert!(note.starts_with("declined"), "{note}");
+    }
+
+    #[test]
+    fn named_recipes_stay_inside_the_recipes_dir() {
+        let base = tempfile::tempdir().unwrap();
+        let dir = base.path().join("recipes");
+        std::fs::create_dir_all(&dir).unwrap();
+        let recipe = r#"{ "name": "demo", "steps": [ { "action": "scroll" } ] }"#;
+        std::fs::write(dir.join("demo.json"), recipe).unwrap();
+        std::fs::write(base.path().join("outside.json"), recipe).unwrap();
+
+        assert_eq!(load_named_recipe(&dir, "demo").unwrap().name, "demo");
+        assert_eq!(load_named_recipe(&dir, "demo.json").unwrap().name, "demo");
+        for name in [
+            "../outside",
+            "sub/demo",
+            "/etc/passwd",
+            "",
+            ".hidden",
+            "nope",
+        ] {
+            assert!(load_named_recipe(&dir, name).is_err(), "{name}");
+        }
+    }
+
+    fn run_json(json: &str, driver: &StubDriver, execute: bool) -> AgentOutcome {
+        let recipe: Recipe = serde_json::from_str(json).unwrap();
+        recipe.validate().unwrap();
+        let orch = orchestrator(enabled_settings());
+        let http = ScriptedAiHttp::new(vec![]);
+        let cancel = AtomicBool::new(false);
+        run_recipe(
+            &recipe,
+            &RecipeVars::default(),
+            orch,
+            5,
+            execute,
+            ConfirmPolicy::AutoApprove,
+            driver,
+            None,
+            &http,
+            &cancel,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn control_flow_steps_parse_and_validate() {
+        let recipe: Recipe = serde_json::from_str(
+            r##"{ "name": "flow", "steps": [
+                { "if": { "kind": "element_present", "selector": "#cookies" },
+                  "then": [{ "action": "click", "selector": "#accept" }] },
+                { "for_each": { "selector": "tr" }, "as": "row",
+                  "steps": [{ "action": "extract", "selector": "tr:nth-child({{row_index}})" }] },
+                { "retry": [{ "action": "click", "selector": "#go" }], "attempts": 2 },
+                { "try": [{ "action": "click", "selector": "#a" }],
+                  "on_error": [{ "action": "click", "selector": "#b" }] }
+            ] }"##,
+        )
+        .unwrap();
+        assert!(matches!(recipe.steps[0], RecipeStep::If(_)));
+        assert!(matches!(recipe.steps[1], RecipeStep::ForEach(_)));
+        assert!(matches!(recipe.steps[2], RecipeStep::Retry(_)));
+        assert!(matches!(recipe.steps[3], RecipeStep::OnError(_)));
+        recipe.validate().unwrap();
+
+        let invalid = [
+            r##"{ "name": "r", "steps": [{ "retry": [{ "action": "click", "selector": "#a" }], "attempts": 0 }] }"##,
+            r##"{ "name": "r", "steps": [{ "for_each": { "selector": "tr", "items": "x" }, "steps": [{ "action": "click", "selector": "#a" }] }] }"##,
+            r##"{ "name": "r", "steps": [{ "if": { "kind": "url_matches", "pattern": "*" }, "then": [] }] }"##,
+            r##"{ "name": "r", "steps": [{ "if": { "kind": "url_matches", "pattern": "*" },
+                 "then": [{ "action": "click", "selector": "#{{nope}}" }] }] }"##,
+        ];
+        for json in invalid {
+            let recipe: Recipe = serde_json::from_str(json).unwrap();
+            let err = recipe.validate().unwrap_err().to_string();
+            assert!(err.contains("step 1"), "{err}");
+        }
+
+        // Nested problems are reported at the paths recorded at run time.
+        let nested = [
+            (
+                r##"{ "name": "r", "steps": [{ "for_each": { "selector": "tr" },
+                     "steps": [{ "action": "click", "selector": "#{{nope}}" }] }] }"##,
+                "step 1.iter1.1",
+            ),
+            (
+                r##"{ "name": "r", "steps": [{ "retry": [{ "action": "click", "selector": "#{{nope}}" }] }] }"##,
+                "step 1.attempt1.1",
+            ),
+        ];
+        for (json, path) in nested {
+            let recipe: Recipe = serde_json::from_str(json).unwrap();
+            let err = recipe.validate().unwrap_err().to_string();
+            assert!(err.starts_with(path), "{err}");
+        }
+    }
+
+    #[test]
+    fn branch_and_loop_variables_stay_in_scope() {
+        let validate = |json: &str| serde_json::from_str::<Recipe>(json).unwrap().validate();
+
+        // Bound on one arm only: unknown after the `if`.
+        let err = validate(
+            r##"{ "name": "r", "steps": [
+                { "if": { "kind": "element_present", "selector": "#a" },
+                  "then": [{ "action": "extract", "selector": "#a", "save_as": "v" }] },
+                { "action": "type", "selector": "#q", "text": "{{v}}" }
+            ] }"##,
+        )
+        .unwrap_err();
+        assert!(err.to_string().starts_with("step 2"), "{err}");
+
+        // Bound on both arms: available afterwards.
+        validate(
+            r##"{ "name": "r", "steps": [
+                { "if": { "kind": "element_present", "selector": "#a" },
+                  "then": [{ "action": "extract", "selector": "#a", "save_as": "v" }],
+                  "else": [{ "action": "extract", "selector": "#b", "save_as": "v" }] },
+                { "action": "type", "selector": "#q", "text": "{{v}}" }
+            ] }"##,
+        )
+        .unwrap();
+
+        // Loop bindings and body variables do not leak out of the loop.
+        for text in ["{{row}}", "{{row_index}}", "{{cell}}"] {
+            let json = format!(
+                r##"{{ "name": "r", "steps": [
+                    {{ "for_each": {{ "selector": "tr" }}, "as": "row",
+                      "steps": [{{ "action": "extract", "selector": "td", "save_as": "cell" }}] }},
+                    {{ "action": "type", "selector": "#q", "text": "{text}" }}
+                ] }}"##
+            );
+            assert!(validate(&json).is_err(), "{text}");
+        }
+
+        // The handler cannot see what `try` saved, and only names bound by
+        // both survive.
+        assert!(
+            validate(
+                r##"{ "name": "r", "steps": [
+                    { "try": [{ "action": "extract", "selector": "#a", "save_as": "v" }],
+                      "on_error": [{ "action": "type", "selector": "#q", "text": "{{v}}" }] }
+                ] }"##,
+            )
+            .is_err()
+        );
+        validate(
+            r##"{ "name": "r", "steps": [
+                { "try": [{ "action": "extract", "selector": "#a", "save_as": "v" }],
+                  "on_error": [{ "action": "extract", "selector": "#b", "save_as": "v" }] },
+                { "action": "type", "selector": "#q", "text": "{{v}}" }
+            ] }"##,
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn if_and_for_each_record_step_paths() {
+        let driver = StubDriver::default();
+        let outcome = run_json(
+            r##"{ "name": "flow", "steps": [
+                { "if": { "kind": "element_present", "selector": "#missing-banner" },
+                  "then": [{ "action": "click", "selector": "#dismiss" }],
+                  "else": [{ "action": "click", "selector": "#continue" }] },
+                { "for_each": { "selector": "tr" }, "as": "row",
+                  "steps": [{ "action": "type", "selector": "#q", "text": "{{row_index}}:{{row}}" }] }
+            ] }"##,
+            &driver,
+            true,
+        );
+
+        assert!(outcome.completed, "{}", outcome.summary);
+        let calls = driver.calls();
+        assert!(calls.iter().any(|c| c == "click:#continue"));
+        assert!(!calls.iter().any(|c| c == "click:#dismiss"));
+        assert!(calls.iter().any(|c| c == "type:#q=1:alpha"));
+        assert!(calls.iter().any(|c| c == "type:#q=2:beta"));
+        let paths: Vec<_> = outcome
+            .steps
+            .iter()
+            .map(|s| s.path.clone().unwrap())
+            .collect();
+        assert_eq!(paths, vec!["1.else.1", "2.iter1.1", "2.iter2.1"]);
+        assert_eq!(outcome.steps[2].index, 3);
+    }
+
+    #[test]
+    fn failed_goal_requests_are_retried() {
+        let recipe: Recipe = serde_json::from_str(
+            r##"{ "name": "flaky model", "steps": [
+                { "retry": [{ "goal": "read the link", "max_steps": 3 }], "attempts": 2, "backoff_ms": 0 }
+            ] }"##,
+        )
+        .unwrap();
+        let driver = StubDriver::default();
+        let http = ScriptedAiHttp::new(vec![
+            HTTP_ERROR,
+            r#"{"action_type":"finish","description":"done"}"#,
+        ]);
+        let cancel = AtomicBool::new(false);
+
+        let outcome = run_recipe(
+            &recipe,
+            &RecipeVars::default(),
+            orchestrator(enabled_settings()),
+            5,
+            true,
+            ConfirmPolicy::AutoApprove,
+            &driver,
+            None,
+            &http,
+            &cancel,
+        )
+        .unwrap();
+
+        assert!(outcome.completed, "{}", outcome.summary);
+        assert_eq!(outcome.steps.len(), 1);
+        assert_eq!(outcome.steps[0].path.as_deref(), Some("1.attempt1.1"));
+        assert!(!outcome.steps[0].result.success);
+        assert!(
+            outcome.steps[0]
+                .result
+                .error
+                .as_deref()
+                .is_some_and(|err| err.contains("HTTP 503"))
+        );
+        assert!(outcome.summary.contains("succeeded on attempt 2"));
+    }
+
+    #[test]
+    fn retry_backoff_is_capped_and_cancellable() {
+        assert_eq!(retry_delay(1_000, 1), std::time::Duration::from_secs(1));
+        assert_eq!(retry_delay(1_000, 3), std::time::Duration::from_secs(4));
+        assert_eq!(retry_delay(1_000, 10), MAX_RETRY_BACKOFF);
+        assert_eq!(retry_delay(u64::MAX, 10), MAX_RETRY_BACKOFF);
+
+        let recipe: Recipe = serde_json::from_str(
+            r##"{ "name": "slow", "steps": [
+                { "retry": [{ "action": "click", "selector": "#flaky" }], "attempts": 3, "backoff_ms": 60000 }
+            ] }"##,
+        )
+        .unwrap();
+        let driver = StubDriver::default();
+        driver.flaky_clicks.set(10);
+        let cancel = AtomicBool::new(false);
+        let started = std::time::Instant::now();
+        let outcome = std::thread::scope(|scope| {
+            scope.spawn(|| {
+                std::thread::sleep(std::time::Duration::from_millis(200));
+                cancel.store(true, std::sync::atomic::Ordering::Relaxed);
+            });
+            run_recipe(
+                &recipe,
+                &RecipeVars::default(),
+                orchestrator(enabled_settings()),
+                5,
+                true,
+                ConfirmPolicy::AutoApprove,
+                &driver,
+                None,
+                &ScriptedAiHttp::new(vec![]),
+                &cancel,
+            )
+            .unwrap()
+        });
+        assert!(started.elapsed() < std::time::Duration::from_secs(5));
+        assert!(!outcome.completed);
+        assert_eq!(
+            driver.calls().iter().filter(|c| *c == "click:#flaky").count(),
+            1
+        );
+    }
+
+    #[test]
+    fn retry_and_on_error_recover_from_failures() {
+        let driver = StubDriver::default();
+        driver.flaky_clicks.set(1);
+        let outcome = run_json(
+            r##"{ "name": "flow", "steps": [
+                { "retry": [{ "action": "click", "selector": "#flaky" }], "attempts": 3, "backoff_ms": 1 },
+                { "try": [{ "action": "click", "selector": "#flaky-again" }],
+                  "on_error": [{ "action": "click", "selector": "#fallback" }] }
+            ] }"##,
+            &driver,
+            true,
+        );
+        // Only the first #flaky click failed, so the try block succeeds.
+        assert!(outcome.completed, "{}", outcome.summary);
+        assert!(outcome.summary.contains("succeeded on attempt 2"));
+        assert!(!driver.calls().iter().any(|c| c == "click:#fallback"));
+
+        let driver = StubDriver::default();
+        driver.flaky_clicks.set(10);
+        let outcome = run_json(
+            r##"{ "name": "flow", "steps": [
+                { "try": [{ "action": "click", "selector": "#flaky" }],
+                  "on_error": [{ "action": "click", "selector": "#fallback" }] },
+                { "retry": [{ "action": "click", "selector": "#flaky" }], "attempts": 2, "backoff_ms": 1 },
+                { "action": "click", "selector": "#never" }
+            ] }"##,
+            &driver,
+            true,
+        );
+        assert!(!outcome.completed);
+        assert!(driver.calls().iter().any(|c| c == "click:#fallback"));
+        assert!(!driver.calls().iter().any(|c| c == "click:#never"));
+        assert!(
+            outcome.summary.contains("step 2.attempt2.1"),
+            "{}",
+            outcome.summary
+        );
+        let paths: Vec<_> = outcome
+            .steps
+            .iter()
+            .map(|s| s.path.clone().unwrap())
+            .collect();
+        assert_eq!(
+            paths,
+            vec!["1.try.1", "1.on_error.1", "2.attempt1.1", "2.attempt2.1"]
+        );
+    }
+
+    #[test]
+    fn recovered_attempts_drop_their_assertions() {
+        let driver = StubDriver::default();
+        driver.flaky_clicks.set(1);
+        let outcome = run_json(
+            r##"{ "name": "flow", "steps": [
+                { "try": [{ "assert": { "kind": "value_equals", "selector": "#total", "equals": "42" } }],
+                  "on_error": [{ "action": "click", "selector": "#fallback" }] },
+                { "retry": [
+                    { "assert": { "kind": "text_present", "text": "body" } },
+                    { "action": "click", "selector": "#flaky" }
+                  ], "attempts": 2, "backoff_ms": 1 }
+            ] }"##,
+            &driver,
+            true,
+        );
+        assert!(outcome.completed, "{}", outcome.summary);
+        let paths: Vec<_> = outcome
+            .assertions
+            .iter()
+            .map(|a| (a.path.as_str(), a.status))
+            .collect();
+        assert_eq!(paths, vec![("2.attempt2.1", AssertionStatus::Passed)]);
+    }
+
+    #[test]
+    fn assert_steps_validate_their_shape() {
+        let invalid = [
+            r##"{ "name": "r", "steps": [{ "assert": { "kind": "element_count", "selector": "tr" } }] }"##,
+            r##"{ "name": "r", "steps": [{ "assert": { "kind": "element_count", "selector": "tr", "equals": 1, "min": 1 } }] }"##,
+            r##"{ "name": "r", "steps": [{ "assert": { "kind": "value_matches", "selector": "#a", "pattern": "(" } }] }"##,
+            r##"{ "name": "r", "steps": [{ "assert": { "kind": "text_present", "text": "{{nope}}" } }] }"##,
+        ];
+        for json in invalid {
+            let recipe: Recipe = serde_json::from_str(json).unwrap();
+            let err = format!("{:#}", recipe.validate().unwrap_err());
+            assert!(err.contains("step 1"), "{err}");
+        }
+    }
+
+    #[test]
+    fn assert_steps_record_results_and_stop_on_failure() {
+        let recipe = r##"{ "name": "smoke", "steps": [
+            { "assert": { "kind": "text_present", "text": "body" } },
+            { "assert": { "kind": "element_count", "selector": "tr", "min": 2, "max": 5 } },
+            { "assert": { "kind": "url_matches", "pattern": "https://example.test/*" } },
+            { "assert": { "kind": "value_matches", "selector": "#total", "pattern": "^ext" } },
+            { "assert": { "kind": "value_equals", "selector": "#total", "equals": "42" },
+              "message": "totals drifted" },
+            { "action": "click", "selector": "#never" }
+        ] }"##;
+
+        let driver = StubDriver::default();
+        let outcome = run_json(recipe, &driver, true);
+        assert!(!outcome.completed);
+        assert!(!driver.calls().iter().any(|c| c == "click:#never"));
+        let statuses: Vec<_> = outcome.assertions.iter().map(|a| a.status).collect();
+        assert_eq!(
+            statuses,
+            vec![
+                AssertionStatus::Passed,
+                AssertionStatus::Passed,
+                AssertionStatus::Passed,
+                AssertionStatus::Passed,
+                AssertionStatus::Failed,
+            ]
+        );
+        let failed = &outcome.assertions[4];
+        assert_eq!(failed.path, "5");
+        assert_eq!(failed.actual.as_deref(), Some("extracted"));
+        assert!(
+            outcome
+                .summary
+                .contains("[assert] step 5 failed: value of #total == \"42\" (actual: extracted) — totals drifted"),
+            "{}",
+            outcome.summary
+        );
+        assert!(render_markdown(&outcome).contains("## Assertions"));
+
+        // Previews skip assertions instead of failing on an unvisited page.
+        let outcome = run_json(recipe, &StubDriver::default(), false);
+        assert!(outcome.completed);
+        assert!(
+            outcome
+                .assertions
+                .iter()
+                .all(|a| a.status == AssertionStatus::Skipped)
+        );
+    }
+
     #[test]
     fn bare_name_resolves_under_automation_recipes() {
         let base = tempfile::tempdir().unwrap();
@@ -0,0 +1,438 @@
+//! Recipe test suites.
+//!
+//! `archon --automate-test <dir>` runs every recipe in a directory as a smoke
+//! test: a recipe passes when it completes and all of its `assert` steps hold.
+//! Each run still produces an [`AgentOutcome`] (persisted by the caller with
+//! [`persist_outcome`](crate::agent::persist_outcome)); the suite adds a JUnit
+//! XML report for CI and a Markdown summary for humans.
+
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+
+use crate::agent::{AgentOutcome, AssertionStatus};
+use crate::recipe::{Recipe, load_recipe};
+
+/// File name of the JUnit report written by [`SuiteReport::write`].
+pub const JUNIT_FILE: &str = "junit.xml";
+/// File name of the Markdown summary written by [`SuiteReport::write`].
+pub const SUMMARY_FILE: &str = "summary.md";
+
+/// One recipe's run within a suite.
+#[derive(Debug, Clone)]
+pub struct RecipeCase {
+    /// Recipe file the case was loaded from.
+    pub path: PathBuf,
+    /// Recipe name, or the file stem when the recipe failed to load.
+    pub name: String,
+    pub duration_ms: u128,
+    /// The run's outcome, or why the recipe could not be run.
+    pub result: Result<AgentOutcome, String>,
+}
+
+/// Verdict for a [`RecipeCase`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CaseStatus {
+    Passed,
+    /// The run stopped early or an assertion failed.
+    Failed,
+    /// Preview run: nothing was executed or asserted.
+    Skipped,
+    /// The recipe could not be loaded or run.
+    Errored,
+}
+
+impl RecipeCase {
+    pub fn status(&self) -> CaseStatus {
+        match &self.result {
+            Err(_) => CaseStatus::Errored,
+            Ok(outcome) if !outcome.executed => CaseStatus::Skipped,
+            Ok(outcome)
+                if outcome.completed
+                    && outcome
+                        .assertions
+                        .iter()
+                        .all(|a| a.status != AssertionStatus::Failed) =>
+            {
+                CaseStatus::Passed
+            }
+            Ok(_) => CaseStatus::Failed,
+        }
+    }
+
+    /// One-line reason for a failed or errored case: the first failed
+    /// assertion, else the last line of the run summary.
+    pub fn failure_reason(&self) -> Option<String> {
+        match (&self.result, self.status()) {
+            (Err(err), _) => Some(err.clone()),
+            (Ok(outcome), CaseStatus::Failed) => Some(
+                match outcome
+                    .assertions
+                    .iter()
+                    .find(|a| a.status == AssertionStatus::Failed)
+                {
+                    Some(failed) => {
+                        let mut reason = format!("step {}: {}", failed.path, failed.description);
+                        if let Some(actual) = &failed.actual {
+                            reason.push_str(&format!(" (actual: {actual})"));
+                        }
+                        if let Some(message) = &failed.message {
+                            reason.push_str(&format!(" — {message}"));
+                        }
+                        reason
+                    }
+                    None => outcome
+                        .summary
+                        .lines()
+                        .last()
+                        .unwrap_or("run did not complete")
+                        .to_string(),
+                },
+            ),
+            _ => None,
+        }
+    }
+}
+
+/// Results of running a directory of recipes.
+#[derive(Debug, Clone)]
+pub struct SuiteReport {
+    /// Suite name (the directory name).
+    pub name: String,
+    pub started: DateTime<Utc>,
+    pub cases: Vec<RecipeCase>,
+}
+
+impl SuiteReport {
+    pub fn count(&self, status: CaseStatus) -> usize {
+        self.cases.iter().filter(|c| c.status() == status).count()
+    }
+
+    /// Whether no case failed or errored.
+    pub fn succeeded(&self) -> bool {
+        self.count(CaseStatus::Failed) == 0 && self.count(CaseStatus::Errored) == 0
+    }
+
+    fn total_secs(&self) -> f64 {
+        self.cases.iter().map(|c| c.duration_ms).sum::<u128>() as f64 / 1000.0
+    }
+
+    /// Render the suite as JUnit XML (one `<testcase>` per recipe).
+    pub fn to_junit_xml(&self) -> String {
+        let mut out = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        let counts = format!(
+            "tests=\"{}\" failures=\"{}\" errors=\"{}\" skipped=\"{}\" time=\"{:.3}\"",
+            self.cases.len(),
+            self.count(CaseStatus::Failed),
+            self.count(CaseStatus::Errored),
+            self.count(CaseStatus::Skipped),
+            self.total_secs()
+        );
+        out.push_str(&format!("<testsuites name=\"archon-recipes\" {counts}>\n"));
+        out.push_str(&format!(
+            "  <testsuite name=\"{}\" {counts} timestamp=\"{}\">\n",
+            xml_escape(&self.name),
+            self.started.format("%Y-%m-%dT%H:%M:%S")
+        ));
+        for case in &self.cases {
+            let classname = case
+                .path
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            out.push_str(&format!(
+                "    <testcase name=\"{}\" classname=\"recipes.{}\" time=\"{:.3}\"",
+                xml_escape(&case.name),
+                xml_escape(&classname),
+                case.duration_ms as f64 / 1000.0
+            ));
+            let reason = case.failure_reason().unwrap_or_default();
+            match (case.status(), &case.result) {
+                (CaseStatus::Passed, _) => out.push_str("/>\n"),
+                (CaseStatus::Skipped, _) => {
+                    out.push_str(">\n      <skipped message=\"preview run; pass --agent-execute\"/>\n    </testcase>\n");
+                }
+                (CaseStatus::Errored, _) => out.push_str(&format!(
+                    ">\n      <error message=\"{}\"/>\n    </testcase>\n",
+                    xml_escape(&reason)
+                )),
+                (CaseStatus::Failed, Ok(outcome)) => out.push_str(&format!(
+                    ">\n      <failure message=\"{}\">{}</failure>\n    </testcase>\n",
+                    xml_escape(&reason),
+                    xml_escape(&outcome.summary)
+                )),
+                (CaseStatus::Failed, Err(_)) => unreachable!("errored cases are Errored"),
+            }
+        }
+        out.push_str("  </testsuite>\n</testsuites>\n");
+        out
+    }
+
+    /// Render a Markdown summary table linking each run's transcript.
+    pub fn to_markdown(&self) -> String {
+        let mut out = format!("# Recipe tests: {}\n\n", self.name);
+        out.push_str(&format!(
+            "- Started: {}\n- Result: {}\n- Passed: {} | Failed: {} | Errored: {} | Skipped: {}\n\n",
+            self.started.format("%Y-%m-%d %H:%M:%S UTC"),
+            if self.succeeded() { "passed" } else { "FAILED" },
+            self.count(CaseStatus::Passed),
+            self.count(CaseStatus::Failed),
+            self.count(CaseStatus::Errored),
+            self.count(CaseStatus::Skipped),
+        ));
+        out.push_str("| Recipe | Status | Assertions | Time | Transcript |\n");
+        out.push_str("| --- | --- | --- | --- | --- |\n");
+        for case in &self.cases {
+            let status = match case.status() {
+                CaseStatus::Passed => "passed",
+                CaseStatus::Failed => "**failed**",
+                CaseStatus::Skipped => "skipped",
+                CaseStatus::Errored => "**error**",
+            };
+            let (assertions, transcript) = match &case.result {
+                Ok(outcome) => {
+                    let passed = outcome
+                        .assertions
+                        .iter()
+                        .filter(|a| a.status == AssertionStatus::Passed)
+                        .count();
+                    (
+                        format!("{passed}/{}", outcome.assertions.len()),
+                        format!("[agent-{0}.md](agent-{0}.md)", outcome.id),
+                    )
+                }
+                Err(_) => ("-".to_string(), "-".to_string()),
+            };
+            out.push_str(&format!(
+                "| {} | {status} | {assertions} | {:.1}s | {transcript} |\n",
+                case.name.replace('|', "\\|"),
+                case.duration_ms as f64 / 1000.0
+            ));
+        }
+
+        let failures: Vec<_> = self
+            .cases
+            .iter()
+            .filter_map(|c| c.failure_reason().map(|reason| (c, reason)))
+            .collect();
+        if !failures.is_empty() {
+            out.push_str("\n## Failures\n\n");
+            for (case, reason) in failures {
+                out.push_str(&format!(
+                    "- **{}** ({}): {reason}\n",
+                    case.name,
+                    case.path.display()
+                ));
+            }
+        }
+        out
+    }
+
+    /// Write [`JUNIT_FILE`] and [`SUMMARY_FILE`] into `dir`.
+    pub fn write(&self, dir: &Path) -> Result<()> {
+        std::fs::create_dir_all(dir)
+            .with_context(|| format!("failed to create report dir {}", dir.display()))?;
+        for (file, body) in [
+            (JUNIT_FILE, self.to_junit_xml()),
+            (SUMMARY_FILE, self.to_markdown()),
+        ] {
+            let path = dir.join(file);
+            std::fs::write(&path, body)
+                .with_context(|| format!("failed to write {}", path.display()))?;
+        }
+        Ok(())
+    }
+}
+
+/// Recipe files (`*.json`) directly inside `dir`, sorted by name.
+pub fn discover_recipes(dir: &Path) -> Result<Vec<PathBuf>> {
+    let entries = std::fs::read_dir(dir)
+        .with_context(|| format!("failed to read recipe dir {}", dir.display()))?;
+    let mut paths: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.is_file() && path.extension().is_some_and(|ext| ext == "json"))
+        .collect();
+    paths.sort();
+    Ok(paths)
+}
+
+/// Load and run every recipe in `dir` with `run`, in file-name order. Load and
+/// run errors become errored cases rather than aborting the suite.
+pub fn run_suite<F>(dir: &Path, mut run: F) -> Result<SuiteReport>
+where
+    F: FnMut(&Path, &Recipe) -> Result<AgentOutcome>,
+{
+    let started = Utc::now();
+    let paths = discover_recipes(dir)?;
+    if paths.is_empty() {
+        anyhow::bail!("no recipes (*.json) found in {}", dir.display());
+    }
+
+    let mut cases = Vec::with_capacity(paths.len());
+    for path in paths {
+        let clock = Instant::now();
+        let (name, result) = match load_recipe(&path.to_string_lossy()) {
+            Ok(recipe) => (
+                recipe.name.clone(),
+                run(&path, &recipe).map_err(|err| format!("{err:#}")),
+            ),
+            Err(err) => (
+                path.file_stem()
+                    .map(|stem| stem.to_string_lossy().into_owned())
+                    .unwrap_or_default(),
+                Err(format!("{err:#}")),
+            ),
+        };
+        cases.push(RecipeCase {
+            path,
+            name,
+            duration_ms: clock.elapsed().as_millis(),
+            result,
+        });
+    }
+
+    Ok(SuiteReport {
+        name: dir
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_else(|| dir.display().to_string()),
+        started,
+        cases,
+    })
+}
+
+fn xml_escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for ch in text.chars() {
+        match ch {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&apos;"),
+            // Control characters other than whitespace are not valid XML 1.0.
+            c if c.is_control() && !matches!(c, '\n' | '\r' | '\t') => {}
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agent::AssertionResult;
+    use uuid::Uuid;
+
+    fn outcome(
+        executed: bool,
+        completed: bool,
+        assertion: Option<AssertionStatus>,
+    ) -> AgentOutcome {
+        AgentOutcome {
+            id: Uuid::new_v4(),
+            goal: "smoke".into(),
+            executed,
+            steps: Vec::new(),
+            completed,
+            summary: "Stopped at failed step 2".into(),
+            verification: None,
+            har: None,
+            assertions: assertion
+                .map(|status| AssertionResult {
+                    path: "2".into(),
+                    description: "value of #total == \"42\"".into(),
+                    status,
+                    actual: Some("41 <€>".into()),
+                    message: Some("totals drifted".into()),
+                })
+                .into_iter()
+                .collect(),
+        }
+    }
+
+    fn write_recipe(dir: &Path, file: &str, name: &str) {
+        let json = format!(
+            r##"{{ "name": "{name}", "steps": [{{ "action": "click", "selector": "#go" }}] }}"##
+        );
+        std::fs::write(dir.join(file), json).unwrap();
+    }
+
+    #[test]
+    fn run_suite_loads_sorted_recipes_and_records_errors() {
+        let dir = tempfile::tempdir().unwrap();
+        write_recipe(dir.path(), "b.json", "Second");
+        write_recipe(dir.path(), "a.json", "First");
+        std::fs::write(dir.path().join("broken.json"), "{ not json").unwrap();
+        std::fs::write(dir.path().join("notes.txt"), "ignored").unwrap();
+
+        let mut seen = Vec::new();
+        let report = run_suite(dir.path(), |_, recipe| {
+            seen.push(recipe.name.clone());
+            match recipe.name.as_str() {
+                "First" => Ok(outcome(true, true, Some(AssertionStatus::Passed))),
+                _ => Ok(outcome(true, false, Some(AssertionStatus::Failed))),
+            }
+        })
+        .unwrap();
+
+        assert_eq!(seen, vec!["First", "Second"]);
+        let statuses: Vec<_> = report.cases.iter().map(RecipeCase::status).collect();
+        assert_eq!(
+            statuses,
+            vec![CaseStatus::Passed, CaseStatus::Failed, CaseStatus::Errored]
+        );
+        assert_eq!(report.cases[2].name, "broken");
+        assert!(!report.succeeded());
+        assert_eq!(
+            report.cases[1].failure_reason().unwrap(),
+            "step 2: value of #total == \"42\" (actual: 41 <€>) — totals drifted"
+        );
+    }
+
+    #[test]
+    fn junit_and_markdown_reports_count_and_escape() {
+        let report = SuiteReport {
+            name: "smoke".into(),
+            started: Utc::now(),
+            cases: vec![
+                RecipeCase {
+                    path: "smoke/login.json".into(),
+                    name: "Login".into(),
+                    duration_ms: 1500,
+                    result: Ok(outcome(true, true, None)),
+                },
+                RecipeCase {
+                    path: "smoke/totals.json".into(),
+                    name: "Totals".into(),
+                    duration_ms: 250,
+                    result: Ok(outcome(true, false, Some(AssertionStatus::Failed))),
+                },
+                RecipeCase {
+                    path: "smoke/preview.json".into(),
+                    name: "Preview".into(),
+                    duration_ms: 0,
+                    result: Ok(outcome(false, true, Some(AssertionStatus::Skipped))),
+                },
+            ],
+        };
+
+        let xml = report.to_junit_xml();
+        assert!(xml.contains(r#"tests="3" failures="1" errors="0" skipped="1" time="1.750""#));
+        assert!(xml.contains(r#"<testcase name="Login" classname="recipes.login" time="1.500"/>"#));
+        assert!(xml.contains("(actual: 41 &lt;€&gt;)"));
+        assert!(xml.contains("<skipped "));
+
+        let md = report.to_markdown();
+        assert!(md.contains("- Result: FAILED"));
+        assert!(md.contains("| Totals | **failed** | 0/1 | 0.2s | [agent-"));
+        assert!(md.contains("## Failures"));
+
+        let out = tempfile::tempdir().unwrap();
+        report.write(out.path()).unwrap();
+        assert!(out.path().join(JUNIT_FILE).exists());
+        assert!(out.path().join(SUMMARY_FILE).exists());
+    }
+}
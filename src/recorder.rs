@@ -0,0 +1,673 @@
+//! Recipe recorder — turn a live browsing session into a recipe file.
+//!
+//! `archon --record <name>` attaches to the running Archon browser over CDP
+//! (the same `automation.remote_debug_port` used by `--agent-attach` and
+//! Conduit), exposes a `Runtime` binding and injects [`CAPTURE_SCRIPT`] at
+//! document-start. The script reports clicks and committed field values with
+//! a selector derived in the page (test ids, stable ids, `name`/`aria-label`,
+//! then an `nth-of-type` path); top-level navigations come from
+//! `Page.frameNavigated`. [`RecipeRecorder`] folds those events into recipe
+//! steps and writes JSON that [`load_recipe`](crate::recipe::load_recipe)
+//! accepts as-is.
+//!
+//! Values typed into sensitive fields (passwords, one-time codes, card
+//! numbers, …) never leave the page: the step types a `{{param}}` placeholder
+//! and the recipe declares a matching `secret` parameter.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::mpsc::{Receiver, Sender, channel};
+use std::time::Duration;
+
+use anyhow::{Context, Result, bail};
+use headless_chrome::protocol::cdp::types::Event;
+use headless_chrome::{Browser, Tab};
+use serde::Deserialize;
+use serde_json::{Map, Value, json};
+
+use crate::conduit::ScriptInjector;
+use crate::recipe::Recipe;
+
+/// Name of the `Runtime` binding the capture script reports through.
+pub const BINDING_NAME: &str = "__archonRecord";
+
+/// A navigation this soon after a recorded click is treated as caused by it
+/// and not recorded as its own `navigate` step.
+pub const NAVIGATION_GRACE_MS: i64 = 3_000;
+
+/// Document-start script that reports user interactions to [`BINDING_NAME`].
+pub const CAPTURE_SCRIPT: &str = r#"(function(){
+if(window.__archonRecorder)return;window.__archonRecorder=true;
+var B='__archonRecord';
+function send(ev){ev.ts=Date.now();try{var f=window[B];if(typeof f==='function')f(JSON.stringify(ev));}catch(e){}}
+function q(v){return JSON.stringify(String(v));}
+function unique(s){try{return document.querySelectorAll(s).length===1;}catch(e){return false;}}
+function stableId(id){return !!id&&!/\d{3,}|^[0-9]|[:.]/.test(id);}
+function esc(v){return window.CSS&&CSS.escape?CSS.escape(v):v;}
+function selectorFor(el){
+var tag=el.tagName.toLowerCase();
+var attrs=['data-testid','data-test','data-qa','data-cy'];
+for(var i=0;i<attrs.length;i++){var v=el.getAttribute(attrs[i]);if(v){var s='['+attrs[i]+'='+q(v)+']';if(unique(s))return s;}}
+if(stableId(el.id)){var s='#'+esc(el.id);if(unique(s))return s;}
+var n=el.getAttribute('name');if(n){var s=tag+'[name='+q(n)+']';if(unique(s))return s;}
+var a=el.getAttribute('aria-label');if(a){var s=tag+'[aria-label='+q(a)+']';if(unique(s))return s;}
+var parts=[],node=el;
+while(node&&node.nodeType===1&&node!==document.documentElement){
+if(node!==el&&stableId(node.id)&&unique('#'+esc(node.id))){parts.unshift('#'+esc(node.id));break;}
+var i=1,sib=node;while((sib=sib.previousElementSibling)){if(sib.tagName===node.tagName)i++;}
+parts.unshift(node.tagName.toLowerCase()+':nth-of-type('+i+')');node=node.parentElement;}
+return parts.join(' > ');}
+function field(el){return el.getAttribute('name')||el.id||el.getAttribute('autocomplete')||el.getAttribute('aria-label')||el.getAttribute('placeholder')||'';}
+function sensitive(el){
+if((el.type||'').toLowerCase()==='password')return true;
+var ac=(el.getAttribute('autocomplete')||'').toLowerCase();
+if(/password|one-time-code|cc-/.test(ac))return true;
+return /pass|pwd|secret|token|otp|pin|cvv|cvc|card.?num|ssn/i.test((el.getAttribute('name')||'')+' '+(el.id||''));}
+function isText(el){var t=(el.type||'').toLowerCase();return el.tagName==='TEXTAREA'||el.tagName==='SELECT'||(el.tagName==='INPUT'&&!/^(checkbox|radio|submit|button|reset|file|image|hidden)$/.test(t));}
+function commit(el){
+if(!isText(el))return;
+var value=el.value;
+if(el.__archonLast===value)return;el.__archonLast=value;
+var s=sensitive(el);
+send({type:el.tagName==='SELECT'?'select':'input',selector:selectorFor(el),value:s?'':value,sensitive:s,field:field(el)});}
+document.addEventListener('click',function(e){
+var t=e.target instanceof Element?e.target:null;if(!t)return;
+var el=t.closest('a,button,input,select,textarea,label,summary,[role=button],[role=link],[role=tab],[role=menuitem],[onclick]')||t;
+if(isText(el))return;
+send({type:'click',selector:selectorFor(el),text:(el.innerText||el.value||'').trim().slice(0,80)});},true);
+document.addEventListener('change',function(e){if(e.target instanceof Element)commit(e.target);},true);
+document.addEventListener('keydown',function(e){
+var el=e.target;if(e.key!=='Enter'||!(el instanceof Element)||el.tagName!=='INPUT')return;
+commit(el);
+var form=el.form;if(!form)return;
+var btn=form.querySelector('button[type=submit],input[type=submit],button:not([type])');
+if(btn)send({type:'click',selector:selectorFor(btn),text:(btn.innerText||btn.value||'').trim().slice(0,80)});},true);
+})();"#;
+
+/// An interaction reported by [`CAPTURE_SCRIPT`] or the navigation listener.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum RecordedEvent {
+    Click {
+        selector: String,
+        /// Visible label, kept for the live log.
+        #[serde(default)]
+        text: String,
+    },
+    /// A committed field value (`change`, or Enter in a text input).
+    Input {
+        selector: String,
+        /// Empty for sensitive fields.
+        #[serde(default)]
+        value: String,
+        #[serde(default)]
+        sensitive: bool,
+        /// The field's name/id/autocomplete, used to name secret parameters.
+        #[serde(default)]
+        field: String,
+    },
+    /// A changed `<select>`, carrying the chosen option's `value`.
+    Select {
+        selector: String,
+        /// Empty for sensitive fields.
+        #[serde(default)]
+        value: String,
+        #[serde(default)]
+        sensitive: bool,
+        #[serde(default)]
+        field: String,
+    },
+    /// A top-level navigation.
+    Navigate { url: String },
+}
+
+impl RecordedEvent {
+    /// Parse a binding payload. Payloads arrive either as the capture
+    /// script's JSON, or wrapped by headless_chrome's page binding shim as
+    /// `{"name", "seq", "args": ["<json>"]}`. Returns the event and its
+    /// page-side timestamp (ms since the epoch).
+    pub fn from_binding_payload(payload: &Value) -> Result<(Self, Option<i64>)> {
+        let mut value = match payload {
+            Value::String(raw) => {
+                serde_json::from_str::<Value>(raw).context("recorder payload is not JSON")?
+            }
+            other => other.clone(),
+        };
+        if let Some(Value::String(inner)) = value.get("args").and_then(|args| args.get(0)) {
+            value = serde_json::from_str(inner).context("recorder payload is not JSON")?;
+        }
+        let ts = value.get("ts").and_then(Value::as_i64);
+        let event = serde_json::from_value(value).context("unrecognised recorder event")?;
+        Ok((event, ts))
+    }
+}
+
+/// A step in the recipe being recorded.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Step {
+    Navigate(String),
+    Click(String),
+    Type { selector: String, text: String },
+    Select { selector: String, value: String },
+}
+
+/// Folds [`RecordedEvent`]s into recipe steps.
+#[derive(Debug, Clone)]
+pub struct RecipeRecorder {
+    name: String,
+    start_url: Option<String>,
+    current_url: Option<String>,
+    steps: Vec<Step>,
+    /// Secret parameter name → the selector it was recorded from.
+    secrets: BTreeMap<String, String>,
+    last_click_ms: Option<i64>,
+}
+
+impl RecipeRecorder {
+    /// Start a recording; `start_url` is the page the tab was on when the
+    /// recorder attached.
+    pub fn new(name: impl Into<String>, start_url: Option<String>) -> Self {
+        let start_url = start_url.filter(|url| is_recordable_url(url));
+        Self {
+            name: name.into(),
+            current_url: start_url.clone(),
+            start_url,
+            steps: Vec::new(),
+            secrets: BTreeMap::new(),
+            last_click_ms: None,
+        }
+    }
+
+    /// Whether any step has been recorded.
+    pub fn is_empty(&self) -> bool {
+        self.steps.is_empty()
+    }
+
+    /// Apply `event`, observed at `at_ms` (ms since the epoch). Returns a
+    /// one-line description of the step it added or changed, if any.
+    pub fn record(&mut self, event: RecordedEvent, at_ms: i64) -> Option<String> {
+        match event {
+            RecordedEvent::Navigate { url } => {
+                if !is_recordable_url(&url) || self.current_url.as_deref() == Some(url.as_str()) {
+                    return None;
+                }
+                self.current_url = Some(url.clone());
+                if self.start_url.is_none() && self.steps.is_empty() {
+                    self.start_url = Some(url.clone());
+                    return Some(format!("start at {url}"));
+                }
+                let caused_by_click = self
+                    .last_click_ms
+                    .is_some_and(|click| at_ms - click <= NAVIGATION_GRACE_MS);
+                if caused_by_click {
+                    return None;
+                }
+                self.steps.push(Step::Navigate(url.clone()));
+                Some(format!("navigate {url}"))
+            }
+            RecordedEvent::Click { selector, text } => {
+                if selector.is_empty() {
+                    return None;
+                }
+                self.last_click_ms = Some(at_ms);
+                self.steps.push(Step::Click(selector.clone()));
+                Some(if text.is_empty() {
+                    format!("click {selector}")
+                } else {
+                    format!("click {selector} ({text})")
+                })
+            }
+            RecordedEvent::Input {
+                selector,
+                value,
+                sensitive,
+                field,
+            } => self.record_value(selector, value, sensitive, &field, false),
+            RecordedEvent::Select {
+                selector,
+                value,
+                sensitive,
+                field,
+            } => self.record_value(selector, value, sensitive, &field, true),
+        }
+    }
+
+    /// Record a committed field value as a `type` step, or a `select` step
+    /// for a `<select>`.
+    fn record_value(
+        &mut self,
+        selector: String,
+        value: String,
+        sensitive: bool,
+        field: &str,
+        select: bool,
+    ) -> Option<String> {
+        if selector.is_empty() {
+            return None;
+        }
+        let value = if sensitive {
+            format!("{{{{{}}}}}", self.secret_param(&selector, field))
+        } else {
+            value
+        };
+        // Setting a value focuses the field itself, so a click that only
+        // focused it is redundant; a re-committed value replaces the
+        // previous one.
+        match self.steps.last() {
+            Some(Step::Click(clicked)) if *clicked == selector => {
+                self.steps.pop();
+            }
+            Some(Step::Type {
+                selector: previous, ..
+            })
+            | Some(Step::Select {
+                selector: previous, ..
+            }) if *previous == selector => {
+                self.steps.pop();
+            }
+            _ => {}
+        }
+        if select {
+            let line = format!("select {selector} = {value}");
+            self.steps.push(Step::Select { selector, value });
+            Some(line)
+        } else {
+            let line = format!("type {selector} = {value}");
+            self.steps.push(Step::Type {
+                selector,
+                text: value,
+            });
+            Some(line)
+        }
+    }
+
+    /// The secret parameter for a sensitive field, named after the field and
+    /// reused when the same field is typed into again.
+    fn secret_param(&mut self, selector: &str, field: &str) -> String {
+        if let Some((name, _)) = self.secrets.iter().find(|(_, s)| *s == selector) {
+            return name.clone();
+        }
+        let base = param_name(field);
+        let mut name = base.clone();
+        let mut n = 2;
+        while self.secrets.contains_key(&name) {
+            name = format!("{base}_{n}");
+            n += 1;
+        }
+        self.secrets.insert(name.clone(), selector.to_string());
+        name
+    }
+
+    /// Render the recording as recipe JSON.
+    pub fn to_json(&self) -> Value {
+        let mut recipe = Map::new();
+        recipe.insert("name".into(), json!(self.name));
+        recipe.insert(
+            "description".into(),
+            json!(format!(
+                "Recorded with `archon --record` on {}",
+                chrono::Utc::now().format("%Y-%m-%d")
+            )),
+        );
+        if !self.secrets.is_empty() {
+            let params: Map<String, Value> = self
+                .secrets
+                .iter()
+                .map(|(name, selector)| {
+                    (
+                        name.clone(),
+                        json!({
+                            "type": "secret",
+                            "env": format!("ARCHON_RECIPE_{}", name.to_ascii_uppercase()),
+                            "description": format!("Value entered into {selector}"),
+                        }),
+                    )
+                })
+                .collect();
+            recipe.insert("params".into(), Value::Object(params));
+        }
+        if let Some(url) = &self.start_url {
+            recipe.insert("start_url".into(), json!(url));
+        }
+        let steps: Vec<Value> = self
+            .steps
+            .iter()
+            .map(|step| match step {
+                Step::Navigate(url) => json!({ "action": "navigate", "url": url }),
+                Step::Click(selector) => json!({ "action": "click", "selector": selector }),
+                Step::Type { selector, text } => {
+                    json!({ "action": "type", "selector": selector, "text": text })
+                }
+                Step::Select { selector, value } => {
+                    json!({ "action": "select", "selector": selector, "value": value })
+                }
+            })
+            .collect();
+        recipe.insert("steps".into(), Value::Array(steps));
+        Value::Object(recipe)
+    }
+
+    /// Write the recipe to `path` (pretty JSON), refusing to overwrite an
+    /// existing file. The JSON is parsed and validated as a [`Recipe`] first,
+    /// so the file always loads.
+    pub fn write(&self, path: &Path) -> Result<()> {
+        if self.steps.is_empty() {
+            bail!("nothing was recorded; interact with the page before stopping");
+        }
+        if path.exists() {
+            bail!(
+                "{} already exists; record under another name",
+                path.display()
+            );
+        }
+        let value = self.to_json();
+        let recipe: Recipe =
+            serde_json::from_value(value.clone()).context("recorded recipe did not parse")?;
+        recipe.validate().context("recorded recipe is invalid")?;
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("failed to create {}", parent.display()))?;
+        }
+        let json = serde_json::to_string_pretty(&value)?;
+        std::fs::write(path, json + "\n")
+            .with_context(|| format!("failed to write recipe {}", path.display()))
+    }
+
+    /// Names of the secret parameters the recording declares.
+    pub fn secret_params(&self) -> impl Iterator<Item = &str> {
+        self.secrets.keys().map(String::as_str)
+    }
+}
+
+/// Where `--record <target>` writes: a bare name becomes
+/// `<base>/automation/recipes/<name>.json` (where `--automate <name>` looks);
+/// anything with a path separator or `.json` extension is used as given.
+pub fn output_path(base: &Path, target: &str) -> PathBuf {
+    let has_sep = target.contains('/') || target.contains(std::path::MAIN_SEPARATOR);
+    if has_sep || target.ends_with(".json") {
+        PathBuf::from(target)
+    } else {
+        base.join("automation")
+            .join("recipes")
+            .join(format!("{target}.json"))
+    }
+}
+
+fn is_recordable_url(url: &str) -> bool {
+    url.starts_with("http://") || url.starts_with("https://") || url.starts_with("file://")
+}
+
+/// A variable name for a secret field: lowercase ASCII alphanumerics and
+/// underscores, `secret` when nothing usable remains.
+fn param_name(field: &str) -> String {
+    let mut name = String::new();
+    for c in field.chars() {
+        if c.is_ascii_alphanumeric() {
+            name.push(c.to_ascii_lowercase());
+        } else if !name.ends_with('_') && !name.is_empty() {
+            name.push('_');
+        }
+    }
+    let name = name.trim_end_matches('_');
+    match name.chars().next() {
+        None => "secret".to_string(),
+        Some(c) if c.is_ascii_digit() => format!("field_{name}"),
+        Some(_) => name.to_string(),
+    }
+}
+
+/// A live recording attached to the first tab of a running browser.
+pub struct RecorderSession {
+    // Held so the CDP connection outlives the tab handle.
+    _browser: Browser,
+    tab: Arc<Tab>,
+    events: Receiver<(RecordedEvent, i64)>,
+}
+
+impl RecorderSession {
+    /// Attach to the browser exposing CDP at `ws_url`, expose the recorder
+    /// binding and inject [`CAPTURE_SCRIPT`] into the current and future
+    /// documents of its first tab.
+    pub fn attach(ws_url: &str) -> Result<Self> {
+        let browser = Browser::connect_with_timeout(ws_url.to_string(), Duration::from_secs(20))
+            .with_context(|| format!("failed to attach recorder to browser at {ws_url}"))?;
+        let tab = {
+            let tabs = browser
+                .get_tabs()
+                .lock()
+                .map_err(|_| anyhow::anyhow!("browser tab list mutex poisoned"))?;
+            tabs.first().cloned()
+        }
+        .context("the attached browser has no open tab to record")?;
+
+        let (tx, events) = channel::<(RecordedEvent, i64)>();
+        let binding_tx: Sender<_> = tx.clone();
+        tab.enable_runtime()
+            .context("failed to enable the CDP Runtime domain")?;
+        tab.expose_function(
+            BINDING_NAME,
+            Arc::new(
+                move |payload: Value| match RecordedEvent::from_binding_payload(&payload) {
+                    Ok((event, ts)) => {
+                        let at = ts.unwrap_or_else(|| chrono::Utc::now().timestamp_millis());
+                        let _ = binding_tx.send((event, at));
+                    }
+                    Err(err) => tracing::debug!(error = %err, "ignored recorder payload"),
+                },
+            ),
+        )
+        .context("failed to expose the recorder binding")?;
+        tab.add_event_listener(Arc::new(move |event: &Event| {
+            if let Event::PageFrameNavigated(nav) = event {
+                let frame = &nav.params.frame;
+                if frame.parent_id.is_none() {
+                    let _ = tx.send((
+                        RecordedEvent::Navigate {
+                            url: frame.url.clone(),
+                        },
+                        chrono::Utc::now().timestamp_millis(),
+                    ));
+                }
+            }
+        }))
+        .context("failed to listen for navigations")?;
+        tab.add_document_start_script(CAPTURE_SCRIPT)?;
+        tab.eval_now(CAPTURE_SCRIPT)?;
+
+        Ok(Self {
+            _browser: browser,
+            tab,
+            events,
+        })
+    }
+
+    /// URL of the recorded tab.
+    pub fn current_url(&self) -> String {
+        self.tab.get_url()
+    }
+
+    /// Wait up to `timeout` for the next event.
+    pub fn next_event(&self, timeout: Duration) -> Option<(RecordedEvent, i64)> {
+        self.events.recv_timeout(timeout).ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn click(selector: &str) -> RecordedEvent {
+        RecordedEvent::Click {
+            selector: selector.into(),
+            text: String::new(),
+        }
+    }
+
+    fn input(selector: &str, value: &str, sensitive: bool, field: &str) -> RecordedEvent {
+        RecordedEvent::Input {
+            selector: selector.into(),
+            value: value.into(),
+            sensitive,
+            field: field.into(),
+        }
+    }
+
+    fn navigate(url: &str) -> RecordedEvent {
+        RecordedEvent::Navigate { url: url.into() }
+    }
+
+    #[test]
+    fn binding_payloads_parse_raw_and_wrapped() {
+        let raw = json!(r##"{"type":"click","selector":"#go","text":"Go","ts":42}"##);
+        let (event, ts) = RecordedEvent::from_binding_payload(&raw).unwrap();
+        assert_eq!(
+            event,
+            RecordedEvent::Click {
+                selector: "#go".into(),
+                text: "Go".into()
+            }
+        );
+        assert_eq!(ts, Some(42));
+
+        let wrapped = json!(
+            json!({
+                "name": BINDING_NAME,
+                "seq": 1,
+                "args": [r#"{"type":"input","selector":"input[name=\"q\"]","value":"rust"}"#],
+            })
+            .to_string()
+        );
+        let (event, ts) = RecordedEvent::from_binding_payload(&wrapped).unwrap();
+        assert_eq!(event, input(r#"input[name="q"]"#, "rust", false, ""));
+        assert_eq!(ts, None);
+
+        assert!(RecordedEvent::from_binding_payload(&json!("{\"type\":\"hover\"}")).is_err());
+    }
+
+    #[test]
+    fn recorder_folds_events_into_steps() {
+        let mut rec = RecipeRecorder::new("Login", Some("https://app.example/login".into()));
+        // A reload of the start page is not a step.
+        assert_eq!(rec.record(navigate("https://app.example/login"), 0), None);
+        rec.record(click("#user"), 1_000);
+        rec.record(input("#user", "ada", false, "user"), 1_500);
+        rec.record(input("#user", "ada.l", false, "user"), 1_600);
+        rec.record(click("#password"), 2_000);
+        rec.record(input("#password", "", true, "password"), 2_500);
+        rec.record(click("button[type=\"submit\"]"), 3_000);
+        // Caused by the submit click.
+        assert_eq!(
+            rec.record(navigate("https://app.example/home"), 4_000),
+            None
+        );
+        // Typed into the address bar long after the last click.
+        assert_eq!(
+            rec.record(navigate("https://app.example/reports"), 60_000),
+            Some("navigate https://app.example/reports".into())
+        );
+        assert_eq!(rec.record(navigate("chrome://settings/"), 61_000), None);
+
+        let json = rec.to_json();
+        assert_eq!(json["start_url"], json!("https://app.example/login"));
+        assert_eq!(
+            json["steps"],
+            json!([
+                { "action": "type", "selector": "#user", "text": "ada.l" },
+                { "action": "type", "selector": "#password", "text": "{{password}}" },
+                { "action": "click", "selector": "button[type=\"submit\"]" },
+                { "action": "navigate", "url": "https://app.example/reports" },
+            ])
+        );
+        assert_eq!(json["params"]["password"]["type"], json!("secret"));
+        assert_eq!(
+            json["params"]["password"]["env"],
+            json!("ARCHON_RECIPE_PASSWORD")
+        );
+        assert_eq!(rec.secret_params().collect::<Vec<_>>(), vec!["password"]);
+    }
+
+    #[test]
+    fn select_changes_become_select_steps() {
+        use crate::automation::ActionType;
+        use crate::recipe::RecipeStep;
+
+        let payload = json!(
+            r##"{"type":"select","selector":"#country","value":"de","field":"country"}"##
+        );
+        let (event, _) = RecordedEvent::from_binding_payload(&payload).unwrap();
+        let mut rec = RecipeRecorder::new("Address", Some("https://shop.example/".into()));
+        rec.record(click("#country"), 1);
+        assert_eq!(
+            rec.record(event, 2),
+            Some("select #country = de".into())
+        );
+        rec.record(
+            RecordedEvent::Select {
+                selector: "#country".into(),
+                value: "fr".into(),
+                sensitive: false,
+                field: "country".into(),
+            },
+            3,
+        );
+
+        let json = rec.to_json();
+        assert_eq!(
+            json["steps"],
+            json!([{ "action": "select", "selector": "#country", "value": "fr" }])
+        );
+        let recipe: Recipe = serde_json::from_value(json).unwrap();
+        recipe.validate().unwrap();
+        let RecipeStep::Action(step) = &recipe.steps[0] else {
+            panic!("expected an action step");
+        };
+        let action = step.to_web_action().unwrap();
+        assert_eq!(action.action_type, ActionType::Select);
+        assert_eq!(action.selector.as_deref(), Some("#country"));
+        assert_eq!(action.value.as_deref(), Some("fr"));
+    }
+
+    #[test]
+    fn secret_params_are_named_uniquely() {
+        let mut rec = RecipeRecorder::new("Card", None);
+        rec.record(navigate("https://shop.example/checkout"), 0);
+        rec.record(input("#cc", "", true, "cc-number"), 1);
+        rec.record(input("#cvc", "", true, "cc-number"), 2);
+        rec.record(input("#pin", "", true, "4-digit PIN"), 3);
+        rec.record(input("#x", "", true, "??"), 4);
+        rec.record(input("#cc", "", true, "cc-number"), 5);
+
+        let names: Vec<_> = rec.secret_params().collect();
+        assert_eq!(
+            names,
+            vec!["cc_number", "cc_number_2", "field_4_digit_pin", "secret"]
+        );
+        let json = rec.to_json();
+        assert_eq!(json["start_url"], json!("https://shop.example/checkout"));
+        assert_eq!(json["steps"][4]["text"], json!("{{cc_number}}"));
+    }
+
+    #[test]
+    fn written_recipe_loads_unchanged() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = output_path(dir.path(), "login");
+        assert_eq!(path, dir.path().join("automation/recipes/login.json"));
+        assert_eq!(
+            output_path(dir.path(), "out/login.json"),
+            PathBuf::from("out/login.json")
+        );
+
+        let mut rec = RecipeRecorder::new("Login", Some("https://app.example/".into()));
+        assert!(rec.write(&path).is_err(), "empty recordings are rejected");
+        rec.record(input("#password", "", true, "password"), 1);
+        rec.record(click("#submit"), 2);
+        rec.write(&path).unwrap();
+        assert!(rec.write(&path).is_err(), "existing files are kept");
+
+        let recipe = crate::recipe::load_recipe(&path.to_string_lossy()).unwrap();
+        assert_eq!(recipe.name, "Login");
+        assert_eq!(recipe.steps.len(), 2);
+        assert!(recipe.params.contains_key("password"));
+    }
+}
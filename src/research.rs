@@ -3,17 +3,32 @@
 //! Provides multi-step research orchestration using search and AI
 //! to answer complex questions with source citations.
 
-use std::sync::Arc;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, mpsc};
+use std::thread;
 use std::time::Instant;
 
-use anyhow::{Context, Result, bail};
+use anyhow::{Context, Result, anyhow, bail};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
-use crate::ai::{AiBridge, AiChatPrompt, BlockingAiHttp};
+use crate::ai::{AiBridge, AiChatPrompt, AiChatResponse, AiHttp, BlockingAiHttp};
 use crate::config::ResearchSettings;
-use crate::search::ArcOrchestrator;
+use crate::search::{ArcOrchestrator, ArcSearchResult, canonical_url};
+use crate::sync_util::LockResultExt;
+use crate::transcript::{TranscriptSource, TranscriptStore};
+use crate::transcript_crypto::{self, TranscriptCipher};
+
+/// Characters of page excerpts per source shown to the model.
+const SOURCE_EXCERPT_CHARS: usize = 1_500;
+
+/// Directory under the transcript root holding research sessions.
+pub const SESSIONS_DIR: &str = "research";
+
+/// Earlier reports of a session passed to a follow-up question.
+const FOLLOW_UP_REPORTS: usize = 3;
 
 /// Research depth levels.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -140,6 +155,12 @@ pub struct ResearchSource {
     pub relevance: f32,
     /// Whether this source was verified.
     pub verified: bool,
+    /// Page excerpts fetched for the source (with Arc page grounding).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub excerpts: Vec<String>,
+    /// Research questions whose searches returned this source.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub questions: Vec<String>,
 }
 
 /// A finding from research.
@@ -178,6 +199,52 @@ pub struct ResearchReport {
     pub provider: String,
     /// Model used.
     pub model: String,
+    /// Search rounds, in order.
+    #[serde(default)]
+    pub iterations: Vec<ResearchIteration>,
+}
+
+/// One search round of a research run.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResearchIteration {
+    /// Questions searched in this round.
+    pub questions: Vec<String>,
+    /// Sources first found in this round.
+    pub new_sources: usize,
+    /// Gaps found after reading the sources; searched in the next round.
+    #[serde(default)]
+    pub gaps: Vec<String>,
+}
+
+/// Progress of a research run, reported as it happens.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "stage", rename_all = "snake_case")]
+pub enum ResearchEvent {
+    /// The model is breaking the question into sub-questions.
+    Planning,
+    /// Searches for a round's questions started.
+    Searching {
+        iteration: usize,
+        questions: Vec<String>,
+    },
+    /// One search finished.
+    Searched {
+        iteration: usize,
+        question: String,
+        results: usize,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        error: Option<String>,
+    },
+    /// The model is reading the sources gathered so far for gaps.
+    Reading { iteration: usize, sources: usize },
+    /// Outcome of the gap analysis.
+    Gaps {
+        iteration: usize,
+        gaps: Vec<String>,
+        complete: bool,
+    },
+    /// The report is being written.
+    Synthesizing { sources: usize },
 }
 
 /// A research session for continued exploration.
@@ -217,6 +284,337 @@ impl ResearchSession {
         self.reports.push(report);
         self.updated_at = Utc::now();
     }
+
+    /// Sources of every report, first occurrence per canonical URL.
+    pub fn sources(&self) -> Vec<&ResearchSource> {
+        let mut seen = std::collections::HashSet::new();
+        self.reports
+            .iter()
+            .flat_map(|report| &report.sources)
+            .filter(|source| seen.insert(canonical_url(&source.url)))
+            .collect()
+    }
+
+    /// Summary for listings.
+    pub fn summary(&self) -> ResearchSessionSummary {
+        ResearchSessionSummary {
+            id: self.id,
+            topic: self.topic.clone(),
+            queries: self.queries.len(),
+            sources: self.sources().len(),
+            created_at: self.created_at,
+            updated_at: self.updated_at,
+        }
+    }
+
+    /// Earlier questions, summaries and findings, as context for a follow-up
+    /// question. Only the last few reports are included.
+    pub fn follow_up_context(&self) -> Option<String> {
+        if self.reports.is_empty() {
+            return None;
+        }
+        let mut context = format!("Earlier research on \"{}\":\n", self.topic);
+        let skip = self.reports.len().saturating_sub(FOLLOW_UP_REPORTS);
+        for report in self.reports.iter().skip(skip) {
+            context.push_str(&format!("\nQuestion: {}\n", report.query));
+            if !report.summary.trim().is_empty() {
+                context.push_str(&format!("Summary: {}\n", report.summary.trim()));
+            }
+            for finding in &report.findings {
+                context.push_str(&format!("- {}\n", finding.statement));
+            }
+        }
+        Some(context)
+    }
+
+    /// Render the session as a Markdown report. Citations are renumbered
+    /// against a bibliography of every source in the session.
+    pub fn to_markdown(&self) -> String {
+        let bibliography = self.sources();
+        let number_of = |url: &str| {
+            let canonical = canonical_url(url);
+            bibliography
+                .iter()
+                .position(|source| canonical_url(&source.url) == canonical)
+                .map(|index| index + 1)
+        };
+
+        let mut out = String::new();
+        out.push_str(&format!("# {}\n\n", self.topic));
+        out.push_str(&format!("- Session: `{}`\n", self.id));
+        out.push_str(&format!("- Started: {}\n", self.created_at.to_rfc3339()));
+        out.push_str(&format!("- Updated: {}\n", self.updated_at.to_rfc3339()));
+        out.push_str(&format!("- Questions: {}\n", self.reports.len()));
+
+        for (i, report) in self.reports.iter().enumerate() {
+            out.push_str(&format!("\n## {}. {}\n\n", i + 1, report.query));
+            if !report.summary.trim().is_empty() {
+                out.push_str(&format!("{}\n", report.summary.trim()));
+            }
+            if !report.findings.is_empty() {
+                out.push_str("\n### Findings\n\n");
+                for finding in &report.findings {
+                    let citations: String = finding
+                        .source_indices
+                        .iter()
+                        .filter_map(|index| report.sources.get(*index))
+                        .filter_map(|source| number_of(&source.url))
+                        .map(|number| format!("[{number}]"))
+                        .collect();
+                    let statement = strip_citations(&finding.statement);
+                    if citations.is_empty() {
+                        out.push_str(&format!("- {statement}\n"));
+                    } else {
+                        out.push_str(&format!("- {statement} {citations}\n"));
+                    }
+                }
+            }
+            if !report.related_questions.is_empty() {
+                out.push_str("\n### Related questions\n\n");
+                for related in &report.related_questions {
+                    out.push_str(&format!("- {related}\n"));
+                }
+            }
+            out.push_str(&format!("\n_{}_\n", report.methodology));
+        }
+
+        if !bibliography.is_empty() {
+            out.push_str("\n## Bibliography\n\n");
+            for (i, source) in bibliography.iter().enumerate() {
+                out.push_str(&format!("{}. [{}]({})\n", i + 1, source.title, source.url));
+            }
+        }
+        out
+    }
+}
+
+/// Listing entry for a stored research session.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResearchSessionSummary {
+    pub id: Uuid,
+    pub topic: String,
+    /// Questions asked in the session.
+    pub queries: usize,
+    /// Distinct sources across the session's reports.
+    pub sources: usize,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// On-disk store of research sessions, one JSON file per session. Files are
+/// encrypted with the transcript cipher when transcript encryption is on.
+#[derive(Debug)]
+pub struct ResearchSessionStore {
+    root: PathBuf,
+    lock: Mutex<()>,
+    cipher: Option<TranscriptCipher>,
+}
+
+impl ResearchSessionStore {
+    /// Open (creating if needed) a session store rooted at `root`.
+    pub fn new(root: PathBuf) -> Result<Self> {
+        fs::create_dir_all(&root).with_context(|| {
+            format!(
+                "failed to create research session directory {}",
+                root.display()
+            )
+        })?;
+        Ok(Self {
+            root,
+            lock: Mutex::new(()),
+            cipher: None,
+        })
+    }
+
+    /// Session store under `transcripts`, sharing its encryption.
+    pub fn for_transcripts(transcripts: &TranscriptStore) -> Result<Self> {
+        let store = Self::new(transcripts.root().join(SESSIONS_DIR))?;
+        Ok(match transcripts.cipher() {
+            Some(cipher) => store.with_encryption(cipher.clone()),
+            None => store,
+        })
+    }
+
+    /// Encrypt sessions written from now on with `cipher`.
+    pub fn with_encryption(mut self, cipher: TranscriptCipher) -> Self {
+        self.cipher = Some(cipher);
+        self
+    }
+
+    /// Directory backing this store.
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Path of the file for session `id`.
+    pub fn path(&self, id: Uuid) -> PathBuf {
+        self.root.join(format!("research-{id}.json"))
+    }
+
+    /// Whether session `id` is stored.
+    pub fn contains(&self, id: Uuid) -> bool {
+        self.path(id).exists()
+    }
+
+    /// Write `session` (through a temp file and rename, so a crash never
+    /// leaves a truncated session).
+    pub fn save(&self, session: &ResearchSession) -> Result<()> {
+        let _guard = self.lock.lock().recover();
+        let path = self.path(session.id);
+        let json = serde_json::to_vec_pretty(session)
+            .with_context(|| format!("failed to serialize research session {}", session.id))?;
+        self.write(&path, json)
+    }
+
+    /// Add `report` to the stored copy of `session` and write it back, both
+    /// under the store lock, so concurrent follow-ups in one session keep each
+    /// other's reports. A session that is not stored yet starts from
+    /// `session`. Returns the session as written.
+    pub fn append_report(
+        &self,
+        session: &ResearchSession,
+        query: impl Into<String>,
+        report: ResearchReport,
+    ) -> Result<ResearchSession> {
+        let _guard = self.lock.lock().recover();
+        let path = self.path(session.id);
+        let mut stored = if path.exists() {
+            self.read(&path)?
+        } else {
+            session.clone()
+        };
+        stored.add_report(query, report);
+        let json = serde_json::to_vec_pretty(&stored)
+            .with_context(|| format!("failed to serialize research session {}", stored.id))?;
+        self.write(&path, json)?;
+        Ok(stored)
+    }
+
+    fn write(&self, path: &Path, json: Vec<u8>) -> Result<()> {
+        let data = match &self.cipher {
+            Some(cipher) => cipher.encrypt(&json)?,
+            None => json,
+        };
+        let tmp = path.with_extension("json.tmp");
+        fs::write(&tmp, data)
+            .and_then(|_| fs::rename(&tmp, path))
+            .with_context(|| format!("failed to write research session {}", path.display()))
+    }
+
+    /// Encrypt every plaintext session in place; returns how many were
+    /// encrypted.
+    pub fn encrypt_existing(&self) -> Result<usize> {
+        if self.cipher.is_none() {
+            bail!("transcript encryption is not enabled");
+        }
+        let _guard = self.lock.lock().recover();
+        let mut encrypted = 0;
+        for (path, _) in self.load_all()? {
+            let data =
+                fs::read(&path).with_context(|| format!("failed to read {}", path.display()))?;
+            if !transcript_crypto::is_encrypted(&data) {
+                self.write(&path, data)?;
+                encrypted += 1;
+            }
+        }
+        Ok(encrypted)
+    }
+
+    /// Load session `id`.
+    pub fn load(&self, id: Uuid) -> Result<ResearchSession> {
+        let path = self.path(id);
+        if !path.exists() {
+            bail!("research session {id} not found");
+        }
+        self.read(&path)
+    }
+
+    fn read(&self, path: &Path) -> Result<ResearchSession> {
+        let mut data = fs::read(path)
+            .with_context(|| format!("failed to read research session {}", path.display()))?;
+        if transcript_crypto::is_encrypted(&data) {
+            let Some(cipher) = &self.cipher else {
+                bail!(
+                    "research session {} is encrypted; enable transcripts_encryption to read it",
+                    path.display()
+                );
+            };
+            data = cipher.decrypt(&data)?;
+        }
+        serde_json::from_slice(&data)
+            .with_context(|| format!("malformed research session {}", path.display()))
+    }
+
+    /// Stored sessions, most recently updated first. Unreadable files are
+    /// skipped with a warning.
+    pub fn list(&self) -> Result<Vec<ResearchSessionSummary>> {
+        let mut sessions: Vec<ResearchSessionSummary> = self
+            .load_all()?
+            .iter()
+            .map(|(_, session)| session.summary())
+            .collect();
+        sessions.sort_by_key(|session| std::cmp::Reverse(session.updated_at));
+        Ok(sessions)
+    }
+
+    /// Delete session `id`; returns whether it existed.
+    pub fn delete(&self, id: Uuid) -> Result<bool> {
+        let _guard = self.lock.lock().recover();
+        let path = self.path(id);
+        if !path.exists() {
+            return Ok(false);
+        }
+        fs::remove_file(&path)
+            .with_context(|| format!("failed to delete research session {}", path.display()))?;
+        Ok(true)
+    }
+
+    /// Delete sessions not updated within `retention_days` days; returns how
+    /// many were removed. Zero keeps sessions forever.
+    pub fn prune(&self, retention_days: u32) -> Result<usize> {
+        if retention_days == 0 {
+            return Ok(0);
+        }
+        let cutoff = Utc::now() - chrono::Duration::days(i64::from(retention_days));
+        let _guard = self.lock.lock().recover();
+        let mut removed = 0;
+        for (path, session) in self.load_all()? {
+            if session.updated_at < cutoff {
+                fs::remove_file(&path).with_context(|| {
+                    format!("failed to delete research session {}", path.display())
+                })?;
+                removed += 1;
+            }
+        }
+        Ok(removed)
+    }
+
+    fn load_all(&self) -> Result<Vec<(PathBuf, ResearchSession)>> {
+        let entries = fs::read_dir(&self.root).with_context(|| {
+            format!(
+                "failed to read research session directory {}",
+                self.root.display()
+            )
+        })?;
+        let mut sessions = Vec::new();
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let is_session = path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| name.starts_with("research-") && name.ends_with(".json"));
+            if !is_session {
+                continue;
+            }
+            match self.read(&path) {
+                Ok(session) => sessions.push((path, session)),
+                Err(err) => {
+                    tracing::warn!(error = %err, path = %path.display(), "skipping research session")
+                }
+            }
+        }
+        Ok(sessions)
+    }
 }
 
 /// Health report for research capabilities.
@@ -240,6 +638,7 @@ pub struct ResearchOrchestrator {
     ai: Arc<AiBridge>,
     arc: Arc<ArcOrchestrator>,
     settings: ResearchSettings,
+    sessions: Option<Arc<ResearchSessionStore>>,
 }
 
 impl ResearchOrchestrator {
@@ -249,7 +648,31 @@ impl ResearchOrchestrator {
         ai: Arc<AiBridge>,
         arc: Arc<ArcOrchestrator>,
     ) -> Self {
-        Self { ai, arc, settings }
+        Self {
+            ai,
+            arc,
+            settings,
+            sessions: None,
+        }
+    }
+
+    /// Keep research sessions in `store`, first pruning sessions older than
+    /// `session_retention_days`. Saving a session prunes again, so long-running
+    /// hosts do not keep expired sessions until their next restart.
+    pub fn with_sessions(mut self, store: ResearchSessionStore) -> Self {
+        prune_sessions(&store, self.settings.session_retention_days);
+        self.sessions = Some(Arc::new(store));
+        self
+    }
+
+    /// The session store, if one is attached.
+    pub fn sessions(&self) -> Option<&ResearchSessionStore> {
+        self.sessions.as_deref()
+    }
+
+    /// Whether research sessions are written to disk.
+    pub fn persists_sessions(&self) -> bool {
+        self.settings.save_sessions && self.sessions.is_some()
     }
 
     /// Get the underlying AI bridge.
@@ -290,6 +713,33 @@ impl ResearchOrchestrator {
 
     /// Perform research on a query.
     pub fn research(&self, query: &ResearchQuery) -> Result<ResearchReport> {
+        self.research_with_progress(query, &mut |_| {})
+    }
+
+    /// Perform research, reporting each step to `on_event`.
+    pub fn research_with_progress(
+        &self,
+        query: &ResearchQuery,
+        on_event: &mut dyn FnMut(&ResearchEvent),
+    ) -> Result<ResearchReport> {
+        self.research_with_http(query, &BlockingAiHttp::default(), on_event)
+    }
+
+    /// Research loop over `http`.
+    ///
+    /// The model first splits the question into sub-questions. Each round
+    /// searches up to `parallel_searches` questions in parallel, adds the new
+    /// sources, and asks the model what is still missing; the gaps become the
+    /// next round's questions. Rounds stop when the model reports full
+    /// coverage, a round finds nothing new, or `min(depth iterations,
+    /// max_iterations)` rounds have run. The report is then synthesized with
+    /// findings citing the numbered sources.
+    pub fn research_with_http<H: AiHttp>(
+        &self,
+        query: &ResearchQuery,
+        http: &H,
+        on_event: &mut dyn FnMut(&ResearchEvent),
+    ) -> Result<ResearchReport> {
         if !self.settings.enabled {
             bail!("Research is disabled");
         }
@@ -302,45 +752,115 @@ impl ResearchOrchestrator {
         let max_sources = query
             .max_sources
             .unwrap_or_else(|| query.depth.max_sources())
-            .min(self.settings.max_sources);
-
-        // Step 1: Perform search to gather sources
-        let search_result = self
-            .arc
-            .grounded_search(&query.question)
-            .with_context(|| "Failed to search for research sources")?;
+            .min(self.settings.max_sources)
+            .max(1);
+        let budget = query
+            .depth
+            .iterations()
+            .min(self.settings.max_iterations)
+            .max(1);
+        let parallel = self.settings.parallel_searches.max(1);
+        let mut conversation = None;
+
+        // Step 1: Plan sub-questions (single-round research searches the
+        // question as asked).
+        let mut questions = vec![query.question.trim().to_string()];
+        if budget > 1 && parallel > 1 {
+            on_event(&ResearchEvent::Planning);
+            let prompt = self.build_planning_prompt(query, parallel - 1);
+            let plan = self
+                .ask(query, prompt, http, &mut conversation)
+                .with_context(|| "Failed to plan research")?;
+            questions.extend(parse_list_items(&plan.reply, "QUESTIONS:"));
+        }
 
-        // Step 2: Build sources list from search results
-        let mut sources: Vec<ResearchSource> = search_result
-            .results
-            .iter()
-            .take(max_sources)
-            .map(|result| ResearchSource {
-                title: result.title.clone(),
-                url: result.url.clone(),
-                snippet: Some(result.snippet.clone()),
-                relevance: 0.8, // Default relevance
-                verified: false,
-            })
-            .collect();
+        // Step 2: Search, read and find gaps until covered.
+        let mut asked: Vec<String> = Vec::new();
+        let mut sources: Vec<ResearchSource> = Vec::new();
+        let mut iterations: Vec<ResearchIteration> = Vec::new();
+        for iteration in 1..=budget {
+            let batch = fresh_questions(&questions, &asked, parallel);
+            if batch.is_empty() {
+                break;
+            }
+            asked.extend(batch.iter().cloned());
+            on_event(&ResearchEvent::Searching {
+                iteration,
+                questions: batch.clone(),
+            });
+
+            let outcomes = self.search_all(&batch, iteration, on_event);
+            if outcomes.iter().all(|outcome| outcome.is_err()) {
+                if iteration == 1 {
+                    let err = outcomes
+                        .into_iter()
+                        .find_map(|outcome| outcome.err())
+                        .unwrap_or_else(|| anyhow!("no searches ran"));
+                    return Err(err).with_context(|| "Failed to search for research sources");
+                }
+                break;
+            }
 
-        // Step 3: Synthesize findings using AI
-        let synthesis_prompt = self.build_synthesis_prompt(query, &sources, &search_result.context);
+            // Later rounds share what the first round leaves of the source budget.
+            let remaining = max_sources - sources.len();
+            let mut allowance = remaining.div_ceil(budget - iteration + 1);
+            if iteration == 1 {
+                allowance = allowance.max(max_sources / 2);
+            }
+            let new_sources = merge_sources(&mut sources, &batch, &outcomes, allowance);
+            let mut round = ResearchIteration {
+                questions: batch,
+                new_sources,
+                gaps: Vec::new(),
+            };
+            if iteration == budget || (iteration > 1 && new_sources == 0) {
+                iterations.push(round);
+                break;
+            }
 
-        let ai_prompt = AiChatPrompt::text(&synthesis_prompt);
-        let http = BlockingAiHttp::default();
+            on_event(&ResearchEvent::Reading {
+                iteration,
+                sources: sources.len(),
+            });
+            let prompt = self.build_gap_prompt(query, &asked, &sources, parallel);
+            let review = self
+                .ask(query, prompt, http, &mut conversation)
+                .with_context(|| "Failed to review research sources")?;
+            let (complete, gaps) = parse_gap_analysis(&review.reply);
+            on_event(&ResearchEvent::Gaps {
+                iteration,
+                gaps: gaps.clone(),
+                complete,
+            });
+            round.gaps = gaps.clone();
+            iterations.push(round);
+            if complete || gaps.is_empty() {
+                break;
+            }
+            questions = gaps;
+        }
 
+        // Step 3: Synthesize findings using AI
+        on_event(&ResearchEvent::Synthesizing {
+            sources: sources.len(),
+        });
+        let synthesis_prompt = self.build_synthesis_prompt(query, &sources);
         let ai_response = self
-            .ai
-            .chat_with_prompt(query.provider.as_deref(), ai_prompt, &http)
+            .ask(query, synthesis_prompt, http, &mut conversation)
             .with_context(|| "Failed to synthesize research findings")?;
 
         // Step 4: Parse response into structured findings
-        let (summary, findings, related) = self.parse_synthesis_response(&ai_response.reply);
+        let (summary, mut findings, related) = self.parse_synthesis_response(&ai_response.reply);
+        for finding in &mut findings {
+            finding
+                .source_indices
+                .retain(|index| *index < sources.len());
+            finding.source_indices.dedup();
+        }
 
         // Update source verification status based on AI response
         for (i, source) in sources.iter_mut().enumerate() {
-            // Simple heuristic: sources mentioned in findings are verified
+            // Sources cited by a finding are verified
             source.verified = findings.iter().any(|f| f.source_indices.contains(&i));
         }
 
@@ -359,33 +879,139 @@ impl ResearchOrchestrator {
             query: query.question.clone(),
             summary,
             findings,
-            sources,
             related_questions: related,
             confidence,
             methodology: format!(
-                "Depth: {:?}, Sources: {}, Iterations: 1",
-                query.depth, max_sources
+                "Depth: {:?}, Iterations: {} of {}, Searches: {}, Sources: {}",
+                query.depth,
+                iterations.len(),
+                budget,
+                asked.len(),
+                sources.len()
             ),
+            sources,
             depth: query.depth,
             latency_ms: elapsed.as_millis() as u64,
             provider: ai_response.provider,
             model: ai_response.model,
+            iterations,
         })
     }
 
-    /// Build the synthesis prompt for AI.
-    fn build_synthesis_prompt(
+    /// Send one research prompt, keeping every step of a run in one transcript.
+    fn ask<H: AiHttp>(
+        &self,
+        query: &ResearchQuery,
+        prompt: String,
+        http: &H,
+        conversation: &mut Option<Uuid>,
+    ) -> Result<AiChatResponse> {
+        let prompt = AiChatPrompt::text(prompt)
+            .with_source(TranscriptSource::ArcSearch)
+            .with_conversation(*conversation);
+        let response = self
+            .ai
+            .chat_with_prompt(query.provider.as_deref(), prompt, http)?;
+        if response.conversation_id.is_some() {
+            *conversation = response.conversation_id;
+        }
+        Ok(response)
+    }
+
+    /// Run one grounded search per question in parallel, reporting each as it
+    /// finishes. Outcomes are returned in question order.
+    fn search_all(
+        &self,
+        questions: &[String],
+        iteration: usize,
+        on_event: &mut dyn FnMut(&ResearchEvent),
+    ) -> Vec<Result<ArcSearchResult>> {
+        let arc = &self.arc;
+        let mut outcomes: Vec<Option<Result<ArcSearchResult>>> =
+            questions.iter().map(|_| None).collect();
+        thread::scope(|scope| {
+            let (tx, rx) = mpsc::channel();
+            for (index, question) in questions.iter().enumerate() {
+                let tx = tx.clone();
+                scope.spawn(move || {
+                    let _ = tx.send((index, arc.grounded_search(question)));
+                });
+            }
+            drop(tx);
+            for (index, outcome) in rx {
+                on_event(&ResearchEvent::Searched {
+                    iteration,
+                    question: questions[index].clone(),
+                    results: outcome.as_ref().map_or(0, |result| result.results.len()),
+                    error: outcome.as_ref().err().map(|err| format!("{err:#}")),
+                });
+                outcomes[index] = Some(outcome);
+            }
+        });
+        outcomes
+            .into_iter()
+            .map(|outcome| outcome.unwrap_or_else(|| Err(anyhow!("research search panicked"))))
+            .collect()
+    }
+
+    /// Build the prompt asking for sub-questions.
+    fn build_planning_prompt(&self, query: &ResearchQuery, max_questions: usize) -> String {
+        let mut prompt = format!(
+            "You are planning web research. Break the research question into at most \
+             {max_questions} focused sub-questions that together cover it. Each must be \
+             answerable by a single web search.\n\n"
+        );
+        prompt.push_str(&format!("Research Question: {}\n\n", query.question));
+        if let Some(ref context) = query.context {
+            prompt.push_str(&format!("Context: {}\n\n", context));
+        }
+        prompt.push_str("Reply in this format:\nQUESTIONS:\n- <sub-question>\n");
+        prompt
+    }
+
+    /// Build the prompt asking which parts of the question the sources leave
+    /// open.
+    fn build_gap_prompt(
         &self,
         query: &ResearchQuery,
+        asked: &[String],
         sources: &[ResearchSource],
-        search_response: &str,
+        max_gaps: usize,
     ) -> String {
+        let mut prompt = format!(
+            "You are reviewing research in progress. Read the sources gathered so far and \
+             decide whether they fully answer the research question. If they do not, list \
+             at most {max_gaps} follow-up web searches that would fill the gaps. Do not \
+             repeat searches already made.\n\n"
+        );
+        prompt.push_str(&format!("Research Question: {}\n\n", query.question));
+        if let Some(ref context) = query.context {
+            prompt.push_str(&format!("Context: {}\n\n", context));
+        }
+        prompt.push_str("Searches made:\n");
+        for question in asked {
+            prompt.push_str(&format!("- {question}\n"));
+        }
+        prompt.push_str("\nSources:\n");
+        prompt.push_str(&source_notes(sources));
+        prompt.push_str(
+            "\nReply in this format:\n\
+            COVERAGE: complete|partial\n\
+            GAPS:\n\
+            - <follow-up search>\n",
+        );
+        prompt
+    }
+
+    /// Build the synthesis prompt for AI.
+    fn build_synthesis_prompt(&self, query: &ResearchQuery, sources: &[ResearchSource]) -> String {
         let mut prompt = String::from(
-            "You are a research assistant. Analyze the following search results and sources \
-             to answer the research question. Provide:\n\n\
+            "You are a research assistant. Analyze the following sources to answer the \
+             research question. Provide:\n\n\
              1. A concise summary (2-3 sentences)\n\
              2. Key findings as numbered points with confidence levels (high/medium/low)\n\
-             3. Source citations using [N] notation\n\
+             3. Source citations using [N] notation; every finding must cite the sources \
+             that support it, and only the sources listed below\n\
              4. 2-3 related questions for further research\n\n",
         );
 
@@ -395,16 +1021,8 @@ impl ResearchOrchestrator {
             prompt.push_str(&format!("Context: {}\n\n", context));
         }
 
-        prompt.push_str("Search Results:\n");
-        prompt.push_str(search_response);
-        prompt.push_str("\n\nSources:\n");
-
-        for (i, source) in sources.iter().enumerate() {
-            prompt.push_str(&format!("[{}] {} - {}\n", i + 1, source.title, source.url));
-            if let Some(ref snippet) = source.snippet {
-                prompt.push_str(&format!("    {}\n", snippet));
-            }
-        }
+        prompt.push_str("Sources:\n");
+        prompt.push_str(&source_notes(sources));
 
         prompt.push_str(
             "\n\nProvide your research synthesis in the following format:\n\
@@ -541,6 +1159,249 @@ impl ResearchOrchestrator {
     pub fn create_session(&self, topic: &str) -> ResearchSession {
         ResearchSession::new(topic)
     }
+
+    /// Load stored session `id`.
+    pub fn resume_session(&self, id: Uuid) -> Result<ResearchSession> {
+        let Some(store) = &self.sessions else {
+            bail!("research sessions are not stored");
+        };
+        store.load(id)
+    }
+
+    /// Research `query` within `session`, reporting each step to `on_event`.
+    pub fn research_in_session(
+        &self,
+        session: &mut ResearchSession,
+        query: &ResearchQuery,
+        on_event: &mut dyn FnMut(&ResearchEvent),
+    ) -> Result<ResearchReport> {
+        self.research_in_session_with_http(session, query, &BlockingAiHttp::default(), on_event)
+    }
+
+    /// Research `query` within `session` over `http`. Follow-up questions get
+    /// the session's earlier findings as context. The report is added to the
+    /// session; when `save_sessions` is on it is appended to the stored copy
+    /// (picking up reports saved meanwhile by concurrent follow-ups) and
+    /// expired sessions are pruned. A failed save is logged, not fatal.
+    pub fn research_in_session_with_http<H: AiHttp>(
+        &self,
+        session: &mut ResearchSession,
+        query: &ResearchQuery,
+        http: &H,
+        on_event: &mut dyn FnMut(&ResearchEvent),
+    ) -> Result<ResearchReport> {
+        let mut query = query.clone();
+        if let Some(earlier) = session.follow_up_context() {
+            query.context = Some(match query.context.take() {
+                Some(context) => format!("{context}\n\n{earlier}"),
+                None => earlier,
+            });
+        }
+        let report = self.research_with_http(&query, http, on_event)?;
+        let store = self
+            .sessions
+            .as_deref()
+            .filter(|_| self.settings.save_sessions);
+        let Some(store) = store else {
+            session.add_report(query.question, report.clone());
+            return Ok(report);
+        };
+        match store.append_report(session, query.question.clone(), report.clone()) {
+            Ok(stored) => *session = stored,
+            Err(err) => {
+                tracing::warn!(error = %err, session = %session.id, "failed to save research session");
+                session.add_report(query.question, report.clone());
+            }
+        }
+        prune_sessions(store, self.settings.session_retention_days);
+        Ok(report)
+    }
+}
+
+/// Delete sessions older than `retention_days`, logging the outcome.
+fn prune_sessions(store: &ResearchSessionStore, retention_days: u32) {
+    match store.prune(retention_days) {
+        Ok(0) => {}
+        Ok(removed) => tracing::info!(removed, "pruned expired research sessions"),
+        Err(err) => tracing::warn!(error = %err, "failed to prune research sessions"),
+    }
+}
+
+/// `text` without `[N]` citation markers (numbered against a single report).
+fn strip_citations(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('[') {
+        out.push_str(&rest[..start]);
+        let tail = &rest[start + 1..];
+        match tail.find(']') {
+            Some(end) if end > 0 && tail[..end].chars().all(|c| c.is_ascii_digit()) => {
+                rest = &tail[end + 1..];
+            }
+            _ => {
+                out.push('[');
+                rest = tail;
+            }
+        }
+    }
+    out.push_str(rest);
+    out.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Up to `limit` questions from `candidates` not searched yet.
+fn fresh_questions(candidates: &[String], asked: &[String], limit: usize) -> Vec<String> {
+    let mut fresh: Vec<String> = Vec::new();
+    for question in candidates {
+        let question = question.trim();
+        let seen = |other: &String| other.eq_ignore_ascii_case(question);
+        if question.is_empty() || asked.iter().any(seen) || fresh.iter().any(seen) {
+            continue;
+        }
+        fresh.push(question.to_string());
+        if fresh.len() == limit {
+            break;
+        }
+    }
+    fresh
+}
+
+/// Add the results of a round to `sources`, taking each search's results in
+/// rank order in turn so every question contributes, and at most `allowance`
+/// new sources. Returns the number added.
+fn merge_sources(
+    sources: &mut Vec<ResearchSource>,
+    questions: &[String],
+    outcomes: &[Result<ArcSearchResult>],
+    allowance: usize,
+) -> usize {
+    let searches: Vec<(&String, &ArcSearchResult)> = questions
+        .iter()
+        .zip(outcomes)
+        .filter_map(|(question, outcome)| outcome.as_ref().ok().map(|result| (question, result)))
+        .collect();
+    let depth = searches
+        .iter()
+        .map(|(_, search)| search.results.len())
+        .max()
+        .unwrap_or(0);
+
+    let mut added = 0;
+    for rank in 0..depth {
+        for (question, search) in &searches {
+            let Some(result) = search.results.get(rank) else {
+                continue;
+            };
+            let excerpts: Vec<String> = search
+                .pages
+                .iter()
+                .filter(|page| page.result_index == rank)
+                .flat_map(|page| page.excerpts.iter().cloned())
+                .collect();
+            let key = canonical_url(&result.url);
+            if let Some(existing) = sources
+                .iter_mut()
+                .find(|source| canonical_url(&source.url) == key)
+            {
+                if !existing.questions.contains(question) {
+                    existing.questions.push((*question).clone());
+                }
+                for excerpt in excerpts {
+                    if !existing.excerpts.contains(&excerpt) {
+                        existing.excerpts.push(excerpt);
+                    }
+                }
+                continue;
+            }
+            if added == allowance {
+                continue;
+            }
+            sources.push(ResearchSource {
+                title: result.title.clone(),
+                url: result.url.clone(),
+                snippet: Some(result.snippet.clone()),
+                relevance: result.score.map_or(0.8, |score| score.clamp(0.0, 1.0)),
+                verified: false,
+                excerpts,
+                questions: vec![(*question).clone()],
+            });
+            added += 1;
+        }
+    }
+    added
+}
+
+/// Numbered source list with snippets and page excerpts for prompts.
+fn source_notes(sources: &[ResearchSource]) -> String {
+    let mut notes = String::new();
+    for (i, source) in sources.iter().enumerate() {
+        notes.push_str(&format!("[{}] {} - {}\n", i + 1, source.title, source.url));
+        if let Some(ref snippet) = source.snippet
+            && !snippet.trim().is_empty()
+        {
+            notes.push_str(&format!("    {}\n", snippet));
+        }
+        let mut budget = SOURCE_EXCERPT_CHARS;
+        for excerpt in &source.excerpts {
+            if budget == 0 {
+                break;
+            }
+            let text: String = excerpt.chars().take(budget).collect();
+            budget -= text.chars().count();
+            for line in text.lines().filter(|line| !line.trim().is_empty()) {
+                notes.push_str(&format!("    > {}\n", line.trim()));
+            }
+        }
+    }
+    notes
+}
+
+/// List items (`-`, `*`, `•` or numbered) following `header`, or anywhere
+/// when the header is missing.
+fn parse_list_items(text: &str, header: &str) -> Vec<String> {
+    let body = match text.find(header) {
+        Some(start) => &text[start + header.len()..],
+        None => text,
+    };
+    let mut items = Vec::new();
+    for line in body.lines() {
+        let trimmed = line.trim();
+        if trimmed.ends_with(':') && trimmed.chars().all(|c| !c.is_lowercase()) {
+            break;
+        }
+        let item = if let Some(rest) = trimmed
+            .strip_prefix('-')
+            .or_else(|| trimmed.strip_prefix('*'))
+            .or_else(|| trimmed.strip_prefix('•'))
+        {
+            rest
+        } else {
+            let rest = trimmed.trim_start_matches(|c: char| c.is_ascii_digit());
+            match rest.strip_prefix('.').or_else(|| rest.strip_prefix(')')) {
+                Some(rest) if rest.len() < trimmed.len() - 1 => rest,
+                _ => continue,
+            }
+        };
+        let item = item.trim();
+        if !item.is_empty() {
+            items.push(item.to_string());
+        }
+    }
+    items
+}
+
+/// Parse `COVERAGE:` and `GAPS:` from a gap analysis reply.
+fn parse_gap_analysis(text: &str) -> (bool, Vec<String>) {
+    let complete = text.lines().any(|line| {
+        line.trim()
+            .strip_prefix("COVERAGE:")
+            .is_some_and(|value| value.trim().to_lowercase().starts_with("complete"))
+    });
+    let gaps = if text.contains("GAPS:") {
+        parse_list_items(text, "GAPS:")
+    } else {
+        Vec::new()
+    };
+    (complete, gaps)
 }
 
 #[cfg(test)]
@@ -572,4 +1433,324 @@ mod tests {
         assert!(session.queries.is_empty());
         assert!(session.reports.is_empty());
     }
+
+    use std::cell::RefCell;
+    use std::collections::VecDeque;
+
+    use serde_json::{Value, json};
+
+    use crate::config::{AiSettings, ArcSearchProviderConfig, ArcSearchSettings};
+    use crate::local_index::LocalIndex;
+    use crate::transcript::TranscriptStore;
+
+    /// AiHttp stub that replays Ollama chat replies and records the prompts.
+    struct ScriptedAiHttp {
+        replies: RefCell<VecDeque<&'static str>>,
+        prompts: RefCell<Vec<String>>,
+    }
+
+    impl ScriptedAiHttp {
+        fn new(replies: Vec<&'static str>) -> Self {
+            Self {
+                replies: RefCell::new(replies.into()),
+                prompts: RefCell::new(Vec::new()),
+            }
+        }
+    }
+
+    impl AiHttp for ScriptedAiHttp {
+        fn get_json(&self, _url: &str, _headers: &[(String, String)]) -> Result<Value> {
+            Ok(json!({ "version": "test" }))
+        }
+
+        fn post_json(
+            &self,
+            url: &str,
+            _headers: &[(String, String)],
+            body: &Value,
+        ) -> Result<Value> {
+            if !url.ends_with("/api/chat") {
+                bail!("unexpected POST {url}");
+            }
+            let messages = body["messages"].as_array().context("chat messages")?;
+            let prompt = messages.last().context("prompt message")?["content"]
+                .as_str()
+                .unwrap_or_default()
+                .to_string();
+            self.prompts.borrow_mut().push(prompt);
+            let reply = self
+                .replies
+                .borrow_mut()
+                .pop_front()
+                .context("ScriptedAiHttp ran out of replies")?;
+            Ok(json!({ "model": "test", "message": { "content": reply } }))
+        }
+    }
+
+    /// Research over a local index holding `pages`, so searches stay offline.
+    fn orchestrator(
+        dir: &std::path::Path,
+        pages: &[(&str, &str, &str)],
+        settings: ResearchSettings,
+    ) -> ResearchOrchestrator {
+        let index_path = dir.join("local-index.sqlite");
+        let index = LocalIndex::open(&index_path).unwrap();
+        for (url, title, body) in pages {
+            index.save_page(url, title, body).unwrap();
+        }
+        let arc = ArcOrchestrator::from_settings(ArcSearchSettings {
+            enabled: true,
+            default_provider: Some("local".into()),
+            providers: vec![ArcSearchProviderConfig {
+                endpoint: index_path.display().to_string(),
+                enabled: true,
+                ..ArcSearchProviderConfig::local_index_default()
+            }],
+            ..ArcSearchSettings::default()
+        });
+        let store = TranscriptStore::new(dir.join("transcripts")).unwrap();
+        let ai = AiBridge::from_settings(&AiSettings::default(), Arc::new(store));
+        ResearchOrchestrator::from_settings(settings, Arc::new(ai), Arc::new(arc))
+    }
+
+    const PAGES: &[(&str, &str, &str)] = &[
+        (
+            "https://a.example/rotation",
+            "Certificate rotation",
+            "DoQ certificates rotate every ninety days.",
+        ),
+        (
+            "https://b.example/restart",
+            "Restarting ghostdns",
+            "After a restart clients reconnect within a minute.",
+        ),
+        (
+            "https://c.example/port",
+            "DoQ port",
+            "DNS over QUIC listens on port 853.",
+        ),
+    ];
+
+    #[test]
+    fn deep_research_iterates_on_gaps_and_cites_sources() {
+        let dir = tempfile::tempdir().unwrap();
+        let research = orchestrator(
+            dir.path(),
+            PAGES,
+            ResearchSettings {
+                parallel_searches: 2,
+                max_iterations: 3,
+                ..ResearchSettings::default()
+            },
+        );
+        let http = ScriptedAiHttp::new(vec![
+            "QUESTIONS:\n- quic certificate rotation\n- something extra",
+            "COVERAGE: partial\nGAPS:\n- ghostdns restart",
+            "COVERAGE: complete\nGAPS:\n",
+            "SUMMARY: Certificates rotate every 90 days.\n\
+             FINDINGS:\n\
+             1. [confidence:high] Certificates rotate every ninety days [1]\n\
+             2. [confidence:medium] Clients reconnect after a restart [3][9]\n\
+             RELATED QUESTIONS:\n\
+             - How is the new chain deployed?",
+        ]);
+        let query = ResearchQuery::new("How often do DoQ certificates rotate?")
+            .with_depth(ResearchDepth::Deep);
+        let mut events = Vec::new();
+        let report = research
+            .research_with_http(&query, &http, &mut |event| events.push(event.clone()))
+            .unwrap();
+
+        let stages: Vec<&str> = events
+            .iter()
+            .map(|event| match event {
+                ResearchEvent::Planning => "planning",
+                ResearchEvent::Searching { .. } => "searching",
+                ResearchEvent::Searched { .. } => "searched",
+                ResearchEvent::Reading { .. } => "reading",
+                ResearchEvent::Gaps { .. } => "gaps",
+                ResearchEvent::Synthesizing { .. } => "synthesizing",
+            })
+            .collect();
+        assert_eq!(
+            stages,
+            [
+                "planning",
+                "searching",
+                "searched",
+                "searched",
+                "reading",
+                "gaps",
+                "searching",
+                "searched",
+                "reading",
+                "gaps",
+                "synthesizing"
+            ]
+        );
+        assert_eq!(
+            events[1],
+            ResearchEvent::Searching {
+                iteration: 1,
+                questions: vec![
+                    "How often do DoQ certificates rotate?".into(),
+                    "quic certificate rotation".into()
+                ],
+            }
+        );
+
+        assert_eq!(report.iterations.len(), 2);
+        assert_eq!(report.iterations[0].gaps, ["ghostdns restart"]);
+        assert_eq!(report.iterations[1].questions, ["ghostdns restart"]);
+        assert_eq!(report.iterations[1].new_sources, 1);
+        assert_eq!(report.sources.len(), 3);
+        let restart = &report.sources[2];
+        assert_eq!(restart.url, "https://b.example/restart");
+        assert_eq!(restart.questions, ["ghostdns restart"]);
+
+        assert_eq!(report.findings.len(), 2);
+        assert_eq!(report.findings[1].source_indices, [2]);
+        assert_eq!(
+            report
+                .sources
+                .iter()
+                .filter(|source| source.verified)
+                .count(),
+            2
+        );
+        assert!(report.methodology.contains("Iterations: 2 of 3"));
+
+        let prompts = http.prompts.borrow();
+        assert!(prompts[1].contains("- quic certificate rotation"));
+        assert!(prompts[3].contains("[3] Restarting ghostdns - https://b.example/restart"));
+    }
+
+    #[test]
+    fn quick_research_searches_once() {
+        let dir = tempfile::tempdir().unwrap();
+        let research = orchestrator(dir.path(), PAGES, ResearchSettings::default());
+        let http = ScriptedAiHttp::new(vec![
+            "SUMMARY: Port 853.\nFINDINGS:\n1. [confidence:high] DoQ uses port 853 [1]",
+        ]);
+        let query = ResearchQuery::new("DoQ port").with_depth(ResearchDepth::Quick);
+        let mut events = Vec::new();
+        let report = research
+            .research_with_http(&query, &http, &mut |event| events.push(event.clone()))
+            .unwrap();
+
+        assert_eq!(http.prompts.borrow().len(), 1);
+        assert_eq!(events.len(), 3);
+        assert_eq!(report.iterations.len(), 1);
+        assert!(report.sources[0].verified);
+        assert_eq!(report.summary, "Port 853.");
+    }
+
+    #[test]
+    fn sessions_persist_follow_ups_and_export_markdown() {
+        let dir = tempfile::tempdir().unwrap();
+        let store_dir = dir.path().join("sessions");
+        let research = orchestrator(dir.path(), PAGES, ResearchSettings::default())
+            .with_sessions(ResearchSessionStore::new(store_dir.clone()).unwrap());
+        let http = ScriptedAiHttp::new(vec![
+            "SUMMARY: Port 853.\nFINDINGS:\n1. [confidence:high] DoQ uses port 853 [1]",
+            "SUMMARY: Every 90 days.\nFINDINGS:\n1. [confidence:high] Certificates rotate every ninety days [1]",
+        ]);
+
+        let mut session = research.create_session("DoQ port");
+        let query = ResearchQuery::new("DoQ port").with_depth(ResearchDepth::Quick);
+        research
+            .research_in_session_with_http(&mut session, &query, &http, &mut |_| {})
+            .unwrap();
+
+        let mut resumed = research.resume_session(session.id).unwrap();
+        let follow_up = ResearchQuery::new("certificate rotation").with_depth(ResearchDepth::Quick);
+        research
+            .research_in_session_with_http(&mut resumed, &follow_up, &http, &mut |_| {})
+            .unwrap();
+        assert!(http.prompts.borrow()[1].contains("Earlier research on \"DoQ port\""));
+        assert!(http.prompts.borrow()[1].contains("- DoQ uses port 853"));
+
+        let store = research.sessions().unwrap();
+        let listed = store.list().unwrap();
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].queries, 2);
+        assert_eq!(listed[0].sources, 2);
+
+        let markdown = store.load(session.id).unwrap().to_markdown();
+        assert!(markdown.starts_with("# DoQ port\n"));
+        assert!(markdown.contains("## 2. certificate rotation"));
+        assert!(markdown.contains("- Certificates rotate every ninety days [2]\n"));
+        assert!(markdown.contains(
+            "## Bibliography\n\n1. [DoQ port](https://c.example/port)\n\
+             2. [Certificate rotation](https://a.example/rotation)\n"
+        ));
+
+        let mut expired = ResearchSession::new("old");
+        expired.updated_at = Utc::now() - chrono::Duration::days(31);
+        store.save(&expired).unwrap();
+        let reopened = orchestrator(dir.path(), PAGES, ResearchSettings::default())
+            .with_sessions(ResearchSessionStore::new(store_dir).unwrap());
+        let store = reopened.sessions().unwrap();
+        assert!(!store.contains(expired.id));
+        assert!(store.contains(session.id));
+    }
+
+    #[test]
+    fn stale_follow_ups_keep_each_report_and_saves_prune() {
+        let dir = tempfile::tempdir().unwrap();
+        let research = orchestrator(dir.path(), PAGES, ResearchSettings::default())
+            .with_sessions(ResearchSessionStore::new(dir.path().join("sessions")).unwrap());
+        let http = ScriptedAiHttp::new(vec![
+            "SUMMARY: Port 853.\nFINDINGS:\n1. [confidence:high] DoQ uses port 853 [1]",
+            "SUMMARY: Every 90 days.\nFINDINGS:\n1. [confidence:high] Certificates rotate [1]",
+        ]);
+        let store = research.sessions().unwrap();
+        let session = research.create_session("DoQ");
+        store.save(&session).unwrap();
+        let mut expired = ResearchSession::new("old");
+        expired.updated_at = Utc::now() - chrono::Duration::days(31);
+        store.save(&expired).unwrap();
+
+        // Two follow-ups started from the same stored copy.
+        let mut first = research.resume_session(session.id).unwrap();
+        let mut second = research.resume_session(session.id).unwrap();
+        let quick = |question: &str| ResearchQuery::new(question).with_depth(ResearchDepth::Quick);
+        research
+            .research_in_session_with_http(&mut first, &quick("DoQ port"), &http, &mut |_| {})
+            .unwrap();
+        research
+            .research_in_session_with_http(&mut second, &quick("rotation"), &http, &mut |_| {})
+            .unwrap();
+
+        assert_eq!(second.queries, ["DoQ port", "rotation"]);
+        assert_eq!(store.load(session.id).unwrap().reports.len(), 2);
+        assert!(!store.contains(expired.id));
+    }
+
+    #[test]
+    fn parses_plans_and_gap_reviews() {
+        assert_eq!(
+            parse_list_items(
+                "Sure!\nQUESTIONS:\n1. first\n2) second\n* third\n\nNOTES:\n- no",
+                "QUESTIONS:"
+            ),
+            ["first", "second", "third"]
+        );
+        assert_eq!(
+            parse_gap_analysis("COVERAGE: partial\nGAPS:\n- missing part"),
+            (false, vec!["missing part".to_string()])
+        );
+        assert_eq!(
+            parse_gap_analysis("COVERAGE: Complete."),
+            (true, Vec::new())
+        );
+        assert_eq!(
+            fresh_questions(
+                &["a".into(), "B".into(), "c".into(), "b".into()],
+                &["A".into()],
+                5
+            ),
+            ["B", "c"]
+        );
+    }
 }
@@ -0,0 +1,1275 @@
+//! Scheduled and webhook-triggered recipe runs.
+//!
+//! `archon-host` owns a [`Scheduler`] when `scheduler.enabled` is set: it
+//! ticks every few seconds, starts each [`ScheduledRecipe`] whose cron
+//! expression matches the current local minute, and accepts
+//! `POST /scheduler/jobs/<name>/trigger` for jobs with `webhook = true` (for
+//! example from an n8n HTTP Request node). Runs are skipped — and recorded as
+//! skipped — while the machine is on battery or offline, when the job is
+//! already running, or when `max_concurrent` runs are in flight.
+//!
+//! Every run is appended to a JSON-lines [`RunHistory`] and, when the job has
+//! a `deliver` target, its result (transcript path, extracted data,
+//! assertions) is POSTed to a webhook or an n8n workflow via
+//! [`N8nOrchestrator::call_webhook`].
+//!
+//! Recipe execution, delivery and host conditions sit behind the
+//! [`RecipeRunner`], [`ResultSink`] and [`HostConditions`] traits so the
+//! scheduling logic can be exercised without a browser.
+
+use std::collections::{BTreeMap, HashSet};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use anyhow::{Context, Result, bail};
+use chrono::{DateTime, Datelike, Duration, NaiveDateTime, Timelike, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::agent::{AgentOutcome, AssertionResult};
+use crate::automation::ActionType;
+use crate::config::{DeliverySettings, ScheduledRecipe, SchedulerSettings};
+use crate::mcp_http::constant_time_eq;
+use crate::n8n::N8nOrchestrator;
+use crate::sync_util::LockResultExt;
+
+/// Missed minutes replayed when a tick runs late (e.g. after suspend).
+const MAX_CATCH_UP_MINUTES: i64 = 5;
+
+// ============================================================================
+// Cron expressions
+// ============================================================================
+
+/// A parsed five-field cron expression (`minute hour day-of-month month
+/// day-of-week`), evaluated against local wall-clock time.
+///
+/// Fields accept `*`, numbers, `a-b` ranges, `/step` and comma lists; months
+/// and weekdays also accept three-letter names, and weekday `7` is Sunday. As
+/// in Vixie cron, when both day fields are restricted a day matching either
+/// one matches.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CronSchedule {
+    minutes: u64,
+    hours: u32,
+    days: u32,
+    months: u16,
+    weekdays: u8,
+    any_day: bool,
+    any_weekday: bool,
+}
+
+const MONTH_NAMES: [&str; 12] = [
+    "jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec",
+];
+const WEEKDAY_NAMES: [&str; 7] = ["sun", "mon", "tue", "wed", "thu", "fri", "sat"];
+
+impl CronSchedule {
+    pub fn parse(expr: &str) -> Result<Self> {
+        let expanded = match expr.trim() {
+            "@yearly" | "@annually" => "0 0 1 1 *",
+            "@monthly" => "0 0 1 * *",
+            "@weekly" => "0 0 * * 0",
+            "@daily" | "@midnight" => "0 0 * * *",
+            "@hourly" => "0 * * * *",
+            other => other,
+        };
+        let fields: Vec<&str> = expanded.split_whitespace().collect();
+        let [minute, hour, day, month, weekday] = fields[..] else {
+            bail!("cron expression '{expr}' must have 5 fields (min hour dom month dow)");
+        };
+        let field = |text: &str, min: u32, max: u32, names: &[&str], what: &str| {
+            parse_field(text, min, max, names)
+                .with_context(|| format!("invalid {what} field '{text}' in cron '{expr}'"))
+        };
+        let mut weekdays = field(weekday, 0, 7, &WEEKDAY_NAMES, "day-of-week")?;
+        if weekdays & (1 << 7) != 0 {
+            weekdays = (weekdays & !(1 << 7)) | 1;
+        }
+        Ok(Self {
+            minutes: field(minute, 0, 59, &[], "minute")?,
+            hours: field(hour, 0, 23, &[], "hour")? as u32,
+            days: field(day, 1, 31, &[], "day-of-month")? as u32,
+            months: field(month, 1, 12, &MONTH_NAMES, "month")? as u16,
+            weekdays: weekdays as u8,
+            any_day: day.starts_with('*'),
+            any_weekday: weekday.starts_with('*'),
+        })
+    }
+
+    /// Whether the schedule fires in the minute containing `at`.
+    pub fn matches(&self, at: &NaiveDateTime) -> bool {
+        self.minutes & (1 << at.minute()) != 0
+            && self.hours & (1 << at.hour()) != 0
+            && self.day_matches(at)
+    }
+
+    fn day_matches(&self, at: &NaiveDateTime) -> bool {
+        if self.months & (1 << at.month()) == 0 {
+            return false;
+        }
+        let day = self.days & (1 << at.day()) != 0;
+        let weekday = self.weekdays & (1 << at.weekday().num_days_from_sunday()) != 0;
+        match (self.any_day, self.any_weekday) {
+            (false, false) => day || weekday,
+            (true, false) => weekday,
+            (false, true) => day,
+            (true, true) => true,
+        }
+    }
+
+    /// The first matching minute strictly after `after`, searching up to
+    /// about four years ahead (enough for `0 0 29 2 *`).
+    pub fn next_after(&self, after: NaiveDateTime) -> Option<NaiveDateTime> {
+        let mut at = after.with_second(0)?.with_nanosecond(0)? + Duration::minutes(1);
+        let limit = at + Duration::days(366 * 4 + 1);
+        while at <= limit {
+            if !self.day_matches(&at) {
+                at = at.date().succ_opt()?.and_hms_opt(0, 0, 0)?;
+                continue;
+            }
+            if self.hours & (1 << at.hour()) == 0 {
+                at = at.with_minute(0)? + Duration::hours(1);
+                continue;
+            }
+            if self.matches(&at) {
+                return Some(at);
+            }
+            at += Duration::minutes(1);
+        }
+        None
+    }
+}
+
+/// Parse one cron field into a bit set over `min..=max`.
+fn parse_field(text: &str, min: u32, max: u32, names: &[&str]) -> Result<u64> {
+    let value = |token: &str| -> Result<u32> {
+        let lower = token.to_ascii_lowercase();
+        if let Some(pos) = names.iter().position(|name| *name == lower) {
+            // Months start at 1, weekdays at 0 (Sunday).
+            return Ok(pos as u32 + min);
+        }
+        let n: u32 = token
+            .parse()
+            .with_context(|| format!("'{token}' is not a number"))?;
+        if !(min..=max).contains(&n) {
+            bail!("{n} is outside {min}-{max}");
+        }
+        Ok(n)
+    };
+
+    let mut bits = 0u64;
+    for part in text.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => {
+                let step: u32 = step
+                    .parse()
+                    .ok()
+                    .filter(|s| *s > 0)
+                    .with_context(|| format!("invalid step '{step}'"))?;
+                (range, step)
+            }
+            None => (part, 1),
+        };
+        let (lo, hi) = match range {
+            "*" => (min, max),
+            _ => match range.split_once('-') {
+                Some((lo, hi)) => (value(lo)?, value(hi)?),
+                None if step > 1 => (value(range)?, max),
+                None => {
+                    let n = value(range)?;
+                    (n, n)
+                }
+            },
+        };
+        if lo > hi {
+            bail!("range {lo}-{hi} is reversed");
+        }
+        for n in (lo..=hi).step_by(step as usize) {
+            bits |= 1 << n;
+        }
+    }
+    Ok(bits)
+}
+
+// ============================================================================
+// Host conditions
+// ============================================================================
+
+/// Machine state that gates scheduled runs.
+pub trait HostConditions: Send + Sync {
+    fn on_battery(&self) -> bool;
+    fn online(&self) -> bool;
+}
+
+/// [`HostConditions`] read from Linux sysfs (`/sys/class/power_supply` and
+/// `/sys/class/net`). Without sysfs the machine is treated as on mains power
+/// and online.
+#[derive(Debug, Clone)]
+pub struct SysfsConditions {
+    root: PathBuf,
+}
+
+impl Default for SysfsConditions {
+    fn default() -> Self {
+        Self::new("/sys/class")
+    }
+}
+
+impl SysfsConditions {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    fn entries(&self, class: &str) -> Vec<PathBuf> {
+        std::fs::read_dir(self.root.join(class))
+            .map(|dir| dir.filter_map(|e| e.ok().map(|e| e.path())).collect())
+            .unwrap_or_default()
+    }
+}
+
+fn read_trimmed(path: &Path) -> String {
+    std::fs::read_to_string(path)
+        .map(|s| s.trim().to_string())
+        .unwrap_or_default()
+}
+
+impl HostConditions for SysfsConditions {
+    /// On battery when a battery is discharging and no mains/USB supply is
+    /// online.
+    fn on_battery(&self) -> bool {
+        let mut discharging = false;
+        for supply in self.entries("power_supply") {
+            match read_trimmed(&supply.join("type")).as_str() {
+                "Mains" | "USB" if read_trimmed(&supply.join("online")) == "1" => return false,
+                "Battery" => discharging |= read_trimmed(&supply.join("status")) == "Discharging",
+                _ => {}
+            }
+        }
+        discharging
+    }
+
+    /// Online when a non-loopback interface is up (or reports `unknown` with
+    /// carrier, as tunnels do).
+    fn online(&self) -> bool {
+        let interfaces = self.entries("net");
+        if interfaces.is_empty() {
+            return true;
+        }
+        interfaces.iter().any(|iface| {
+            if iface.file_name().is_some_and(|name| name == "lo") {
+                return false;
+            }
+            match read_trimmed(&iface.join("operstate")).as_str() {
+                "up" => true,
+                "unknown" => read_trimmed(&iface.join("carrier")) == "1",
+                _ => false,
+            }
+        })
+    }
+}
+
+// ============================================================================
+// Run history
+// ============================================================================
+
+/// What started a run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RunTrigger {
+    Cron,
+    Webhook,
+    Manual,
+}
+
+/// State of a scheduled run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RunStatus {
+    /// Accepted and in progress (only returned by [`Scheduler::trigger`]).
+    Running,
+    /// The recipe completed and no assertion failed.
+    Succeeded,
+    /// The recipe ran but stopped early or an assertion failed.
+    Failed,
+    /// The recipe could not be loaded or run.
+    Error,
+    /// Not started; see [`RunRecord::summary`].
+    Skipped,
+}
+
+/// A value produced by an `extract` or `extract_response` step.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExtractedValue {
+    /// Recipe step path.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub step: Option<String>,
+    /// Selector (extract) or URL pattern (extract_response).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    pub value: String,
+}
+
+/// One entry of the [`RunHistory`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunRecord {
+    pub id: Uuid,
+    pub job: String,
+    pub trigger: RunTrigger,
+    pub status: RunStatus,
+    pub started_at: DateTime<Utc>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub finished_at: Option<DateTime<Utc>>,
+    /// Run summary, error, or skip reason.
+    #[serde(default)]
+    pub summary: String,
+    /// Markdown transcript of the run.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub transcript: Option<PathBuf>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub extracted: Vec<ExtractedValue>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub assertions: Vec<AssertionResult>,
+    /// Delivery result: `delivered`, or the delivery error.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub delivery: Option<String>,
+}
+
+impl RunRecord {
+    fn new(job: &str, trigger: RunTrigger, status: RunStatus, summary: String) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            job: job.to_string(),
+            trigger,
+            status,
+            started_at: Utc::now(),
+            finished_at: None,
+            summary,
+            transcript: None,
+            extracted: Vec::new(),
+            assertions: Vec::new(),
+            delivery: None,
+        }
+    }
+
+    /// JSON sent to a job's delivery target.
+    pub fn delivery_payload(&self) -> Value {
+        json!({
+            "source": "archon-scheduler",
+            "run": self,
+        })
+    }
+}
+
+/// Append-only JSON-lines history of scheduled runs, trimmed to `limit`
+/// entries.
+#[derive(Debug)]
+pub struct RunHistory {
+    path: PathBuf,
+    limit: usize,
+    lock: Mutex<()>,
+}
+
+impl RunHistory {
+    pub fn new(path: impl Into<PathBuf>, limit: usize) -> Self {
+        Self {
+            path: path.into(),
+            limit: limit.max(1),
+            lock: Mutex::new(()),
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn append(&self, record: &RunRecord) -> Result<()> {
+        let _guard = self.lock.lock().recover();
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("failed to create {}", parent.display()))?;
+        }
+        let line = serde_json::to_string(record)?;
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .with_context(|| format!("failed to open {}", self.path.display()))?;
+        writeln!(file, "{line}")
+            .with_context(|| format!("failed to write {}", self.path.display()))?;
+        drop(file);
+
+        // Trim in batches so most appends stay O(1).
+        let lines = self.read_lines()?;
+        if lines.len() > self.limit + self.limit / 4 {
+            let keep = lines[lines.len() - self.limit..].join("\n") + "\n";
+            let tmp = self.path.with_extension("jsonl.tmp");
+            std::fs::write(&tmp, keep)
+                .with_context(|| format!("failed to write {}", tmp.display()))?;
+            std::fs::rename(&tmp, &self.path)
+                .with_context(|| format!("failed to replace {}", self.path.display()))?;
+        }
+        Ok(())
+    }
+
+    /// Up to `limit` most recent runs, newest first, optionally for one job.
+    /// Unparseable lines are skipped.
+    pub fn recent(&self, job: Option<&str>, limit: usize) -> Result<Vec<RunRecord>> {
+        let _guard = self.lock.lock().recover();
+        Ok(self
+            .read_lines()?
+            .iter()
+            .rev()
+            .filter_map(|line| serde_json::from_str::<RunRecord>(line).ok())
+            .filter(|record| job.is_none_or(|job| record.job == job))
+            .take(limit)
+            .collect())
+    }
+
+    fn read_lines(&self) -> Result<Vec<String>> {
+        match std::fs::read_to_string(&self.path) {
+            Ok(raw) => Ok(raw
+                .lines()
+                .filter(|l| !l.trim().is_empty())
+                .map(String::from)
+                .collect()),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(Vec::new()),
+            Err(err) => Err(err).with_context(|| format!("failed to read {}", self.path.display())),
+        }
+    }
+}
+
+// ============================================================================
+// Runner and delivery
+// ============================================================================
+
+/// Executes a job's recipe. Implementations persist the transcript and
+/// report where it was written.
+pub trait RecipeRunner: Send + Sync {
+    fn run(&self, job: &ScheduledRecipe) -> Result<(AgentOutcome, Option<PathBuf>)>;
+}
+
+/// Delivers a run's result to a job's [`DeliverySettings`].
+pub trait ResultSink: Send + Sync {
+    fn deliver(&self, target: &DeliverySettings, payload: &Value) -> Result<()>;
+}
+
+/// [`ResultSink`] that POSTs to `webhook_url` and calls `n8n_webhook` through
+/// [`N8nOrchestrator::call_webhook`].
+pub struct WebhookSink {
+    n8n: Arc<N8nOrchestrator>,
+}
+
+impl WebhookSink {
+    pub fn new(n8n: Arc<N8nOrchestrator>) -> Self {
+        Self { n8n }
+    }
+}
+
+impl ResultSink for WebhookSink {
+    fn deliver(&self, target: &DeliverySettings, payload: &Value) -> Result<()> {
+        if let Some(url) = &target.webhook_url {
+            let status = reqwest::blocking::Client::builder()
+                .timeout(std::time::Duration::from_secs(30))
+                .build()?
+                .post(url)
+                .json(payload)
+                .send()
+                .with_context(|| format!("failed to POST result to {url}"))?
+                .status();
+            if !status.is_success() {
+                bail!("result webhook {url} returned {status}");
+            }
+        }
+        if let Some(path) = &target.n8n_webhook {
+            let result =
+                self.n8n
+                    .call_webhook(path, payload.clone(), target.n8n_instance.as_deref())?;
+            if !(200..300).contains(&result.status_code) {
+                bail!("n8n webhook {path} returned {}", result.status_code);
+            }
+        }
+        Ok(())
+    }
+}
+
+// ============================================================================
+// Scheduler
+// ============================================================================
+
+/// A job as listed by `GET /scheduler/jobs`.
+#[derive(Debug, Clone, Serialize)]
+pub struct JobStatus {
+    pub name: String,
+    pub recipe: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cron: Option<String>,
+    pub webhook: bool,
+    pub enabled: bool,
+    pub running: bool,
+    /// Next cron run in local time.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_run: Option<NaiveDateTime>,
+}
+
+/// Why [`Scheduler::check_webhook`] refused a trigger.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TriggerRefusal {
+    UnknownJob,
+    Forbidden(String),
+}
+
+/// Runs [`ScheduledRecipe`]s on their schedules and on demand.
+pub struct Scheduler {
+    settings: SchedulerSettings,
+    schedules: BTreeMap<String, CronSchedule>,
+    runner: Arc<dyn RecipeRunner>,
+    sink: Arc<dyn ResultSink>,
+    conditions: Arc<dyn HostConditions>,
+    history: Arc<RunHistory>,
+    running: Mutex<HashSet<String>>,
+    /// Last minute [`Scheduler::tick`] processed.
+    last_tick: Mutex<Option<NaiveDateTime>>,
+}
+
+impl Scheduler {
+    /// Build a scheduler, rejecting duplicate job names and invalid cron
+    /// expressions.
+    pub fn new(
+        settings: SchedulerSettings,
+        runner: Arc<dyn RecipeRunner>,
+        sink: Arc<dyn ResultSink>,
+        conditions: Arc<dyn HostConditions>,
+        history: Arc<RunHistory>,
+    ) -> Result<Self> {
+        let mut names = HashSet::new();
+        let mut schedules = BTreeMap::new();
+        for job in &settings.jobs {
+            if !names.insert(job.name.as_str()) {
+                bail!("duplicate scheduler job name '{}'", job.name);
+            }
+            if job.webhook && job.webhook_token_env.is_none() {
+                warn!(
+                    job = %job.name,
+                    "webhook job has no webhook_token_env; its triggers will be refused"
+                );
+            }
+            if let Some(cron) = &job.cron {
+                let schedule = CronSchedule::parse(cron)
+                    .with_context(|| format!("scheduler job '{}'", job.name))?;
+                schedules.insert(job.name.clone(), schedule);
+            }
+        }
+        Ok(Self {
+            settings,
+            schedules,
+            runner,
+            sink,
+            conditions,
+            history,
+            running: Mutex::new(HashSet::new()),
+            last_tick: Mutex::new(None),
+        })
+    }
+
+    pub fn history(&self) -> &RunHistory {
+        &self.history
+    }
+
+    pub fn job(&self, name: &str) -> Option<&ScheduledRecipe> {
+        self.settings.jobs.iter().find(|job| job.name == name)
+    }
+
+    /// All jobs with their next cron run after `now` (local time).
+    pub fn jobs(&self, now: NaiveDateTime) -> Vec<JobStatus> {
+        let running = self.running.lock().recover();
+        self.settings
+            .jobs
+            .iter()
+            .map(|job| JobStatus {
+                name: job.name.clone(),
+                recipe: job.recipe.clone(),
+                cron: job.cron.clone(),
+                webhook: job.webhook,
+                enabled: job.enabled,
+                running: running.contains(&job.name),
+                next_run: self
+                    .schedules
+                    .get(&job.name)
+                    .filter(|_| job.enabled)
+                    .and_then(|schedule| schedule.next_after(now)),
+            })
+            .collect()
+    }
+
+    /// Check that `name` accepts webhook triggers with `token` (the request's
+    /// bearer token) overriding `params`, which must be listed in the job's
+    /// `webhook_params`.
+    pub fn check_webhook(
+        &self,
+        name: &str,
+        token: Option<&str>,
+        params: &BTreeMap<String, String>,
+    ) -> Result<(), TriggerRefusal> {
+        let job = self.job(name).ok_or(TriggerRefusal::UnknownJob)?;
+        if !job.enabled || !job.webhook {
+            return Err(TriggerRefusal::Forbidden(format!(
+                "job '{name}' does not accept webhook triggers"
+            )));
+        }
+        let Some(var) = &job.webhook_token_env else {
+            return Err(TriggerRefusal::Forbidden(format!(
+                "job '{name}' has no webhook_token_env, so webhook triggers are refused"
+            )));
+        };
+        let expected = std::env::var(var).ok().filter(|v| !v.is_empty());
+        match (expected, token) {
+            (Some(expected), Some(token)) if constant_time_eq(token, &expected) => {}
+            (None, _) => {
+                return Err(TriggerRefusal::Forbidden(format!(
+                    "job '{name}' requires a token but ${var} is not set"
+                )));
+            }
+            _ => {
+                return Err(TriggerRefusal::Forbidden(
+                    "missing or invalid bearer token".to_string(),
+                ));
+            }
+        }
+        if let Some(param) = params
+            .keys()
+            .find(|param| !job.webhook_params.contains(param))
+        {
+            return Err(TriggerRefusal::Forbidden(format!(
+                "job '{name}' does not let webhooks set parameter '{param}'"
+            )));
+        }
+        Ok(())
+    }
+
+    /// Names of enabled jobs due in the minutes since the previous tick (at
+    /// most [`MAX_CATCH_UP_MINUTES`], including `now`'s minute). Each minute
+    /// is considered once.
+    pub fn due(&self, now: NaiveDateTime) -> Vec<String> {
+        let Some(minute) = now.with_second(0).and_then(|t| t.with_nanosecond(0)) else {
+            return Vec::new();
+        };
+        let mut last = self.last_tick.lock().recover();
+        let first = match *last {
+            Some(prev) if prev >= minute => return Vec::new(),
+            Some(prev) => (prev + Duration::minutes(1))
+                .max(minute - Duration::minutes(MAX_CATCH_UP_MINUTES - 1)),
+            None => minute,
+        };
+        *last = Some(minute);
+
+        let mut due = Vec::new();
+        for job in self.settings.jobs.iter().filter(|job| job.enabled) {
+            let Some(schedule) = self.schedules.get(&job.name) else {
+                continue;
+            };
+            let mut at = first;
+            while at <= minute {
+                if schedule.matches(&at) {
+                    due.push(job.name.clone());
+                    break;
+                }
+                at += Duration::minutes(1);
+            }
+        }
+        due
+    }
+
+    /// Start every job due at `now` on a background thread.
+    pub fn tick(self: &Arc<Self>, now: NaiveDateTime) -> Vec<RunRecord> {
+        self.due(now)
+            .into_iter()
+            .filter_map(
+                |name| match self.trigger(&name, RunTrigger::Cron, BTreeMap::new()) {
+                    Ok(record) => Some(record),
+                    Err(err) => {
+                        warn!(job = %name, error = %err, "failed to start scheduled recipe");
+                        None
+                    }
+                },
+            )
+            .collect()
+    }
+
+    /// Start `name` on a background thread, with `params` overriding the
+    /// job's configured parameters. Returns a `running` record, or the
+    /// recorded `skipped` run when a gate refused it.
+    pub fn trigger(
+        self: &Arc<Self>,
+        name: &str,
+        trigger: RunTrigger,
+        params: BTreeMap<String, String>,
+    ) -> Result<RunRecord> {
+        let mut job = self
+            .job(name)
+            .cloned()
+            .with_context(|| format!("unknown scheduler job '{name}'"))?;
+        job.params.extend(params);
+        if let Some(skipped) = self.admit(&job, trigger) {
+            return Ok(skipped);
+        }
+        let mut record = RunRecord::new(&job.name, trigger, RunStatus::Running, String::new());
+        let scheduler = Arc::clone(self);
+        let running = record.clone();
+        std::thread::Builder::new()
+            .name(format!("archon-scheduler-{}", job.name))
+            .spawn(move || {
+                scheduler.execute(&job, running);
+            })
+            .inspect_err(|_| {
+                self.running.lock().recover().remove(name);
+            })
+            .context("failed to spawn scheduler worker")?;
+        record.summary = "started".to_string();
+        Ok(record)
+    }
+
+    /// Run `name` on the calling thread, returning the finished record.
+    pub fn run_blocking(&self, name: &str, trigger: RunTrigger) -> Result<RunRecord> {
+        let job = self
+            .job(name)
+            .cloned()
+            .with_context(|| format!("unknown scheduler job '{name}'"))?;
+        if let Some(skipped) = self.admit(&job, trigger) {
+            return Ok(skipped);
+        }
+        let record = RunRecord::new(&job.name, trigger, RunStatus::Running, String::new());
+        Ok(self.execute(&job, record))
+    }
+
+    /// Apply the battery/offline/concurrency gates. On success the job is
+    /// marked running; otherwise the skipped run is recorded and returned.
+    fn admit(&self, job: &ScheduledRecipe, trigger: RunTrigger) -> Option<RunRecord> {
+        let reason = if self.settings.skip_on_battery && self.conditions.on_battery() {
+            Some("machine is on battery power".to_string())
+        } else if self.settings.skip_when_offline && !self.conditions.online() {
+            Some("machine is offline".to_string())
+        } else {
+            let mut running = self.running.lock().recover();
+            if running.contains(&job.name) {
+                Some("previous run still in progress".to_string())
+            } else if running.len() >= self.settings.max_concurrent.max(1) {
+                Some(format!(
+                    "concurrency limit reached ({} running)",
+                    running.len()
+                ))
+            } else {
+                running.insert(job.name.clone());
+                None
+            }
+        }?;
+
+        info!(job = %job.name, %reason, "skipping scheduled recipe");
+        let mut record = RunRecord::new(&job.name, trigger, RunStatus::Skipped, reason);
+        record.finished_at = Some(record.started_at);
+        self.record(&record);
+        Some(record)
+    }
+
+    /// Run an admitted job, deliver its result and record it.
+    fn execute(&self, job: &ScheduledRecipe, mut record: RunRecord) -> RunRecord {
+        let running = RunningGuard {
+            running: &self.running,
+            name: &job.name,
+        };
+        info!(job = %job.name, trigger = ?record.trigger, "running scheduled recipe");
+        match self.runner.run(job) {
+            Ok((outcome, transcript)) => {
+                record.status = if outcome.completed
+                    && outcome
+                        .assertions
+                        .iter()
+                        .all(|a| a.status != crate::agent::AssertionStatus::Failed)
+                {
+                    RunStatus::Succeeded
+                } else {
+                    RunStatus::Failed
+                };
+                record.summary = outcome.summary.clone();
+                record.transcript = transcript;
+                record.extracted = extracted_values(&outcome);
+                record.assertions = outcome.assertions;
+            }
+            Err(err) => {
+                record.status = RunStatus::Error;
+                record.summary = format!("{err:#}");
+            }
+        }
+        record.finished_at = Some(Utc::now());
+        drop(running);
+
+        if let Some(target) = &job.deliver {
+            record.delivery = Some(
+                match self.sink.deliver(target, &record.delivery_payload()) {
+                    Ok(()) => "delivered".to_string(),
+                    Err(err) => {
+                        warn!(job = %job.name, error = %err, "failed to deliver scheduled run result");
+                        format!("{err:#}")
+                    }
+                },
+            );
+        }
+        self.record(&record);
+        record
+    }
+
+    fn record(&self, record: &RunRecord) {
+        if let Err(err) = self.history.append(record) {
+            warn!(error = %err, path = %self.history.path().display(), "failed to record scheduled run");
+        }
+    }
+}
+
+/// Clears a job's `running` mark when its run ends, including by panic.
+struct RunningGuard<'a> {
+    running: &'a Mutex<HashSet<String>>,
+    name: &'a str,
+}
+
+impl Drop for RunningGuard<'_> {
+    fn drop(&mut self) {
+        self.running.lock().recover().remove(self.name);
+    }
+}
+
+/// Values returned by successful `extract`/`extract_response` steps.
+fn extracted_values(outcome: &AgentOutcome) -> Vec<ExtractedValue> {
+    if !outcome.executed {
+        return Vec::new();
+    }
+    outcome
+        .steps
+        .iter()
+        .filter(|step| {
+            step.result.success
+                && matches!(
+                    step.action.action_type,
+                    ActionType::Extract | ActionType::ExtractResponse
+                )
+        })
+        .filter_map(|step| {
+            Some(ExtractedValue {
+                step: step.path.clone(),
+                source: step.action.selector.clone().or(step.action.value.clone()),
+                value: step.result.data.clone()?,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agent::AgentStep;
+    use crate::automation::{ActionResult, WebAction};
+    use crate::test_util::EnvVarGuard;
+    use chrono::NaiveDate;
+
+    fn at(y: i32, m: u32, d: u32, h: u32, min: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(y, m, d)
+            .unwrap()
+            .and_hms_opt(h, min, 0)
+            .unwrap()
+    }
+
+    #[test]
+    fn cron_parses_fields_names_and_aliases() {
+        let weekday_mornings = CronSchedule::parse("*/15 9-17 * * mon-fri").unwrap();
+        // 2026-10-19 is a Monday.
+        assert!(weekday_mornings.matches(&at(2026, 10, 19, 9, 45)));
+        assert!(!weekday_mornings.matches(&at(2026, 10, 19, 9, 50)));
+        assert!(!weekday_mornings.matches(&at(2026, 10, 18, 9, 45)));
+        assert_eq!(
+            weekday_mornings.next_after(at(2026, 10, 16, 17, 45)),
+            Some(at(2026, 10, 19, 9, 0))
+        );
+
+        // Both day fields restricted: either matches.
+        let either = CronSchedule::parse("0 8 1 * 7").unwrap();
+        assert!(either.matches(&at(2026, 10, 18, 8, 0))); // a Sunday
+        assert!(either.matches(&at(2026, 11, 1, 8, 0)));
+        assert!(!either.matches(&at(2026, 10, 20, 8, 0)));
+
+        assert_eq!(
+            CronSchedule::parse("@daily").unwrap(),
+            CronSchedule::parse("0 0 * * *").unwrap()
+        );
+        let leap = CronSchedule::parse("30 6 29 feb *").unwrap();
+        assert_eq!(
+            leap.next_after(at(2026, 10, 18, 0, 0)),
+            Some(at(2028, 2, 29, 6, 30))
+        );
+        assert_eq!(
+            CronSchedule::parse("5,10-12/2 * * jan,dec *")
+                .unwrap()
+                .minutes,
+            (1 << 5) | (1 << 10) | (1 << 12)
+        );
+
+        for bad in [
+            "* * * *",
+            "60 * * * *",
+            "* * 0 * *",
+            "*/0 * * * *",
+            "5-1 * * * *",
+            "x * * * *",
+        ] {
+            assert!(CronSchedule::parse(bad).is_err(), "{bad}");
+        }
+    }
+
+    #[test]
+    fn sysfs_conditions_detect_battery_and_network() {
+        let dir = tempfile::tempdir().unwrap();
+        let write = |rel: &str, body: &str| {
+            let path = dir.path().join(rel);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, body).unwrap();
+        };
+        let conditions = SysfsConditions::new(dir.path());
+        assert!(!conditions.on_battery());
+        assert!(conditions.online());
+
+        write("power_supply/BAT0/type", "Battery\n");
+        write("power_supply/BAT0/status", "Discharging\n");
+        write("power_supply/AC/type", "Mains\n");
+        write("power_supply/AC/online", "0\n");
+        write("net/lo/operstate", "unknown\n");
+        write("net/lo/carrier", "1\n");
+        write("net/wlan0/operstate", "down\n");
+        assert!(conditions.on_battery());
+        assert!(!conditions.online());
+
+        write("power_supply/AC/online", "1\n");
+        write("net/wg0/operstate", "unknown\n");
+        write("net/wg0/carrier", "1\n");
+        assert!(!conditions.on_battery());
+        assert!(conditions.online());
+    }
+
+    #[derive(Default)]
+    struct FakeConditions {
+        battery: bool,
+        offline: bool,
+    }
+
+    impl HostConditions for FakeConditions {
+        fn on_battery(&self) -> bool {
+            self.battery
+        }
+        fn online(&self) -> bool {
+            !self.offline
+        }
+    }
+
+    struct FakeRunner;
+
+    impl RecipeRunner for FakeRunner {
+        fn run(&self, job: &ScheduledRecipe) -> Result<(AgentOutcome, Option<PathBuf>)> {
+            if job.recipe == "missing" {
+                bail!("failed to read recipe missing");
+            }
+            if job.recipe == "panics" {
+                panic!("runner bug");
+            }
+            let action = WebAction::extract("#price");
+            let outcome = AgentOutcome {
+                id: Uuid::new_v4(),
+                goal: job.recipe.clone(),
+                executed: true,
+                steps: vec![AgentStep {
+                    index: 1,
+                    observation: String::new(),
+                    result: ActionResult {
+                        action_id: action.id,
+                        success: true,
+                        data: Some("$42".into()),
+                        error: None,
+                        latency_ms: 1,
+                        timestamp: Utc::now(),
+                    },
+                    action,
+                    path: Some("1".into()),
+                }],
+                completed: true,
+                summary: "Ran 1 step(s)".into(),
+                verification: None,
+                har: None,
+                assertions: Vec::new(),
+            };
+            Ok((outcome, Some(PathBuf::from("/tmp/agent.md"))))
+        }
+    }
+
+    #[derive(Default)]
+    struct CapturingSink {
+        payloads: Mutex<Vec<Value>>,
+    }
+
+    impl ResultSink for CapturingSink {
+        fn deliver(&self, _target: &DeliverySettings, payload: &Value) -> Result<()> {
+            self.payloads.lock().unwrap().push(payload.clone());
+            Ok(())
+        }
+    }
+
+    fn job(name: &str, recipe: &str, cron: Option<&str>) -> ScheduledRecipe {
+        ScheduledRecipe {
+            name: name.into(),
+            recipe: recipe.into(),
+            cron: cron.map(String::from),
+            webhook: false,
+            webhook_token_env: None,
+            params: BTreeMap::new(),
+            webhook_params: Vec::new(),
+            execute: true,
+            attach: false,
+            max_steps: None,
+            deliver: None,
+            enabled: true,
+        }
+    }
+
+    fn scheduler(
+        jobs: Vec<ScheduledRecipe>,
+        conditions: FakeConditions,
+        sink: Arc<CapturingSink>,
+        dir: &Path,
+    ) -> Arc<Scheduler> {
+        let settings = SchedulerSettings {
+            enabled: true,
+            jobs,
+            ..SchedulerSettings::default()
+        };
+        Arc::new(
+            Scheduler::new(
+                settings,
+                Arc::new(FakeRunner),
+                sink,
+                Arc::new(conditions),
+                Arc::new(RunHistory::new(dir.join("history.jsonl"), 10)),
+            )
+            .unwrap(),
+        )
+    }
+
+    #[test]
+    fn due_fires_each_minute_once_and_catches_up() {
+        let dir = tempfile::tempdir().unwrap();
+        let jobs = vec![
+            job("quarter", "prices", Some("*/15 * * * *")),
+            job("hourly", "prices", Some("@hourly")),
+            job("manual", "prices", None),
+        ];
+        let sched = scheduler(jobs, FakeConditions::default(), Arc::default(), dir.path());
+
+        assert_eq!(
+            sched.due(at(2026, 10, 18, 10, 0)),
+            vec!["quarter", "hourly"]
+        );
+        assert!(sched.due(at(2026, 10, 18, 10, 0)).is_empty());
+        assert!(sched.due(at(2026, 10, 18, 10, 14)).is_empty());
+        // A late tick still sees 11:00 (within the catch-up window).
+        assert_eq!(
+            sched.due(at(2026, 10, 18, 11, 2)),
+            vec!["quarter", "hourly"]
+        );
+
+        let statuses = sched.jobs(at(2026, 10, 18, 11, 2));
+        assert_eq!(statuses[0].next_run, Some(at(2026, 10, 18, 11, 15)));
+        assert_eq!(statuses[2].next_run, None);
+
+        let dup = SchedulerSettings {
+            jobs: vec![job("a", "x", None), job("a", "y", None)],
+            ..SchedulerSettings::default()
+        };
+        let history = Arc::new(RunHistory::new(dir.path().join("h2.jsonl"), 10));
+        assert!(
+            Scheduler::new(
+                dup,
+                Arc::new(FakeRunner),
+                Arc::new(CapturingSink::default()),
+                Arc::new(FakeConditions::default()),
+                history,
+            )
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn panicking_runs_release_their_slot() {
+        let dir = tempfile::tempdir().unwrap();
+        let sched = scheduler(
+            vec![job("crash", "panics", None)],
+            FakeConditions::default(),
+            Arc::default(),
+            dir.path(),
+        );
+        for _ in 0..2 {
+            let run = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+                sched.run_blocking("crash", RunTrigger::Manual)
+            }));
+            // Panics again rather than being skipped as still running.
+            assert!(run.is_err());
+            assert!(sched.running.lock().unwrap().is_empty());
+        }
+    }
+
+    #[test]
+    fn runs_are_recorded_delivered_and_gated() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut prices = job("prices", "prices", None);
+        prices.deliver = Some(DeliverySettings {
+            n8n_webhook: Some("archon-results".into()),
+            ..DeliverySettings::default()
+        });
+        let sink = Arc::new(CapturingSink::default());
+        let sched = scheduler(
+            vec![prices, job("broken", "missing", None)],
+            FakeConditions::default(),
+            Arc::clone(&sink),
+            dir.path(),
+        );
+
+        let record = sched.run_blocking("prices", RunTrigger::Manual).unwrap();
+        assert_eq!(record.status, RunStatus::Succeeded);
+        assert_eq!(record.delivery.as_deref(), Some("delivered"));
+        assert_eq!(record.extracted[0].value, "$42");
+        assert_eq!(record.extracted[0].source.as_deref(), Some("#price"));
+        let payloads = sink.payloads.lock().unwrap().clone();
+        assert_eq!(payloads[0]["run"]["job"], json!("prices"));
+        assert_eq!(payloads[0]["run"]["transcript"], json!("/tmp/agent.md"));
+
+        let record = sched.run_blocking("broken", RunTrigger::Cron).unwrap();
+        assert_eq!(record.status, RunStatus::Error);
+        assert!(record.delivery.is_none());
+
+        // A job cannot overlap itself.
+        sched.running.lock().unwrap().insert("prices".into());
+        let record = sched.run_blocking("prices", RunTrigger::Webhook).unwrap();
+        assert_eq!(record.status, RunStatus::Skipped);
+        assert_eq!(record.summary, "previous run still in progress");
+        // max_concurrent = 1 is already used by "prices".
+        let record = sched.run_blocking("broken", RunTrigger::Cron).unwrap();
+        assert!(record.summary.starts_with("concurrency limit reached"));
+
+        let history = sched.history().recent(None, 10).unwrap();
+        let statuses: Vec<_> = history.iter().map(|r| r.status).collect();
+        assert_eq!(
+            statuses,
+            vec![
+                RunStatus::Skipped,
+                RunStatus::Skipped,
+                RunStatus::Error,
+                RunStatus::Succeeded
+            ]
+        );
+        assert_eq!(sched.history().recent(Some("broken"), 10).unwrap().len(), 2);
+
+        let on_battery = scheduler(
+            vec![job("prices", "prices", None)],
+            FakeConditions {
+                battery: true,
+                offline: false,
+            },
+            Arc::default(),
+            &dir.path().join("battery"),
+        );
+        let record = on_battery.run_blocking("prices", RunTrigger::Cron).unwrap();
+        assert_eq!(record.status, RunStatus::Skipped);
+        assert_eq!(record.summary, "machine is on battery power");
+    }
+
+    #[test]
+    fn webhook_triggers_require_opt_in_and_token() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut open = job("open", "prices", None);
+        open.webhook = true;
+        let mut guarded = job("guarded", "prices", None);
+        guarded.webhook = true;
+        guarded.webhook_token_env = Some("ARCHON_TEST_SCHEDULER_TOKEN".into());
+        guarded.webhook_params = vec!["symbol".into()];
+        let sched = scheduler(
+            vec![open, guarded, job("closed", "prices", None)],
+            FakeConditions::default(),
+            Arc::default(),
+            dir.path(),
+        );
+        let none = BTreeMap::new();
+
+        // A webhook job without a token refuses every trigger.
+        assert!(matches!(
+            sched.check_webhook("open", None, &none),
+            Err(TriggerRefusal::Forbidden(_))
+        ));
+        assert_eq!(
+            sched.check_webhook("nope", None, &none),
+            Err(TriggerRefusal::UnknownJob)
+        );
+        assert!(matches!(
+            sched.check_webhook("closed", None, &none),
+            Err(TriggerRefusal::Forbidden(_))
+        ));
+        let mut env = EnvVarGuard::new();
+        env.remove("ARCHON_TEST_SCHEDULER_TOKEN");
+        assert!(
+            sched
+                .check_webhook("guarded", Some("s3cret"), &none)
+                .is_err()
+        );
+        env.set("ARCHON_TEST_SCHEDULER_TOKEN", "s3cret");
+        assert!(
+            sched
+                .check_webhook("guarded", Some("wrong"), &none)
+                .is_err()
+        );
+        assert!(
+            sched
+                .check_webhook("guarded", Some("s3cre"), &none)
+                .is_err()
+        );
+        assert_eq!(
+            sched.check_webhook("guarded", Some("s3cret"), &none),
+            Ok(())
+        );
+
+        let mut params = BTreeMap::from([("symbol".to_string(), "ACME".to_string())]);
+        assert_eq!(
+            sched.check_webhook("guarded", Some("s3cret"), &params),
+            Ok(())
+        );
+        params.insert("start_url".into(), "https://evil.test".into());
+        let refusal = sched.check_webhook("guarded", Some("s3cret"), &params);
+        assert!(
+            matches!(&refusal, Err(TriggerRefusal::Forbidden(m)) if m.contains("start_url")),
+            "{refusal:?}"
+        );
+    }
+
+    #[test]
+    fn history_trims_to_limit() {
+        let dir = tempfile::tempdir().unwrap();
+        let history = RunHistory::new(dir.path().join("history.jsonl"), 4);
+        for n in 0..9 {
+            let record = RunRecord::new(
+                &format!("job{n}"),
+                RunTrigger::Cron,
+                RunStatus::Succeeded,
+                String::new(),
+            );
+            history.append(&record).unwrap();
+        }
+        let lines = std::fs::read_to_string(history.path()).unwrap();
+        assert!(lines.lines().count() <= 5);
+        let recent = history.recent(None, 2).unwrap();
+        assert_eq!(recent[0].job, "job8");
+        assert_eq!(recent[1].job, "job7");
+    }
+}
@@ -5,16 +5,22 @@
 //! Think of Arc as your research assistant built into the Archon browser.
 
 use std::collections::HashMap;
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::thread;
 use std::time::{Duration, Instant};
 
-use anyhow::{Context, Result};
+use anyhow::{Context, Result, anyhow, bail};
 use reqwest::blocking::Client;
 use serde::{Deserialize, Serialize};
 use serde_json::{Value, json};
 use tracing::{debug, warn};
 use url::form_urlencoded;
 
-use crate::config::{ArcSearchProviderConfig, ArcSearchProviderKind, ArcSearchSettings};
+use crate::config::{
+    ArcFusionSettings, ArcSearchProviderConfig, ArcSearchProviderKind, ArcSearchSettings,
+};
+use crate::grounding::{GroundedSource, PageFetcher};
+use crate::local_index::LocalIndex;
 
 /// URL-encode a query string for search APIs.
 fn encode_query(query: &str) -> String {
@@ -27,6 +33,14 @@ const SEARCH_TIMEOUT: Duration = Duration::from_secs(10);
 /// Maximum number of search results to return.
 const MAX_RESULTS: usize = 10;
 
+/// Provider name that requests a fused search across every enabled provider.
+pub const FUSION_PROVIDER: &str = "all";
+
+/// Query parameters that only track the click and never change the page.
+const TRACKING_PARAMS: &[&str] = &[
+    "fbclid", "gclid", "dclid", "msclkid", "yclid", "igshid", "mc_cid", "mc_eid", "ref", "ref_src",
+];
+
 /// A single search result with source information.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SearchResult {
@@ -45,6 +59,36 @@ pub struct SearchResult {
     /// Relevance score (0.0 - 1.0).
     #[serde(default)]
     pub score: Option<f32>,
+    /// Providers that returned this result and where they ranked it
+    /// (filled by fused searches).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub sources: Vec<ResultSource>,
+}
+
+/// Provenance of a fused search result.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ResultSource {
+    /// Provider name.
+    pub provider: String,
+    /// 1-based rank in that provider's results.
+    pub rank: usize,
+    /// Score reported by the provider, if any.
+    #[serde(default)]
+    pub score: Option<f32>,
+}
+
+/// How one provider fared in a fused search.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProviderOutcome {
+    /// Provider name.
+    pub provider: String,
+    /// Number of results the provider returned.
+    pub results: usize,
+    /// Time until the provider answered or was given up on.
+    pub latency_ms: u64,
+    /// Why the provider contributed nothing (error or timeout).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
 }
 
 /// Search response with results and metadata.
@@ -60,6 +104,9 @@ pub struct SearchResponse {
     pub latency_ms: u64,
     /// Search provider used.
     pub provider: String,
+    /// Per-provider outcome when results were fused.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub providers: Vec<ProviderOutcome>,
 }
 
 /// Citation reference for grounding AI responses.
@@ -119,6 +166,21 @@ fn resolve_api_key(config: &ArcSearchProviderConfig) -> Option<String> {
         .filter(|key| !key.trim().is_empty())
 }
 
+/// GET `url` and parse the JSON body for providers that only accept the API
+/// key inside the URL. reqwest errors embed the request URL, so it is stripped
+/// before the error can reach logs or the UI.
+fn get_json_keyed_url(client: &Client, url: &str, provider: &str) -> Result<Value> {
+    client
+        .get(url)
+        .send()
+        .and_then(|response| response.error_for_status())
+        .map_err(reqwest::Error::without_url)
+        .with_context(|| format!("Failed to reach {provider}"))?
+        .json()
+        .map_err(reqwest::Error::without_url)
+        .with_context(|| format!("Invalid {provider} response"))
+}
+
 /// Web search client.
 #[derive(Debug, Clone)]
 pub struct SearchClient {
@@ -130,8 +192,12 @@ pub struct SearchClient {
 impl SearchClient {
     /// Create a new search client.
     pub fn new(config: ArcSearchProviderConfig, max_results: usize) -> Result<Self> {
+        let timeout = config
+            .timeout_ms
+            .map(Duration::from_millis)
+            .unwrap_or(SEARCH_TIMEOUT);
         let client = Client::builder()
-            .timeout(SEARCH_TIMEOUT)
+            .timeout(timeout)
             .user_agent("Archon/0.1 (arc-search)")
             .build()
             .context("Failed to build HTTP client for Arc search")?;
@@ -155,6 +221,11 @@ impl SearchClient {
             ArcSearchProviderKind::Brave => self.search_brave(query),
             ArcSearchProviderKind::Tavily => self.search_tavily(query),
             ArcSearchProviderKind::DuckDuckGo => self.search_duckduckgo(query),
+            ArcSearchProviderKind::Kagi => self.search_kagi(query),
+            ArcSearchProviderKind::Exa => self.search_exa(query),
+            ArcSearchProviderKind::Mojeek => self.search_mojeek(query),
+            ArcSearchProviderKind::Marginalia => self.search_marginalia(query),
+            ArcSearchProviderKind::LocalIndex => self.search_local_index(query),
         }
     }
 
@@ -203,6 +274,7 @@ impl SearchClient {
                                 .and_then(|d| d.as_str())
                                 .map(|s| s.to_string()),
                             score: item.get("score").and_then(|s| s.as_f64()).map(|s| s as f32),
+                            sources: Vec::new(),
                         })
                     })
                     .collect()
@@ -223,6 +295,7 @@ impl SearchClient {
             total_results: response.get("number_of_results").and_then(|n| n.as_u64()),
             latency_ms,
             provider: "searxng".into(),
+            providers: Vec::new(),
         })
     }
 
@@ -277,6 +350,7 @@ impl SearchClient {
                                 .and_then(|a| a.as_str())
                                 .map(|s| s.to_string()),
                             score: None,
+                            sources: Vec::new(),
                         })
                     })
                     .collect()
@@ -297,6 +371,7 @@ impl SearchClient {
             total_results: None,
             latency_ms,
             provider: "brave".into(),
+            providers: Vec::new(),
         })
     }
 
@@ -346,6 +421,7 @@ impl SearchClient {
                                 .and_then(|d| d.as_str())
                                 .map(|s| s.to_string()),
                             score: item.get("score").and_then(|s| s.as_f64()).map(|s| s as f32),
+                            sources: Vec::new(),
                         })
                     })
                     .collect()
@@ -366,6 +442,7 @@ impl SearchClient {
             total_results: None,
             latency_ms,
             provider: "tavily".into(),
+            providers: Vec::new(),
         })
     }
 
@@ -412,6 +489,7 @@ impl SearchClient {
                     .map(|s| s.to_string()),
                 published_date: None,
                 score: Some(1.0),
+                sources: Vec::new(),
             });
         }
 
@@ -429,6 +507,7 @@ impl SearchClient {
                         domain: extract_domain(url),
                         published_date: None,
                         score: None,
+                        sources: Vec::new(),
                     });
                 }
             }
@@ -448,8 +527,303 @@ impl SearchClient {
             total_results: None,
             latency_ms,
             provider: "duckduckgo".into(),
+            providers: Vec::new(),
         })
     }
+
+    /// Search using the Kagi Search API.
+    fn search_kagi(&self, query: &str) -> Result<SearchResponse> {
+        let api_key = resolve_api_key(&self.config).context("Kagi API key not configured")?;
+
+        let url = format!(
+            "{}?q={}&limit={}",
+            self.config.endpoint,
+            encode_query(query),
+            self.max_results
+        );
+
+        let started = Instant::now();
+        let response: Value = self
+            .client
+            .get(&url)
+            .header("Authorization", format!("Bot {api_key}"))
+            .send()
+            .and_then(|response| response.error_for_status())
+            .context("Failed to reach Kagi")?
+            .json()
+            .context("Invalid Kagi response")?;
+
+        let latency_ms = started.elapsed().as_millis() as u64;
+
+        // `t: 0` entries are results; `t: 1` carries related searches.
+        let results: Vec<SearchResult> = response
+            .get("data")
+            .and_then(|d| d.as_array())
+            .map(|arr| {
+                arr.iter()
+                    .filter(|item| item.get("t").and_then(|t| t.as_u64()) == Some(0))
+                    .take(self.max_results)
+                    .filter_map(|item| {
+                        let url = item.get("url")?.as_str()?;
+                        Some(SearchResult {
+                            title: item.get("title")?.as_str()?.to_string(),
+                            url: url.to_string(),
+                            snippet: item
+                                .get("snippet")
+                                .and_then(|s| s.as_str())
+                                .unwrap_or("")
+                                .to_string(),
+                            domain: extract_domain(url),
+                            published_date: item
+                                .get("published")
+                                .and_then(|p| p.as_str())
+                                .map(|s| s.to_string()),
+                            score: None,
+                            sources: Vec::new(),
+                        })
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        Ok(self.finish_search("kagi", query, results, None, latency_ms))
+    }
+
+    /// Search using the Exa API, with highlights as snippets.
+    fn search_exa(&self, query: &str) -> Result<SearchResponse> {
+        let api_key = resolve_api_key(&self.config).context("Exa API key not configured")?;
+
+        let payload = json!({
+            "query": query,
+            "numResults": self.max_results,
+            "contents": { "highlights": { "numSentences": 3 } },
+        });
+
+        let started = Instant::now();
+        let response: Value = self
+            .client
+            .post(&self.config.endpoint)
+            .header("x-api-key", api_key)
+            .json(&payload)
+            .send()
+            .and_then(|response| response.error_for_status())
+            .context("Failed to reach Exa")?
+            .json()
+            .context("Invalid Exa response")?;
+
+        let latency_ms = started.elapsed().as_millis() as u64;
+
+        let results: Vec<SearchResult> = response
+            .get("results")
+            .and_then(|r| r.as_array())
+            .map(|arr| {
+                arr.iter()
+                    .take(self.max_results)
+                    .filter_map(|item| {
+                        let url = item.get("url")?.as_str()?;
+                        let highlights: Vec<&str> = item
+                            .get("highlights")
+                            .and_then(|h| h.as_array())
+                            .map(|h| h.iter().filter_map(|s| s.as_str()).collect())
+                            .unwrap_or_default();
+                        let snippet = if highlights.is_empty() {
+                            item.get("text")
+                                .or_else(|| item.get("summary"))
+                                .and_then(|t| t.as_str())
+                                .map(|t| t.chars().take(500).collect())
+                                .unwrap_or_default()
+                        } else {
+                            highlights.join(" … ")
+                        };
+                        Some(SearchResult {
+                            title: item
+                                .get("title")
+                                .and_then(|t| t.as_str())
+                                .filter(|t| !t.trim().is_empty())
+                                .unwrap_or(url)
+                                .to_string(),
+                            url: url.to_string(),
+                            snippet,
+                            domain: extract_domain(url),
+                            published_date: item
+                                .get("publishedDate")
+                                .and_then(|d| d.as_str())
+                                .map(|s| s.to_string()),
+                            score: item.get("score").and_then(|s| s.as_f64()).map(|s| s as f32),
+                            sources: Vec::new(),
+                        })
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        Ok(self.finish_search("exa", query, results, None, latency_ms))
+    }
+
+    /// Search using the Mojeek API. Mojeek only takes the key as the `api_key`
+    /// query parameter.
+    fn search_mojeek(&self, query: &str) -> Result<SearchResponse> {
+        let api_key = resolve_api_key(&self.config).context("Mojeek API key not configured")?;
+
+        let url = format!(
+            "{}?q={}&fmt=json&t={}&api_key={}",
+            self.config.endpoint,
+            encode_query(query),
+            self.max_results,
+            encode_query(&api_key)
+        );
+
+        let started = Instant::now();
+        let response = get_json_keyed_url(&self.client, &url, "Mojeek")?;
+
+        let latency_ms = started.elapsed().as_millis() as u64;
+
+        let body = response.get("response").unwrap_or(&Value::Null);
+        if let Some(status) = body.get("status").and_then(|s| s.as_str())
+            && status != "OK"
+        {
+            bail!("Mojeek search failed: {status}");
+        }
+
+        let results: Vec<SearchResult> = body
+            .get("results")
+            .and_then(|r| r.as_array())
+            .map(|arr| {
+                arr.iter()
+                    .take(self.max_results)
+                    .filter_map(|item| {
+                        let url = item.get("url")?.as_str()?;
+                        Some(SearchResult {
+                            title: item.get("title")?.as_str()?.to_string(),
+                            url: url.to_string(),
+                            snippet: item
+                                .get("desc")
+                                .and_then(|d| d.as_str())
+                                .unwrap_or("")
+                                .to_string(),
+                            domain: extract_domain(url),
+                            published_date: item
+                                .get("date")
+                                .and_then(|d| d.as_i64())
+                                .filter(|secs| *secs > 0)
+                                .and_then(|secs| chrono::DateTime::from_timestamp(secs, 0))
+                                .map(|date| date.format("%Y-%m-%d").to_string()),
+                            score: None,
+                            sources: Vec::new(),
+                        })
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let total_results = body
+            .get("head")
+            .and_then(|h| h.get("results"))
+            .and_then(|n| n.as_u64());
+        Ok(self.finish_search("mojeek", query, results, total_results, latency_ms))
+    }
+
+    /// Search using the Marginalia API (`public` key when none is set). The
+    /// key is part of the request path; this API has no header for it.
+    fn search_marginalia(&self, query: &str) -> Result<SearchResponse> {
+        let api_key = resolve_api_key(&self.config).unwrap_or_else(|| "public".into());
+
+        let url = format!(
+            "{}/{}/search/{}?count={}",
+            self.config.endpoint.trim_end_matches('/'),
+            encode_query(&api_key),
+            encode_query(query),
+            self.max_results
+        );
+
+        let started = Instant::now();
+        let response = get_json_keyed_url(&self.client, &url, "Marginalia")?;
+
+        let latency_ms = started.elapsed().as_millis() as u64;
+
+        let results: Vec<SearchResult> = response
+            .get("results")
+            .and_then(|r| r.as_array())
+            .map(|arr| {
+                arr.iter()
+                    .take(self.max_results)
+                    .filter_map(|item| {
+                        let url = item.get("url")?.as_str()?;
+                        Some(SearchResult {
+                            title: item.get("title")?.as_str()?.to_string(),
+                            url: url.to_string(),
+                            snippet: item
+                                .get("description")
+                                .and_then(|d| d.as_str())
+                                .unwrap_or("")
+                                .to_string(),
+                            domain: extract_domain(url),
+                            published_date: None,
+                            score: None,
+                            sources: Vec::new(),
+                        })
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        Ok(self.finish_search("marginalia", query, results, None, latency_ms))
+    }
+
+    /// Search Archon's local index of history and saved pages.
+    fn search_local_index(&self, query: &str) -> Result<SearchResponse> {
+        let path = self.config.resolve_local_index()?;
+        let started = Instant::now();
+        let hits = LocalIndex::open(&path)?.search(query, self.max_results)?;
+        let latency_ms = started.elapsed().as_millis() as u64;
+
+        let results = hits
+            .into_iter()
+            .map(|hit| SearchResult {
+                domain: extract_domain(&hit.url),
+                title: if hit.title.trim().is_empty() {
+                    hit.url.clone()
+                } else {
+                    hit.title
+                },
+                snippet: hit.snippet,
+                url: hit.url,
+                published_date: hit
+                    .last_visited
+                    .map(|time| time.format("%Y-%m-%d").to_string()),
+                score: None,
+                sources: Vec::new(),
+            })
+            .collect();
+
+        Ok(self.finish_search(&self.config.name, query, results, None, latency_ms))
+    }
+
+    fn finish_search(
+        &self,
+        provider: &str,
+        query: &str,
+        results: Vec<SearchResult>,
+        total_results: Option<u64>,
+        latency_ms: u64,
+    ) -> SearchResponse {
+        debug!(
+            provider = provider,
+            query = query,
+            results = results.len(),
+            latency_ms = latency_ms,
+            "search completed"
+        );
+
+        SearchResponse {
+            query: query.to_string(),
+            results,
+            total_results,
+            latency_ms,
+            provider: provider.to_string(),
+            providers: Vec::new(),
+        }
+    }
 }
 
 /// Arc - Archon's intelligent search orchestrator.
@@ -460,6 +834,9 @@ pub struct ArcOrchestrator {
     default_provider: Option<String>,
     system_prompt: String,
     clients: HashMap<String, SearchClient>,
+    max_results: usize,
+    fusion: ArcFusionSettings,
+    fetcher: Option<PageFetcher>,
 }
 
 impl ArcOrchestrator {
@@ -485,14 +862,36 @@ impl ArcOrchestrator {
             }
         }
 
+        let fetcher = if settings.fetch.enabled {
+            PageFetcher::from_settings(settings.fetch)
+                .inspect_err(|err| warn!(error = %err, "failed to initialize Arc page fetcher"))
+                .ok()
+        } else {
+            None
+        };
+
         Self {
             enabled: settings.enabled,
             default_provider: settings.default_provider,
             system_prompt: settings.system_prompt,
             clients,
+            max_results: settings.max_results,
+            fusion: settings.fusion,
+            fetcher,
         }
     }
 
+    /// Ground searches on page content fetched by `fetcher`.
+    pub fn with_page_fetcher(mut self, fetcher: PageFetcher) -> Self {
+        self.fetcher = Some(fetcher);
+        self
+    }
+
+    /// Whether grounded searches fetch result pages.
+    pub fn fetch_enabled(&self) -> bool {
+        self.fetcher.is_some()
+    }
+
     /// Check if Arc search is enabled.
     pub fn is_enabled(&self) -> bool {
         self.enabled && !self.clients.is_empty()
@@ -508,8 +907,18 @@ impl ArcOrchestrator {
         self.clients.keys().map(|s| s.as_str()).collect()
     }
 
-    /// Execute a search.
+    /// Whether searches without an explicit provider fuse all providers.
+    pub fn fusion_enabled(&self) -> bool {
+        self.fusion.enabled && self.clients.len() > 1
+    }
+
+    /// Execute a search. `provider` [`FUSION_PROVIDER`] (or none, when fusion
+    /// is enabled) queries every provider and fuses the results.
     pub fn search(&self, query: &str, provider: Option<&str>) -> Result<SearchResponse> {
+        if provider == Some(FUSION_PROVIDER) || (provider.is_none() && self.fusion_enabled()) {
+            return self.search_fused(query);
+        }
+
         let provider_name = provider
             .or(self.default_provider.as_deref())
             .or_else(|| self.clients.keys().next().map(|s| s.as_str()))
@@ -523,6 +932,41 @@ impl ArcOrchestrator {
         client.search(query)
     }
 
+    /// Query all providers in parallel, giving each its own deadline, and
+    /// merge their rankings with reciprocal rank fusion. Fails only when no
+    /// provider answers.
+    pub fn search_fused(&self, query: &str) -> Result<SearchResponse> {
+        if self.clients.is_empty() {
+            bail!("No Arc search provider available");
+        }
+        let mut names: Vec<&String> = self.clients.keys().collect();
+        names.sort();
+        let tasks = names
+            .into_iter()
+            .map(|name| {
+                let client = self.clients[name].clone();
+                let timeout = Duration::from_millis(
+                    client.config.timeout_ms.unwrap_or(self.fusion.timeout_ms),
+                );
+                let query = query.to_string();
+                (name.clone(), timeout, move || client.search(&query))
+            })
+            .collect();
+
+        let started = Instant::now();
+        let runs = gather_searches(tasks);
+        let mut response = fuse_responses(query, runs, self.fusion.rrf_k, self.max_results)?;
+        response.latency_ms = started.elapsed().as_millis() as u64;
+        debug!(
+            provider = "fusion",
+            query = query,
+            results = response.results.len(),
+            latency_ms = response.latency_ms,
+            "search completed"
+        );
+        Ok(response)
+    }
+
     /// Generate citations from search results.
     pub fn generate_citations(&self, results: &[SearchResult]) -> Vec<Citation> {
         results
@@ -534,6 +978,16 @@ impl ArcOrchestrator {
 
     /// Build context for AI prompt from search results.
     pub fn build_search_context(&self, response: &SearchResponse) -> String {
+        self.build_grounded_context(response, &[])
+    }
+
+    /// Build context for AI prompt from search results, adding the page
+    /// excerpts fetched for them.
+    pub fn build_grounded_context(
+        &self,
+        response: &SearchResponse,
+        pages: &[GroundedSource],
+    ) -> String {
         let mut context = String::new();
         context.push_str("## Web Search Results\n\n");
         context.push_str(&format!("Query: {}\n\n", response.query));
@@ -541,10 +995,25 @@ impl ArcOrchestrator {
         for (i, result) in response.results.iter().enumerate() {
             context.push_str(&format!("### [{}] {}\n", i + 1, result.title));
             context.push_str(&format!("Source: {}\n", result.url));
+            if !result.sources.is_empty() {
+                let found_by: Vec<String> = result
+                    .sources
+                    .iter()
+                    .map(|source| format!("{} #{}", source.provider, source.rank))
+                    .collect();
+                context.push_str(&format!("Found by: {}\n", found_by.join(", ")));
+            }
             if let Some(date) = &result.published_date {
                 context.push_str(&format!("Date: {}\n", date));
             }
             context.push_str(&format!("\n{}\n\n", result.snippet));
+            if let Some(page) = pages.iter().find(|page| page.result_index == i) {
+                context.push_str("Excerpts from the page:\n\n");
+                for excerpt in &page.excerpts {
+                    context.push_str(excerpt);
+                    context.push_str("\n\n");
+                }
+            }
         }
 
         context.push_str("---\n\n");
@@ -568,7 +1037,11 @@ impl ArcOrchestrator {
     ) -> Result<ArcSearchResult> {
         let search_response = self.search(query, provider)?;
         let citations = self.generate_citations(&search_response.results);
-        let context = self.build_search_context(&search_response);
+        let pages = match &self.fetcher {
+            Some(fetcher) => fetcher.ground(query, &search_response.results),
+            None => Vec::new(),
+        };
+        let context = self.build_grounded_context(&search_response, &pages);
 
         Ok(ArcSearchResult {
             query: query.to_string(),
@@ -577,6 +1050,8 @@ impl ArcOrchestrator {
             results: search_response.results,
             latency_ms: search_response.latency_ms,
             provider: search_response.provider,
+            providers: search_response.providers,
+            pages,
         })
     }
 }
@@ -594,8 +1069,262 @@ pub struct ArcSearchResult {
     pub results: Vec<SearchResult>,
     /// Search latency in milliseconds.
     pub latency_ms: u64,
-    /// Provider used.
+    /// Provider used (`fusion` when results were merged).
     pub provider: String,
+    /// Per-provider outcome when results were fused.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub providers: Vec<ProviderOutcome>,
+    /// Page excerpts the context was grounded on.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub pages: Vec<GroundedSource>,
+}
+
+/// Answer (or failure) of one provider in a fused search.
+struct ProviderRun {
+    provider: String,
+    latency_ms: u64,
+    result: Result<SearchResponse>,
+}
+
+/// Run every search on its own thread and collect what arrives before each
+/// task's deadline. Late searches are abandoned; their threads end with the
+/// HTTP client's own timeout.
+fn gather_searches<F>(tasks: Vec<(String, Duration, F)>) -> Vec<ProviderRun>
+where
+    F: FnOnce() -> Result<SearchResponse> + Send + 'static,
+{
+    let started = Instant::now();
+    let (tx, rx) = mpsc::channel();
+    let mut runs: Vec<Option<ProviderRun>> = Vec::with_capacity(tasks.len());
+    let mut pending: Vec<(usize, String, Instant)> = Vec::new();
+
+    for (index, (provider, timeout, task)) in tasks.into_iter().enumerate() {
+        let tx = tx.clone();
+        let spawned = thread::Builder::new()
+            .name(format!("arc-search-{provider}"))
+            .spawn(move || {
+                let _ = tx.send((index, task()));
+            });
+        match spawned {
+            Ok(_) => {
+                pending.push((index, provider, started + timeout));
+                runs.push(None);
+            }
+            Err(err) => runs.push(Some(ProviderRun {
+                provider,
+                latency_ms: 0,
+                result: Err(anyhow!(err).context("failed to start search thread")),
+            })),
+        }
+    }
+    drop(tx);
+
+    while let Some(deadline) = pending.iter().map(|(_, _, deadline)| *deadline).min() {
+        match rx.recv_timeout(deadline.saturating_duration_since(Instant::now())) {
+            Ok((index, result)) => {
+                if let Some(position) = pending.iter().position(|(i, _, _)| *i == index) {
+                    let (_, provider, _) = pending.remove(position);
+                    runs[index] = Some(ProviderRun {
+                        provider,
+                        latency_ms: started.elapsed().as_millis() as u64,
+                        result,
+                    });
+                }
+            }
+            Err(RecvTimeoutError::Timeout) => {
+                let now = Instant::now();
+                pending.retain(|(index, provider, deadline)| {
+                    if *deadline > now {
+                        return true;
+                    }
+                    let latency_ms = started.elapsed().as_millis() as u64;
+                    runs[*index] = Some(ProviderRun {
+                        provider: provider.clone(),
+                        latency_ms,
+                        result: Err(anyhow!("timed out after {latency_ms} ms")),
+                    });
+                    false
+                });
+            }
+            Err(RecvTimeoutError::Disconnected) => {
+                for (index, provider, _) in pending.drain(..) {
+                    runs[index] = Some(ProviderRun {
+                        provider,
+                        latency_ms: started.elapsed().as_millis() as u64,
+                        result: Err(anyhow!("search thread exited without a result")),
+                    });
+                }
+            }
+        }
+    }
+
+    runs.into_iter().flatten().collect()
+}
+
+/// Merge provider rankings with reciprocal rank fusion: each result scores
+/// `sum(1 / (k + rank))` over the providers that returned it, results are
+/// deduplicated by [`canonical_url`], and each keeps the providers and ranks
+/// it came from. `score` is scaled so a result ranked first everywhere is 1.0.
+fn fuse_responses(
+    query: &str,
+    runs: Vec<ProviderRun>,
+    rrf_k: f32,
+    max_results: usize,
+) -> Result<SearchResponse> {
+    let rrf_k = rrf_k.max(0.0);
+    let mut fused: Vec<(SearchResult, f32)> = Vec::new();
+    let mut positions: HashMap<String, usize> = HashMap::new();
+    let mut outcomes = Vec::with_capacity(runs.len());
+    let mut answered = 0usize;
+
+    for run in runs {
+        let response = match run.result {
+            Ok(response) => response,
+            Err(err) => {
+                warn!(provider = %run.provider, error = %err, "Arc search provider failed");
+                outcomes.push(ProviderOutcome {
+                    provider: run.provider,
+                    results: 0,
+                    latency_ms: run.latency_ms,
+                    error: Some(format!("{err:#}")),
+                });
+                continue;
+            }
+        };
+        answered += 1;
+        outcomes.push(ProviderOutcome {
+            provider: run.provider.clone(),
+            results: response.results.len(),
+            latency_ms: run.latency_ms,
+            error: None,
+        });
+
+        for (offset, result) in response.results.into_iter().enumerate() {
+            if result.url.trim().is_empty() {
+                continue;
+            }
+            let rank = offset + 1;
+            let source = ResultSource {
+                provider: run.provider.clone(),
+                rank,
+                score: result.score,
+            };
+            let key = canonical_url(&result.url);
+            match positions.get(&key) {
+                Some(&position) => {
+                    let (existing, score) = &mut fused[position];
+                    // A provider listing the same page twice counts once.
+                    if existing
+                        .sources
+                        .iter()
+                        .any(|known| known.provider == source.provider)
+                    {
+                        continue;
+                    }
+                    *score += 1.0 / (rrf_k + rank as f32);
+                    if existing.snippet.trim().is_empty() {
+                        existing.snippet = result.snippet;
+                    }
+                    if existing.domain.is_none() {
+                        existing.domain = result.domain;
+                    }
+                    if existing.published_date.is_none() {
+                        existing.published_date = result.published_date;
+                    }
+                    existing.sources.push(source);
+                }
+                None => {
+                    positions.insert(key, fused.len());
+                    let score = 1.0 / (rrf_k + rank as f32);
+                    fused.push((
+                        SearchResult {
+                            sources: vec![source],
+                            ..result
+                        },
+                        score,
+                    ));
+                }
+            }
+        }
+    }
+
+    if answered == 0 {
+        let reasons: Vec<String> = outcomes
+            .iter()
+            .map(|outcome| {
+                format!(
+                    "{}: {}",
+                    outcome.provider,
+                    outcome.error.as_deref().unwrap_or("no answer")
+                )
+            })
+            .collect();
+        bail!("all Arc search providers failed ({})", reasons.join("; "));
+    }
+
+    let best = answered as f32 / (rrf_k + 1.0);
+    fused.sort_by(|(a, a_score), (b, b_score)| {
+        b_score.total_cmp(a_score).then_with(|| {
+            let best_rank =
+                |result: &SearchResult| result.sources.iter().map(|source| source.rank).min();
+            best_rank(a).cmp(&best_rank(b))
+        })
+    });
+    fused.truncate(max_results);
+    let results = fused
+        .into_iter()
+        .map(|(result, score)| SearchResult {
+            score: Some((score / best).min(1.0)),
+            ..result
+        })
+        .collect();
+
+    Ok(SearchResponse {
+        query: query.to_string(),
+        results,
+        total_results: None,
+        latency_ms: 0,
+        provider: "fusion".into(),
+        providers: outcomes,
+    })
+}
+
+/// Key identifying the same page across providers: scheme, `www.`, fragment,
+/// trailing slash and tracking parameters are ignored and the remaining query
+/// parameters are sorted.
+pub fn canonical_url(raw: &str) -> String {
+    let raw = raw.trim();
+    let Ok(url) = url::Url::parse(raw) else {
+        return raw.to_lowercase();
+    };
+    let host = url
+        .host_str()
+        .map(|host| host.trim_start_matches("www.").to_lowercase())
+        .unwrap_or_default();
+    let mut key = host;
+    if let Some(port) = url.port() {
+        key.push_str(&format!(":{port}"));
+    }
+    key.push_str(url.path().trim_end_matches('/'));
+
+    let mut params: Vec<(String, String)> = url
+        .query_pairs()
+        .filter(|(name, _)| {
+            let name = name.to_ascii_lowercase();
+            !name.starts_with("utm_") && !TRACKING_PARAMS.contains(&name.as_str())
+        })
+        .map(|(name, value)| (name.into_owned(), value.into_owned()))
+        .collect();
+    if !params.is_empty() {
+        params.sort();
+        key.push('?');
+        key.push_str(
+            &form_urlencoded::Serializer::new(String::new())
+                .extend_pairs(params)
+                .finish(),
+        );
+    }
+    key
 }
 
 /// Format citations as a footer for AI responses.
@@ -630,6 +1359,7 @@ mod tests {
             domain: Some("example.com".into()),
             published_date: None,
             score: None,
+            sources: Vec::new(),
         };
 
         let citation = Citation::from_result(1, &result);
@@ -647,6 +1377,138 @@ mod tests {
         assert_eq!(extract_domain("invalid"), None);
     }
 
+    fn response(provider: &str, urls: &[&str]) -> SearchResponse {
+        SearchResponse {
+            query: "q".into(),
+            results: urls
+                .iter()
+                .map(|url| SearchResult {
+                    title: format!("{provider} {url}"),
+                    url: url.to_string(),
+                    snippet: String::new(),
+                    domain: None,
+                    published_date: None,
+                    score: None,
+                    sources: Vec::new(),
+                })
+                .collect(),
+            total_results: None,
+            latency_ms: 1,
+            provider: provider.into(),
+            providers: Vec::new(),
+        }
+    }
+
+    fn run(provider: &str, result: Result<SearchResponse>) -> ProviderRun {
+        ProviderRun {
+            provider: provider.into(),
+            latency_ms: 1,
+            result,
+        }
+    }
+
+    #[test]
+    fn test_canonical_url() {
+        let key = canonical_url("https://www.Example.com/guide/?b=2&a=1&utm_source=x#intro");
+        assert_eq!(key, "example.com/guide?a=1&b=2");
+        assert_eq!(
+            canonical_url("http://example.com/guide?a=1&b=2&fbclid=z"),
+            key
+        );
+        assert_ne!(canonical_url("https://example.com:8443/guide?a=1&b=2"), key);
+        assert_ne!(canonical_url("https://example.com/guide?a=2&b=2"), key);
+    }
+
+    #[test]
+    fn test_fusion_merges_rankings_and_keeps_provenance() {
+        let runs = vec![
+            run(
+                "brave",
+                Ok(response(
+                    "brave",
+                    &[
+                        "https://a.example/",
+                        "https://b.example/",
+                        "https://c.example/",
+                    ],
+                )),
+            ),
+            run(
+                "searxng",
+                Ok(response(
+                    "searxng",
+                    &[
+                        "https://www.b.example",
+                        "https://d.example/",
+                        "https://a.example/#top",
+                    ],
+                )),
+            ),
+            run("tavily", Err(anyhow!("rate limited"))),
+        ];
+
+        let fused = fuse_responses("q", runs, 60.0, 3).unwrap();
+        assert_eq!(fused.provider, "fusion");
+        let urls: Vec<&str> = fused.results.iter().map(|r| r.url.as_str()).collect();
+        // b: 1/62 + 1/61 beats a: 1/61 + 1/63; single hits follow.
+        assert_eq!(
+            urls,
+            vec![
+                "https://b.example/",
+                "https://a.example/",
+                "https://d.example/"
+            ]
+        );
+        assert_eq!(
+            fused.results[0].sources,
+            vec![
+                ResultSource {
+                    provider: "brave".into(),
+                    rank: 2,
+                    score: None,
+                },
+                ResultSource {
+                    provider: "searxng".into(),
+                    rank: 1,
+                    score: None,
+                },
+            ]
+        );
+        assert!(fused.results[0].score.unwrap() > fused.results[2].score.unwrap());
+        assert_eq!(fused.providers.len(), 3);
+        assert_eq!(fused.providers[2].error.as_deref(), Some("rate limited"));
+
+        let err =
+            fuse_responses("q", vec![run("brave", Err(anyhow!("down")))], 60.0, 5).unwrap_err();
+        assert!(err.to_string().contains("brave: down"), "{err}");
+    }
+
+    #[test]
+    fn test_gather_gives_up_on_slow_providers() {
+        type Task = Box<dyn FnOnce() -> Result<SearchResponse> + Send>;
+        let fast: Task = Box::new(|| Ok(response("fast", &["https://a.example/"])));
+        let slow: Task = Box::new(|| {
+            thread::sleep(Duration::from_secs(2));
+            Ok(response("slow", &["https://b.example/"]))
+        });
+        let started = Instant::now();
+        let runs = gather_searches(vec![
+            ("slow".to_string(), Duration::from_millis(100), slow),
+            ("fast".to_string(), Duration::from_secs(5), fast),
+        ]);
+        assert!(started.elapsed() < Duration::from_secs(1));
+        assert_eq!(runs[0].provider, "slow");
+        assert!(
+            runs[0]
+                .result
+                .as_ref()
+                .unwrap_err()
+                .to_string()
+                .contains("timed out")
+        );
+        assert_eq!(runs[1].result.as_ref().unwrap().results.len(), 1);
+    }
+
     #[test]
     fn test_arc_orchestrator_disabled() {
         let settings = ArcSearchSettings::default();
@@ -660,4 +1522,225 @@ mod tests {
         let orchestrator = ArcOrchestrator::from_settings(settings);
         assert!(orchestrator.system_prompt().contains("Arc"));
     }
+
+    /// Serve `body` as JSON to one request and hand back the request text.
+    fn fixture_server(body: &'static str) -> (String, mpsc::Receiver<String>) {
+        use std::io::{Read, Write};
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let endpoint = format!("http://{}/api", listener.local_addr().unwrap());
+        let (tx, rx) = mpsc::channel();
+        thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut raw = Vec::new();
+            let mut buf = [0u8; 4096];
+            loop {
+                let n = stream.read(&mut buf).unwrap();
+                raw.extend_from_slice(&buf[..n]);
+                let text = String::from_utf8_lossy(&raw).to_string();
+                if let Some(end) = text.find("\r\n\r\n") {
+                    let length = text[..end]
+                        .lines()
+                        .find_map(|line| {
+                            line.to_ascii_lowercase()
+                                .strip_prefix("content-length:")
+                                .map(|value| value.trim().parse::<usize>().unwrap())
+                        })
+                        .unwrap_or(0);
+                    if raw.len() >= end + 4 + length {
+                        tx.send(text).unwrap();
+                        break;
+                    }
+                }
+            }
+            let response = format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                body.len()
+            );
+            stream.write_all(response.as_bytes()).unwrap();
+        });
+        (endpoint, rx)
+    }
+
+    /// Run one search against `fixture` through `SearchClient::search`.
+    fn search_fixture(
+        kind: ArcSearchProviderKind,
+        fixture: &'static str,
+    ) -> (SearchResponse, String) {
+        let (endpoint, requests) = fixture_server(fixture);
+        let mut env = crate::test_util::EnvVarGuard::new();
+        env.set("ARCHON_TEST_SEARCH_KEY", "test-key");
+        let client = SearchClient::new(
+            ArcSearchProviderConfig {
+                name: "fixture".into(),
+                kind,
+                endpoint,
+                api_key_env: Some("ARCHON_TEST_SEARCH_KEY".into()),
+                enabled: true,
+                timeout_ms: Some(5_000),
+            },
+            5,
+        )
+        .unwrap();
+        let response = client.search("dns over quic").unwrap();
+        (response, requests.recv().unwrap())
+    }
+
+    #[test]
+    fn test_kagi_fixture() {
+        let (response, request) = search_fixture(
+            ArcSearchProviderKind::Kagi,
+            include_str!("../tests/fixtures/arc/kagi.json"),
+        );
+        assert!(
+            request.starts_with("GET /api?q=dns+over+quic&limit=5 "),
+            "{request}"
+        );
+        assert!(request.contains("authorization: Bot test-key"), "{request}");
+        assert_eq!(response.provider, "kagi");
+        assert_eq!(response.results.len(), 2);
+        assert_eq!(
+            response.results[0].url,
+            "https://www.rfc-editor.org/rfc/rfc9250"
+        );
+        assert_eq!(
+            response.results[0].domain.as_deref(),
+            Some("www.rfc-editor.org")
+        );
+        assert_eq!(
+            response.results[0].published_date.as_deref(),
+            Some("2022-05-01T00:00:00Z")
+        );
+        assert_eq!(response.results[1].title, "DoQ in practice");
+    }
+
+    #[test]
+    fn test_exa_fixture() {
+        let (response, request) = search_fixture(
+            ArcSearchProviderKind::Exa,
+            include_str!("../tests/fixtures/arc/exa.json"),
+        );
+        assert!(request.starts_with("POST /api "), "{request}");
+        assert!(request.contains("x-api-key: test-key"), "{request}");
+        assert!(request.contains(r#""numResults":5"#), "{request}");
+        assert_eq!(response.provider, "exa");
+        let first = &response.results[0];
+        assert_eq!(
+            first.snippet,
+            "DoQ provides transport confidentiality for DNS. … The default port for DoQ is UDP port 853."
+        );
+        assert_eq!(first.score, Some(0.4211));
+        let second = &response.results[1];
+        assert_eq!(second.title, "https://notes.example/quic");
+        assert_eq!(
+            second.snippet,
+            "Notes on running a DoQ resolver behind a load balancer."
+        );
+        assert_eq!(second.published_date, None);
+    }
+
+    #[test]
+    fn test_mojeek_fixture() {
+        let (response, request) = search_fixture(
+            ArcSearchProviderKind::Mojeek,
+            include_str!("../tests/fixtures/arc/mojeek.json"),
+        );
+        assert!(
+            request.starts_with("GET /api?q=dns+over+quic&fmt=json&t=5&api_key=test-key "),
+            "{request}"
+        );
+        assert_eq!(response.provider, "mojeek");
+        assert_eq!(response.total_results, Some(1840));
+        assert_eq!(response.results.len(), 2);
+        assert_eq!(
+            response.results[0].published_date.as_deref(),
+            Some("2022-05-01")
+        );
+        assert_eq!(response.results[1].published_date, None);
+        assert_eq!(
+            response.results[1].snippet,
+            "DNS over QUIC (DoQ) is a protocol for encrypting DNS queries."
+        );
+    }
+
+    #[test]
+    fn keyed_url_errors_do_not_leak_the_key() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let endpoint = format!("http://{}/api", listener.local_addr().unwrap());
+        drop(listener);
+        let mut env = crate::test_util::EnvVarGuard::new();
+        env.set("ARCHON_TEST_SEARCH_KEY", "secret-key");
+        for kind in [
+            ArcSearchProviderKind::Mojeek,
+            ArcSearchProviderKind::Marginalia,
+        ] {
+            let client = SearchClient::new(
+                ArcSearchProviderConfig {
+                    name: "offline".into(),
+                    kind,
+                    endpoint: endpoint.clone(),
+                    api_key_env: Some("ARCHON_TEST_SEARCH_KEY".into()),
+                    enabled: true,
+                    timeout_ms: Some(5_000),
+                },
+                5,
+            )
+            .unwrap();
+            let err = client.search("dns over quic").unwrap_err();
+            assert!(!format!("{err:?}").contains("secret-key"), "{err:?}");
+        }
+    }
+
+    #[test]
+    fn test_marginalia_fixture() {
+        let (response, request) = search_fixture(
+            ArcSearchProviderKind::Marginalia,
+            include_str!("../tests/fixtures/arc/marginalia.json"),
+        );
+        assert!(
+            request.starts_with("GET /api/test-key/search/dns+over+quic?count=5 "),
+            "{request}"
+        );
+        assert_eq!(response.provider, "marginalia");
+        let titles: Vec<&str> = response.results.iter().map(|r| r.title.as_str()).collect();
+        assert_eq!(
+            titles,
+            ["Running my own DoQ resolver", "QUIC for the curious"]
+        );
+        assert_eq!(
+            response.results[0].domain.as_deref(),
+            Some("smallweb.example")
+        );
+    }
+
+    #[test]
+    fn test_local_index_provider() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("local-index.sqlite");
+        LocalIndex::open(&path)
+            .unwrap()
+            .save_page(
+                "https://wiki.example/quic",
+                "QUIC notes",
+                "DNS over QUIC listens on port 853.",
+            )
+            .unwrap();
+        let client = SearchClient::new(
+            ArcSearchProviderConfig {
+                endpoint: path.display().to_string(),
+                enabled: true,
+                ..ArcSearchProviderConfig::local_index_default()
+            },
+            5,
+        )
+        .unwrap();
+
+        let response = client.search("quic port").unwrap();
+        assert_eq!(response.provider, "local");
+        assert_eq!(response.results.len(), 1);
+        assert_eq!(response.results[0].title, "QUIC notes");
+        assert_eq!(response.results[0].domain.as_deref(), Some("wiki.example"));
+        assert!(response.results[0].snippet.contains("853"));
+        assert!(client.search("unrelated").unwrap().results.is_empty());
+    }
 }
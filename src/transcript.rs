@@ -3,12 +3,14 @@ use std::fs;
 use std::path::{Path, PathBuf};
 use std::sync::Mutex;
 
-use anyhow::{Context, Result};
-use chrono::{DateTime, Duration, Utc};
+use anyhow::{Context, Result, anyhow, bail};
+use chrono::{DateTime, Duration, NaiveDate, TimeZone, Utc};
+use rusqlite::{Connection, params};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
 use crate::sync_util::LockResultExt;
+use crate::transcript_crypto::{self, TranscriptCipher};
 
 /// Maximum length used when deriving a transcript title from the first user message.
 const DEFAULT_TITLE_LIMIT: usize = 80;
@@ -16,6 +18,22 @@ const DEFAULT_TITLE_LIMIT: usize = 80;
 /// Directory name used to store attachments relative to the transcript folder.
 const ATTACHMENTS_DIR: &str = "attachments";
 
+/// SQLite full-text index kept alongside the transcript folders.
+const SEARCH_INDEX_FILE: &str = "search.sqlite";
+
+/// Encrypted copy of a conversation's embeddings, kept beside its transcript
+/// while the search index lives in memory.
+const SEALED_EMBEDDINGS_FILE: &str = "embeddings.sealed";
+
+/// Schema version stored in `PRAGMA user_version`; a mismatch triggers a rebuild.
+const SEARCH_INDEX_VERSION: i64 = 1;
+
+/// Default number of conversations returned by [`TranscriptStore::search`].
+const DEFAULT_SEARCH_LIMIT: usize = 20;
+
+/// Number of tokens FTS5 includes in each highlighted snippet.
+const SNIPPET_TOKENS: i64 = 16;
+
 /// Persistent store for AI conversation transcripts.
 #[derive(Debug, Clone)]
 pub struct TranscriptRetention {
@@ -47,6 +65,8 @@ pub struct TranscriptStore {
     root: PathBuf,
     lock: Mutex<()>,
     retention: TranscriptRetention,
+    index: Mutex<Option<Connection>>,
+    cipher: Option<TranscriptCipher>,
 }
 
 impl TranscriptStore {
@@ -63,9 +83,30 @@ impl TranscriptStore {
             root,
             lock: Mutex::new(()),
             retention,
+            index: Mutex::new(None),
+            cipher: None,
         })
     }
 
+    /// Encrypt everything written from now on with `cipher` and decrypt
+    /// encrypted files on load. The search index is then kept in memory, since
+    /// it holds message text; recall embeddings persist in an encrypted
+    /// sidecar per conversation.
+    pub fn with_encryption(mut self, cipher: TranscriptCipher) -> Self {
+        self.cipher = Some(cipher);
+        self
+    }
+
+    /// Whether this store encrypts what it writes.
+    pub fn is_encrypted(&self) -> bool {
+        self.cipher.is_some()
+    }
+
+    /// Cipher used for new files, when encryption is on.
+    pub fn cipher(&self) -> Option<&TranscriptCipher> {
+        self.cipher.as_ref()
+    }
+
     /// Root directory backing this transcript store.
     pub fn root(&self) -> &std::path::Path {
         &self.root
@@ -113,6 +154,11 @@ impl TranscriptStore {
         Ok(summaries)
     }
 
+    /// Whether a conversation with this ID exists in the store.
+    pub fn contains(&self, id: Uuid) -> bool {
+        self.conversation_dir(id).exists()
+    }
+
     /// Retrieve the absolute path to the JSON representation of a transcript.
     pub fn json_path(&self, id: Uuid) -> PathBuf {
         self.conversation_dir(id).join("transcript.json")
@@ -131,7 +177,8 @@ impl TranscriptStore {
     /// Load the JSON representation as a string.
     pub fn load_json(&self, id: Uuid) -> Result<String> {
         let path = self.json_path(id);
-        let data = fs::read_to_string(&path)
+        let data = self
+            .read_text(&path)
             .with_context(|| format!("failed to read transcript JSON {}", path.display()))?;
         Ok(data)
     }
@@ -139,11 +186,21 @@ impl TranscriptStore {
     /// Load the Markdown representation as a string.
     pub fn load_markdown(&self, id: Uuid) -> Result<String> {
         let path = self.markdown_path(id);
-        let data = fs::read_to_string(&path)
+        let data = self
+            .read_text(&path)
             .with_context(|| format!("failed to read transcript Markdown {}", path.display()))?;
         Ok(data)
     }
 
+    /// Load an attachment by its `stored_filename` (e.g. `attachments/photo.png`).
+    pub fn load_attachment(&self, id: Uuid, stored_filename: &str) -> Result<Vec<u8>> {
+        let path = self
+            .conversation_dir(id)
+            .join(attachment_path(stored_filename)?);
+        self.read_file(&path)
+            .with_context(|| format!("failed to read transcript attachment {}", path.display()))
+    }
+
     /// Load all recorded transcript messages for a conversation.
     pub fn load_messages(&self, id: Uuid) -> Result<Vec<TranscriptMessage>> {
         let transcript = self.load_transcript(id)?;
@@ -170,7 +227,8 @@ impl TranscriptStore {
         };
 
         let now = Utc::now();
-        let attachments = persist_attachments(&conversation_dir, input.attachments)?;
+        let attachments =
+            persist_attachments(&conversation_dir, input.attachments, self.cipher.as_ref())?;
         transcript.append_user_message(input.prompt_text, attachments, now);
         transcript.append_assistant_message(
             input.reply_text,
@@ -186,7 +244,14 @@ impl TranscriptStore {
         }
 
         transcript.updated_at = now;
-        persist_transcript_files(&conversation_dir, &transcript)?;
+        persist_transcript_files(&conversation_dir, &transcript, self.cipher.as_ref())?;
+        if let Err(err) = self.with_index(|conn| index_transcript(conn, &transcript)) {
+            tracing::warn!(
+                error = %err,
+                transcript = %resolved_id,
+                "failed to update transcript search index"
+            );
+        }
 
         if self.retention.prune_on_write && !self.retention.is_unbounded() {
             self.prune_locked()?;
@@ -209,6 +274,190 @@ impl TranscriptStore {
         })
     }
 
+    /// Fork conversation `id` after message `message_index` (zero-based): the
+    /// new conversation starts with copies of messages `0..=message_index`
+    /// and their attachments, and points back at its parent.
+    pub fn branch(&self, id: Uuid, message_index: usize) -> Result<TranscriptRecord> {
+        let _guard = self.lock.lock().recover();
+        let parent = self.load_transcript(id)?;
+        if message_index >= parent.messages.len() {
+            bail!(
+                "message {message_index} is out of range; transcript {id} has {} message(s)",
+                parent.messages.len()
+            );
+        }
+
+        let branch_id = Uuid::new_v4();
+        let parent_dir = self.conversation_dir(id);
+        let branch_dir = self.conversation_dir(branch_id);
+        fs::create_dir_all(&branch_dir).with_context(|| {
+            format!(
+                "failed to create transcript conversation directory {}",
+                branch_dir.display()
+            )
+        })?;
+
+        let messages = parent.messages[..=message_index].to_vec();
+        for attachment in messages.iter().flat_map(|message| &message.attachments) {
+            let relative = attachment_path(&attachment.stored_filename)?;
+            let from = parent_dir.join(relative);
+            let to = branch_dir.join(relative);
+            if let Some(dir) = to.parent() {
+                fs::create_dir_all(dir)
+                    .with_context(|| format!("failed to create {}", dir.display()))?;
+            }
+            // Copied as stored, so encrypted attachments stay encrypted.
+            fs::copy(&from, &to)
+                .with_context(|| format!("failed to copy attachment {}", from.display()))?;
+        }
+
+        let now = Utc::now();
+        let title = if parent.title.is_empty() {
+            format!("Conversation {id}")
+        } else {
+            parent.title.clone()
+        };
+        let transcript = Transcript {
+            id: branch_id,
+            title: format!("{title} (branch)"),
+            source: parent.source,
+            created_at: now,
+            updated_at: now,
+            messages,
+            recall: parent.recall,
+            parent: Some(TranscriptParent {
+                conversation_id: id,
+                message_index,
+            }),
+        };
+        self.write_new_transcript(&transcript)
+    }
+
+    /// The branch tree containing conversation `id`, rooted at its oldest
+    /// ancestor still on disk. Children are ordered oldest first, then by the
+    /// message they branch from.
+    pub fn tree(&self, id: Uuid) -> Result<TranscriptTree> {
+        let summaries = self.list()?;
+        if !summaries.iter().any(|summary| summary.id == id) {
+            bail!("transcript {id} not found");
+        }
+
+        let mut root = id;
+        let mut seen = HashSet::from([id]);
+        while let Some(parent) = summaries
+            .iter()
+            .find(|summary| summary.id == root)
+            .and_then(|summary| summary.parent.as_ref())
+            .map(|parent| parent.conversation_id)
+            .filter(|parent| summaries.iter().any(|summary| summary.id == *parent))
+        {
+            if !seen.insert(parent) {
+                break;
+            }
+            root = parent;
+        }
+
+        // `visited` stops parent cycles (e.g. from hand-edited files) from
+        // recursing forever; each conversation appears once.
+        fn build(
+            id: Uuid,
+            summaries: &[TranscriptSummary],
+            visited: &mut HashSet<Uuid>,
+        ) -> TranscriptTree {
+            visited.insert(id);
+            let summary = summaries
+                .iter()
+                .find(|summary| summary.id == id)
+                .cloned()
+                .expect("tree node exists");
+            let mut children: Vec<&TranscriptSummary> = summaries
+                .iter()
+                .filter(|child| {
+                    !visited.contains(&child.id)
+                        && child
+                            .parent
+                            .as_ref()
+                            .is_some_and(|parent| parent.conversation_id == id)
+                })
+                .collect();
+            children.sort_by_key(|child| {
+                (
+                    child.created_at,
+                    child.parent.as_ref().map(|parent| parent.message_index),
+                )
+            });
+            let mut nodes = Vec::with_capacity(children.len());
+            for child in children {
+                if !visited.contains(&child.id) {
+                    nodes.push(build(child.id, summaries, visited));
+                }
+            }
+            TranscriptTree {
+                summary,
+                children: nodes,
+            }
+        }
+
+        Ok(build(root, &summaries, &mut HashSet::new()))
+    }
+
+    /// Store a complete transcript under its own ID (which must be unused),
+    /// writing `attachments` as `(stored_filename, bytes)` pairs next to it.
+    pub fn insert_transcript(
+        &self,
+        transcript: &Transcript,
+        attachments: &[(String, Vec<u8>)],
+    ) -> Result<TranscriptRecord> {
+        let _guard = self.lock.lock().recover();
+        let dir = self.conversation_dir(transcript.id);
+        if dir.exists() {
+            bail!("transcript {} already exists", transcript.id);
+        }
+        fs::create_dir_all(&dir).with_context(|| {
+            format!(
+                "failed to create transcript conversation directory {}",
+                dir.display()
+            )
+        })?;
+        for (stored_filename, data) in attachments {
+            let path = dir.join(attachment_path(stored_filename)?);
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)
+                    .with_context(|| format!("failed to create {}", parent.display()))?;
+            }
+            write_sealed(&path, data, self.cipher.as_ref())
+                .with_context(|| format!("failed to persist attachment {}", path.display()))?;
+        }
+        self.write_new_transcript(transcript)
+    }
+
+    fn write_new_transcript(&self, transcript: &Transcript) -> Result<TranscriptRecord> {
+        let dir = self.conversation_dir(transcript.id);
+        persist_transcript_files(&dir, transcript, self.cipher.as_ref())?;
+        if let Err(err) = self.with_index(|conn| index_transcript(conn, transcript)) {
+            tracing::warn!(
+                error = %err,
+                transcript = %transcript.id,
+                "failed to update transcript search index"
+            );
+        }
+
+        let mut summary = transcript.clone().into_summary();
+        match directory_size(&dir) {
+            Ok(size) => summary.size_bytes = size,
+            Err(err) => tracing::warn!(
+                error = %err,
+                transcript = %summary.id,
+                "failed to determine transcript size after write"
+            ),
+        }
+        Ok(TranscriptRecord {
+            summary,
+            json_path: self.json_path(transcript.id),
+            markdown_path: self.markdown_path(transcript.id),
+        })
+    }
+
     /// Manually apply the configured retention policy.
     pub fn prune(&self) -> Result<()> {
         let _guard = self.lock.lock().recover();
@@ -267,8 +516,8 @@ impl TranscriptStore {
             }
         }
 
-        for id in remove {
-            let dir = self.conversation_dir(id);
+        for id in &remove {
+            let dir = self.conversation_dir(*id);
             if dir.exists() {
                 fs::remove_dir_all(&dir).with_context(|| {
                     format!("failed to remove expired transcript {}", dir.display())
@@ -276,22 +525,539 @@ impl TranscriptStore {
             }
         }
 
+        if !remove.is_empty()
+            && let Err(err) = self.with_index(|conn| unindex_transcripts(conn, &remove))
+        {
+            tracing::warn!(error = %err, "failed to drop pruned transcripts from search index");
+        }
+
+        Ok(())
+    }
+
+    /// Path of the SQLite full-text index backing [`TranscriptStore::search`].
+    pub fn search_index_path(&self) -> PathBuf {
+        self.root.join(SEARCH_INDEX_FILE)
+    }
+
+    /// Full-text search across recorded messages, returning the best-matching
+    /// message of each conversation ordered by relevance.
+    pub fn search(&self, query: &TranscriptSearchQuery) -> Result<Vec<TranscriptSearchHit>> {
+        let expression = fts_expression(&query.text)
+            .ok_or_else(|| anyhow!("search query must contain at least one term"))?;
+        self.with_index(|conn| search_index(conn, &expression, query))
+    }
+
+    /// Drop and repopulate the search index from the transcripts on disk.
+    pub fn rebuild_search_index(&self) -> Result<usize> {
+        let _guard = self.lock.lock().recover();
+        self.with_index(|conn| self.rebuild_index(conn))
+    }
+
+    /// Whether semantic recall is switched on for a conversation.
+    pub fn recall_enabled(&self, id: Uuid) -> Result<bool> {
+        Ok(self.load_transcript(id)?.recall)
+    }
+
+    /// Switch semantic recall on or off for a conversation. Switching it off
+    /// drops the conversation's stored embeddings.
+    pub fn set_recall(&self, id: Uuid, enabled: bool) -> Result<()> {
+        let _guard = self.lock.lock().recover();
+        let mut transcript = self.load_transcript(id)?;
+        if transcript.recall != enabled {
+            transcript.recall = enabled;
+            persist_transcript_files(
+                &self.conversation_dir(id),
+                &transcript,
+                self.cipher.as_ref(),
+            )?;
+        }
+        if !enabled {
+            self.with_index(|conn| {
+                conn.execute(
+                    "DELETE FROM embeddings WHERE conversation_id = ?1",
+                    params![id.to_string()],
+                )
+                .context("failed to drop transcript embeddings")?;
+                Ok(())
+            })?;
+            let sealed = self.conversation_dir(id).join(SEALED_EMBEDDINGS_FILE);
+            if sealed.exists() {
+                fs::remove_file(&sealed)
+                    .with_context(|| format!("failed to remove {}", sealed.display()))?;
+            }
+        }
         Ok(())
     }
 
+    /// Messages of a conversation (with their position) that have no embedding
+    /// for `model` yet.
+    pub fn unembedded_messages(
+        &self,
+        id: Uuid,
+        model: &str,
+    ) -> Result<Vec<(usize, TranscriptMessage)>> {
+        let transcript = self.load_transcript(id)?;
+        let embedded: HashSet<i64> = self.with_index(|conn| {
+            let mut stmt = conn
+                .prepare(
+                    "SELECT message_index FROM embeddings WHERE conversation_id = ?1 AND model = ?2",
+                )
+                .context("failed to prepare embedding lookup")?;
+            let rows = stmt
+                .query_map(params![id.to_string(), model], |row| row.get(0))
+                .context("embedding lookup failed")?;
+            rows.collect::<rusqlite::Result<_>>()
+                .context("embedding lookup failed")
+        })?;
+        Ok(transcript
+            .messages
+            .into_iter()
+            .enumerate()
+            .filter(|(index, message)| {
+                !message.content.trim().is_empty() && !embedded.contains(&(*index as i64))
+            })
+            .collect())
+    }
+
+    /// Store embedding vectors for messages returned by [`Self::unembedded_messages`].
+    pub fn store_embeddings(
+        &self,
+        id: Uuid,
+        model: &str,
+        embeddings: &[(usize, TranscriptMessage, Vec<f32>)],
+    ) -> Result<()> {
+        self.with_index(|conn| {
+            let tx = conn
+                .transaction()
+                .context("failed to start transcript embedding update")?;
+            for (index, message, vector) in embeddings {
+                tx.execute(
+                    "INSERT OR REPLACE INTO embeddings
+                         (conversation_id, message_index, model, role, content, timestamp, vector)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                    params![
+                        id.to_string(),
+                        *index as i64,
+                        model,
+                        role_key(message.role),
+                        message.content,
+                        message.timestamp.timestamp(),
+                        encode_vector(vector),
+                    ],
+                )
+                .context("failed to store transcript embedding")?;
+            }
+            tx.commit()
+                .context("failed to commit transcript embedding update")?;
+            match &self.cipher {
+                Some(cipher) => self.seal_embeddings(conn, id, cipher),
+                None => Ok(()),
+            }
+        })
+    }
+
+    /// Write conversation `id`'s rows of the in-memory embeddings table to
+    /// its encrypted sidecar, from which [`Self::open_index`] restores them.
+    fn seal_embeddings(
+        &self,
+        conn: &Connection,
+        id: Uuid,
+        cipher: &TranscriptCipher,
+    ) -> Result<()> {
+        let rows = embedding_rows(conn, &id.to_string())?;
+        let path = self.conversation_dir(id).join(SEALED_EMBEDDINGS_FILE);
+        let json = serde_json::to_vec(&rows).context("failed to serialise embeddings")?;
+        write_sealed(&path, &json, Some(cipher))
+            .with_context(|| format!("failed to write {}", path.display()))
+    }
+
+    /// Load every conversation's sealed embeddings into `conn`. Unreadable
+    /// sidecars are skipped; their conversations are embedded again.
+    fn load_sealed_embeddings(&self, conn: &mut Connection) -> Result<()> {
+        let tx = conn
+            .transaction()
+            .context("failed to start transcript embedding restore")?;
+        for id in self.conversation_ids()? {
+            let path = self.conversation_dir(id).join(SEALED_EMBEDDINGS_FILE);
+            if !path.exists() {
+                continue;
+            }
+            let rows = self.read_file(&path).and_then(|data| {
+                serde_json::from_slice::<Vec<SealedEmbedding>>(&data)
+                    .context("malformed sealed embeddings")
+            });
+            match rows {
+                Ok(rows) => insert_embedding_rows(&tx, &id.to_string(), &rows)?,
+                Err(err) => tracing::warn!(
+                    error = %err,
+                    path = %path.display(),
+                    "skipping unreadable sealed embeddings"
+                ),
+            }
+        }
+        tx.commit()
+            .context("failed to commit transcript embedding restore")
+    }
+
+    /// Messages from other conversations most similar to `query` by cosine
+    /// similarity, best first.
+    pub fn recall_similar(
+        &self,
+        model: &str,
+        query: &[f32],
+        exclude: Option<Uuid>,
+        limit: usize,
+        min_similarity: f32,
+    ) -> Result<Vec<RecalledMessage>> {
+        let excluded = exclude.map(|id| id.to_string()).unwrap_or_default();
+        let mut recalled = self.with_index(|conn| {
+            let mut stmt = conn
+                .prepare(
+                    "SELECT e.conversation_id, c.title, e.role, e.content, e.timestamp, e.vector
+                     FROM embeddings e LEFT JOIN conversations c ON c.id = e.conversation_id
+                     WHERE e.model = ?1 AND e.conversation_id != ?2",
+                )
+                .context("failed to prepare transcript recall")?;
+            let mut rows = stmt
+                .query(params![model, excluded])
+                .context("transcript recall failed")?;
+            let mut recalled = Vec::new();
+            while let Some(row) = rows.next().context("transcript recall failed")? {
+                let vector: Vec<u8> = row.get(5)?;
+                let similarity = cosine_similarity(query, &decode_vector(&vector));
+                if similarity < min_similarity {
+                    continue;
+                }
+                let id: String = row.get(0)?;
+                let Ok(conversation_id) = Uuid::try_parse(&id) else {
+                    continue;
+                };
+                let role: String = row.get(2)?;
+                recalled.push(RecalledMessage {
+                    conversation_id,
+                    title: row
+                        .get::<_, Option<String>>(1)?
+                        .unwrap_or_else(|| format!("Conversation {conversation_id}")),
+                    role: role_from_key(&role),
+                    content: row.get(3)?,
+                    timestamp: timestamp_from_secs(row.get(4)?),
+                    similarity,
+                });
+            }
+            Ok(recalled)
+        })?;
+        recalled.sort_by(|a, b| b.similarity.total_cmp(&a.similarity));
+        recalled.truncate(limit);
+        Ok(recalled)
+    }
+
+    /// Conversations whose messages are closest to the average embedding of
+    /// conversation `id`, best first. Empty when `id` has no embeddings.
+    pub fn related_conversations(
+        &self,
+        id: Uuid,
+        model: &str,
+        limit: usize,
+    ) -> Result<Vec<RelatedConversation>> {
+        let vectors: Vec<Vec<f32>> = self.with_index(|conn| {
+            let mut stmt = conn
+                .prepare("SELECT vector FROM embeddings WHERE conversation_id = ?1 AND model = ?2")
+                .context("failed to prepare embedding lookup")?;
+            let rows = stmt
+                .query_map(params![id.to_string(), model], |row| {
+                    row.get::<_, Vec<u8>>(0).map(|blob| decode_vector(&blob))
+                })
+                .context("embedding lookup failed")?;
+            rows.collect::<rusqlite::Result<_>>()
+                .context("embedding lookup failed")
+        })?;
+        let Some(centroid) = centroid(&vectors) else {
+            return Ok(Vec::new());
+        };
+
+        let mut related: Vec<RelatedConversation> = Vec::new();
+        for message in self.recall_similar(model, &centroid, Some(id), usize::MAX, f32::MIN)? {
+            match related
+                .iter_mut()
+                .find(|entry| entry.id == message.conversation_id)
+            {
+                Some(entry) => entry.similarity = entry.similarity.max(message.similarity),
+                None => related.push(RelatedConversation {
+                    id: message.conversation_id,
+                    title: message.title,
+                    similarity: message.similarity,
+                }),
+            }
+        }
+        related.sort_by(|a, b| b.similarity.total_cmp(&a.similarity));
+        related.truncate(limit);
+        Ok(related)
+    }
+
+    /// Run `f` against the search index, opening (and populating) it on first use.
+    fn with_index<T>(&self, f: impl FnOnce(&mut Connection) -> Result<T>) -> Result<T> {
+        let mut guard = self.index.lock().recover();
+        if guard.is_none() {
+            *guard = Some(self.open_index()?);
+        }
+        let conn = guard.as_mut().expect("search index opened above");
+        f(conn)
+    }
+
+    fn open_index(&self) -> Result<Connection> {
+        let path = self.search_index_path();
+        let mut conn = if self.cipher.is_some() {
+            Connection::open_in_memory().context("failed to open in-memory transcript index")?
+        } else {
+            let conn = Connection::open(&path).with_context(|| {
+                format!("failed to open transcript search index {}", path.display())
+            })?;
+            conn.pragma_update(None, "journal_mode", "WAL")
+                .context("failed to enable WAL mode for transcript search index")?;
+            conn
+        };
+        conn.busy_timeout(std::time::Duration::from_secs(5))
+            .context("failed to configure transcript search index timeout")?;
+
+        let version: i64 = conn
+            .pragma_query_value(None, "user_version", |row| row.get(0))
+            .context("failed to read transcript search index version")?;
+        if version != SEARCH_INDEX_VERSION {
+            conn.execute_batch(
+                "DROP TABLE IF EXISTS conversations;
+                 DROP TABLE IF EXISTS messages;
+                 CREATE TABLE conversations (
+                     id TEXT PRIMARY KEY,
+                     title TEXT NOT NULL,
+                     source TEXT NOT NULL,
+                     providers TEXT NOT NULL,
+                     has_attachments INTEGER NOT NULL,
+                     created_at INTEGER NOT NULL,
+                     updated_at INTEGER NOT NULL
+                 );
+                 CREATE VIRTUAL TABLE messages USING fts5(
+                     content,
+                     attachments,
+                     conversation_id UNINDEXED,
+                     role UNINDEXED,
+                     provider UNINDEXED,
+                     timestamp UNINDEXED,
+                     tokenize = 'porter unicode61'
+                 );",
+            )
+            .context("failed to create transcript search index schema")?;
+            let indexed = self.rebuild_index(&mut conn)?;
+            conn.pragma_update(None, "user_version", SEARCH_INDEX_VERSION)
+                .context("failed to record transcript search index version")?;
+            tracing::info!(
+                transcripts = indexed,
+                index = %path.display(),
+                "built transcript search index"
+            );
+        }
+        // Embeddings cannot be recomputed offline, so they survive full-text rebuilds.
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS embeddings (
+                conversation_id TEXT NOT NULL,
+                message_index INTEGER NOT NULL,
+                model TEXT NOT NULL,
+                role TEXT NOT NULL,
+                content TEXT NOT NULL,
+                timestamp INTEGER NOT NULL,
+                vector BLOB NOT NULL,
+                PRIMARY KEY (conversation_id, message_index, model)
+            )",
+            [],
+        )
+        .context("failed to create transcript embeddings table")?;
+        if self.cipher.is_some() {
+            self.load_sealed_embeddings(&mut conn)?;
+        }
+        Ok(conn)
+    }
+
+    fn rebuild_index(&self, conn: &mut Connection) -> Result<usize> {
+        let tx = conn
+            .transaction()
+            .context("failed to start transcript search index rebuild")?;
+        tx.execute_batch("DELETE FROM conversations; DELETE FROM messages;")
+            .context("failed to clear transcript search index")?;
+        let mut indexed = 0;
+        for id in self.conversation_ids()? {
+            match self.load_transcript(id) {
+                Ok(transcript) => {
+                    write_index_rows(&tx, &transcript)?;
+                    indexed += 1;
+                }
+                Err(err) => tracing::warn!(
+                    error = %err,
+                    transcript = %id,
+                    "skipping malformed transcript while indexing"
+                ),
+            }
+        }
+        tx.commit()
+            .context("failed to commit transcript search index rebuild")?;
+        Ok(indexed)
+    }
+
+    fn conversation_ids(&self) -> Result<Vec<Uuid>> {
+        let mut ids = Vec::new();
+        for entry in fs::read_dir(&self.root).with_context(|| {
+            format!(
+                "failed to list transcript directory {}",
+                self.root.display()
+            )
+        })? {
+            let entry = entry?;
+            if !entry.file_type()?.is_dir() {
+                continue;
+            }
+            if let Some(id) = entry
+                .file_name()
+                .to_str()
+                .and_then(|value| Uuid::try_parse(value).ok())
+            {
+                ids.push(id);
+            }
+        }
+        Ok(ids)
+    }
+
     fn conversation_dir(&self, id: Uuid) -> PathBuf {
         self.root.join(id.to_string())
     }
 
-    fn load_transcript(&self, id: Uuid) -> Result<Transcript> {
+    /// Load and parse a conversation's transcript.
+    pub fn load_transcript(&self, id: Uuid) -> Result<Transcript> {
         let path = self.json_path(id);
-        let raw = fs::read_to_string(&path)
+        let raw = self
+            .read_text(&path)
             .with_context(|| format!("failed to read transcript {}", path.display()))?;
         let transcript: Transcript = serde_json::from_str(&raw)
             .with_context(|| format!("malformed transcript JSON {}", path.display()))?;
         Ok(transcript)
     }
 
+    /// Read a file, decrypting it when it carries the encrypted-file header.
+    fn read_file(&self, path: &Path) -> Result<Vec<u8>> {
+        read_sealed(path, self.cipher.as_ref())
+    }
+
+    fn read_text(&self, path: &Path) -> Result<String> {
+        String::from_utf8(self.read_file(path)?).context("file is not valid UTF-8")
+    }
+
+    /// Encrypt every plaintext transcript, Markdown file and attachment in
+    /// place and delete the on-disk search index (which holds message text).
+    pub fn encrypt_existing(&self) -> Result<EncryptionReport> {
+        let cipher = self
+            .cipher
+            .as_ref()
+            .context("transcript encryption is not enabled")?;
+        let _guard = self.lock.lock().recover();
+        let mut report = EncryptionReport::default();
+        for id in self.conversation_ids()? {
+            let dir = self.conversation_dir(id);
+            let mut files = vec![self.json_path(id), self.markdown_path(id)];
+            let attachments_dir = dir.join(ATTACHMENTS_DIR);
+            if attachments_dir.is_dir() {
+                for entry in fs::read_dir(&attachments_dir).with_context(|| {
+                    format!("failed to list attachments {}", attachments_dir.display())
+                })? {
+                    let entry = entry?;
+                    if entry.file_type()?.is_file() {
+                        files.push(entry.path());
+                    }
+                }
+            }
+
+            let mut changed = false;
+            for path in files.into_iter().filter(|path| path.exists()) {
+                let data = fs::read(&path)
+                    .with_context(|| format!("failed to read {}", path.display()))?;
+                if transcript_crypto::is_encrypted(&data) {
+                    report.already_encrypted += 1;
+                    continue;
+                }
+                write_sealed(&path, &data, Some(cipher))
+                    .with_context(|| format!("failed to encrypt {}", path.display()))?;
+                report.files += 1;
+                changed = true;
+            }
+            if changed {
+                report.conversations += 1;
+            }
+        }
+
+        // Agent run outcomes and the browser artifacts (screenshots, HARs,
+        // PDFs) they reference. Checkpoints under `agents/checkpoints` are
+        // migrated by `CheckpointStore::encrypt_existing`.
+        for dir in ["agents", "agent-artifacts"] {
+            let dir = self.root.join(dir);
+            if !dir.is_dir() {
+                continue;
+            }
+            for entry in fs::read_dir(&dir)
+                .with_context(|| format!("failed to list {}", dir.display()))?
+            {
+                let entry = entry?;
+                if !entry.file_type()?.is_file() {
+                    continue;
+                }
+                let path = entry.path();
+                let data = fs::read(&path)
+                    .with_context(|| format!("failed to read {}", path.display()))?;
+                if transcript_crypto::is_encrypted(&data) {
+                    report.already_encrypted += 1;
+                    continue;
+                }
+                write_sealed(&path, &data, Some(cipher))
+                    .with_context(|| format!("failed to encrypt {}", path.display()))?;
+                report.agent_files += 1;
+            }
+        }
+
+        // Recall embeddings cannot be recomputed offline: seal them before
+        // the plaintext index goes.
+        let index_path = self.search_index_path();
+        if index_path.exists() {
+            *self.index.lock().recover() = None;
+            let conn = Connection::open(&index_path).with_context(|| {
+                format!(
+                    "failed to open transcript search index {}",
+                    index_path.display()
+                )
+            })?;
+            let has_embeddings: bool = conn
+                .query_row(
+                    "SELECT COUNT(*) > 0 FROM sqlite_master WHERE name = 'embeddings'",
+                    [],
+                    |row| row.get(0),
+                )
+                .context("failed to inspect transcript search index")?;
+            for id in self
+                .conversation_ids()?
+                .into_iter()
+                .filter(|_| has_embeddings)
+            {
+                if !embedding_rows(&conn, &id.to_string())?.is_empty() {
+                    self.seal_embeddings(&conn, id, cipher)?;
+                }
+            }
+        }
+
+        for suffix in ["", "-wal", "-shm"] {
+            let path = self.root.join(format!("{SEARCH_INDEX_FILE}{suffix}"));
+            if path.exists() {
+                fs::remove_file(&path)
+                    .with_context(|| format!("failed to remove {}", path.display()))?;
+                report.removed_index = true;
+            }
+        }
+        Ok(report)
+    }
+
     fn collect_disk_entries(&self) -> Result<Vec<DiskEntry>> {
         let mut entries = Vec::new();
         if !self.root.exists() {
@@ -334,6 +1100,21 @@ impl TranscriptStore {
     }
 }
 
+/// Outcome of [`TranscriptStore::encrypt_existing`].
+#[derive(Debug, Default, Clone, Serialize)]
+pub struct EncryptionReport {
+    /// Conversations with at least one file encrypted.
+    pub conversations: usize,
+    /// Files encrypted by this run.
+    pub files: usize,
+    /// Agent outcomes and browser artifacts encrypted by this run.
+    pub agent_files: usize,
+    /// Files that were encrypted already.
+    pub already_encrypted: usize,
+    /// Whether a plaintext on-disk search index was deleted.
+    pub removed_index: bool,
+}
+
 #[derive(Debug)]
 struct DiskEntry {
     id: Uuid,
@@ -354,6 +1135,138 @@ pub struct TranscriptSummary {
     pub source: TranscriptSource,
     #[serde(skip_serializing_if = "is_zero")]
     pub size_bytes: u64,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub recall: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parent: Option<TranscriptParent>,
+}
+
+/// Where a branched conversation was forked from.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TranscriptParent {
+    pub conversation_id: Uuid,
+    /// Index of the last parent message copied into the branch.
+    pub message_index: usize,
+}
+
+/// A conversation and the branches forked from it, see [`TranscriptStore::tree`].
+#[derive(Debug, Clone, Serialize)]
+pub struct TranscriptTree {
+    #[serde(flatten)]
+    pub summary: TranscriptSummary,
+    pub children: Vec<TranscriptTree>,
+}
+
+/// Full-text query and filters for [`TranscriptStore::search`].
+#[derive(Debug, Clone)]
+pub struct TranscriptSearchQuery {
+    /// Search terms; every term must match. A trailing `*` makes a term a prefix.
+    pub text: String,
+    /// Only conversations answered by this provider (case-insensitive).
+    pub provider: Option<String>,
+    /// Only conversations started from this source.
+    pub source: Option<TranscriptSource>,
+    /// Only messages sent at or after this instant.
+    pub since: Option<DateTime<Utc>>,
+    /// Only messages sent at or before this instant.
+    pub until: Option<DateTime<Utc>>,
+    /// Only conversations with (`true`) or without (`false`) attachments.
+    pub attachments: Option<bool>,
+    /// Maximum number of conversations returned.
+    pub limit: usize,
+    /// Markers placed around matched terms in snippets.
+    pub highlight: (String, String),
+}
+
+impl TranscriptSearchQuery {
+    pub fn new(text: impl Into<String>) -> Self {
+        Self {
+            text: text.into(),
+            provider: None,
+            source: None,
+            since: None,
+            until: None,
+            attachments: None,
+            limit: DEFAULT_SEARCH_LIMIT,
+            highlight: ("**".into(), "**".into()),
+        }
+    }
+
+    pub fn with_provider(mut self, provider: impl Into<String>) -> Self {
+        self.provider = Some(provider.into());
+        self
+    }
+
+    pub fn with_source(mut self, source: TranscriptSource) -> Self {
+        self.source = Some(source);
+        self
+    }
+
+    pub fn with_range(
+        mut self,
+        since: Option<DateTime<Utc>>,
+        until: Option<DateTime<Utc>>,
+    ) -> Self {
+        self.since = since;
+        self.until = until;
+        self
+    }
+
+    pub fn with_attachments(mut self, attachments: bool) -> Self {
+        self.attachments = Some(attachments);
+        self
+    }
+
+    pub fn with_limit(mut self, limit: usize) -> Self {
+        self.limit = limit;
+        self
+    }
+
+    pub fn with_highlight(mut self, open: impl Into<String>, close: impl Into<String>) -> Self {
+        self.highlight = (open.into(), close.into());
+        self
+    }
+}
+
+/// Best-matching message of a conversation returned by [`TranscriptStore::search`].
+#[derive(Debug, Clone, Serialize)]
+pub struct TranscriptSearchHit {
+    pub id: Uuid,
+    pub title: String,
+    pub source: TranscriptSource,
+    #[serde(with = "chrono::serde::ts_seconds")]
+    pub updated_at: DateTime<Utc>,
+    pub role: TranscriptRole,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub provider: Option<String>,
+    #[serde(with = "chrono::serde::ts_seconds")]
+    pub timestamp: DateTime<Utc>,
+    /// Excerpt of the matching message with matched terms wrapped in the query's highlight markers.
+    pub snippet: String,
+    /// BM25 relevance; lower is more relevant.
+    pub score: f64,
+}
+
+/// Past message returned by [`TranscriptStore::recall_similar`].
+#[derive(Debug, Clone, Serialize)]
+pub struct RecalledMessage {
+    pub conversation_id: Uuid,
+    pub title: String,
+    pub role: TranscriptRole,
+    pub content: String,
+    #[serde(with = "chrono::serde::ts_seconds")]
+    pub timestamp: DateTime<Utc>,
+    /// Cosine similarity to the query embedding.
+    pub similarity: f32,
+}
+
+/// Conversation returned by [`TranscriptStore::related_conversations`].
+#[derive(Debug, Clone, Serialize)]
+pub struct RelatedConversation {
+    pub id: Uuid,
+    pub title: String,
+    /// Best cosine similarity between one of its messages and the source conversation.
+    pub similarity: f32,
 }
 
 /// Capture result providing filesystem paths for local tooling (e.g. CLI).
@@ -398,6 +1311,34 @@ pub enum TranscriptSource {
     Unknown,
 }
 
+impl TranscriptSource {
+    /// Stable identifier matching the serialized form.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TranscriptSource::Cli => "cli",
+            TranscriptSource::Sidebar => "sidebar",
+            TranscriptSource::HostApi => "host_api",
+            TranscriptSource::ArcSearch => "arc_search",
+            TranscriptSource::Unknown => "unknown",
+        }
+    }
+}
+
+impl std::str::FromStr for TranscriptSource {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().replace('-', "_").as_str() {
+            "cli" => Ok(TranscriptSource::Cli),
+            "sidebar" => Ok(TranscriptSource::Sidebar),
+            "host_api" | "host" | "api" => Ok(TranscriptSource::HostApi),
+            "arc_search" | "arc" => Ok(TranscriptSource::ArcSearch),
+            "unknown" => Ok(TranscriptSource::Unknown),
+            _ => Err(()),
+        }
+    }
+}
+
 impl std::fmt::Display for TranscriptSource {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -465,6 +1406,12 @@ pub struct Transcript {
     pub updated_at: DateTime<Utc>,
     #[serde(default)]
     pub messages: Vec<TranscriptMessage>,
+    /// Whether this conversation takes part in semantic recall.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub recall: bool,
+    /// Conversation and message this one was branched from.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent: Option<TranscriptParent>,
 }
 
 impl Transcript {
@@ -477,6 +1424,8 @@ impl Transcript {
             created_at: now,
             updated_at: now,
             messages: Vec::new(),
+            recall: false,
+            parent: None,
         }
     }
 
@@ -530,8 +1479,375 @@ impl Transcript {
             message_count,
             source: self.source,
             size_bytes: 0,
+            recall: self.recall,
+            parent: self.parent,
+        }
+    }
+}
+
+/// Parse a `--transcripts-since`/`until` style bound: RFC 3339, or a `YYYY-MM-DD`
+/// date taken as the start of the day (or its last second when `end_of_day`).
+pub fn parse_search_date(value: &str, end_of_day: bool) -> Result<DateTime<Utc>> {
+    let value = value.trim();
+    if let Ok(instant) = DateTime::parse_from_rfc3339(value) {
+        return Ok(instant.with_timezone(&Utc));
+    }
+    let date = NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .with_context(|| format!("invalid date '{value}' (expected YYYY-MM-DD or RFC 3339)"))?;
+    let time = if end_of_day {
+        date.and_hms_opt(23, 59, 59)
+    } else {
+        date.and_hms_opt(0, 0, 0)
+    }
+    .expect("valid wall-clock time");
+    Ok(Utc.from_utc_datetime(&time))
+}
+
+/// Turn free text into an FTS5 expression: each whitespace-separated term is
+/// quoted (so punctuation cannot break the query syntax) and all must match.
+fn fts_expression(text: &str) -> Option<String> {
+    let terms: Vec<String> = text
+        .split_whitespace()
+        .filter_map(|term| {
+            let (stem, prefix) = match term.strip_suffix('*') {
+                Some(stem) => (stem, true),
+                None => (term, false),
+            };
+            if !stem.chars().any(char::is_alphanumeric) {
+                return None;
+            }
+            let quoted = format!("\"{}\"", stem.replace('"', "\"\""));
+            Some(if prefix { format!("{quoted}*") } else { quoted })
+        })
+        .collect();
+    if terms.is_empty() {
+        None
+    } else {
+        Some(terms.join(" "))
+    }
+}
+
+fn index_transcript(conn: &mut Connection, transcript: &Transcript) -> Result<()> {
+    let tx = conn
+        .transaction()
+        .context("failed to start transcript search index update")?;
+    let id = transcript.id.to_string();
+    tx.execute("DELETE FROM conversations WHERE id = ?1", params![id])
+        .context("failed to clear indexed conversation")?;
+    tx.execute(
+        "DELETE FROM messages WHERE conversation_id = ?1",
+        params![id],
+    )
+    .context("failed to clear indexed messages")?;
+    write_index_rows(&tx, transcript)?;
+    tx.commit()
+        .context("failed to commit transcript search index update")
+}
+
+fn unindex_transcripts(conn: &mut Connection, ids: &HashSet<Uuid>) -> Result<()> {
+    let tx = conn
+        .transaction()
+        .context("failed to start transcript search index update")?;
+    for id in ids {
+        let id = id.to_string();
+        tx.execute("DELETE FROM conversations WHERE id = ?1", params![id])
+            .context("failed to remove indexed conversation")?;
+        tx.execute(
+            "DELETE FROM messages WHERE conversation_id = ?1",
+            params![id],
+        )
+        .context("failed to remove indexed messages")?;
+        tx.execute(
+            "DELETE FROM embeddings WHERE conversation_id = ?1",
+            params![id],
+        )
+        .context("failed to remove transcript embeddings")?;
+    }
+    tx.commit()
+        .context("failed to commit transcript search index update")
+}
+
+/// One row of the embeddings table, as stored in a sealed sidecar.
+#[derive(Debug, Serialize, Deserialize)]
+struct SealedEmbedding {
+    message_index: i64,
+    model: String,
+    role: String,
+    content: String,
+    timestamp: i64,
+    vector: Vec<f32>,
+}
+
+fn embedding_rows(conn: &Connection, conversation_id: &str) -> Result<Vec<SealedEmbedding>> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT message_index, model, role, content, timestamp, vector
+             FROM embeddings WHERE conversation_id = ?1",
+        )
+        .context("failed to prepare embedding export")?;
+    let rows = stmt
+        .query_map(params![conversation_id], |row| {
+            Ok(SealedEmbedding {
+                message_index: row.get(0)?,
+                model: row.get(1)?,
+                role: row.get(2)?,
+                content: row.get(3)?,
+                timestamp: row.get(4)?,
+                vector: decode_vector(&row.get::<_, Vec<u8>>(5)?),
+            })
+        })
+        .context("embedding export failed")?;
+    rows.collect::<rusqlite::Result<_>>()
+        .context("embedding export failed")
+}
+
+fn insert_embedding_rows(
+    conn: &Connection,
+    conversation_id: &str,
+    rows: &[SealedEmbedding],
+) -> Result<()> {
+    for row in rows {
+        conn.execute(
+            "INSERT OR REPLACE INTO embeddings
+                 (conversation_id, message_index, model, role, content, timestamp, vector)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![
+                conversation_id,
+                row.message_index,
+                row.model,
+                row.role,
+                row.content,
+                row.timestamp,
+                encode_vector(&row.vector),
+            ],
+        )
+        .context("failed to restore transcript embedding")?;
+    }
+    Ok(())
+}
+
+fn write_index_rows(conn: &Connection, transcript: &Transcript) -> Result<()> {
+    let id = transcript.id.to_string();
+    let mut providers: Vec<String> = transcript
+        .messages
+        .iter()
+        .filter_map(|message| message.provider.as_deref())
+        .map(str::to_lowercase)
+        .collect();
+    providers.sort();
+    providers.dedup();
+    let has_attachments = transcript
+        .messages
+        .iter()
+        .any(|message| !message.attachments.is_empty());
+    let title = if transcript.title.is_empty() {
+        format!("Conversation {}", transcript.id)
+    } else {
+        transcript.title.clone()
+    };
+
+    conn.execute(
+        "INSERT INTO conversations (id, title, source, providers, has_attachments, created_at, updated_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        params![
+            id,
+            title,
+            transcript.source.as_str(),
+            format!(",{},", providers.join(",")),
+            has_attachments,
+            transcript.created_at.timestamp(),
+            transcript.updated_at.timestamp(),
+        ],
+    )
+    .context("failed to index transcript conversation")?;
+
+    let mut insert = conn
+        .prepare(
+            "INSERT INTO messages (content, attachments, conversation_id, role, provider, timestamp)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        )
+        .context("failed to prepare transcript message indexing")?;
+    for message in &transcript.messages {
+        let attachments = message
+            .attachments
+            .iter()
+            .map(|attachment| {
+                attachment
+                    .original_filename
+                    .as_deref()
+                    .unwrap_or(&attachment.stored_filename)
+            })
+            .collect::<Vec<_>>()
+            .join(" ");
+        insert
+            .execute(params![
+                message.content,
+                attachments,
+                id,
+                role_key(message.role),
+                message.provider,
+                message.timestamp.timestamp(),
+            ])
+            .context("failed to index transcript message")?;
+    }
+    Ok(())
+}
+
+fn search_index(
+    conn: &mut Connection,
+    expression: &str,
+    query: &TranscriptSearchQuery,
+) -> Result<Vec<TranscriptSearchHit>> {
+    use rusqlite::types::Value as SqlValue;
+
+    let mut sql = String::from(
+        "SELECT messages.conversation_id, c.title, c.source, c.updated_at, messages.role,
+                messages.provider, messages.timestamp, snippet(messages, -1, ?2, ?3, '…', ?4), bm25(messages)
+         FROM messages JOIN conversations c ON c.id = messages.conversation_id
+         WHERE messages MATCH ?1",
+    );
+    let mut values = vec![
+        SqlValue::Text(expression.to_string()),
+        SqlValue::Text(query.highlight.0.clone()),
+        SqlValue::Text(query.highlight.1.clone()),
+        SqlValue::Integer(SNIPPET_TOKENS),
+    ];
+    if let Some(provider) = query.provider.as_deref() {
+        values.push(SqlValue::Text(format!(
+            ",{},",
+            provider.trim().to_lowercase()
+        )));
+        sql.push_str(&format!(" AND instr(c.providers, ?{}) > 0", values.len()));
+    }
+    if let Some(source) = query.source {
+        values.push(SqlValue::Text(source.as_str().to_string()));
+        sql.push_str(&format!(" AND c.source = ?{}", values.len()));
+    }
+    if let Some(since) = query.since {
+        values.push(SqlValue::Integer(since.timestamp()));
+        sql.push_str(&format!(
+            " AND CAST(messages.timestamp AS INTEGER) >= ?{}",
+            values.len()
+        ));
+    }
+    if let Some(until) = query.until {
+        values.push(SqlValue::Integer(until.timestamp()));
+        sql.push_str(&format!(
+            " AND CAST(messages.timestamp AS INTEGER) <= ?{}",
+            values.len()
+        ));
+    }
+    if let Some(attachments) = query.attachments {
+        values.push(SqlValue::Integer(i64::from(attachments)));
+        sql.push_str(&format!(" AND c.has_attachments = ?{}", values.len()));
+    }
+    sql.push_str(" ORDER BY bm25(messages)");
+
+    let mut stmt = conn
+        .prepare(&sql)
+        .context("failed to prepare transcript search")?;
+    let mut rows = stmt
+        .query(rusqlite::params_from_iter(values))
+        .context("transcript search failed")?;
+
+    let limit = if query.limit == 0 {
+        DEFAULT_SEARCH_LIMIT
+    } else {
+        query.limit
+    };
+    let mut seen = HashSet::new();
+    let mut hits = Vec::new();
+    while hits.len() < limit {
+        let Some(row) = rows.next().context("transcript search failed")? else {
+            break;
+        };
+        let id: String = row.get(0)?;
+        let Ok(id) = Uuid::try_parse(&id) else {
+            continue;
+        };
+        if !seen.insert(id) {
+            continue;
+        }
+        let source: String = row.get(2)?;
+        let role: String = row.get(4)?;
+        hits.push(TranscriptSearchHit {
+            id,
+            title: row.get(1)?,
+            source: source.parse().unwrap_or_default(),
+            updated_at: timestamp_from_secs(row.get(3)?),
+            role: role_from_key(&role),
+            provider: row.get(5)?,
+            timestamp: timestamp_from_secs(row.get(6)?),
+            snippet: row.get(7)?,
+            score: row.get(8)?,
+        });
+    }
+    Ok(hits)
+}
+
+fn role_key(role: TranscriptRole) -> &'static str {
+    match role {
+        TranscriptRole::System => "system",
+        TranscriptRole::User => "user",
+        TranscriptRole::Assistant => "assistant",
+    }
+}
+
+fn role_from_key(key: &str) -> TranscriptRole {
+    match key {
+        "system" => TranscriptRole::System,
+        "assistant" => TranscriptRole::Assistant,
+        _ => TranscriptRole::User,
+    }
+}
+
+fn encode_vector(vector: &[f32]) -> Vec<u8> {
+    vector
+        .iter()
+        .flat_map(|value| value.to_le_bytes())
+        .collect()
+}
+
+fn decode_vector(bytes: &[u8]) -> Vec<f32> {
+    bytes
+        .chunks_exact(4)
+        .map(|chunk| f32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]))
+        .collect()
+}
+
+/// Cosine similarity of two vectors; 0.0 when their lengths differ or either is zero.
+fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
+    if a.len() != b.len() || a.is_empty() {
+        return 0.0;
+    }
+    let (mut dot, mut norm_a, mut norm_b) = (0.0f32, 0.0f32, 0.0f32);
+    for (x, y) in a.iter().zip(b) {
+        dot += x * y;
+        norm_a += x * x;
+        norm_b += y * y;
+    }
+    if norm_a == 0.0 || norm_b == 0.0 {
+        return 0.0;
+    }
+    dot / (norm_a.sqrt() * norm_b.sqrt())
+}
+
+fn centroid(vectors: &[Vec<f32>]) -> Option<Vec<f32>> {
+    let first = vectors.first()?;
+    let dimensions = first.len();
+    let mut sum = vec![0.0f32; dimensions];
+    let mut count = 0.0f32;
+    for vector in vectors.iter().filter(|vector| vector.len() == dimensions) {
+        for (total, value) in sum.iter_mut().zip(vector) {
+            *total += value;
         }
+        count += 1.0;
     }
+    Some(sum.into_iter().map(|total| total / count).collect())
+}
+
+fn timestamp_from_secs(secs: i64) -> DateTime<Utc> {
+    DateTime::from_timestamp(secs, 0).unwrap_or_default()
 }
 
 fn derive_title(prompt: &str) -> String {
@@ -547,15 +1863,19 @@ fn derive_title(prompt: &str) -> String {
     title
 }
 
-fn persist_transcript_files(dir: &Path, transcript: &Transcript) -> Result<()> {
+fn persist_transcript_files(
+    dir: &Path,
+    transcript: &Transcript,
+    cipher: Option<&TranscriptCipher>,
+) -> Result<()> {
     let json_path = dir.join("transcript.json");
     let markdown_path = dir.join("transcript.md");
     let serialised = serde_json::to_string_pretty(transcript)
         .with_context(|| "failed to serialise transcript to JSON".to_string())?;
-    fs::write(&json_path, serialised)
+    write_sealed(&json_path, serialised.as_bytes(), cipher)
         .with_context(|| format!("failed to write transcript JSON {}", json_path.display()))?;
     let markdown = render_markdown(transcript);
-    fs::write(&markdown_path, markdown).with_context(|| {
+    write_sealed(&markdown_path, markdown.as_bytes(), cipher).with_context(|| {
         format!(
             "failed to write transcript Markdown {}",
             markdown_path.display()
@@ -564,6 +1884,52 @@ fn persist_transcript_files(dir: &Path, transcript: &Transcript) -> Result<()> {
     Ok(())
 }
 
+/// Write `data`, encrypted when a cipher is given. Encrypted files go through a
+/// temporary file and rename so a crash never leaves a half-written file.
+pub(crate) fn write_sealed(
+    path: &Path,
+    data: &[u8],
+    cipher: Option<&TranscriptCipher>,
+) -> Result<()> {
+    let Some(cipher) = cipher else {
+        fs::write(path, data)?;
+        return Ok(());
+    };
+    let sealed = cipher.encrypt(data)?;
+    let mut tmp_name = path.file_name().unwrap_or_default().to_os_string();
+    tmp_name.push(".tmp");
+    let tmp = path.with_file_name(tmp_name);
+    fs::write(&tmp, sealed)?;
+    fs::rename(&tmp, path)?;
+    Ok(())
+}
+
+/// Read a file written by [`write_sealed`], decrypting it when it carries the
+/// encrypted-file header.
+pub(crate) fn read_sealed(path: &Path, cipher: Option<&TranscriptCipher>) -> Result<Vec<u8>> {
+    let data = fs::read(path)?;
+    if !transcript_crypto::is_encrypted(&data) {
+        return Ok(data);
+    }
+    match cipher {
+        Some(cipher) => cipher.decrypt(&data),
+        None => bail!("file is encrypted; enable transcripts_encryption to read it"),
+    }
+}
+
+/// Validate a `stored_filename` as a plain path inside the attachments folder.
+fn attachment_path(stored_filename: &str) -> Result<&Path> {
+    let relative = Path::new(stored_filename);
+    if !relative.starts_with(ATTACHMENTS_DIR)
+        || relative
+            .components()
+            .any(|component| !matches!(component, std::path::Component::Normal(_)))
+    {
+        bail!("invalid attachment path '{stored_filename}'");
+    }
+    Ok(relative)
+}
+
 fn directory_size(path: &Path) -> Result<u64> {
     let mut total = 0u64;
     for entry in fs::read_dir(path)
@@ -589,6 +1955,7 @@ fn is_zero(value: &u64) -> bool {
 fn persist_attachments(
     conversation_dir: &Path,
     attachments: &[AttachmentInput<'_>],
+    cipher: Option<&TranscriptCipher>,
 ) -> Result<Vec<TranscriptAttachment>> {
     if attachments.is_empty() {
         return Ok(Vec::new());
@@ -615,7 +1982,7 @@ fn persist_attachments(
             format!("{base_name}.{ext}")
         };
         let stored_path = attachments_dir.join(&stored_name);
-        fs::write(&stored_path, attachment.data)
+        write_sealed(&stored_path, attachment.data, cipher)
             .with_context(|| format!("failed to persist attachment {}", stored_path.display()))?;
         stored.push(TranscriptAttachment {
             mime: attachment.mime.to_string(),
@@ -664,6 +2031,13 @@ fn render_markdown(transcript: &Transcript) -> String {
     output.push_str(&format!("# {}\n\n", transcript.title));
     output.push_str(&format!("- ID: {}\n", transcript.id));
     output.push_str(&format!("- Source: {}\n", transcript.source));
+    if let Some(parent) = &transcript.parent {
+        output.push_str(&format!(
+            "- Branched from: {} after message {}\n",
+            parent.conversation_id,
+            parent.message_index + 1
+        ));
+    }
     output.push_str(&format!(
         "- Created: {}\n",
         transcript.created_at.to_rfc3339()
@@ -712,3 +2086,496 @@ fn render_markdown(transcript: &Transcript) -> String {
 
     output
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(
+        store: &TranscriptStore,
+        conversation_id: Option<Uuid>,
+        source: TranscriptSource,
+        prompt: &str,
+        reply: &str,
+        provider: &str,
+        attachments: &[AttachmentInput<'_>],
+    ) -> Uuid {
+        store
+            .record_interaction(&TranscriptInput {
+                conversation_id,
+                source,
+                prompt_text: prompt,
+                attachments,
+                reply_text: reply,
+                provider,
+                model: "test-model",
+                latency_ms: 5,
+            })
+            .expect("record interaction")
+            .summary
+            .id
+    }
+
+    #[test]
+    fn search_finds_messages_with_highlighted_snippets() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = TranscriptStore::new(dir.path().to_path_buf()).unwrap();
+        let doq = record(
+            &store,
+            None,
+            TranscriptSource::Cli,
+            "How do I rotate the DoQ certificates?",
+            "Point ghostdns at the renewed certificate chain and restart.",
+            "ollama",
+            &[],
+        );
+        record(
+            &store,
+            None,
+            TranscriptSource::Sidebar,
+            "Summarize this recipe",
+            "It logs in and downloads the invoice.",
+            "openai",
+            &[],
+        );
+
+        let hits = store
+            .search(&TranscriptSearchQuery::new("doq certificates"))
+            .unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].id, doq);
+        assert_eq!(hits[0].role, TranscriptRole::User);
+        assert!(hits[0].snippet.contains("**DoQ**"), "{}", hits[0].snippet);
+
+        // Stemming matches "certificate" in the reply as well; one hit per conversation.
+        let hits = store
+            .search(&TranscriptSearchQuery::new("certificate"))
+            .unwrap();
+        assert_eq!(hits.len(), 1);
+
+        let hits = store.search(&TranscriptSearchQuery::new("invoi*")).unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].provider.as_deref(), Some("openai"));
+
+        assert!(store.search(&TranscriptSearchQuery::new("  - ")).is_err());
+        assert!(
+            store
+                .search(&TranscriptSearchQuery::new("\"unbalanced"))
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn search_applies_filters() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = TranscriptStore::new(dir.path().to_path_buf()).unwrap();
+        let cli = record(
+            &store,
+            None,
+            TranscriptSource::Cli,
+            "firewall rules",
+            "Use nftables.",
+            "ollama",
+            &[],
+        );
+        let attachment = AttachmentInput {
+            mime: "image/png",
+            data: b"png",
+            filename: Some("topology.png"),
+        };
+        let sidebar = record(
+            &store,
+            None,
+            TranscriptSource::Sidebar,
+            "firewall diagram",
+            "The diagram shows two zones.",
+            "claude",
+            std::slice::from_ref(&attachment),
+        );
+
+        let ids = |query: TranscriptSearchQuery| -> Vec<Uuid> {
+            store
+                .search(&query)
+                .unwrap()
+                .into_iter()
+                .map(|hit| hit.id)
+                .collect()
+        };
+        assert_eq!(
+            ids(TranscriptSearchQuery::new("firewall").with_provider("Ollama")),
+            vec![cli]
+        );
+        assert_eq!(
+            ids(TranscriptSearchQuery::new("firewall").with_source(TranscriptSource::Sidebar)),
+            vec![sidebar]
+        );
+        assert_eq!(
+            ids(TranscriptSearchQuery::new("firewall").with_attachments(true)),
+            vec![sidebar]
+        );
+        assert_eq!(
+            ids(TranscriptSearchQuery::new("firewall").with_attachments(false)),
+            vec![cli]
+        );
+        assert_eq!(ids(TranscriptSearchQuery::new("topology")), vec![sidebar]);
+
+        let tomorrow = Utc::now() + Duration::days(1);
+        assert!(
+            ids(TranscriptSearchQuery::new("firewall").with_range(Some(tomorrow), None)).is_empty()
+        );
+        assert_eq!(
+            ids(TranscriptSearchQuery::new("firewall").with_range(None, Some(tomorrow))).len(),
+            2
+        );
+        assert_eq!(
+            ids(TranscriptSearchQuery::new("firewall").with_limit(1)).len(),
+            1
+        );
+    }
+
+    #[test]
+    fn search_index_follows_prune_and_rebuilds_when_missing() {
+        let dir = tempfile::tempdir().unwrap();
+        let retention = TranscriptRetention {
+            max_entries: Some(1),
+            ..TranscriptRetention::default()
+        };
+        let store = TranscriptStore::with_retention(dir.path().to_path_buf(), retention).unwrap();
+        record(
+            &store,
+            None,
+            TranscriptSource::Cli,
+            "alpha topic",
+            "ok",
+            "ollama",
+            &[],
+        );
+        std::thread::sleep(std::time::Duration::from_millis(1100));
+        let kept = record(
+            &store,
+            None,
+            TranscriptSource::Cli,
+            "beta topic",
+            "ok",
+            "ollama",
+            &[],
+        );
+
+        let hits = store.search(&TranscriptSearchQuery::new("topic")).unwrap();
+        assert_eq!(
+            hits.iter().map(|hit| hit.id).collect::<Vec<_>>(),
+            vec![kept]
+        );
+
+        // A fresh store over an existing directory without an index builds one from disk.
+        drop(store);
+        for suffix in ["", "-wal", "-shm"] {
+            let _ = fs::remove_file(dir.path().join(format!("{SEARCH_INDEX_FILE}{suffix}")));
+        }
+        let store = TranscriptStore::new(dir.path().to_path_buf()).unwrap();
+        let hits = store.search(&TranscriptSearchQuery::new("beta")).unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].id, kept);
+        assert_eq!(store.rebuild_search_index().unwrap(), 1);
+    }
+
+    #[test]
+    fn embeddings_are_ranked_and_dropped_with_their_conversation() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = TranscriptStore::new(dir.path().to_path_buf()).unwrap();
+        let first = record(
+            &store,
+            None,
+            TranscriptSource::Cli,
+            "dns",
+            "doh",
+            "ollama",
+            &[],
+        );
+        let second = record(
+            &store,
+            None,
+            TranscriptSource::Cli,
+            "css",
+            "grid",
+            "ollama",
+            &[],
+        );
+        for (id, vector) in [(first, vec![1.0, 0.0]), (second, vec![0.0, 1.0])] {
+            let pending = store.unembedded_messages(id, "m").unwrap();
+            assert_eq!(pending.len(), 2);
+            let embeddings: Vec<_> = pending
+                .into_iter()
+                .map(|(index, message)| (index, message, vector.clone()))
+                .collect();
+            store.store_embeddings(id, "m", &embeddings).unwrap();
+            assert!(store.unembedded_messages(id, "m").unwrap().is_empty());
+        }
+
+        let recalled = store
+            .recall_similar("m", &[0.9, 0.1], None, 3, 0.5)
+            .unwrap();
+        assert_eq!(recalled.len(), 2);
+        assert!(
+            recalled
+                .iter()
+                .all(|message| message.conversation_id == first)
+        );
+        assert!(
+            store
+                .recall_similar("m", &[0.9, 0.1], Some(first), 3, 0.5)
+                .unwrap()
+                .is_empty()
+        );
+        assert!(
+            store
+                .recall_similar("other", &[0.9, 0.1], None, 3, 0.0)
+                .unwrap()
+                .is_empty()
+        );
+
+        let related = store.related_conversations(second, "m", 5).unwrap();
+        assert_eq!(related.len(), 1);
+        assert_eq!(related[0].id, first);
+
+        store.set_recall(first, true).unwrap();
+        assert!(store.recall_enabled(first).unwrap());
+        store.set_recall(first, false).unwrap();
+        assert!(!store.recall_enabled(first).unwrap());
+        assert_eq!(store.unembedded_messages(first, "m").unwrap().len(), 2);
+    }
+
+    #[test]
+    fn encrypted_store_keeps_no_plaintext_on_disk() {
+        use crate::config::TranscriptKeySource;
+
+        let dir = tempfile::tempdir().unwrap();
+        let cipher = || TranscriptCipher::from_key([7; 32], TranscriptKeySource::File);
+        let attachment = AttachmentInput {
+            mime: "image/png",
+            data: b"secret-png-bytes",
+            filename: Some("plan.png"),
+        };
+
+        let plain = TranscriptStore::new(dir.path().to_path_buf()).unwrap();
+        let legacy = record(
+            &plain,
+            None,
+            TranscriptSource::Cli,
+            "legacy launch codes",
+            "Stored before encryption.",
+            "ollama",
+            &[],
+        );
+        assert!(plain.search_index_path().exists());
+        let embed = |store: &TranscriptStore, id: Uuid| {
+            let embeddings: Vec<_> = store
+                .unembedded_messages(id, "m")
+                .unwrap()
+                .into_iter()
+                .map(|(index, message)| (index, message, vec![1.0, 0.0]))
+                .collect();
+            store.store_embeddings(id, "m", &embeddings).unwrap();
+        };
+        embed(&plain, legacy);
+        drop(plain);
+        fs::create_dir_all(dir.path().join("agents")).unwrap();
+        fs::write(dir.path().join("agents/agent-1.md"), "# book the launch").unwrap();
+        fs::create_dir_all(dir.path().join("agent-artifacts")).unwrap();
+        fs::write(dir.path().join("agent-artifacts/agent-1.har"), "{}").unwrap();
+
+        let store = TranscriptStore::new(dir.path().to_path_buf())
+            .unwrap()
+            .with_encryption(cipher());
+        let id = record(
+            &store,
+            None,
+            TranscriptSource::Sidebar,
+            "quarterly merger plans",
+            "Keep them confidential.",
+            "openai",
+            &[attachment],
+        );
+
+        embed(&store, id);
+
+        let report = store.encrypt_existing().unwrap();
+        assert_eq!(report.conversations, 1);
+        assert_eq!(report.files, 2);
+        assert_eq!(report.agent_files, 2);
+        assert!(report.removed_index);
+        assert!(!store.search_index_path().exists());
+
+        for entry in walkdir(dir.path()) {
+            let data = fs::read(&entry).unwrap();
+            assert!(
+                transcript_crypto::is_encrypted(&data),
+                "{} is not encrypted",
+                entry.display()
+            );
+        }
+
+        assert!(store.load_json(id).unwrap().contains("merger"));
+        assert!(
+            store
+                .load_markdown(legacy)
+                .unwrap()
+                .contains("launch codes")
+        );
+        assert_eq!(store.load_messages(legacy).unwrap().len(), 2);
+        let stored = store.load_messages(id).unwrap()[0].attachments[0]
+            .stored_filename
+            .clone();
+        assert_eq!(
+            store.load_attachment(id, &stored).unwrap(),
+            b"secret-png-bytes"
+        );
+        assert!(store.load_attachment(id, "../transcript.json").is_err());
+
+        let hits = store
+            .search(&TranscriptSearchQuery::new("launch codes"))
+            .unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].id, legacy);
+        assert!(!store.search_index_path().exists());
+
+        // Embeddings from before and after encryption survive a restart.
+        let reopened = TranscriptStore::new(dir.path().to_path_buf())
+            .unwrap()
+            .with_encryption(cipher());
+        for conversation in [legacy, id] {
+            assert!(
+                reopened
+                    .unembedded_messages(conversation, "m")
+                    .unwrap()
+                    .is_empty()
+            );
+        }
+        let recalled = reopened
+            .recall_similar("m", &[1.0, 0.0], Some(id), 5, 0.5)
+            .unwrap();
+        assert_eq!(recalled.len(), 2);
+        assert!(
+            recalled
+                .iter()
+                .all(|message| message.conversation_id == legacy)
+        );
+        reopened.set_recall(id, false).unwrap();
+        assert!(
+            !dir.path()
+                .join(id.to_string())
+                .join(SEALED_EMBEDDINGS_FILE)
+                .exists()
+        );
+
+        let locked_out = TranscriptStore::new(dir.path().to_path_buf()).unwrap();
+        assert!(locked_out.load_json(id).is_err());
+    }
+
+    fn walkdir(root: &Path) -> Vec<PathBuf> {
+        let mut files = Vec::new();
+        for entry in fs::read_dir(root).unwrap() {
+            let path = entry.unwrap().path();
+            if path.is_dir() {
+                files.extend(walkdir(&path));
+            } else {
+                files.push(path);
+            }
+        }
+        files
+    }
+
+    #[test]
+    fn branches_copy_history_and_form_a_tree() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = TranscriptStore::new(dir.path().to_path_buf()).unwrap();
+        let attachment = AttachmentInput {
+            mime: "image/png",
+            data: b"png",
+            filename: Some("shot"),
+        };
+        let root = record(
+            &store,
+            None,
+            TranscriptSource::Sidebar,
+            "what is in this screenshot?",
+            "A login form.",
+            "ollama",
+            &[attachment],
+        );
+        record(
+            &store,
+            Some(root),
+            TranscriptSource::Sidebar,
+            "fill it in",
+            "Done.",
+            "ollama",
+            &[],
+        );
+
+        let first = store.branch(root, 1).unwrap().summary;
+        assert_eq!(first.message_count, 2);
+        assert_eq!(first.title, "what is in this screenshot? (branch)");
+        assert_eq!(
+            first.parent,
+            Some(TranscriptParent {
+                conversation_id: root,
+                message_index: 1,
+            })
+        );
+        let copied = &store.load_messages(first.id).unwrap()[0].attachments[0];
+        assert_eq!(
+            store
+                .load_attachment(first.id, &copied.stored_filename)
+                .unwrap(),
+            b"png"
+        );
+        assert!(
+            store
+                .load_markdown(first.id)
+                .unwrap()
+                .contains(&format!("Branched from: {root} after message 2"))
+        );
+
+        let nested = store.branch(first.id, 0).unwrap().summary;
+        let second = store.branch(root, 3).unwrap().summary;
+        assert!(store.branch(root, 4).is_err());
+
+        let tree = store.tree(nested.id).unwrap();
+        assert_eq!(tree.summary.id, root);
+        let children: Vec<Uuid> = tree.children.iter().map(|child| child.summary.id).collect();
+        assert_eq!(children, vec![first.id, second.id]);
+        assert_eq!(tree.children[0].children[0].summary.id, nested.id);
+
+        // Branches stay usable once their parent is gone.
+        fs::remove_dir_all(dir.path().join(root.to_string())).unwrap();
+        assert_eq!(store.tree(nested.id).unwrap().summary.id, first.id);
+
+        // A parent cycle lists each conversation once instead of recursing.
+        let (a, b) = (Uuid::new_v4(), Uuid::new_v4());
+        for (id, parent) in [(a, b), (b, a)] {
+            let mut transcript = store.load_transcript(first.id).unwrap();
+            transcript.id = id;
+            transcript.parent = Some(TranscriptParent {
+                conversation_id: parent,
+                message_index: 0,
+            });
+            store.insert_transcript(&transcript, &[]).unwrap();
+        }
+        let tree = store.tree(a).unwrap();
+        assert_eq!(tree.children.len(), 1);
+        assert!(tree.children[0].children.is_empty());
+    }
+
+    #[test]
+    fn parses_search_dates() {
+        let start = parse_search_date("2026-09-01", false).unwrap();
+        assert_eq!(start.to_rfc3339(), "2026-09-01T00:00:00+00:00");
+        let end = parse_search_date("2026-09-30", true).unwrap();
+        assert_eq!(end.to_rfc3339(), "2026-09-30T23:59:59+00:00");
+        let exact = parse_search_date("2026-09-15T12:30:00+02:00", false).unwrap();
+        assert_eq!(exact.to_rfc3339(), "2026-09-15T10:30:00+00:00");
+        assert!(parse_search_date("last month", false).is_err());
+    }
+}
@@ -0,0 +1,455 @@
+//! Portable transcript archives.
+//!
+//! A bundle is a tar archive holding `manifest.json` followed by one folder per
+//! conversation (`<id>/transcript.json`, `<id>/transcript.md` and
+//! `<id>/attachments/*`). The manifest lists every file with its SHA-256 so an
+//! import can verify the archive before writing anything. Bundles are written
+//! in plaintext even when the store is encrypted; the importing store seals the
+//! files again with its own key.
+
+use std::collections::{HashMap, HashSet};
+use std::fs::{File, OpenOptions};
+use std::io::Read;
+use std::path::{Component, Path};
+
+use anyhow::{Context, Result, bail};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+use crate::transcript::{Transcript, TranscriptStore};
+
+/// `format` value identifying a transcript bundle manifest.
+pub const BUNDLE_FORMAT: &str = "archon-transcripts";
+
+/// Manifest version written by [`export_bundle`].
+pub const BUNDLE_VERSION: u32 = 1;
+
+const MANIFEST_FILE: &str = "manifest.json";
+
+/// Table of contents of a bundle.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BundleManifest {
+    pub format: String,
+    pub version: u32,
+    pub exported_at: DateTime<Utc>,
+    pub conversations: Vec<BundleConversation>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BundleConversation {
+    pub id: Uuid,
+    pub title: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent: Option<Uuid>,
+    pub files: Vec<BundleFile>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BundleFile {
+    /// Path inside the archive, e.g. `<id>/attachments/photo.png`.
+    pub path: String,
+    pub sha256: String,
+    pub size_bytes: u64,
+}
+
+/// Outcome of [`import_bundle`].
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ImportReport {
+    pub conversations: Vec<ImportedConversation>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ImportedConversation {
+    /// ID recorded in the bundle.
+    pub original_id: Uuid,
+    /// ID in the store; differs from `original_id` when that was taken.
+    pub id: Uuid,
+    pub title: String,
+}
+
+impl ImportedConversation {
+    pub fn reassigned(&self) -> bool {
+        self.id != self.original_id
+    }
+}
+
+/// Write conversations `ids` from `store` into a bundle at `path`.
+pub fn export_bundle(store: &TranscriptStore, ids: &[Uuid], path: &Path) -> Result<BundleManifest> {
+    let mut manifest = BundleManifest {
+        format: BUNDLE_FORMAT.to_string(),
+        version: BUNDLE_VERSION,
+        exported_at: Utc::now(),
+        conversations: Vec::with_capacity(ids.len()),
+    };
+    let mut files: Vec<(String, Vec<u8>)> = Vec::new();
+
+    for &id in ids {
+        let transcript = store.load_transcript(id)?;
+        let mut entries = vec![
+            (
+                "transcript.json".to_string(),
+                store.load_json(id)?.into_bytes(),
+            ),
+            (
+                "transcript.md".to_string(),
+                store.load_markdown(id)?.into_bytes(),
+            ),
+        ];
+        let mut seen = HashSet::new();
+        for attachment in transcript
+            .messages
+            .iter()
+            .flat_map(|message| &message.attachments)
+        {
+            if seen.insert(attachment.stored_filename.as_str()) {
+                let data = store.load_attachment(id, &attachment.stored_filename)?;
+                entries.push((attachment.stored_filename.clone(), data));
+            }
+        }
+
+        let mut conversation = BundleConversation {
+            id,
+            title: transcript.title.clone(),
+            parent: transcript
+                .parent
+                .as_ref()
+                .map(|parent| parent.conversation_id),
+            files: Vec::with_capacity(entries.len()),
+        };
+        for (name, data) in entries {
+            let path = format!("{id}/{name}");
+            conversation.files.push(BundleFile {
+                path: path.clone(),
+                sha256: digest(&data),
+                size_bytes: data.len() as u64,
+            });
+            files.push((path, data));
+        }
+        manifest.conversations.push(conversation);
+    }
+
+    // Bundles hold decrypted transcripts and attachments even when the store
+    // is encrypted, so keep them owner-only.
+    let mut options = OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let file = options
+        .open(path)
+        .with_context(|| format!("failed to create {}", path.display()))?;
+    // `mode` only applies to new files; tighten a bundle being overwritten.
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        file.set_permissions(std::fs::Permissions::from_mode(0o600))
+            .with_context(|| format!("failed to restrict {}", path.display()))?;
+    }
+    let mut builder = tar::Builder::new(file);
+    let manifest_json = serde_json::to_vec_pretty(&manifest)?;
+    append(&mut builder, MANIFEST_FILE, &manifest_json)?;
+    for (name, data) in &files {
+        append(&mut builder, name, data)?;
+    }
+    builder
+        .into_inner()
+        .with_context(|| format!("failed to finish bundle {}", path.display()))?
+        .sync_all()
+        .with_context(|| format!("failed to flush bundle {}", path.display()))?;
+    Ok(manifest)
+}
+
+/// Verify the bundle at `path` and add its conversations to `store`.
+/// Conversations whose ID is already in use get a fresh one, and parent
+/// pointers inside the bundle follow the new IDs.
+pub fn import_bundle(store: &TranscriptStore, path: &Path) -> Result<ImportReport> {
+    let mut files = read_archive(path)?;
+    let manifest: BundleManifest = serde_json::from_slice(
+        &files
+            .remove(MANIFEST_FILE)
+            .context("bundle has no manifest.json")?,
+    )
+    .context("malformed bundle manifest")?;
+    if manifest.format != BUNDLE_FORMAT {
+        bail!("not a transcript bundle (format '{}')", manifest.format);
+    }
+    if manifest.version > BUNDLE_VERSION {
+        bail!(
+            "bundle version {} is newer than supported version {BUNDLE_VERSION}",
+            manifest.version
+        );
+    }
+
+    for conversation in &manifest.conversations {
+        for file in &conversation.files {
+            let data = files
+                .get(&file.path)
+                .with_context(|| format!("bundle is missing {}", file.path))?;
+            if data.len() as u64 != file.size_bytes || digest(data) != file.sha256 {
+                bail!("checksum mismatch for {}", file.path);
+            }
+        }
+    }
+
+    let mut pending = Vec::with_capacity(manifest.conversations.len());
+    let mut ids = HashMap::new();
+    let mut used = HashSet::new();
+    for conversation in &manifest.conversations {
+        let prefix = format!("{}/", conversation.id);
+        let json_name = format!("{prefix}transcript.json");
+        if !conversation.files.iter().any(|file| file.path == json_name) {
+            bail!("bundle has no transcript for {}", conversation.id);
+        }
+        let json = &files[&json_name];
+        let transcript: Transcript = serde_json::from_slice(json)
+            .with_context(|| format!("malformed transcript {}", conversation.id))?;
+        if transcript.id != conversation.id {
+            bail!(
+                "transcript {} is filed under {} in the bundle",
+                transcript.id,
+                conversation.id
+            );
+        }
+
+        let mut attachments = Vec::new();
+        let mut seen = HashSet::new();
+        for attachment in transcript
+            .messages
+            .iter()
+            .flat_map(|message| &message.attachments)
+        {
+            if !seen.insert(attachment.stored_filename.clone()) {
+                continue;
+            }
+            let name = format!("{prefix}{}", attachment.stored_filename);
+            if !conversation.files.iter().any(|file| file.path == name) {
+                bail!("bundle is missing attachment {name}");
+            }
+            attachments.push((attachment.stored_filename.clone(), files[&name].clone()));
+        }
+
+        let mut id = conversation.id;
+        while store.contains(id) || !used.insert(id) {
+            id = Uuid::new_v4();
+        }
+        ids.insert(conversation.id, id);
+        pending.push((transcript, attachments));
+    }
+    reject_parent_cycles(pending.iter().map(|(transcript, _)| transcript))?;
+
+    let mut report = ImportReport::default();
+    for (mut transcript, attachments) in pending {
+        let original_id = transcript.id;
+        transcript.id = ids[&original_id];
+        if let Some(parent) = transcript.parent.as_mut()
+            && let Some(&id) = ids.get(&parent.conversation_id)
+        {
+            parent.conversation_id = id;
+        }
+        let record = store.insert_transcript(&transcript, &attachments)?;
+        report.conversations.push(ImportedConversation {
+            original_id,
+            id: transcript.id,
+            title: record.summary.title,
+        });
+    }
+    Ok(report)
+}
+
+/// Fail when following parent pointers between `transcripts` loops back.
+fn reject_parent_cycles<'a>(transcripts: impl Iterator<Item = &'a Transcript>) -> Result<()> {
+    let parents: HashMap<Uuid, Uuid> = transcripts
+        .filter_map(|transcript| {
+            let parent = transcript.parent.as_ref()?;
+            Some((transcript.id, parent.conversation_id))
+        })
+        .collect();
+    for &start in parents.keys() {
+        let mut seen = HashSet::from([start]);
+        let mut id = start;
+        while let Some(&parent) = parents.get(&id) {
+            if !seen.insert(parent) {
+                bail!("bundle conversation {start} is its own ancestor");
+            }
+            id = parent;
+        }
+    }
+    Ok(())
+}
+
+fn append(builder: &mut tar::Builder<File>, name: &str, data: &[u8]) -> Result<()> {
+    let mut header = tar::Header::new_gnu();
+    header.set_size(data.len() as u64);
+    header.set_mode(0o600);
+    header.set_mtime(Utc::now().timestamp().max(0) as u64);
+    header.set_cksum();
+    builder
+        .append_data(&mut header, name, data)
+        .with_context(|| format!("failed to add {name} to bundle"))
+}
+
+/// Read every regular file of the archive, keyed by its path.
+fn read_archive(path: &Path) -> Result<HashMap<String, Vec<u8>>> {
+    let file = File::open(path).with_context(|| format!("failed to open {}", path.display()))?;
+    let mut archive = tar::Archive::new(file);
+    let mut files = HashMap::new();
+    for entry in archive
+        .entries()
+        .with_context(|| format!("failed to read bundle {}", path.display()))?
+    {
+        let mut entry = entry.context("failed to read bundle entry")?;
+        if !entry.header().entry_type().is_file() {
+            continue;
+        }
+        let name = entry.path().context("invalid bundle entry path")?;
+        if name
+            .components()
+            .any(|component| !matches!(component, Component::Normal(_)))
+        {
+            bail!("bundle entry '{}' escapes the archive", name.display());
+        }
+        let name = name
+            .to_str()
+            .context("bundle entry path is not UTF-8")?
+            .to_string();
+        let mut data = Vec::new();
+        entry
+            .read_to_end(&mut data)
+            .with_context(|| format!("failed to read {name} from bundle"))?;
+        files.insert(name, data);
+    }
+    Ok(files)
+}
+
+fn digest(data: &[u8]) -> String {
+    hex::encode(Sha256::digest(data))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transcript::{AttachmentInput, TranscriptInput, TranscriptParent, TranscriptSource};
+
+    fn record(store: &TranscriptStore, prompt: &str, attachments: &[AttachmentInput<'_>]) -> Uuid {
+        store
+            .record_interaction(&TranscriptInput {
+                conversation_id: None,
+                source: TranscriptSource::Cli,
+                prompt_text: prompt,
+                attachments,
+                reply_text: "Done.",
+                provider: "ollama",
+                model: "test-model",
+                latency_ms: 3,
+            })
+            .unwrap()
+            .summary
+            .id
+    }
+
+    #[test]
+    fn round_trips_conversations_and_reassigns_taken_ids() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = TranscriptStore::new(dir.path().join("source")).unwrap();
+        let attachment = AttachmentInput {
+            mime: "image/png",
+            data: b"png-bytes",
+            filename: Some("diagram.png"),
+        };
+        let root = record(&source, "draw the topology", &[attachment]);
+        let branch = source.branch(root, 1).unwrap().summary.id;
+        let bundle = dir.path().join("transcripts.tar");
+
+        let manifest = export_bundle(&source, &[root, branch], &bundle).unwrap();
+        assert_eq!(manifest.conversations.len(), 2);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(&bundle).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+        assert_eq!(manifest.conversations[0].files.len(), 3);
+
+        let target = TranscriptStore::new(dir.path().join("target")).unwrap();
+        let report = import_bundle(&target, &bundle).unwrap();
+        assert!(report.conversations.iter().all(|entry| !entry.reassigned()));
+        let stored = target.load_messages(root).unwrap()[0].attachments[0]
+            .stored_filename
+            .clone();
+        assert_eq!(target.load_attachment(root, &stored).unwrap(), b"png-bytes");
+
+        // Importing again collides with both IDs; the copy keeps its own tree.
+        let report = import_bundle(&target, &bundle).unwrap();
+        assert!(report.conversations.iter().all(|entry| entry.reassigned()));
+        let new_root = report.conversations[0].id;
+        let new_branch = report.conversations[1].id;
+        let parent = target.load_transcript(new_branch).unwrap().parent.unwrap();
+        assert_eq!(parent.conversation_id, new_root);
+        assert_eq!(target.list().unwrap().len(), 4);
+        assert!(
+            target
+                .load_markdown(new_root)
+                .unwrap()
+                .contains(&new_root.to_string())
+        );
+    }
+
+    #[test]
+    fn rejects_parent_cycles_before_writing() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = TranscriptStore::new(dir.path().join("source")).unwrap();
+        let first = record(&source, "hello", &[]);
+        let second = source.branch(first, 1).unwrap().summary.id;
+
+        let cyclic = TranscriptStore::new(dir.path().join("cyclic")).unwrap();
+        let mut looped = source.load_transcript(first).unwrap();
+        looped.parent = Some(TranscriptParent {
+            conversation_id: second,
+            message_index: 0,
+        });
+        cyclic.insert_transcript(&looped, &[]).unwrap();
+        cyclic
+            .insert_transcript(&source.load_transcript(second).unwrap(), &[])
+            .unwrap();
+        let bundle = dir.path().join("cyclic.tar");
+        export_bundle(&cyclic, &[first, second], &bundle).unwrap();
+
+        let target = TranscriptStore::new(dir.path().join("target")).unwrap();
+        let err = import_bundle(&target, &bundle).unwrap_err();
+        assert!(err.to_string().contains("its own ancestor"), "{err}");
+        assert!(target.list().unwrap().is_empty());
+    }
+
+    #[test]
+    fn rejects_tampered_bundles_before_writing() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = TranscriptStore::new(dir.path().join("source")).unwrap();
+        let id = record(&source, "hello", &[]);
+        let bundle = dir.path().join("one.tar");
+        export_bundle(&source, &[id], &bundle).unwrap();
+
+        let mut files = read_archive(&bundle).unwrap();
+        let json = files.get_mut(&format!("{id}/transcript.json")).unwrap();
+        let text = String::from_utf8(json.clone())
+            .unwrap()
+            .replace("hello", "HELLO");
+        *json = text.into_bytes();
+        let tampered = dir.path().join("tampered.tar");
+        let mut builder = tar::Builder::new(File::create(&tampered).unwrap());
+        let mut names: Vec<_> = files.keys().cloned().collect();
+        names.sort_by_key(|name| name != MANIFEST_FILE);
+        for name in names {
+            append(&mut builder, &name, &files[&name]).unwrap();
+        }
+        builder.into_inner().unwrap();
+
+        let target = TranscriptStore::new(dir.path().join("target")).unwrap();
+        let err = import_bundle(&target, &tampered).unwrap_err();
+        assert!(err.to_string().contains("checksum mismatch"), "{err}");
+        assert!(target.list().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,450 @@
+//! At-rest encryption for the transcript store.
+//!
+//! Files are sealed with XChaCha20-Poly1305 under a 256-bit key that is either
+//! derived from a passphrase (Argon2id) or kept in a keyring. Encrypted files
+//! start with [`MAGIC`] followed by the nonce, so plaintext files written before
+//! encryption was enabled stay readable until `archon --transcripts-encrypt`
+//! converts them.
+
+use std::fmt;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use anyhow::{Context, Result, anyhow, bail};
+use argon2::{Algorithm, Argon2, Params, Version};
+use base64::{Engine as _, engine::general_purpose::STANDARD};
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng};
+use chacha20poly1305::{Key, XChaCha20Poly1305, XNonce};
+use serde::{Deserialize, Serialize};
+
+use crate::config::{TranscriptEncryptionSettings, TranscriptKeySource};
+
+/// Header identifying an encrypted transcript file.
+const MAGIC: &[u8; 8] = b"ARCENC01";
+
+/// XChaCha20 nonce length.
+const NONCE_LEN: usize = 24;
+
+/// Key metadata written to the transcript root on first use.
+const KEY_META_FILE: &str = ".encryption.json";
+
+/// Plaintext sealed into the key metadata to detect a wrong key or passphrase.
+const KEY_CHECK_PLAINTEXT: &[u8] = b"archon-transcripts";
+
+/// Argon2id parameters: 64 MiB, 3 passes, 1 lane.
+const ARGON2_MEMORY_KIB: u32 = 64 * 1024;
+const ARGON2_PASSES: u32 = 3;
+
+/// Symmetric cipher for transcript files.
+#[derive(Clone)]
+pub struct TranscriptCipher {
+    cipher: XChaCha20Poly1305,
+    source: TranscriptKeySource,
+}
+
+impl fmt::Debug for TranscriptCipher {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TranscriptCipher")
+            .field("source", &self.source)
+            .finish_non_exhaustive()
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct KeyMeta {
+    version: u32,
+    key_source: TranscriptKeySource,
+    /// Hex salt for the passphrase KDF.
+    salt: String,
+    /// Base64 of [`KEY_CHECK_PLAINTEXT`] sealed with the key.
+    check: String,
+}
+
+impl TranscriptCipher {
+    /// Cipher from a raw 256-bit key.
+    pub fn from_key(key: [u8; 32], source: TranscriptKeySource) -> Self {
+        Self {
+            cipher: XChaCha20Poly1305::new(Key::from_slice(&key)),
+            source,
+        }
+    }
+
+    /// Build the cipher configured for a store rooted at `root`, or `None` when
+    /// encryption is disabled. The first call records a key check in the root
+    /// so later runs with a different key or passphrase fail loudly instead of
+    /// producing unreadable files.
+    pub fn from_settings(
+        settings: &TranscriptEncryptionSettings,
+        root: &Path,
+    ) -> Result<Option<Self>> {
+        if !settings.enabled {
+            return Ok(None);
+        }
+        let meta_path = key_meta_path(root);
+        let existing = match fs::read_to_string(&meta_path) {
+            Ok(raw) => Some(
+                serde_json::from_str::<KeyMeta>(&raw)
+                    .with_context(|| format!("malformed key metadata {}", meta_path.display()))?,
+            ),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => None,
+            Err(err) => {
+                return Err(err).with_context(|| {
+                    format!("failed to read key metadata {}", meta_path.display())
+                });
+            }
+        };
+        if let Some(meta) = &existing
+            && meta.key_source != settings.key_source
+        {
+            bail!(
+                "transcripts were encrypted with a {} key but transcripts_encryption.key_source is {}",
+                meta.key_source,
+                settings.key_source
+            );
+        }
+
+        let salt = match &existing {
+            Some(meta) => hex::decode(&meta.salt).context("malformed key salt")?,
+            None => random_bytes::<16>().to_vec(),
+        };
+        let key = match settings.key_source {
+            TranscriptKeySource::Passphrase => {
+                let passphrase = std::env::var(&settings.passphrase_env).with_context(|| {
+                    format!(
+                        "transcript encryption needs a passphrase in ${}",
+                        settings.passphrase_env
+                    )
+                })?;
+                derive_key(passphrase.as_bytes(), &salt)?
+            }
+            TranscriptKeySource::SecretService => {
+                secret_service_key(&settings.key_name, existing.is_none())?
+            }
+            TranscriptKeySource::KernelKeyring => kernel_keyring_key(&settings.key_name)?,
+            TranscriptKeySource::File => {
+                let path = settings.key_file.as_deref().context(
+                    "transcripts_encryption.key_file is required for the file key source",
+                )?;
+                file_key(path, existing.is_none())?
+            }
+        };
+        let cipher = Self::from_key(key, settings.key_source);
+
+        match existing {
+            Some(meta) => {
+                let check = STANDARD
+                    .decode(&meta.check)
+                    .context("malformed key check")?;
+                match cipher.decrypt(&check) {
+                    Ok(plaintext) if plaintext == KEY_CHECK_PLAINTEXT => {}
+                    _ => bail!(
+                        "transcript encryption key does not match the one used for this store"
+                    ),
+                }
+            }
+            None => {
+                let meta = KeyMeta {
+                    version: 1,
+                    key_source: settings.key_source,
+                    salt: hex::encode(&salt),
+                    check: STANDARD.encode(cipher.encrypt(KEY_CHECK_PLAINTEXT)?),
+                };
+                let serialised = serde_json::to_string_pretty(&meta)?;
+                fs::write(&meta_path, serialised).with_context(|| {
+                    format!("failed to write key metadata {}", meta_path.display())
+                })?;
+            }
+        }
+        Ok(Some(cipher))
+    }
+
+    pub fn key_source(&self) -> TranscriptKeySource {
+        self.source
+    }
+
+    /// Seal `plaintext` as `MAGIC || nonce || ciphertext`.
+    pub fn encrypt(&self, plaintext: &[u8]) -> Result<Vec<u8>> {
+        let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+        let ciphertext = self
+            .cipher
+            .encrypt(&nonce, plaintext)
+            .map_err(|_| anyhow!("failed to encrypt transcript data"))?;
+        let mut sealed = Vec::with_capacity(MAGIC.len() + NONCE_LEN + ciphertext.len());
+        sealed.extend_from_slice(MAGIC);
+        sealed.extend_from_slice(&nonce);
+        sealed.extend_from_slice(&ciphertext);
+        Ok(sealed)
+    }
+
+    /// Open data produced by [`Self::encrypt`].
+    pub fn decrypt(&self, sealed: &[u8]) -> Result<Vec<u8>> {
+        if !is_encrypted(sealed) || sealed.len() < MAGIC.len() + NONCE_LEN {
+            bail!("data is not an encrypted transcript file");
+        }
+        let (nonce, ciphertext) = sealed[MAGIC.len()..].split_at(NONCE_LEN);
+        self.cipher
+            .decrypt(XNonce::from_slice(nonce), ciphertext)
+            .map_err(|_| anyhow!("failed to decrypt transcript data (wrong key or corrupted file)"))
+    }
+}
+
+/// Location of the key metadata for a store rooted at `root`.
+pub fn key_meta_path(root: &Path) -> PathBuf {
+    root.join(KEY_META_FILE)
+}
+
+/// Whether `data` carries the encrypted-file header.
+pub fn is_encrypted(data: &[u8]) -> bool {
+    data.starts_with(MAGIC)
+}
+
+fn derive_key(passphrase: &[u8], salt: &[u8]) -> Result<[u8; 32]> {
+    if passphrase.is_empty() {
+        bail!("transcript encryption passphrase must not be empty");
+    }
+    let params = Params::new(ARGON2_MEMORY_KIB, ARGON2_PASSES, 1, Some(32))
+        .map_err(|err| anyhow!("invalid Argon2 parameters: {err}"))?;
+    let mut key = [0u8; 32];
+    Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+        .hash_password_into(passphrase, salt, &mut key)
+        .map_err(|err| anyhow!("failed to derive transcript key: {err}"))?;
+    Ok(key)
+}
+
+fn random_bytes<const N: usize>() -> [u8; N] {
+    use chacha20poly1305::aead::rand_core::RngCore;
+
+    let mut bytes = [0u8; N];
+    OsRng.fill_bytes(&mut bytes);
+    bytes
+}
+
+fn parse_hex_key(value: &str, origin: &str) -> Result<[u8; 32]> {
+    let bytes = hex::decode(value.trim())
+        .with_context(|| format!("transcript key from {origin} is not hex"))?;
+    bytes
+        .try_into()
+        .map_err(|_| anyhow!("transcript key from {origin} must be 32 bytes (64 hex characters)"))
+}
+
+/// Key stored in a file; a random one is created (mode 0600) when missing and
+/// `create` is set. Stores that already have key metadata never get a new key,
+/// since that would leave their existing files unreadable.
+fn file_key(path: &Path, create: bool) -> Result<[u8; 32]> {
+    match fs::read_to_string(path) {
+        Ok(raw) => parse_hex_key(&raw, &path.display().to_string()),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+            if !create {
+                bail!(
+                    "transcript key file {} is missing but this store is already encrypted; \
+                     restore the original key file",
+                    path.display()
+                );
+            }
+            let key = random_bytes::<32>();
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent).with_context(|| {
+                    format!("failed to create key directory {}", parent.display())
+                })?;
+            }
+            let mut options = fs::OpenOptions::new();
+            options.write(true).create_new(true);
+            #[cfg(unix)]
+            {
+                use std::os::unix::fs::OpenOptionsExt;
+                options.mode(0o600);
+            }
+            let mut file = options
+                .open(path)
+                .with_context(|| format!("failed to create key file {}", path.display()))?;
+            file.write_all(hex::encode(key).as_bytes())
+                .with_context(|| format!("failed to write key file {}", path.display()))?;
+            Ok(key)
+        }
+        Err(err) => Err(err).with_context(|| format!("failed to read key file {}", path.display())),
+    }
+}
+
+/// Key kept in the Secret Service (GNOME Keyring, KWallet) through
+/// `secret-tool`; generated and stored on first use. When `create` is unset
+/// (the store already has key metadata) a failed lookup is an error rather
+/// than a reason to store a fresh key over the real one.
+fn secret_service_key(name: &str, create: bool) -> Result<[u8; 32]> {
+    let lookup = Command::new("secret-tool")
+        .args(["lookup", "service", "archon", "key", name])
+        .output()
+        .context("failed to run secret-tool (install libsecret-tools)")?;
+    let value = String::from_utf8_lossy(&lookup.stdout);
+    if lookup.status.success() && !value.trim().is_empty() {
+        return parse_hex_key(&value, "the Secret Service");
+    }
+    if !create {
+        bail!(
+            "no '{name}' transcript key found in the Secret Service but this store is already \
+             encrypted; unlock the keyring or restore the key"
+        );
+    }
+
+    let key = random_bytes::<32>();
+    run_with_stdin(
+        Command::new("secret-tool").args([
+            "store",
+            "--label=Archon transcript encryption key",
+            "service",
+            "archon",
+            "key",
+            name,
+        ]),
+        &hex::encode(key),
+    )
+    .context("failed to store the transcript key in the Secret Service")?;
+    tracing::info!(
+        key = name,
+        "stored new transcript encryption key in the Secret Service"
+    );
+    Ok(key)
+}
+
+/// Key read from the kernel user keyring through `keyctl`. The kernel keyring
+/// does not survive a reboot, so the key is never generated here; add it with
+/// `keyctl padd user <name> @u` (64 hex characters) from a login script.
+fn kernel_keyring_key(name: &str) -> Result<[u8; 32]> {
+    let search = Command::new("keyctl")
+        .args(["search", "@u", "user", name])
+        .output()
+        .context("failed to run keyctl (install keyutils)")?;
+    if !search.status.success() {
+        bail!(
+            "no '{name}' key in the kernel user keyring; add one with \
+             `openssl rand -hex 32 | keyctl padd user {name} @u`"
+        );
+    }
+    let id = String::from_utf8_lossy(&search.stdout).trim().to_string();
+    let pipe = Command::new("keyctl")
+        .args(["pipe", &id])
+        .output()
+        .context("failed to run keyctl")?;
+    if !pipe.status.success() {
+        bail!("keyctl could not read key {id}");
+    }
+    parse_hex_key(&String::from_utf8_lossy(&pipe.stdout), "the kernel keyring")
+}
+
+fn run_with_stdin(command: &mut Command, input: &str) -> Result<()> {
+    let mut child = command
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .context("failed to spawn command")?;
+    child
+        .stdin
+        .take()
+        .context("command stdin unavailable")?
+        .write_all(input.as_bytes())
+        .context("failed to write command input")?;
+    let output = child.wait_with_output().context("command failed")?;
+    if !output.status.success() {
+        bail!(
+            "command exited with {}: {}",
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn file_settings(key_file: PathBuf) -> TranscriptEncryptionSettings {
+        TranscriptEncryptionSettings {
+            enabled: true,
+            key_source: TranscriptKeySource::File,
+            key_file: Some(key_file),
+            ..TranscriptEncryptionSettings::default()
+        }
+    }
+
+    #[test]
+    fn round_trips_and_rejects_tampering() {
+        let cipher = TranscriptCipher::from_key([7u8; 32], TranscriptKeySource::File);
+        let sealed = cipher.encrypt(b"page contents").unwrap();
+        assert!(is_encrypted(&sealed));
+        assert!(!sealed.windows(13).any(|window| window == b"page contents"));
+        assert_eq!(cipher.decrypt(&sealed).unwrap(), b"page contents");
+
+        let mut tampered = sealed.clone();
+        *tampered.last_mut().unwrap() ^= 1;
+        assert!(cipher.decrypt(&tampered).is_err());
+        let other = TranscriptCipher::from_key([8u8; 32], TranscriptKeySource::File);
+        assert!(other.decrypt(&sealed).is_err());
+        assert!(cipher.decrypt(b"plain").is_err());
+    }
+
+    #[test]
+    fn file_keyring_creates_key_and_detects_mismatch() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().join("transcripts");
+        fs::create_dir_all(&root).unwrap();
+        let key_file = dir.path().join("keys/transcripts.key");
+
+        assert!(
+            TranscriptCipher::from_settings(&TranscriptEncryptionSettings::default(), &root)
+                .unwrap()
+                .is_none()
+        );
+
+        let first = TranscriptCipher::from_settings(&file_settings(key_file.clone()), &root)
+            .unwrap()
+            .unwrap();
+        assert_eq!(fs::read_to_string(&key_file).unwrap().len(), 64);
+        assert!(key_meta_path(&root).exists());
+        let sealed = first.encrypt(b"hello").unwrap();
+
+        let again = TranscriptCipher::from_settings(&file_settings(key_file.clone()), &root)
+            .unwrap()
+            .unwrap();
+        assert_eq!(again.decrypt(&sealed).unwrap(), b"hello");
+
+        fs::write(&key_file, hex::encode([1u8; 32])).unwrap();
+        let err =
+            TranscriptCipher::from_settings(&file_settings(key_file.clone()), &root).unwrap_err();
+        assert!(err.to_string().contains("does not match"), "{err}");
+
+        fs::remove_file(&key_file).unwrap();
+        let err =
+            TranscriptCipher::from_settings(&file_settings(key_file.clone()), &root).unwrap_err();
+        assert!(err.to_string().contains("already encrypted"), "{err}");
+        assert!(!key_file.exists());
+    }
+
+    #[test]
+    fn passphrase_key_is_derived_with_stored_salt() {
+        let dir = tempfile::tempdir().unwrap();
+        let settings = TranscriptEncryptionSettings {
+            enabled: true,
+            passphrase_env: "ARCHON_TEST_TRANSCRIPT_PASSPHRASE".into(),
+            ..TranscriptEncryptionSettings::default()
+        };
+        let mut env = crate::test_util::EnvVarGuard::new();
+        env.remove("ARCHON_TEST_TRANSCRIPT_PASSPHRASE");
+        assert!(TranscriptCipher::from_settings(&settings, dir.path()).is_err());
+
+        env.set("ARCHON_TEST_TRANSCRIPT_PASSPHRASE", "correct horse");
+        let cipher = TranscriptCipher::from_settings(&settings, dir.path())
+            .unwrap()
+            .unwrap();
+        let sealed = cipher.encrypt(b"note").unwrap();
+        let reopened = TranscriptCipher::from_settings(&settings, dir.path())
+            .unwrap()
+            .unwrap();
+        assert_eq!(reopened.decrypt(&sealed).unwrap(), b"note");
+
+        env.set("ARCHON_TEST_TRANSCRIPT_PASSPHRASE", "wrong horse");
+        assert!(TranscriptCipher::from_settings(&settings, dir.path()).is_err());
+    }
+}